@@ -1,6 +1,7 @@
 mod connection;
 mod diff;
 mod mention;
+mod subagent;
 mod terminal;
 
 use agent_settings::AgentSettings;
@@ -12,6 +13,7 @@ pub use mention::*;
 use project::lsp_store::{FormatTrigger, LspFormatTarget};
 use serde::{Deserialize, Serialize};
 use settings::Settings as _;
+pub use subagent::*;
 pub use terminal::*;
 
 use action_log::ActionLog;
@@ -286,6 +288,7 @@ impl ToolCall {
             ToolCallContent::Diff(diff) => Some(diff),
             ToolCallContent::ContentBlock(_) => None,
             ToolCallContent::Terminal(_) => None,
+            ToolCallContent::Subagent(_) => None,
         })
     }
 
@@ -294,6 +297,16 @@ impl ToolCall {
             ToolCallContent::Terminal(terminal) => Some(terminal),
             ToolCallContent::ContentBlock(_) => None,
             ToolCallContent::Diff(_) => None,
+            ToolCallContent::Subagent(_) => None,
+        })
+    }
+
+    pub fn subagents(&self) -> impl Iterator<Item = &Entity<Subagent>> {
+        self.content.iter().filter_map(|content| match content {
+            ToolCallContent::Subagent(subagent) => Some(subagent),
+            ToolCallContent::ContentBlock(_) => None,
+            ToolCallContent::Diff(_) => None,
+            ToolCallContent::Terminal(_) => None,
         })
     }
 
@@ -543,6 +556,7 @@ pub enum ToolCallContent {
     ContentBlock(ContentBlock),
     Diff(Entity<Diff>),
     Terminal(Entity<Terminal>),
+    Subagent(Entity<Subagent>),
 }
 
 impl ToolCallContent {
@@ -594,6 +608,7 @@ impl ToolCallContent {
             Self::ContentBlock(content) => content.to_markdown(cx).to_string(),
             Self::Diff(diff) => diff.read(cx).to_markdown(cx),
             Self::Terminal(terminal) => terminal.read(cx).to_markdown(cx),
+            Self::Subagent(subagent) => subagent.read(cx).to_markdown(cx),
         }
     }
 }
@@ -603,6 +618,7 @@ pub enum ToolCallUpdate {
     UpdateFields(acp::ToolCallUpdate),
     UpdateDiff(ToolCallUpdateDiff),
     UpdateTerminal(ToolCallUpdateTerminal),
+    UpdateSubagent(ToolCallUpdateSubagent),
 }
 
 impl ToolCallUpdate {
@@ -611,6 +627,7 @@ impl ToolCallUpdate {
             Self::UpdateFields(update) => &update.id,
             Self::UpdateDiff(diff) => &diff.id,
             Self::UpdateTerminal(terminal) => &terminal.id,
+            Self::UpdateSubagent(subagent) => &subagent.id,
         }
     }
 }
@@ -645,6 +662,18 @@ pub struct ToolCallUpdateTerminal {
     pub terminal: Entity<Terminal>,
 }
 
+impl From<ToolCallUpdateSubagent> for ToolCallUpdate {
+    fn from(subagent: ToolCallUpdateSubagent) -> Self {
+        Self::UpdateSubagent(subagent)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ToolCallUpdateSubagent {
+    pub id: acp::ToolCallId,
+    pub subagent: Entity<Subagent>,
+}
+
 #[derive(Debug, Default)]
 pub struct Plan {
     pub entries: Vec<PlanEntry>,
@@ -1105,6 +1134,12 @@ impl AcpThread {
                     .content
                     .push(ToolCallContent::Terminal(update.terminal));
             }
+            ToolCallUpdate::UpdateSubagent(update) => {
+                current_call.content.clear();
+                current_call
+                    .content
+                    .push(ToolCallContent::Subagent(update.subagent));
+            }
         }
 
         cx.emit(AcpThreadEvent::EntryUpdated(ix));
@@ -1448,6 +1483,22 @@ impl AcpThread {
         })
     }
 
+    pub fn can_compact(&self, cx: &App) -> bool {
+        self.connection.compact(&self.session_id, cx).is_some()
+    }
+
+    pub fn compact(&mut self, cx: &mut Context<Self>) -> BoxFuture<'static, Result<()>> {
+        self.run_turn(cx, async move |this, cx| {
+            this.update(cx, |this, cx| {
+                this.connection
+                    .compact(&this.session_id, cx)
+                    .map(|compact| compact.run(cx))
+            })?
+            .context("compacting a session is not supported")?
+            .await
+        })
+    }
+
     fn run_turn(
         &mut self,
         cx: &mut Context<Self>,
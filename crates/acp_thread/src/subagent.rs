@@ -0,0 +1,212 @@
+use agent_client_protocol as acp;
+use futures::channel::oneshot;
+use gpui::{App, AppContext, Context, Entity, SharedString};
+use language::LanguageRegistry;
+use markdown::Markdown;
+use std::{sync::Arc, time::Instant};
+
+/// A sub-agent that a tool call delegated a task to. The sub-agent runs in its
+/// own thread, and only its progress is shown in the delegating thread.
+pub struct Subagent {
+    label: SharedString,
+    tool_calls: Vec<SubagentToolCall>,
+    response: Entity<Markdown>,
+    response_needs_separator: bool,
+    started_at: Instant,
+    ended_at: Option<Instant>,
+    used_tokens: u64,
+    token_budget: u64,
+    status: SubagentStatus,
+    cancel_tx: Option<oneshot::Sender<()>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SubagentToolCall {
+    pub id: acp::ToolCallId,
+    pub title: SharedString,
+    pub kind: acp::ToolKind,
+    pub status: acp::ToolCallStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubagentStatus {
+    Running,
+    Completed,
+    /// The sub-agent used up its token budget before finishing its task.
+    BudgetExhausted,
+    Canceled,
+    Failed,
+}
+
+impl Subagent {
+    pub fn new(
+        label: SharedString,
+        token_budget: u64,
+        cancel_tx: oneshot::Sender<()>,
+        language_registry: Arc<LanguageRegistry>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self {
+            label,
+            tool_calls: Vec::new(),
+            response: cx.new(|cx| {
+                Markdown::new(SharedString::default(), Some(language_registry), None, cx)
+            }),
+            response_needs_separator: false,
+            started_at: Instant::now(),
+            ended_at: None,
+            used_tokens: 0,
+            token_budget,
+            status: SubagentStatus::Running,
+            cancel_tx: Some(cancel_tx),
+        }
+    }
+
+    pub fn push_text(&mut self, text: &str, cx: &mut Context<Self>) {
+        let separator =
+            self.response_needs_separator && !self.response.read(cx).source().is_empty();
+        self.response_needs_separator = false;
+        self.response.update(cx, |response, cx| {
+            if separator {
+                response.append("\n\n", cx);
+            }
+            response.append(text, cx);
+        });
+        cx.notify();
+    }
+
+    pub fn push_tool_call(&mut self, tool_call: &acp::ToolCall, cx: &mut Context<Self>) {
+        let title = first_line(&tool_call.title);
+        if let Some(existing) = self.tool_call_mut(&tool_call.id) {
+            existing.title = title;
+            existing.kind = tool_call.kind;
+            existing.status = tool_call.status;
+        } else {
+            self.tool_calls.push(SubagentToolCall {
+                id: tool_call.id.clone(),
+                title,
+                kind: tool_call.kind,
+                status: tool_call.status,
+            });
+        }
+        // Text that streams after a tool call belongs to a new paragraph.
+        self.response_needs_separator = true;
+        cx.notify();
+    }
+
+    pub fn update_tool_call(&mut self, update: &acp::ToolCallUpdate, cx: &mut Context<Self>) {
+        let Some(tool_call) = self.tool_call_mut(&update.id) else {
+            return;
+        };
+        if let Some(title) = update.fields.title.as_ref() {
+            tool_call.title = first_line(title);
+        }
+        if let Some(kind) = update.fields.kind {
+            tool_call.kind = kind;
+        }
+        if let Some(status) = update.fields.status {
+            tool_call.status = status;
+        }
+        cx.notify();
+    }
+
+    pub fn set_used_tokens(&mut self, used_tokens: u64, cx: &mut Context<Self>) {
+        if self.used_tokens != used_tokens {
+            self.used_tokens = used_tokens;
+            cx.notify();
+        }
+    }
+
+    pub fn finish(&mut self, status: SubagentStatus, cx: &mut Context<Self>) {
+        self.status = status;
+        self.ended_at = Some(Instant::now());
+        self.cancel_tx.take();
+        for tool_call in &mut self.tool_calls {
+            if matches!(
+                tool_call.status,
+                acp::ToolCallStatus::Pending | acp::ToolCallStatus::InProgress
+            ) {
+                tool_call.status = acp::ToolCallStatus::Failed;
+            }
+        }
+        cx.notify();
+    }
+
+    /// Asks the sub-agent to stop. The task that runs it finishes the
+    /// sub-agent once it has stopped.
+    pub fn cancel(&mut self, cx: &mut Context<Self>) {
+        if let Some(cancel_tx) = self.cancel_tx.take() {
+            cancel_tx.send(()).ok();
+            cx.notify();
+        }
+    }
+
+    pub fn label(&self) -> &SharedString {
+        &self.label
+    }
+
+    pub fn tool_calls(&self) -> &[SubagentToolCall] {
+        &self.tool_calls
+    }
+
+    pub fn response(&self) -> &Entity<Markdown> {
+        &self.response
+    }
+
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    pub fn ended_at(&self) -> Option<Instant> {
+        self.ended_at
+    }
+
+    pub fn used_tokens(&self) -> u64 {
+        self.used_tokens
+    }
+
+    pub fn token_budget(&self) -> u64 {
+        self.token_budget
+    }
+
+    pub fn status(&self) -> SubagentStatus {
+        self.status
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == SubagentStatus::Running
+    }
+
+    /// Whether the sub-agent was asked to stop but hasn't stopped yet.
+    pub fn is_canceling(&self) -> bool {
+        self.is_running() && self.cancel_tx.is_none()
+    }
+
+    pub fn to_markdown(&self, cx: &App) -> String {
+        let mut markdown = format!("Sub-agent: {}\n", self.label);
+        for tool_call in &self.tool_calls {
+            markdown.push_str(&format!("- {}\n", tool_call.title));
+        }
+        let response = self.response.read(cx).source();
+        if !response.is_empty() {
+            markdown.push('\n');
+            markdown.push_str(response);
+            markdown.push('\n');
+        }
+        markdown
+    }
+
+    fn tool_call_mut(&mut self, id: &acp::ToolCallId) -> Option<&mut SubagentToolCall> {
+        self.tool_calls
+            .iter_mut()
+            .find(|tool_call| &tool_call.id == id)
+    }
+}
+
+fn first_line(title: &str) -> SharedString {
+    if let Some((first_line, _)) = title.split_once('\n') {
+        format!("{first_line}…").into()
+    } else {
+        title.to_string().into()
+    }
+}
@@ -541,6 +541,24 @@ impl ActionLog {
         tracked_buffer.schedule_diff_update(ChangeAuthor::Agent, cx);
     }
 
+    /// Mark a buffer as edited by agent through a transaction that was applied
+    /// before the buffer was tracked (e.g. a language server rename), so that
+    /// the transaction's edits show up for review.
+    pub fn buffer_edited_in_transaction(
+        &mut self,
+        buffer: Entity<Buffer>,
+        transaction: &text::Transaction,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.tracked_buffers.contains_key(&buffer) {
+            let diff_base = buffer.read(cx).rope_for_version(&transaction.start);
+            let tracked_buffer = self.track_buffer_internal(buffer.clone(), false, cx);
+            tracked_buffer.diff_base = diff_base.clone();
+            tracked_buffer.last_seen_base = diff_base;
+        }
+        self.buffer_edited(buffer, cx);
+    }
+
     pub fn will_delete_buffer(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
         let tracked_buffer = self.track_buffer_internal(buffer.clone(), false, cx);
         match tracked_buffer.status {
@@ -1085,6 +1103,56 @@ mod tests {
         assert_eq!(unreviewed_hunks(&action_log, cx), vec![]);
     }
 
+    #[gpui::test(iterations = 10)]
+    async fn test_edits_in_untracked_transaction(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({"file": "abc\ndef\nghi"}))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let file_path = project
+            .read_with(cx, |project, cx| project.find_project_path("dir/file", cx))
+            .unwrap();
+        let buffer = project
+            .update(cx, |project, cx| project.open_buffer(file_path, cx))
+            .await
+            .unwrap();
+
+        cx.update(|cx| {
+            let transaction = buffer.update(cx, |buffer, cx| {
+                buffer.start_transaction();
+                buffer.edit([(Point::new(0, 0)..Point::new(0, 3), "ABC")], None, cx);
+                buffer.edit([(Point::new(2, 0)..Point::new(2, 3), "GHI")], None, cx);
+                let transaction_id = buffer.end_transaction(cx).unwrap();
+                buffer.get_transaction(transaction_id).unwrap().clone()
+            });
+            action_log.update(cx, |log, cx| {
+                log.buffer_edited_in_transaction(buffer.clone(), &transaction, cx)
+            });
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            unreviewed_hunks(&action_log, cx),
+            vec![(
+                buffer.clone(),
+                vec![
+                    HunkStatus {
+                        range: Point::new(0, 0)..Point::new(1, 0),
+                        diff_status: DiffHunkStatusKind::Modified,
+                        old_text: "abc\n".into(),
+                    },
+                    HunkStatus {
+                        range: Point::new(2, 0)..Point::new(2, 3),
+                        diff_status: DiffHunkStatusKind::Modified,
+                        old_text: "ghi".into(),
+                    }
+                ],
+            )]
+        );
+    }
+
     #[gpui::test(iterations = 10)]
     async fn test_deletions(cx: &mut TestAppContext) {
         init_test(cx);
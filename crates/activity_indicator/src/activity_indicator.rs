@@ -3,9 +3,9 @@ use editor::Editor;
 use extension_host::ExtensionStore;
 use futures::StreamExt;
 use gpui::{
-    Animation, AnimationExt as _, App, Context, CursorStyle, Entity, EventEmitter,
-    InteractiveElement as _, ParentElement as _, Render, SharedString, StatefulInteractiveElement,
-    Styled, Transformation, Window, actions, percentage,
+    Animation, AnimationExt as _, App, BorrowAppContext as _, Context, CursorStyle, Entity,
+    EventEmitter, InteractiveElement as _, ParentElement as _, Render, SharedString,
+    StatefulInteractiveElement, Styled, Subscription, Transformation, Window, actions, percentage,
 };
 use language::{
     BinaryStatus, LanguageRegistry, LanguageServerId, LanguageServerName,
@@ -16,6 +16,7 @@ use project::{
     ProjectEnvironmentEvent,
     git_store::{GitStoreEvent, Repository},
 };
+use semantic_index::{ProjectIndex, SemanticDb};
 use smallvec::SmallVec;
 use std::{
     cmp::Reverse,
@@ -51,6 +52,7 @@ pub struct ActivityIndicator {
     project: Entity<Project>,
     auto_updater: Option<Entity<AutoUpdater>>,
     context_menu_handle: PopoverMenuHandle<ContextMenu>,
+    project_index: Option<(Entity<ProjectIndex>, Subscription)>,
 }
 
 #[derive(Debug)]
@@ -212,12 +214,18 @@ impl ActivityIndicator {
                 cx.observe(auto_updater, |_, _, cx| cx.notify()).detach();
             }
 
-            Self {
+            cx.observe_global::<SemanticDb>(|this, cx| this.observe_project_index(cx))
+                .detach();
+
+            let mut this = Self {
                 statuses: Vec::new(),
                 project: project.clone(),
                 auto_updater,
                 context_menu_handle: Default::default(),
-            }
+                project_index: None,
+            };
+            this.observe_project_index(cx);
+            this
         });
 
         cx.subscribe_in(&this, window, move |_, _, event, window, cx| match event {
@@ -317,6 +325,25 @@ impl ActivityIndicator {
         });
     }
 
+    fn observe_project_index(&mut self, cx: &mut Context<Self>) {
+        let project_index = cx.has_global::<SemanticDb>().then(|| {
+            let project = self.project.clone();
+            cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+                semantic_db.project_index(project, cx)
+            })
+        });
+        let project_index = project_index.flatten();
+        if project_index.as_ref() == self.project_index.as_ref().map(|(index, _)| index) {
+            return;
+        }
+
+        self.project_index = project_index.map(|project_index| {
+            let subscription = cx.subscribe(&project_index, |_, _, _, cx| cx.notify());
+            (project_index, subscription)
+        });
+        cx.notify();
+    }
+
     fn pending_language_server_work<'a>(
         &self,
         cx: &'a App,
@@ -446,6 +473,33 @@ impl ActivityIndicator {
             });
         }
 
+        let indexing_message = self.project_index.as_ref().and_then(|(project_index, _)| {
+            match project_index.read(cx).status() {
+                semantic_index::Status::Idle => None,
+                semantic_index::Status::Loading => Some("Loading semantic index…".to_string()),
+                semantic_index::Status::Scanning { remaining_count } => {
+                    Some(format!("Indexing {remaining_count} files…"))
+                }
+            }
+        });
+        if let Some(message) = indexing_message {
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message,
+                tooltip_message: Some("Building the semantic index of the project".to_string()),
+                on_click: None,
+            });
+        }
+
         let current_job = self
             .project
             .read(cx)
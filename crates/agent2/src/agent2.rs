@@ -6,6 +6,7 @@ mod templates;
 mod thread;
 mod tool_schema;
 mod tools;
+mod usage_db;
 
 #[cfg(test)]
 mod tests;
@@ -17,3 +18,4 @@ pub use native_agent_server::NativeAgentServer;
 pub use templates::*;
 pub use thread::*;
 pub use tools::*;
+pub use usage_db::*;
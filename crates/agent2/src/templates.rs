@@ -44,6 +44,18 @@ impl Template for SystemPromptTemplate<'_> {
     const TEMPLATE_NAME: &'static str = "system_prompt.hbs";
 }
 
+/// The system prompt of threads that run a task delegated by another thread.
+#[derive(Serialize)]
+pub struct SubagentSystemPromptTemplate<'a> {
+    #[serde(flatten)]
+    pub project: &'a prompt_store::ProjectContext,
+    pub available_tools: Vec<SharedString>,
+}
+
+impl Template for SubagentSystemPromptTemplate<'_> {
+    const TEMPLATE_NAME: &'static str = "subagent_system_prompt.hbs";
+}
+
 /// Handlebars helper for checking if an item is in a list
 fn contains(
     h: &handlebars::Helper,
@@ -84,4 +96,17 @@ mod tests {
         let rendered = template.render(&templates).unwrap();
         assert!(rendered.contains("## Fixing Diagnostics"));
     }
+
+    #[test]
+    fn test_subagent_system_prompt_template() {
+        let project = prompt_store::ProjectContext::default();
+        let template = SubagentSystemPromptTemplate {
+            project: &project,
+            available_tools: vec!["grep".into()],
+        };
+        let templates = Templates::new();
+        let rendered = template.render(&templates).unwrap();
+        assert!(rendered.contains("You are a sub-agent."));
+        assert!(rendered.contains("prefer the `grep` tool"));
+    }
 }
@@ -1734,6 +1734,150 @@ async fn test_title_generation(cx: &mut TestAppContext) {
     thread.read_with(cx, |thread, _| assert_eq!(thread.title(), "Hello world"));
 }
 
+#[gpui::test]
+async fn test_compaction(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let summary_model = Arc::new(FakeLanguageModel::default());
+    thread.update(cx, |thread, cx| {
+        thread.set_title("Compaction".into(), cx);
+        thread.set_summarization_model(Some(summary_model.clone()), cx);
+    });
+
+    for (message, response) in [
+        ("Message 1", "Response 1"),
+        ("Message 2", "Response 2"),
+        ("Message 3", "Response 3"),
+    ] {
+        let events = thread
+            .update(cx, |thread, cx| {
+                thread.send(UserMessageId::new(), [message], cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk(response);
+        fake_model.end_last_completion_stream();
+        events.collect::<Vec<_>>().await;
+    }
+
+    // The two most recent turns are kept out of the summary.
+    let events = thread.update(cx, |thread, cx| thread.compact(cx)).unwrap();
+    cx.run_until_parked();
+    let completion = summary_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion
+            .messages
+            .iter()
+            .map(|message| message.string_contents())
+            .collect::<Vec<_>>(),
+        vec![
+            "Message 1".to_string(),
+            "Response 1".to_string(),
+            agent_settings::COMPACT_THREAD_PROMPT.to_string()
+        ]
+    );
+    summary_model.send_last_completion_stream_text_chunk("The user sent a first message.");
+    summary_model.end_last_completion_stream();
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+
+    // Subsequent requests replace the compacted messages with the summary.
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 4"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages[1..]
+            .iter()
+            .map(|message| message.string_contents())
+            .collect::<Vec<_>>(),
+        vec![
+            "The earlier part of this conversation was replaced with the following summary:\n\n\
+            The user sent a first message."
+                .to_string(),
+            "Message 2".to_string(),
+            "Response 2".to_string(),
+            "Message 3".to_string(),
+            "Response 3".to_string(),
+            "Message 4".to_string(),
+        ]
+    );
+    fake_model.send_last_completion_stream_text_chunk("Response 4");
+    fake_model.end_last_completion_stream();
+    events.collect::<Vec<_>>().await;
+
+    // The compacted messages are still part of the thread's history.
+    thread.read_with(cx, |thread, _| {
+        let markdown = thread.to_markdown();
+        assert!(markdown.contains("Message 1"), "{markdown}");
+        assert!(markdown.contains("Response 1"), "{markdown}");
+        assert!(
+            markdown.contains("The user sent a first message."),
+            "{markdown}"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_usage_of_compaction_is_recorded(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let summary_model = Arc::new(FakeLanguageModel::default());
+    thread.update(cx, |thread, cx| {
+        thread.set_title("Compaction".into(), cx);
+        thread.set_summarization_model(Some(summary_model.clone()), cx);
+    });
+    for message in ["Message 1", "Message 2", "Message 3"] {
+        let events = thread
+            .update(cx, |thread, cx| {
+                thread.send(UserMessageId::new(), [message], cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk("Response");
+        fake_model.end_last_completion_stream();
+        events.collect::<Vec<_>>().await;
+    }
+    let total_before = TOKEN_USAGE_DB.total_since(0).unwrap();
+
+    let usage = language_model::TokenUsage {
+        input_tokens: 3_000,
+        output_tokens: 200,
+        cache_creation_input_tokens: 0,
+        cache_read_input_tokens: 1_000,
+    };
+    let events = thread.update(cx, |thread, cx| thread.compact(cx)).unwrap();
+    cx.run_until_parked();
+    summary_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(usage));
+    summary_model.send_last_completion_stream_text_chunk("The user sent a first message.");
+    summary_model.end_last_completion_stream();
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+    cx.run_until_parked();
+
+    let thread_id = thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.cumulative_token_usage(), usage);
+        thread.id().to_string()
+    });
+    let thread_total = TOKEN_USAGE_DB
+        .totals_by_thread(0)
+        .unwrap()
+        .into_iter()
+        .find(|summary| summary.key == thread_id)
+        .unwrap();
+    assert_eq!(thread_total.requests, 1);
+    assert_eq!(thread_total.usage, usage);
+
+    // Other tests may record usage concurrently.
+    let total_after = TOKEN_USAGE_DB.total_since(0).unwrap();
+    assert!(total_after.requests > total_before.requests);
+    assert!(total_after.usage.input_tokens >= total_before.usage.input_tokens + usage.input_tokens);
+}
+
 #[gpui::test]
 async fn test_building_request_with_pending_tools(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -2044,6 +2188,129 @@ async fn test_tool_updates_to_completion(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_delegate_tool(cx: &mut TestAppContext) {
+    let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
+    thread.update(cx, |thread, cx| {
+        thread.add_tool(DelegateTool::new(cx.weak_entity()))
+    });
+    let fake_model = model.as_fake();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Where is the parser?"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    let input = json!({
+        "label": "Find the parser",
+        "task": "Find where the parser is defined."
+    });
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "1".into(),
+            name: DelegateTool::name().into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The sub-agent runs in its own conversation, with its own system prompt.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert!(
+        completion.messages[0]
+            .string_contents()
+            .contains("sub-agent")
+    );
+    assert_eq!(
+        completion.messages.last().unwrap().content,
+        vec!["Find where the parser is defined.".into()]
+    );
+    assert!(
+        !tool_names_for_completion(&completion).contains(&DelegateTool::name().to_string()),
+        "sub-agents can't delegate tasks themselves"
+    );
+    fake_model.send_last_completion_stream_text_chunk("The parser is in src/parser.rs.");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // The delegating thread only receives the sub-agent's final message.
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages.last().unwrap().content,
+        vec![MessageContent::ToolResult(LanguageModelToolResult {
+            tool_use_id: "1".into(),
+            tool_name: DelegateTool::name().into(),
+            is_error: false,
+            content: "The parser is in src/parser.rs.".into(),
+            output: Some("The parser is in src/parser.rs.".into()),
+        })]
+    );
+    fake_model.send_last_completion_stream_text_chunk("It's in src/parser.rs.");
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let tool_call = expect_tool_call(&mut events).await;
+    assert_eq!(tool_call.title, "Find the parser");
+}
+
+#[gpui::test]
+async fn test_cancel_during_delegation(cx: &mut TestAppContext) {
+    let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
+    thread.update(cx, |thread, cx| {
+        thread.add_tool(DelegateTool::new(cx.weak_entity()))
+    });
+    let fake_model = model.as_fake();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Where is the parser?"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    let input = json!({
+        "label": "Find the parser",
+        "task": "Find where the parser is defined."
+    });
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "1".into(),
+            name: DelegateTool::name().into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let mut subagent = None;
+    while subagent.is_none() {
+        if let ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdateSubagent(update)) =
+            events.next().await.unwrap().unwrap()
+        {
+            subagent = Some(update.subagent);
+        }
+    }
+    let subagent = subagent.unwrap();
+    fake_model.send_last_completion_stream_text_chunk("Let me look");
+    cx.run_until_parked();
+    assert!(subagent.read_with(cx, |subagent, _| subagent.is_running()));
+
+    // Canceling the delegating turn drops the tool call that runs the sub-agent.
+    thread.update(cx, |thread, cx| thread.cancel(cx));
+    cx.run_until_parked();
+    subagent.read_with(cx, |subagent, _| {
+        assert_eq!(subagent.status(), acp_thread::SubagentStatus::Canceled);
+        assert!(subagent.ended_at().is_some());
+    });
+}
+
 #[gpui::test]
 async fn test_send_no_retry_on_success(cx: &mut TestAppContext) {
     let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
@@ -2153,6 +2420,76 @@ async fn test_send_retry_on_error(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_usage_of_retried_request_is_recorded(cx: &mut TestAppContext) {
+    let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.set_completion_mode(agent_settings::CompletionMode::Burn, cx);
+            thread.send(UserMessageId::new(), ["Hello!"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    let usage = language_model::TokenUsage {
+        input_tokens: 1_000,
+        output_tokens: 100,
+        cache_creation_input_tokens: 0,
+        cache_read_input_tokens: 0,
+    };
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(usage));
+    fake_model.send_last_completion_stream_error(LanguageModelCompletionError::ServerOverloaded {
+        provider: LanguageModelProviderName::new("Anthropic"),
+        retry_after: Some(Duration::from_secs(3)),
+    });
+    fake_model.end_last_completion_stream();
+    cx.executor().advance_clock(Duration::from_secs(3));
+    cx.run_until_parked();
+
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(usage));
+    fake_model.send_last_completion_stream_text_chunk("Hey!");
+    fake_model.end_last_completion_stream();
+    events.collect::<Vec<_>>().await;
+
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.cumulative_token_usage(), usage + usage);
+    });
+}
+
+#[gpui::test]
+async fn test_usage_of_canceled_turn_is_recorded(cx: &mut TestAppContext) {
+    let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Hello!"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    let usage = language_model::TokenUsage {
+        input_tokens: 2_000,
+        output_tokens: 300,
+        cache_creation_input_tokens: 500,
+        cache_read_input_tokens: 0,
+    };
+    fake_model.send_last_completion_stream_text_chunk("Let me think");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(usage));
+    cx.run_until_parked();
+
+    // The completion stream never ends, so the turn is dropped mid-request.
+    thread.update(cx, |thread, cx| thread.cancel(cx));
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::Cancelled]);
+
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.cumulative_token_usage(), usage);
+    });
+}
+
 #[gpui::test]
 async fn test_send_retry_finishes_tool_calls_on_error(cx: &mut TestAppContext) {
     let ThreadTest { thread, model, .. } = setup(cx, TestModel::Fake).await;
@@ -2339,6 +2676,7 @@ async fn setup(cx: &mut TestAppContext, model: TestModel) -> ThreadTest {
                             ToolRequiringPermission::name(): true,
                             InfiniteTool::name(): true,
                             ThinkingTool::name(): true,
+                            DelegateTool::name(): true,
                         }
                     }
                 }
@@ -1,15 +1,16 @@
 use crate::{
-    ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
-    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
-    ListDirectoryTool, MovePathTool, NowTool, OpenTool, ReadFileTool, SystemPromptTemplate,
-    Template, Templates, TerminalTool, ThinkingTool, WebSearchTool,
+    CodeNavigationTool, ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel,
+    DbThread, DelegateTool, DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool,
+    GrepTool, ListDirectoryTool, MovePathTool, NowTool, OpenTool, ReadFileTool, RenameSymbolTool,
+    SemanticSearchTool, SubagentSystemPromptTemplate, SystemPromptTemplate, TOKEN_USAGE_DB,
+    Template, Templates, TerminalTool, ThinkingTool, WebSearchTool, WorkspaceSymbolsTool,
 };
 use acp_thread::{MentionUri, UserMessageId};
 use action_log::ActionLog;
 use agent::thread::{GitState, ProjectSnapshot, WorktreeSnapshot};
 use agent_client_protocol as acp;
 use agent_settings::{
-    AgentProfileId, AgentProfileSettings, AgentSettings, CompletionMode,
+    AgentProfileId, AgentProfileSettings, AgentSettings, COMPACT_THREAD_PROMPT, CompletionMode,
     SUMMARIZE_THREAD_DETAILED_PROMPT, SUMMARIZE_THREAD_PROMPT,
 };
 use anyhow::{Context as _, Result, anyhow};
@@ -48,6 +49,7 @@ use smol::stream::StreamExt;
 use std::fmt::Write;
 use std::{
     collections::BTreeMap,
+    mem,
     ops::RangeInclusive,
     path::Path,
     sync::Arc,
@@ -97,6 +99,7 @@ pub enum Message {
     User(UserMessage),
     Agent(AgentMessage),
     Resume,
+    Compaction(CompactionMessage),
 }
 
 impl Message {
@@ -116,6 +119,7 @@ impl Message {
                 content: vec!["Continue where you left off".into()],
                 cache: false,
             }],
+            Message::Compaction(message) => vec![message.to_request()],
         }
     }
 
@@ -124,12 +128,13 @@ impl Message {
             Message::User(message) => message.to_markdown(),
             Message::Agent(message) => message.to_markdown(),
             Message::Resume => "[resume]\n".into(),
+            Message::Compaction(message) => message.to_markdown(),
         }
     }
 
     pub fn role(&self) -> Role {
         match self {
-            Message::User(_) | Message::Resume => Role::User,
+            Message::User(_) | Message::Resume | Message::Compaction(_) => Role::User,
             Message::Agent(_) => Role::Assistant,
         }
     }
@@ -356,6 +361,52 @@ impl UserMessage {
     }
 }
 
+/// Replaces the messages before it with a summary when building requests, so
+/// that long threads fit in the model's context window. The messages it
+/// replaces are kept in the thread, so that they can still be viewed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionMessage {
+    pub summary: String,
+    /// The number of messages the summary replaces.
+    pub compacted_message_count: usize,
+    /// The first of the most recent messages, which are sent verbatim along
+    /// with the summary.
+    pub first_kept_message: Option<UserMessageId>,
+    /// The context the user attached to the messages the summary replaces,
+    /// which is sent verbatim along with it.
+    pub pinned_context: Vec<UserMessageContent>,
+}
+
+impl CompactionMessage {
+    pub fn title(&self) -> String {
+        if self.compacted_message_count == 1 {
+            "Compacted 1 earlier message".into()
+        } else {
+            format!(
+                "Compacted {} earlier messages",
+                self.compacted_message_count
+            )
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        format!("## Summary of earlier messages\n\n{}\n", self.summary)
+    }
+
+    fn to_request(&self) -> LanguageModelRequestMessage {
+        let mut content = vec![UserMessageContent::Text(format!(
+            "The earlier part of this conversation was replaced with the following summary:\n\n{}",
+            self.summary
+        ))];
+        content.extend(self.pinned_context.iter().cloned());
+        UserMessage {
+            id: UserMessageId::new(),
+            content,
+        }
+        .to_request()
+    }
+}
+
 fn codeblock_tag(full_path: &Path, line_range: Option<&RangeInclusive<u32>>) -> String {
     let mut result = String::new();
 
@@ -542,6 +593,19 @@ pub struct ToolCallAuthorization {
     pub response: oneshot::Sender<acp::PermissionOptionId>,
 }
 
+/// Returned when a delegated thread uses up its token budget before finishing its turn.
+#[derive(Debug, thiserror::Error)]
+#[error("used up the token budget of {token_budget} tokens")]
+pub struct TokenBudgetExhaustedError {
+    pub token_budget: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Delegation {
+    parent_id: acp::SessionId,
+    token_budget: u64,
+}
+
 #[derive(Debug, thiserror::Error)]
 enum CompletionError {
     #[error("max tokens")]
@@ -569,8 +633,15 @@ pub struct Thread {
     tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
     tool_use_limit_reached: bool,
     request_token_usage: HashMap<UserMessageId, language_model::TokenUsage>,
-    #[allow(unused)]
     cumulative_token_usage: TokenUsage,
+    /// The latest usage reported for the completion request that's streaming,
+    /// recorded in the usage database once the request finishes.
+    completion_token_usage: Option<TokenUsage>,
+    /// The usage of the latest completion request since the thread was last
+    /// compacted, which shows how much of the context window it takes up.
+    last_request_token_usage: Option<TokenUsage>,
+    /// Set when this thread runs a task delegated by another thread.
+    delegation: Option<Delegation>,
     #[allow(unused)]
     initial_project_snapshot: Shared<Task<Option<Arc<ProjectSnapshot>>>>,
     context_server_registry: Entity<ContextServerRegistry>,
@@ -622,6 +693,9 @@ impl Thread {
             tool_use_limit_reached: false,
             request_token_usage: HashMap::default(),
             cumulative_token_usage: TokenUsage::default(),
+            completion_token_usage: None,
+            last_request_token_usage: None,
+            delegation: None,
             initial_project_snapshot: {
                 let project_snapshot = Self::project_snapshot(project.clone(), cx);
                 cx.foreground_executor()
@@ -641,6 +715,34 @@ impl Thread {
         }
     }
 
+    /// Creates a thread that runs a task delegated by `parent`, using the
+    /// tools of the given profile and stopping after `token_budget` tokens.
+    pub fn new_subagent(
+        parent: &Thread,
+        profile_id: AgentProfileId,
+        token_budget: u64,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self::new(
+            parent.project.clone(),
+            parent.project_context.clone(),
+            parent.context_server_registry.clone(),
+            parent.templates.clone(),
+            parent.model.clone(),
+            cx,
+        );
+        // Share the parent's action log so that the sub-agent's edits can be
+        // reviewed alongside the parent's.
+        this.action_log = parent.action_log.clone();
+        this.profile_id = profile_id;
+        this.completion_mode = parent.completion_mode;
+        this.delegation = Some(Delegation {
+            parent_id: parent.id.clone(),
+            token_budget,
+        });
+        this
+    }
+
     pub fn id(&self) -> &acp::SessionId {
         &self.id
     }
@@ -651,7 +753,7 @@ impl Thread {
     ) -> mpsc::UnboundedReceiver<Result<ThreadEvent>> {
         let (tx, rx) = mpsc::unbounded();
         let stream = ThreadEventStream(tx);
-        for message in &self.messages {
+        for (ix, message) in self.messages.iter().enumerate() {
             match message {
                 Message::User(user_message) => stream.send_user_message(user_message),
                 Message::Agent(assistant_message) => {
@@ -674,6 +776,23 @@ impl Thread {
                     }
                 }
                 Message::Resume => {}
+                Message::Compaction(compaction) => {
+                    let id = compaction_tool_use_id(ix);
+                    stream.send_tool_call(
+                        &id,
+                        compaction.title().into(),
+                        acp::ToolKind::Think,
+                        serde_json::Value::Null,
+                    );
+                    stream.update_tool_call_fields(
+                        &id,
+                        acp::ToolCallUpdateFields {
+                            status: Some(acp::ToolCallStatus::Completed),
+                            content: Some(vec![compaction.summary.clone().into()]),
+                            ..Default::default()
+                        },
+                    );
+                }
             }
         }
         rx
@@ -800,6 +919,9 @@ impl Thread {
             tool_use_limit_reached: false,
             request_token_usage: db_thread.request_token_usage.clone(),
             cumulative_token_usage: db_thread.cumulative_token_usage,
+            completion_token_usage: None,
+            last_request_token_usage: None,
+            delegation: None,
             initial_project_snapshot: Task::ready(db_thread.initial_project_snapshot).shared(),
             context_server_registry,
             profile_id,
@@ -956,6 +1078,14 @@ impl Thread {
         })
     }
 
+    pub fn is_subagent(&self) -> bool {
+        self.delegation.is_some()
+    }
+
+    pub fn cumulative_token_usage(&self) -> TokenUsage {
+        self.cumulative_token_usage
+    }
+
     pub fn project_context(&self) -> &Entity<ProjectContext> {
         &self.project_context
     }
@@ -1026,8 +1156,13 @@ impl Thread {
 
     pub fn add_default_tools(&mut self, cx: &mut Context<Self>) {
         let language_registry = self.project.read(cx).languages().clone();
+        self.add_tool(CodeNavigationTool::new(self.project.clone()));
         self.add_tool(CopyPathTool::new(self.project.clone()));
         self.add_tool(CreateDirectoryTool::new(self.project.clone()));
+        // Sub-agents can't delegate tasks themselves.
+        if !self.is_subagent() {
+            self.add_tool(DelegateTool::new(cx.weak_entity()));
+        }
         self.add_tool(DeletePathTool::new(
             self.project.clone(),
             self.action_log.clone(),
@@ -1045,9 +1180,15 @@ impl Thread {
             self.project.clone(),
             self.action_log.clone(),
         ));
+        self.add_tool(RenameSymbolTool::new(
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
+        self.add_tool(SemanticSearchTool::new(self.project.clone()));
         self.add_tool(TerminalTool::new(self.project.clone(), cx));
         self.add_tool(ThinkingTool);
         self.add_tool(WebSearchTool);
+        self.add_tool(WorkspaceSymbolsTool::new(self.project.clone()));
     }
 
     pub fn add_tool<T: AgentTool>(&mut self, tool: T) {
@@ -1068,6 +1209,9 @@ impl Thread {
 
     pub fn cancel(&mut self, cx: &mut Context<Self>) {
         if let Some(running_turn) = self.running_turn.take() {
+            // The turn's task is dropped mid-request, so record the tokens the
+            // request has used so far here.
+            self.record_completion_usage(&running_turn.model.clone(), cx);
             running_turn.cancel();
         }
         self.flush_pending_message(cx);
@@ -1084,6 +1228,35 @@ impl Thread {
         cx.notify();
     }
 
+    /// Adds the usage of the completion request that just finished to the
+    /// thread's total, and records it in the usage database along with its cost.
+    fn record_completion_usage(&mut self, model: &Arc<dyn LanguageModel>, cx: &mut Context<Self>) {
+        let Some(usage) = self.completion_token_usage.take() else {
+            return;
+        };
+        self.last_request_token_usage = Some(usage);
+        self.cumulative_token_usage = self.cumulative_token_usage + usage;
+
+        // Attribute the usage of sub-agents to the thread that delegated to them.
+        let thread_id = match &self.delegation {
+            Some(delegation) => delegation.parent_id.to_string(),
+            None => self.id.to_string(),
+        };
+        let provider = model.provider_id().0.to_string();
+        let model_id = model.id().0.to_string();
+        let cost = AgentSettings::pricing_for_model(model, cx).map(|pricing| pricing.cost(&usage));
+        db::write_and_log(cx, move || {
+            TOKEN_USAGE_DB.record_usage(thread_id, provider, model_id, usage, cost)
+        });
+    }
+
+    /// Returns the token budget of a delegated thread once it has used it up.
+    fn exhausted_token_budget(&self) -> Option<u64> {
+        let delegation = self.delegation.as_ref()?;
+        (self.cumulative_token_usage.total_tokens() >= delegation.token_budget)
+            .then_some(delegation.token_budget)
+    }
+
     pub fn truncate(&mut self, message_id: UserMessageId, cx: &mut Context<Self>) -> Result<()> {
         self.cancel(cx);
         let Some(position) = self.messages.iter().position(
@@ -1097,9 +1270,10 @@ impl Thread {
                 Message::User(message) => {
                     self.request_token_usage.remove(&message.id);
                 }
-                Message::Agent(_) | Message::Resume => {}
+                Message::Agent(_) | Message::Resume | Message::Compaction(_) => {}
             }
         }
+        self.last_request_token_usage = None;
         self.summary = None;
         cx.notify();
         Ok(())
@@ -1127,6 +1301,293 @@ impl Thread {
         self.run_turn(cx)
     }
 
+    /// Replaces the older messages of the thread with a summary, reporting
+    /// progress as a tool call on the returned channel.
+    pub fn compact(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> Result<mpsc::UnboundedReceiver<Result<ThreadEvent>>> {
+        self.cancel(cx);
+
+        let model = self
+            .compaction_model()
+            .context("No language model configured")?;
+        let (events_tx, events_rx) = mpsc::unbounded::<Result<ThreadEvent>>();
+        let event_stream = ThreadEventStream(events_tx);
+        self.running_turn = Some(RunningTurn {
+            event_stream: event_stream.clone(),
+            model,
+            tools: BTreeMap::default(),
+            _task: cx.spawn(async move |this, cx| {
+                match Self::compact_internal(&this, &event_stream, cx).await {
+                    Ok(()) => event_stream.send_stop(acp::StopReason::EndTurn),
+                    Err(error) => event_stream.send_error(error),
+                }
+                _ = this.update(cx, |this, _| this.running_turn.take());
+            }),
+        });
+        Ok(events_rx)
+    }
+
+    /// Whether the last request filled enough of the model's context window
+    /// for the thread to be compacted automatically.
+    fn needs_compaction(&self, cx: &App) -> bool {
+        let settings = AgentSettings::get_global(cx);
+        if !settings.auto_compact {
+            return false;
+        }
+        let Some(model) = self.model.as_ref() else {
+            return false;
+        };
+        let Some(usage) = self.last_request_token_usage else {
+            return false;
+        };
+        let max_tokens = model.max_token_count_for_mode(self.completion_mode.into());
+        max_tokens > 0
+            && usage.total_tokens() as f64
+                >= max_tokens as f64 * settings.auto_compact_threshold as f64
+    }
+
+    async fn compact_internal(
+        this: &WeakEntity<Self>,
+        event_stream: &ThreadEventStream,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let (model, request, compaction, tool_use_id) =
+            this.update(cx, |this, cx| this.prepare_compaction(cx))??;
+        log::debug!("Compacting thread with model: {}", model.name().0);
+        // The usage of a compaction that gets cancelled is recorded against
+        // the model that summarizes the thread.
+        let turn_model = this.update(cx, |this, _| {
+            this.running_turn
+                .as_mut()
+                .map(|turn| mem::replace(&mut turn.model, model.clone()))
+        })?;
+
+        event_stream.send_tool_call(
+            &tool_use_id,
+            "Compacting thread".into(),
+            acp::ToolKind::Think,
+            serde_json::Value::Null,
+        );
+        event_stream.update_tool_call_fields(
+            &tool_use_id,
+            acp::ToolCallUpdateFields {
+                status: Some(acp::ToolCallStatus::InProgress),
+                ..Default::default()
+            },
+        );
+
+        let summary = async {
+            let mut summary = String::new();
+            let mut events = model.stream_completion(request, cx).await?;
+            while let Some(event) = events.next().await {
+                match event? {
+                    LanguageModelCompletionEvent::Text(text) => summary.push_str(&text),
+                    LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                        this.update(cx, |thread, _| {
+                            thread.completion_token_usage = Some(usage);
+                        })?;
+                    }
+                    LanguageModelCompletionEvent::StatusUpdate(
+                        CompletionRequestStatus::UsageUpdated { amount, limit },
+                    ) => {
+                        this.update(cx, |thread, cx| {
+                            thread.update_model_request_usage(amount, limit, cx);
+                        })?;
+                    }
+                    _ => {}
+                }
+            }
+            let summary = summary.trim();
+            anyhow::ensure!(!summary.is_empty(), "the model returned an empty summary");
+            anyhow::Ok(summary.to_string())
+        }
+        .await;
+        // Requests that failed before the end of their stream still used tokens.
+        this.update(cx, |this, cx| {
+            this.record_completion_usage(&model, cx);
+            if let Some((turn, turn_model)) = this.running_turn.as_mut().zip(turn_model) {
+                turn.model = turn_model;
+            }
+        })?;
+        let summary = match summary {
+            Ok(summary) => summary,
+            Err(error) => {
+                event_stream.update_tool_call_fields(
+                    &tool_use_id,
+                    acp::ToolCallUpdateFields {
+                        status: Some(acp::ToolCallStatus::Failed),
+                        ..Default::default()
+                    },
+                );
+                return Err(error.context("failed to compact thread"));
+            }
+        };
+
+        let compaction = CompactionMessage {
+            summary,
+            ..compaction
+        };
+        event_stream.update_tool_call_fields(
+            &tool_use_id,
+            acp::ToolCallUpdateFields {
+                status: Some(acp::ToolCallStatus::Completed),
+                title: Some(compaction.title()),
+                content: Some(vec![compaction.summary.clone().into()]),
+                ..Default::default()
+            },
+        );
+        this.update(cx, |this, cx| {
+            // The usage of the last request no longer reflects the size of
+            // the thread.
+            if let Some(id) = this.last_user_message().map(|message| message.id.clone()) {
+                this.request_token_usage.remove(&id);
+            }
+            this.last_request_token_usage = None;
+            this.messages.push(Message::Compaction(compaction));
+            this.updated_at = Utc::now();
+            this.summary = None;
+            cx.emit(TokenUsageUpdated(this.latest_token_usage()));
+            cx.notify();
+        })
+    }
+
+    /// The model that summarizes the messages to compact.
+    fn compaction_model(&self) -> Option<Arc<dyn LanguageModel>> {
+        self.summarization_model
+            .clone()
+            .or_else(|| self.model.clone())
+    }
+
+    /// Builds the request that summarizes the messages to compact, keeping the
+    /// most recent turns out of it, along with the compaction it will produce.
+    fn prepare_compaction(
+        &self,
+        cx: &App,
+    ) -> Result<(
+        Arc<dyn LanguageModel>,
+        LanguageModelRequest,
+        CompactionMessage,
+        LanguageModelToolUseId,
+    )> {
+        let model = self
+            .compaction_model()
+            .context("No language model configured")?;
+        let keep_recent_turns = AgentSettings::get_global(cx).compact_keep_recent_turns;
+
+        let messages = self.messages_for_request();
+        // Keep as many of the most recent turns as possible, as long as there
+        // is something left to compact.
+        let split_ix = messages
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, message)| matches!(message, Message::User(_)))
+            .take(keep_recent_turns)
+            .map(|(ix, _)| ix)
+            .filter(|ix| *ix > 0)
+            .last()
+            .unwrap_or(messages.len());
+        let (compacted, kept) = messages.split_at(split_ix);
+        if compacted
+            .iter()
+            .all(|message| matches!(message, Message::Compaction(_)))
+        {
+            return Err(anyhow!("There are no messages to compact yet"));
+        }
+
+        let mut compacted_message_count = 0;
+        let mut pinned_context = Vec::new();
+        for message in compacted {
+            let context = match message {
+                Message::User(message) => {
+                    compacted_message_count += 1;
+                    message.content.as_slice()
+                }
+                Message::Agent(_) => {
+                    compacted_message_count += 1;
+                    &[]
+                }
+                Message::Compaction(compaction) => {
+                    compacted_message_count += compaction.compacted_message_count;
+                    compaction.pinned_context.as_slice()
+                }
+                Message::Resume => &[],
+            };
+            for content in context {
+                let UserMessageContent::Mention { uri, .. } = content else {
+                    continue;
+                };
+                // Only keep the latest version of each attached item.
+                pinned_context.retain(|pinned| {
+                    !matches!(pinned, UserMessageContent::Mention { uri: pinned_uri, .. } if pinned_uri == uri)
+                });
+                pinned_context.push(content.clone());
+            }
+        }
+        let first_kept_message = kept.first().and_then(|message| match message {
+            Message::User(message) => Some(message.id.clone()),
+            _ => None,
+        });
+
+        let mut request = LanguageModelRequest {
+            intent: Some(CompletionIntent::ThreadContextSummarization),
+            temperature: AgentSettings::temperature_for_model(&model, cx),
+            ..Default::default()
+        };
+        for message in compacted {
+            request.messages.extend(message.to_request());
+        }
+        request.messages.push(LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![COMPACT_THREAD_PROMPT.into()],
+            cache: false,
+        });
+
+        let compaction = CompactionMessage {
+            summary: String::new(),
+            compacted_message_count,
+            first_kept_message,
+            pinned_context,
+        };
+        let tool_use_id = compaction_tool_use_id(self.messages.len());
+        Ok((model, request, compaction, tool_use_id))
+    }
+
+    /// Returns the messages the model sees: everything since the latest
+    /// compaction, preceded by the compaction and the messages it kept.
+    fn messages_for_request(&self) -> Vec<&Message> {
+        let Some(compaction_ix) = self
+            .messages
+            .iter()
+            .rposition(|message| matches!(message, Message::Compaction(_)))
+        else {
+            return self.messages.iter().collect();
+        };
+        let Message::Compaction(compaction) = &self.messages[compaction_ix] else {
+            unreachable!()
+        };
+
+        let kept_ix = compaction
+            .first_kept_message
+            .as_ref()
+            .and_then(|kept_id| {
+                self.messages[..compaction_ix].iter().rposition(
+                    |message| matches!(message, Message::User(UserMessage { id, .. }) if id == kept_id),
+                )
+            })
+            .unwrap_or(compaction_ix);
+        let kept = self.messages[kept_ix..compaction_ix]
+            .iter()
+            .filter(|message| !matches!(message, Message::Compaction(_)));
+
+        std::iter::once(&self.messages[compaction_ix])
+            .chain(kept)
+            .chain(&self.messages[compaction_ix + 1..])
+            .collect()
+    }
+
     /// Sending a message results in the model streaming a response, which could include tool calls.
     /// After calling tools, the model will stops and waits for any outstanding tool calls to be completed and their results sent.
     /// The returned channel will report all the occurrences in which the model stops before erroring or ending its turn.
@@ -1173,12 +1634,19 @@ impl Thread {
         self.summary = None;
         self.running_turn = Some(RunningTurn {
             event_stream: event_stream.clone(),
+            model: model.clone(),
             tools: self.enabled_tools(profile, &model, cx),
             _task: cx.spawn(async move |this, cx| {
                 log::debug!("Starting agent turn execution");
 
-                let turn_result = Self::run_turn_internal(&this, model, &event_stream, cx).await;
-                _ = this.update(cx, |this, cx| this.flush_pending_message(cx));
+                let turn_result =
+                    Self::run_turn_internal(&this, model.clone(), &event_stream, cx).await;
+                _ = this.update(cx, |this, cx| {
+                    // Requests that failed before the end of their stream still
+                    // used tokens.
+                    this.record_completion_usage(&model, cx);
+                    this.flush_pending_message(cx);
+                });
 
                 match turn_result {
                     Ok(()) => {
@@ -1216,7 +1684,17 @@ impl Thread {
     ) -> Result<()> {
         let mut attempt = 0;
         let mut intent = CompletionIntent::UserPrompt;
+        // Stop compacting the thread for the rest of the turn once compaction
+        // fails, so that we don't summarize the thread before every request.
+        let mut can_compact = true;
         loop {
+            if can_compact && this.read_with(cx, |this, cx| this.needs_compaction(cx))? {
+                can_compact = Self::compact_internal(this, event_stream, cx)
+                    .await
+                    .log_err()
+                    .is_some();
+            }
+
             let request =
                 this.update(cx, |this, cx| this.build_completion_request(intent, cx))??;
 
@@ -1250,6 +1728,7 @@ impl Thread {
                     }
                 }
             }
+            this.update(cx, |this, cx| this.record_completion_usage(&model, cx))?;
 
             let end_turn = tool_results.is_empty();
             while let Some(tool_result) = tool_results.next().await {
@@ -1282,6 +1761,22 @@ impl Thread {
             })?;
 
             if let Some(error) = error {
+                if can_compact
+                    && matches!(error, LanguageModelCompletionError::PromptTooLarge { .. })
+                    && this.read_with(cx, |_, cx| AgentSettings::get_global(cx).auto_compact)?
+                {
+                    // Make room in the context window and try again, at most
+                    // once per turn.
+                    can_compact = false;
+                    if Self::compact_internal(this, event_stream, cx)
+                        .await
+                        .log_err()
+                        .is_some()
+                    {
+                        continue;
+                    }
+                }
+
                 attempt += 1;
                 let retry =
                     this.update(cx, |this, _| this.handle_completion_error(error, attempt))??;
@@ -1300,6 +1795,10 @@ impl Thread {
                 return Err(language_model::ToolUseLimitReachedError.into());
             } else if end_turn {
                 return Ok(());
+            } else if let Some(token_budget) =
+                this.read_with(cx, |this, _| this.exhausted_token_budget())?
+            {
+                return Err(TokenBudgetExhaustedError { token_budget }.into());
             } else {
                 intent = CompletionIntent::ToolResults;
                 attempt = 0;
@@ -1399,6 +1898,7 @@ impl Thread {
                     cache_creation_input_tokens = usage.cache_creation_input_tokens,
                     cache_read_input_tokens = usage.cache_read_input_tokens,
                 );
+                self.completion_token_usage = Some(usage);
                 self.update_token_usage(usage, cx);
             }
             StatusUpdate(CompletionRequestStatus::UsageUpdated { amount, limit }) => {
@@ -1626,7 +2126,7 @@ impl Thread {
             ..Default::default()
         };
 
-        for message in &self.messages {
+        for message in self.messages_for_request() {
             request.messages.extend(message.to_request());
         }
 
@@ -1684,7 +2184,7 @@ impl Thread {
             ..Default::default()
         };
 
-        for message in &self.messages {
+        for message in self.messages_for_request() {
             request.messages.extend(message.to_request());
         }
 
@@ -1748,6 +2248,7 @@ impl Thread {
                 Message::User(user_message) => Some(user_message),
                 Message::Agent(_) => None,
                 Message::Resume => None,
+                Message::Compaction(_) => None,
             })
     }
 
@@ -1914,11 +2415,21 @@ impl Thread {
             self.messages.len()
         );
 
-        let system_prompt = SystemPromptTemplate {
-            project: self.project_context.read(cx),
-            available_tools: self.tools.keys().cloned().collect(),
+        let project = self.project_context.read(cx);
+        let available_tools = self.tools.keys().cloned().collect();
+        let system_prompt = if self.is_subagent() {
+            SubagentSystemPromptTemplate {
+                project,
+                available_tools,
+            }
+            .render(&self.templates)
+        } else {
+            SystemPromptTemplate {
+                project,
+                available_tools,
+            }
+            .render(&self.templates)
         }
-        .render(&self.templates)
         .context("failed to build system prompt")
         .expect("Invalid template");
         let mut messages = vec![LanguageModelRequestMessage {
@@ -1926,7 +2437,7 @@ impl Thread {
             content: vec![system_prompt.into()],
             cache: false,
         }];
-        for message in &self.messages {
+        for message in self.messages_for_request() {
             messages.extend(message.to_request());
         }
 
@@ -2078,6 +2589,9 @@ struct RunningTurn {
     /// The current event stream for the running turn. Used to report a final
     /// cancellation event if we cancel the turn.
     event_stream: ThreadEventStream,
+    /// The model the turn's requests are sent to, whose usage is recorded if
+    /// the turn is cancelled.
+    model: Arc<dyn LanguageModel>,
     /// The tools that were enabled for this turn.
     tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
 }
@@ -2089,6 +2603,11 @@ impl RunningTurn {
     }
 }
 
+/// The ID of the tool call that shows the compaction at the given message index.
+fn compaction_tool_use_id(message_ix: usize) -> LanguageModelToolUseId {
+    format!("compaction-{message_ix}").into()
+}
+
 pub struct TokenUsageUpdated(pub Option<acp_thread::TokenUsage>);
 
 impl EventEmitter<TokenUsageUpdated> for Thread {}
@@ -2402,6 +2921,19 @@ impl ToolCallEventStream {
             .ok();
     }
 
+    pub fn update_subagent(&self, subagent: Entity<acp_thread::Subagent>) {
+        self.stream
+            .0
+            .unbounded_send(Ok(ThreadEvent::ToolCallUpdate(
+                acp_thread::ToolCallUpdateSubagent {
+                    id: acp::ToolCallId(self.tool_use_id.to_string().into()),
+                    subagent,
+                }
+                .into(),
+            )))
+            .ok();
+    }
+
     pub fn authorize(&self, title: impl Into<String>, cx: &mut App) -> Task<Result<()>> {
         if agent_settings::AgentSettings::get_global(cx).always_allow_tool_actions {
             return Task::ready(Ok(()));
@@ -1,6 +1,8 @@
+mod code_navigation_tool;
 mod context_server_registry;
 mod copy_path_tool;
 mod create_directory_tool;
+mod delegate_tool;
 mod delete_path_tool;
 mod diagnostics_tool;
 mod edit_file_tool;
@@ -12,15 +14,20 @@ mod move_path_tool;
 mod now_tool;
 mod open_tool;
 mod read_file_tool;
+mod rename_symbol_tool;
+mod semantic_search_tool;
 mod terminal_tool;
 mod thinking_tool;
 mod web_search_tool;
+mod workspace_symbols_tool;
 
 /// A list of all built in tool names, for use in deduplicating MCP tool names
 pub fn default_tool_names() -> impl Iterator<Item = &'static str> {
     [
+        CodeNavigationTool::name(),
         CopyPathTool::name(),
         CreateDirectoryTool::name(),
+        DelegateTool::name(),
         DeletePathTool::name(),
         DiagnosticsTool::name(),
         EditFileTool::name(),
@@ -32,16 +39,21 @@ pub fn default_tool_names() -> impl Iterator<Item = &'static str> {
         NowTool::name(),
         OpenTool::name(),
         ReadFileTool::name(),
+        RenameSymbolTool::name(),
+        SemanticSearchTool::name(),
         TerminalTool::name(),
         ThinkingTool::name(),
         WebSearchTool::name(),
+        WorkspaceSymbolsTool::name(),
     ]
     .into_iter()
 }
 
+pub use code_navigation_tool::*;
 pub use context_server_registry::*;
 pub use copy_path_tool::*;
 pub use create_directory_tool::*;
+pub use delegate_tool::*;
 pub use delete_path_tool::*;
 pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
@@ -53,8 +65,11 @@ pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
 pub use read_file_tool::*;
+pub use rename_symbol_tool::*;
+pub use semantic_search_tool::*;
 pub use terminal_tool::*;
 pub use thinking_tool::*;
 pub use web_search_tool::*;
+pub use workspace_symbols_tool::*;
 
 use crate::AgentTool;
@@ -0,0 +1,298 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use gpui::{App, AsyncApp, Entity, SharedString, Task};
+use language::{Buffer, BufferSnapshot, Location, OffsetRangeExt, Point};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// The maximum number of locations returned by a single navigation request.
+const MAX_LOCATIONS: usize = 100;
+
+/// Uses the language server to navigate code semantically.
+///
+/// Prefer this tool over `grep` when you need to find where a symbol is defined, what type it has, or every place it is used. Unlike text search, the results are resolved by the language server, so they are not confused by shadowing, re-exports or identically named symbols.
+///
+/// The symbol is identified by the file it appears in, the line it appears on, and its name. The name must appear on that line exactly as written.
+///
+/// <example>
+/// To find the definition of `parse_config` called on line 42 of `src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "line": 42,
+///     "symbol": "parse_config",
+///     "operation": "definition"
+/// }
+/// </example>
+///
+/// <guidelines>
+/// - Use `definition` or `type_definition` before reading a file to find the exact location of an item.
+/// - Use `references` before changing the signature of a function, to find every caller.
+/// - Use `hover` to learn the inferred type or documentation of a symbol without opening its definition.
+/// </guidelines>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CodeNavigationToolInput {
+    /// The relative path of the file containing the symbol.
+    ///
+    /// This path should never be absolute, and the first component of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line number the symbol appears on (1-based index).
+    pub line: u32,
+    /// The name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+    /// The kind of navigation to perform.
+    pub operation: CodeNavigationOperation,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeNavigationOperation {
+    /// Find where the symbol is defined.
+    Definition,
+    /// Find where the type of the symbol is defined.
+    TypeDefinition,
+    /// Find the implementations of a trait, interface or abstract method.
+    Implementation,
+    /// Find every place the symbol is referenced.
+    References,
+    /// Show the type signature and documentation of the symbol.
+    Hover,
+}
+
+impl CodeNavigationOperation {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Definition => "definition",
+            Self::TypeDefinition => "type definition",
+            Self::Implementation => "implementations",
+            Self::References => "references",
+            Self::Hover => "type information",
+        }
+    }
+}
+
+pub struct CodeNavigationTool {
+    project: Entity<Project>,
+}
+
+impl CodeNavigationTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for CodeNavigationTool {
+    type Input = CodeNavigationToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "code_navigation"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(&self, input: Result<Self::Input, serde_json::Value>) -> SharedString {
+        match input {
+            Ok(input) => format!(
+                "Find {} of {}",
+                input.operation.label(),
+                MarkdownInlineCode(&input.symbol)
+            )
+            .into(),
+            Err(_) => "Navigate code".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let (buffer, position) =
+                open_symbol_position(&project, &input.path, input.line, &input.symbol, cx).await?;
+
+            let locations = match input.operation {
+                CodeNavigationOperation::Definition => project
+                    .update(cx, |project, cx| project.definitions(&buffer, position, cx))?
+                    .await?
+                    .map(|links| links.into_iter().map(|link| link.target).collect()),
+                CodeNavigationOperation::TypeDefinition => project
+                    .update(cx, |project, cx| {
+                        project.type_definitions(&buffer, position, cx)
+                    })?
+                    .await?
+                    .map(|links| links.into_iter().map(|link| link.target).collect()),
+                CodeNavigationOperation::Implementation => project
+                    .update(cx, |project, cx| {
+                        project.implementations(&buffer, position, cx)
+                    })?
+                    .await?
+                    .map(|links| links.into_iter().map(|link| link.target).collect()),
+                CodeNavigationOperation::References => {
+                    project
+                        .update(cx, |project, cx| project.references(&buffer, position, cx))?
+                        .await?
+                }
+                CodeNavigationOperation::Hover => {
+                    let hovers = project
+                        .update(cx, |project, cx| project.hover(&buffer, position, cx))?
+                        .await
+                        .ok_or_else(|| {
+                            anyhow!("No language server supports hover for {}", input.path)
+                        })?;
+                    let mut output = String::new();
+                    for block in hovers.iter().flat_map(|hover| &hover.contents) {
+                        if block.text.trim().is_empty() {
+                            continue;
+                        }
+                        writeln!(output, "{}\n", block.text.trim())?;
+                    }
+                    if output.is_empty() {
+                        return Ok(format!(
+                            "No type information available for {}.",
+                            MarkdownInlineCode(&input.symbol)
+                        ));
+                    }
+                    return Ok(output);
+                }
+            };
+
+            let Some(locations) = locations.filter(|locations| !locations.is_empty()) else {
+                return Ok(format!(
+                    "No {} found for {}.",
+                    input.operation.label(),
+                    MarkdownInlineCode(&input.symbol)
+                ));
+            };
+
+            cx.update(|cx| format_locations(&locations, cx))
+        })
+    }
+}
+
+/// Opens the buffer at `path` and finds the position of `symbol` on the given 1-based `line`.
+pub(crate) async fn open_symbol_position(
+    project: &Entity<Project>,
+    path: &str,
+    line: u32,
+    symbol: &str,
+    cx: &mut AsyncApp,
+) -> Result<(Entity<Buffer>, Point)> {
+    let buffer = project
+        .update(cx, |project, cx| {
+            let project_path = project
+                .find_project_path(path, cx)
+                .ok_or_else(|| anyhow!("Path {path} not found in project"))?;
+            anyhow::Ok(project.open_buffer(project_path, cx))
+        })??
+        .await?;
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
+    let position = symbol_position(&snapshot, line, symbol)?;
+    Ok((buffer, position))
+}
+
+/// Finds the start of `symbol` on the given 1-based `line`, preferring whole-word occurrences.
+pub(crate) fn symbol_position(snapshot: &BufferSnapshot, line: u32, symbol: &str) -> Result<Point> {
+    let row = line.saturating_sub(1);
+    if line == 0 || row > snapshot.max_point().row {
+        return Err(anyhow!(
+            "Line {line} is out of range; the file has {} lines",
+            snapshot.max_point().row + 1
+        ));
+    }
+    if symbol.is_empty() {
+        return Err(anyhow!("Symbol name can't be empty"));
+    }
+
+    let line_text = snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect::<String>();
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut first_match = None;
+    for (column, _) in line_text.match_indices(symbol) {
+        first_match.get_or_insert(column);
+        let before = line_text[..column].chars().next_back();
+        let after = line_text[column + symbol.len()..].chars().next();
+        if !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char) {
+            return Ok(Point::new(row, column as u32));
+        }
+    }
+
+    first_match
+        .map(|column| Point::new(row, column as u32))
+        .ok_or_else(|| anyhow!("Symbol `{symbol}` not found on line {line}: `{line_text}`"))
+}
+
+fn format_locations(locations: &[Location], cx: &App) -> String {
+    let mut output = String::new();
+    for location in locations.iter().take(MAX_LOCATIONS) {
+        let buffer = location.buffer.read(cx);
+        let Some(file) = buffer.file() else {
+            continue;
+        };
+        let range = location.range.to_point(&buffer.snapshot());
+        let line_text = buffer
+            .text_for_range(
+                Point::new(range.start.row, 0)
+                    ..Point::new(range.start.row, buffer.line_len(range.start.row)),
+            )
+            .collect::<String>();
+        writeln!(
+            output,
+            "{}:{}:{}: {}",
+            file.full_path(cx).display(),
+            range.start.row + 1,
+            range.start.column + 1,
+            line_text.trim()
+        )
+        .ok();
+    }
+    if locations.len() > MAX_LOCATIONS {
+        writeln!(
+            output,
+            "\n{} more locations were omitted.",
+            locations.len() - MAX_LOCATIONS
+        )
+        .ok();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use language::Buffer;
+
+    #[gpui::test]
+    fn test_symbol_position(cx: &mut TestAppContext) {
+        let buffer =
+            cx.new(|cx| Buffer::local("fn main() {\n    let mainly = main_loop(main);\n}\n", cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        assert_eq!(
+            symbol_position(&snapshot, 1, "main").unwrap(),
+            Point::new(0, 3)
+        );
+        // Whole-word occurrences are preferred over substrings of other identifiers.
+        assert_eq!(
+            symbol_position(&snapshot, 2, "main").unwrap(),
+            Point::new(1, 27)
+        );
+        assert_eq!(
+            symbol_position(&snapshot, 2, "main_lo").unwrap(),
+            Point::new(1, 17)
+        );
+        assert!(symbol_position(&snapshot, 2, "missing").is_err());
+        assert!(symbol_position(&snapshot, 0, "main").is_err());
+        assert!(symbol_position(&snapshot, 10, "main").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{AgentTool, Thread, ThreadEvent, TokenBudgetExhaustedError, ToolCallEventStream};
+use acp_thread::{Subagent, SubagentStatus, UserMessageId};
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::{Result, anyhow};
+use futures::{FutureExt as _, StreamExt as _, channel::oneshot};
+use gpui::{App, AppContext as _, AsyncApp, Entity, SharedString, Task, WeakEntity};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use std::sync::Arc;
+
+/// Delegates a self-contained task to a sub-agent, which works on it in a separate conversation and reports back with a summary.
+///
+/// Use this tool for tasks that need a lot of exploration but whose outcome can be summarized briefly, e.g. finding where something is implemented, understanding how a subsystem works or surveying every usage of an API. The files the sub-agent reads and the searches it runs don't take up space in this conversation; only its final summary does.
+///
+/// The sub-agent can't see this conversation, so the task must contain everything it needs to know: what to look for, where to start, and what to include in its summary. It can't ask questions.
+///
+/// To work on independent tasks in parallel, call this tool several times in the same message. Don't delegate tasks that are quick to do yourself, or that depend on each other's results.
+///
+/// <example>
+/// To find out how errors are reported to the user:
+/// {
+///     "label": "Find how errors are reported to the user",
+///     "task": "Find where errors that occur while saving a file are reported to the user. Report the file paths and line numbers of the code that shows the error, the type of notification it uses and how other parts of the code can trigger the same notification."
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DelegateToolInput {
+    /// A short description of the task, shown to the user while the sub-agent works on it.
+    pub label: String,
+    /// The complete instructions for the sub-agent, including what its summary should contain.
+    pub task: String,
+}
+
+pub struct DelegateTool {
+    thread: WeakEntity<Thread>,
+}
+
+impl DelegateTool {
+    pub fn new(thread: WeakEntity<Thread>) -> Self {
+        Self { thread }
+    }
+}
+
+impl AgentTool for DelegateTool {
+    type Input = DelegateToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "delegate"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Think
+    }
+
+    fn initial_title(&self, input: Result<Self::Input, serde_json::Value>) -> SharedString {
+        match input {
+            Ok(input) => input.label.into(),
+            Err(_) => "Delegate task".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let Some(parent) = self.thread.upgrade() else {
+            return Task::ready(Err(anyhow!("The thread was closed")));
+        };
+
+        let settings = AgentSettings::get_global(cx);
+        let profile_id = settings.delegate_profile.clone();
+        let token_budget = settings.delegate_token_budget;
+        let subagent_thread = cx.new(|cx| {
+            let mut thread = Thread::new_subagent(parent.read(cx), profile_id, token_budget, cx);
+            thread.add_default_tools(cx);
+            thread.set_title(input.label.clone().into(), cx);
+            thread
+        });
+        let mut events = match subagent_thread.update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), [input.task.as_str()], cx)
+        }) {
+            Ok(events) => events,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let language_registry = parent.read(cx).project().read(cx).languages().clone();
+        let label = SharedString::from(input.label);
+        let subagent = cx.new(|cx| {
+            Subagent::new(
+                label.clone(),
+                token_budget,
+                cancel_tx,
+                language_registry,
+                cx,
+            )
+        });
+        event_stream.update_subagent(subagent.clone());
+
+        cx.spawn(async move |cx| {
+            let _cancel_on_drop = CancelOnDrop {
+                subagent: subagent.clone(),
+                thread: subagent_thread.clone(),
+                cx: cx.clone(),
+            };
+            let mut cancel_rx = cancel_rx.fuse();
+            // The text the sub-agent streamed since its last tool call, i.e. its final message.
+            let mut response = String::new();
+            let mut error = None;
+            let status = loop {
+                let event = futures::select_biased! {
+                    _ = cancel_rx => {
+                        subagent_thread.update(cx, |thread, cx| thread.cancel(cx))?;
+                        break SubagentStatus::Canceled;
+                    }
+                    event = events.next() => event,
+                };
+
+                let status = match event {
+                    Some(Ok(ThreadEvent::AgentText(text))) => {
+                        response.push_str(&text);
+                        subagent.update(cx, |subagent, cx| subagent.push_text(&text, cx))?;
+                        None
+                    }
+                    Some(Ok(ThreadEvent::ToolCall(tool_call))) => {
+                        response.clear();
+                        subagent.update(cx, |subagent, cx| {
+                            subagent.push_tool_call(&tool_call, cx)
+                        })?;
+                        None
+                    }
+                    Some(Ok(ThreadEvent::ToolCallUpdate(
+                        acp_thread::ToolCallUpdate::UpdateFields(update),
+                    ))) => {
+                        subagent
+                            .update(cx, |subagent, cx| subagent.update_tool_call(&update, cx))?;
+                        None
+                    }
+                    Some(Ok(ThreadEvent::ToolCallAuthorization(authorization))) => {
+                        // The sub-agent's tool calls aren't shown as entries of
+                        // the parent thread, so ask for permission on the
+                        // delegating tool call instead.
+                        let title = authorization
+                            .tool_call
+                            .fields
+                            .title
+                            .unwrap_or_else(|| "Run a tool".into());
+                        let authorize = cx.update(|cx| {
+                            event_stream.authorize(format!("{label}: {title}"), cx)
+                        })?;
+                        let option_id = if authorize.await.is_ok() {
+                            "allow"
+                        } else {
+                            "deny"
+                        };
+                        authorization
+                            .response
+                            .send(acp::PermissionOptionId(option_id.into()))
+                            .ok();
+                        event_stream.update_fields(acp::ToolCallUpdateFields {
+                            title: Some(label.to_string()),
+                            status: Some(acp::ToolCallStatus::InProgress),
+                            ..Default::default()
+                        });
+                        None
+                    }
+                    Some(Ok(ThreadEvent::Stop(acp::StopReason::EndTurn))) => {
+                        Some(SubagentStatus::Completed)
+                    }
+                    Some(Ok(ThreadEvent::Stop(acp::StopReason::Cancelled))) => {
+                        Some(SubagentStatus::Canceled)
+                    }
+                    Some(Ok(ThreadEvent::Stop(reason))) => {
+                        error = Some(anyhow!("The sub-agent stopped early: {reason:?}"));
+                        Some(SubagentStatus::Failed)
+                    }
+                    Some(Ok(
+                        ThreadEvent::UserMessage(_)
+                        | ThreadEvent::AgentThinking(_)
+                        | ThreadEvent::ToolCallUpdate(_)
+                        | ThreadEvent::Retry(_),
+                    )) => None,
+                    Some(Err(err)) if err.is::<TokenBudgetExhaustedError>() => {
+                        Some(SubagentStatus::BudgetExhausted)
+                    }
+                    Some(Err(err)) => {
+                        error = Some(err);
+                        Some(SubagentStatus::Failed)
+                    }
+                    None => Some(SubagentStatus::Completed),
+                };
+
+                let used_tokens = subagent_thread
+                    .read_with(cx, |thread, _| thread.cumulative_token_usage().total_tokens())?;
+                subagent.update(cx, |subagent, cx| subagent.set_used_tokens(used_tokens, cx))?;
+                if let Some(status) = status {
+                    break status;
+                }
+            };
+            subagent.update(cx, |subagent, cx| subagent.finish(status, cx))?;
+
+            let response = response.trim();
+            match status {
+                SubagentStatus::Completed | SubagentStatus::Running => {
+                    if response.is_empty() {
+                        Ok("The sub-agent finished without reporting anything.".into())
+                    } else {
+                        Ok(response.to_string())
+                    }
+                }
+                SubagentStatus::BudgetExhausted => {
+                    let mut output = format!(
+                        "The sub-agent used up its budget of {token_budget} tokens before finishing the task."
+                    );
+                    if !response.is_empty() {
+                        output.push_str(" Its last message was:\n\n");
+                        output.push_str(response);
+                    }
+                    Ok(output)
+                }
+                SubagentStatus::Canceled => Err(anyhow!(
+                    "The user stopped the sub-agent before it finished the task."
+                )),
+                SubagentStatus::Failed => {
+                    Err(error.unwrap_or_else(|| anyhow!("The sub-agent failed.")))
+                }
+            }
+        })
+    }
+}
+
+/// Stops the sub-agent when the task that runs it is dropped before it finished, e.g. because
+/// the delegating turn was canceled, so that its card doesn't keep showing it as running.
+struct CancelOnDrop {
+    subagent: Entity<Subagent>,
+    thread: Entity<Thread>,
+    cx: AsyncApp,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let subagent = self.subagent.clone();
+        let thread = self.thread.clone();
+        // The task is usually dropped while the delegating thread is being
+        // updated, so the entities can only be updated afterwards.
+        self.cx
+            .spawn(async move |cx| {
+                let is_running = subagent.read_with(cx, |subagent, _| subagent.is_running())?;
+                if is_running {
+                    thread.update(cx, |thread, cx| thread.cancel(cx))?;
+                    subagent.update(cx, |subagent, cx| {
+                        subagent.finish(SubagentStatus::Canceled, cx)
+                    })?;
+                }
+                anyhow::Ok(())
+            })
+            .detach();
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{AgentTool, ToolCallEventStream, tools::code_navigation_tool::open_symbol_position};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// Renames a symbol and every reference to it across the project, using the language server.
+///
+/// Prefer this tool over editing files by hand whenever you rename a function, type, variable, field or module: the language server understands scoping, so it won't touch unrelated identifiers that happen to share the name.
+///
+/// The symbol is identified by the file it appears in, the line it appears on, and its name. The name must appear on that line exactly as written.
+///
+/// <example>
+/// To rename the function `parse_config` defined on line 12 of `src/config.rs` to `load_config`:
+/// {
+///     "path": "project/src/config.rs",
+///     "line": 12,
+///     "symbol": "parse_config",
+///     "new_name": "load_config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbolToolInput {
+    /// The relative path of a file containing the symbol.
+    ///
+    /// This path should never be absolute, and the first component of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line number the symbol appears on (1-based index).
+    pub line: u32,
+    /// The current name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+    /// The new name for the symbol.
+    pub new_name: String,
+}
+
+pub struct RenameSymbolTool {
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl RenameSymbolTool {
+    pub fn new(project: Entity<Project>, action_log: Entity<ActionLog>) -> Self {
+        Self {
+            project,
+            action_log,
+        }
+    }
+}
+
+impl AgentTool for RenameSymbolTool {
+    type Input = RenameSymbolToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "rename_symbol"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(&self, input: Result<Self::Input, serde_json::Value>) -> SharedString {
+        match input {
+            Ok(input) => format!(
+                "Rename {} to {}",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name)
+            )
+            .into(),
+            Err(_) => "Rename symbol".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+        cx.spawn(async move |cx| {
+            if input.new_name.trim().is_empty() {
+                return Err(anyhow!("The new name can't be empty"));
+            }
+
+            let (buffer, position) =
+                open_symbol_position(&project, &input.path, input.line, &input.symbol, cx).await?;
+            let project_transaction = project
+                .update(cx, |project, cx| {
+                    project.perform_rename(buffer, position, input.new_name.clone(), cx)
+                })?
+                .await?;
+
+            if project_transaction.0.is_empty() {
+                return Err(anyhow!(
+                    "The language server didn't produce any edits when renaming {}",
+                    MarkdownInlineCode(&input.symbol)
+                ));
+            }
+
+            action_log.update(cx, |action_log, cx| {
+                let mut output = format!(
+                    "Renamed {} to {} in {} file(s):\n",
+                    MarkdownInlineCode(&input.symbol),
+                    MarkdownInlineCode(&input.new_name),
+                    project_transaction.0.len()
+                );
+                for (buffer, transaction) in &project_transaction.0 {
+                    if let Some(file) = buffer.read(cx).file() {
+                        writeln!(output, "- {}", file.full_path(cx).display())?;
+                    }
+                    action_log.buffer_edited_in_transaction(buffer.clone(), transaction, cx);
+                }
+                anyhow::Ok(output)
+            })?
+        })
+    }
+}
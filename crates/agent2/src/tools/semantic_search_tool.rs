@@ -0,0 +1,211 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::{Result, anyhow};
+use gpui::{App, BorrowAppContext as _, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use semantic_index::{SemanticDb, Status};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::{MarkdownCodeBlock, MarkdownInlineCode};
+
+const MAX_RESULTS: usize = 20;
+
+/// Searches the project's code by meaning rather than by exact text, using the project's semantic index.
+///
+/// Use this tool when you know what some code does but not what it's called, e.g. "where are HTTP requests retried?" or "code that parses the config file". When you know the exact text or symbol name, `grep` and `workspace_symbols` are faster and more precise.
+///
+/// The results are the excerpts of the project that are the most relevant to the query, best matches first.
+///
+/// <example>
+/// To find the code that handles authentication failures:
+/// {
+///     "query": "handle an authentication failure and prompt the user to sign in again"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SemanticSearchToolInput {
+    /// A natural language description of the code to find.
+    pub query: String,
+}
+
+pub struct SemanticSearchTool {
+    project: Entity<Project>,
+}
+
+impl SemanticSearchTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for SemanticSearchTool {
+    type Input = SemanticSearchToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "semantic_search"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(&self, input: Result<Self::Input, serde_json::Value>) -> SharedString {
+        match input {
+            Ok(input) => format!("Semantic search for {}", MarkdownInlineCode(&input.query)).into(),
+            Err(_) => "Semantic search".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let project_index = cx.has_global::<SemanticDb>().then(|| {
+            cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+                semantic_db.project_index(self.project.clone(), cx)
+            })
+        });
+        let Some(project_index) = project_index.flatten() else {
+            return Task::ready(Err(anyhow!(
+                "The semantic index isn't enabled for this project. \
+                It can be enabled with the `semantic_index.enabled` setting."
+            )));
+        };
+
+        let (search, fs, status) = project_index.read_with(cx, |project_index, cx| {
+            (
+                project_index.search(vec![input.query.clone()], MAX_RESULTS, cx),
+                project_index.fs(),
+                project_index.status(),
+            )
+        });
+        cx.spawn(async move |cx| {
+            let results = search.await?;
+            let results = SemanticDb::load_results(results, &fs, cx).await?;
+
+            let mut output = String::new();
+            if let Status::Loading | Status::Scanning { .. } = status {
+                writeln!(
+                    output,
+                    "The project is still being indexed, so some relevant code may be missing.\n"
+                )?;
+            }
+            if results.is_empty() {
+                write!(
+                    output,
+                    "No results found for {}.",
+                    MarkdownInlineCode(&input.query)
+                )?;
+                return Ok(output);
+            }
+
+            for result in results {
+                writeln!(
+                    output,
+                    "## {}:{}-{}\n\n{}",
+                    result.full_path.display(),
+                    result.row_range.start() + 1,
+                    result.row_range.end() + 1,
+                    MarkdownCodeBlock {
+                        tag: &result.full_path.to_string_lossy(),
+                        text: &result.excerpt_content,
+                    }
+                )?;
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use semantic_index::{FakeEmbeddingProvider, SemanticIndexSettings};
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use util::path;
+
+    #[gpui::test]
+    async fn test_semantic_search_requires_index(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), json!({ "main.rs": "fn main() {}" }))
+            .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let semantic_db = SemanticDb::new(
+            temp_dir.path().into(),
+            Arc::new(FakeEmbeddingProvider),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        cx.update(|cx| {
+            cx.set_global(semantic_db);
+            SemanticDb::refresh_project(project.clone(), cx);
+        });
+
+        let tool = Arc::new(SemanticSearchTool::new(project.clone()));
+        let result = cx
+            .update(|cx| {
+                tool.clone().run(
+                    SemanticSearchToolInput {
+                        query: "entry point".into(),
+                    },
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await;
+        assert!(
+            result.unwrap_err().to_string().contains("isn't enabled"),
+            "semantic search should fail while the index is disabled"
+        );
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<SemanticIndexSettings>(cx, |settings| {
+                    settings.enabled = true;
+                });
+            });
+            SemanticDb::refresh_project(project.clone(), cx);
+        });
+        cx.run_until_parked();
+
+        let result = cx
+            .update(|cx| {
+                tool.run(
+                    SemanticSearchToolInput {
+                        query: "entry point".into(),
+                    },
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(
+            result.contains("root/main.rs"),
+            "unexpected output: {result}"
+        );
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            SemanticIndexSettings::register(cx);
+        });
+    }
+}
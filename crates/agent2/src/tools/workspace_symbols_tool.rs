@@ -0,0 +1,105 @@
+use crate::{AgentTool, ToolCallEventStream};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, path::Path, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_SYMBOLS: usize = 50;
+
+/// Searches the symbols (functions, types, constants, modules...) of the whole project by name, using the project's language servers.
+///
+/// Prefer this tool over `grep` when looking for the definition of an item whose name you know but whose location you don't. Matching is fuzzy, so partial names work.
+///
+/// <example>
+/// To find where the `ProjectIndex` struct is defined:
+/// {
+///     "query": "ProjectIndex"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSymbolsToolInput {
+    /// The name, or part of the name, of the symbol to search for.
+    pub query: String,
+}
+
+pub struct WorkspaceSymbolsTool {
+    project: Entity<Project>,
+}
+
+impl WorkspaceSymbolsTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for WorkspaceSymbolsTool {
+    type Input = WorkspaceSymbolsToolInput;
+    type Output = String;
+
+    fn name() -> &'static str {
+        "workspace_symbols"
+    }
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(&self, input: Result<Self::Input, serde_json::Value>) -> SharedString {
+        match input {
+            Ok(input) => format!("Search symbols for {}", MarkdownInlineCode(&input.query)).into(),
+            Err(_) => "Search symbols".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: Self::Input,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output>> {
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(&input.query, cx));
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let symbols = symbols.await?;
+            if symbols.is_empty() {
+                return Ok(format!(
+                    "No symbols found matching {}.",
+                    MarkdownInlineCode(&input.query)
+                ));
+            }
+
+            project.read_with(cx, |project, cx| {
+                let mut output = String::new();
+                for symbol in symbols.iter().take(MAX_SYMBOLS) {
+                    let Some(worktree) = project.worktree_for_id(symbol.path.worktree_id, cx)
+                    else {
+                        continue;
+                    };
+                    let path = Path::new(worktree.read(cx).root_name()).join(&symbol.path.path);
+                    writeln!(
+                        output,
+                        "{:?} `{}` at {}:{}",
+                        symbol.kind,
+                        symbol.name,
+                        path.display(),
+                        symbol.range.start.0.row + 1
+                    )?;
+                }
+                if symbols.len() > MAX_SYMBOLS {
+                    writeln!(
+                        output,
+                        "\n{} more symbols were omitted. Use a more specific query.",
+                        symbols.len() - MAX_SYMBOLS
+                    )?;
+                }
+                anyhow::Ok(output)
+            })?
+        })
+    }
+}
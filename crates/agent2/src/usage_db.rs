@@ -0,0 +1,289 @@
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{
+        bindable::Column, domain::Domain, statement::Statement,
+        thread_safe_connection::ThreadSafeConnection,
+    },
+    sqlez_macros::sql,
+};
+use language_model::TokenUsage;
+
+/// The token usage and cost of a group of completion requests, e.g. all the
+/// requests made by one thread, or on one day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSummary {
+    /// What the requests have in common, depending on the query: a thread
+    /// ID, a `provider/model` pair or a day, as the number of local days
+    /// since the unix epoch.
+    pub key: String,
+    pub requests: u64,
+    /// The number of requests to models whose price isn't known, which
+    /// aren't included in `cost`.
+    pub unpriced_requests: u64,
+    pub usage: TokenUsage,
+    /// The cost of the requests in US dollars.
+    pub cost: f64,
+}
+
+impl Column for UsageSummary {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (key, next_index): (String, i32) = Column::column(statement, start_index)?;
+        let (requests, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (unpriced_requests, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (input_tokens, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (output_tokens, next_index): (u64, i32) = Column::column(statement, next_index)?;
+        let (cache_creation_input_tokens, next_index): (u64, i32) =
+            Column::column(statement, next_index)?;
+        let (cache_read_input_tokens, next_index): (u64, i32) =
+            Column::column(statement, next_index)?;
+        let (cost, next_index): (f64, i32) = Column::column(statement, next_index)?;
+
+        let summary = Self {
+            key,
+            requests,
+            unpriced_requests,
+            usage: TokenUsage {
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+            },
+            cost,
+        };
+        Ok((summary, next_index))
+    }
+}
+
+pub struct TokenUsageDb(ThreadSafeConnection);
+
+impl Domain for TokenUsageDb {
+    const NAME: &str = stringify!(TokenUsageDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE IF NOT EXISTS token_usage(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            thread_id TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            cache_creation_input_tokens INTEGER NOT NULL,
+            cache_read_input_tokens INTEGER NOT NULL,
+            cost REAL,
+            recorded_at INTEGER DEFAULT (unixepoch()) NOT NULL
+        ) STRICT;
+        CREATE INDEX IF NOT EXISTS token_usage_recorded_at ON token_usage(recorded_at);
+    )];
+}
+
+db::static_connection!(TOKEN_USAGE_DB, TokenUsageDb, []);
+
+impl TokenUsageDb {
+    /// Records the usage of a single completion request. `cost` is `None`
+    /// when the price of the model isn't known.
+    pub async fn record_usage(
+        &self,
+        thread_id: String,
+        provider: String,
+        model: String,
+        usage: TokenUsage,
+        cost: Option<f64>,
+    ) -> Result<()> {
+        log::debug!("Recording token usage of thread {thread_id} with {provider}/{model}");
+        self.insert_usage(
+            thread_id,
+            provider,
+            model,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_creation_input_tokens,
+            usage.cache_read_input_tokens,
+            cost,
+        )
+        .await
+    }
+
+    query! {
+        async fn insert_usage(
+            thread_id: String,
+            provider: String,
+            model: String,
+            input_tokens: u64,
+            output_tokens: u64,
+            cache_creation_input_tokens: u64,
+            cache_read_input_tokens: u64,
+            cost: Option<f64>
+        ) -> Result<()> {
+            INSERT INTO token_usage (
+                thread_id,
+                provider,
+                model,
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens,
+                cache_read_input_tokens,
+                cost
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        }
+    }
+
+    // The combined usage of all requests made since the given unix timestamp.
+    query! {
+        pub fn total_since(since: i64) -> Result<UsageSummary> {
+            SELECT
+                NULL,
+                COUNT(*),
+                COUNT(*) - COUNT(cost),
+                COALESCE(SUM(input_tokens), 0),
+                COALESCE(SUM(output_tokens), 0),
+                COALESCE(SUM(cache_creation_input_tokens), 0),
+                COALESCE(SUM(cache_read_input_tokens), 0),
+                TOTAL(cost)
+            FROM token_usage
+            WHERE recorded_at >= ?
+        }
+    }
+
+    // The usage of each thread since the given unix timestamp, most expensive first.
+    query! {
+        pub fn totals_by_thread(since: i64) -> Result<Vec<UsageSummary>> {
+            SELECT
+                thread_id,
+                COUNT(*),
+                COUNT(*) - COUNT(cost),
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens),
+                TOTAL(cost) AS total_cost
+            FROM token_usage
+            WHERE recorded_at >= ?
+            GROUP BY thread_id
+            ORDER BY total_cost DESC, MAX(recorded_at) DESC
+        }
+    }
+
+    // The usage of each model since the given unix timestamp, most expensive first.
+    query! {
+        pub fn totals_by_model(since: i64) -> Result<Vec<UsageSummary>> {
+            SELECT
+                provider || '/' || model,
+                COUNT(*),
+                COUNT(*) - COUNT(cost),
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens),
+                TOTAL(cost) AS total_cost
+            FROM token_usage
+            WHERE recorded_at >= ?
+            GROUP BY provider, model
+            ORDER BY total_cost DESC
+        }
+    }
+
+    // The usage of each day since the given unix timestamp, oldest first. Days
+    // start at local midnight, given the local time zone's offset from UTC in seconds.
+    query! {
+        pub fn totals_by_day(since: i64, utc_offset: i64) -> Result<Vec<UsageSummary>> {
+            SELECT
+                (recorded_at + ?2) / 86400 AS day,
+                COUNT(*),
+                COUNT(*) - COUNT(cost),
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(cache_creation_input_tokens),
+                SUM(cache_read_input_tokens),
+                TOTAL(cost)
+            FROM token_usage
+            WHERE recorded_at >= ?1
+            GROUP BY day
+            ORDER BY day ASC
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_usage_summaries() {
+        let db = TokenUsageDb::open_test_db("test_usage_summaries").await;
+
+        let usage = |input_tokens, output_tokens| TokenUsage {
+            input_tokens,
+            output_tokens,
+            cache_creation_input_tokens: 10,
+            cache_read_input_tokens: 20,
+        };
+        db.record_usage(
+            "thread-1".into(),
+            "anthropic".into(),
+            "claude-sonnet-4".into(),
+            usage(100, 50),
+            Some(0.5),
+        )
+        .await
+        .unwrap();
+        db.record_usage(
+            "thread-1".into(),
+            "ollama".into(),
+            "llama3".into(),
+            usage(200, 100),
+            None,
+        )
+        .await
+        .unwrap();
+        db.record_usage(
+            "thread-2".into(),
+            "anthropic".into(),
+            "claude-sonnet-4".into(),
+            usage(300, 150),
+            Some(1.5),
+        )
+        .await
+        .unwrap();
+
+        let total = db.total_since(0).unwrap();
+        assert_eq!(total.requests, 3);
+        assert_eq!(total.unpriced_requests, 1);
+        assert_eq!(
+            total.usage,
+            TokenUsage {
+                input_tokens: 600,
+                output_tokens: 300,
+                cache_creation_input_tokens: 30,
+                cache_read_input_tokens: 60,
+            }
+        );
+        assert_eq!(total.cost, 2.0);
+
+        let threads = db.totals_by_thread(0).unwrap();
+        assert_eq!(
+            threads
+                .iter()
+                .map(|summary| (summary.key.as_str(), summary.requests, summary.cost))
+                .collect::<Vec<_>>(),
+            vec![("thread-2", 1, 1.5), ("thread-1", 2, 0.5)]
+        );
+
+        let models = db.totals_by_model(0).unwrap();
+        assert_eq!(
+            models
+                .iter()
+                .map(|summary| (summary.key.as_str(), summary.usage.input_tokens))
+                .collect::<Vec<_>>(),
+            vec![("anthropic/claude-sonnet-4", 400), ("ollama/llama3", 200)]
+        );
+
+        let days = db.totals_by_day(0, 0).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].requests, 3);
+
+        let future = db.total_since(i64::MAX).unwrap();
+        assert_eq!(future.requests, 0);
+        assert_eq!(future.usage, TokenUsage::default());
+        assert_eq!(future.cost, 0.0);
+    }
+}
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use anyhow::{Result, bail};
 use collections::IndexMap;
 use gpui::{App, Pixels, SharedString};
-use language_model::LanguageModel;
+use language_model::{LanguageModel, ModelPricing};
 use schemars::{JsonSchema, json_schema};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources, SettingsUi};
@@ -17,6 +17,8 @@ pub const SUMMARIZE_THREAD_PROMPT: &str =
     include_str!("../../agent/src/prompts/summarize_thread_prompt.txt");
 pub const SUMMARIZE_THREAD_DETAILED_PROMPT: &str =
     include_str!("../../agent/src/prompts/summarize_thread_detailed_prompt.txt");
+pub const COMPACT_THREAD_PROMPT: &str =
+    include_str!("../../agent/src/prompts/compact_thread_prompt.txt");
 
 pub fn init(cx: &mut App) {
     AgentSettings::register(cx);
@@ -62,6 +64,8 @@ pub struct AgentSettings {
     pub inline_alternatives: Vec<LanguageModelSelection>,
     pub using_outdated_settings_version: bool,
     pub default_profile: AgentProfileId,
+    pub delegate_profile: AgentProfileId,
+    pub delegate_token_budget: u64,
     pub default_view: DefaultView,
     pub profiles: IndexMap<AgentProfileId, AgentProfileSettings>,
     pub always_allow_tool_actions: bool,
@@ -75,6 +79,11 @@ pub struct AgentSettings {
     pub expand_edit_card: bool,
     pub expand_terminal_card: bool,
     pub use_modifier_to_send: bool,
+    pub model_pricing: Vec<ModelPricingSetting>,
+    pub usage_budget: UsageBudget,
+    pub auto_compact: bool,
+    pub auto_compact_threshold: f32,
+    pub compact_keep_recent_turns: usize,
 }
 
 impl AgentSettings {
@@ -87,6 +96,17 @@ impl AgentSettings {
             .and_then(|m| m.temperature)
     }
 
+    /// The price of the model's tokens, preferring the user's pricing over the model's list price.
+    pub fn pricing_for_model(model: &Arc<dyn LanguageModel>, cx: &App) -> Option<ModelPricing> {
+        let settings = Self::get_global(cx);
+        settings
+            .model_pricing
+            .iter()
+            .rfind(|setting| setting.matches(model))
+            .map(|setting| setting.pricing)
+            .or_else(|| model.pricing())
+    }
+
     pub fn set_inline_assistant_model(&mut self, provider: String, model: String) {
         self.inline_assistant_model = Some(LanguageModelSelection {
             provider: provider.into(),
@@ -132,6 +152,39 @@ impl LanguageModelParameters {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ModelPricingSetting {
+    pub provider: Option<LanguageModelProviderSetting>,
+    pub model: Option<SharedString>,
+    #[serde(flatten)]
+    pub pricing: ModelPricing,
+}
+
+impl ModelPricingSetting {
+    pub fn matches(&self, model: &Arc<dyn LanguageModel>) -> bool {
+        if let Some(provider) = &self.provider
+            && provider.0 != model.provider_id().0
+        {
+            return false;
+        }
+        if let Some(setting_model) = &self.model
+            && *setting_model != model.id().0
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Spending limits, in US dollars, past which the usage dashboard shows a warning.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct UsageBudget {
+    /// The maximum amount to spend per day.
+    pub daily: Option<f64>,
+    /// The maximum amount to spend per calendar month.
+    pub monthly: Option<f64>,
+}
+
 impl AgentSettingsContent {
     pub fn set_dock(&mut self, dock: AgentDockPosition) {
         self.dock = Some(dock);
@@ -259,6 +312,16 @@ pub struct AgentSettingsContent {
     ///
     /// Default: write
     default_profile: Option<AgentProfileId>,
+    /// The profile whose tools are available to sub-agents started with the
+    /// `delegate` tool.
+    ///
+    /// Default: ask
+    delegate_profile: Option<AgentProfileId>,
+    /// The number of tokens a sub-agent started with the `delegate` tool can
+    /// use, across all of its requests, before it's stopped.
+    ///
+    /// Default: 500000
+    delegate_token_budget: Option<u64>,
     /// Which view type to show by default in the agent panel.
     ///
     /// Default: "thread"
@@ -315,6 +378,34 @@ pub struct AgentSettingsContent {
     ///
     /// Default: false
     use_modifier_to_send: Option<bool>,
+    /// Token prices, in US dollars per million tokens, used to compute the
+    /// cost of agent requests. These take precedence over the list prices
+    /// built into Zed, and are required for models whose price Zed doesn't
+    /// know. Like `model_parameters`, the last matching entry wins.
+    ///
+    /// Default: []
+    #[serde(default)]
+    model_pricing: Vec<ModelPricingSetting>,
+    /// Daily and monthly spending limits, in US dollars. The usage dashboard
+    /// warns when the agent's spend exceeds them.
+    ///
+    /// Default: no limits
+    usage_budget: Option<UsageBudget>,
+    /// Whether to summarize the older messages of a thread once it approaches
+    /// the model's context window, so that the conversation can continue.
+    ///
+    /// Default: true
+    auto_compact: Option<bool>,
+    /// The fraction of the model's context window a thread can fill before
+    /// it's compacted.
+    ///
+    /// Default: 0.8
+    auto_compact_threshold: Option<f32>,
+    /// How many of the most recent turns to keep verbatim when compacting a
+    /// thread.
+    ///
+    /// Default: 2
+    compact_keep_recent_turns: Option<usize>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Default)]
@@ -456,6 +547,14 @@ impl Settings for AgentSettings {
             merge(&mut settings.stream_edits, value.stream_edits);
             merge(&mut settings.single_file_review, value.single_file_review);
             merge(&mut settings.default_profile, value.default_profile.clone());
+            merge(
+                &mut settings.delegate_profile,
+                value.delegate_profile.clone(),
+            );
+            merge(
+                &mut settings.delegate_token_budget,
+                value.delegate_token_budget,
+            );
             merge(&mut settings.default_view, value.default_view);
             merge(
                 &mut settings.preferred_completion_mode,
@@ -475,6 +574,19 @@ impl Settings for AgentSettings {
             settings
                 .model_parameters
                 .extend_from_slice(&value.model_parameters);
+            settings
+                .model_pricing
+                .extend_from_slice(&value.model_pricing);
+            merge(&mut settings.usage_budget, value.usage_budget);
+            merge(&mut settings.auto_compact, value.auto_compact);
+            merge(
+                &mut settings.auto_compact_threshold,
+                value.auto_compact_threshold,
+            );
+            merge(
+                &mut settings.compact_keep_recent_turns,
+                value.compact_keep_recent_turns,
+            );
 
             if let Some(profiles) = value.profiles.as_ref() {
                 settings
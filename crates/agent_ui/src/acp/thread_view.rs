@@ -65,8 +65,9 @@ use crate::ui::{
     AgentNotification, AgentNotificationEvent, BurnModeTooltip, UnavailableEditingTooltip,
 };
 use crate::{
-    AgentDiffPane, AgentPanel, ContinueThread, ContinueWithBurnMode, ExpandMessageEditor, Follow,
-    KeepAll, OpenAgentDiff, OpenHistory, RejectAll, ToggleBurnMode, ToggleProfileSelector,
+    AgentDiffPane, AgentPanel, CompactThread, ContinueThread, ContinueWithBurnMode,
+    ExpandMessageEditor, Follow, KeepAll, OpenAgentDiff, OpenHistory, RejectAll, ToggleBurnMode,
+    ToggleProfileSelector,
 };
 
 pub const MIN_EDITOR_LINES: usize = 4;
@@ -880,6 +881,28 @@ impl AcpThreadView {
         .detach();
     }
 
+    fn compact_thread(&mut self, _: &CompactThread, _window: &mut Window, cx: &mut Context<Self>) {
+        self.thread_error.take();
+        let Some(thread) = self.thread() else {
+            return;
+        };
+        if !thread.read(cx).can_compact(cx) {
+            return;
+        }
+
+        let task = thread.update(cx, |thread, cx| thread.compact(cx));
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+
+            this.update(cx, |this, cx| {
+                if let Err(err) = result {
+                    this.handle_thread_error(err, cx);
+                }
+            })
+        })
+        .detach();
+    }
+
     fn send(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(thread) = self.thread() else { return };
 
@@ -1711,6 +1734,7 @@ impl AcpThreadView {
             }
             AgentThreadEntry::ToolCall(tool_call) => {
                 let has_terminals = tool_call.terminals().next().is_some();
+                let has_subagents = tool_call.subagents().next().is_some();
 
                 div().w_full().map(|this| {
                     if has_terminals {
@@ -1719,6 +1743,12 @@ impl AcpThreadView {
                                 entry_ix, terminal, tool_call, window, cx,
                             )
                         }))
+                    } else if has_subagents {
+                        this.children(tool_call.subagents().map(|subagent| {
+                            self.render_subagent_tool_call(
+                                entry_ix, subagent, tool_call, window, cx,
+                            )
+                        }))
                     } else {
                         this.child(self.render_tool_call(entry_ix, tool_call, window, cx))
                     }
@@ -1899,17 +1929,7 @@ impl AcpThreadView {
                     .map(Icon::from_path)
                     .unwrap_or(Icon::new(IconName::ToolPencil))
             } else {
-                Icon::new(match tool_call.kind {
-                    acp::ToolKind::Read => IconName::ToolSearch,
-                    acp::ToolKind::Edit => IconName::ToolPencil,
-                    acp::ToolKind::Delete => IconName::ToolDeleteFile,
-                    acp::ToolKind::Move => IconName::ArrowRightLeft,
-                    acp::ToolKind::Search => IconName::ToolSearch,
-                    acp::ToolKind::Execute => IconName::ToolTerminal,
-                    acp::ToolKind::Think => IconName::ToolThink,
-                    acp::ToolKind::Fetch => IconName::ToolWeb,
-                    acp::ToolKind::Other => IconName::ToolHammer,
-                })
+                Icon::new(tool_kind_icon(tool_call.kind))
             }
             .size(IconSize::Small)
             .color(Color::Muted);
@@ -2162,6 +2182,9 @@ impl AcpThreadView {
             ToolCallContent::Terminal(terminal) => {
                 self.render_terminal_tool_call(entry_ix, terminal, tool_call, window, cx)
             }
+            ToolCallContent::Subagent(subagent) => {
+                self.render_subagent_tool_call(entry_ix, subagent, tool_call, window, cx)
+            }
         }
     }
 
@@ -2652,6 +2675,308 @@ impl AcpThreadView {
             .into_any()
     }
 
+    fn render_subagent_tool_call(
+        &self,
+        entry_ix: usize,
+        subagent: &Entity<acp_thread::Subagent>,
+        tool_call: &ToolCall,
+        window: &Window,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let subagent_data = subagent.read(cx);
+        // If the delegating tool call was canceled along with its turn, the
+        // sub-agent never got the chance to report that it stopped.
+        let tool_call_running = matches!(
+            tool_call.status,
+            ToolCallStatus::Pending
+                | ToolCallStatus::InProgress
+                | ToolCallStatus::WaitingForConfirmation { .. }
+        );
+        let is_running = subagent_data.is_running() && tool_call_running;
+        let status = subagent_data.status();
+        let tool_failed = matches!(
+            &tool_call.status,
+            ToolCallStatus::Rejected | ToolCallStatus::Canceled | ToolCallStatus::Failed
+        ) || matches!(
+            status,
+            acp_thread::SubagentStatus::Canceled | acp_thread::SubagentStatus::Failed
+        );
+
+        let time_elapsed = subagent_data
+            .ended_at()
+            .unwrap_or_else(Instant::now)
+            .duration_since(subagent_data.started_at());
+
+        let header_id =
+            SharedString::from(format!("subagent-tool-header-{}", subagent.entity_id()));
+        let header_group = SharedString::from(format!(
+            "subagent-tool-header-group-{}",
+            subagent.entity_id()
+        ));
+        let border_color = self.tool_card_border_color(cx);
+        let is_expanded = self.expanded_tool_calls.contains(&tool_call.id);
+
+        let header = h_flex()
+            .id(header_id)
+            .flex_none()
+            .gap_1p5()
+            .justify_between()
+            .child(
+                h_flex()
+                    .gap_1p5()
+                    .min_w_0()
+                    .child(
+                        Icon::new(IconName::ToolThink)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(subagent_data.label().clone())
+                            .size(LabelSize::Small)
+                            .truncate(),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1p5()
+                    .flex_none()
+                    .child(
+                        Label::new(format!(
+                            "{} / {} tokens",
+                            crate::text_thread_editor::humanize_token_count(
+                                subagent_data.used_tokens()
+                            ),
+                            crate::text_thread_editor::humanize_token_count(
+                                subagent_data.token_budget()
+                            ),
+                        ))
+                        .buffer_font(cx)
+                        .color(Color::Muted)
+                        .size(LabelSize::XSmall),
+                    )
+                    .when(time_elapsed > Duration::from_secs(10), |header| {
+                        header.child(
+                            Label::new(format!("({})", duration_alt_display(time_elapsed)))
+                                .buffer_font(cx)
+                                .color(Color::Muted)
+                                .size(LabelSize::XSmall),
+                        )
+                    })
+                    .when(is_running, |header| {
+                        header
+                            .child(
+                                Button::new(
+                                    SharedString::from(format!(
+                                        "stop-subagent-{}",
+                                        subagent.entity_id()
+                                    )),
+                                    if subagent_data.is_canceling() {
+                                        "Stopping…"
+                                    } else {
+                                        "Stop"
+                                    },
+                                )
+                                .icon(IconName::Stop)
+                                .icon_position(IconPosition::Start)
+                                .icon_size(IconSize::Small)
+                                .icon_color(Color::Error)
+                                .label_size(LabelSize::Small)
+                                .disabled(subagent_data.is_canceling())
+                                .tooltip(Tooltip::text("Stop This Sub-agent"))
+                                .on_click({
+                                    let subagent = subagent.clone();
+                                    cx.listener(move |_this, _event, _window, cx| {
+                                        subagent.update(cx, |subagent, cx| subagent.cancel(cx));
+                                    })
+                                }),
+                            )
+                            .child(Divider::vertical())
+                            .child(
+                                Icon::new(IconName::ArrowCircle)
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Info)
+                                    .with_animation(
+                                        "arrow-circle",
+                                        Animation::new(Duration::from_secs(2)).repeat(),
+                                        |icon, delta| {
+                                            icon.transform(Transformation::rotate(percentage(
+                                                delta,
+                                            )))
+                                        },
+                                    ),
+                            )
+                    })
+                    .when(
+                        !is_running && status == acp_thread::SubagentStatus::BudgetExhausted,
+                        |header| {
+                            header.child(
+                                div()
+                                    .id(("subagent-budget-indicator", subagent.entity_id()))
+                                    .child(
+                                        Icon::new(IconName::Warning)
+                                            .size(IconSize::Small)
+                                            .color(Color::Warning),
+                                    )
+                                    .tooltip(Tooltip::text(
+                                        "The sub-agent used up its token budget",
+                                    )),
+                            )
+                        },
+                    )
+                    .when(!is_running && tool_failed, |header| {
+                        header.child(
+                            Icon::new(IconName::Close)
+                                .size(IconSize::Small)
+                                .color(Color::Error),
+                        )
+                    })
+                    .child(
+                        Disclosure::new(
+                            SharedString::from(format!(
+                                "subagent-tool-disclosure-{}",
+                                subagent.entity_id()
+                            )),
+                            is_expanded,
+                        )
+                        .opened_icon(IconName::ChevronUp)
+                        .closed_icon(IconName::ChevronDown)
+                        .visible_on_hover(&header_group)
+                        .on_click(cx.listener({
+                            let id = tool_call.id.clone();
+                            move |this, _event, _window, _cx| {
+                                if is_expanded {
+                                    this.expanded_tool_calls.remove(&id);
+                                } else {
+                                    this.expanded_tool_calls.insert(id.clone());
+                                }
+                            }
+                        })),
+                    ),
+            );
+
+        let tool_calls = subagent_data.tool_calls();
+        let tool_call_list =
+            v_flex()
+                .py_1()
+                .px_2()
+                .gap_0p5()
+                .children(tool_calls.iter().enumerate().map(|(ix, child)| {
+                    let status_icon = match child.status {
+                        acp::ToolCallStatus::Pending | acp::ToolCallStatus::InProgress => {
+                            Icon::new(IconName::ArrowCircle)
+                                .size(IconSize::XSmall)
+                                .color(Color::Info)
+                                .with_animation(
+                                    ("subagent-tool-call-spinner", ix),
+                                    Animation::new(Duration::from_secs(2)).repeat(),
+                                    |icon, delta| {
+                                        icon.transform(Transformation::rotate(percentage(delta)))
+                                    },
+                                )
+                                .into_any_element()
+                        }
+                        acp::ToolCallStatus::Completed => Icon::new(IconName::Check)
+                            .size(IconSize::XSmall)
+                            .color(Color::Success)
+                            .into_any_element(),
+                        acp::ToolCallStatus::Failed => Icon::new(IconName::Close)
+                            .size(IconSize::XSmall)
+                            .color(Color::Error)
+                            .into_any_element(),
+                    };
+
+                    h_flex()
+                        .gap_1p5()
+                        .min_w_0()
+                        .child(
+                            Icon::new(tool_kind_icon(child.kind))
+                                .size(IconSize::XSmall)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            div().flex_1().min_w_0().child(
+                                Label::new(child.title.clone())
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted)
+                                    .truncate(),
+                            ),
+                        )
+                        .child(status_icon)
+                }));
+
+        let response = subagent_data.response().clone();
+        let show_response = is_expanded && !response.read(cx).source().is_empty();
+
+        v_flex()
+            .my_1p5()
+            .mx_5()
+            .border_1()
+            .when(tool_failed, |card| card.border_dashed())
+            .border_color(border_color)
+            .rounded_md()
+            .overflow_hidden()
+            .child(
+                v_flex()
+                    .group(&header_group)
+                    .py_1p5()
+                    .pr_1p5()
+                    .pl_2()
+                    .bg(self.tool_card_header_bg(cx))
+                    .child(header),
+            )
+            .when(!tool_calls.is_empty(), |card| {
+                card.child(
+                    div()
+                        .border_t_1()
+                        .border_color(border_color)
+                        .child(tool_call_list),
+                )
+            })
+            .when(show_response, |card| {
+                card.child(
+                    div()
+                        .p_2()
+                        .border_t_1()
+                        .border_color(border_color)
+                        .bg(cx.theme().colors().editor_background)
+                        .text_ui_sm(cx)
+                        .child(MarkdownElement::new(
+                            response,
+                            default_markdown_style(false, false, window, cx),
+                        )),
+                )
+            })
+            .when_some(
+                match &tool_call.status {
+                    ToolCallStatus::WaitingForConfirmation { options, .. } => Some(options),
+                    _ => None,
+                },
+                |card, options| {
+                    card.child(
+                        v_flex()
+                            .child(
+                                div()
+                                    .px_2()
+                                    .py_1()
+                                    .border_t_1()
+                                    .border_color(border_color)
+                                    .child(
+                                        Label::new(tool_call.label.read(cx).source().to_string())
+                                            .size(LabelSize::Small),
+                                    ),
+                            )
+                            .child(self.render_permission_buttons(
+                                options,
+                                entry_ix,
+                                tool_call.id.clone(),
+                                cx,
+                            )),
+                    )
+                },
+            )
+            .into_any()
+    }
+
     fn render_rules_item(&self, cx: &Context<Self>) -> Option<AnyElement> {
         let project_context = self
             .as_native_thread(cx)?
@@ -4548,10 +4873,17 @@ impl AcpThreadView {
                     .is_some_and(|model| model.supports_burn_mode())
         });
 
-        let description = if burn_mode_available {
-            "To continue, start a new thread from a summary or turn Burn Mode on."
-        } else {
-            "To continue, start a new thread from a summary."
+        let can_compact = self.thread()?.read(cx).can_compact(cx);
+
+        let description = match (can_compact, burn_mode_available) {
+            (true, true) => {
+                "To continue, compact the thread, start a new thread from a summary or turn Burn Mode on."
+            }
+            (true, false) => {
+                "To continue, compact the thread or start a new thread from a summary."
+            }
+            (false, true) => "To continue, start a new thread from a summary or turn Burn Mode on.",
+            (false, false) => "To continue, start a new thread from a summary.",
         };
 
         Some(
@@ -4563,6 +4895,18 @@ impl AcpThreadView {
                 .actions_slot(
                     h_flex()
                         .gap_0p5()
+                        .when(can_compact, |this| {
+                            this.child(
+                                Button::new("compact-thread", "Compact Thread")
+                                    .label_size(LabelSize::Small)
+                                    .tooltip(Tooltip::text(
+                                        "Summarize older messages to make room in the context window",
+                                    ))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.compact_thread(&CompactThread, window, cx);
+                                    })),
+                            )
+                        })
                         .child(
                             Button::new("start-new-thread", "Start New Thread")
                                 .label_size(LabelSize::Small)
@@ -5011,6 +5355,7 @@ impl Render for AcpThreadView {
             .key_context("AcpThread")
             .on_action(cx.listener(Self::open_agent_diff))
             .on_action(cx.listener(Self::toggle_burn_mode))
+            .on_action(cx.listener(Self::compact_thread))
             .on_action(cx.listener(Self::keep_all))
             .on_action(cx.listener(Self::reject_all))
             .track_focus(&self.focus_handle)
@@ -5087,6 +5432,20 @@ impl Render for AcpThreadView {
     }
 }
 
+fn tool_kind_icon(kind: acp::ToolKind) -> IconName {
+    match kind {
+        acp::ToolKind::Read => IconName::ToolSearch,
+        acp::ToolKind::Edit => IconName::ToolPencil,
+        acp::ToolKind::Delete => IconName::ToolDeleteFile,
+        acp::ToolKind::Move => IconName::ArrowRightLeft,
+        acp::ToolKind::Search => IconName::ToolSearch,
+        acp::ToolKind::Execute => IconName::ToolTerminal,
+        acp::ToolKind::Think => IconName::ToolThink,
+        acp::ToolKind::Fetch => IconName::ToolWeb,
+        acp::ToolKind::Other => IconName::ToolHammer,
+    }
+}
+
 fn default_markdown_style(
     buffer_font: bool,
     muted_text: bool,
@@ -2,6 +2,7 @@ mod add_llm_provider_modal;
 mod configure_context_server_modal;
 mod manage_profiles_modal;
 mod tool_picker;
+mod usage_dashboard;
 
 use std::{ops::Range, sync::Arc, time::Duration};
 
@@ -44,7 +45,10 @@ pub(crate) use manage_profiles_modal::ManageProfilesModal;
 
 use crate::{
     AddContextServer, ExternalAgent, NewExternalAgentThread,
-    agent_configuration::add_llm_provider_modal::{AddLlmProviderModal, LlmCompatibleProvider},
+    agent_configuration::{
+        add_llm_provider_modal::{AddLlmProviderModal, LlmCompatibleProvider},
+        usage_dashboard::UsageDashboard,
+    },
 };
 
 pub struct AgentConfiguration {
@@ -57,6 +61,7 @@ pub struct AgentConfiguration {
     expanded_context_server_tools: HashMap<ContextServerId, bool>,
     expanded_provider_configurations: HashMap<LanguageModelProviderId, bool>,
     tools: Entity<ToolWorkingSet>,
+    usage_dashboard: Entity<UsageDashboard>,
     _registry_subscription: Subscription,
     scroll_handle: ScrollHandle,
     scrollbar_state: ScrollbarState,
@@ -108,6 +113,7 @@ impl AgentConfiguration {
             expanded_context_server_tools: HashMap::default(),
             expanded_provider_configurations: HashMap::default(),
             tools,
+            usage_dashboard: cx.new(UsageDashboard::new),
             _registry_subscription: registry_subscription,
             scroll_handle,
             scrollbar_state,
@@ -491,6 +497,15 @@ impl AgentConfiguration {
             .child(self.render_modifier_to_send(cx))
     }
 
+    fn render_usage_section(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .p(DynamicSpacing::Base16.rems(cx))
+            .pr(DynamicSpacing::Base20.rems(cx))
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(self.usage_dashboard.clone())
+    }
+
     fn render_zed_plan_info(&self, plan: Option<Plan>, cx: &mut Context<Self>) -> impl IntoElement {
         if let Some(plan) = plan {
             let free_chip_bg = cx
@@ -1141,7 +1156,8 @@ impl Render for AgentConfiguration {
                     .child(self.render_general_settings_section(cx))
                     .child(self.render_agent_servers_section(cx))
                     .child(self.render_context_servers_section(window, cx))
-                    .child(self.render_provider_configuration_section(cx)),
+                    .child(self.render_provider_configuration_section(cx))
+                    .child(self.render_usage_section(cx)),
             )
             .child(
                 div()
@@ -0,0 +1,408 @@
+use agent_settings::{AgentSettings, UsageBudget};
+use agent2::{TOKEN_USAGE_DB, ThreadsDatabase, UsageSummary};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike as _, Days, Local, NaiveDate};
+use collections::HashMap;
+use gpui::{App, Context, Subscription, Task};
+use settings::{Settings as _, SettingsStore};
+use ui::{Callout, Tooltip, prelude::*};
+use util::ResultExt as _;
+
+use crate::text_thread_editor::humanize_token_count;
+
+/// The number of days shown in the daily chart.
+const CHART_DAYS: u64 = 30;
+const MAX_THREADS: usize = 10;
+const CHART_HEIGHT: f32 = 64.;
+/// The fraction of a budget past which the dashboard warns about it.
+const BUDGET_WARNING_THRESHOLD: f64 = 0.8;
+
+struct UsageReport {
+    today: UsageSummary,
+    month: UsageSummary,
+    days: Vec<(NaiveDate, UsageSummary)>,
+    models: Vec<UsageSummary>,
+    threads: Vec<(SharedString, UsageSummary)>,
+}
+
+/// Shows the agent's token usage and spend, as recorded in the usage database.
+pub struct UsageDashboard {
+    report: Option<UsageReport>,
+    budget: UsageBudget,
+    _load_report: Task<()>,
+    _settings_subscription: Subscription,
+}
+
+impl UsageDashboard {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let settings_subscription = cx.observe_global::<SettingsStore>(|this, cx| {
+            let budget = AgentSettings::get_global(cx).usage_budget;
+            if budget != this.budget {
+                this.budget = budget;
+                cx.notify();
+            }
+        });
+
+        let mut this = Self {
+            report: None,
+            budget: AgentSettings::get_global(cx).usage_budget,
+            _load_report: Task::ready(()),
+            _settings_subscription: settings_subscription,
+        };
+        this.refresh(cx);
+        this
+    }
+
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        let threads_database = ThreadsDatabase::connect(cx);
+        let report = cx.background_spawn(load_report(Local::now()));
+        self._load_report = cx.spawn(async move |this, cx| {
+            let Some(mut report) = report.await.log_err() else {
+                return;
+            };
+
+            let titles = match threads_database.await {
+                Ok(database) => database
+                    .list_threads()
+                    .await
+                    .log_err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|thread| (thread.id.to_string(), thread.title))
+                    .collect::<HashMap<_, _>>(),
+                Err(error) => {
+                    log::error!("failed to connect to the threads database: {error}");
+                    HashMap::default()
+                }
+            };
+            for (title, summary) in &mut report.threads {
+                if let Some(thread_title) = titles.get(&summary.key) {
+                    *title = thread_title.clone();
+                }
+            }
+
+            this.update(cx, |this, cx| {
+                this.report = Some(report);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn render_budget_warnings(&self, report: &UsageReport) -> impl IntoElement {
+        let warnings = [
+            ("today", "daily", self.budget.daily, report.today.cost),
+            (
+                "this month",
+                "monthly",
+                self.budget.monthly,
+                report.month.cost,
+            ),
+        ];
+
+        v_flex().gap_1().children(warnings.into_iter().filter_map(
+            |(period, name, budget, cost)| {
+                let budget = budget.filter(|budget| *budget > 0.)?;
+                let (severity, icon, title) = if cost >= budget {
+                    (
+                        Severity::Error,
+                        IconName::XCircle,
+                        format!("The agent's spend {period} has exceeded your {name} budget."),
+                    )
+                } else if cost >= budget * BUDGET_WARNING_THRESHOLD {
+                    (
+                        Severity::Warning,
+                        IconName::Warning,
+                        format!("The agent's spend {period} is approaching your {name} budget."),
+                    )
+                } else {
+                    return None;
+                };
+
+                Some(
+                    Callout::new()
+                        .severity(severity)
+                        .icon(icon)
+                        .title(title)
+                        .description(format!(
+                            "{} of {} spent.",
+                            format_cost(cost),
+                            format_cost(budget)
+                        )),
+                )
+            },
+        ))
+    }
+
+    fn render_period(
+        &self,
+        label: &'static str,
+        summary: &UsageSummary,
+        budget: Option<f64>,
+        cx: &App,
+    ) -> impl IntoElement {
+        let cost = match budget {
+            Some(budget) => format!("{} / {}", format_cost(summary.cost), format_cost(budget)),
+            None => format_cost(summary.cost),
+        };
+
+        v_flex()
+            .flex_1()
+            .p_2()
+            .gap_0p5()
+            .border_1()
+            .rounded_md()
+            .border_color(cx.theme().colors().border.opacity(0.6))
+            .bg(cx.theme().colors().background.opacity(0.25))
+            .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+            .child(Label::new(cost).size(LabelSize::Large))
+            .child(
+                Label::new(format!(
+                    "{} requests · {} tokens",
+                    summary.requests,
+                    humanize_token_count(total_tokens(summary))
+                ))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .when(summary.unpriced_requests > 0, |this| {
+                this.child(
+                    Label::new(format!(
+                        "{} requests to models without pricing aren't included",
+                        summary.unpriced_requests
+                    ))
+                    .size(LabelSize::XSmall)
+                    .color(Color::Muted),
+                )
+            })
+    }
+
+    fn render_daily_chart(&self, report: &UsageReport, cx: &App) -> impl IntoElement {
+        // Chart tokens instead of cost when none of the models used have a price.
+        let chart_cost = report.days.iter().any(|(_, summary)| summary.cost > 0.);
+        let value = |summary: &UsageSummary| {
+            if chart_cost {
+                summary.cost
+            } else {
+                total_tokens(summary) as f64
+            }
+        };
+        let max_value = report
+            .days
+            .iter()
+            .map(|(_, summary)| value(summary))
+            .fold(0., f64::max);
+
+        v_flex()
+            .gap_1()
+            .child(
+                Label::new(format!(
+                    "Daily {}, last {CHART_DAYS} days",
+                    if chart_cost { "spend" } else { "tokens" }
+                ))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(h_flex().h(px(CHART_HEIGHT)).items_end().gap_px().children(
+                report.days.iter().map(|(date, summary)| {
+                    let height = if max_value > 0. {
+                        (value(summary) / max_value) as f32 * CHART_HEIGHT
+                    } else {
+                        0.
+                    };
+                    let tooltip = format!(
+                        "{}: {}, {} tokens",
+                        date.format("%b %-d"),
+                        format_cost(summary.cost),
+                        humanize_token_count(total_tokens(summary))
+                    );
+
+                    div()
+                        .id(SharedString::from(format!("usage-day-{date}")))
+                        .flex_1()
+                        .h(px(CHART_HEIGHT))
+                        .flex()
+                        .items_end()
+                        .tooltip(Tooltip::text(tooltip))
+                        .child(div().w_full().h(px(height.max(1.))).rounded_t_sm().bg(
+                            if summary.requests > 0 {
+                                cx.theme().colors().text_accent
+                            } else {
+                                cx.theme().colors().border_variant
+                            },
+                        ))
+                }),
+            ))
+    }
+
+    fn render_table(
+        &self,
+        title: &'static str,
+        rows: impl IntoIterator<Item = (SharedString, UsageSummary)>,
+        cx: &App,
+    ) -> impl IntoElement {
+        v_flex()
+            .gap_1()
+            .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+            .children(rows.into_iter().map(|(name, summary)| {
+                h_flex()
+                    .py_0p5()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(name).size(LabelSize::Small).truncate())
+                    .child(
+                        h_flex()
+                            .flex_none()
+                            .gap_3()
+                            .child(
+                                Label::new(format!(
+                                    "{} in · {} out · {} cached",
+                                    humanize_token_count(summary.usage.input_tokens),
+                                    humanize_token_count(summary.usage.output_tokens),
+                                    humanize_token_count(
+                                        summary.usage.cache_creation_input_tokens
+                                            + summary.usage.cache_read_input_tokens
+                                    )
+                                ))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                            )
+                            .child(Label::new(format_cost(summary.cost)).size(LabelSize::Small)),
+                    )
+            }))
+    }
+}
+
+impl Render for UsageDashboard {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .w_full()
+            .gap_2()
+            .justify_between()
+            .child(
+                v_flex().gap_0p5().child(Headline::new("Usage")).child(
+                    Label::new("Tokens used and money spent by the agent on your API keys.")
+                        .color(Color::Muted),
+                ),
+            )
+            .child(
+                IconButton::new("refresh-usage", IconName::RotateCw)
+                    .icon_size(IconSize::Small)
+                    .icon_color(Color::Muted)
+                    .tooltip(Tooltip::text("Refresh Usage"))
+                    .on_click(cx.listener(|this, _, _window, cx| this.refresh(cx))),
+            );
+
+        let Some(report) = self.report.as_ref() else {
+            return v_flex()
+                .gap_2()
+                .child(header)
+                .child(Label::new("Loading usage…").color(Color::Muted))
+                .into_any_element();
+        };
+
+        if report.month.requests == 0 && report.days.iter().all(|(_, day)| day.requests == 0) {
+            return v_flex()
+                .gap_2()
+                .child(header)
+                .child(
+                    Label::new("The agent hasn't made any requests recently.").color(Color::Muted),
+                )
+                .into_any_element();
+        }
+
+        v_flex()
+            .gap_2p5()
+            .child(header)
+            .child(self.render_budget_warnings(report))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(self.render_period("Today", &report.today, self.budget.daily, cx))
+                    .child(self.render_period(
+                        "This Month",
+                        &report.month,
+                        self.budget.monthly,
+                        cx,
+                    )),
+            )
+            .child(self.render_daily_chart(report, cx))
+            .child(
+                self.render_table(
+                    "By Model, last 30 days",
+                    report
+                        .models
+                        .iter()
+                        .map(|summary| (summary.key.clone().into(), summary.clone())),
+                    cx,
+                ),
+            )
+            .child(self.render_table("Top Threads, last 30 days", report.threads.clone(), cx))
+            .into_any_element()
+    }
+}
+
+async fn load_report(now: DateTime<Local>) -> Result<UsageReport> {
+    let today = now.date_naive();
+    let chart_start = today
+        .checked_sub_days(Days::new(CHART_DAYS - 1))
+        .ok_or_else(|| anyhow!("invalid date"))?;
+    let month_start = today.with_day(1).ok_or_else(|| anyhow!("invalid date"))?;
+    let utc_offset = now.offset().local_minus_utc() as i64;
+
+    let db = &*TOKEN_USAGE_DB;
+    let chart_start_timestamp = local_midnight_timestamp(chart_start)?;
+    let days_by_number = db
+        .totals_by_day(chart_start_timestamp, utc_offset)?
+        .into_iter()
+        .filter_map(|summary| Some((summary.key.parse::<i64>().ok()?, summary)))
+        .collect::<HashMap<_, _>>();
+    let days = chart_start
+        .iter_days()
+        .take(CHART_DAYS as usize)
+        .map(|date| {
+            let day_number = date.signed_duration_since(NaiveDate::default()).num_days();
+            let summary = days_by_number.get(&day_number).cloned().unwrap_or_default();
+            (date, summary)
+        })
+        .collect();
+
+    let threads = db
+        .totals_by_thread(chart_start_timestamp)?
+        .into_iter()
+        .take(MAX_THREADS)
+        .map(|summary| ("Untitled Thread".into(), summary))
+        .collect();
+
+    Ok(UsageReport {
+        today: db.total_since(local_midnight_timestamp(today)?)?,
+        month: db.total_since(local_midnight_timestamp(month_start)?)?,
+        days,
+        models: db.totals_by_model(chart_start_timestamp)?,
+        threads,
+    })
+}
+
+fn local_midnight_timestamp(date: NaiveDate) -> Result<i64> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map(|midnight| midnight.timestamp())
+        .ok_or_else(|| anyhow!("no local midnight on {date}"))
+}
+
+fn total_tokens(summary: &UsageSummary) -> u64 {
+    summary.usage.input_tokens
+        + summary.usage.output_tokens
+        + summary.usage.cache_creation_input_tokens
+        + summary.usage.cache_read_input_tokens
+}
+
+fn format_cost(cost: f64) -> String {
+    if cost > 0. && cost < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("${cost:.2}")
+    }
+}
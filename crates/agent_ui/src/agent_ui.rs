@@ -126,6 +126,9 @@ actions!(
         ContinueWithBurnMode,
         /// Toggles burn mode for faster responses.
         ToggleBurnMode,
+        /// Summarizes the older messages of the current thread to make room in
+        /// the model's context window.
+        CompactThread,
     ]
 );
 
@@ -1,3 +1,4 @@
+mod code_navigation_tool;
 mod copy_path_tool;
 mod create_directory_tool;
 mod delete_path_tool;
@@ -13,12 +14,15 @@ mod now_tool;
 mod open_tool;
 mod project_notifications_tool;
 mod read_file_tool;
+mod rename_symbol_tool;
 mod schema;
+mod semantic_search_tool;
 pub mod templates;
 mod terminal_tool;
 mod thinking_tool;
 mod ui;
 mod web_search_tool;
+mod workspace_symbols_tool;
 
 use assistant_tool::ToolRegistry;
 use copy_path_tool::CopyPathTool;
@@ -31,6 +35,7 @@ use web_search_tool::WebSearchTool;
 
 pub(crate) use templates::*;
 
+use crate::code_navigation_tool::CodeNavigationTool;
 use crate::create_directory_tool::CreateDirectoryTool;
 use crate::delete_path_tool::DeletePathTool;
 use crate::diagnostics_tool::DiagnosticsTool;
@@ -38,7 +43,10 @@ use crate::edit_file_tool::EditFileTool;
 use crate::fetch_tool::FetchTool;
 use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
+use crate::rename_symbol_tool::RenameSymbolTool;
+use crate::semantic_search_tool::SemanticSearchTool;
 use crate::thinking_tool::ThinkingTool;
+use crate::workspace_symbols_tool::WorkspaceSymbolsTool;
 
 pub use edit_file_tool::{EditFileMode, EditFileToolInput};
 pub use find_path_tool::*;
@@ -68,6 +76,10 @@ pub fn init(http_client: Arc<HttpClientWithUrl>, cx: &mut App) {
     registry.register_tool(ThinkingTool);
     registry.register_tool(FetchTool::new(http_client));
     registry.register_tool(EditFileTool);
+    registry.register_tool(CodeNavigationTool);
+    registry.register_tool(WorkspaceSymbolsTool);
+    registry.register_tool(RenameSymbolTool);
+    registry.register_tool(SemanticSearchTool);
 
     register_web_search_tool(&LanguageModelRegistry::global(cx), cx);
     cx.subscribe(
@@ -0,0 +1,272 @@
+use crate::{code_navigation_tool::open_symbol_position, schema::json_schema_for};
+use action_log::ActionLog;
+use anyhow::{Result, anyhow};
+use assistant_tool::{Tool, ToolResult};
+use gpui::{AnyWindowHandle, App, Entity, Task};
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use ui::IconName;
+use util::markdown::MarkdownInlineCode;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbolToolInput {
+    /// The relative path of a file containing the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The line number the symbol appears on (1-based index).
+    pub line: u32,
+    /// The current name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+    /// The new name for the symbol.
+    pub new_name: String,
+}
+
+pub struct RenameSymbolTool;
+
+impl Tool for RenameSymbolTool {
+    fn name(&self) -> String {
+        "rename_symbol".into()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &Entity<Project>, _: &App) -> bool {
+        false
+    }
+
+    fn may_perform_edits(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> String {
+        include_str!("./rename_symbol_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::ToolPencil
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        json_schema_for::<RenameSymbolToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<RenameSymbolToolInput>(input.clone()) {
+            Ok(input) => format!(
+                "Rename {} to {}",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name)
+            ),
+            Err(_) => "Rename symbol".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _request: Arc<LanguageModelRequest>,
+        project: Entity<Project>,
+        action_log: Entity<ActionLog>,
+        _model: Arc<dyn LanguageModel>,
+        _window: Option<AnyWindowHandle>,
+        cx: &mut App,
+    ) -> ToolResult {
+        let input = match serde_json::from_value::<RenameSymbolToolInput>(input) {
+            Ok(input) => input,
+            Err(error) => return Task::ready(Err(anyhow!(error))).into(),
+        };
+        if input.new_name.trim().is_empty() {
+            return Task::ready(Err(anyhow!("The new name can't be empty"))).into();
+        }
+
+        cx.spawn(async move |cx| {
+            let (buffer, position) =
+                open_symbol_position(&project, &input.path, input.line, &input.symbol, cx).await?;
+            let project_transaction = project
+                .update(cx, |project, cx| {
+                    project.perform_rename(buffer, position, input.new_name.clone(), cx)
+                })?
+                .await?;
+
+            if project_transaction.0.is_empty() {
+                return Err(anyhow!(
+                    "The language server didn't produce any edits when renaming {}",
+                    MarkdownInlineCode(&input.symbol)
+                ));
+            }
+
+            let output = action_log.update(cx, |action_log, cx| {
+                let mut output = format!(
+                    "Renamed {} to {} in {} file(s):\n",
+                    MarkdownInlineCode(&input.symbol),
+                    MarkdownInlineCode(&input.new_name),
+                    project_transaction.0.len()
+                );
+                for (buffer, transaction) in &project_transaction.0 {
+                    if let Some(file) = buffer.read(cx).file() {
+                        writeln!(output, "- {}", file.full_path(cx).display())?;
+                    }
+                    action_log.buffer_edited_in_transaction(buffer.clone(), transaction, cx);
+                }
+                anyhow::Ok(output)
+            })??;
+            Ok(output.into())
+        })
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::{AppContext as _, TestAppContext};
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use language_model::fake_provider::FakeLanguageModel;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_rename_symbol_tracks_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "lib.rs": "pub fn old_name() {}\n",
+                "main.rs": "fn main() {\n    lib::old_name();\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )));
+        let mut fake_language_servers = language_registry.register_fake_lsp(
+            "Rust",
+            language::FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    rename_provider: Some(lsp::OneOf::Left(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let _handle = project.update(cx, |project, cx| {
+            project.register_buffer_with_language_servers(&buffer, cx)
+        });
+        let fake_language_server = fake_language_servers.next().await.unwrap();
+        fake_language_server.set_request_handler::<lsp::request::Rename, _, _>(
+            |params, _| async move {
+                assert_eq!(
+                    params.text_document_position.position,
+                    lsp::Position::new(1, 9)
+                );
+                assert_eq!(params.new_name, "new_name");
+                Ok(Some(lsp::WorkspaceEdit {
+                    changes: Some(
+                        [
+                            (
+                                lsp::Uri::from_file_path(path!("/root/lib.rs")).unwrap(),
+                                vec![lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(0, 7),
+                                        lsp::Position::new(0, 15),
+                                    ),
+                                    "new_name".into(),
+                                )],
+                            ),
+                            (
+                                lsp::Uri::from_file_path(path!("/root/main.rs")).unwrap(),
+                                vec![lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(1, 9),
+                                        lsp::Position::new(1, 17),
+                                    ),
+                                    "new_name".into(),
+                                )],
+                            ),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                }))
+            },
+        );
+
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let model = Arc::new(FakeLanguageModel::default());
+        let result = cx
+            .update(|cx| {
+                Arc::new(RenameSymbolTool)
+                    .run(
+                        json!({
+                            "path": "root/main.rs",
+                            "line": 2,
+                            "symbol": "old_name",
+                            "new_name": "new_name",
+                        }),
+                        Arc::default(),
+                        project.clone(),
+                        action_log.clone(),
+                        model,
+                        None,
+                        cx,
+                    )
+                    .output
+            })
+            .await
+            .unwrap();
+        assert!(result.content.as_str().unwrap().contains("in 2 file(s)"));
+
+        cx.run_until_parked();
+        let changed_texts = action_log.read_with(cx, |action_log, cx| {
+            let mut texts = action_log
+                .changed_buffers(cx)
+                .into_keys()
+                .map(|buffer| buffer.read(cx).text())
+                .collect::<Vec<_>>();
+            texts.sort();
+            texts
+        });
+        assert_eq!(
+            changed_texts,
+            vec![
+                "fn main() {\n    lib::new_name();\n}\n".to_string(),
+                "pub fn new_name() {}\n".to_string(),
+            ]
+        );
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
@@ -0,0 +1,129 @@
+use crate::schema::json_schema_for;
+use action_log::ActionLog;
+use anyhow::{Result, anyhow};
+use assistant_tool::{Tool, ToolResult};
+use gpui::{AnyWindowHandle, App, BorrowAppContext as _, Entity, Task};
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use semantic_index::{SemanticDb, Status};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use ui::IconName;
+use util::markdown::{MarkdownCodeBlock, MarkdownInlineCode};
+
+const MAX_RESULTS: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SemanticSearchToolInput {
+    /// A natural language description of the code to find.
+    pub query: String,
+}
+
+pub struct SemanticSearchTool;
+
+impl Tool for SemanticSearchTool {
+    fn name(&self) -> String {
+        "semantic_search".into()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &Entity<Project>, _: &App) -> bool {
+        false
+    }
+
+    fn may_perform_edits(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./semantic_search_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::ToolSearch
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        json_schema_for::<SemanticSearchToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<SemanticSearchToolInput>(input.clone()) {
+            Ok(input) => format!("Semantic search for {}", MarkdownInlineCode(&input.query)),
+            Err(_) => "Semantic search".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _request: Arc<LanguageModelRequest>,
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        _model: Arc<dyn LanguageModel>,
+        _window: Option<AnyWindowHandle>,
+        cx: &mut App,
+    ) -> ToolResult {
+        let input = match serde_json::from_value::<SemanticSearchToolInput>(input) {
+            Ok(input) => input,
+            Err(error) => return Task::ready(Err(anyhow!(error))).into(),
+        };
+
+        let project_index = cx.has_global::<SemanticDb>().then(|| {
+            cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+                semantic_db.project_index(project, cx)
+            })
+        });
+        let Some(project_index) = project_index.flatten() else {
+            return Task::ready(Err(anyhow!(
+                "The semantic index isn't enabled for this project. \
+                It can be enabled with the `semantic_index.enabled` setting."
+            )))
+            .into();
+        };
+
+        let (search, fs, status) = project_index.read_with(cx, |project_index, cx| {
+            (
+                project_index.search(vec![input.query.clone()], MAX_RESULTS, cx),
+                project_index.fs(),
+                project_index.status(),
+            )
+        });
+        cx.spawn(async move |cx| {
+            let results = search.await?;
+            let results = SemanticDb::load_results(results, &fs, cx).await?;
+
+            let mut output = String::new();
+            if let Status::Loading | Status::Scanning { .. } = status {
+                writeln!(
+                    output,
+                    "The project is still being indexed, so some relevant code may be missing.\n"
+                )?;
+            }
+            if results.is_empty() {
+                write!(
+                    output,
+                    "No results found for {}.",
+                    MarkdownInlineCode(&input.query)
+                )?;
+                return Ok(output.into());
+            }
+
+            for result in results {
+                writeln!(
+                    output,
+                    "## {}:{}-{}\n\n{}",
+                    result.full_path.display(),
+                    result.row_range.start() + 1,
+                    result.row_range.end() + 1,
+                    MarkdownCodeBlock {
+                        tag: &result.full_path.to_string_lossy(),
+                        text: &result.excerpt_content,
+                    }
+                )?;
+            }
+            Ok(output.into())
+        })
+        .into()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::schema::json_schema_for;
+use action_log::ActionLog;
+use anyhow::{Result, anyhow};
+use assistant_tool::{Tool, ToolResult};
+use gpui::{AnyWindowHandle, App, Entity, Task};
+use language_model::{LanguageModel, LanguageModelRequest, LanguageModelToolSchemaFormat};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, path::Path, sync::Arc};
+use ui::IconName;
+use util::markdown::MarkdownInlineCode;
+
+const MAX_SYMBOLS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSymbolsToolInput {
+    /// The name, or part of the name, of the symbol to search for.
+    pub query: String,
+}
+
+pub struct WorkspaceSymbolsTool;
+
+impl Tool for WorkspaceSymbolsTool {
+    fn name(&self) -> String {
+        "workspace_symbols".into()
+    }
+
+    fn needs_confirmation(&self, _: &serde_json::Value, _: &Entity<Project>, _: &App) -> bool {
+        false
+    }
+
+    fn may_perform_edits(&self) -> bool {
+        false
+    }
+
+    fn description(&self) -> String {
+        include_str!("./workspace_symbols_tool/description.md").into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::ToolSearch
+    }
+
+    fn input_schema(&self, format: LanguageModelToolSchemaFormat) -> Result<serde_json::Value> {
+        json_schema_for::<WorkspaceSymbolsToolInput>(format)
+    }
+
+    fn ui_text(&self, input: &serde_json::Value) -> String {
+        match serde_json::from_value::<WorkspaceSymbolsToolInput>(input.clone()) {
+            Ok(input) => format!("Search symbols for {}", MarkdownInlineCode(&input.query)),
+            Err(_) => "Search symbols".to_string(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        _request: Arc<LanguageModelRequest>,
+        project: Entity<Project>,
+        _action_log: Entity<ActionLog>,
+        _model: Arc<dyn LanguageModel>,
+        _window: Option<AnyWindowHandle>,
+        cx: &mut App,
+    ) -> ToolResult {
+        let input = match serde_json::from_value::<WorkspaceSymbolsToolInput>(input) {
+            Ok(input) => input,
+            Err(error) => return Task::ready(Err(anyhow!(error))).into(),
+        };
+
+        let symbols = project.update(cx, |project, cx| project.symbols(&input.query, cx));
+        cx.spawn(async move |cx| {
+            let symbols = symbols.await?;
+            if symbols.is_empty() {
+                return Ok(format!(
+                    "No symbols found matching {}.",
+                    MarkdownInlineCode(&input.query)
+                )
+                .into());
+            }
+
+            let output = project.read_with(cx, |project, cx| {
+                let mut output = String::new();
+                for symbol in symbols.iter().take(MAX_SYMBOLS) {
+                    let Some(worktree) = project.worktree_for_id(symbol.path.worktree_id, cx)
+                    else {
+                        continue;
+                    };
+                    let path = Path::new(worktree.read(cx).root_name()).join(&symbol.path.path);
+                    writeln!(
+                        output,
+                        "{:?} `{}` at {}:{}",
+                        symbol.kind,
+                        symbol.name,
+                        path.display(),
+                        symbol.range.start.0.row + 1
+                    )?;
+                }
+                if symbols.len() > MAX_SYMBOLS {
+                    writeln!(
+                        output,
+                        "\n{} more symbols were omitted. Use a more specific query.",
+                        symbols.len() - MAX_SYMBOLS
+                    )?;
+                }
+                anyhow::Ok(output)
+            })??;
+            Ok(output.into())
+        })
+        .into()
+    }
+}
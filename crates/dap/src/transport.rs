@@ -75,13 +75,25 @@ pub trait Transport: Send + Sync {
     }
 }
 
+#[cfg(any(test, feature = "test-support"))]
+thread_local! {
+    static USE_REAL_ADAPTERS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Makes the debug sessions that the current test starts run their adapter's binary instead of
+/// talking to a fake adapter, for end-to-end tests against debuggers installed on the machine.
+#[cfg(any(test, feature = "test-support"))]
+pub fn use_real_adapters_in_tests() {
+    USE_REAL_ADAPTERS.with(|use_real_adapters| use_real_adapters.set(true));
+}
+
 async fn start(
     binary: &DebugAdapterBinary,
     log_handlers: LogHandlers,
     cx: &mut AsyncApp,
 ) -> Result<Box<dyn Transport>> {
     #[cfg(any(test, feature = "test-support"))]
-    if cfg!(any(test, feature = "test-support")) {
+    if !USE_REAL_ADAPTERS.with(|use_real_adapters| use_real_adapters.get()) {
         if let Some(connection) = binary.connection.clone() {
             return Ok(Box::new(FakeTransport::start_tcp(connection, cx).await?));
         } else {
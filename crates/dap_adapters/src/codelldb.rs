@@ -36,6 +36,20 @@ impl CodeLldbDebugAdapter {
         obj.entry("cwd")
             .or_insert(delegate.worktree_root_path().to_string_lossy().into());
 
+        // CodeLLDB has no dedicated core dump option; load the core through the target creation
+        // commands and skip process creation entirely.
+        if let Some(core_file) = obj.get("coreFile").and_then(Value::as_str)
+            && !obj.contains_key("targetCreateCommands")
+        {
+            let mut command = format!("target create --core {}", lldb_quote(core_file));
+            if let Some(program) = obj.get("program").and_then(Value::as_str) {
+                command.push(' ');
+                command.push_str(&lldb_quote(program));
+            }
+            obj.insert("targetCreateCommands".into(), json!([command]));
+            obj.entry("processCreateCommands").or_insert(json!([]));
+        }
+
         let request = self.request_kind(&configuration).await?;
 
         Ok(dap::StartDebuggingRequestArguments {
@@ -114,7 +128,12 @@ impl DebugAdapter for CodeLldbDebugAdapter {
                 if !launch.env.is_empty() {
                     map.insert("env".into(), launch.env_json());
                 }
-                if let Some(stop_on_entry) = zed_scenario.stop_on_entry {
+                if let Some(core_file) = launch.core_file.as_ref() {
+                    map.insert(
+                        "coreFile".into(),
+                        core_file.to_string_lossy().into_owned().into(),
+                    );
+                } else if let Some(stop_on_entry) = zed_scenario.stop_on_entry {
                     map.insert("stopOnEntry".into(), stop_on_entry.into());
                 }
                 if let Some(cwd) = launch.cwd.as_ref() {
@@ -273,6 +292,10 @@ impl DebugAdapter for CodeLldbDebugAdapter {
                     "type": ["integer", "string"],
                     "description": "Process id to attach to"
                 },
+                "coreFile": {
+                    "type": "string",
+                    "description": "Core dump of the program to inspect instead of launching it. The session is read-only."
+                },
                 "waitFor": {
                     "type": "boolean",
                     "description": "Wait for the process to launch (MacOS only)",
@@ -406,3 +429,11 @@ impl DebugAdapter for CodeLldbDebugAdapter {
         })
     }
 }
+
+/// Quotes an argument for the LLDB command interpreter.
+fn lldb_quote(argument: &str) -> String {
+    format!(
+        "\"{}\"",
+        argument.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
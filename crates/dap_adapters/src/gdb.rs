@@ -30,6 +30,21 @@ impl DebugAdapter for GdbDebugAdapter {
                 obj.insert("pid".into(), attach.process_id.into());
             }
 
+            dap::DebugRequest::Launch(launch) if launch.core_file.is_some() => {
+                // GDB loads core files as an attach target, there is no process to launch.
+                obj.insert("request".into(), "attach".into());
+                obj.insert("program".into(), launch.program.clone().into());
+                if let Some(core_file) = launch.core_file.as_ref() {
+                    obj.insert(
+                        "coreFile".into(),
+                        core_file.to_string_lossy().into_owned().into(),
+                    );
+                }
+                if let Some(cwd) = launch.cwd.as_ref() {
+                    obj.insert("cwd".into(), cwd.to_string_lossy().into_owned().into());
+                }
+            }
+
             dap::DebugRequest::Launch(launch) => {
                 obj.insert("request".into(), "launch".into());
                 obj.insert("program".into(), launch.program.clone().into());
@@ -144,9 +159,17 @@ impl DebugAdapter for GdbDebugAdapter {
                                 "target": {
                                     "type": "string",
                                     "description": "The target to which GDB should connect. This is passed to the 'target remote' command."
+                                },
+                                "coreFile": {
+                                    "type": "string",
+                                    "description": "A core dump of the program to inspect instead of attaching to a live process. This corresponds to the GDB 'core-file' command. The session is read-only."
                                 }
                             },
-                            "required": ["pid"]
+                            "oneOf": [
+                                { "required": ["pid"] },
+                                { "required": ["target"] },
+                                { "required": ["coreFile"] }
+                            ]
                         }
                     ]
                 }
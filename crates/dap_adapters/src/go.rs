@@ -362,6 +362,12 @@ impl DebugAdapter for GoDebugAdapter {
     }
 
     async fn config_from_zed_format(&self, zed_scenario: ZedDebugConfig) -> Result<DebugScenario> {
+        if let dap::DebugRequest::Launch(launch) = &zed_scenario.request
+            && launch.core_file.is_some()
+        {
+            bail!("{} cannot debug core dumps", Self::ADAPTER_NAME);
+        }
+
         let mut args = match &zed_scenario.request {
             dap::DebugRequest::Attach(attach_config) => {
                 json!({
@@ -180,6 +180,12 @@ impl DebugAdapter for JsDebugAdapter {
     }
 
     async fn config_from_zed_format(&self, zed_scenario: ZedDebugConfig) -> Result<DebugScenario> {
+        if let DebugRequest::Launch(launch) = &zed_scenario.request
+            && launch.core_file.is_some()
+        {
+            anyhow::bail!("{} cannot debug core dumps", Self::ADAPTER_NAME);
+        }
+
         let mut args = json!({
             "type": "pwa-node",
             "request": match zed_scenario.request {
@@ -44,6 +44,7 @@ pub(super) struct NewProcessModal {
     debug_picker: Entity<Picker<DebugDelegate>>,
     attach_mode: Entity<AttachMode>,
     configure_mode: Entity<ConfigureMode>,
+    core_dump_mode: Entity<CoreDumpMode>,
     task_mode: TaskMode,
     debugger: Option<DebugAdapterName>,
     _subscriptions: [Subscription; 3],
@@ -51,6 +52,15 @@ pub(super) struct NewProcessModal {
 
 fn suggested_label(request: &DebugRequest, debugger: &str) -> SharedString {
     match request {
+        DebugRequest::Launch(config) if config.core_file.is_some() => {
+            let core_file = config.core_file.as_deref().unwrap_or(Path::new(""));
+            let file_name = core_file
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_else(|| core_file.to_string_lossy());
+
+            format!("core: {file_name} ({debugger})").into()
+        }
         DebugRequest::Launch(config) => {
             let last_path_component = Path::new(&config.program)
                 .file_name()
@@ -98,6 +108,7 @@ impl NewProcessModal {
                     });
 
                     let configure_mode = ConfigureMode::new(window, cx);
+                    let core_dump_mode = CoreDumpMode::new(window, cx);
 
                     let task_overrides = Some(TaskOverrides { reveal_target });
 
@@ -133,6 +144,7 @@ impl NewProcessModal {
                     cx.spawn_in(window, {
                         let debug_picker = debug_picker.downgrade();
                         let configure_mode = configure_mode.downgrade();
+                        let core_dump_mode = core_dump_mode.downgrade();
                         let task_modal = task_mode.task_modal.downgrade();
                         let workspace = workspace_handle.clone();
 
@@ -219,7 +231,12 @@ impl NewProcessModal {
                             {
                                 configure_mode
                                     .update_in(cx, |configure_mode, window, cx| {
-                                        configure_mode.load(active_cwd, window, cx);
+                                        configure_mode.load(active_cwd.clone(), window, cx);
+                                    })
+                                    .ok();
+                                core_dump_mode
+                                    .update_in(cx, |core_dump_mode, window, cx| {
+                                        core_dump_mode.load(active_cwd, window, cx);
                                     })
                                     .ok();
                             }
@@ -252,6 +269,7 @@ impl NewProcessModal {
                         debug_picker,
                         attach_mode,
                         configure_mode,
+                        core_dump_mode,
                         task_mode,
                         debugger: None,
                         mode,
@@ -283,6 +301,9 @@ impl NewProcessModal {
             NewProcessMode::Launch => self.configure_mode.update(cx, |this, cx| {
                 this.clone().render(dap_menu, window, cx).into_any_element()
             }),
+            NewProcessMode::CoreDump => self.core_dump_mode.update(cx, |this, cx| {
+                this.clone().render(dap_menu, window, cx).into_any_element()
+            }),
             NewProcessMode::Debug => v_flex()
                 .w(rems(34.))
                 .child(self.debug_picker.clone())
@@ -295,6 +316,7 @@ impl NewProcessModal {
             NewProcessMode::Task => self.task_mode.task_modal.focus_handle(cx),
             NewProcessMode::Attach => self.attach_mode.read(cx).attach_picker.focus_handle(cx),
             NewProcessMode::Launch => self.configure_mode.read(cx).program.focus_handle(cx),
+            NewProcessMode::CoreDump => self.core_dump_mode.read(cx).program.focus_handle(cx),
             NewProcessMode::Debug => self.debug_picker.focus_handle(cx),
         }
     }
@@ -307,6 +329,9 @@ impl NewProcessModal {
             NewProcessMode::Attach => {
                 DebugRequest::Attach(self.attach_mode.read(cx).debug_request())
             }
+            NewProcessMode::CoreDump => {
+                DebugRequest::Launch(self.core_dump_mode.read(cx).debug_request(cx))
+            }
             _ => return Task::ready(None),
         };
         let label = suggested_label(&request, debugger);
@@ -507,6 +532,7 @@ pub(crate) enum NewProcessMode {
     Task,
     Launch,
     Attach,
+    CoreDump,
     Debug,
 }
 
@@ -517,6 +543,7 @@ impl std::fmt::Display for NewProcessMode {
             NewProcessMode::Debug => "Debug",
             NewProcessMode::Attach => "Attach",
             NewProcessMode::Launch => "Launch",
+            NewProcessMode::CoreDump => "Core Dump",
         };
 
         write!(f, "{}", mode)
@@ -592,7 +619,8 @@ impl Render for NewProcessModal {
                     NewProcessMode::Task => NewProcessMode::Debug,
                     NewProcessMode::Debug => NewProcessMode::Attach,
                     NewProcessMode::Attach => NewProcessMode::Launch,
-                    NewProcessMode::Launch => NewProcessMode::Task,
+                    NewProcessMode::Launch => NewProcessMode::CoreDump,
+                    NewProcessMode::CoreDump => NewProcessMode::Task,
                 };
 
                 this.mode_focus_handle(cx).focus(window);
@@ -600,10 +628,11 @@ impl Render for NewProcessModal {
             .on_action(
                 cx.listener(|this, _: &pane::ActivatePreviousItem, window, cx| {
                     this.mode = match this.mode {
-                        NewProcessMode::Task => NewProcessMode::Launch,
+                        NewProcessMode::Task => NewProcessMode::CoreDump,
                         NewProcessMode::Debug => NewProcessMode::Task,
                         NewProcessMode::Attach => NewProcessMode::Debug,
                         NewProcessMode::Launch => NewProcessMode::Attach,
+                        NewProcessMode::CoreDump => NewProcessMode::Launch,
                     };
 
                     this.mode_focus_handle(cx).focus(window);
@@ -681,6 +710,22 @@ impl Render for NewProcessModal {
                             cx.notify();
                         }))
                         .tooltip(Tooltip::text("Launch a new process with a debugger"))
+                        .middle(),
+                    )
+                    .child(
+                        ToggleButton::new(
+                            "debugger-session-ui-core-dump-button",
+                            NewProcessMode::CoreDump.to_string(),
+                        )
+                        .size(ButtonSize::Default)
+                        .toggle_state(matches!(self.mode, NewProcessMode::CoreDump))
+                        .style(ui::ButtonStyle::Subtle)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.mode = NewProcessMode::CoreDump;
+                            this.mode_focus_handle(cx).focus(window);
+                            cx.notify();
+                        }))
+                        .tooltip(Tooltip::text("Inspect a core dump of a crashed program"))
                         .last(),
                     ),
             )
@@ -764,6 +809,29 @@ impl Render for NewProcessModal {
                                     .child(div().child(self.adapter_drop_down_menu(window, cx))),
                             )
                     }),
+                    NewProcessMode::CoreDump => {
+                        let disabled = self.debugger.is_none()
+                            || !self.core_dump_mode.read(cx).is_complete(cx);
+                        el.child(
+                            container
+                                .child(
+                                    h_flex().child(
+                                        Button::new("edit-core-dump-debug", "Edit in debug.json")
+                                            .on_click(cx.listener(|this, _, window, cx| {
+                                                this.save_debug_scenario(window, cx);
+                                            }))
+                                            .disabled(disabled),
+                                    ),
+                                )
+                                .child(
+                                    Button::new("debugger-open-core-dump", "Open")
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.start_new_session(window, cx)
+                                        }))
+                                        .disabled(disabled),
+                                ),
+                        )
+                    }
                     NewProcessMode::Debug => el,
                     NewProcessMode::Task => el,
                 }
@@ -839,6 +907,7 @@ impl ConfigureMode {
                 cwd,
                 args: Default::default(),
                 env: Default::default(),
+                core_file: None,
             };
         }
         let command = self.program.read(cx).text(cx);
@@ -864,6 +933,7 @@ impl ConfigureMode {
             cwd,
             args,
             env,
+            core_file: None,
         }
     }
 
@@ -930,6 +1000,120 @@ impl ConfigureMode {
     }
 }
 
+#[derive(Clone)]
+pub(super) struct CoreDumpMode {
+    program: Entity<Editor>,
+    core_file: Entity<Editor>,
+    cwd: Entity<Editor>,
+}
+
+impl CoreDumpMode {
+    pub(super) fn new(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let program = cx.new(|cx| Editor::single_line(window, cx));
+        program.update(cx, |this, cx| {
+            this.set_placeholder_text("Ex: $ZED_WORKTREE_ROOT/target/debug/program", cx);
+        });
+
+        let core_file = cx.new(|cx| Editor::single_line(window, cx));
+        core_file.update(cx, |this, cx| {
+            this.set_placeholder_text("Ex: $ZED_WORKTREE_ROOT/core", cx);
+        });
+
+        let cwd = cx.new(|cx| Editor::single_line(window, cx));
+        cwd.update(cx, |this, cx| {
+            this.set_placeholder_text("Ex: $ZED_WORKTREE_ROOT", cx);
+        });
+
+        cx.new(|_| Self {
+            program,
+            core_file,
+            cwd,
+        })
+    }
+
+    fn load(&mut self, cwd: PathBuf, window: &mut Window, cx: &mut App) {
+        self.cwd.update(cx, |editor, cx| {
+            if editor.is_empty(cx) {
+                editor.set_text(cwd.to_string_lossy(), window, cx);
+            }
+        });
+    }
+
+    fn is_complete(&self, cx: &App) -> bool {
+        !self.program.read(cx).is_empty(cx) && !self.core_file.read(cx).is_empty(cx)
+    }
+
+    pub(super) fn debug_request(&self, cx: &App) -> task::LaunchRequest {
+        let cwd_text = self.cwd.read(cx).text(cx);
+        let cwd = if cwd_text.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(cwd_text))
+        };
+
+        task::LaunchRequest {
+            program: self.program.read(cx).text(cx),
+            cwd,
+            args: Default::default(),
+            env: Default::default(),
+            core_file: Some(PathBuf::from(self.core_file.read(cx).text(cx))),
+        }
+    }
+
+    fn render(
+        &mut self,
+        adapter_menu: DropdownMenu,
+        window: &mut Window,
+        cx: &mut ui::Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .p_2()
+            .w_full()
+            .gap_2()
+            .track_focus(&self.program.focus_handle(cx))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new("Debugger")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(adapter_menu),
+            )
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(
+                        Label::new("Executable")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(render_editor(&self.program, window, cx)),
+            )
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(
+                        Label::new("Core File")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(render_editor(&self.core_file, window, cx)),
+            )
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(
+                        Label::new("Working Directory")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(render_editor(&self.cwd, window, cx)),
+            )
+    }
+}
+
 #[derive(Clone)]
 pub(super) struct AttachMode {
     pub(super) definition: ZedDebugConfig,
@@ -0,0 +1,531 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use collections::{HashMap, HashSet};
+use dap::{DisassembledInstruction, StackFrameId};
+use gpui::{
+    Action as _, AnyElement, Entity, FocusHandle, Focusable, MouseButton, ScrollStrategy, Stateful,
+    Subscription, UniformListScrollHandle, WeakEntity, uniform_list,
+};
+use language::{BufferSnapshot, Point};
+use project::debugger::session::{Session, SessionEvent};
+use ui::{Scrollbar, ScrollbarState, Tooltip, prelude::*};
+use util::ResultExt;
+use workspace::Workspace;
+
+use crate::{
+    StepIntoInstruction, StepOverInstruction,
+    session::running::stack_frame_list::{StackFrameList, StackFrameListEvent},
+};
+
+/// How many instructions to disassemble on either side of the instruction pointer.
+const INSTRUCTION_WINDOW: u64 = 64;
+
+#[derive(Debug, Clone)]
+enum DisassemblyEntry {
+    /// A source line that the following instructions were generated from.
+    Source {
+        path: Option<Arc<Path>>,
+        name: SharedString,
+        line: u64,
+    },
+    Instruction(DisassembledInstruction),
+}
+
+pub(crate) struct DisassemblyView {
+    session: Entity<Session>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    scrollbar_state: ScrollbarState,
+    selected_stack_frame_id: Option<StackFrameId>,
+    instruction_pointer: Option<u64>,
+    entries: Vec<DisassemblyEntry>,
+    /// Contents of the source files interleaved with the instructions.
+    source_snapshots: HashMap<Arc<Path>, BufferSnapshot>,
+    requested_sources: HashSet<Arc<Path>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DisassemblyView {
+    pub(crate) fn new(
+        session: Entity<Session>,
+        workspace: WeakEntity<Workspace>,
+        stack_frame_list: Entity<StackFrameList>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let scroll_handle = UniformListScrollHandle::new();
+
+        let _subscriptions = vec![
+            cx.subscribe(&stack_frame_list, |this, _, event, cx| match event {
+                StackFrameListEvent::SelectedStackFrameChanged(stack_frame_id) => {
+                    this.selected_stack_frame_id = Some(*stack_frame_id);
+                    this.rebuild(true, cx);
+                }
+                StackFrameListEvent::BuiltEntries => {}
+            }),
+            cx.subscribe(&session, |this, _, event, cx| match event {
+                SessionEvent::Disassembly => this.rebuild(false, cx),
+                _ => {}
+            }),
+        ];
+
+        let selected_stack_frame_id = stack_frame_list.read(cx).opened_stack_frame_id();
+        let mut this = Self {
+            session,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            scrollbar_state: ScrollbarState::new(scroll_handle.clone()),
+            scroll_handle,
+            selected_stack_frame_id,
+            instruction_pointer: None,
+            entries: Vec::new(),
+            source_snapshots: HashMap::default(),
+            requested_sources: HashSet::default(),
+            _subscriptions,
+        };
+        this.rebuild(true, cx);
+        this
+    }
+
+    fn rebuild(&mut self, scroll_to_instruction_pointer: bool, cx: &mut Context<Self>) {
+        let instruction_pointer_reference = self.selected_stack_frame_id.and_then(|id| {
+            self.session
+                .read(cx)
+                .stack_frame(id)
+                .and_then(|stack_frame| stack_frame.dap.instruction_pointer_reference.clone())
+        });
+        let Some(instruction_pointer_reference) = instruction_pointer_reference else {
+            self.instruction_pointer = None;
+            self.entries.clear();
+            cx.notify();
+            return;
+        };
+
+        self.instruction_pointer = parse_address(&instruction_pointer_reference);
+        let Some(instructions) = self.session.update(cx, |session, cx| {
+            session.disassemble(
+                instruction_pointer_reference,
+                -(INSTRUCTION_WINDOW as i64),
+                INSTRUCTION_WINDOW * 2,
+                cx,
+            )
+        }) else {
+            cx.notify();
+            return;
+        };
+
+        self.entries = interleave_source(&instructions);
+        self.load_sources(cx);
+
+        if scroll_to_instruction_pointer
+            && let Some(ix) = self.entries.iter().position(|entry| {
+                matches!(entry, DisassemblyEntry::Instruction(instruction)
+                    if parse_address(&instruction.address) == self.instruction_pointer)
+            })
+        {
+            self.scroll_handle
+                .scroll_to_item(ix, ScrollStrategy::Center);
+        }
+        cx.notify();
+    }
+
+    fn load_sources(&mut self, cx: &mut Context<Self>) {
+        let paths = self
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                DisassemblyEntry::Source {
+                    path: Some(path), ..
+                } if !self.requested_sources.contains(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        if paths.is_empty() {
+            return;
+        }
+        self.requested_sources.extend(paths.iter().cloned());
+
+        let Some(project) = self
+            .workspace
+            .read_with(cx, |workspace, _| workspace.project().clone())
+            .log_err()
+        else {
+            return;
+        };
+
+        let buffers = paths
+            .into_iter()
+            .map(|path| {
+                let buffer = project.update(cx, |project, cx| project.open_local_buffer(&path, cx));
+                (path, buffer)
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(async move |this, cx| {
+            for (path, buffer) in buffers {
+                let Some(buffer) = buffer.await.log_err() else {
+                    continue;
+                };
+                let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
+                this.update(cx, |this, cx| {
+                    this.source_snapshots.insert(path, snapshot);
+                    cx.notify();
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn source_line_text(&self, path: &Arc<Path>, line: u64) -> Option<SharedString> {
+        let snapshot = self.source_snapshots.get(path)?;
+        let row = u32::try_from(line).ok()?.checked_sub(1)?;
+        if row > snapshot.max_point().row {
+            return None;
+        }
+        let text = snapshot
+            .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+            .collect::<String>();
+        Some(text.trim().to_string().into())
+    }
+
+    fn toggle_instruction_breakpoint(&mut self, address: &str, cx: &mut Context<Self>) {
+        self.session.update(cx, |session, cx| {
+            session.toggle_instruction_breakpoint(address, cx);
+        });
+        cx.notify();
+    }
+
+    fn open_source(
+        &mut self,
+        path: Arc<Path>,
+        line: u64,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self
+            .workspace
+            .read_with(cx, |workspace, _| workspace.project().clone())
+            .log_err()
+        else {
+            return;
+        };
+        let buffer = project.update(cx, |project, cx| project.open_local_buffer(&path, cx));
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let buffer = buffer.await?;
+            let row = line.saturating_sub(1) as u32;
+            workspace.update_in(cx, |workspace, window, cx| {
+                let editor = workspace.open_project_item::<editor::Editor>(
+                    workspace.active_pane().clone(),
+                    buffer,
+                    true,
+                    true,
+                    window,
+                    cx,
+                );
+                editor.update(cx, |editor, cx| {
+                    editor.go_to_singleton_buffer_point(Point::new(row, 0), window, cx);
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_entry(&mut self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        match self.entries[ix].clone() {
+            DisassemblyEntry::Source { path, name, line } => {
+                let text = path
+                    .as_ref()
+                    .and_then(|path| self.source_line_text(path, line));
+                h_flex()
+                    .id(("disassembly-source", ix))
+                    .w_full()
+                    .px_1()
+                    .gap_2()
+                    .text_ui_xs(cx)
+                    .text_color(cx.theme().colors().text_muted)
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .child(
+                        Label::new(format!("{name}:{line}"))
+                            .size(LabelSize::XSmall)
+                            .color(Color::Accent),
+                    )
+                    .when_some(text, |this, text| {
+                        this.child(Label::new(text).buffer_font(cx).size(LabelSize::Small))
+                    })
+                    .when_some(path, |this, path| {
+                        this.cursor_pointer()
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.open_source(path.clone(), line, window, cx);
+                            }))
+                    })
+                    .into_any_element()
+            }
+            DisassemblyEntry::Instruction(instruction) => {
+                let address = instruction.address.clone();
+                let is_current = self.instruction_pointer.is_some()
+                    && parse_address(&address) == self.instruction_pointer;
+                let breakpoint = self
+                    .session
+                    .read(cx)
+                    .instruction_breakpoints()
+                    .find(|state| state.dap.instruction_reference == address)
+                    .map(|state| state.is_enabled);
+                let is_invalid = matches!(
+                    instruction.presentation_hint,
+                    Some(dap::DisassembledInstructionPresentationHint::Invalid)
+                );
+
+                h_flex()
+                    .id(("disassembly-instruction", ix))
+                    .w_full()
+                    .gap_2()
+                    .when(is_current, |this| {
+                        this.bg(cx.theme().colors().editor_debugger_active_line_background)
+                    })
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .child(
+                        div()
+                            .id(("disassembly-gutter", ix))
+                            .w_4()
+                            .flex_none()
+                            .cursor_pointer()
+                            .when_some(breakpoint, |this, is_enabled| {
+                                this.child(
+                                    Icon::new(if is_enabled {
+                                        IconName::DebugBreakpoint
+                                    } else {
+                                        IconName::DebugDisabledBreakpoint
+                                    })
+                                    .size(IconSize::XSmall)
+                                    .color(Color::Debugger),
+                                )
+                            })
+                            .when(breakpoint.is_none() && is_current, |this| {
+                                this.child(
+                                    Icon::new(IconName::ArrowRight)
+                                        .size(IconSize::XSmall)
+                                        .color(Color::Debugger),
+                                )
+                            })
+                            .tooltip(Tooltip::text("Toggle Instruction Breakpoint"))
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.toggle_instruction_breakpoint(&address, cx);
+                            })),
+                    )
+                    .child(
+                        Label::new(instruction.address.clone())
+                            .buffer_font(cx)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .when_some(instruction.instruction_bytes.clone(), |this, bytes| {
+                        this.child(
+                            div().w_32().flex_none().overflow_hidden().child(
+                                Label::new(bytes)
+                                    .buffer_font(cx)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                        )
+                    })
+                    .child(
+                        Label::new(instruction.instruction.clone())
+                            .buffer_font(cx)
+                            .size(LabelSize::Small)
+                            .when(is_invalid, |this| this.color(Color::Disabled)),
+                    )
+                    .when_some(instruction.symbol.clone(), |this, symbol| {
+                        this.child(
+                            Label::new(format!("<{symbol}>"))
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .into_any_element()
+            }
+        }
+    }
+
+    fn render_vertical_scrollbar(&self, cx: &mut Context<Self>) -> Stateful<Div> {
+        div()
+            .occlude()
+            .id("disassembly-view-vertical-scrollbar")
+            .on_mouse_move(cx.listener(|_, _, _, cx| {
+                cx.notify();
+                cx.stop_propagation()
+            }))
+            .on_hover(|_, _, cx| {
+                cx.stop_propagation();
+            })
+            .on_any_mouse_down(|_, _, cx| {
+                cx.stop_propagation();
+            })
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|_, _, _, cx| {
+                    cx.stop_propagation();
+                }),
+            )
+            .on_scroll_wheel(cx.listener(|_, _, _, cx| {
+                cx.notify();
+            }))
+            .h_full()
+            .absolute()
+            .right_1()
+            .top_1()
+            .bottom_0()
+            .w(px(12.))
+            .cursor_default()
+            .children(Scrollbar::vertical(self.scrollbar_state.clone()))
+    }
+
+    pub(crate) fn render_control_strip(&self) -> AnyElement {
+        h_flex()
+            .child(
+                IconButton::new("disassembly-step-over-instruction", IconName::DebugStepOver)
+                    .icon_size(IconSize::Small)
+                    .tooltip(move |window, cx| {
+                        Tooltip::for_action(
+                            "Step Over Instruction",
+                            &StepOverInstruction,
+                            window,
+                            cx,
+                        )
+                    })
+                    .on_click(|_, window, cx| {
+                        window.dispatch_action(StepOverInstruction.boxed_clone(), cx)
+                    }),
+            )
+            .child(
+                IconButton::new("disassembly-step-into-instruction", IconName::DebugStepInto)
+                    .icon_size(IconSize::Small)
+                    .tooltip(move |window, cx| {
+                        Tooltip::for_action(
+                            "Step Into Instruction",
+                            &StepIntoInstruction,
+                            window,
+                            cx,
+                        )
+                    })
+                    .on_click(|_, window, cx| {
+                        window.dispatch_action(StepIntoInstruction.boxed_clone(), cx)
+                    }),
+            )
+            .into_any_element()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn instruction_addresses(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                DisassemblyEntry::Instruction(instruction) => Some(instruction.address.clone()),
+                DisassemblyEntry::Source { .. } => None,
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn source_line_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry, DisassemblyEntry::Source { .. }))
+            .count()
+    }
+}
+
+/// Splits the instructions into runs that share a source line, with a source entry before each run.
+///
+/// Adapters only send `location` when it changes from the previous instruction, so it carries over.
+fn interleave_source(instructions: &[DisassembledInstruction]) -> Vec<DisassemblyEntry> {
+    let mut entries = Vec::with_capacity(instructions.len());
+    let mut current_source: Option<(Option<Arc<Path>>, SharedString)> = None;
+    let mut current_line = None;
+
+    for instruction in instructions {
+        if let Some(location) = &instruction.location {
+            let path = location
+                .path
+                .as_deref()
+                .map(|path| Arc::<Path>::from(Path::new(path)));
+            let name = location
+                .name
+                .clone()
+                .or_else(|| location.path.clone())
+                .unwrap_or_default();
+            current_source = Some((path, name.into()));
+            current_line = None;
+        }
+
+        if let Some(line) = instruction.line
+            && current_line != Some(line)
+        {
+            current_line = Some(line);
+            if let Some((path, name)) = &current_source {
+                entries.push(DisassemblyEntry::Source {
+                    path: path.clone(),
+                    name: name.clone(),
+                    line,
+                });
+            }
+        }
+
+        entries.push(DisassemblyEntry::Instruction(instruction.clone()));
+    }
+
+    entries
+}
+
+fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+impl Focusable for DisassemblyView {
+    fn focus_handle(&self, _: &gpui::App) -> gpui::FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for DisassemblyView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_empty = self.entries.is_empty();
+        div()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_1()
+            .map(|this| {
+                if is_empty {
+                    this.child(
+                        h_flex().size_full().justify_center().child(
+                            Label::new("No disassembly available for the selected stack frame")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(
+                            "disassembly-view",
+                            self.entries.len(),
+                            cx.processor(|this, range: Range<usize>, _window, cx| {
+                                range.map(|ix| this.render_entry(ix, cx)).collect()
+                            }),
+                        )
+                        .track_scroll(self.scroll_handle.clone())
+                        .size_full(),
+                    )
+                    .child(self.render_vertical_scrollbar(cx))
+                }
+            })
+    }
+}
@@ -29,10 +29,12 @@ use std::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    time::{Duration, Instant},
 };
+use task::{BackgroundTaskPatterns, BuildTaskDefinition, DebugScenario, TaskContext, TaskTemplate};
 use terminal_view::terminal_panel::TerminalPanel;
 use tests::{active_debug_session_panel, init_test, init_test_workspace};
-use util::path;
+use util::{path, test::TempTree};
 use workspace::item::SaveOptions;
 use workspace::{Item, dock::Panel};
 
@@ -1944,3 +1946,100 @@ async fn test_adapter_shutdown_with_child_sessions_on_app_quit(
         "Child session should have received disconnect request"
     );
 }
+
+#[gpui::test]
+async fn test_build_task_waits_for_background_task_to_be_ready(
+    executor: BackgroundExecutor,
+    cx: &mut TestAppContext,
+) {
+    cx.executor().allow_parking();
+    init_test(cx);
+
+    let dir = TempTree::new(json!({}));
+    let fs = FakeFs::new(executor.clone());
+    fs.insert_tree(path!("/project"), json!({ "main.rs": "" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let _subscription = project::debugger::test::intercept_debug_sessions(cx, |_| {});
+
+    // The build task is a server that gets ready once the test lets it.
+    let build = TaskTemplate {
+        label: "serve".into(),
+        command: "sh".into(),
+        args: vec![
+            "-c".into(),
+            "echo Compiling; while [ ! -e ready ]; do sleep 0.05; done; echo Listening; sleep 30"
+                .into(),
+        ],
+        cwd: Some(dir.path().to_string_lossy().into_owned()),
+        is_background: true,
+        background_patterns: Some(BackgroundTaskPatterns {
+            begins: Some("^Compiling".into()),
+            ends: Some("^Listening".into()),
+        }),
+        ..TaskTemplate::default()
+    };
+    workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.start_debug_session(
+                DebugScenario {
+                    adapter: "fake-adapter".into(),
+                    label: "test".into(),
+                    build: Some(BuildTaskDefinition::Template {
+                        task_template: build,
+                        locator_name: None,
+                    }),
+                    config: json!({ "request": "launch" }),
+                    tcp_connection: None,
+                },
+                TaskContext::default(),
+                None,
+                None,
+                window,
+                cx,
+            )
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    let session = workspace
+        .update(cx, |workspace, _, cx| {
+            let panel = workspace.panel::<DebugPanel>(cx).unwrap();
+            let session = panel.read(cx).active_session().unwrap();
+            session.read(cx).running_state().read(cx).session().clone()
+        })
+        .unwrap();
+
+    let wait_until = |cx: &mut VisualTestContext, condition: &dyn Fn(&mut App) -> bool| {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while !cx.update(|_, cx| condition(cx)) {
+            assert!(Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(20));
+            cx.run_until_parked();
+        }
+    };
+
+    wait_until(cx, &|cx| {
+        project
+            .read(cx)
+            .background_tasks(cx)
+            .iter()
+            .any(|terminal| terminal.read(cx).get_content().contains("Compiling"))
+    });
+    std::thread::sleep(Duration::from_millis(200));
+    cx.run_until_parked();
+    assert!(
+        session.read_with(cx, |session, _| session.is_building()),
+        "The session should wait for the build task to be ready"
+    );
+
+    std::fs::write(dir.path().join("ready"), "").unwrap();
+    wait_until(cx, &|cx| !session.read(cx).is_building());
+    assert!(
+        project.read_with(cx, |project, cx| !project.background_tasks(cx).is_empty()),
+        "The build task should keep running once the session starts"
+    );
+}
@@ -0,0 +1,217 @@
+use crate::{
+    debugger_panel::DebugPanel,
+    persistence::DebuggerPaneItem,
+    tests::{active_debug_session_panel, init_test, init_test_workspace, start_debug_session},
+};
+use dap::{
+    StackFrame, SteppingGranularity,
+    requests::{
+        Disassemble, Initialize, Next, Scopes, SetInstructionBreakpoints, StackTrace, StepIn,
+        Threads,
+    },
+};
+use gpui::{BackgroundExecutor, TestAppContext, VisualTestContext};
+use project::{FakeFs, Project};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use util::path;
+
+fn instruction(address: u64, line: Option<u64>) -> dap::DisassembledInstruction {
+    dap::DisassembledInstruction {
+        address: format!("0x{address:x}"),
+        instruction_bytes: None,
+        instruction: format!("nop ; {address}"),
+        symbol: None,
+        location: line.map(|_| dap::Source {
+            name: Some("main.c".into()),
+            path: Some(path!("/project/main.c").into()),
+            source_reference: None,
+            presentation_hint: None,
+            origin: None,
+            sources: None,
+            adapter_data: None,
+            checksums: None,
+        }),
+        line,
+        column: None,
+        end_line: None,
+        end_column: None,
+        presentation_hint: None,
+    }
+}
+
+#[gpui::test]
+async fn test_disassembly_follows_stack_frame(
+    executor: BackgroundExecutor,
+    cx: &mut TestAppContext,
+) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.focus_panel::<DebugPanel>(window, cx);
+        })
+        .unwrap();
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let session = start_debug_session(&workspace, cx, |client| {
+        client.on_request::<Initialize, _>(move |_, _| {
+            Ok(dap::Capabilities {
+                supports_disassemble_request: Some(true),
+                supports_instruction_breakpoints: Some(true),
+                supports_stepping_granularity: Some(true),
+                ..Default::default()
+            })
+        });
+    })
+    .unwrap();
+
+    let client = session.update(cx, |session, _| session.adapter_client().unwrap());
+    client.on_request::<Scopes, _>(move |_, _| Ok(dap::ScopesResponse { scopes: vec![] }));
+    client.on_request::<Threads, _>(move |_, _| {
+        Ok(dap::ThreadsResponse {
+            threads: vec![dap::Thread {
+                id: 1,
+                name: "Thread 1".into(),
+            }],
+        })
+    });
+    client.on_request::<StackTrace, _>(move |_, _| {
+        Ok(dap::StackTraceResponse {
+            stack_frames: vec![StackFrame {
+                id: 1,
+                name: "main".into(),
+                source: None,
+                line: 0,
+                column: 0,
+                end_line: None,
+                end_column: None,
+                can_restart: None,
+                instruction_pointer_reference: Some("0x1002".into()),
+                module_id: None,
+                presentation_hint: None,
+            }],
+            total_frames: None,
+        })
+    });
+    client.on_request::<Disassemble, _>(move |_, args| {
+        assert_eq!(args.memory_reference, "0x1002");
+        assert_eq!(args.instruction_offset, Some(-64));
+        Ok(dap::DisassembleResponse {
+            instructions: vec![
+                instruction(0x1000, Some(3)),
+                instruction(0x1001, None),
+                instruction(0x1002, Some(4)),
+            ],
+        })
+    });
+
+    let set_instruction_breakpoints = Arc::new(AtomicBool::new(false));
+    client.on_request::<SetInstructionBreakpoints, _>({
+        let set_instruction_breakpoints = set_instruction_breakpoints.clone();
+        move |_, args| {
+            assert_eq!(
+                args.breakpoints
+                    .iter()
+                    .map(|breakpoint| breakpoint.instruction_reference.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["0x1001"]
+            );
+            set_instruction_breakpoints.store(true, Ordering::SeqCst);
+            Ok(dap::SetInstructionBreakpointsResponse {
+                breakpoints: vec![],
+            })
+        }
+    });
+
+    let stepped_in = Arc::new(AtomicBool::new(false));
+    client.on_request::<StepIn, _>({
+        let stepped_in = stepped_in.clone();
+        move |_, args| {
+            assert_eq!(args.granularity, Some(SteppingGranularity::Instruction));
+            stepped_in.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    });
+    let stepped_over = Arc::new(AtomicBool::new(false));
+    client.on_request::<Next, _>({
+        let stepped_over = stepped_over.clone();
+        move |_, args| {
+            assert_eq!(args.granularity, Some(SteppingGranularity::Instruction));
+            stepped_over.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    });
+
+    client
+        .fake_event(dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason: dap::StoppedEventReason::Pause,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: None,
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+
+    cx.run_until_parked();
+
+    let running_state =
+        active_debug_session_panel(workspace, cx).update_in(cx, |item, window, cx| {
+            cx.focus_self(window);
+            item.running_state().clone()
+        });
+
+    running_state.update_in(cx, |this, window, cx| {
+        this.activate_item(DebuggerPaneItem::Disassembly, window, cx);
+        cx.refresh_windows();
+    });
+
+    cx.run_until_parked();
+
+    running_state.update(cx, |state, cx| {
+        let disassembly_view = state.disassembly_view().read(cx);
+        assert_eq!(
+            disassembly_view.instruction_addresses(),
+            vec!["0x1000", "0x1001", "0x1002"]
+        );
+        assert_eq!(disassembly_view.source_line_count(), 2);
+    });
+
+    session.update(cx, |session, cx| {
+        session.toggle_instruction_breakpoint("0x1001", cx);
+    });
+    cx.run_until_parked();
+    assert!(
+        set_instruction_breakpoints.load(Ordering::SeqCst),
+        "Toggling an instruction breakpoint should send setInstructionBreakpoints"
+    );
+
+    running_state.update(cx, |state, cx| state.step_in_instruction(cx));
+    cx.run_until_parked();
+    assert!(stepped_in.load(Ordering::SeqCst));
+
+    client
+        .fake_event(dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason: dap::StoppedEventReason::Step,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: None,
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+    cx.run_until_parked();
+
+    running_state.update(cx, |state, cx| state.step_over_instruction(cx));
+    cx.run_until_parked();
+    assert!(stepped_over.load(Ordering::SeqCst));
+}
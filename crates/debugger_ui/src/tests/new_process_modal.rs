@@ -307,6 +307,7 @@ async fn test_dap_adapter_config_conversion_and_validation(cx: &mut TestAppConte
             cwd: None,
             args: vec![],
             env: Default::default(),
+            core_file: None,
         }),
         stop_on_entry: Some(true),
     };
@@ -370,3 +371,77 @@ async fn test_dap_adapter_config_conversion_and_validation(cx: &mut TestAppConte
         expected_adapters
     );
 }
+
+#[gpui::test]
+async fn test_core_dump_config_conversion(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let zed_config = ZedDebugConfig {
+        label: "core: core.1234".into(),
+        adapter: "test_adapter".into(),
+        request: DebugRequest::Launch(LaunchRequest {
+            program: "/project/crash".into(),
+            cwd: Some("/project".into()),
+            args: vec![],
+            env: Default::default(),
+            core_file: Some("/project/core.1234".into()),
+        }),
+        stop_on_entry: Some(true),
+    };
+
+    for (adapter_name, expected_request) in [
+        (
+            "CodeLLDB",
+            Some(dap::StartDebuggingRequestArgumentsRequest::Launch),
+        ),
+        (
+            "GDB",
+            Some(dap::StartDebuggingRequestArgumentsRequest::Attach),
+        ),
+        ("Debugpy", None),
+        ("JavaScript", None),
+        ("Delve", None),
+    ] {
+        let adapter = cx
+            .update(|cx| DapRegistry::global(cx).adapter(adapter_name))
+            .unwrap_or_else(|| panic!("Adapter {} should exist", adapter_name));
+
+        let mut adapter_specific_config = zed_config.clone();
+        adapter_specific_config.adapter = adapter_name.into();
+
+        let debug_scenario = adapter
+            .config_from_zed_format(adapter_specific_config)
+            .await;
+        let Some(expected_request) = expected_request else {
+            assert!(
+                debug_scenario.is_err(),
+                "Adapter {} cannot debug core dumps and should reject the config",
+                adapter_name
+            );
+            continue;
+        };
+
+        let debug_scenario = debug_scenario.unwrap_or_else(|_| {
+            panic!(
+                "Adapter {} should convert a core dump config from Zed format",
+                adapter_name
+            )
+        });
+        assert_eq!(
+            debug_scenario.config["coreFile"],
+            json!("/project/core.1234"),
+            "Adapter {} should pass the core file through",
+            adapter_name
+        );
+        assert_eq!(
+            debug_scenario.config.get("stopOnEntry"),
+            None,
+            "Adapter {} should not stop on entry in a core dump",
+            adapter_name
+        );
+        assert_eq!(
+            adapter.request_kind(&debug_scenario.config).await.unwrap(),
+            expected_request
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{
+    debugger_panel::DebugPanel,
+    tests::{init_test, init_test_workspace, start_debug_session_with},
+};
+use dap::{
+    SteppingGranularity,
+    adapters::{DebugAdapterName, DebugTaskDefinition},
+    requests::{Continue, Initialize, Next, Scopes, StackTrace, StepIn, Threads},
+};
+use gpui::{BackgroundExecutor, TestAppContext, VisualTestContext};
+use project::{
+    FakeFs, Project, RealFs,
+    debugger::session::{ThreadId, ThreadStatus},
+    project_settings::{DapSettings, ProjectSettings},
+};
+use serde_json::json;
+use settings::SettingsStore;
+use std::{
+    process::Command,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use task::TaskContext;
+use util::{path, test::TempTree};
+
+#[gpui::test]
+async fn test_core_dump_session_is_read_only(
+    executor: BackgroundExecutor,
+    cx: &mut TestAppContext,
+) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.focus_panel::<DebugPanel>(window, cx);
+        })
+        .unwrap();
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let session = start_debug_session_with(
+        &workspace,
+        cx,
+        DebugTaskDefinition {
+            adapter: "fake-adapter".into(),
+            label: "core: core.1234".into(),
+            config: json!({
+                "request": "launch",
+                "program": path!("/project/crash"),
+                "coreFile": path!("/project/core.1234"),
+            }),
+            tcp_connection: None,
+        },
+        |client| {
+            client.on_request::<Initialize, _>(move |_, _| {
+                Ok(dap::Capabilities {
+                    supports_set_variable: Some(true),
+                    supports_write_memory_request: Some(true),
+                    supports_step_back: Some(true),
+                    supports_stepping_granularity: Some(true),
+                    ..Default::default()
+                })
+            });
+        },
+    )
+    .unwrap();
+
+    let client = session.update(cx, |session, _| session.adapter_client().unwrap());
+    client.on_request::<Scopes, _>(move |_, _| Ok(dap::ScopesResponse { scopes: vec![] }));
+    client.on_request::<Threads, _>(move |_, _| {
+        Ok(dap::ThreadsResponse {
+            threads: vec![dap::Thread {
+                id: 1,
+                name: "Thread 1".into(),
+            }],
+        })
+    });
+    client.on_request::<StackTrace, _>(move |_, _| {
+        Ok(dap::StackTraceResponse {
+            stack_frames: vec![],
+            total_frames: None,
+        })
+    });
+
+    let sent_execution_request = Arc::new(AtomicBool::new(false));
+    client.on_request::<Next, _>({
+        let sent_execution_request = sent_execution_request.clone();
+        move |_, _| {
+            sent_execution_request.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    });
+    client.on_request::<StepIn, _>({
+        let sent_execution_request = sent_execution_request.clone();
+        move |_, _| {
+            sent_execution_request.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    });
+    client.on_request::<Continue, _>({
+        let sent_execution_request = sent_execution_request.clone();
+        move |_, _| {
+            sent_execution_request.store(true, Ordering::SeqCst);
+            Ok(dap::ContinueResponse {
+                all_threads_continued: Some(true),
+            })
+        }
+    });
+
+    client
+        .fake_event(dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason: dap::StoppedEventReason::Exception,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: Some(true),
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+
+    cx.run_until_parked();
+
+    session.update(cx, |session, _| {
+        assert!(session.is_post_mortem());
+
+        let capabilities = session.capabilities();
+        assert_eq!(capabilities.supports_set_variable, Some(false));
+        assert_eq!(capabilities.supports_write_memory_request, Some(false));
+        assert_eq!(capabilities.supports_step_back, Some(false));
+        assert_eq!(capabilities.supports_stepping_granularity, Some(true));
+    });
+
+    session.update(cx, |session, cx| {
+        session.step_over(ThreadId(1), SteppingGranularity::Line, cx);
+        session.step_in(ThreadId(1), SteppingGranularity::Line, cx);
+        session.continue_thread(ThreadId(1), cx);
+    });
+    cx.run_until_parked();
+
+    assert!(
+        !sent_execution_request.load(Ordering::SeqCst),
+        "Execution control requests should not be sent for a core dump"
+    );
+    session.update(cx, |session, _| {
+        assert_eq!(session.thread_status(ThreadId(1)), ThreadStatus::Stopped);
+    });
+}
+
+/// Opens the core dump of a crashing C program with the GDB installed on the machine. Skipped
+/// when there is no C compiler or GDB, or when GDB can't write the core dump.
+#[gpui::test]
+async fn test_real_core_dump(cx: &mut TestAppContext) {
+    let (Ok(gdb), Ok(compiler)) = (which::which("gdb"), which::which("cc")) else {
+        eprintln!("skipping test_real_core_dump: gdb or cc is not installed");
+        return;
+    };
+
+    let tree = TempTree::new(json!({
+        "crash.c": r#"
+            #include <stddef.h>
+
+            static void crash_here(volatile int *pointer) {
+                *pointer = 42;
+            }
+
+            int main(void) {
+                crash_here(NULL);
+                return 0;
+            }
+        "#,
+    }));
+    let dir = tree.path();
+    let run = |command: &mut Command| {
+        command
+            .current_dir(dir)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    };
+    assert!(
+        run(Command::new(&compiler).args(["-g", "-O0", "-o", "crash", "crash.c"])),
+        "failed to compile the crashing program"
+    );
+    // Let GDB write the core when the program crashes, so that the test doesn't depend on
+    // where the system puts core dumps.
+    run(Command::new(&gdb).args([
+        "-batch",
+        "-ex",
+        "run",
+        "-ex",
+        "generate-core-file core",
+        "./crash",
+    ]));
+    let core_file = dir.join("core");
+    if !core_file.exists() {
+        eprintln!("skipping test_real_core_dump: gdb couldn't write a core dump");
+        return;
+    }
+
+    init_test(cx);
+    cx.executor().allow_parking();
+    dap::transport::use_real_adapters_in_tests();
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.dap.insert(
+                    DebugAdapterName("GDB".into()),
+                    DapSettings {
+                        binary: Some(gdb.to_string_lossy().into_owned()),
+                        args: vec!["-i=dap".into()],
+                    },
+                );
+            });
+        });
+    });
+
+    let project = Project::test(Arc::new(RealFs::new(None, cx.executor())), [dir], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.focus_panel::<DebugPanel>(window, cx);
+            workspace.start_debug_session(
+                DebugTaskDefinition {
+                    adapter: "GDB".into(),
+                    label: "core: core".into(),
+                    config: json!({
+                        "request": "attach",
+                        "program": dir.join("crash"),
+                        "coreFile": core_file,
+                        "cwd": dir,
+                    }),
+                    tcp_connection: None,
+                }
+                .to_scenario(),
+                TaskContext::default(),
+                None,
+                None,
+                window,
+                cx,
+            );
+        })
+        .unwrap();
+
+    // GDB runs as a separate process, so wait for it in real time.
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let frames = loop {
+        cx.run_until_parked();
+        let frames = workspace
+            .update(cx, |workspace, _, cx| {
+                let session = workspace
+                    .panel::<DebugPanel>(cx)?
+                    .read(cx)
+                    .active_session()?
+                    .read(cx)
+                    .running_state()
+                    .read(cx)
+                    .session()
+                    .clone();
+                session.update(cx, |session, cx| {
+                    assert!(session.is_post_mortem());
+                    let (thread, _) = session.threads(cx).into_iter().next()?;
+                    let frames = session.stack_frames(ThreadId(thread.id), cx).ok()?;
+                    (!frames.is_empty()).then(|| {
+                        frames
+                            .into_iter()
+                            .map(|frame| frame.dap.name)
+                            .collect::<Vec<_>>()
+                    })
+                })
+            })
+            .unwrap();
+        if let Some(frames) = frames {
+            break frames;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for the stack of the crashed thread"
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    assert!(
+        frames[0].contains("crash_here"),
+        "the core dump should stop in the crashing function, got {frames:?}"
+    );
+    assert!(frames.iter().any(|frame| frame.contains("main")));
+}
@@ -301,6 +301,34 @@ pub struct GoToPreviousDiagnostic {
     pub severity: GoToDiagnosticSeverityFilter,
 }
 
+/// Moves to an earlier state of the undo tree, going through its branches in the order the
+/// states were created.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct UndoEarlier {
+    /// The number of states to move back by. Defaults to one.
+    #[serde(default)]
+    pub count: Option<usize>,
+    /// Moves to the last state edited at least this many seconds earlier instead.
+    #[serde(default)]
+    pub seconds: Option<u64>,
+}
+
+/// Moves to a later state of the undo tree, going through its branches in the order the
+/// states were created.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct UndoLater {
+    /// The number of states to move forward by. Defaults to one.
+    #[serde(default)]
+    pub count: Option<usize>,
+    /// Moves to the last state edited at most this many seconds later instead.
+    #[serde(default)]
+    pub seconds: Option<u64>,
+}
+
 actions!(
     debugger,
     [
@@ -145,7 +145,7 @@ use multi_buffer::{
     MultiOrSingleBufferOffsetRange, ToOffsetUtf16,
 };
 use parking_lot::Mutex;
-use persistence::DB;
+use persistence::{DB, SerializedUndoState};
 use project::{
     BreakpointWithPosition, CodeAction, Completion, CompletionIntent, CompletionResponse,
     CompletionSource, DisableAiSettings, DocumentHighlight, InlayHint, Location, LocationLink,
@@ -1168,6 +1168,7 @@ pub struct Editor {
     _scroll_cursor_center_top_bottom_task: Task<()>,
     serialize_selections: Task<()>,
     serialize_folds: Task<()>,
+    serialize_undo_tree: Task<()>,
     mouse_cursor_hidden: bool,
     minimap: Option<Entity<Self>>,
     hide_mouse_mode: HideMouseMode,
@@ -2243,6 +2244,7 @@ impl Editor {
             toggle_fold_multiple_buffers: Task::ready(()),
             serialize_selections: Task::ready(()),
             serialize_folds: Task::ready(()),
+            serialize_undo_tree: Task::ready(()),
             text_style_refinement: None,
             load_diff_task: load_uncommitted_diff,
             temporary_diff_override: false,
@@ -3255,6 +3257,35 @@ impl Editor {
         });
     }
 
+    fn undo_tree_did_change(&mut self, cx: &mut Context<Self>) {
+        if self.mode.is_minimap()
+            || WorkspaceSettings::get(None, cx).restore_on_startup == RestoreOnStartupBehavior::None
+        {
+            return;
+        }
+
+        let Some(workspace_id) = self.workspace.as_ref().and_then(|workspace| workspace.1) else {
+            return;
+        };
+        let Some(tree) = self.buffer().read(cx).undo_tree(cx) else {
+            return;
+        };
+        let background_executor = cx.background_executor().clone();
+        let editor_id = cx.entity().entity_id().as_u64() as ItemId;
+        self.serialize_undo_tree = cx.background_spawn(async move {
+            background_executor.timer(SERIALIZATION_THROTTLE_TIME).await;
+            let states = SerializedUndoState::serialize_tree(&tree);
+            DB.save_editor_undo_states(editor_id, workspace_id, states)
+                .await
+                .with_context(|| {
+                    format!(
+                        "persisting editor undo tree for editor {editor_id}, workspace {workspace_id:?}"
+                    )
+                })
+                .log_err();
+        });
+    }
+
     pub fn sync_selections(
         &mut self,
         other: Entity<Editor>,
@@ -12458,6 +12489,83 @@ impl Editor {
         }
     }
 
+    pub fn undo_earlier(
+        &mut self,
+        action: &UndoEarlier,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(tree) = self.buffer.read(cx).undo_tree(cx) else {
+            return;
+        };
+        let target = match action.seconds {
+            Some(seconds) => tree.earlier_by(Duration::from_secs(seconds)),
+            None => tree.earlier(action.count.unwrap_or(1)),
+        };
+        self.undo_tree_move_to(target, window, cx);
+    }
+
+    pub fn undo_later(&mut self, action: &UndoLater, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tree) = self.buffer.read(cx).undo_tree(cx) else {
+            return;
+        };
+        let target = match action.seconds {
+            Some(seconds) => tree.later_by(Duration::from_secs(seconds)),
+            None => tree.later(action.count.unwrap_or(1)),
+        };
+        self.undo_tree_move_to(target, window, cx);
+    }
+
+    /// Moves the buffer to the given state of its undo tree, where `None` is the tree's root.
+    pub fn undo_tree_move_to(
+        &mut self,
+        target: Option<TransactionId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+        let Some(tree) = self.buffer.read(cx).undo_tree(cx) else {
+            return;
+        };
+        // The selections of the root are the ones from before the first transaction leading
+        // away from it.
+        let Some(transaction_id) = target.or_else(|| {
+            let mut state = tree.state(tree.current?)?;
+            while let Some(parent) = state.parent {
+                state = tree.state(parent)?;
+            }
+            Some(state.transaction_id)
+        }) else {
+            return;
+        };
+
+        self.hide_mouse_cursor(HideMouseCursorOrigin::TypingAction, cx);
+
+        if self
+            .buffer
+            .update(cx, |buffer, cx| buffer.undo_tree_move_to(target, cx))
+        {
+            let selections =
+                self.selection_history
+                    .transaction(transaction_id)
+                    .and_then(|(before, after)| match target {
+                        Some(_) => after.clone(),
+                        None => Some(before.clone()),
+                    });
+            if let Some(selections) = selections {
+                self.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                    s.select_anchors(selections.to_vec());
+                });
+            }
+            self.request_autoscroll(Autoscroll::fit(), cx);
+            self.unmark_text(window, cx);
+            self.refresh_edit_prediction(true, false, window, cx);
+            cx.emit(EditorEvent::Edited { transaction_id });
+        }
+    }
+
     pub fn finalize_last_transaction(&mut self, cx: &mut Context<Self>) {
         self.buffer
             .update(cx, |buffer, cx| buffer.finalize_last_transaction(cx));
@@ -20117,6 +20225,7 @@ impl Editor {
                 }
 
                 if *singleton_buffer_edited {
+                    self.undo_tree_did_change(cx);
                     if let Some(buffer) = edited_buffer
                         && buffer.read(cx).file().is_none()
                     {
@@ -21238,6 +21347,18 @@ impl Editor {
             && !self.mode.is_minimap()
             && WorkspaceSettings::get(None, cx).restore_on_startup != RestoreOnStartupBehavior::None
         {
+            // The undo tree is restored first, as it replaces the buffer's text with an
+            // identical one that the folds and selections below are anchored to.
+            if let Some(buffer) = self.buffer.read(cx).as_singleton()
+                && let Some(states) = DB.get_editor_undo_states(item_id, workspace_id).log_err()
+                && let Some((root_text, states, current)) =
+                    SerializedUndoState::deserialize_tree(states)
+            {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.restore_undo_tree(&root_text, &states, current, cx)
+                });
+            }
+
             let buffer_snapshot = OnceCell::new();
 
             if let Some(folds) = DB.get_editor_folds(item_id, workspace_id).log_err()
@@ -1427,6 +1427,20 @@ fn clip_ranges<'a>(
         .collect()
 }
 
+/// Structural matches are replaced using the syntax tree of the buffer they were found in,
+/// rather than their text alone.
+fn structural_replacement(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    let (buffer, range, _) = snapshot
+        .range_to_buffer_ranges(range.clone())
+        .into_iter()
+        .next()?;
+    query.structural_replacement_for(buffer, range)
+}
+
 impl EventEmitter<SearchEvent> for Editor {}
 
 impl Editor {
@@ -1545,6 +1559,7 @@ impl SearchableItem for Editor {
                 case: true,
                 word: true,
                 regex: true,
+                structural: true,
                 replacement: false,
                 selection: false,
                 find_in_results: true,
@@ -1554,6 +1569,7 @@ impl SearchableItem for Editor {
                 case: true,
                 word: true,
                 regex: true,
+                structural: true,
                 replacement: true,
                 selection: true,
                 find_in_results: false,
@@ -1626,6 +1642,14 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            if let Some(replacement) = structural_replacement(&text, identifier, query) {
+                self.transact(window, cx, |this, _, cx| {
+                    this.edit([(identifier.clone(), Arc::from(replacement))], cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1652,6 +1676,12 @@ impl SearchableItem for Editor {
         let mut edits = vec![];
 
         for m in matches {
+            if query.is_structural() {
+                if let Some(replacement) = structural_replacement(&text, m, query) {
+                    edits.push((m.clone(), Arc::from(replacement)));
+                }
+                continue;
+            }
             let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
 
             let text: Cow<_> = if text.len() == 1 {
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use collections::HashMap;
 use db::{
     query,
     sqlez::{
@@ -10,7 +11,11 @@ use db::{
 };
 use fs::MTime;
 use itertools::Itertools as _;
-use std::path::PathBuf;
+use language::{SavedUndoState, UndoTree};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use workspace::{ItemId, WorkspaceDb, WorkspaceId};
 
@@ -87,6 +92,161 @@ impl Column for SerializedEditor {
     }
 }
 
+/// How many states of an editor's undo tree are kept across restarts.
+const MAX_SERIALIZED_UNDO_STATES: usize = 100;
+
+/// A state of an editor's undo tree, stored as the edits from its parent state. The root of the
+/// tree is stored with an id of zero and edits that insert its text.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedUndoState {
+    pub(crate) id: u64,
+    pub(crate) parent_id: Option<u64>,
+    /// Milliseconds since the Unix epoch.
+    pub(crate) edited_at: i64,
+    pub(crate) is_current: bool,
+    pub(crate) edits: Vec<(usize, usize, String)>,
+}
+
+impl StaticColumnCount for SerializedUndoState {
+    fn column_count() -> usize {
+        5
+    }
+}
+
+impl Bind for SerializedUndoState {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let start_index = statement.bind(&self.id, start_index)?;
+        let start_index = statement.bind(&self.parent_id, start_index)?;
+        let start_index = statement.bind(&self.edited_at, start_index)?;
+        let start_index = statement.bind(&self.is_current, start_index)?;
+        statement.bind(&serde_json::to_string(&self.edits)?, start_index)
+    }
+}
+
+impl Column for SerializedUndoState {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, start_index): (u64, i32) = Column::column(statement, start_index)?;
+        let (parent_id, start_index): (Option<u64>, i32) = Column::column(statement, start_index)?;
+        let (edited_at, start_index): (i64, i32) = Column::column(statement, start_index)?;
+        let (is_current, start_index): (bool, i32) = Column::column(statement, start_index)?;
+        let (edits, start_index): (String, i32) = Column::column(statement, start_index)?;
+
+        let state = Self {
+            id,
+            parent_id,
+            edited_at,
+            is_current,
+            edits: serde_json::from_str(&edits)?,
+        };
+        Ok((state, start_index))
+    }
+}
+
+impl SerializedUndoState {
+    /// Serializes the current state of the tree and its most recent states, diffing each of them
+    /// against its closest serialized ancestor.
+    pub(crate) fn serialize_tree(tree: &UndoTree) -> Vec<Self> {
+        let instant_now = Instant::now();
+        let system_now = SystemTime::now();
+        let to_unix_millis = |time: Instant| {
+            (system_now - instant_now.saturating_duration_since(time))
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_millis() as i64)
+        };
+
+        let mut serialized_states = tree.states
+            [tree.states.len().saturating_sub(MAX_SERIALIZED_UNDO_STATES)..]
+            .iter()
+            .collect::<Vec<_>>();
+        if let Some(current) = tree.current.and_then(|current| tree.state(current))
+            && !serialized_states
+                .iter()
+                .any(|state| state.transaction_id == current.transaction_id)
+        {
+            serialized_states.remove(0);
+            let ix = serialized_states
+                .partition_point(|state| state.transaction_id < current.transaction_id);
+            serialized_states.insert(ix, current);
+        }
+        let ids = serialized_states
+            .iter()
+            .enumerate()
+            .map(|(ix, state)| (state.transaction_id, ix as u64 + 1))
+            .collect::<HashMap<_, _>>();
+
+        let root_text = tree.root_text.to_string();
+        let mut result = vec![Self {
+            id: 0,
+            parent_id: None,
+            edited_at: tree
+                .states
+                .first()
+                .map_or(0, |state| to_unix_millis(state.first_edit_at)),
+            is_current: tree.current.is_none(),
+            edits: vec![(0, 0, root_text.clone())],
+        }];
+        for state in serialized_states {
+            let mut parent = state.parent.and_then(|parent| tree.state(parent));
+            while let Some(ancestor) = parent
+                && !ids.contains_key(&ancestor.transaction_id)
+            {
+                parent = ancestor.parent.and_then(|parent| tree.state(parent));
+            }
+            let parent_text =
+                parent.map_or_else(|| root_text.clone(), |state| state.text.to_string());
+            result.push(Self {
+                id: ids[&state.transaction_id],
+                parent_id: Some(parent.map_or(0, |parent| ids[&parent.transaction_id])),
+                edited_at: to_unix_millis(state.last_edit_at),
+                is_current: tree.current == Some(state.transaction_id),
+                edits: language::text_diff(&parent_text, &state.text.to_string())
+                    .into_iter()
+                    .map(|(range, text)| (range.start, range.end, text.to_string()))
+                    .collect(),
+            });
+        }
+        result
+    }
+
+    /// Converts serialized states back into the root text, states, and current state to restore
+    /// a buffer's undo tree with.
+    pub(crate) fn deserialize_tree(
+        states: Vec<Self>,
+    ) -> Option<(String, Vec<SavedUndoState>, Option<u64>)> {
+        let instant_now = Instant::now();
+        let system_now = SystemTime::now();
+        let to_instant = |unix_millis: i64| {
+            let time = UNIX_EPOCH + Duration::from_millis(unix_millis.max(0) as u64);
+            instant_now
+                .checked_sub(system_now.duration_since(time).unwrap_or_default())
+                .unwrap_or(instant_now)
+        };
+
+        let mut states = states.into_iter();
+        let root = states.next().filter(|root| root.id == 0)?;
+        let (_, _, root_text) = root.edits.into_iter().next()?;
+        let mut current = None;
+        let states = states
+            .map(|state| {
+                if state.is_current {
+                    current = Some(state.id);
+                }
+                SavedUndoState {
+                    id: state.id,
+                    parent: state.parent_id.filter(|parent_id| *parent_id != 0),
+                    edited_at: to_instant(state.edited_at),
+                    edits: state
+                        .edits
+                        .into_iter()
+                        .map(|(start, end, text)| (start..end, text.into()))
+                        .collect(),
+                }
+            })
+            .collect();
+        Some((root_text, states, current))
+    }
+}
+
 pub struct EditorDb(db::sqlez::thread_safe_connection::ThreadSafeConnection);
 
 impl Domain for EditorDb {
@@ -121,6 +281,16 @@ impl Domain for EditorDb {
     //   start: usize,
     //   end: usize,
     // )
+    //
+    // editor_undo_states(
+    //   editor_id: usize,
+    //   workspace_id: usize,
+    //   state_id: usize,
+    //   parent_id: Option<usize>,
+    //   edited_at: i64,
+    //   is_current: bool,
+    //   edits: String,
+    // )
 
     const MIGRATIONS: &[&str] = &[
         sql! (
@@ -197,6 +367,23 @@ impl Domain for EditorDb {
                 ON DELETE CASCADE
             ) STRICT;
         ),
+        sql! (
+            CREATE TABLE editor_undo_states (
+                editor_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                state_id INTEGER NOT NULL,
+                parent_id INTEGER,
+                edited_at INTEGER NOT NULL,
+                is_current INTEGER NOT NULL,
+                edits TEXT NOT NULL,
+                PRIMARY KEY(editor_id, workspace_id, state_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE,
+                FOREIGN KEY(editor_id, workspace_id) REFERENCES editors(item_id, workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        ),
     ];
 }
 
@@ -281,6 +468,18 @@ impl EditorDb {
         }
     }
 
+    query! {
+        pub fn get_editor_undo_states(
+            editor_id: ItemId,
+            workspace_id: WorkspaceId
+        ) -> Result<Vec<SerializedUndoState>> {
+            SELECT state_id, parent_id, edited_at, is_current, edits
+            FROM editor_undo_states
+            WHERE editor_id = ?1 AND workspace_id = ?2
+            ORDER BY state_id
+        }
+    }
+
     pub async fn save_editor_selections(
         &self,
         editor_id: ItemId,
@@ -384,6 +583,31 @@ VALUES {placeholders};
         }
         Ok(())
     }
+
+    pub async fn save_editor_undo_states(
+        &self,
+        editor_id: ItemId,
+        workspace_id: WorkspaceId,
+        states: Vec<SerializedUndoState>,
+    ) -> Result<()> {
+        log::debug!("Saving undo states for editor {editor_id} in workspace {workspace_id:?}");
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM editor_undo_states WHERE editor_id = ? AND workspace_id = ?
+            ))?((editor_id, workspace_id))?;
+            let mut insert_state = conn.exec_bound(sql!(
+                INSERT INTO editor_undo_states
+                    (editor_id, workspace_id, state_id, parent_id, edited_at, is_current, edits)
+                VALUES
+                    (?, ?, ?, ?, ?, ?, ?)
+            ))?;
+            for state in states {
+                insert_state((editor_id, workspace_id, state))?;
+            }
+            Ok(())
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -465,4 +689,45 @@ mod tests {
             .unwrap();
         assert_eq!(have, serialized_editor);
     }
+
+    #[gpui::test]
+    async fn test_save_and_restore_undo_tree() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        DB.save_serialized_editor(1234, workspace_id, SerializedEditor::default())
+            .await
+            .unwrap();
+
+        let mut buffer = text::Buffer::new(0, text::BufferId::new(1).unwrap(), "one");
+        buffer.edit([(3..3, " two")]);
+        buffer.edit([(7..7, " three")]);
+        buffer.undo();
+        buffer.edit([(7..7, " four")]);
+
+        // Saving the states again replaces them.
+        for _ in 0..2 {
+            DB.save_editor_undo_states(
+                1234,
+                workspace_id,
+                SerializedUndoState::serialize_tree(&buffer.undo_tree()),
+            )
+            .await
+            .unwrap();
+        }
+        let states = DB.get_editor_undo_states(1234, workspace_id).unwrap();
+        let (root_text, states, current) = SerializedUndoState::deserialize_tree(states).unwrap();
+
+        let mut restored = text::Buffer::new(0, text::BufferId::new(2).unwrap(), "one two four");
+        restored
+            .restore_undo_tree(&root_text, &states, current)
+            .unwrap();
+        let tree = restored.undo_tree();
+        assert_eq!(
+            tree.states
+                .iter()
+                .map(|state| state.text.to_string())
+                .collect::<Vec<_>>(),
+            ["one two", "one two three", "one two four"]
+        );
+        assert_eq!(tree.current, Some(tree.states[2].transaction_id));
+    }
 }
@@ -189,6 +189,7 @@ impl From<LaunchRequest> for task::LaunchRequest {
             cwd: value.cwd.map(|p| p.into()),
             args: value.args,
             env: value.envs.into_iter().collect(),
+            core_file: None,
         }
     }
 }
@@ -0,0 +1,163 @@
+//! Measures how well the file finder ranks the file one is after in a synthetic monorepo of
+//! 500k files, where tens of thousands of files are called `mod.rs` or `index.ts`, with and
+//! without frecency. The quality is printed as the mean reciprocal rank of the wanted files
+//! and the share of them that ranked first, over a simulated session that keeps coming back
+//! to a small working set of files.
+//!
+//! The ranking is the file finder's own, exposed by its `bench` feature:
+//! `cargo bench -p file_finder --features bench`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use collections::HashMap;
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use file_finder::bench::{
+    FrecencyScores, MAX_RERANKED_SEARCH_MATCHES, now_timestamp, rank_search_matches,
+};
+use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+const FILE_COUNT: usize = 500_000;
+const MODULES_PER_PACKAGE: usize = 50;
+const FILE_NAMES: [&str; 4] = ["mod.rs", "index.ts", "types.rs", "tests.rs"];
+const MODULE_NAMES: [&str; 10] = [
+    "user", "api", "auth", "billing", "search", "storage", "render", "net", "config", "util",
+];
+/// How many distinct files the simulated session keeps opening.
+const WORKING_SET_SIZE: usize = 200;
+const SESSION_LENGTH: usize = 100;
+
+fn synthetic_tree() -> Vec<PathBuf> {
+    let mut paths = Vec::with_capacity(FILE_COUNT);
+    for package in 0.. {
+        for module in 0..MODULES_PER_PACKAGE {
+            let module_name = MODULE_NAMES[module % MODULE_NAMES.len()];
+            for file_name in FILE_NAMES {
+                if paths.len() == FILE_COUNT {
+                    return paths;
+                }
+                paths.push(PathBuf::from(format!(
+                    "packages/package{package}/src/{module_name}{module}/{file_name}"
+                )));
+            }
+        }
+    }
+    paths
+}
+
+fn candidates(paths: &[PathBuf]) -> Vec<PathMatchCandidate<'_>> {
+    paths
+        .iter()
+        .map(|path| PathMatchCandidate {
+            is_dir: false,
+            path,
+            char_bag: CharBag::from(path.to_string_lossy().to_lowercase().as_str()),
+        })
+        .collect()
+}
+
+/// Returns the matches of the query that the file finder shows, best first.
+fn rank(
+    candidates: &[PathMatchCandidate],
+    query: &str,
+    frecency: &FrecencyScores,
+) -> Vec<PathMatch> {
+    let matches = fuzzy::match_fixed_path_set(
+        candidates.to_vec(),
+        0,
+        query,
+        false,
+        MAX_RERANKED_SEARCH_MATCHES,
+    );
+    rank_search_matches(matches, frecency)
+}
+
+/// Queries for a file either by its name alone, or by its name and its directory's.
+fn query_for(path: &Path, rng: &mut StdRng) -> String {
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    if rng.gen_bool(0.5) {
+        file_name.to_string()
+    } else {
+        let directory = path
+            .parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy();
+        format!("{directory}/{file_name}")
+    }
+}
+
+#[derive(Default)]
+struct Quality {
+    reciprocal_rank_sum: f64,
+    first_count: usize,
+}
+
+impl Quality {
+    fn record(&mut self, matches: &[PathMatch], target: &Path) {
+        if let Some(ix) = matches
+            .iter()
+            .position(|path_match| path_match.path.as_ref() == target)
+        {
+            self.reciprocal_rank_sum += 1. / (ix + 1) as f64;
+            if ix == 0 {
+                self.first_count += 1;
+            }
+        }
+    }
+
+    fn report(&self, name: &str) {
+        println!(
+            "{name}: mean reciprocal rank {:.3}, ranked first {:.1}%",
+            self.reciprocal_rank_sum / SESSION_LENGTH as f64,
+            self.first_count as f64 * 100. / SESSION_LENGTH as f64,
+        );
+    }
+}
+
+fn ranking_benchmarks(c: &mut Criterion) {
+    let paths = synthetic_tree();
+    let candidates = candidates(&paths);
+    let mut rng = StdRng::seed_from_u64(0);
+    let working_set = (0..WORKING_SET_SIZE)
+        .map(|_| paths[rng.gen_range(0..paths.len())].clone())
+        .collect::<Vec<_>>();
+
+    // Replays a session where a few files of the working set are opened much more often than
+    // the rest, scoring each open before it's counted towards frecency.
+    let now = now_timestamp();
+    let mut open_counts = HashMap::<PathBuf, f64>::default();
+    let mut frecency = FrecencyScores::default();
+    let mut fuzzy_quality = Quality::default();
+    let mut frecency_quality = Quality::default();
+    for _ in 0..SESSION_LENGTH {
+        let skewed = rng.gen_range(0.0f64..1.0).powi(3);
+        let target = &working_set[(skewed * WORKING_SET_SIZE as f64) as usize];
+        let query = query_for(target, &mut rng);
+
+        fuzzy_quality.record(
+            &rank(&candidates, &query, &FrecencyScores::default()),
+            target,
+        );
+        frecency_quality.record(&rank(&candidates, &query, &frecency), target);
+
+        let open_count = open_counts.entry(target.clone()).or_default();
+        *open_count += 1.;
+        frecency.insert(0, Arc::from(target.as_path()), *open_count, now, now);
+    }
+    fuzzy_quality.report("fuzzy score");
+    frecency_quality.report("fuzzy score and frecency");
+
+    let mut group = c.benchmark_group("rank 500k files");
+    for (name, frecency) in [("fuzzy", FrecencyScores::default()), ("frecency", frecency)] {
+        group.bench_function(name, |b| {
+            b.iter(|| rank(&candidates, black_box("user3/mod.rs"), &frecency))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, ranking_benchmarks);
+criterion_main!(benches);
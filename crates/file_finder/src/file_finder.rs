@@ -4,24 +4,28 @@ mod file_finder_tests;
 mod open_path_prompt_tests;
 
 pub mod file_finder_settings;
+mod frecency;
 mod open_path_prompt;
+mod persistence;
 
 use futures::future::join_all;
 pub use open_path_prompt::OpenPathDelegate;
 
 use collections::HashMap;
-use editor::Editor;
+use editor::{Anchor, Bias, Editor, SelectionEffects, scroll::Autoscroll};
 use file_finder_settings::{FileFinderSettings, FileFinderWidth};
 use file_icons::FileIcons;
-use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
+use frecency::FrecencyScores;
+use fuzzy::{CharBag, PathMatch, PathMatchCandidate, StringMatch, StringMatchCandidate};
 use gpui::{
     Action, AnyElement, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
     KeyContext, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Task, WeakEntity,
     Window, actions, rems,
 };
 use open_path_prompt::OpenPathPrompt;
+use persistence::FILE_FINDER_DB;
 use picker::{Picker, PickerDelegate};
-use project::{PathMatchCandidateSet, Project, ProjectPath, WorktreeId};
+use project::{PathMatchCandidateSet, Project, ProjectPath, Symbol, WorktreeId};
 use search::ToggleIncludeIgnored;
 use settings::Settings;
 use std::{
@@ -161,12 +165,24 @@ impl FileFinder {
                 }
             })
             .collect::<Vec<_>>();
+        let active_editor = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .map(|editor| editor.downgrade());
+        let workspace_id = workspace.database_id();
+        let opened_files = cx.background_spawn(async move {
+            workspace_id
+                .and_then(|workspace_id| FILE_FINDER_DB.get_frecency(workspace_id).log_err())
+                .unwrap_or_default()
+        });
         cx.spawn_in(window, async move |workspace, cx| {
             let history_items = join_all(history_items).await.into_iter().flatten();
+            let opened_files = opened_files.await;
 
             workspace
                 .update_in(cx, |workspace, window, cx| {
                     let project = workspace.project().clone();
+                    let frecency = frecency_scores(project.read(cx), opened_files, cx);
                     let weak_workspace = cx.entity().downgrade();
                     workspace.toggle_modal(window, cx, |window, cx| {
                         let delegate = FileFinderDelegate::new(
@@ -174,7 +190,9 @@ impl FileFinder {
                             weak_workspace,
                             project,
                             currently_opened_path,
+                            active_editor,
                             history_items.collect(),
+                            frecency,
                             separate_history,
                             window,
                             cx,
@@ -323,8 +341,10 @@ impl FileFinder {
         self.picker.update(cx, |picker, cx| {
             let delegate = &mut picker.delegate;
             if let Some(workspace) = delegate.workspace.upgrade()
+                && delegate.symbol_matches.is_none()
                 && let Some(m) = delegate.matches.get(delegate.selected_index())
             {
+                delegate.record_open(m, cx);
                 let path = match &m {
                     Match::History { path, .. } => {
                         let worktree_id = path.project.worktree_id;
@@ -401,11 +421,15 @@ pub struct FileFinderDelegate {
     latest_search_did_cancel: bool,
     latest_search_query: Option<FileSearchQuery>,
     currently_opened_path: Option<FoundPath>,
+    active_editor: Option<WeakEntity<Editor>>,
     matches: Matches,
+    /// The matches of an `@` or `#` query, which searches symbols instead of files.
+    symbol_matches: Option<Vec<SymbolMatch>>,
     selected_index: usize,
     has_changed_selected_index: bool,
     cancel_flag: Arc<AtomicBool>,
     history_items: Vec<FoundPath>,
+    frecency: Arc<FrecencyScores>,
     separate_history: bool,
     first_update: bool,
     filter_popover_menu_handle: PopoverMenuHandle<ContextMenu>,
@@ -546,6 +570,7 @@ impl Matches {
         history_items: impl IntoIterator<Item = &'a FoundPath> + Clone,
         currently_opened: Option<&'a FoundPath>,
         query: Option<&FileSearchQuery>,
+        frecency: &FrecencyScores,
         new_search_matches: impl Iterator<Item = ProjectPanelOrdMatch>,
         extend_old_matches: bool,
     ) {
@@ -562,7 +587,8 @@ impl Matches {
             return;
         };
 
-        let new_history_matches = matching_history_items(history_items, currently_opened, query);
+        let new_history_matches =
+            matching_history_items(history_items, currently_opened, query, frecency);
         let new_search_matches: Vec<Match> = new_search_matches
             .filter(|path_match| !new_history_matches.contains_key(&path_match.0.path))
             .map(Match::Search)
@@ -696,12 +722,16 @@ fn matching_history_items<'a>(
     history_items: impl IntoIterator<Item = &'a FoundPath>,
     currently_opened: Option<&'a FoundPath>,
     query: &FileSearchQuery,
+    frecency: &FrecencyScores,
 ) -> HashMap<Arc<Path>, Match> {
     let mut candidates_paths = HashMap::default();
 
     let history_items_by_worktrees = history_items
         .into_iter()
         .chain(currently_opened)
+        .filter(|found_path| {
+            query.matches(found_path.project.worktree_id, &found_path.project.path)
+        })
         .filter_map(|found_path| {
             let candidate = PathMatchCandidate {
                 is_dir: false, // You can't open directories as project items
@@ -744,7 +774,8 @@ fn matching_history_items<'a>(
                 max_results,
             )
             .into_iter()
-            .filter_map(|path_match| {
+            .filter_map(|mut path_match| {
+                frecency.boost(&mut path_match);
                 candidates_paths
                     .remove_entry(&ProjectPath {
                         worktree_id: WorktreeId::from_usize(path_match.worktree_id),
@@ -778,6 +809,10 @@ impl FoundPath {
 }
 
 const MAX_RECENT_SELECTIONS: usize = 20;
+const MAX_SEARCH_MATCHES: usize = 100;
+/// How many fuzzy matches are reranked by frecency or filtered, out of which the best
+/// [`MAX_SEARCH_MATCHES`] are shown.
+const MAX_RERANKED_SEARCH_MATCHES: usize = 1000;
 
 pub enum Event {
     Selected(ProjectPath),
@@ -789,6 +824,10 @@ struct FileSearchQuery {
     raw_query: String,
     file_query_end: Option<usize>,
     path_position: PathWithPosition,
+    /// The lowercase components of the query's directory segments, see [`directory_segments`].
+    directory_segments: Vec<Vec<String>>,
+    /// The worktree to search, when the first directory segment names one of several worktrees.
+    worktree_id: Option<WorktreeId>,
 }
 
 impl FileSearchQuery {
@@ -798,6 +837,148 @@ impl FileSearchQuery {
             None => &self.raw_query,
         }
     }
+
+    /// Whether a file that matches the path query also satisfies the query's filters.
+    fn matches(&self, worktree_id: WorktreeId, path: &Path) -> bool {
+        self.worktree_id.is_none_or(|id| id == worktree_id)
+            && matches_directory_segments(path, &self.directory_segments)
+    }
+
+    /// Whether the fuzzy matches need more candidates than are shown, as they're reranked or
+    /// filtered afterwards.
+    fn reranks(&self, frecency: &FrecencyScores) -> bool {
+        !frecency.is_empty() || !self.directory_segments.is_empty() || self.worktree_id.is_some()
+    }
+}
+
+/// Returns the directory segments of a query made of several space-separated terms: the terms
+/// that end with a path separator, like `api/` in `api/ user`, split into lowercase components.
+fn directory_segments(raw_query: &str) -> Vec<Vec<String>> {
+    let terms = raw_query.split_whitespace().collect::<Vec<_>>();
+    if terms.len() < 2 {
+        return Vec::new();
+    }
+    terms
+        .into_iter()
+        .filter(|term| term.ends_with(['/', '\\']))
+        .map(|term| {
+            term.split(['/', '\\'])
+                .filter(|component| !component.is_empty() && *component != ".")
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+        })
+        .filter(|components| !components.is_empty())
+        .collect()
+}
+
+/// Whether the directories of `path` contain each segment in order, as consecutive directories
+/// whose names start with the segment's components.
+fn matches_directory_segments(path: &Path, segments: &[Vec<String>]) -> bool {
+    if segments.is_empty() {
+        return true;
+    }
+    let directories = path
+        .parent()
+        .into_iter()
+        .flat_map(|parent| parent.components())
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_lowercase()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut start = 0;
+    'segments: for segment in segments {
+        while start + segment.len() <= directories.len() {
+            if segment
+                .iter()
+                .zip(&directories[start..])
+                .all(|(component, directory)| directory.starts_with(component.as_str()))
+            {
+                start += segment.len();
+                continue 'segments;
+            }
+            start += 1;
+        }
+        return false;
+    }
+    true
+}
+
+/// Boosts the fuzzy matches of a search by the frecency of their files, and keeps the best
+/// [`MAX_SEARCH_MATCHES`] of them, best first.
+fn rank_search_matches(
+    matches: Vec<PathMatch>,
+    frecency: &FrecencyScores,
+) -> Vec<ProjectPanelOrdMatch> {
+    let mut matches = matches
+        .into_iter()
+        .map(|mut path_match| {
+            frecency.boost(&mut path_match);
+            ProjectPanelOrdMatch(path_match)
+        })
+        .collect::<Vec<_>>();
+    matches.sort_unstable_by(|a, b| b.cmp(a));
+    matches.truncate(MAX_SEARCH_MATCHES);
+    matches
+}
+
+/// The file finder's ranking, for the ranking benchmark.
+#[cfg(feature = "bench")]
+pub mod bench {
+    use fuzzy::PathMatch;
+
+    pub use crate::frecency::{FrecencyScores, now_timestamp};
+
+    pub const MAX_RERANKED_SEARCH_MATCHES: usize = crate::MAX_RERANKED_SEARCH_MATCHES;
+
+    /// Ranks the fuzzy matches of a search the way the file finder does.
+    pub fn rank_search_matches(
+        matches: Vec<PathMatch>,
+        frecency: &FrecencyScores,
+    ) -> Vec<PathMatch> {
+        crate::rank_search_matches(matches, frecency)
+            .into_iter()
+            .map(|path_match| path_match.0)
+            .collect()
+    }
+}
+
+/// Resolves the files opened from the file finder in a workspace to the project's worktrees.
+fn frecency_scores(
+    project: &Project,
+    opened_files: Vec<(Arc<Path>, f64, i64)>,
+    cx: &App,
+) -> FrecencyScores {
+    let now = frecency::now_timestamp();
+    let mut scores = FrecencyScores::default();
+    for (abs_path, open_count, last_opened) in opened_files {
+        if let Some((worktree, path)) = project.find_worktree(&abs_path, cx) {
+            scores.insert(
+                worktree.read(cx).id().to_usize(),
+                Arc::from(path),
+                open_count,
+                last_opened,
+                now,
+            );
+        }
+    }
+    scores
+}
+
+/// A symbol matching an `@` query, which searches the symbols of the active editor, or a `#`
+/// query, which searches the symbols of the whole project.
+#[derive(Debug, Clone)]
+struct SymbolMatch {
+    label: String,
+    positions: Vec<usize>,
+    detail: Option<String>,
+    target: SymbolTarget,
+}
+
+#[derive(Debug, Clone)]
+enum SymbolTarget {
+    Document(Range<Anchor>),
+    Workspace(Symbol),
 }
 
 impl FileFinderDelegate {
@@ -806,7 +987,9 @@ impl FileFinderDelegate {
         workspace: WeakEntity<Workspace>,
         project: Entity<Project>,
         currently_opened_path: Option<FoundPath>,
+        active_editor: Option<WeakEntity<Editor>>,
         history_items: Vec<FoundPath>,
+        frecency: FrecencyScores,
         separate_history: bool,
         window: &mut Window,
         cx: &mut Context<FileFinder>,
@@ -821,11 +1004,14 @@ impl FileFinderDelegate {
             latest_search_did_cancel: false,
             latest_search_query: None,
             currently_opened_path,
+            active_editor,
             matches: Matches::default(),
+            symbol_matches: None,
             has_changed_selected_index: false,
             selected_index: 0,
             cancel_flag: Arc::new(AtomicBool::new(false)),
             history_items,
+            frecency: Arc::new(frecency),
             separate_history,
             first_update: true,
             filter_popover_menu_handle: PopoverMenuHandle::default(),
@@ -872,6 +1058,11 @@ impl FileFinderDelegate {
         let include_root_name = worktrees.len() > 1;
         let candidate_sets = worktrees
             .into_iter()
+            .filter(|worktree| {
+                query
+                    .worktree_id
+                    .is_none_or(|worktree_id| worktree.read(cx).id() == worktree_id)
+            })
             .map(|worktree| {
                 let worktree = worktree.read(cx);
                 PathMatchCandidateSet {
@@ -889,19 +1080,32 @@ impl FileFinderDelegate {
         self.cancel_flag.store(true, atomic::Ordering::Relaxed);
         self.cancel_flag = Arc::new(AtomicBool::new(false));
         let cancel_flag = self.cancel_flag.clone();
+        let frecency = self.frecency.clone();
         cx.spawn_in(window, async move |picker, cx| {
+            let max_results = if query.reranks(&frecency) {
+                MAX_RERANKED_SEARCH_MATCHES
+            } else {
+                MAX_SEARCH_MATCHES
+            };
             let matches = fuzzy::match_path_sets(
                 candidate_sets.as_slice(),
                 query.path_query(),
                 relative_to,
                 false,
-                100,
+                max_results,
                 &cancel_flag,
                 cx.background_executor().clone(),
             )
             .await
             .into_iter()
-            .map(ProjectPanelOrdMatch);
+            .filter(|path_match| {
+                query.matches(
+                    WorktreeId::from_usize(path_match.worktree_id),
+                    &path_match.path,
+                )
+            })
+            .collect();
+            let matches = rank_search_matches(matches, &frecency);
             let did_cancel = cancel_flag.load(atomic::Ordering::Relaxed);
             picker
                 .update(cx, |picker, cx| {
@@ -940,6 +1144,7 @@ impl FileFinderDelegate {
                 &self.history_items,
                 self.currently_opened_path.as_ref(),
                 Some(&query),
+                &self.frecency,
                 matches.into_iter(),
                 extend_old_matches,
             );
@@ -1235,6 +1440,263 @@ impl FileFinderDelegate {
         })
     }
 
+    fn spawn_document_symbol_search(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let outline = self
+            .active_editor
+            .as_ref()
+            .and_then(|editor| editor.upgrade())
+            .and_then(|editor| editor.read(cx).buffer().read(cx).snapshot(cx).outline(None));
+        let search_id = util::post_inc(&mut self.search_count);
+        let Some(outline) = outline else {
+            self.set_symbol_matches(search_id, Vec::new(), cx);
+            return Task::ready(());
+        };
+        let candidates = outline
+            .items
+            .iter()
+            .enumerate()
+            .map(|(id, item)| StringMatchCandidate::new(id, &item.text))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, async move |picker, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.,
+                        positions: Vec::new(),
+                        string: candidate.string,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    MAX_SEARCH_MATCHES,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            let symbol_matches = matches
+                .into_iter()
+                .map(|string_match| {
+                    let item = &outline.items[string_match.candidate_id];
+                    SymbolMatch {
+                        label: string_match.string,
+                        positions: string_match.positions,
+                        detail: None,
+                        target: SymbolTarget::Document(item.range.clone()),
+                    }
+                })
+                .collect();
+            picker
+                .update(cx, |picker, cx| {
+                    picker
+                        .delegate
+                        .set_symbol_matches(search_id, symbol_matches, cx)
+                })
+                .log_err();
+        })
+    }
+
+    fn spawn_workspace_symbol_search(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let search_id = util::post_inc(&mut self.search_count);
+        if query.is_empty() {
+            self.set_symbol_matches(search_id, Vec::new(), cx);
+            return Task::ready(());
+        }
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.symbols(&query, cx));
+        cx.spawn_in(window, async move |picker, cx| {
+            let symbols = symbols.await.log_err().unwrap_or_default();
+            let candidates = symbols
+                .iter()
+                .enumerate()
+                .map(|(id, symbol)| StringMatchCandidate::new(id, symbol.label.filter_text()))
+                .collect::<Vec<_>>();
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                true,
+                MAX_SEARCH_MATCHES,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(cx, |picker, cx| {
+                    let project = picker.delegate.project.read(cx);
+                    let include_root_name = project.visible_worktrees(cx).count() > 1;
+                    let symbol_matches = matches
+                        .into_iter()
+                        .map(|string_match| {
+                            let symbol = &symbols[string_match.candidate_id];
+                            let filter_start = symbol.label.filter_range.start;
+                            let mut detail = symbol.path.path.to_string_lossy().to_string();
+                            if include_root_name
+                                && let Some(worktree) =
+                                    project.worktree_for_id(symbol.path.worktree_id, cx)
+                            {
+                                detail = format!(
+                                    "{}{}{detail}",
+                                    worktree.read(cx).root_name(),
+                                    std::path::MAIN_SEPARATOR,
+                                );
+                            }
+                            SymbolMatch {
+                                label: symbol.label.text.clone(),
+                                positions: string_match
+                                    .positions
+                                    .iter()
+                                    .map(|position| position + filter_start)
+                                    .collect(),
+                                detail: Some(detail),
+                                target: SymbolTarget::Workspace(symbol.clone()),
+                            }
+                        })
+                        .collect();
+                    picker
+                        .delegate
+                        .set_symbol_matches(search_id, symbol_matches, cx)
+                })
+                .log_err();
+        })
+    }
+
+    fn set_symbol_matches(
+        &mut self,
+        search_id: usize,
+        symbol_matches: Vec<SymbolMatch>,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        if search_id >= self.latest_search_id {
+            self.latest_search_id = search_id;
+            self.symbol_matches = Some(symbol_matches);
+            self.selected_index = 0;
+            cx.notify();
+        }
+    }
+
+    fn confirm_symbol(
+        &mut self,
+        secondary: bool,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        let Some(symbol_match) = self
+            .symbol_matches
+            .as_ref()
+            .and_then(|symbol_matches| symbol_matches.get(self.selected_index))
+        else {
+            return;
+        };
+        match &symbol_match.target {
+            SymbolTarget::Document(range) => {
+                if let Some(editor) = self.active_editor.as_ref().and_then(|e| e.upgrade()) {
+                    let position = range.start;
+                    editor.update(cx, |editor, cx| {
+                        editor.change_selections(
+                            SelectionEffects::scroll(Autoscroll::center()),
+                            window,
+                            cx,
+                            |s| s.select_anchor_ranges([position..position]),
+                        );
+                        window.focus(&editor.focus_handle(cx));
+                    });
+                }
+            }
+            SymbolTarget::Workspace(symbol) => {
+                let buffer = self
+                    .project
+                    .update(cx, |project, cx| project.open_buffer_for_symbol(symbol, cx));
+                let symbol = symbol.clone();
+                let workspace = self.workspace.clone();
+                cx.spawn_in(window, async move |_, cx| {
+                    let buffer = buffer.await?;
+                    workspace.update_in(cx, |workspace, window, cx| {
+                        let position = buffer
+                            .read(cx)
+                            .clip_point_utf16(symbol.range.start, Bias::Left);
+                        let pane = if secondary {
+                            workspace.adjacent_pane(window, cx)
+                        } else {
+                            workspace.active_pane().clone()
+                        };
+                        let editor = workspace
+                            .open_project_item::<Editor>(pane, buffer, true, true, window, cx);
+                        editor.update(cx, |editor, cx| {
+                            editor.change_selections(
+                                SelectionEffects::scroll(Autoscroll::center()),
+                                window,
+                                cx,
+                                |s| s.select_ranges([position..position]),
+                            );
+                        });
+                    })?;
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+        }
+        self.dismissed(window, cx);
+    }
+
+    /// Counts an open of the match's file towards its frecency in the workspace.
+    fn record_open(&self, path_match: &Match, cx: &App) {
+        let Some(workspace_id) = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).database_id())
+        else {
+            return;
+        };
+        let Some(abs_path) = path_match.abs_path(&self.project, cx) else {
+            return;
+        };
+        db::write_and_log(cx, move || {
+            FILE_FINDER_DB.record_open(workspace_id, Arc::from(abs_path), frecency::now_timestamp())
+        });
+    }
+
+    /// Takes the first component of the query's directory segments as the worktree to search,
+    /// when the project has several and it's the root name of one of them.
+    fn worktree_filter(
+        &self,
+        directory_segments: &mut Vec<Vec<String>>,
+        cx: &App,
+    ) -> Option<WorktreeId> {
+        let project = self.project.read(cx);
+        if project.visible_worktrees(cx).count() < 2 {
+            return None;
+        }
+        let first_segment = directory_segments.first_mut()?;
+        let root_name = first_segment.first()?;
+        let worktree_id = project.visible_worktrees(cx).find_map(|worktree| {
+            let worktree = worktree.read(cx);
+            (worktree.root_name().to_lowercase() == *root_name).then(|| worktree.id())
+        })?;
+        first_segment.remove(0);
+        if first_segment.is_empty() {
+            directory_segments.remove(0);
+        }
+        Some(worktree_id)
+    }
+
     /// Skips first history match (that is displayed topmost) if it's currently opened.
     fn calculate_selected_index(&self, cx: &mut Context<Picker<Self>>) -> usize {
         if FileFinderSettings::get_global(cx).skip_focus_for_active_in_search
@@ -1282,7 +1744,9 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn match_count(&self) -> usize {
-        self.matches.len()
+        self.symbol_matches
+            .as_ref()
+            .map_or_else(|| self.matches.len(), Vec::len)
     }
 
     fn selected_index(&self) -> usize {
@@ -1296,7 +1760,7 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn separators_after_indices(&self) -> Vec<usize> {
-        if self.separate_history {
+        if self.separate_history && self.symbol_matches.is_none() {
             let first_non_history_index = self
                 .matches
                 .matches
@@ -1319,6 +1783,17 @@ impl PickerDelegate for FileFinderDelegate {
         window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) -> Task<()> {
+        let symbol_query = raw_query.trim_start();
+        if let Some(symbol_query) = symbol_query.strip_prefix('@') {
+            return self.spawn_document_symbol_search(symbol_query.trim().to_owned(), window, cx);
+        } else if let Some(symbol_query) = symbol_query.strip_prefix('#') {
+            return self.spawn_workspace_symbol_search(symbol_query.trim().to_owned(), window, cx);
+        }
+        if self.symbol_matches.take().is_some() {
+            self.selected_index = 0;
+        }
+
+        let mut directory_segments = directory_segments(&raw_query);
         let raw_query = raw_query.replace(' ', "");
         let raw_query = raw_query.trim();
 
@@ -1386,6 +1861,7 @@ impl PickerDelegate for FileFinderDelegate {
                     }),
                     self.currently_opened_path.as_ref(),
                     None,
+                    &self.frecency,
                     None.into_iter(),
                     false,
                 );
@@ -1413,10 +1889,13 @@ impl PickerDelegate for FileFinderDelegate {
                 Some(path.unwrap().len())
             };
 
+            let worktree_id = self.worktree_filter(&mut directory_segments, cx);
             let query = FileSearchQuery {
                 raw_query,
                 file_query_end,
                 path_position,
+                directory_segments,
+                worktree_id,
             };
 
             if Path::new(query.path_query()).is_absolute() {
@@ -1433,9 +1912,14 @@ impl PickerDelegate for FileFinderDelegate {
         window: &mut Window,
         cx: &mut Context<Picker<FileFinderDelegate>>,
     ) {
+        if self.symbol_matches.is_some() {
+            self.confirm_symbol(secondary, window, cx);
+            return;
+        }
         if let Some(m) = self.matches.get(self.selected_index())
             && let Some(workspace) = self.workspace.upgrade()
         {
+            self.record_open(m, cx);
             let open_task = workspace.update(cx, |workspace, cx| {
                 let split_or_open =
                     |workspace: &mut Workspace,
@@ -1597,6 +2081,35 @@ impl PickerDelegate for FileFinderDelegate {
         window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) -> Option<Self::ListItem> {
+        if let Some(symbol_matches) = &self.symbol_matches {
+            let symbol_match = symbol_matches.get(ix)?;
+            return Some(
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .start_slot(
+                        Icon::new(IconName::Code)
+                            .color(Color::Muted)
+                            .size(IconSize::Small),
+                    )
+                    .inset(true)
+                    .toggle_state(selected)
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .py_px()
+                            .child(HighlightedLabel::new(
+                                symbol_match.label.clone(),
+                                symbol_match.positions.clone(),
+                            ))
+                            .children(symbol_match.detail.clone().map(|detail| {
+                                Label::new(detail)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                            })),
+                    ),
+            );
+        }
+
         let settings = FileFinderSettings::get_global(cx);
 
         let path_match = self
@@ -2722,6 +2722,8 @@ fn test_path_position(test_str: &str) -> FileSearchQuery {
             Some(path_position.path.to_str().unwrap().len())
         },
         path_position,
+        directory_segments: Vec::new(),
+        worktree_id: None,
     }
 }
 
@@ -2911,3 +2913,189 @@ async fn test_filename_precedence(cx: &mut TestAppContext) {
         );
     });
 }
+
+#[test]
+fn test_directory_segments() {
+    assert_eq!(directory_segments("user"), Vec::<Vec<String>>::new());
+    assert_eq!(directory_segments("api/"), Vec::<Vec<String>>::new());
+    assert_eq!(directory_segments("api/user"), Vec::<Vec<String>>::new());
+    assert_eq!(
+        directory_segments("api/ user"),
+        vec![vec!["api".to_string()]]
+    );
+    assert_eq!(
+        directory_segments("Src/Api/ v1/ user"),
+        vec![
+            vec!["src".to_string(), "api".to_string()],
+            vec!["v1".to_string()]
+        ]
+    );
+
+    let segments = directory_segments("api/ v1/ user");
+    assert!(matches_directory_segments(
+        Path::new("src/api/v1/user.rs"),
+        &segments
+    ));
+    assert!(matches_directory_segments(
+        Path::new("apis/v1beta/user.rs"),
+        &segments
+    ));
+    assert!(!matches_directory_segments(
+        Path::new("v1/api/user.rs"),
+        &segments
+    ));
+    // The file name itself is not a directory.
+    assert!(!matches_directory_segments(
+        Path::new("api/v1.rs"),
+        &segments
+    ));
+}
+
+#[gpui::test]
+async fn test_directory_segment_query(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            path!("/root"),
+            json!({
+                "api": {
+                    "user.rs": "",
+                    "v1": { "user_test.rs": "" },
+                },
+                "apps": { "pi": { "user.rs": "" } },
+                "web": { "user.rs": "" },
+                "user_api.rs": "",
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), [path!("/root").as_ref()], cx).await;
+    let (picker, _, cx) = build_find_picker(project, cx);
+
+    cx.simulate_input("api/ user");
+    picker.update(cx, |picker, _| {
+        let mut search_matches = collect_search_matches(picker).search_paths_only();
+        search_matches.sort();
+        assert_eq!(
+            search_matches,
+            vec![
+                PathBuf::from(path!("api/user.rs")),
+                PathBuf::from(path!("api/v1/user_test.rs")),
+            ],
+            "Only files under a directory starting with 'api' should match"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_worktree_name_query(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(path!("/app"), json!({ "src": { "main.rs": "" } }))
+        .await;
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(path!("/app-server"), json!({ "src": { "main.rs": "" } }))
+        .await;
+
+    let project = Project::test(
+        app_state.fs.clone(),
+        [path!("/app").as_ref(), path!("/app-server").as_ref()],
+        cx,
+    )
+    .await;
+    let (picker, workspace, cx) = build_find_picker(project, cx);
+    let app_id = cx.read(|cx| {
+        let worktrees = workspace.read(cx).worktrees(cx).collect::<Vec<_>>();
+        worktrees[0].read(cx).id()
+    });
+
+    cx.simulate_input("main");
+    picker.update(cx, |picker, _| {
+        assert_eq!(
+            collect_search_matches(picker).search_matches_only().len(),
+            2
+        );
+    });
+
+    for query in ["app/ main", "App/src/ main"] {
+        picker
+            .update_in(cx, |picker, window, cx| {
+                picker
+                    .delegate
+                    .update_matches(query.to_string(), window, cx)
+            })
+            .await;
+        picker.update(cx, |picker, _| {
+            let search_matches = collect_search_matches(picker).search_matches_only();
+            assert_eq!(search_matches.len(), 1, "Wrong matches for query '{query}'");
+            assert_eq!(
+                search_matches[0].worktree_id,
+                app_id.to_usize(),
+                "Wrong worktree for query '{query}'"
+            );
+        });
+    }
+}
+
+#[gpui::test]
+async fn test_frecency_ranking(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            path!("/root"),
+            json!({
+                "a": { "mod.rs": "" },
+                "b": { "mod.rs": "" },
+                "c": { "mod.rs": "" },
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), [path!("/root").as_ref()], cx).await;
+    let (picker, workspace, cx) = build_find_picker(project, cx);
+    let worktree_id = cx.read(|cx| {
+        let worktrees = workspace.read(cx).worktrees(cx).collect::<Vec<_>>();
+        worktrees[0].read(cx).id()
+    });
+
+    let now = frecency::now_timestamp();
+    let mut frecency = FrecencyScores::default();
+    frecency.insert(
+        worktree_id.to_usize(),
+        Arc::from(Path::new(path!("c/mod.rs"))),
+        3.,
+        now,
+        now,
+    );
+    frecency.insert(
+        worktree_id.to_usize(),
+        Arc::from(Path::new(path!("b/mod.rs"))),
+        1.,
+        now,
+        now,
+    );
+    picker.update(cx, |picker, _| {
+        picker.delegate.frecency = Arc::new(frecency);
+    });
+
+    cx.simulate_input("mod.rs");
+    picker.update(cx, |picker, _| {
+        assert_eq!(
+            collect_search_matches(picker).search_paths_only(),
+            vec![
+                PathBuf::from(path!("c/mod.rs")),
+                PathBuf::from(path!("b/mod.rs")),
+                PathBuf::from(path!("a/mod.rs")),
+            ],
+            "Files opened more often should rank first among equal matches"
+        );
+    });
+}
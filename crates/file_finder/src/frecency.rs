@@ -0,0 +1,129 @@
+//! Ranks the files of a project by how often and how recently they were opened from the file
+//! finder, so that the `mod.rs` or `index.ts` one works on wins over its many namesakes.
+
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use collections::HashMap;
+use fuzzy::PathMatch;
+
+/// How much a file's frecency weighs against its fuzzy match score.
+const FRECENCY_WEIGHT: f64 = 0.1;
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Returns how much a file that was opened `open_count` times should be favored, given the
+/// time since it was last opened. The weights of recent opens are the ones zoxide uses.
+pub fn frecency(open_count: f64, since_last_open: Duration) -> f64 {
+    let recency = if since_last_open < HOUR {
+        4.
+    } else if since_last_open < DAY {
+        2.
+    } else if since_last_open < WEEK {
+        0.5
+    } else {
+        0.25
+    };
+    open_count * recency
+}
+
+/// Returns the current time as a Unix timestamp, which is how open times are stored.
+pub fn now_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+/// The frecency of the opened files of the worktrees the file finder searches.
+#[derive(Clone, Debug, Default)]
+pub struct FrecencyScores {
+    scores: HashMap<(usize, Arc<Path>), f64>,
+}
+
+impl FrecencyScores {
+    /// Records a file of the given worktree, opened `open_count` times and last at `last_opened`.
+    pub fn insert(
+        &mut self,
+        worktree_id: usize,
+        path: Arc<Path>,
+        open_count: f64,
+        last_opened: i64,
+        now: i64,
+    ) {
+        let since_last_open = Duration::from_secs(now.saturating_sub(last_opened).max(0) as u64);
+        self.scores
+            .insert((worktree_id, path), frecency(open_count, since_last_open));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, worktree_id: usize, path: &Arc<Path>) -> f64 {
+        self.scores
+            .get(&(worktree_id, path.clone()))
+            .copied()
+            .unwrap_or(0.)
+    }
+
+    /// Scales the fuzzy score of a match up by the frecency of its file. The boost grows
+    /// logarithmically, so that it breaks near ties without burying a much better match.
+    pub fn boost(&self, path_match: &mut PathMatch) {
+        let score = self.score(path_match.worktree_id, &path_match.path);
+        if score > 0. {
+            path_match.score *= 1. + FRECENCY_WEIGHT * score.ln_1p();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frecency() {
+        assert_eq!(frecency(3., Duration::from_secs(60)), 12.);
+        assert_eq!(frecency(3., 2 * HOUR), 6.);
+        assert_eq!(frecency(3., 2 * DAY), 1.5);
+        assert_eq!(frecency(3., 2 * WEEK), 0.75);
+
+        // A file opened once just now beats one opened twice long ago.
+        assert!(frecency(1., Duration::ZERO) > frecency(2., 2 * WEEK));
+    }
+
+    #[test]
+    fn test_boost() {
+        let path_match = |path: &str| PathMatch {
+            score: 0.5,
+            positions: Vec::new(),
+            worktree_id: 0,
+            path: Arc::from(Path::new(path)),
+            path_prefix: "".into(),
+            is_dir: false,
+            distance_to_relative_ancestor: usize::MAX,
+        };
+
+        let now = now_timestamp();
+        let mut scores = FrecencyScores::default();
+        scores.insert(0, Arc::from(Path::new("a/mod.rs")), 1., now - 60, now);
+        scores.insert(0, Arc::from(Path::new("b/mod.rs")), 5., now - 60, now);
+
+        let mut unopened = path_match("c/mod.rs");
+        let mut opened_once = path_match("a/mod.rs");
+        let mut opened_often = path_match("b/mod.rs");
+        scores.boost(&mut unopened);
+        scores.boost(&mut opened_once);
+        scores.boost(&mut opened_often);
+
+        assert_eq!(unopened.score, 0.5);
+        assert!(opened_once.score > unopened.score);
+        assert!(opened_often.score > opened_once.score);
+        // Frecency only breaks near ties.
+        assert!(opened_often.score < 1.);
+    }
+}
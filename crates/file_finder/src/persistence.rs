@@ -0,0 +1,141 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+/// Once the open counts of a workspace's files add up to more than this, they're all aged,
+/// which eventually forgets the files that aren't opened anymore.
+const MAX_TOTAL_OPEN_COUNT: f64 = 2000.;
+
+/// The factor open counts are multiplied by when they're aged.
+const OPEN_COUNT_AGING: f64 = 0.9;
+
+pub struct FileFinderDb(ThreadSafeConnection);
+
+impl Domain for FileFinderDb {
+    const NAME: &str = stringify!(FileFinderDb);
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE file_finder_frecency(
+            workspace_id INTEGER NOT NULL,
+            abs_path BLOB NOT NULL,
+            open_count REAL NOT NULL,
+            last_opened INTEGER NOT NULL,
+            PRIMARY KEY(workspace_id, abs_path),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(FILE_FINDER_DB, FileFinderDb, [WorkspaceDb]);
+
+impl FileFinderDb {
+    query! {
+        pub fn get_frecency(workspace_id: WorkspaceId) -> Result<Vec<(Arc<Path>, f64, i64)>> {
+            SELECT abs_path, open_count, last_opened
+            FROM file_finder_frecency
+            WHERE workspace_id = (?)
+        }
+    }
+
+    /// Counts an open of the file at `abs_path` in the given workspace, at the given Unix time.
+    pub async fn record_open(
+        &self,
+        workspace_id: WorkspaceId,
+        abs_path: Arc<Path>,
+        opened_at: i64,
+    ) -> Result<()> {
+        log::debug!("Recording open of {abs_path:?} in workspace {workspace_id:?}");
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT INTO file_finder_frecency
+                    (workspace_id, abs_path, open_count, last_opened)
+                VALUES
+                    (?1, ?2, 1, ?3)
+                ON CONFLICT DO UPDATE SET
+                    open_count = open_count + 1,
+                    last_opened = ?3
+            ))?((workspace_id, abs_path, opened_at))?;
+            conn.exec_bound(sql!(
+                UPDATE file_finder_frecency
+                SET open_count = open_count * ?2
+                WHERE workspace_id = ?1 AND (
+                    SELECT SUM(open_count) FROM file_finder_frecency WHERE workspace_id = ?1
+                ) > ?3
+            ))?((workspace_id, OPEN_COUNT_AGING, MAX_TOTAL_OPEN_COUNT))?;
+            conn.exec_bound(sql!(
+                DELETE FROM file_finder_frecency
+                WHERE workspace_id = ? AND open_count < 1
+            ))?(workspace_id)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_record_open() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let other_workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let abs_path: Arc<Path> = Arc::from(Path::new("/project/src/mod.rs"));
+
+        assert_eq!(
+            FILE_FINDER_DB.get_frecency(workspace_id).unwrap(),
+            Vec::new()
+        );
+
+        FILE_FINDER_DB
+            .record_open(workspace_id, abs_path.clone(), 10)
+            .await
+            .unwrap();
+        FILE_FINDER_DB
+            .record_open(workspace_id, abs_path.clone(), 20)
+            .await
+            .unwrap();
+        FILE_FINDER_DB
+            .record_open(other_workspace_id, abs_path.clone(), 30)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            FILE_FINDER_DB.get_frecency(workspace_id).unwrap(),
+            vec![(abs_path.clone(), 2., 20)]
+        );
+        assert_eq!(
+            FILE_FINDER_DB.get_frecency(other_workspace_id).unwrap(),
+            vec![(abs_path, 1., 30)]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_open_counts_age() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let old_path: Arc<Path> = Arc::from(Path::new("/project/old.rs"));
+        let new_path: Arc<Path> = Arc::from(Path::new("/project/new.rs"));
+
+        FILE_FINDER_DB
+            .record_open(workspace_id, old_path, 0)
+            .await
+            .unwrap();
+        for opened_at in 1..=MAX_TOTAL_OPEN_COUNT as i64 {
+            FILE_FINDER_DB
+                .record_open(workspace_id, new_path.clone(), opened_at)
+                .await
+                .unwrap();
+        }
+
+        // The counts went over the limit, which forgot the file that was opened only once.
+        let frecency = FILE_FINDER_DB.get_frecency(workspace_id).unwrap();
+        assert_eq!(frecency.len(), 1);
+        assert_eq!(frecency[0].0, new_path);
+        assert!(frecency[0].1 < MAX_TOTAL_OPEN_COUNT);
+    }
+}
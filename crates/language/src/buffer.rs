@@ -58,9 +58,9 @@ use text::operation_queue::OperationQueue;
 use text::*;
 pub use text::{
     Anchor, Bias, Buffer as TextBuffer, BufferId, BufferSnapshot as TextBufferSnapshot, Edit,
-    LineIndent, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, Selection,
-    SelectionGoal, Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint,
-    ToPointUtf16, Transaction, TransactionId, Unclipped,
+    LineIndent, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, SavedUndoState,
+    Selection, SelectionGoal, Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16,
+    ToPoint, ToPointUtf16, Transaction, TransactionId, Unclipped, UndoState, UndoTree,
 };
 use theme::{ActiveTheme as _, SyntaxTheme};
 #[cfg(any(test, feature = "test-support"))]
@@ -2766,6 +2766,55 @@ impl Buffer {
         redone
     }
 
+    /// Undoes and redoes transactions until the buffer is in the given state of its undo tree,
+    /// where `None` is the tree's root.
+    pub fn undo_tree_move_to(
+        &mut self,
+        target: Option<TransactionId>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let was_dirty = self.is_dirty();
+        let old_version = self.version.clone();
+
+        let operations = self.text.undo_tree_move_to(target);
+        let moved = !operations.is_empty();
+        for operation in operations {
+            self.send_operation(Operation::Buffer(operation), true, cx);
+        }
+        if moved {
+            self.did_edit(&old_version, was_dirty, cx)
+        }
+        moved
+    }
+
+    /// Rebuilds an undo tree that was saved from a previous buffer with the same text. See
+    /// [`TextBuffer::restore_undo_tree`].
+    pub fn restore_undo_tree(
+        &mut self,
+        root_text: &str,
+        states: &[SavedUndoState],
+        current: Option<u64>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let was_dirty = self.is_dirty();
+        let old_version = self.version.clone();
+
+        let Some(operations) = self.text.restore_undo_tree(root_text, states, current) else {
+            return false;
+        };
+        for operation in operations {
+            self.send_operation(Operation::Buffer(operation), true, cx);
+        }
+        if !was_dirty {
+            // The text is the same as before, so it still matches the saved file.
+            self.saved_version = self.version.clone();
+            self.has_unsaved_edits
+                .set((self.saved_version.clone(), false));
+        }
+        self.did_edit(&old_version, was_dirty, cx);
+        true
+    }
+
     /// Override current completion triggers with the user-provided completion triggers.
     pub fn set_completion_triggers(
         &mut self,
@@ -414,6 +414,32 @@ impl Sub<TokenUsage> for TokenUsage {
     }
 }
 
+/// The price of a model's tokens, in US dollars per million tokens.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ModelPricing {
+    /// The price of uncached input tokens.
+    pub input: f64,
+    /// The price of output tokens, including reasoning tokens.
+    pub output: f64,
+    /// The price of input tokens written to the prompt cache.
+    #[serde(default)]
+    pub cache_write: f64,
+    /// The price of input tokens read from the prompt cache.
+    #[serde(default)]
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    /// Returns the cost of the given usage, in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct LanguageModelToolUseId(Arc<str>);
 
@@ -503,6 +529,11 @@ pub trait LanguageModel: Send + Sync {
         None
     }
 
+    /// The list price of the model's tokens, if known.
+    fn pricing(&self) -> Option<ModelPricing> {
+        None
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -848,4 +879,23 @@ mod tests {
             ),
         }
     }
+
+    #[test]
+    fn test_model_pricing_cost() {
+        let pricing = ModelPricing {
+            input: 3.0,
+            output: 15.0,
+            cache_write: 3.75,
+            cache_read: 0.3,
+        };
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 200_000,
+            cache_read_input_tokens: 2_000_000,
+        };
+        let cost = pricing.cost(&usage);
+        assert!((cost - (3.0 + 1.5 + 0.75 + 0.6)).abs() < 1e-9, "{cost}");
+        assert_eq!(pricing.cost(&TokenUsage::default()), 0.0);
+    }
 }
@@ -19,7 +19,7 @@ use language_model::{
     LanguageModelCacheConfiguration, LanguageModelCompletionError, LanguageModelId,
     LanguageModelName, LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
-    LanguageModelToolResultContent, MessageContent, RateLimiter, Role,
+    LanguageModelToolResultContent, MessageContent, ModelPricing, RateLimiter, Role,
 };
 use language_model::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
 use schemars::JsonSchema;
@@ -487,6 +487,10 @@ impl LanguageModel for AnthropicModel {
         Some(self.model.max_output_tokens())
     }
 
+    fn pricing(&self) -> Option<ModelPricing> {
+        anthropic_pricing(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -911,6 +915,34 @@ fn update_usage(usage: &mut Usage, new: &Usage) {
     }
 }
 
+/// Anthropic's list prices. Prompt cache writes are billed at the five-minute cache rate.
+pub fn anthropic_pricing(model: &anthropic::Model) -> Option<ModelPricing> {
+    use anthropic::Model;
+
+    let (input, output) = match model {
+        Model::ClaudeOpus4
+        | Model::ClaudeOpus4_1
+        | Model::ClaudeOpus4Thinking
+        | Model::ClaudeOpus4_1Thinking
+        | Model::Claude3Opus => (15.0, 75.0),
+        Model::ClaudeSonnet4
+        | Model::ClaudeSonnet4Thinking
+        | Model::Claude3_7Sonnet
+        | Model::Claude3_7SonnetThinking
+        | Model::Claude3_5Sonnet
+        | Model::Claude3Sonnet => (3.0, 15.0),
+        Model::Claude3_5Haiku => (0.8, 4.0),
+        Model::Claude3Haiku => (0.25, 1.25),
+        Model::Custom { .. } => return None,
+    };
+    Some(ModelPricing {
+        input,
+        output,
+        cache_write: input * 1.25,
+        cache_read: input * 0.1,
+    })
+}
+
 fn convert_usage(usage: &Usage) -> language_model::TokenUsage {
     language_model::TokenUsage {
         input_tokens: usage.input_tokens.unwrap_or(0),
@@ -2,6 +2,7 @@ use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::provider::anthropic::anthropic_pricing;
 use crate::ui::InstructionListItem;
 use anyhow::{Context as _, Result, anyhow};
 use aws_config::stalled_stream_protection::StalledStreamProtectionConfig;
@@ -36,8 +37,8 @@ use language_model::{
     LanguageModelCompletionError, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
-    LanguageModelToolResultContent, LanguageModelToolUse, MessageContent, RateLimiter, Role,
-    TokenUsage,
+    LanguageModelToolResultContent, LanguageModelToolUse, MessageContent, ModelPricing,
+    RateLimiter, Role, TokenUsage,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -526,6 +527,10 @@ impl LanguageModel for BedrockModel {
         Some(self.model.max_output_tokens())
     }
 
+    fn pricing(&self) -> Option<ModelPricing> {
+        bedrock_pricing(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -817,6 +822,28 @@ pub fn into_bedrock(
 
 // TODO: just call the ConverseOutput.usage() method:
 // https://docs.rs/aws-sdk-bedrockruntime/latest/aws_sdk_bedrockruntime/operation/converse/struct.ConverseOutput.html#method.output
+/// Bedrock bills Claude models at Anthropic's list prices. Other models' prices vary by
+/// region, so they're left unknown.
+pub fn bedrock_pricing(model: &Model) -> Option<ModelPricing> {
+    let model = match model {
+        Model::ClaudeSonnet4 => anthropic::Model::ClaudeSonnet4,
+        Model::ClaudeSonnet4Thinking => anthropic::Model::ClaudeSonnet4Thinking,
+        Model::ClaudeOpus4 => anthropic::Model::ClaudeOpus4,
+        Model::ClaudeOpus4_1 => anthropic::Model::ClaudeOpus4_1,
+        Model::ClaudeOpus4Thinking => anthropic::Model::ClaudeOpus4Thinking,
+        Model::ClaudeOpus4_1Thinking => anthropic::Model::ClaudeOpus4_1Thinking,
+        Model::Claude3_5SonnetV2 | Model::Claude3_5Sonnet => anthropic::Model::Claude3_5Sonnet,
+        Model::Claude3_7Sonnet => anthropic::Model::Claude3_7Sonnet,
+        Model::Claude3_7SonnetThinking => anthropic::Model::Claude3_7SonnetThinking,
+        Model::Claude3Opus => anthropic::Model::Claude3Opus,
+        Model::Claude3Sonnet => anthropic::Model::Claude3Sonnet,
+        Model::Claude3_5Haiku => anthropic::Model::Claude3_5Haiku,
+        Model::Claude3Haiku => anthropic::Model::Claude3Haiku,
+        _ => return None,
+    };
+    anthropic_pricing(&model)
+}
+
 pub fn get_bedrock_tokens(
     request: LanguageModelRequest,
     cx: &App,
@@ -11,7 +11,7 @@ use language_model::{
     LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
     LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
     LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolUse, MessageContent,
-    RateLimiter, Role, StopReason, TokenUsage,
+    ModelPricing, RateLimiter, Role, StopReason, TokenUsage,
 };
 use menu;
 use open_ai::{ImageUrl, Model, ReasoningEffort, ResponseStreamEvent, stream_completion};
@@ -349,6 +349,10 @@ impl LanguageModel for OpenAiLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<ModelPricing> {
+        open_ai_pricing(&self.model)
+    }
+
     fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -573,11 +577,14 @@ impl OpenAiEventMapper {
     ) -> Vec<Result<LanguageModelCompletionEvent, LanguageModelCompletionError>> {
         let mut events = Vec::new();
         if let Some(usage) = event.usage {
+            let cached_tokens = usage
+                .prompt_tokens_details
+                .map_or(0, |details| details.cached_tokens);
             events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
-                input_tokens: usage.prompt_tokens,
+                input_tokens: usage.prompt_tokens.saturating_sub(cached_tokens),
                 output_tokens: usage.completion_tokens,
                 cache_creation_input_tokens: 0,
-                cache_read_input_tokens: 0,
+                cache_read_input_tokens: cached_tokens,
             })));
         }
 
@@ -673,6 +680,34 @@ pub(crate) fn collect_tiktoken_messages(
         .collect::<Vec<_>>()
 }
 
+/// OpenAI's list prices. Writing to OpenAI's prompt cache costs the same as regular input.
+pub fn open_ai_pricing(model: &Model) -> Option<ModelPricing> {
+    let (input, cache_read, output) = match model {
+        Model::ThreePointFiveTurbo => (0.5, 0.5, 1.5),
+        Model::Four => (30.0, 30.0, 60.0),
+        Model::FourTurbo => (10.0, 10.0, 30.0),
+        Model::FourOmni => (2.5, 1.25, 10.0),
+        Model::FourOmniMini => (0.15, 0.075, 0.6),
+        Model::FourPointOne => (2.0, 0.5, 8.0),
+        Model::FourPointOneMini => (0.4, 0.1, 1.6),
+        Model::FourPointOneNano => (0.1, 0.025, 0.4),
+        Model::O1 => (15.0, 7.5, 60.0),
+        Model::O3Mini => (1.1, 0.55, 4.4),
+        Model::O3 => (2.0, 0.5, 8.0),
+        Model::O4Mini => (1.1, 0.275, 4.4),
+        Model::Five => (1.25, 0.125, 10.0),
+        Model::FiveMini => (0.25, 0.025, 2.0),
+        Model::FiveNano => (0.05, 0.005, 0.4),
+        Model::Custom { .. } => return None,
+    };
+    Some(ModelPricing {
+        input,
+        output,
+        cache_write: input,
+        cache_read,
+    })
+}
+
 pub fn count_open_ai_tokens(
     request: LanguageModelRequest,
     model: Model,
@@ -61,6 +61,11 @@ impl Default for ModelCapabilities {
     }
 }
 
+/// The environment variable holding the API key of the OpenAI-compatible provider with the given id.
+pub fn api_key_env_var_name(provider_id: &str) -> String {
+    format!("{}_API_KEY", provider_id).to_case(Case::Constant)
+}
+
 pub struct OpenAiCompatibleLanguageModelProvider {
     id: LanguageModelProviderId,
     name: LanguageModelProviderName,
@@ -155,7 +160,7 @@ impl OpenAiCompatibleLanguageModelProvider {
 
         let state = cx.new(|cx| State {
             id: id.clone(),
-            env_var_name: format!("{}_API_KEY", id).to_case(Case::Constant).into(),
+            env_var_name: api_key_env_var_name(&id).into(),
             settings: resolve_settings(&id, cx).cloned().unwrap_or_default(),
             api_key: None,
             api_key_from_env: false,
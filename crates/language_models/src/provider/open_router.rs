@@ -12,7 +12,7 @@ use language_model::{
     LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
     LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest,
     LanguageModelToolChoice, LanguageModelToolResultContent, LanguageModelToolSchemaFormat,
-    LanguageModelToolUse, MessageContent, RateLimiter, Role, StopReason, TokenUsage,
+    LanguageModelToolUse, MessageContent, ModelPricing, RateLimiter, Role, StopReason, TokenUsage,
 };
 use open_router::{
     Model, ModelMode as OpenRouterModelMode, ResponseStreamEvent, list_models, stream_completion,
@@ -278,6 +278,7 @@ impl LanguageModelProvider for OpenRouterLanguageModelProvider {
                 supports_tools: model.supports_tools,
                 supports_images: model.supports_images,
                 mode: model.mode.clone().unwrap_or_default().into(),
+                pricing: None,
             });
         }
 
@@ -286,7 +287,11 @@ impl LanguageModelProvider for OpenRouterLanguageModelProvider {
                 .iter()
                 .position(|m| m.name == settings_model.name)
             {
-                models_from_api[pos] = settings_model.clone();
+                let pricing = models_from_api[pos].pricing;
+                models_from_api[pos] = open_router::Model {
+                    pricing,
+                    ..settings_model.clone()
+                };
             } else {
                 models_from_api.push(settings_model.clone());
             }
@@ -400,6 +405,17 @@ impl LanguageModel for OpenRouterLanguageModel {
         self.model.max_output_tokens()
     }
 
+    fn pricing(&self) -> Option<ModelPricing> {
+        let pricing = self.model.pricing?;
+        let per_million = |price: f64| price * 1_000_000.0;
+        Some(ModelPricing {
+            input: per_million(pricing.prompt),
+            output: per_million(pricing.completion),
+            cache_write: per_million(pricing.input_cache_write.unwrap_or(pricing.prompt)),
+            cache_read: per_million(pricing.input_cache_read.unwrap_or(pricing.prompt)),
+        })
+    }
+
     fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
         match choice {
             LanguageModelToolChoice::Auto => true,
@@ -666,11 +682,14 @@ impl OpenRouterEventMapper {
         }
 
         if let Some(usage) = event.usage {
+            let cached_tokens = usage
+                .prompt_tokens_details
+                .map_or(0, |details| details.cached_tokens);
             events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
-                input_tokens: usage.prompt_tokens,
+                input_tokens: usage.prompt_tokens.saturating_sub(cached_tokens),
                 output_tokens: usage.completion_tokens,
                 cache_creation_input_tokens: 0,
-                cache_read_input_tokens: 0,
+                cache_read_input_tokens: cached_tokens,
             })));
         }
 
@@ -20,7 +20,7 @@ use language::{
     CharKind, Chunk, CursorShape, DiagnosticEntry, DiskState, File, IndentSize, Language,
     LanguageScope, OffsetRangeExt, OffsetUtf16, Outline, OutlineItem, Point, PointUtf16, Selection,
     TextDimension, TextObject, ToOffset as _, ToPoint as _, TransactionId, TreeSitterOptions,
-    Unclipped,
+    Unclipped, UndoTree,
     language_settings::{IndentGuideSettings, LanguageSettings, language_settings},
 };
 
@@ -1451,6 +1451,22 @@ impl MultiBuffer {
         None
     }
 
+    /// Returns the undo tree of the buffer, if this multibuffer is a singleton.
+    pub fn undo_tree(&self, cx: &App) -> Option<UndoTree> {
+        Some(self.as_singleton()?.read(cx).undo_tree())
+    }
+
+    /// Moves the buffer to the given state of its undo tree, if this multibuffer is a singleton.
+    pub fn undo_tree_move_to(
+        &mut self,
+        target: Option<TransactionId>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.as_singleton().is_some_and(|buffer| {
+            buffer.update(cx, |buffer, cx| buffer.undo_tree_move_to(target, cx))
+        })
+    }
+
     pub fn undo_transaction(&mut self, transaction_id: TransactionId, cx: &mut Context<Self>) {
         if let Some(buffer) = self.as_singleton() {
             buffer.update(cx, |buffer, cx| buffer.undo_transaction(transaction_id, cx));
@@ -423,6 +423,15 @@ pub struct Usage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
+    /// A breakdown of `prompt_tokens`, reporting how many of them were read from the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct PromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -531,9 +540,18 @@ pub enum OpenAiEmbeddingModel {
     TextEmbedding3Large,
 }
 
+impl OpenAiEmbeddingModel {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::TextEmbedding3Small => "text-embedding-3-small",
+            Self::TextEmbedding3Large => "text-embedding-3-large",
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct OpenAiEmbeddingRequest<'a> {
-    model: OpenAiEmbeddingModel,
+    model: &'a str,
     input: Vec<&'a str>,
 }
 
@@ -551,7 +569,7 @@ pub fn embed<'a>(
     client: &dyn HttpClient,
     api_url: &str,
     api_key: &str,
-    model: OpenAiEmbeddingModel,
+    model: &'a str,
     texts: impl IntoIterator<Item = &'a str>,
 ) -> impl 'static + Future<Output = Result<OpenAiEmbeddingResponse>> {
     let uri = format!("{api_url}/embeddings");
@@ -55,6 +55,21 @@ pub struct Model {
     pub supports_images: Option<bool>,
     #[serde(default)]
     pub mode: ModelMode,
+    /// The price of the model's tokens, as reported by the models endpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+}
+
+/// The price of a model's tokens, in US dollars per token.
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelPricing {
+    pub prompt: f64,
+    pub completion: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cache_read: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cache_write: Option<f64>,
 }
 
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -98,6 +113,7 @@ impl Model {
             supports_tools,
             supports_images,
             mode: mode.unwrap_or(ModelMode::Default),
+            pricing: None,
         }
     }
 
@@ -352,6 +368,15 @@ pub struct Usage {
     pub prompt_tokens: u64,
     pub completion_tokens: u64,
     pub total_tokens: u64,
+    /// A breakdown of `prompt_tokens`, reporting how many of them were read from the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct PromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -405,6 +430,35 @@ pub struct ModelEntry {
     pub supported_parameters: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub architecture: Option<ModelArchitecture>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelEntryPricing>,
+}
+
+/// The prices of a model's tokens, which OpenRouter reports as decimal strings of US dollars
+/// per token. Routers whose price depends on the model they pick report `-1`.
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelEntryPricing {
+    pub prompt: String,
+    pub completion: String,
+    #[serde(default)]
+    pub input_cache_read: Option<String>,
+    #[serde(default)]
+    pub input_cache_write: Option<String>,
+}
+
+impl ModelEntryPricing {
+    pub fn parse(&self) -> Option<ModelPricing> {
+        fn price(value: &str) -> Option<f64> {
+            value.parse::<f64>().ok().filter(|price| *price >= 0.0)
+        }
+
+        Some(ModelPricing {
+            prompt: price(&self.prompt)?,
+            completion: price(&self.completion)?,
+            input_cache_read: self.input_cache_read.as_deref().and_then(price),
+            input_cache_write: self.input_cache_write.as_deref().and_then(price),
+        })
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Deserialize)]
@@ -632,6 +686,7 @@ pub async fn list_models(client: &dyn HttpClient, api_url: &str) -> Result<Vec<M
                 } else {
                     ModelMode::Default
                 },
+                pricing: entry.pricing.as_ref().and_then(ModelEntryPricing::parse),
             })
             .collect();
 
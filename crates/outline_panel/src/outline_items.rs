@@ -0,0 +1,393 @@
+//! Where the items of the outline come from, which of them are shown and in what order.
+//! These options are chosen per language.
+
+use std::{cmp, ops::Range};
+
+use collections::BTreeSet;
+use language::{
+    Anchor, Bias, BufferSnapshot, Language, OutlineItem, Point, PointUtf16, ToPoint as _, Unclipped,
+};
+use lsp::SymbolKind;
+use project::DocumentSymbol;
+use serde::{Deserialize, Serialize};
+use theme::SyntaxTheme;
+use workspace::ui::IconName;
+
+/// The kind of an outline item, which it can be filtered by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OutlineKind {
+    Function,
+    Type,
+    Field,
+    Constant,
+    /// Modules, variables and anything else, which are always shown.
+    Other,
+}
+
+impl OutlineKind {
+    pub(crate) fn from_lsp(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::FUNCTION
+            | SymbolKind::METHOD
+            | SymbolKind::CONSTRUCTOR
+            | SymbolKind::OPERATOR => Self::Function,
+            SymbolKind::CLASS
+            | SymbolKind::INTERFACE
+            | SymbolKind::STRUCT
+            | SymbolKind::ENUM
+            | SymbolKind::TYPE_PARAMETER => Self::Type,
+            SymbolKind::FIELD
+            | SymbolKind::PROPERTY
+            | SymbolKind::ENUM_MEMBER
+            | SymbolKind::EVENT => Self::Field,
+            SymbolKind::CONSTANT
+            | SymbolKind::NUMBER
+            | SymbolKind::STRING
+            | SymbolKind::BOOLEAN => Self::Constant,
+            _ => Self::Other,
+        }
+    }
+
+    /// Returns the kind the keyword that introduces a declaration stands for, if any.
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "fn" | "func" | "function" | "def" | "method" => Some(Self::Function),
+            "struct" | "enum" | "union" | "trait" | "impl" | "class" | "interface" | "type"
+            | "typedef" | "protocol" | "record" => Some(Self::Type),
+            "const" | "constant" => Some(Self::Constant),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn icon(self) -> IconName {
+        match self {
+            Self::Function => IconName::Code,
+            Self::Type => IconName::Blocks,
+            Self::Field => IconName::Dash,
+            Self::Constant => IconName::Hash,
+            Self::Other => IconName::SquareDot,
+        }
+    }
+}
+
+/// Where the items of the outline come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OutlineSource {
+    /// The language's `outline.scm` query.
+    TreeSitter,
+    /// The document symbols of the buffer's language server.
+    LanguageServer,
+}
+
+impl OutlineSource {
+    /// Languages whose grammar has no outline query fall back to their language server.
+    fn default_for(language: Option<&Language>) -> Self {
+        match language.and_then(|language| language.grammar()) {
+            Some(grammar) if grammar.outline_config.is_some() => Self::TreeSitter,
+            Some(_) => Self::LanguageServer,
+            None => Self::TreeSitter,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OutlineSort {
+    #[default]
+    Position,
+    /// Sorts the items that share a parent alphabetically.
+    Name,
+}
+
+/// How the outline of a language's buffers is built, persisted per language.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OutlineOptions {
+    /// The source of the items, or `None` for the default of the language.
+    #[serde(default)]
+    pub source: Option<OutlineSource>,
+    #[serde(default)]
+    pub hidden_kinds: BTreeSet<OutlineKind>,
+    #[serde(default)]
+    pub sort: OutlineSort,
+}
+
+impl OutlineOptions {
+    pub(crate) fn source(&self, language: Option<&Language>) -> OutlineSource {
+        self.source
+            .unwrap_or_else(|| OutlineSource::default_for(language))
+    }
+}
+
+/// Returns the tree-sitter outline items of the given range, along with the kinds guessed from
+/// the keywords they're declared with.
+pub(crate) fn tree_sitter_outline_items(
+    buffer: &BufferSnapshot,
+    range: Range<Anchor>,
+    syntax_theme: &SyntaxTheme,
+) -> Vec<(OutlineItem<Anchor>, OutlineKind)> {
+    let buffer_language = buffer.language();
+    let mut items = buffer
+        .outline_items_containing(range, false, Some(syntax_theme))
+        .unwrap_or_default();
+    items.retain(|item| {
+        buffer_language.is_none() || buffer_language == buffer.language_at(item.range.start)
+    });
+    let kinds = infer_kinds(&items);
+    items.into_iter().zip(kinds).collect()
+}
+
+/// Guesses the kinds of tree-sitter outline items from the keywords before their names. Items
+/// without such a keyword are fields when they're nested in a type, like struct fields and
+/// enum variants.
+pub(crate) fn infer_kinds<T>(items: &[OutlineItem<T>]) -> Vec<OutlineKind> {
+    let mut kinds = Vec::with_capacity(items.len());
+    let mut ancestors = Vec::<(usize, OutlineKind)>::new();
+    for item in items {
+        while ancestors
+            .last()
+            .is_some_and(|(depth, _)| *depth >= item.depth)
+        {
+            ancestors.pop();
+        }
+        let context_end = item
+            .name_ranges
+            .first()
+            .map_or(item.text.len(), |range| range.start);
+        let keyword_kind = item.text[..context_end]
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter_map(OutlineKind::from_keyword)
+            .min();
+        let kind = keyword_kind.unwrap_or_else(|| {
+            if ancestors
+                .last()
+                .is_some_and(|(_, kind)| *kind == OutlineKind::Type)
+            {
+                OutlineKind::Field
+            } else {
+                OutlineKind::Other
+            }
+        });
+        ancestors.push((item.depth, kind));
+        kinds.push(kind);
+    }
+    kinds
+}
+
+/// Flattens the document symbols that overlap the given range into outline items, depth-first
+/// and in the order of their position.
+pub(crate) fn lsp_outline_items(
+    symbols: &[DocumentSymbol],
+    range: Range<Anchor>,
+    buffer: &BufferSnapshot,
+) -> Vec<(OutlineItem<Anchor>, OutlineKind)> {
+    fn push_symbols(
+        symbols: &[DocumentSymbol],
+        depth: usize,
+        range: &Range<Point>,
+        buffer: &BufferSnapshot,
+        items: &mut Vec<(OutlineItem<Anchor>, OutlineKind)>,
+    ) {
+        let mut symbols = symbols
+            .iter()
+            .map(|symbol| (clip_range(&symbol.range, buffer), symbol))
+            .filter(|(symbol_range, _)| {
+                symbol_range.start <= range.end && symbol_range.end >= range.start
+            })
+            .collect::<Vec<_>>();
+        symbols
+            .sort_by_key(|(symbol_range, _)| (symbol_range.start, cmp::Reverse(symbol_range.end)));
+        for (symbol_range, symbol) in symbols {
+            items.push((
+                OutlineItem {
+                    depth,
+                    range: buffer.anchor_after(symbol_range.start)
+                        ..buffer.anchor_before(symbol_range.end),
+                    text: symbol.name.clone(),
+                    highlight_ranges: Vec::new(),
+                    name_ranges: vec![0..symbol.name.len()],
+                    body_range: None,
+                    annotation_range: None,
+                },
+                OutlineKind::from_lsp(symbol.kind),
+            ));
+            push_symbols(&symbol.children, depth + 1, range, buffer, items);
+        }
+    }
+
+    let range = range.start.to_point(buffer)..range.end.to_point(buffer);
+    let mut items = Vec::new();
+    push_symbols(symbols, 0, &range, buffer, &mut items);
+    items
+}
+
+fn clip_range(range: &Range<Unclipped<PointUtf16>>, buffer: &BufferSnapshot) -> Range<Point> {
+    let start = buffer.clip_point_utf16(range.start, Bias::Left);
+    let end = buffer.clip_point_utf16(range.end, Bias::Right);
+    start.to_point(buffer)..end.to_point(buffer)
+}
+
+/// Drops the items of hidden kinds, whose children take their place, and sorts the rest.
+pub(crate) fn arrange_outline_items<T>(
+    items: Vec<(OutlineItem<T>, OutlineKind)>,
+    options: &OutlineOptions,
+) -> Vec<(OutlineItem<T>, OutlineKind)> {
+    let mut kept_ancestor_depths = Vec::new();
+    let items = items
+        .into_iter()
+        .filter_map(|(mut item, kind)| {
+            while kept_ancestor_depths
+                .last()
+                .is_some_and(|depth| *depth >= item.depth)
+            {
+                kept_ancestor_depths.pop();
+            }
+            if options.hidden_kinds.contains(&kind) {
+                return None;
+            }
+            kept_ancestor_depths.push(item.depth);
+            item.depth = kept_ancestor_depths.len() - 1;
+            Some((item, kind))
+        })
+        .collect();
+    match options.sort {
+        OutlineSort::Position => items,
+        OutlineSort::Name => sort_by_name(items),
+    }
+}
+
+/// Sorts the items that share a parent by name, keeping each one's children right after it.
+fn sort_by_name<T>(
+    items: Vec<(OutlineItem<T>, OutlineKind)>,
+) -> Vec<(OutlineItem<T>, OutlineKind)> {
+    let Some(min_depth) = items.iter().map(|(item, _)| item.depth).min() else {
+        return items;
+    };
+    let mut subtrees = Vec::<Vec<_>>::new();
+    for entry in items {
+        match subtrees.last_mut() {
+            Some(subtree) if entry.0.depth > min_depth => subtree.push(entry),
+            _ => subtrees.push(vec![entry]),
+        }
+    }
+    subtrees.sort_by_cached_key(|subtree| item_name(&subtree[0].0).to_lowercase());
+    subtrees
+        .into_iter()
+        .flat_map(|mut subtree| {
+            let children = subtree.split_off(1);
+            subtree.extend(sort_by_name(children));
+            subtree
+        })
+        .collect()
+}
+
+fn item_name<T>(item: &OutlineItem<T>) -> String {
+    if item.name_ranges.is_empty() {
+        item.text.clone()
+    } else {
+        item.name_ranges
+            .iter()
+            .map(|range| &item.text[range.clone()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(depth: usize, text: &str, name: &str) -> OutlineItem<usize> {
+        let name_start = text.rfind(name).unwrap();
+        OutlineItem {
+            depth,
+            range: 0..0,
+            text: text.to_string(),
+            highlight_ranges: Vec::new(),
+            name_ranges: vec![name_start..name_start + name.len()],
+            body_range: None,
+            annotation_range: None,
+        }
+    }
+
+    fn rust_items() -> Vec<OutlineItem<usize>> {
+        vec![
+            item(0, "const LIMIT", "LIMIT"),
+            item(0, "pub struct Zebra", "Zebra"),
+            item(1, "pub stripes", "stripes"),
+            item(1, "legs", "legs"),
+            item(0, "impl Zebra", "Zebra"),
+            item(1, "pub const fn run", "run"),
+            item(1, "fn eat", "eat"),
+            item(0, "mod animals", "animals"),
+            item(1, "fn adopt", "adopt"),
+        ]
+    }
+
+    fn texts(items: &[(OutlineItem<usize>, OutlineKind)]) -> Vec<String> {
+        items
+            .iter()
+            .map(|(item, _)| format!("{}{}", "  ".repeat(item.depth), item.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_infer_kinds() {
+        assert_eq!(
+            infer_kinds(&rust_items()),
+            vec![
+                OutlineKind::Constant,
+                OutlineKind::Type,
+                OutlineKind::Field,
+                OutlineKind::Field,
+                OutlineKind::Type,
+                OutlineKind::Function,
+                OutlineKind::Function,
+                OutlineKind::Other,
+                OutlineKind::Function,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arrange_outline_items() {
+        let items = rust_items();
+        let kinds = infer_kinds(&items);
+        let items = items.into_iter().zip(kinds).collect::<Vec<_>>();
+
+        let options = OutlineOptions {
+            hidden_kinds: BTreeSet::from([OutlineKind::Type, OutlineKind::Field]),
+            ..OutlineOptions::default()
+        };
+        assert_eq!(
+            texts(&arrange_outline_items(items.clone(), &options)),
+            vec![
+                "const LIMIT",
+                "pub const fn run",
+                "fn eat",
+                "mod animals",
+                "  fn adopt"
+            ],
+            "children of hidden items should take their place"
+        );
+
+        let options = OutlineOptions {
+            hidden_kinds: BTreeSet::from([OutlineKind::Field]),
+            sort: OutlineSort::Name,
+            ..OutlineOptions::default()
+        };
+        assert_eq!(
+            texts(&arrange_outline_items(items, &options)),
+            vec![
+                "mod animals",
+                "  fn adopt",
+                "const LIMIT",
+                "pub struct Zebra",
+                "impl Zebra",
+                "  fn eat",
+                "  pub const fn run",
+            ],
+            "siblings should be sorted by name, keeping their children"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+mod outline_items;
 mod outline_panel_settings;
 
 use anyhow::Context as _;
@@ -22,7 +23,10 @@ use gpui::{
     uniform_list,
 };
 use itertools::Itertools;
-use language::{Anchor, BufferId, BufferSnapshot, OffsetRangeExt, OutlineItem};
+use language::{
+    Anchor, BufferId, BufferSnapshot, Language, LanguageName, OffsetRangeExt, OutlineItem,
+    PLAIN_TEXT,
+};
 use menu::{Cancel, SelectFirst, SelectLast, SelectNext, SelectPrevious};
 use std::{
     cmp,
@@ -38,6 +42,10 @@ use std::{
     u32,
 };
 
+use outline_items::{
+    OutlineKind, OutlineOptions, OutlineSort, OutlineSource, arrange_outline_items,
+    lsp_outline_items, tree_sitter_outline_items,
+};
 use outline_panel_settings::{OutlinePanelDockPosition, OutlinePanelSettings, ShowIndentGuides};
 use project::{File, Fs, GitEntry, GitTraversal, Project, ProjectItem};
 use search::{BufferSearchBar, ProjectSearchView};
@@ -54,9 +62,9 @@ use workspace::{
     searchable::{SearchEvent, SearchableItem},
     ui::{
         ActiveTheme, ButtonCommon, Clickable, Color, ContextMenu, FluentBuilder, HighlightedLabel,
-        Icon, IconButton, IconButtonShape, IconName, IconSize, Label, LabelCommon, ListItem,
-        Scrollbar, ScrollbarState, StyledExt, StyledTypography, Toggleable, Tooltip, h_flex,
-        v_flex,
+        Icon, IconButton, IconButtonShape, IconName, IconPosition, IconSize, Label, LabelCommon,
+        ListItem, PopoverMenu, Scrollbar, ScrollbarState, StyledExt, StyledTypography, Toggleable,
+        Tooltip, h_flex, v_flex,
     },
 };
 use worktree::{Entry, ProjectEntryId, WorktreeId};
@@ -86,10 +94,23 @@ actions!(
         UnfoldDirectory,
         /// Toggles focus on the outline panel.
         ToggleFocus,
+        /// Toggles whether functions and methods are shown in the outline.
+        ToggleFunctions,
+        /// Toggles whether types are shown in the outline.
+        ToggleTypes,
+        /// Toggles whether fields and enum variants are shown in the outline.
+        ToggleFields,
+        /// Toggles whether constants are shown in the outline.
+        ToggleConstants,
+        /// Toggles between sorting the outline by position and by name.
+        ToggleSortByName,
+        /// Toggles between the tree-sitter outline and the language server's document symbols.
+        ToggleOutlineSource,
     ]
 );
 
 const OUTLINE_PANEL_KEY: &str = "OutlinePanel";
+const OUTLINE_OPTIONS_KEY: &str = "OutlinePanelLanguageOptions";
 const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
 
 type Outline = OutlineItem<language::Anchor>;
@@ -133,6 +154,10 @@ pub struct OutlinePanel {
     preserve_selection_on_buffer_fold_toggles: HashSet<BufferId>,
     pending_default_expansion_depth: Option<usize>,
     outline_children_cache: HashMap<BufferId, HashMap<(Range<Anchor>, usize), bool>>,
+    /// The kinds of the outlines that come from a language server, shown as icons.
+    outline_kinds: HashMap<BufferId, HashMap<(Range<Anchor>, usize), OutlineKind>>,
+    language_options: HashMap<LanguageName, OutlineOptions>,
+    pending_options_serialization: Task<Option<()>>,
 }
 
 #[derive(Debug)]
@@ -712,9 +737,22 @@ impl OutlinePanel {
                 .flatten(),
             None => None,
         };
+        let language_options = cx
+            .background_spawn(async move { KEY_VALUE_STORE.read_kvp(OUTLINE_OPTIONS_KEY) })
+            .await
+            .context("loading outline options")
+            .log_err()
+            .flatten()
+            .map(|options| serde_json::from_str::<HashMap<LanguageName, OutlineOptions>>(&options))
+            .transpose()
+            .log_err()
+            .flatten();
 
         workspace.update_in(&mut cx, |workspace, window, cx| {
             let panel = Self::new(workspace, window, cx);
+            if let Some(language_options) = language_options {
+                panel.update(cx, |panel, _| panel.language_options = language_options);
+            }
             if let Some(serialized_panel) = serialized_panel {
                 panel.update(cx, |panel, cx| {
                     panel.width = serialized_panel.width.map(|px| px.round());
@@ -908,6 +946,9 @@ impl OutlinePanel {
                     filter_update_subscription,
                 ],
                 outline_children_cache: HashMap::default(),
+                outline_kinds: HashMap::default(),
+                language_options: HashMap::default(),
+                pending_options_serialization: Task::ready(None),
             };
             if let Some((item, editor)) = workspace_active_editor(workspace, cx) {
                 outline_panel.replace_active_editor(item, editor, window, cx);
@@ -951,6 +992,22 @@ impl OutlinePanel {
         );
     }
 
+    fn serialize_language_options(&mut self, cx: &mut Context<Self>) {
+        let language_options = self.language_options.clone();
+        self.pending_options_serialization = cx.background_spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        OUTLINE_OPTIONS_KEY.to_string(),
+                        serde_json::to_string(&language_options)?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
     fn dispatch_context(&self, window: &mut Window, cx: &mut Context<Self>) -> KeyContext {
         let mut dispatch_context = KeyContext::new_with_defaults();
         dispatch_context.add("OutlinePanel");
@@ -2218,8 +2275,24 @@ impl OutlinePanel {
                 .map(|string_match| string_match.ranges().collect::<Vec<_>>())
                 .unwrap_or_default(),
             cx,
-        )
-        .into_any_element();
+        );
+        let kind = self
+            .outline_kinds
+            .get(&outline.buffer_id)
+            .and_then(|kinds| kinds.get(&(outline.outline.range.clone(), outline.outline.depth)))
+            .copied();
+        let label_element = match kind {
+            Some(kind) => h_flex()
+                .gap_1()
+                .child(
+                    Icon::new(kind.icon())
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(label_element)
+                .into_any_element(),
+            None => label_element.into_any_element(),
+        };
 
         let is_active = match self.selected_entry() {
             Some(PanelEntry::Outline(OutlineEntry::Outline(selected))) => {
@@ -3340,42 +3413,44 @@ impl OutlinePanel {
         let syntax_theme = cx.theme().syntax().clone();
         let first_update = Arc::new(AtomicBool::new(true));
         for (buffer_id, (buffer_snapshot, excerpt_ranges)) in excerpt_fetch_ranges {
+            let options = self.outline_options(buffer_snapshot.language());
+            if options.source(buffer_snapshot.language().map(|language| language.as_ref()))
+                == OutlineSource::LanguageServer
+            {
+                self.fetch_lsp_outlines(
+                    buffer_id,
+                    buffer_snapshot,
+                    excerpt_ranges,
+                    options,
+                    first_update.clone(),
+                    window,
+                    cx,
+                );
+                continue;
+            }
+
             for (excerpt_id, excerpt_range) in excerpt_ranges {
                 let syntax_theme = syntax_theme.clone();
                 let buffer_snapshot = buffer_snapshot.clone();
                 let first_update = first_update.clone();
+                let options = options.clone();
                 self.outline_fetch_tasks.insert(
                     (buffer_id, excerpt_id),
                     cx.spawn_in(window, async move |outline_panel, cx| {
-                        let buffer_language = buffer_snapshot.language().cloned();
                         let fetched_outlines = cx
                             .background_spawn(async move {
-                                let mut outlines = buffer_snapshot
-                                    .outline_items_containing(
+                                let outlines = arrange_outline_items(
+                                    tree_sitter_outline_items(
+                                        &buffer_snapshot,
                                         excerpt_range.context,
-                                        false,
-                                        Some(&syntax_theme),
-                                    )
-                                    .unwrap_or_default();
-                                outlines.retain(|outline| {
-                                    buffer_language.is_none()
-                                        || buffer_language.as_ref()
-                                            == buffer_snapshot.language_at(outline.range.start)
-                                });
-
-                                let outlines_with_children = outlines
-                                    .windows(2)
-                                    .filter_map(|window| {
-                                        let current = &window[0];
-                                        let next = &window[1];
-                                        if next.depth > current.depth {
-                                            Some((current.range.clone(), current.depth))
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .collect::<HashSet<_>>();
-
+                                        &syntax_theme,
+                                    ),
+                                    &options,
+                                )
+                                .into_iter()
+                                .map(|(outline, _)| outline)
+                                .collect::<Vec<_>>();
+                                let outlines_with_children = outlines_with_children(&outlines);
                                 (outlines, outlines_with_children)
                             })
                             .await;
@@ -3384,53 +3459,16 @@ impl OutlinePanel {
 
                         outline_panel
                             .update_in(cx, |outline_panel, window, cx| {
-                                let pending_default_depth =
-                                    outline_panel.pending_default_expansion_depth.take();
-
-                                let debounce =
-                                    if first_update.fetch_and(false, atomic::Ordering::AcqRel) {
-                                        None
-                                    } else {
-                                        Some(UPDATE_DEBOUNCE)
-                                    };
-
-                                if let Some(excerpt) = outline_panel
-                                    .excerpts
-                                    .entry(buffer_id)
-                                    .or_default()
-                                    .get_mut(&excerpt_id)
-                                {
-                                    excerpt.outlines = ExcerptOutlines::Outlines(fetched_outlines);
-
-                                    if let Some(default_depth) = pending_default_depth
-                                        && let ExcerptOutlines::Outlines(outlines) =
-                                            &excerpt.outlines
-                                    {
-                                        outlines
-                                            .iter()
-                                            .filter(|outline| {
-                                                (default_depth == 0
-                                                    || outline.depth >= default_depth)
-                                                    && outlines_with_children.contains(&(
-                                                        outline.range.clone(),
-                                                        outline.depth,
-                                                    ))
-                                            })
-                                            .for_each(|outline| {
-                                                outline_panel.collapsed_entries.insert(
-                                                    CollapsedEntry::Outline(
-                                                        buffer_id,
-                                                        excerpt_id,
-                                                        outline.range.clone(),
-                                                    ),
-                                                );
-                                            });
-                                    }
-
-                                    // Even if no outlines to check, we still need to update cached entries
-                                    // to show the outline entries that were just fetched
-                                    outline_panel.update_cached_entries(debounce, window, cx);
-                                }
+                                outline_panel.outline_kinds.remove(&buffer_id);
+                                outline_panel.set_excerpt_outlines(
+                                    buffer_id,
+                                    excerpt_id,
+                                    fetched_outlines,
+                                    outlines_with_children,
+                                    &first_update,
+                                    window,
+                                    cx,
+                                );
                             })
                             .ok();
                     }),
@@ -3439,6 +3477,145 @@ impl OutlinePanel {
         }
     }
 
+    /// Fetches the outlines of a buffer's excerpts from the document symbols of its language
+    /// server, falling back to tree-sitter when the server reports none.
+    fn fetch_lsp_outlines(
+        &mut self,
+        buffer_id: BufferId,
+        buffer_snapshot: BufferSnapshot,
+        excerpt_ranges: HashMap<ExcerptId, ExcerptRange<language::Anchor>>,
+        options: OutlineOptions,
+        first_update: Arc<AtomicBool>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(&first_excerpt_id) = excerpt_ranges.keys().next() else {
+            return;
+        };
+        let Some(buffer) = self
+            .active_editor()
+            .and_then(|editor| editor.read(cx).buffer().read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+        let syntax_theme = cx.theme().syntax().clone();
+        let symbols = self
+            .project
+            .update(cx, |project, cx| project.document_symbols(&buffer, cx));
+        self.outline_fetch_tasks.insert(
+            (buffer_id, first_excerpt_id),
+            cx.spawn_in(window, async move |outline_panel, cx| {
+                let symbols = symbols.await.log_err().unwrap_or_default();
+                let from_language_server = !symbols.is_empty();
+                let fetched_outlines = cx
+                    .background_spawn(async move {
+                        excerpt_ranges
+                            .into_iter()
+                            .map(|(excerpt_id, excerpt_range)| {
+                                let items = if from_language_server {
+                                    lsp_outline_items(
+                                        &symbols,
+                                        excerpt_range.context,
+                                        &buffer_snapshot,
+                                    )
+                                } else {
+                                    tree_sitter_outline_items(
+                                        &buffer_snapshot,
+                                        excerpt_range.context,
+                                        &syntax_theme,
+                                    )
+                                };
+                                (excerpt_id, arrange_outline_items(items, &options))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .await;
+
+                outline_panel
+                    .update_in(cx, |outline_panel, window, cx| {
+                        let kinds = outline_panel.outline_kinds.entry(buffer_id).or_default();
+                        kinds.clear();
+                        if from_language_server {
+                            kinds.extend(fetched_outlines.iter().flat_map(|(_, items)| {
+                                items.iter().map(|(outline, kind)| {
+                                    ((outline.range.clone(), outline.depth), *kind)
+                                })
+                            }));
+                        }
+
+                        for (excerpt_id, items) in fetched_outlines {
+                            let outlines = items
+                                .into_iter()
+                                .map(|(outline, _)| outline)
+                                .collect::<Vec<_>>();
+                            let outlines_with_children = outlines_with_children(&outlines);
+                            outline_panel.set_excerpt_outlines(
+                                buffer_id,
+                                excerpt_id,
+                                outlines,
+                                outlines_with_children,
+                                &first_update,
+                                window,
+                                cx,
+                            );
+                        }
+                    })
+                    .ok();
+            }),
+        );
+    }
+
+    fn set_excerpt_outlines(
+        &mut self,
+        buffer_id: BufferId,
+        excerpt_id: ExcerptId,
+        outlines: Vec<Outline>,
+        outlines_with_children: HashSet<(Range<Anchor>, usize)>,
+        first_update: &AtomicBool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let pending_default_depth = self.pending_default_expansion_depth.take();
+
+        let debounce = if first_update.fetch_and(false, atomic::Ordering::AcqRel) {
+            None
+        } else {
+            Some(UPDATE_DEBOUNCE)
+        };
+
+        if let Some(excerpt) = self
+            .excerpts
+            .entry(buffer_id)
+            .or_default()
+            .get_mut(&excerpt_id)
+        {
+            excerpt.outlines = ExcerptOutlines::Outlines(outlines);
+
+            if let Some(default_depth) = pending_default_depth
+                && let ExcerptOutlines::Outlines(outlines) = &excerpt.outlines
+            {
+                outlines
+                    .iter()
+                    .filter(|outline| {
+                        (default_depth == 0 || outline.depth >= default_depth)
+                            && outlines_with_children
+                                .contains(&(outline.range.clone(), outline.depth))
+                    })
+                    .for_each(|outline| {
+                        self.collapsed_entries.insert(CollapsedEntry::Outline(
+                            buffer_id,
+                            excerpt_id,
+                            outline.range.clone(),
+                        ));
+                    });
+            }
+
+            // Even if no outlines to check, we still need to update cached entries
+            // to show the outline entries that were just fetched
+            self.update_cached_entries(debounce, window, cx);
+        }
+    }
+
     fn is_singleton_active(&self, cx: &App) -> bool {
         self.active_editor()
             .is_some_and(|active_editor| active_editor.read(cx).buffer().read(cx).is_singleton())
@@ -4459,6 +4636,116 @@ impl OutlinePanel {
         cx.notify();
     }
 
+    fn toggle_functions(
+        &mut self,
+        _: &ToggleFunctions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_hidden_kind(OutlineKind::Function, window, cx);
+    }
+
+    fn toggle_types(&mut self, _: &ToggleTypes, window: &mut Window, cx: &mut Context<Self>) {
+        self.toggle_hidden_kind(OutlineKind::Type, window, cx);
+    }
+
+    fn toggle_fields(&mut self, _: &ToggleFields, window: &mut Window, cx: &mut Context<Self>) {
+        self.toggle_hidden_kind(OutlineKind::Field, window, cx);
+    }
+
+    fn toggle_constants(
+        &mut self,
+        _: &ToggleConstants,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_hidden_kind(OutlineKind::Constant, window, cx);
+    }
+
+    fn toggle_sort_by_name(
+        &mut self,
+        _: &ToggleSortByName,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_outline_options(window, cx, |options, _| {
+            options.sort = match options.sort {
+                OutlineSort::Position => OutlineSort::Name,
+                OutlineSort::Name => OutlineSort::Position,
+            };
+        });
+    }
+
+    fn toggle_outline_source(
+        &mut self,
+        _: &ToggleOutlineSource,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_outline_options(window, cx, |options, language| {
+            options.source = Some(match options.source(language) {
+                OutlineSource::TreeSitter => OutlineSource::LanguageServer,
+                OutlineSource::LanguageServer => OutlineSource::TreeSitter,
+            });
+        });
+    }
+
+    fn toggle_hidden_kind(
+        &mut self,
+        kind: OutlineKind,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_outline_options(window, cx, |options, _| {
+            if !options.hidden_kinds.remove(&kind) {
+                options.hidden_kinds.insert(kind);
+            }
+        });
+    }
+
+    /// The language of the buffer with the active editor's cursor, whose outline options the
+    /// panel's toggles change.
+    fn active_language(&self, cx: &App) -> Option<Arc<Language>> {
+        let active_editor = self.active_editor()?;
+        let (_, buffer, _) = active_editor.read(cx).active_excerpt(cx)?;
+        buffer.read(cx).language().cloned()
+    }
+
+    fn outline_options(&self, language: Option<&Arc<Language>>) -> OutlineOptions {
+        let language_name = language.unwrap_or(&*PLAIN_TEXT).name();
+        self.language_options
+            .get(&language_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Changes the outline options of the active language and refetches the outlines.
+    fn update_outline_options(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        update: impl FnOnce(&mut OutlineOptions, Option<&Language>),
+    ) {
+        let language = self.active_language(cx);
+        let language_name = language.as_ref().unwrap_or(&*PLAIN_TEXT).name();
+        update(
+            self.language_options.entry(language_name).or_default(),
+            language.as_deref(),
+        );
+        self.serialize_language_options(cx);
+
+        self.outline_fetch_tasks.clear();
+        for excerpts in self.excerpts.values_mut() {
+            for excerpt in excerpts.values_mut() {
+                excerpt.invalidate_outlines();
+            }
+        }
+        if self.update_non_fs_items(window, cx) {
+            self.update_cached_entries(Some(UPDATE_DEBOUNCE), window, cx);
+        }
+        cx.notify();
+    }
+
     fn selected_entry(&self) -> Option<&PanelEntry> {
         match &self.selected_entry {
             SelectedEntry::Invalidated(entry) => entry.as_ref(),
@@ -4901,10 +5188,100 @@ impl OutlinePanel {
                             },
                         )),
                     ),
-                ),
+                )
+                .child(self.render_options_menu(cx)),
         )
     }
 
+    fn render_options_menu(&self, cx: &mut Context<Self>) -> PopoverMenu<ContextMenu> {
+        let language = self.active_language(cx);
+        let options = self.outline_options(language.as_ref());
+        let source = options.source(language.as_deref());
+        let outline_panel = cx.entity().downgrade();
+        let focus_handle = self.focus_handle.clone();
+
+        PopoverMenu::new("outline-panel-options")
+            .trigger_with_tooltip(
+                IconButton::new("outline-panel-options-button", IconName::ListFilter)
+                    .shape(IconButtonShape::Square),
+                Tooltip::text("Outline Options"),
+            )
+            .anchor(gpui::Corner::BottomRight)
+            .menu(move |window, cx| {
+                Some(ContextMenu::build(window, cx, |menu, _, _| {
+                    let mut menu = menu.context(focus_handle.clone()).header("Show");
+                    let kind_toggles: [(&str, OutlineKind, Box<dyn Action>); 4] = [
+                        (
+                            "Functions",
+                            OutlineKind::Function,
+                            Box::new(ToggleFunctions),
+                        ),
+                        ("Types", OutlineKind::Type, Box::new(ToggleTypes)),
+                        ("Fields", OutlineKind::Field, Box::new(ToggleFields)),
+                        (
+                            "Constants",
+                            OutlineKind::Constant,
+                            Box::new(ToggleConstants),
+                        ),
+                    ];
+                    for (label, kind, action) in kind_toggles {
+                        let outline_panel = outline_panel.clone();
+                        menu = menu.toggleable_entry(
+                            label,
+                            !options.hidden_kinds.contains(&kind),
+                            IconPosition::Start,
+                            Some(action),
+                            move |window, cx| {
+                                outline_panel
+                                    .update(cx, |outline_panel, cx| {
+                                        outline_panel.toggle_hidden_kind(kind, window, cx)
+                                    })
+                                    .ok();
+                            },
+                        );
+                    }
+
+                    let sort_outline_panel = outline_panel.clone();
+                    let source_outline_panel = outline_panel.clone();
+                    menu.separator()
+                        .toggleable_entry(
+                            "Sort by Name",
+                            options.sort == OutlineSort::Name,
+                            IconPosition::Start,
+                            Some(Box::new(ToggleSortByName)),
+                            move |window, cx| {
+                                sort_outline_panel
+                                    .update(cx, |outline_panel, cx| {
+                                        outline_panel.toggle_sort_by_name(
+                                            &ToggleSortByName,
+                                            window,
+                                            cx,
+                                        )
+                                    })
+                                    .ok();
+                            },
+                        )
+                        .toggleable_entry(
+                            "Language Server Symbols",
+                            source == OutlineSource::LanguageServer,
+                            IconPosition::Start,
+                            Some(Box::new(ToggleOutlineSource)),
+                            move |window, cx| {
+                                source_outline_panel
+                                    .update(cx, |outline_panel, cx| {
+                                        outline_panel.toggle_outline_source(
+                                            &ToggleOutlineSource,
+                                            window,
+                                            cx,
+                                        )
+                                    })
+                                    .ok();
+                            },
+                        )
+                }))
+            })
+    }
+
     fn buffers_inside_directory(
         &self,
         dir_worktree: WorktreeId,
@@ -4974,6 +5351,22 @@ fn back_to_common_visited_parent(
     None
 }
 
+/// Returns the outlines that are followed by deeper ones, which are their children.
+fn outlines_with_children(outlines: &[Outline]) -> HashSet<(Range<Anchor>, usize)> {
+    outlines
+        .windows(2)
+        .filter_map(|window| {
+            let current = &window[0];
+            let next = &window[1];
+            if next.depth > current.depth {
+                Some((current.range.clone(), current.depth))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
 fn file_name(path: &Path) -> String {
     let mut current_path = path;
     loop {
@@ -5149,6 +5542,12 @@ impl Render for OutlinePanel {
             .on_action(cx.listener(Self::fold_directory))
             .on_action(cx.listener(Self::open_excerpts))
             .on_action(cx.listener(Self::open_excerpts_split))
+            .on_action(cx.listener(Self::toggle_functions))
+            .on_action(cx.listener(Self::toggle_types))
+            .on_action(cx.listener(Self::toggle_fields))
+            .on_action(cx.listener(Self::toggle_constants))
+            .on_action(cx.listener(Self::toggle_sort_by_name))
+            .on_action(cx.listener(Self::toggle_outline_source))
             .when(is_local, |el| {
                 el.on_action(cx.listener(Self::reveal_in_finder))
             })
@@ -5390,6 +5789,7 @@ mod tests {
     use project::FakeFs;
     use search::project_search::{self, perform_project_search};
     use serde_json::json;
+    use smol::stream::StreamExt as _;
     use util::path;
     use workspace::{OpenOptions, OpenVisible};
 
@@ -7654,4 +8054,484 @@ outline: fn main()"
             );
         });
     }
+
+    #[gpui::test]
+    async fn test_outline_kind_filtering_and_sorting(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/test",
+            json!({
+                "src": {
+                    "lib.rs": indoc!("
+                            mod outer {
+                                pub struct OuterStruct {
+                                    field: String,
+                                }
+                                impl OuterStruct {
+                                    pub fn new() -> Self {
+                                        Self { field: String::new() }
+                                    }
+                                }
+                                mod inner {
+                                    pub fn inner_function() {}
+                                    pub struct InnerStruct {
+                                        value: i32,
+                                    }
+                                }
+                            }
+                            fn main() {}
+                        "),
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/test".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(
+                rust_lang()
+                    .with_outline_query(
+                        r#"
+                            (struct_item
+                                (visibility_modifier)? @context
+                                "struct" @context
+                                name: (_) @name) @item
+                            (impl_item
+                                "impl" @context
+                                trait: (_)? @context
+                                "for"? @context
+                                type: (_) @context
+                                body: (_)) @item
+                            (function_item
+                                (visibility_modifier)? @context
+                                "fn" @context
+                                name: (_) @name
+                                parameters: (_) @context) @item
+                            (mod_item
+                                (visibility_modifier)? @context
+                                "mod" @context
+                                name: (_) @name) @item
+                            (field_declaration
+                                (visibility_modifier)? @context
+                                name: (_) @name
+                                ":" @context
+                                type: (_) @context) @item
+                            "#,
+                    )
+                    .unwrap(),
+            ))
+        });
+        let workspace = add_outline_panel(&project, cx).await;
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let outline_panel = outline_panel(&workspace, cx);
+
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.set_active(true, window, cx)
+        });
+
+        workspace
+            .update(cx, |workspace, window, cx| {
+                workspace.open_abs_path(
+                    PathBuf::from("/test/src/lib.rs"),
+                    OpenOptions {
+                        visible: Some(OpenVisible::All),
+                        ..Default::default()
+                    },
+                    window,
+                    cx,
+                )
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.toggle_functions(&ToggleFunctions, window, cx);
+        });
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+        outline_panel.update(cx, |outline_panel, cx| {
+            assert_eq!(
+                display_entries(
+                    &project,
+                    &snapshot(outline_panel, cx),
+                    &outline_panel.cached_entries,
+                    None,
+                    cx,
+                ),
+                indoc!(
+                    "
+outline: mod outer
+  outline: pub struct OuterStruct
+    outline: field: String
+  outline: impl OuterStruct
+  outline: mod inner
+    outline: pub struct InnerStruct
+      outline: value: i32"
+                ),
+                "Functions and methods should be hidden"
+            );
+        });
+
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.toggle_types(&ToggleTypes, window, cx);
+            outline_panel.toggle_sort_by_name(&ToggleSortByName, window, cx);
+        });
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+        outline_panel.update(cx, |outline_panel, cx| {
+            assert_eq!(
+                display_entries(
+                    &project,
+                    &snapshot(outline_panel, cx),
+                    &outline_panel.cached_entries,
+                    None,
+                    cx,
+                ),
+                indoc!(
+                    "
+outline: mod outer
+  outline: field: String
+  outline: mod inner
+    outline: value: i32"
+                ),
+                "Fields of hidden types should take their place, sorted by name"
+            );
+            assert_eq!(
+                outline_panel
+                    .language_options
+                    .get(&LanguageName::new("Rust")),
+                Some(&OutlineOptions {
+                    source: None,
+                    hidden_kinds: BTreeSet::from([OutlineKind::Function, OutlineKind::Type]),
+                    sort: OutlineSort::Name,
+                }),
+                "The options should be kept for the language of the active buffer"
+            );
+        });
+
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.toggle_functions(&ToggleFunctions, window, cx);
+            outline_panel.toggle_types(&ToggleTypes, window, cx);
+        });
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+        outline_panel.update(cx, |outline_panel, cx| {
+            assert_eq!(
+                display_entries(
+                    &project,
+                    &snapshot(outline_panel, cx),
+                    &outline_panel.cached_entries,
+                    None,
+                    cx,
+                ),
+                indoc!(
+                    "
+outline: fn main()
+outline: mod outer
+  outline: impl OuterStruct
+    outline: pub fn new()
+  outline: mod inner
+    outline: pub fn inner_function()
+    outline: pub struct InnerStruct
+      outline: value: i32
+  outline: pub struct OuterStruct
+    outline: field: String"
+                ),
+                "All items should be shown again, still sorted by name"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_outline_from_language_server(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/test",
+            json!({
+                "src": {
+                    "lib.rs": indoc!("
+                            struct Point {
+                                x: i32,
+                            }
+                            fn main() {}
+                        "),
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/test".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(
+            rust_lang()
+                .with_outline_query(
+                    r#"
+                        (struct_item
+                            "struct" @context
+                            name: (_) @name) @item
+                        (field_declaration
+                            name: (_) @name
+                            ":" @context
+                            type: (_) @context) @item
+                        (function_item
+                            "fn" @context
+                            name: (_) @name
+                            parameters: (_) @context) @item
+                    "#,
+                )
+                .unwrap(),
+        ));
+        let mut fake_language_servers = language_registry.register_fake_lsp(
+            "Rust",
+            language::FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    document_symbol_provider: Some(lsp::OneOf::Left(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let workspace = add_outline_panel(&project, cx).await;
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let outline_panel = outline_panel(&workspace, cx);
+
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.set_active(true, window, cx)
+        });
+
+        workspace
+            .update(cx, |workspace, window, cx| {
+                workspace.open_abs_path(
+                    PathBuf::from("/test/src/lib.rs"),
+                    OpenOptions {
+                        visible: Some(OpenVisible::All),
+                        ..Default::default()
+                    },
+                    window,
+                    cx,
+                )
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        let fake_language_server = fake_language_servers.next().await.unwrap();
+        let document_symbols = Arc::new(std::sync::Mutex::new(vec![
+            document_symbol(
+                "Point",
+                lsp::SymbolKind::STRUCT,
+                lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(2, 1)),
+                vec![document_symbol(
+                    "x",
+                    lsp::SymbolKind::FIELD,
+                    lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 10)),
+                    Vec::new(),
+                )],
+            ),
+            document_symbol(
+                "main",
+                lsp::SymbolKind::FUNCTION,
+                lsp::Range::new(lsp::Position::new(3, 0), lsp::Position::new(3, 12)),
+                Vec::new(),
+            ),
+        ]));
+        fake_language_server.set_request_handler::<lsp::request::DocumentSymbolRequest, _, _>({
+            let document_symbols = document_symbols.clone();
+            move |_, _| {
+                let symbols = document_symbols.lock().unwrap().clone();
+                async move { Ok(Some(lsp::DocumentSymbolResponse::Nested(symbols))) }
+            }
+        });
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+
+        let tree_sitter_outline = indoc!(
+            "
+outline: struct Point
+  outline: x: i32
+outline: fn main()"
+        );
+        outline_panel.update(cx, |outline_panel, cx| {
+            assert_eq!(
+                display_entries(
+                    &project,
+                    &snapshot(outline_panel, cx),
+                    &outline_panel.cached_entries,
+                    None,
+                    cx,
+                ),
+                tree_sitter_outline,
+                "Rust has an outline query, so tree-sitter should be used by default"
+            );
+        });
+
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.toggle_outline_source(&ToggleOutlineSource, window, cx);
+        });
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+        outline_panel.update(cx, |outline_panel, cx| {
+            assert_eq!(
+                display_entries(
+                    &project,
+                    &snapshot(outline_panel, cx),
+                    &outline_panel.cached_entries,
+                    None,
+                    cx,
+                ),
+                indoc!(
+                    "
+outline: Point
+  outline: x
+outline: main"
+                ),
+                "Document symbols should replace the tree-sitter outline"
+            );
+            assert_eq!(
+                outline_icons(outline_panel),
+                vec![
+                    ("Point".to_string(), Some(IconName::Blocks)),
+                    ("x".to_string(), Some(IconName::Dash)),
+                    ("main".to_string(), Some(IconName::Code)),
+                ],
+                "Document symbols should be shown with the icons of their kinds"
+            );
+        });
+
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.toggle_functions(&ToggleFunctions, window, cx);
+        });
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+        outline_panel.update(cx, |outline_panel, cx| {
+            assert_eq!(
+                display_entries(
+                    &project,
+                    &snapshot(outline_panel, cx),
+                    &outline_panel.cached_entries,
+                    None,
+                    cx,
+                ),
+                indoc!(
+                    "
+outline: Point
+  outline: x"
+                ),
+                "Hidden kinds should filter the document symbols too"
+            );
+        });
+
+        document_symbols.lock().unwrap().clear();
+        outline_panel.update_in(cx, |outline_panel, window, cx| {
+            outline_panel.toggle_functions(&ToggleFunctions, window, cx);
+        });
+        cx.executor()
+            .advance_clock(UPDATE_DEBOUNCE + Duration::from_millis(500));
+        cx.run_until_parked();
+        outline_panel.update(cx, |outline_panel, cx| {
+            assert_eq!(
+                display_entries(
+                    &project,
+                    &snapshot(outline_panel, cx),
+                    &outline_panel.cached_entries,
+                    None,
+                    cx,
+                ),
+                tree_sitter_outline,
+                "Without document symbols, the tree-sitter outline should be used"
+            );
+            assert_eq!(
+                outline_icons(outline_panel),
+                vec![
+                    ("struct Point".to_string(), None),
+                    ("x: i32".to_string(), None),
+                    ("fn main()".to_string(), None),
+                ],
+                "Tree-sitter items should not get kind icons"
+            );
+        });
+
+        let language_options = outline_panel.read_with(cx, |outline_panel, _| {
+            outline_panel.language_options.clone()
+        });
+        assert_eq!(
+            language_options.get(&LanguageName::new("Rust")),
+            Some(&OutlineOptions {
+                source: Some(OutlineSource::LanguageServer),
+                hidden_kinds: BTreeSet::default(),
+                sort: OutlineSort::default(),
+            }),
+        );
+        let stored_options = KEY_VALUE_STORE
+            .read_kvp(OUTLINE_OPTIONS_KEY)
+            .unwrap()
+            .expect("outline options should be stored");
+        assert_eq!(
+            serde_json::from_str::<HashMap<LanguageName, OutlineOptions>>(&stored_options).unwrap(),
+            language_options,
+        );
+
+        let new_workspace = add_outline_panel(&project, cx).await;
+        let new_outline_panel = new_workspace
+            .update(cx, |workspace, _, cx| workspace.panel::<OutlinePanel>(cx))
+            .unwrap()
+            .expect("no outline panel");
+        new_outline_panel.read_with(cx, |new_outline_panel, _| {
+            assert_eq!(
+                new_outline_panel.language_options, language_options,
+                "Loaded outline panels should restore the stored options"
+            );
+        });
+    }
+
+    fn outline_icons(outline_panel: &OutlinePanel) -> Vec<(String, Option<IconName>)> {
+        outline_panel
+            .cached_entries
+            .iter()
+            .filter_map(|cached_entry| match &cached_entry.entry {
+                PanelEntry::Outline(OutlineEntry::Outline(outline)) => {
+                    let kind = outline_panel
+                        .outline_kinds
+                        .get(&outline.buffer_id)
+                        .and_then(|kinds| {
+                            kinds.get(&(outline.outline.range.clone(), outline.outline.depth))
+                        });
+                    Some((outline.outline.text.clone(), kind.map(|kind| kind.icon())))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[allow(deprecated)]
+    fn document_symbol(
+        name: &str,
+        kind: lsp::SymbolKind,
+        range: lsp::Range,
+        children: Vec<lsp::DocumentSymbol>,
+    ) -> lsp::DocumentSymbol {
+        lsp::DocumentSymbol {
+            name: name.to_string(),
+            detail: None,
+            kind,
+            tags: None,
+            deprecated: None,
+            range,
+            selection_range: range,
+            children: Some(children),
+        }
+    }
 }
@@ -212,6 +212,7 @@ impl DapLocator for CargoLocator {
             cwd: build_config.cwd,
             args,
             env: build_config.env.into_iter().collect(),
+            core_file: None,
         }))
     }
 }
@@ -6,12 +6,13 @@ use super::breakpoint_store::{
     BreakpointStore, BreakpointStoreEvent, BreakpointUpdatedReason, SourceBreakpoint,
 };
 use super::dap_command::{
-    self, Attach, ConfigurationDone, ContinueCommand, DataBreakpointInfoCommand, DisconnectCommand,
-    EvaluateCommand, Initialize, Launch, LoadedSourcesCommand, LocalDapCommand, LocationsCommand,
-    ModulesCommand, NextCommand, PauseCommand, RestartCommand, RestartStackFrameCommand,
-    ScopesCommand, SetDataBreakpointsCommand, SetExceptionBreakpoints, SetVariableValueCommand,
-    StackTraceCommand, StepBackCommand, StepCommand, StepInCommand, StepOutCommand,
-    TerminateCommand, TerminateThreadsCommand, ThreadsCommand, VariablesCommand,
+    self, Attach, ConfigurationDone, ContinueCommand, DataBreakpointInfoCommand,
+    DisassembleCommand, DisconnectCommand, EvaluateCommand, Initialize, Launch,
+    LoadedSourcesCommand, LocalDapCommand, LocationsCommand, ModulesCommand, NextCommand,
+    PauseCommand, RestartCommand, RestartStackFrameCommand, ScopesCommand,
+    SetDataBreakpointsCommand, SetExceptionBreakpoints, SetInstructionBreakpointsCommand,
+    SetVariableValueCommand, StackTraceCommand, StepBackCommand, StepCommand, StepInCommand,
+    StepOutCommand, TerminateCommand, TerminateThreadsCommand, ThreadsCommand, VariablesCommand,
 };
 use super::dap_store::DapStore;
 use anyhow::{Context as _, Result, anyhow};
@@ -140,6 +141,12 @@ pub struct DataBreakpointState {
     pub context: Arc<DataBreakpointContext>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstructionBreakpointState {
+    pub dap: dap::InstructionBreakpoint,
+    pub is_enabled: bool,
+}
+
 pub enum SessionState {
     /// Represents a session that is building/initializing
     /// even if a session doesn't have a pre build task this state
@@ -181,6 +188,21 @@ fn client_source(abs_path: &Path) -> dap::Source {
     }
 }
 
+/// Masks out the capabilities that would modify a debuggee, for sessions that inspect a core dump.
+fn read_only_capabilities(capabilities: Capabilities) -> Capabilities {
+    Capabilities {
+        supports_set_variable: Some(false),
+        supports_set_expression: Some(false),
+        supports_write_memory_request: Some(false),
+        supports_restart_frame: Some(false),
+        supports_step_back: Some(false),
+        supports_goto_targets_request: Some(false),
+        supports_data_breakpoints: Some(false),
+        supports_instruction_breakpoints: Some(false),
+        ..capabilities
+    }
+}
+
 impl RunningMode {
     async fn new(
         session_id: SessionId,
@@ -691,6 +713,8 @@ pub struct Session {
     ignore_breakpoints: bool,
     exception_breakpoints: BTreeMap<String, (ExceptionBreakpointsFilter, IsEnabled)>,
     data_breakpoints: BTreeMap<String, DataBreakpointState>,
+    instruction_breakpoints: BTreeMap<String, InstructionBreakpointState>,
+    disassembly: HashMap<DisassembleCommand, Arc<[dap::DisassembledInstruction]>>,
     background_tasks: Vec<Task<()>>,
     restart_task: Option<Task<()>>,
     task_context: TaskContext,
@@ -785,6 +809,7 @@ pub enum SessionEvent {
     },
     DataBreakpointInfo,
     ConsoleOutput,
+    Disassembly,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -861,6 +886,8 @@ impl Session {
                 ignore_breakpoints: false,
                 breakpoint_store,
                 data_breakpoints: Default::default(),
+                instruction_breakpoints: Default::default(),
+                disassembly: HashMap::default(),
                 exception_breakpoints: Default::default(),
                 label,
                 adapter,
@@ -1277,7 +1304,11 @@ impl Session {
                     }
                     Ok(capabilities) => {
                         this.update(cx, |session, cx| {
-                            session.capabilities = capabilities;
+                            session.capabilities = if session.is_post_mortem() {
+                                read_only_capabilities(capabilities)
+                            } else {
+                                capabilities
+                            };
 
                             cx.emit(SessionEvent::CapabilitiesLoaded);
                         })?;
@@ -1527,6 +1558,9 @@ impl Session {
             }
             Events::Capabilities(event) => {
                 self.capabilities = self.capabilities.merge(event.capabilities);
+                if self.is_post_mortem() {
+                    self.capabilities = read_only_capabilities(self.capabilities.clone());
+                }
 
                 // The adapter might've enabled new exception breakpoints (or disabled existing ones).
                 let recent_filters = self
@@ -1673,6 +1707,7 @@ impl Session {
         self.invalidate_command_type::<ThreadsCommand>();
         self.invalidate_command_type::<DataBreakpointInfoCommand>();
         self.invalidate_command_type::<ReadMemory>();
+        self.invalidate_command_type::<DisassembleCommand>();
         let executor = self.as_running().map(|running| running.executor.clone());
         if let Some(executor) = executor {
             self.memory.clear(&executor);
@@ -1986,6 +2021,101 @@ impl Session {
         self.send_data_breakpoints(cx);
     }
 
+    pub fn instruction_breakpoints(&self) -> impl Iterator<Item = &InstructionBreakpointState> {
+        self.instruction_breakpoints.values()
+    }
+
+    /// Adds an instruction breakpoint at the given instruction reference, or removes it if one is already set there.
+    pub fn toggle_instruction_breakpoint(
+        &mut self,
+        instruction_reference: &str,
+        cx: &mut Context<Self>,
+    ) {
+        if self
+            .instruction_breakpoints
+            .remove(instruction_reference)
+            .is_none()
+        {
+            self.instruction_breakpoints.insert(
+                instruction_reference.to_string(),
+                InstructionBreakpointState {
+                    dap: dap::InstructionBreakpoint {
+                        instruction_reference: instruction_reference.to_string(),
+                        offset: None,
+                        condition: None,
+                        hit_condition: None,
+                        mode: None,
+                    },
+                    is_enabled: true,
+                },
+            );
+        }
+        self.send_instruction_breakpoints(cx);
+        cx.notify();
+    }
+
+    pub fn toggle_instruction_breakpoint_enabled(
+        &mut self,
+        instruction_reference: &str,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(state) = self.instruction_breakpoints.get_mut(instruction_reference) {
+            state.is_enabled = !state.is_enabled;
+            self.send_instruction_breakpoints(cx);
+            cx.notify();
+        }
+    }
+
+    fn send_instruction_breakpoints(&mut self, cx: &mut Context<Self>) {
+        if let Some(mode) = self.as_running() {
+            let breakpoints = self
+                .instruction_breakpoints
+                .values()
+                .filter_map(|state| state.is_enabled.then(|| state.dap.clone()))
+                .collect();
+            let command = SetInstructionBreakpointsCommand { breakpoints };
+            mode.request(command).detach_and_log_err(cx);
+        }
+    }
+
+    /// Disassembles `instruction_count` instructions starting `instruction_offset` instructions away from `memory_reference`.
+    ///
+    /// Returns the last known disassembly of that range while a fresh one is being fetched.
+    pub fn disassemble(
+        &mut self,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: u64,
+        cx: &mut Context<Self>,
+    ) -> Option<Arc<[dap::DisassembledInstruction]>> {
+        let command = DisassembleCommand {
+            memory_reference,
+            instruction_offset,
+            instruction_count,
+        };
+        self.fetch(
+            command.clone(),
+            {
+                let command = command.clone();
+                move |this, result, cx| {
+                    let Some(instructions) = result.log_err() else {
+                        return;
+                    };
+                    this.disassembly.insert(command, instructions.into());
+                    cx.emit(SessionEvent::Disassembly);
+                    cx.notify();
+                }
+            },
+            cx,
+        );
+
+        self.disassembly.get(&command).cloned()
+    }
+
+    pub fn stack_frame(&self, stack_frame_id: StackFrameId) -> Option<&StackFrame> {
+        self.stack_frames.get(&stack_frame_id)
+    }
+
     pub fn breakpoints_enabled(&self) -> bool {
         self.ignore_breakpoints
     }
@@ -2060,6 +2190,10 @@ impl Session {
     }
 
     pub fn pause_thread(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
+        if self.is_post_mortem() {
+            return;
+        }
+
         self.request(
             PauseCommand {
                 thread_id: thread_id.0,
@@ -2071,6 +2205,10 @@ impl Session {
     }
 
     pub fn restart_stack_frame(&mut self, stack_frame_id: u64, cx: &mut Context<Self>) {
+        if self.is_post_mortem() {
+            return;
+        }
+
         self.request(
             RestartStackFrameCommand { stack_frame_id },
             Self::empty_response,
@@ -2176,6 +2314,10 @@ impl Session {
     }
 
     pub fn continue_thread(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
+        if self.is_post_mortem() {
+            return;
+        }
+
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         self.thread_states.continue_thread(thread_id);
@@ -2208,6 +2350,10 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
+
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2238,6 +2384,10 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
+
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2268,6 +2418,10 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
+
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2298,6 +2452,10 @@ impl Session {
         granularity: SteppingGranularity,
         cx: &mut Context<Self>,
     ) {
+        if self.is_post_mortem() {
+            return;
+        }
+
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         let supports_stepping_granularity = self
@@ -2673,6 +2831,22 @@ impl Session {
         self.locations.get(&reference).cloned()
     }
 
+    /// Whether this session inspects a core dump rather than a live process.
+    ///
+    /// Adapters put the core file under `coreFile` in their launch configuration. Such sessions
+    /// are read-only: execution control requests are never sent and writes are reported as unsupported.
+    pub fn is_post_mortem(&self) -> bool {
+        let SessionState::Running(local_mode) = &self.mode else {
+            return false;
+        };
+        local_mode
+            .binary
+            .request_args
+            .configuration
+            .get("coreFile")
+            .is_some()
+    }
+
     pub fn is_attached(&self) -> bool {
         let SessionState::Running(local_mode) = &self.mode else {
             return false;
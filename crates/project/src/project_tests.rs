@@ -39,7 +39,7 @@ use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
 use std::{env, mem, num::NonZeroU32, ops::Range, str::FromStr, sync::OnceLock, task::Poll};
-use task::{ResolvedTask, ShellKind, TaskContext};
+use task::{ProblemSeverity, ResolvedTask, ShellKind, TaskContext, TaskProblem};
 use unindent::Unindent as _;
 use util::{
     TryFutureExt as _, assert_set_eq, maybe, path,
@@ -2720,6 +2720,134 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_task_problems_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({ "a.rs": "one two three\nfour five six", "b.rs": "seven" }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let problem = |path: &str, line, column, severity, message: &str| TaskProblem {
+        path: PathBuf::from(path),
+        line,
+        column,
+        end_line: None,
+        end_column: None,
+        severity,
+        code: None,
+        message: message.to_string(),
+    };
+    project.update(cx, |project, cx| {
+        project.set_task_problems(
+            "build".to_string(),
+            vec![
+                problem(
+                    path!("/dir/a.rs"),
+                    1,
+                    Some(5),
+                    ProblemSeverity::Error,
+                    "bad two",
+                ),
+                problem(
+                    path!("/dir/b.rs"),
+                    1,
+                    None,
+                    ProblemSeverity::Warning,
+                    "odd seven",
+                ),
+            ],
+            cx,
+        );
+        project.set_task_problems(
+            "lint".to_string(),
+            vec![problem(
+                path!("/dir/a.rs"),
+                2,
+                Some(6),
+                ProblemSeverity::Warning,
+                "lint five",
+            )],
+            cx,
+        );
+    });
+    cx.run_until_parked();
+
+    let diagnostics = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| {
+                    (
+                        entry.range,
+                        entry.diagnostic.source.unwrap(),
+                        entry.diagnostic.message,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        diagnostics(cx),
+        vec![
+            (
+                Point::new(0, 4)..Point::new(0, 5),
+                "build".to_string(),
+                "bad two".to_string()
+            ),
+            (
+                Point::new(1, 5)..Point::new(1, 6),
+                "lint".to_string(),
+                "lint five".to_string()
+            ),
+        ]
+    );
+    project.read_with(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 2,
+            }
+        );
+    });
+
+    // The next run of a task clears its previous problems, leaving the other tasks' ones.
+    project.update(cx, |project, cx| {
+        project.set_task_problems("build".to_string(), Vec::new(), cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        diagnostics(cx),
+        vec![(
+            Point::new(1, 5)..Point::new(1, 6),
+            "lint".to_string(),
+            "lint five".to_string()
+        )]
+    );
+    project.read_with(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 0,
+                warning_count: 1,
+            }
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -6102,6 +6230,132 @@ async fn test_search_with_unicode(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_fuzzy_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "let foo_bar = FooBar::new();",
+            "two.rs": "let bar_foo = fob;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+
+    let query = SearchQuery::fuzzy(
+        "fbr",
+        false,
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    assert!(query.is_fuzzy());
+    assert_eq!(query.as_str(), "fbr");
+    assert_eq!(
+        search(&project, query, cx).await.unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![4..11, 14..20])]),
+        "Identifiers containing the query's characters in order should match"
+    );
+
+    let query = SearchQuery::fuzzy(
+        "FB",
+        true,
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query, cx).await.unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![14..20])]),
+    );
+}
+
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "main.rs": "fn main() {\n    let a = parse(x).unwrap();\n    let b = value.unwrap();\n    let c = compute(1, 2).unwrap();\n}\n",
+            "lib.rs": "fn same(x: usize, y: usize) -> bool {\n    x == x || x == y\n}\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let main_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/main.rs"), cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/lib.rs"), cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    let query = SearchQuery::structural(
+        "$F($$$ARGS).unwrap()",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([(path!("dir/main.rs").to_string(), vec![24..41, 83..105])]),
+        "Only calls whose receiver is a call should match"
+    );
+
+    let query = query.with_replacement("$F($$$ARGS)?".to_string());
+    let snapshot = main_buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(
+        query
+            .structural_replacement_for(&snapshot, 24..41)
+            .as_deref(),
+        Some("parse(x)?")
+    );
+    assert_eq!(
+        query
+            .structural_replacement_for(&snapshot, 83..105)
+            .as_deref(),
+        Some("compute(1, 2)?")
+    );
+
+    let query = SearchQuery::structural(
+        "$A == $A",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query, cx).await.unwrap(),
+        HashMap::from_iter([(path!("dir/lib.rs").to_string(), vec![42..48])]),
+        "A repeated metavariable should only match identical nodes"
+    );
+}
+
 #[gpui::test]
 async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1,3 +1,4 @@
+use crate::structural_search::StructuralPattern;
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
@@ -73,6 +74,15 @@ pub enum SearchQuery {
         case_sensitive: bool,
         include_ignored: bool,
         one_match_per_line: bool,
+        /// Whether `regex` was built from a fuzzy query, see [`SearchQuery::fuzzy`].
+        fuzzy: bool,
+        inner: SearchInputs,
+    },
+
+    Structural {
+        pattern: StructuralPattern,
+        replacement: Option<String>,
+        include_ignored: bool,
         inner: SearchInputs,
     },
 }
@@ -197,6 +207,73 @@ impl SearchQuery {
             include_ignored,
             inner,
             one_match_per_line,
+            fuzzy: false,
+        })
+    }
+
+    /// Create a fuzzy query, matching identifiers that contain the query's characters in order,
+    /// e.g. `fbr` matches `foo_bar` and `FooBar`.
+    pub fn fuzzy(
+        query: impl ToString,
+        case_sensitive: bool,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let mut pattern = String::from(r"\b\w*?");
+        for (ix, char) in query.chars().filter(|c| !c.is_whitespace()).enumerate() {
+            if ix > 0 {
+                pattern.push_str(r"\w*?");
+            }
+            pattern.push_str(&regex::escape(char.encode_utf8(&mut [0; 4])));
+        }
+        pattern.push_str(r"\w*");
+
+        let mut search = Self::regex(
+            pattern,
+            false,
+            case_sensitive,
+            include_ignored,
+            false,
+            files_to_include,
+            files_to_exclude,
+            match_full_paths,
+            buffers,
+        )?;
+        if let Self::Regex { fuzzy, inner, .. } = &mut search {
+            *fuzzy = true;
+            inner.query = query.into();
+        }
+        Ok(search)
+    }
+
+    /// Create a structural query, matching syntax trees against an ast-grep-style pattern
+    /// such as `$X.unwrap()`. See [`StructuralPattern`] for the pattern syntax.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            match_full_paths,
+            buffers,
+        };
+        Ok(Self::Structural {
+            pattern,
+            replacement: None,
+            include_ignored,
+            inner,
         })
     }
 
@@ -264,7 +341,26 @@ impl SearchQuery {
             message.files_to_exclude
         };
 
-        if message.regex {
+        if message.fuzzy {
+            Self::fuzzy(
+                message.query,
+                message.case_sensitive,
+                message.include_ignored,
+                PathMatcher::new(files_to_include)?,
+                PathMatcher::new(files_to_exclude)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                PathMatcher::new(files_to_include)?,
+                PathMatcher::new(files_to_exclude)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -299,6 +395,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -312,6 +412,8 @@ impl SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
+            fuzzy: self.is_fuzzy(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -341,6 +443,16 @@ impl SearchQuery {
                     None => Ok(false),
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let Some(literal) = pattern.literal() else {
+                    return Ok(true);
+                };
+                match literal.stream_find_iter(reader).next() {
+                    Some(Ok(_)) => Ok(true),
+                    Some(Err(err)) => Err(err.into()),
+                    None => Ok(false),
+                }
+            }
             Self::Regex {
                 regex, multiline, ..
             } => {
@@ -366,15 +478,18 @@ impl SearchQuery {
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
+    ///
+    /// Structural queries need the syntax tree around the match, see [`Self::structural_replacement_for`].
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
+            SearchQuery::Structural { .. } => None,
             SearchQuery::Regex {
                 regex, replacement, ..
             } => {
@@ -398,6 +513,23 @@ impl SearchQuery {
         }
     }
 
+    /// Renders the replacement template of a structural query for the match spanning `range` in `buffer`,
+    /// substituting the text captured by each metavariable.
+    pub fn structural_replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement: Some(replacement),
+                ..
+            } => pattern.replacement(buffer, range, replacement),
+            _ => None,
+        }
+    }
+
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -409,6 +541,11 @@ impl SearchQuery {
             return Default::default();
         }
 
+        if let Self::Structural { pattern, .. } = self {
+            let range = subrange.unwrap_or(0..buffer.len());
+            return pattern.search(buffer, range).await;
+        }
+
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
         let rope = if let Some(range) = subrange {
             buffer.as_rope().slice(range)
@@ -493,6 +630,7 @@ impl SearchQuery {
                     }
                 }
             }
+            Self::Structural { .. } => unreachable!("structural queries are handled above"),
         }
 
         matches
@@ -510,6 +648,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -517,6 +656,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -528,6 +668,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -535,6 +678,14 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_fuzzy(&self) -> bool {
+        matches!(self, Self::Regex { fuzzy: true, .. })
+    }
+
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -578,20 +729,22 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 
     /// Whether this search should replace only one match per line, instead of
     /// all matches.
-    /// Returns `None` for text searches, as only regex searches support this
-    /// option.
+    /// Returns `None` for text and structural searches, as only regex searches
+    /// support this option.
     pub fn one_match_per_line(&self) -> Option<bool> {
         match self {
             Self::Regex {
                 one_match_per_line, ..
             } => Some(*one_match_per_line),
-            Self::Text { .. } => None,
+            Self::Text { .. } | Self::Structural { .. } => None,
         }
     }
 }
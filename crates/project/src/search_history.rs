@@ -93,6 +93,17 @@ impl SearchHistory {
     }
 }
 
+/// A search saved under a name, so that it can be run again later.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub files_to_include: String,
+    pub files_to_exclude: String,
+    /// The bits of the search options (whole word, regex, ...) the search was saved with.
+    pub search_options: u16,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,567 @@
+//! Structural (syntax-aware) search, in the style of ast-grep.
+//!
+//! A structural pattern is a snippet of source code in the language being
+//! searched, where `$NAME` stands for any single syntax node and `$$$NAME`
+//! stands for any number of sibling nodes. For example, `$X.unwrap()` matches
+//! every call to `unwrap`, whatever its receiver is. A metavariable used more
+//! than once must match the same text each time, and `$_` / `$$$_` match
+//! without capturing anything.
+//!
+//! Patterns are parsed lazily, once per language, with the same tree-sitter
+//! grammar that produced the buffer's syntax tree, and then compared node by
+//! node against every syntax layer of the buffer.
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
+use collections::HashMap;
+use language::{BufferSnapshot, Language, LanguageName, Node, Tree, with_parser};
+use parking_lot::Mutex;
+use smol::future::yield_now;
+use std::{fmt, ops::Range, sync::Arc};
+use text::Rope;
+
+const SINGLE_METAVARIABLE_PREFIX: &str = "zed_meta_var_";
+const MULTIPLE_METAVARIABLE_PREFIX: &str = "zed_meta_vars_";
+const WILDCARD: &str = "_";
+
+#[derive(Clone)]
+pub struct StructuralPattern {
+    /// The pattern, with metavariables rewritten into identifiers that any
+    /// grammar can parse.
+    expanded: Arc<str>,
+    /// The longest literal word of the pattern, used to skip files that
+    /// cannot possibly match.
+    literal: Option<AhoCorasick>,
+    compiled: Arc<Mutex<HashMap<LanguageName, Option<Arc<CompiledPattern>>>>>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("expanded", &self.expanded)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let expanded = expand_metavariables(pattern);
+        let literal = longest_literal_word(pattern)
+            .map(|word| AhoCorasickBuilder::new().build([word]))
+            .transpose()?;
+        Ok(Self {
+            expanded: expanded.into(),
+            literal,
+            compiled: Default::default(),
+        })
+    }
+
+    pub fn literal(&self) -> Option<&AhoCorasick> {
+        self.literal.as_ref()
+    }
+
+    /// Returns the ranges of all nodes in `range` that match this pattern. Matches never overlap,
+    /// and are returned relative to the start of `range`.
+    pub async fn search(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+        let rope = buffer.as_rope();
+        let layers = buffer.syntax_layers().collect::<Vec<_>>();
+        let mut matches = Vec::new();
+        for layer in layers {
+            let Some(compiled) = self.compiled_for(layer.language) else {
+                continue;
+            };
+            compiled.find_matches(layer.node(), &range, rope, &mut matches);
+            yield_now().await;
+        }
+
+        matches.sort_unstable_by_key(|range| (range.start, range.end));
+        matches.dedup();
+        matches
+            .into_iter()
+            .map(|matched| matched.start - range.start..matched.end - range.start)
+            .collect()
+    }
+
+    /// Renders `template` for the node spanning exactly `range`, substituting the text captured by
+    /// each metavariable. Returns `None` if no such node matches this pattern.
+    pub fn replacement(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        template: &str,
+    ) -> Option<String> {
+        let rope = buffer.as_rope();
+        for layer in buffer.syntax_layers() {
+            let Some(compiled) = self.compiled_for(layer.language) else {
+                continue;
+            };
+            let mut node = layer
+                .node()
+                .descendant_for_byte_range(range.start, range.end);
+            while let Some(candidate) = node.filter(|node| node.byte_range() == range) {
+                if let Some(captures) = compiled.matches(candidate, rope) {
+                    return Some(render_template(template, &captures, rope));
+                }
+                node = candidate.parent();
+            }
+        }
+        None
+    }
+
+    fn compiled_for(&self, language: &Arc<Language>) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(language.name())
+            .or_insert_with(|| CompiledPattern::new(&self.expanded, language).map(Arc::new))
+            .clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Metavariable<'a> {
+    Single(&'a str),
+    Multiple(&'a str),
+}
+
+type Captures = HashMap<String, Range<usize>>;
+
+struct CompiledPattern {
+    source: String,
+    tree: Tree,
+    root_range: Range<usize>,
+}
+
+impl CompiledPattern {
+    fn new(expanded: &str, language: &Language) -> Option<Self> {
+        let grammar = language.grammar()?;
+        let start = expanded.len() - expanded.trim_start().len();
+        let end = expanded.trim_end().len();
+        if start >= end {
+            return None;
+        }
+
+        // Expressions such as `$X.unwrap()` are only valid as statements in many grammars.
+        ["", ";"].into_iter().find_map(|suffix| {
+            let source = format!("{expanded}{suffix}");
+            let tree = with_parser(|parser| {
+                parser.set_language(&grammar.ts_language).ok()?;
+                parser.parse(&source, None)
+            })?;
+            let root = tree.root_node().descendant_for_byte_range(start, end)?;
+            if root.has_error() || root.byte_range() != (start..end) {
+                return None;
+            }
+            let root_range = root.byte_range();
+            Some(Self {
+                source,
+                tree,
+                root_range,
+            })
+        })
+    }
+
+    fn root(&self) -> Node<'_> {
+        self.tree
+            .root_node()
+            .descendant_for_byte_range(self.root_range.start, self.root_range.end)
+            .expect("pattern root must exist")
+    }
+
+    fn find_matches(
+        &self,
+        root: Node,
+        range: &Range<usize>,
+        rope: &Rope,
+        matches: &mut Vec<Range<usize>>,
+    ) {
+        let pattern = self.root();
+        let mut cursor = root.walk();
+        loop {
+            let node = cursor.node();
+            let node_range = node.byte_range();
+            let descend = if node_range.end <= range.start || node_range.start >= range.end {
+                false
+            } else if node_range.start >= range.start
+                && node_range.end <= range.end
+                && self.matches_node(pattern, node, rope, &mut Captures::default())
+            {
+                matches.push(node_range);
+                false
+            } else {
+                true
+            };
+
+            if descend && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn matches(&self, target: Node, rope: &Rope) -> Option<Captures> {
+        let mut captures = Captures::default();
+        self.matches_node(self.root(), target, rope, &mut captures)
+            .then_some(captures)
+    }
+
+    fn matches_node(
+        &self,
+        pattern: Node,
+        target: Node,
+        rope: &Rope,
+        captures: &mut Captures,
+    ) -> bool {
+        if let Some(Metavariable::Single(name) | Metavariable::Multiple(name)) =
+            self.metavariable(pattern)
+        {
+            return bind(name, target.byte_range(), rope, captures);
+        }
+        if pattern.kind_id() != target.kind_id() {
+            return false;
+        }
+
+        let pattern_children = children(pattern);
+        if pattern_children.is_empty() {
+            let expected = &self.source[pattern.byte_range()];
+            return text_eq(rope, target.byte_range(), expected);
+        }
+        let target_children = children(target);
+        self.matches_children(
+            &pattern_children,
+            &target_children,
+            target.end_byte(),
+            rope,
+            captures,
+        )
+    }
+
+    fn matches_children(
+        &self,
+        patterns: &[Node],
+        targets: &[Node],
+        end: usize,
+        rope: &Rope,
+        captures: &mut Captures,
+    ) -> bool {
+        let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+            return targets.is_empty();
+        };
+
+        if let Some(Metavariable::Multiple(name)) = self.metavariable(*pattern) {
+            for count in 0..=targets.len() {
+                let (consumed, remaining_targets) = targets.split_at(count);
+                let range = match (consumed.first(), consumed.last()) {
+                    (Some(first), Some(last)) => first.start_byte()..last.end_byte(),
+                    _ => {
+                        let position = remaining_targets.first().map_or(end, Node::start_byte);
+                        position..position
+                    }
+                };
+                let mut attempt = captures.clone();
+                if bind(name, range, rope, &mut attempt)
+                    && self.matches_children(
+                        remaining_patterns,
+                        remaining_targets,
+                        end,
+                        rope,
+                        &mut attempt,
+                    )
+                {
+                    *captures = attempt;
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        let Some((target, remaining_targets)) = targets.split_first() else {
+            return false;
+        };
+        self.matches_node(*pattern, *target, rope, captures)
+            && self.matches_children(remaining_patterns, remaining_targets, end, rope, captures)
+    }
+
+    fn metavariable(&self, node: Node) -> Option<Metavariable<'_>> {
+        let text = self.source[node.byte_range()].trim_end_matches([';', ' ', '\t', '\n']);
+        if let Some(name) = text.strip_prefix(MULTIPLE_METAVARIABLE_PREFIX) {
+            is_metavariable_name(name).then_some(Metavariable::Multiple(name))
+        } else if let Some(name) = text.strip_prefix(SINGLE_METAVARIABLE_PREFIX) {
+            is_metavariable_name(name).then_some(Metavariable::Single(name))
+        } else {
+            None
+        }
+    }
+}
+
+fn children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect()
+}
+
+fn bind(name: &str, range: Range<usize>, rope: &Rope, captures: &mut Captures) -> bool {
+    if name == WILDCARD {
+        return true;
+    }
+    if let Some(existing) = captures.get(name) {
+        let existing = rope.chunks_in_range(existing.clone()).collect::<String>();
+        return text_eq(rope, range, &existing);
+    }
+    captures.insert(name.to_string(), range);
+    true
+}
+
+fn text_eq(rope: &Rope, range: Range<usize>, expected: &str) -> bool {
+    if range.len() != expected.len() {
+        return false;
+    }
+    let mut expected = expected.as_bytes();
+    for chunk in rope.chunks_in_range(range) {
+        let Some(rest) = expected.strip_prefix(chunk.as_bytes()) else {
+            return false;
+        };
+        expected = rest;
+    }
+    expected.is_empty()
+}
+
+fn is_metavariable_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Splits `$NAME` or `$$$NAME` off the start of `text`, returning the metavariable and the
+/// length of its source text.
+fn parse_metavariable(text: &str) -> Option<(Metavariable<'_>, usize)> {
+    let (sigil, multiple) = if text.starts_with("$$$") {
+        ("$$$", true)
+    } else if text.starts_with('$') {
+        ("$", false)
+    } else {
+        return None;
+    };
+    let rest = &text[sigil.len()..];
+    let name_len = rest
+        .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+        .unwrap_or(rest.len());
+    let name = &rest[..name_len];
+    if !is_metavariable_name(name) {
+        return None;
+    }
+    let metavariable = if multiple {
+        Metavariable::Multiple(name)
+    } else {
+        Metavariable::Single(name)
+    };
+    Some((metavariable, sigil.len() + name_len))
+}
+
+/// Calls `f` with each literal fragment and metavariable of `text`, in order.
+fn for_each_metavariable<'a>(text: &'a str, mut f: impl FnMut(&'a str, Option<Metavariable<'a>>)) {
+    let mut rest = text;
+    while let Some(ix) = rest.find('$') {
+        if let Some((metavariable, len)) = parse_metavariable(&rest[ix..]) {
+            f(&rest[..ix], Some(metavariable));
+            rest = &rest[ix + len..];
+        } else {
+            f(&rest[..=ix], None);
+            rest = &rest[ix + 1..];
+        }
+    }
+    f(rest, None);
+}
+
+fn expand_metavariables(pattern: &str) -> String {
+    let mut expanded = String::with_capacity(pattern.len());
+    for_each_metavariable(pattern, |literal, metavariable| {
+        expanded.push_str(literal);
+        match metavariable {
+            Some(Metavariable::Single(name)) => {
+                expanded.push_str(SINGLE_METAVARIABLE_PREFIX);
+                expanded.push_str(name);
+            }
+            Some(Metavariable::Multiple(name)) => {
+                expanded.push_str(MULTIPLE_METAVARIABLE_PREFIX);
+                expanded.push_str(name);
+            }
+            None => {}
+        }
+    });
+    expanded
+}
+
+fn longest_literal_word(pattern: &str) -> Option<String> {
+    let mut literals = String::new();
+    for_each_metavariable(pattern, |literal, _| {
+        literals.push_str(literal);
+        literals.push(' ');
+    });
+    literals
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .max_by_key(|word| word.len())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+}
+
+fn render_template(template: &str, captures: &Captures, rope: &Rope) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    for_each_metavariable(template, |literal, metavariable| {
+        rendered.push_str(literal);
+        if let Some(Metavariable::Single(name) | Metavariable::Multiple(name)) = metavariable {
+            match captures.get(name) {
+                Some(range) => rendered.extend(rope.chunks_in_range(range.clone())),
+                None => rendered.push_str(&format!("${name}")),
+            }
+        }
+    });
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use language::{Buffer, LanguageConfig, tree_sitter_rust};
+
+    #[gpui::test]
+    async fn test_multiple_metavariables(cx: &mut TestAppContext) {
+        let snapshot = rust_buffer(
+            "fn main() {\n    map.insert(key, value);\n    map.insert(key);\n    other.insert(1, 2).unwrap();\n    call(1, 2, 3);\n    call(1);\n}\n",
+            cx,
+        );
+
+        let pattern = StructuralPattern::new("$MAP.insert($KEY, $VALUE)").unwrap();
+        let matches = pattern.search(&snapshot, 0..snapshot.len()).await;
+        assert_eq!(
+            texts(&snapshot, &matches),
+            ["map.insert(key, value)", "other.insert(1, 2)"]
+        );
+        assert_eq!(
+            pattern
+                .replacement(
+                    &snapshot,
+                    matches[1].clone(),
+                    "$MAP.entry($KEY).or_insert($VALUE)"
+                )
+                .as_deref(),
+            Some("other.entry(1).or_insert(2)")
+        );
+
+        let pattern = StructuralPattern::new("call($FIRST, $$$REST)").unwrap();
+        let matches = pattern.search(&snapshot, 0..snapshot.len()).await;
+        assert_eq!(texts(&snapshot, &matches), ["call(1, 2, 3)"]);
+        assert_eq!(
+            pattern
+                .replacement(
+                    &snapshot,
+                    matches[0].clone(),
+                    "call($$$REST, $FIRST, $UNBOUND)"
+                )
+                .as_deref(),
+            Some("call(2, 3, 1, $UNBOUND)"),
+            "Metavariables that capture nothing should be kept as they are"
+        );
+
+        let pattern = StructuralPattern::new("$_.insert($_, $_)").unwrap();
+        assert_eq!(
+            pattern.search(&snapshot, 0..snapshot.len()).await.len(),
+            2,
+            "Wildcards should match different nodes each time"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_nested_and_overlapping_matches(cx: &mut TestAppContext) {
+        let text = "fn main() {\n    f(g(h(1)));\n    a.unwrap().unwrap();\n}\n";
+        let snapshot = rust_buffer(text, cx);
+
+        let pattern = StructuralPattern::new("$F($X)").unwrap();
+        let matches = pattern.search(&snapshot, 0..snapshot.len()).await;
+        assert_eq!(
+            texts(&snapshot, &matches),
+            ["f(g(h(1)))"],
+            "Nodes within a match should not be reported again"
+        );
+
+        let inner = text.find("g(h(1))").unwrap();
+        let inner = inner..inner + "g(h(1))".len();
+        assert_eq!(
+            pattern
+                .replacement(&snapshot, inner.clone(), "$X")
+                .as_deref(),
+            Some("h(1)"),
+            "Nested matches should still be replaceable on their own"
+        );
+        assert_eq!(
+            pattern.search(&snapshot, inner.clone()).await,
+            [0..inner.len()],
+            "Matches should be found within the range searched, relative to its start"
+        );
+
+        let pattern = StructuralPattern::new("$X.unwrap()").unwrap();
+        let matches = pattern.search(&snapshot, 0..snapshot.len()).await;
+        assert_eq!(texts(&snapshot, &matches), ["a.unwrap().unwrap()"]);
+        assert_eq!(
+            pattern
+                .replacement(&snapshot, matches[0].clone(), "$X?")
+                .as_deref(),
+            Some("a.unwrap()?")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_patterns_failing_to_parse(cx: &mut TestAppContext) {
+        let snapshot = rust_buffer("fn main() {\n    f(1);\n}\n", cx);
+
+        for source in ["fn (", ")(", "   ", "$$$"] {
+            let pattern = StructuralPattern::new(source).unwrap();
+            assert!(
+                pattern
+                    .search(&snapshot, 0..snapshot.len())
+                    .await
+                    .is_empty(),
+                "{source:?} should match nothing"
+            );
+            assert_eq!(pattern.replacement(&snapshot, 16..20, "g()"), None);
+        }
+    }
+
+    #[test]
+    fn test_metavariable_expansion() {
+        assert_eq!(
+            expand_metavariables("$X + $$$YS - $lower + $"),
+            "zed_meta_var_X + zed_meta_vars_YS - $lower + $"
+        );
+        assert_eq!(
+            longest_literal_word("$RECEIVER.unwrap_or_default()").as_deref(),
+            Some("unwrap_or_default")
+        );
+        assert_eq!(longest_literal_word("$A + $B"), None);
+    }
+
+    fn rust_buffer(text: &str, cx: &mut TestAppContext) -> BufferSnapshot {
+        let language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        ));
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language, cx));
+        cx.run_until_parked();
+        buffer.read_with(cx, |buffer, _| buffer.snapshot())
+    }
+
+    fn texts(snapshot: &BufferSnapshot, ranges: &[Range<usize>]) -> Vec<String> {
+        ranges
+            .iter()
+            .map(|range| snapshot.text_for_range(range.clone()).collect())
+            .collect()
+    }
+}
@@ -8,7 +8,7 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::Result;
+use anyhow::{Context as _, Result, bail};
 use collections::{HashMap, HashSet, VecDeque};
 use dap::DapRegistry;
 use fs::Fs;
@@ -22,8 +22,8 @@ use lsp::{LanguageServerId, LanguageServerName};
 use paths::{debug_task_file_name, task_file_name};
 use settings::{InvalidSettingsError, parse_json_with_comments};
 use task::{
-    DebugScenario, ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables,
-    VariableName,
+    DebugScenario, DependsOrder, ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates,
+    TaskVariables, TerminalLayout, TerminalLayoutTemplate, VariableName,
 };
 use text::{BufferId, Point, ToPoint};
 use util::{NumericPrefixWithSuffix, ResultExt as _, paths::PathExt as _, post_inc};
@@ -45,6 +45,85 @@ pub struct Inventory {
     last_scheduled_scenarios: VecDeque<(DebugScenario, DebugScenarioContext)>,
     templates_from_settings: InventoryFor<TaskTemplate>,
     scenarios_from_settings: InventoryFor<DebugScenario>,
+    layouts_from_settings: InventoryFor<TerminalLayoutTemplate>,
+    task_graph_runs: HashMap<TaskId, TaskGraphRun>,
+}
+
+/// A task together with all tasks it transitively depends on.
+#[derive(Debug, Clone)]
+pub struct TaskGraph {
+    nodes: Vec<TaskGraphNode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskGraphNode {
+    pub source_kind: TaskSourceKind,
+    pub task: ResolvedTask,
+    /// Indices of the nodes that have to succeed before this one starts.
+    pub dependencies: Vec<usize>,
+}
+
+impl TaskGraph {
+    /// All tasks of the graph, the task that was scheduled is the last one.
+    pub fn nodes(&self) -> &[TaskGraphNode] {
+        &self.nodes
+    }
+
+    pub fn root(&self) -> &TaskGraphNode {
+        self.nodes.last().expect("task graph always has a root")
+    }
+}
+
+/// Where a single task of a [`TaskGraph`] is in its run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskGraphNodeStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    /// One of the task's dependencies failed, so it was never started.
+    Skipped,
+}
+
+/// Combined status of all tasks in a [`TaskGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskGraphStatus {
+    Running { finished: usize, total: usize },
+    Succeeded,
+    Failed { label: String },
+}
+
+#[derive(Debug)]
+struct TaskGraphRun {
+    labels: Vec<String>,
+    statuses: Vec<TaskGraphNodeStatus>,
+}
+
+impl TaskGraphRun {
+    fn status(&self) -> TaskGraphStatus {
+        if let Some(ix) = self
+            .statuses
+            .iter()
+            .position(|status| *status == TaskGraphNodeStatus::Failed)
+        {
+            return TaskGraphStatus::Failed {
+                label: self.labels[ix].clone(),
+            };
+        }
+        let finished = self
+            .statuses
+            .iter()
+            .filter(|status| **status == TaskGraphNodeStatus::Succeeded)
+            .count();
+        if finished == self.statuses.len() {
+            TaskGraphStatus::Succeeded
+        } else {
+            TaskGraphStatus::Running {
+                finished,
+                total: self.statuses.len(),
+            }
+        }
+    }
 }
 
 impl std::fmt::Debug for Inventory {
@@ -54,6 +133,8 @@ impl std::fmt::Debug for Inventory {
             .field("last_scheduled_scenarios", &self.last_scheduled_scenarios)
             .field("templates_from_settings", &self.templates_from_settings)
             .field("scenarios_from_settings", &self.scenarios_from_settings)
+            .field("layouts_from_settings", &self.layouts_from_settings)
+            .field("task_graph_runs", &self.task_graph_runs)
             .finish()
     }
 }
@@ -75,6 +156,11 @@ impl InventoryContents for DebugScenario {
     const LABEL: &'static str = "debug scenarios";
 }
 
+impl InventoryContents for TerminalLayoutTemplate {
+    const GLOBAL_SOURCE_FILE: &'static str = "tasks.json";
+    const LABEL: &'static str = "terminal layouts";
+}
+
 #[derive(Debug)]
 struct InventoryFor<T> {
     global: HashMap<PathBuf, Vec<T>>,
@@ -249,6 +335,8 @@ impl Inventory {
             last_scheduled_scenarios: VecDeque::default(),
             templates_from_settings: InventoryFor::default(),
             scenarios_from_settings: InventoryFor::default(),
+            layouts_from_settings: InventoryFor::default(),
+            task_graph_runs: HashMap::default(),
         })
     }
 
@@ -607,6 +695,114 @@ impl Inventory {
         }
     }
 
+    /// Resolves the tasks that `task` depends on, transitively, with the context `task` was resolved with.
+    ///
+    /// Dependencies are looked up by label among the tasks from settings: tasks from the same source as the
+    /// dependent task first, then the other tasks of its worktree, then global tasks.
+    /// Fails if a dependency cannot be found, or if dependencies form a cycle.
+    pub fn resolve_task_graph(
+        &self,
+        source_kind: TaskSourceKind,
+        task: ResolvedTask,
+    ) -> Result<TaskGraph> {
+        let worktree = match &source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => None,
+        };
+        let templates = worktree
+            .into_iter()
+            .flat_map(|worktree| self.worktree_templates_from_settings(worktree))
+            .chain(self.global_templates_from_settings())
+            .sorted_by_key(|(kind, _)| *kind != source_kind)
+            .collect::<Vec<_>>();
+
+        let mut resolver = TaskGraphResolver {
+            templates: &templates,
+            context: task.context().clone(),
+            nodes: Vec::new(),
+            resolved: HashMap::default(),
+            visiting: Vec::new(),
+        };
+        let root_label = task.original_task().label.clone();
+        resolver.visiting.push(root_label.clone());
+        let dependencies = resolver.resolve_dependencies(task.original_task())?;
+        resolver.nodes.push(TaskGraphNode {
+            source_kind,
+            task,
+            dependencies,
+        });
+        let graph = TaskGraph {
+            nodes: resolver.nodes,
+        };
+
+        // Sequential dependencies add ordering constraints between tasks that might be shared with other
+        // dependents, which can still form a cycle.
+        let mut in_degree = graph
+            .nodes
+            .iter()
+            .map(|node| node.dependencies.len())
+            .collect::<Vec<_>>();
+        let mut ready = (0..graph.nodes.len())
+            .filter(|ix| in_degree[*ix] == 0)
+            .collect::<Vec<_>>();
+        let mut visited = 0;
+        while let Some(ix) = ready.pop() {
+            visited += 1;
+            for (dependent, node) in graph.nodes.iter().enumerate() {
+                for _ in node
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| **dependency == ix)
+                {
+                    in_degree[dependent] -= 1;
+                    if in_degree[dependent] == 0 {
+                        ready.push(dependent);
+                    }
+                }
+            }
+        }
+        if visited != graph.nodes.len() {
+            bail!("Task `{root_label}` has dependencies with conflicting `depends_order`");
+        }
+
+        Ok(graph)
+    }
+
+    /// Starts tracking the run of the given task graph, replacing any previous run of the same task.
+    pub fn task_graph_started(&mut self, graph: &TaskGraph) {
+        self.task_graph_runs.insert(
+            graph.root().task.id.clone(),
+            TaskGraphRun {
+                labels: graph
+                    .nodes()
+                    .iter()
+                    .map(|node| node.task.resolved_label.clone())
+                    .collect(),
+                statuses: vec![TaskGraphNodeStatus::Pending; graph.nodes().len()],
+            },
+        );
+    }
+
+    pub fn update_task_graph_status(
+        &mut self,
+        root: &TaskId,
+        node_ix: usize,
+        status: TaskGraphNodeStatus,
+    ) {
+        if let Some(node_status) = self
+            .task_graph_runs
+            .get_mut(root)
+            .and_then(|run| run.statuses.get_mut(node_ix))
+        {
+            *node_status = status;
+        }
+    }
+
+    /// Combined status of the latest run of the task with the given id, if it has dependencies.
+    pub fn task_graph_status(&self, root: &TaskId) -> Option<TaskGraphStatus> {
+        Some(self.task_graph_runs.get(root)?.status())
+    }
+
     /// Deletes a resolved task from history, using its id.
     /// A similar may still resurface in `used_and_current_resolved_tasks` when its [`TaskTemplate`] is resolved again.
     pub fn delete_previously_used(&mut self, id: &TaskId) {
@@ -664,6 +860,18 @@ impl Inventory {
                 });
             }
         };
+        let (raw_layouts, raw_tasks): (Vec<_>, Vec<_>) = raw_tasks
+            .into_iter()
+            .partition(|raw_task| raw_task.get("terminal_layout").is_some());
+        self.update_layouts(
+            &location,
+            raw_layouts
+                .into_iter()
+                .filter_map(|raw_layout| {
+                    serde_json::from_value::<TerminalLayoutTemplate>(raw_layout).log_err()
+                })
+                .collect(),
+        );
         let new_templates = raw_tasks.into_iter().filter_map(|raw_template| {
             serde_json::from_value::<TaskTemplate>(raw_template).log_err()
         });
@@ -716,6 +924,53 @@ impl Inventory {
         Ok(())
     }
 
+    fn update_layouts(
+        &mut self,
+        location: &TaskSettingsLocation<'_>,
+        new_layouts: Vec<TerminalLayoutTemplate>,
+    ) {
+        let parsed_layouts = &mut self.layouts_from_settings;
+        match location {
+            TaskSettingsLocation::Global(path) => {
+                parsed_layouts
+                    .global
+                    .insert(path.to_path_buf(), new_layouts);
+            }
+            TaskSettingsLocation::Worktree(location) => {
+                if new_layouts.is_empty() {
+                    if let Some(worktree_layouts) =
+                        parsed_layouts.worktree.get_mut(&location.worktree_id)
+                    {
+                        worktree_layouts.remove(location.path);
+                    }
+                } else {
+                    parsed_layouts
+                        .worktree
+                        .entry(location.worktree_id)
+                        .or_default()
+                        .insert(Arc::from(location.path), new_layouts);
+                }
+            }
+        }
+    }
+
+    /// Terminal layouts defined in the tasks files, by their labels.
+    /// The layouts of the worktree given take precedence over the global ones.
+    pub fn terminal_layouts(
+        &self,
+        worktree: Option<WorktreeId>,
+    ) -> HashMap<String, TerminalLayout> {
+        self.layouts_from_settings
+            .global_scenarios()
+            .chain(
+                worktree
+                    .into_iter()
+                    .flat_map(|worktree| self.layouts_from_settings.worktree_scenarios(worktree)),
+            )
+            .map(|(_, layout)| (layout.label, layout.terminal_layout))
+            .collect()
+    }
+
     /// Updates in-memory task metadata from the JSON string given.
     /// Will fail if the JSON is not a valid array of objects, but will continue if any object will not parse into a [`TaskTemplate`].
     ///
@@ -846,6 +1101,74 @@ fn task_variables_preference(task: &ResolvedTask) -> Reverse<usize> {
     })
 }
 
+struct TaskGraphResolver<'a> {
+    templates: &'a [(TaskSourceKind, TaskTemplate)],
+    context: TaskContext,
+    nodes: Vec<TaskGraphNode>,
+    resolved: HashMap<String, usize>,
+    visiting: Vec<String>,
+}
+
+impl TaskGraphResolver<'_> {
+    fn resolve_dependencies(&mut self, template: &TaskTemplate) -> Result<Vec<usize>> {
+        let dependencies = template
+            .depends_on
+            .iter()
+            .map(|label| self.resolve(label))
+            .collect::<Result<Vec<_>>>()?;
+
+        if template.depends_order == DependsOrder::Sequential {
+            for (previous, next) in dependencies.iter().copied().tuple_windows() {
+                let next_dependencies = &mut self.nodes[next].dependencies;
+                if !next_dependencies.contains(&previous) {
+                    next_dependencies.push(previous);
+                }
+            }
+        }
+
+        Ok(dependencies)
+    }
+
+    fn resolve(&mut self, label: &str) -> Result<usize> {
+        if let Some(ix) = self.resolved.get(label) {
+            return Ok(*ix);
+        }
+        if self.visiting.iter().any(|visiting| visiting == label) {
+            bail!(
+                "Task dependency cycle: {} -> {label}",
+                self.visiting.join(" -> ")
+            );
+        }
+
+        let (source_kind, template) = self
+            .templates
+            .iter()
+            .find(|(_, template)| template.label == label)
+            .with_context(|| {
+                format!(
+                    "Task `{label}` depended on by `{}` not found",
+                    self.visiting.last().map_or("", |label| label.as_str())
+                )
+            })?;
+        let task = template
+            .resolve_task(&source_kind.to_id_base(), &self.context)
+            .with_context(|| format!("Failed to resolve task `{label}`"))?;
+
+        self.visiting.push(label.to_owned());
+        let dependencies = self.resolve_dependencies(template)?;
+        self.visiting.pop();
+
+        let ix = self.nodes.len();
+        self.nodes.push(TaskGraphNode {
+            source_kind: source_kind.clone(),
+            task,
+            dependencies,
+        });
+        self.resolved.insert(label.to_owned(), ix);
+        Ok(ix)
+    }
+}
+
 #[cfg(test)]
 mod test_inventory {
     use gpui::{AppContext as _, Entity, Task, TestAppContext};
@@ -1555,6 +1878,213 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_resolving_task_graphs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let inventory = cx.update(|cx| Inventory::new(fs, cx));
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    TaskSettingsLocation::Global(tasks_file()),
+                    Some(
+                        &json!([
+                            { "label": "fetch", "command": "git fetch" },
+                            { "label": "build", "command": "cargo build", "depends_on": ["fetch"] },
+                            { "label": "test", "command": "cargo test", "depends_on": ["fetch"] },
+                            {
+                                "label": "ci",
+                                "depends_on": ["build", "test"],
+                                "depends_order": "sequential"
+                            },
+                            { "label": "missing", "command": "true", "depends_on": ["nope"] },
+                            { "label": "ping", "command": "true", "depends_on": ["pong"] },
+                            { "label": "pong", "command": "true", "depends_on": ["ping"] },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let resolve = |label: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, _| {
+                let (source_kind, template) = inventory
+                    .global_templates_from_settings()
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                let task = template
+                    .resolve_task(&source_kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory
+                    .resolve_task_graph(source_kind, task)
+                    .map(|graph| {
+                        graph
+                            .nodes()
+                            .iter()
+                            .map(|node| {
+                                (
+                                    node.task.resolved_label.clone(),
+                                    node.dependencies
+                                        .iter()
+                                        .map(|dependency| {
+                                            graph.nodes()[*dependency].task.resolved_label.clone()
+                                        })
+                                        .collect::<Vec<_>>(),
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+            })
+        };
+
+        assert_eq!(
+            resolve("ci", cx).unwrap(),
+            vec![
+                ("fetch".to_string(), vec![]),
+                ("build".to_string(), vec!["fetch".to_string()]),
+                (
+                    "test".to_string(),
+                    vec!["fetch".to_string(), "build".to_string()]
+                ),
+                (
+                    "ci".to_string(),
+                    vec!["build".to_string(), "test".to_string()]
+                ),
+            ],
+            "Shared dependencies should be resolved once, sequential ones should wait for each other"
+        );
+        let ci = inventory.update(cx, |inventory, _| {
+            let (source_kind, template) = inventory
+                .global_templates_from_settings()
+                .find(|(_, template)| template.label == "ci")
+                .unwrap();
+            template
+                .resolve_task(&source_kind.to_id_base(), &TaskContext::default())
+                .unwrap()
+        });
+        assert!(ci.is_compound());
+
+        assert!(resolve("missing", cx).is_err());
+        assert!(resolve("ping", cx).is_err());
+    }
+
+    #[gpui::test]
+    async fn test_terminal_layouts_from_tasks_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let inventory = cx.update(|cx| Inventory::new(fs, cx));
+        let worktree_id = WorktreeId::from_usize(0);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    TaskSettingsLocation::Global(tasks_file()),
+                    Some(
+                        &json!([
+                            { "label": "build", "command": "cargo build" },
+                            { "label": "dev", "terminal_layout": { "command": "htop" } },
+                            { "label": "logs", "terminal_layout": { "command": "tail -f log" } },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+            inventory
+                .update_file_based_tasks(
+                    TaskSettingsLocation::Worktree(SettingsLocation {
+                        worktree_id,
+                        path: Path::new(""),
+                    }),
+                    Some(
+                        &json!([{
+                            "label": "dev",
+                            "terminal_layout": {
+                                "direction": "horizontal",
+                                "panes": [{ "command": "npm run dev" }, { "cwd": "server" }],
+                            },
+                        }])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let pane = |command: Option<&str>, cwd: Option<&str>| {
+            TerminalLayout::Pane(task::TerminalLayoutPane {
+                command: command.map(ToOwned::to_owned),
+                cwd: cwd.map(PathBuf::from),
+                ..Default::default()
+            })
+        };
+        inventory.update(cx, |inventory, _| {
+            let global_layouts = inventory.terminal_layouts(None);
+            assert_eq!(
+                global_layouts.keys().sorted().collect::<Vec<_>>(),
+                vec!["dev", "logs"],
+                "Layouts should not be listed as tasks, and tasks not as layouts"
+            );
+            assert_eq!(global_layouts["dev"], pane(Some("htop"), None));
+
+            let layouts = inventory.terminal_layouts(Some(worktree_id));
+            assert_eq!(layouts["logs"], pane(Some("tail -f log"), None));
+            assert_eq!(
+                layouts["dev"],
+                TerminalLayout::Split {
+                    direction: task::TerminalLayoutDirection::Horizontal,
+                    panes: vec![pane(Some("npm run dev"), None), pane(None, Some("server"))],
+                },
+                "Layouts of the worktree should take precedence over the global ones"
+            );
+        });
+        assert_eq!(
+            resolved_task_names(&inventory, Some(worktree_id), cx).await,
+            vec!["build"]
+        );
+    }
+
+    #[test]
+    fn test_task_graph_run_status() {
+        let mut run = TaskGraphRun {
+            labels: vec!["build".to_string(), "test".to_string(), "ci".to_string()],
+            statuses: vec![TaskGraphNodeStatus::Pending; 3],
+        };
+        assert_eq!(
+            run.status(),
+            TaskGraphStatus::Running {
+                finished: 0,
+                total: 3
+            }
+        );
+
+        run.statuses = vec![
+            TaskGraphNodeStatus::Succeeded,
+            TaskGraphNodeStatus::Running,
+            TaskGraphNodeStatus::Pending,
+        ];
+        assert_eq!(
+            run.status(),
+            TaskGraphStatus::Running {
+                finished: 1,
+                total: 3
+            }
+        );
+
+        run.statuses = vec![
+            TaskGraphNodeStatus::Succeeded,
+            TaskGraphNodeStatus::Failed,
+            TaskGraphNodeStatus::Skipped,
+        ];
+        assert_eq!(
+            run.status(),
+            TaskGraphStatus::Failed {
+                label: "test".to_string()
+            }
+        );
+
+        run.statuses = vec![TaskGraphNodeStatus::Succeeded; 3];
+        assert_eq!(run.status(), TaskGraphStatus::Succeeded);
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         zlog::init_test();
         TaskStore::init(None);
@@ -1,27 +1,49 @@
 use anyhow::Result;
 use collections::HashMap;
-use gpui::{App, AppContext as _, Context, Entity, Task, WeakEntity};
+use futures::{StreamExt as _, stream::FuturesUnordered};
+use gpui::{App, AppContext as _, AsyncApp, Context, Entity, Task, WeakEntity};
 
 use itertools::Itertools as _;
-use language::LanguageName;
+use language::{
+    Diagnostic, DiagnosticEntry, DiagnosticSourceKind, LanguageName, PointUtf16, Unclipped,
+};
+use lsp::{DiagnosticSeverity, LanguageServerId, NumberOrString};
 use remote::RemoteClient;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
     borrow::Cow,
+    mem,
     path::{Path, PathBuf},
+    process::ExitStatus,
     sync::Arc,
 };
-use task::{Shell, ShellBuilder, ShellKind, SpawnInTerminal};
+use task::{ProblemSeverity, Shell, ShellBuilder, ShellKind, SpawnInTerminal, TaskProblem};
 use terminal::{
-    TaskState, TaskStatus, Terminal, TerminalBuilder, terminal_settings::TerminalSettings,
+    BackgroundTaskState, SavedScrollback, TaskOutputReader, TaskState, TaskStatus, Terminal,
+    TerminalBuilder, terminal_settings::TerminalSettings,
 };
-use util::{get_default_system_shell, get_system_shell, maybe};
+use util::{ResultExt as _, get_default_system_shell, get_system_shell, maybe};
+
+use crate::{Event, Project, ProjectPath, TaskGraph, TaskGraphNodeStatus};
 
-use crate::{Project, ProjectPath};
+/// Diagnostics of all tasks are reported on behalf of this fake language server,
+/// `LanguageServerId(usize::MAX)` being taken by snippets in the editor.
+const TASK_PROBLEMS_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX - 1);
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    /// Problems found by the problem matchers in the output of the last run of each task, by task label.
+    pub(crate) task_problems: HashMap<String, HashMap<PathBuf, Vec<TaskProblem>>>,
+}
+
+/// Additional setup of a terminal shell, see [`Project::create_terminal_shell_with_options`].
+#[derive(Default)]
+pub struct TerminalShellOptions {
+    /// Environment variables set on top of the ones from the settings.
+    pub env: HashMap<String, String>,
+    /// Scrollback saved from a previous session, shown above the shell's prompt.
+    pub restored_scrollback: Option<SavedScrollback>,
 }
 
 impl Project {
@@ -95,6 +117,9 @@ impl Project {
         env.extend(settings.env);
 
         let local_path = if is_via_remote { None } else { path.clone() };
+        let problem_source = spawn_task.label.clone();
+        let problem_matchers = spawn_task.problem_matchers;
+        let is_background = spawn_task.background.is_some();
         let task_state = Some(TaskState {
             id: spawn_task.id,
             full_label: spawn_task.full_label,
@@ -106,6 +131,7 @@ impl Project {
             show_command: spawn_task.show_command,
             show_rerun: spawn_task.show_rerun,
             completion_rx,
+            background: spawn_task.background.as_ref().map(BackgroundTaskState::new),
         });
         let remote_client = self.remote_client.clone();
         let shell = match &remote_client {
@@ -222,7 +248,7 @@ impl Project {
                     }
                 };
                 TerminalBuilder::new(
-                    local_path.map(|path| path.to_path_buf()),
+                    local_path.as_ref().map(|path| path.to_path_buf()),
                     task_state,
                     shell,
                     env,
@@ -232,6 +258,7 @@ impl Project {
                     is_via_remote,
                     cx.entity_id().as_u64(),
                     Some(completion_tx),
+                    None,
                     cx,
                     activation_script,
                 )
@@ -242,6 +269,60 @@ impl Project {
                         .local_handles
                         .push(terminal_handle.downgrade());
 
+                    if !is_via_remote {
+                        this.set_task_problems(problem_source.clone(), Vec::new(), cx);
+                    }
+                    if !is_via_remote && !problem_matchers.is_empty() {
+                        let cwd = local_path.as_deref();
+                        let mut line_matchers = problem_matchers
+                            .iter()
+                            .filter_map(|matcher| matcher.line_matcher(cwd).log_err())
+                            .collect::<Vec<_>>();
+                        let mut output = TaskOutputReader::default();
+                        let mut problems = Vec::new();
+                        // The output is matched as it's printed, long outputs don't fit in the
+                        // terminal's scrollback by the time the task finishes.
+                        cx.subscribe(&terminal_handle, move |project, terminal, event, cx| {
+                            let finished = match event {
+                                terminal::Event::Wakeup => false,
+                                terminal::Event::TaskFinished => true,
+                                _ => return,
+                            };
+                            let terminal = terminal.read(cx);
+                            if !finished
+                                && terminal
+                                    .task()
+                                    .is_none_or(|task| task.status != TaskStatus::Running)
+                            {
+                                return;
+                            }
+                            for line in terminal.read_task_output(&mut output) {
+                                for line_matcher in &mut line_matchers {
+                                    problems.extend(line_matcher.match_line(&line));
+                                }
+                            }
+                            if finished {
+                                project.set_task_problems(
+                                    problem_source.clone(),
+                                    mem::take(&mut problems),
+                                    cx,
+                                );
+                            }
+                        })
+                        .detach();
+                    }
+                    if is_background {
+                        cx.subscribe(&terminal_handle, |_, _, event, cx| {
+                            if let terminal::Event::TaskReadinessChanged
+                            | terminal::Event::TaskFinished = event
+                            {
+                                cx.emit(Event::BackgroundTasksChanged);
+                            }
+                        })
+                        .detach();
+                        cx.emit(Event::BackgroundTasksChanged);
+                    }
+
                     let id = terminal_handle.entity_id();
                     cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
                         let handles = &mut project.terminals.local_handles;
@@ -253,6 +334,9 @@ impl Project {
                             handles.remove(index);
                             cx.notify();
                         }
+                        if is_background {
+                            cx.emit(Event::BackgroundTasksChanged);
+                        }
                     })
                     .detach();
 
@@ -262,11 +346,113 @@ impl Project {
         })
     }
 
+    /// Terminals running background tasks, in the order they were started.
+    pub fn background_tasks(&self, cx: &App) -> Vec<Entity<Terminal>> {
+        self.terminals
+            .local_handles
+            .iter()
+            .filter_map(|terminal| terminal.upgrade())
+            .filter(|terminal| {
+                terminal.read(cx).task().is_some_and(|task| {
+                    task.background.is_some() && task.status == TaskStatus::Running
+                })
+            })
+            .collect()
+    }
+
+    /// Replaces the diagnostics reported for the previous run of the task with the given problems.
+    pub(crate) fn set_task_problems(
+        &mut self,
+        source: String,
+        problems: Vec<TaskProblem>,
+        cx: &mut Context<Self>,
+    ) {
+        let mut problems_by_path = HashMap::<PathBuf, Vec<TaskProblem>>::default();
+        for problem in problems {
+            problems_by_path
+                .entry(problem.path.clone())
+                .or_default()
+                .push(problem);
+        }
+        let previous_problems = self
+            .terminals
+            .task_problems
+            .remove(&source)
+            .unwrap_or_default();
+        let updated_paths = previous_problems
+            .into_keys()
+            .chain(problems_by_path.keys().cloned())
+            .unique()
+            .collect::<Vec<_>>();
+        if updated_paths.is_empty() {
+            return;
+        }
+        if !problems_by_path.is_empty() {
+            self.terminals
+                .task_problems
+                .insert(source, problems_by_path);
+        }
+
+        // All tasks share the same server id, so each path gets the problems of every task that reported some there.
+        let diagnostics_by_path = updated_paths
+            .into_iter()
+            .map(|path| {
+                let diagnostics = self
+                    .terminals
+                    .task_problems
+                    .iter()
+                    .filter_map(|(source, problems_by_path)| {
+                        Some(
+                            problems_by_path
+                                .get(&path)?
+                                .iter()
+                                .map(move |problem| (source, problem)),
+                        )
+                    })
+                    .flatten()
+                    .enumerate()
+                    .map(|(group_id, (source, problem))| {
+                        task_problem_diagnostic(source, problem, group_id)
+                    })
+                    .collect::<Vec<_>>();
+                (path, diagnostics)
+            })
+            .collect::<Vec<_>>();
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            for (path, diagnostics) in diagnostics_by_path {
+                lsp_store
+                    .update_diagnostic_entries(
+                        TASK_PROBLEMS_SERVER_ID,
+                        path,
+                        None,
+                        None,
+                        diagnostics,
+                        cx,
+                    )
+                    .log_err();
+            }
+        });
+    }
+
     pub fn create_terminal_shell(
         &mut self,
         cwd: Option<PathBuf>,
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<Terminal>>> {
+        self.create_terminal_shell_with_options(cwd, TerminalShellOptions::default(), cx)
+    }
+
+    /// Creates a terminal shell, set up further with the given options.
+    pub fn create_terminal_shell_with_options(
+        &mut self,
+        cwd: Option<PathBuf>,
+        options: TerminalShellOptions,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Terminal>>> {
+        let TerminalShellOptions {
+            env: extra_env,
+            restored_scrollback,
+        } = options;
         let project_path_context = self
             .active_entry()
             .and_then(|entry_id| self.worktree_id_for_entry(entry_id, cx))
@@ -299,6 +485,7 @@ impl Project {
         // Then extend it with the explicit env variables from the settings, so they take
         // precedence.
         env.extend(settings.env);
+        env.extend(extra_env);
 
         let local_path = if is_via_remote { None } else { path.clone() };
 
@@ -360,6 +547,7 @@ impl Project {
                     is_via_remote,
                     cx.entity_id().as_u64(),
                     None,
+                    restored_scrollback,
                     cx,
                     activation_script,
                 )
@@ -390,6 +578,104 @@ impl Project {
         })
     }
 
+    /// Runs the tasks of the graph, starting each one with `spawn` as soon as all of its dependencies succeeded.
+    /// Tasks whose dependencies failed are skipped, compound tasks succeed once their dependencies do.
+    ///
+    /// Resolves to whether the root task of the graph succeeded.
+    pub fn run_task_graph(
+        &mut self,
+        graph: TaskGraph,
+        spawn: impl Fn(SpawnInTerminal, &mut AsyncApp) -> Task<Option<Result<ExitStatus>>> + 'static,
+        cx: &mut Context<Self>,
+    ) -> Task<bool> {
+        let inventory = self.task_store.read(cx).task_inventory().cloned();
+        let root_id = graph.root().task.id.clone();
+        if let Some(inventory) = &inventory {
+            inventory.update(cx, |inventory, cx| {
+                inventory.task_graph_started(&graph);
+                cx.notify();
+            });
+        }
+
+        cx.spawn(async move |_, cx| {
+            let nodes = graph.nodes();
+            let mut statuses = vec![TaskGraphNodeStatus::Pending; nodes.len()];
+            let report_status = |ix: usize, status, cx: &mut AsyncApp| {
+                if let Some(inventory) = &inventory {
+                    inventory
+                        .update(cx, |inventory, cx| {
+                            inventory.update_task_graph_status(&root_id, ix, status);
+                            cx.notify();
+                        })
+                        .ok();
+                }
+            };
+
+            let mut running = FuturesUnordered::new();
+            loop {
+                // Starting or skipping a task may unblock others, so repeat until nothing changes.
+                let mut changed = true;
+                while changed {
+                    changed = false;
+                    for (ix, node) in nodes.iter().enumerate() {
+                        if statuses[ix] != TaskGraphNodeStatus::Pending {
+                            continue;
+                        }
+                        let dependency_statuses = node
+                            .dependencies
+                            .iter()
+                            .map(|dependency| statuses[*dependency]);
+                        if dependency_statuses.clone().any(|status| {
+                            matches!(
+                                status,
+                                TaskGraphNodeStatus::Failed | TaskGraphNodeStatus::Skipped
+                            )
+                        }) {
+                            statuses[ix] = TaskGraphNodeStatus::Skipped;
+                            report_status(ix, TaskGraphNodeStatus::Skipped, cx);
+                            changed = true;
+                        } else if dependency_statuses
+                            .clone()
+                            .all(|status| status == TaskGraphNodeStatus::Succeeded)
+                        {
+                            if node.task.is_compound() {
+                                statuses[ix] = TaskGraphNodeStatus::Succeeded;
+                                report_status(ix, TaskGraphNodeStatus::Succeeded, cx);
+                                changed = true;
+                            } else {
+                                statuses[ix] = TaskGraphNodeStatus::Running;
+                                report_status(ix, TaskGraphNodeStatus::Running, cx);
+                                let task = spawn(node.task.resolved.clone(), cx);
+                                running.push(async move { (ix, task.await) });
+                            }
+                        }
+                    }
+                }
+
+                let Some((ix, result)) = running.next().await else {
+                    break;
+                };
+                let status = match result {
+                    Some(Ok(exit_status)) if exit_status.success() => {
+                        TaskGraphNodeStatus::Succeeded
+                    }
+                    Some(Err(e)) => {
+                        log::error!(
+                            "Task `{}` failed to spawn: {e:#}",
+                            nodes[ix].task.resolved_label
+                        );
+                        TaskGraphNodeStatus::Failed
+                    }
+                    _ => TaskGraphNodeStatus::Failed,
+                };
+                statuses[ix] = status;
+                report_status(ix, status, cx);
+            }
+
+            statuses.last() == Some(&TaskGraphNodeStatus::Succeeded)
+        })
+    }
+
     pub fn clone_terminal(
         &mut self,
         terminal: &Entity<Terminal>,
@@ -520,3 +806,46 @@ fn create_remote_shell(
         title_override: Some(format!("{} — Terminal", host).into()),
     })
 }
+
+fn task_problem_diagnostic(
+    source: &str,
+    problem: &TaskProblem,
+    group_id: usize,
+) -> DiagnosticEntry<Unclipped<PointUtf16>> {
+    let row = problem.line.saturating_sub(1);
+    let (start, end) = match problem.column {
+        Some(column) => {
+            let start = PointUtf16::new(row, column.saturating_sub(1));
+            let end = match (problem.end_line, problem.end_column) {
+                (end_line, Some(end_column)) => PointUtf16::new(
+                    end_line.unwrap_or(problem.line).saturating_sub(1),
+                    end_column.saturating_sub(1),
+                ),
+                (Some(end_line), None) => PointUtf16::new(end_line.saturating_sub(1), u32::MAX),
+                (None, None) => start,
+            };
+            (start, end)
+        }
+        // Without a column, the whole line is reported.
+        None => (PointUtf16::new(row, 0), PointUtf16::new(row, u32::MAX)),
+    };
+    DiagnosticEntry {
+        range: Unclipped(start)..Unclipped(end),
+        diagnostic: Diagnostic {
+            source: Some(source.to_owned()),
+            source_kind: DiagnosticSourceKind::Other,
+            code: problem.code.clone().map(NumberOrString::String),
+            severity: match problem.severity {
+                ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+            },
+            message: problem.message.clone(),
+            group_id,
+            is_primary: true,
+            is_disk_based: true,
+            ..Diagnostic::default()
+        },
+    }
+}
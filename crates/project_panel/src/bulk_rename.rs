@@ -0,0 +1,787 @@
+//! BulkRename lists project entries in an editable buffer, so they can be renamed, moved,
+//! deleted and created by editing their paths and saving.
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use collections::HashSet;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AnyView, App, AppContext as _, AsyncWindowContext, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, IntoElement, PromptLevel, Render, SharedString, Task, Window,
+};
+use language::Buffer;
+use project::{Entry, Project, ProjectEntryId, WorktreeId};
+use std::{
+    any::{Any, TypeId},
+    fmt,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use ui::{Color, Icon, IconName};
+use workspace::{
+    Item, ItemNavHistory, ToolbarItemLocation, Workspace,
+    item::{BreadcrumbText, ItemEvent, SaveOptions},
+    searchable::SearchableItemHandle,
+};
+use worktree::{CreatedEntry, Snapshot};
+
+/// Lists entries of a single worktree, one per line, as `<number> <path>`, with a trailing `/`
+/// for directories. On save, lines whose path changed are renamed, entries whose numbered line
+/// was removed are deleted, and lines without a number are created.
+pub struct BulkRename {
+    editor: Entity<Editor>,
+    buffer: Entity<Buffer>,
+    project: Entity<Project>,
+    worktree_id: WorktreeId,
+    /// The directory whose children are listed, or `None` when a fixed set of entries is listed
+    /// relative to the worktree root.
+    directory: Option<Arc<Path>>,
+    /// The listed entries, numbered from 1 in the buffer.
+    entries: Vec<ListedEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct ListedEntry {
+    id: ProjectEntryId,
+    path: Arc<Path>,
+    is_dir: bool,
+}
+
+impl ListedEntry {
+    fn new(entry: &Entry) -> Self {
+        Self {
+            id: entry.id,
+            path: entry.path.clone(),
+            is_dir: entry.is_dir(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Operation {
+    Rename {
+        entry_id: ProjectEntryId,
+        old_path: Arc<Path>,
+        new_path: Arc<Path>,
+    },
+    Delete {
+        entry_id: ProjectEntryId,
+        path: Arc<Path>,
+    },
+    Create {
+        path: Arc<Path>,
+        is_dir: bool,
+    },
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Rename {
+                old_path, new_path, ..
+            } => write!(f, "Rename {} → {}", old_path.display(), new_path.display()),
+            Operation::Delete { path, .. } => write!(f, "Move {} to the trash", path.display()),
+            Operation::Create { path, is_dir } => {
+                write!(
+                    f,
+                    "Create {}{}",
+                    path.display(),
+                    if *is_dir { "/" } else { "" }
+                )
+            }
+        }
+    }
+}
+
+/// The operations described by the edited listing, along with the paths it lists once they're
+/// applied.
+#[derive(Debug)]
+struct Changes {
+    operations: Vec<Operation>,
+    listed_paths: Vec<Arc<Path>>,
+}
+
+impl BulkRename {
+    /// Lists the children of `directory`.
+    pub fn for_directory(
+        project: Entity<Project>,
+        worktree_id: WorktreeId,
+        directory: Arc<Path>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::new(
+            project,
+            worktree_id,
+            Some(directory),
+            Vec::new(),
+            window,
+            cx,
+        )
+    }
+
+    /// Lists the entries at `paths`, relative to the root of their worktree.
+    pub fn for_paths(
+        project: Entity<Project>,
+        worktree_id: WorktreeId,
+        paths: Vec<Arc<Path>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::new(project, worktree_id, None, paths, window, cx)
+    }
+
+    fn new(
+        project: Entity<Project>,
+        worktree_id: WorktreeId,
+        directory: Option<Arc<Path>>,
+        paths: Vec<Arc<Path>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let buffer = cx.new(|cx| Buffer::local("", cx));
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx);
+            editor.disable_diagnostics(cx);
+            editor
+        });
+        cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        })
+        .detach();
+
+        let mut this = Self {
+            editor,
+            buffer,
+            project,
+            worktree_id,
+            directory,
+            entries: Vec::new(),
+        };
+        this.reload(paths, cx);
+        this
+    }
+
+    fn snapshot(&self, cx: &App) -> Option<Snapshot> {
+        let worktree = self
+            .project
+            .read(cx)
+            .worktree_for_id(self.worktree_id, cx)?;
+        Some(worktree.read(cx).snapshot())
+    }
+
+    fn base_path(&self) -> &Path {
+        self.directory.as_deref().unwrap_or(Path::new(""))
+    }
+
+    /// Replaces the listing with the current children of the listed directory, or with the
+    /// entries at `paths` when a fixed set of entries is listed.
+    fn reload(&mut self, paths: Vec<Arc<Path>>, cx: &mut Context<Self>) {
+        let Some(snapshot) = self.snapshot(cx) else {
+            return;
+        };
+        self.entries = match &self.directory {
+            Some(directory) => snapshot
+                .child_entries(directory)
+                .map(ListedEntry::new)
+                .collect(),
+            None => paths
+                .iter()
+                .filter_map(|path| snapshot.entry_for_path(path))
+                .map(ListedEntry::new)
+                .collect(),
+        };
+
+        let width = self.entries.len().to_string().len();
+        let mut text = String::new();
+        for (ix, entry) in self.entries.iter().enumerate() {
+            let path = entry
+                .path
+                .strip_prefix(self.base_path())
+                .unwrap_or(&entry.path);
+            text.push_str(&format!(
+                "{:0width$} {}{}\n",
+                ix + 1,
+                path.display(),
+                if entry.is_dir { "/" } else { "" },
+            ));
+        }
+
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.set_text(text, cx);
+            buffer.did_save(buffer.version(), None, cx);
+        });
+        cx.notify();
+    }
+
+    fn changes(&self, cx: &App) -> Result<Changes> {
+        let snapshot = self
+            .snapshot(cx)
+            .context("the worktree is no longer in the project")?;
+        let text = self.buffer.read(cx).text();
+        parse_changes(&text, self.base_path(), &self.entries, &snapshot)
+    }
+}
+
+fn parse_changes(
+    text: &str,
+    base_path: &Path,
+    entries: &[ListedEntry],
+    snapshot: &Snapshot,
+) -> Result<Changes> {
+    let mut new_paths = vec![None; entries.len()];
+    let mut created = Vec::new();
+    let mut listed_paths = Vec::new();
+    let mut seen_paths = HashSet::default();
+    for (row, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let is_number = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+        let (number, name) = match line.split_once(char::is_whitespace) {
+            Some((number, name)) if is_number(number) => (Some(number), name.trim()),
+            _ if is_number(line) => (Some(line), ""),
+            _ => (None, line),
+        };
+
+        let is_dir = name.ends_with('/');
+        let relative_path = Path::new(name.trim_end_matches('/'));
+        if relative_path.as_os_str().is_empty()
+            || !relative_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("Line {}: `{name}` is not a valid relative path", row + 1);
+        }
+        let path: Arc<Path> = base_path.join(relative_path).into();
+        if !seen_paths.insert(path.clone()) {
+            bail!("Line {}: `{name}` is listed more than once", row + 1);
+        }
+        listed_paths.push(path.clone());
+
+        match number {
+            Some(number) => {
+                let ix = number
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|number| number.checked_sub(1))
+                    .filter(|&ix| ix < entries.len())
+                    .ok_or_else(|| anyhow!("Line {}: there is no entry {number}", row + 1))?;
+                if new_paths[ix].replace(path).is_some() {
+                    bail!("Line {}: entry {number} is listed more than once", row + 1);
+                }
+            }
+            None => created.push((path, is_dir)),
+        }
+    }
+
+    let mut operations = Vec::new();
+    for (entry, new_path) in entries.iter().zip(new_paths) {
+        match new_path {
+            None => operations.push(Operation::Delete {
+                entry_id: entry.id,
+                path: entry.path.clone(),
+            }),
+            Some(new_path) if new_path != entry.path => operations.push(Operation::Rename {
+                entry_id: entry.id,
+                old_path: entry.path.clone(),
+                new_path,
+            }),
+            Some(_) => {}
+        }
+    }
+    operations.extend(
+        created
+            .into_iter()
+            .map(|(path, is_dir)| Operation::Create { path, is_dir }),
+    );
+
+    validate_operations(&operations, snapshot)?;
+    Ok(Changes {
+        operations,
+        listed_paths,
+    })
+}
+
+/// Checks that the operations can all be applied to the worktree as it is, so that none of them
+/// is applied when any would fail.
+fn validate_operations(operations: &[Operation], snapshot: &Snapshot) -> Result<()> {
+    let mut renamed_paths = Vec::new();
+    let mut deleted_paths = Vec::new();
+    let mut renamed_ids = HashSet::default();
+    for operation in operations {
+        let (entry_id, path) = match operation {
+            Operation::Rename {
+                entry_id, old_path, ..
+            } => {
+                renamed_paths.push(old_path);
+                renamed_ids.insert(*entry_id);
+                (entry_id, old_path)
+            }
+            Operation::Delete { entry_id, path } => {
+                deleted_paths.push(path);
+                (entry_id, path)
+            }
+            Operation::Create { .. } => continue,
+        };
+        if snapshot
+            .entry_for_id(*entry_id)
+            .is_none_or(|entry| entry.path != *path)
+        {
+            bail!("`{}` changed since it was listed", path.display());
+        }
+    }
+
+    let sources = renamed_paths.iter().chain(&deleted_paths);
+    for path in sources.clone() {
+        if let Some(ancestor) = sources
+            .clone()
+            .find(|ancestor| path != *ancestor && path.starts_with(ancestor))
+        {
+            bail!(
+                "`{}` can't be changed along with `{}`, which contains it",
+                path.display(),
+                ancestor.display()
+            );
+        }
+    }
+
+    for operation in operations {
+        let (Operation::Rename { new_path: path, .. } | Operation::Create { path, .. }) = operation
+        else {
+            continue;
+        };
+        // Deletions are applied last, so their paths can't be reused.
+        if let Some(deleted_path) = deleted_paths
+            .iter()
+            .find(|deleted_path| path.starts_with(deleted_path))
+        {
+            bail!(
+                "`{}` can't be used, as `{}` is moved to the trash",
+                path.display(),
+                deleted_path.display()
+            );
+        }
+        if let Some(renamed_path) = renamed_paths
+            .iter()
+            .find(|renamed_path| path != **renamed_path && path.starts_with(renamed_path))
+        {
+            bail!(
+                "`{}` is in `{}`, which is renamed",
+                path.display(),
+                renamed_path.display()
+            );
+        }
+        if let Some(existing) = snapshot.entry_for_path(path)
+            && !renamed_ids.contains(&existing.id)
+        {
+            bail!("`{}` already exists", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Applies the operations through the project, so that language servers are notified of the
+/// renamed files. The operations are validated against the worktree first, and deletions go last,
+/// moving entries to the trash. Renames whose target is another rename's source are moved out of
+/// the way through temporary names.
+async fn apply_operations(
+    project: &Entity<Project>,
+    worktree_id: WorktreeId,
+    operations: Vec<Operation>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let snapshot = project
+        .update(cx, |project, cx| {
+            project
+                .worktree_for_id(worktree_id, cx)
+                .map(|worktree| worktree.read(cx).snapshot())
+        })?
+        .context("the worktree is no longer in the project")?;
+    validate_operations(&operations, &snapshot)?;
+
+    let mut renames = Vec::new();
+    let mut creates = Vec::new();
+    let mut deletes = Vec::new();
+    for operation in operations {
+        match operation {
+            Operation::Delete { entry_id, path } => deletes.push((entry_id, path)),
+            Operation::Rename {
+                entry_id,
+                old_path,
+                new_path,
+            } => renames.push((entry_id, old_path, new_path)),
+            Operation::Create { path, is_dir } => creates.push((path, is_dir)),
+        }
+    }
+
+    let mut parents = Vec::new();
+    for (_, _, new_path) in &renames {
+        if let Some(parent) = new_path.parent()
+            && !parent.as_os_str().is_empty()
+            && !parents
+                .iter()
+                .any(|existing: &Arc<Path>| existing.as_ref() == parent)
+        {
+            parents.push(Arc::from(parent));
+        }
+    }
+    for parent in parents {
+        let exists = project.update(cx, |project, cx| {
+            project
+                .entry_for_path(&(worktree_id, parent.clone()).into(), cx)
+                .is_some()
+        })?;
+        if !exists {
+            project
+                .update(cx, |project, cx| {
+                    project.create_entry((worktree_id, parent.clone()), true, cx)
+                })?
+                .await
+                .with_context(|| format!("creating `{}`", parent.display()))?;
+        }
+    }
+
+    let sources = renames
+        .iter()
+        .map(|(_, old_path, _)| old_path.clone())
+        .collect::<HashSet<_>>();
+    if renames
+        .iter()
+        .any(|(_, _, new_path)| sources.contains(new_path))
+    {
+        for (ix, (entry_id, old_path, _)) in renames.iter_mut().enumerate() {
+            let file_name = old_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let temporary_path: Arc<Path> = old_path
+                .with_file_name(format!("{file_name}~bulk-rename-{ix}"))
+                .into();
+            *entry_id =
+                rename_entry(project, *entry_id, old_path, temporary_path.clone(), cx).await?;
+            *old_path = temporary_path;
+        }
+    }
+    for (entry_id, old_path, new_path) in renames {
+        rename_entry(project, entry_id, &old_path, new_path, cx).await?;
+    }
+
+    for (path, is_dir) in creates {
+        project
+            .update(cx, |project, cx| {
+                project.create_entry((worktree_id, path.clone()), is_dir, cx)
+            })?
+            .await
+            .with_context(|| format!("creating `{}`", path.display()))?;
+    }
+
+    for (entry_id, path) in deletes {
+        if let Some(task) =
+            project.update(cx, |project, cx| project.delete_entry(entry_id, true, cx))?
+        {
+            task.await
+                .with_context(|| format!("moving `{}` to the trash", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+async fn rename_entry(
+    project: &Entity<Project>,
+    entry_id: ProjectEntryId,
+    old_path: &Path,
+    new_path: Arc<Path>,
+    cx: &mut AsyncWindowContext,
+) -> Result<ProjectEntryId> {
+    let created_entry = project
+        .update(cx, |project, cx| {
+            project.rename_entry(entry_id, new_path.clone(), cx)
+        })?
+        .await
+        .with_context(|| {
+            format!(
+                "renaming `{}` to `{}`",
+                old_path.display(),
+                new_path.display()
+            )
+        })?;
+    match created_entry {
+        CreatedEntry::Included(entry) => Ok(entry.id),
+        CreatedEntry::Excluded { abs_path } => {
+            bail!("`{}` is excluded from the project", abs_path.display())
+        }
+    }
+}
+
+impl EventEmitter<EditorEvent> for BulkRename {}
+
+impl Focusable for BulkRename {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for BulkRename {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Pencil).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        match &self.directory {
+            Some(directory) => {
+                let name = match directory.file_name() {
+                    Some(name) => name.to_string_lossy().into_owned(),
+                    None => self
+                        .snapshot(cx)
+                        .map(|snapshot| snapshot.root_name().to_string())
+                        .unwrap_or_default(),
+                };
+                format!("Rename in {name}").into()
+            }
+            None => "Rename Entries".into(),
+        }
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let snapshot = self.snapshot(cx)?;
+        let path = PathBuf::from(snapshot.root_name()).join(self.base_path());
+        Some(path.to_string_lossy().into_owned().into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn is_singleton(&self, _: &App) -> bool {
+        false
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn can_save_as(&self, _: &App) -> bool {
+        false
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        _: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        // Applying the listing deletes and moves files, which must never happen implicitly.
+        if options.autosave {
+            return Task::ready(Ok(()));
+        }
+        let changes = match self.changes(cx) {
+            Ok(changes) => changes,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        if changes.operations.is_empty() {
+            self.reload(changes.listed_paths, cx);
+            return Task::ready(Ok(()));
+        }
+
+        let count = changes.operations.len();
+        let delete_count = changes
+            .operations
+            .iter()
+            .filter(|operation| matches!(operation, Operation::Delete { .. }))
+            .count();
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        let (level, message) = if delete_count > 0 {
+            (
+                PromptLevel::Warning,
+                format!(
+                    "Apply {count} change{}, moving {delete_count} entr{} to the trash?",
+                    plural(count),
+                    if delete_count == 1 { "y" } else { "ies" }
+                ),
+            )
+        } else {
+            (
+                PromptLevel::Info,
+                format!("Apply {count} change{}?", plural(count)),
+            )
+        };
+        let detail = changes
+            .operations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let answer = window.prompt(level, &message, Some(&detail), &["Apply", "Cancel"], cx);
+
+        let project = self.project.clone();
+        let worktree_id = self.worktree_id;
+        cx.spawn_in(window, async move |this, cx| {
+            if answer.await != Ok(0) {
+                return Ok(());
+            }
+            let result = apply_operations(&project, worktree_id, changes.operations, cx).await;
+            this.update(cx, |this, cx| this.reload(changes.listed_paths, cx))?;
+            result
+        })
+    }
+}
+
+impl Render for BulkRename {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        self.editor.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_invalid_changes(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "a": { "one.rs": "" },
+                "b": { "two.rs": "" },
+                "c.rs": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        let snapshot = cx.update(|cx| {
+            let worktree = project.read(cx).worktrees(cx).next().unwrap();
+            worktree.read(cx).snapshot()
+        });
+        let listed_entries = |paths: &[&str]| {
+            paths
+                .iter()
+                .map(|path| ListedEntry::new(snapshot.entry_for_path(path).unwrap()))
+                .collect::<Vec<_>>()
+        };
+        let error = |entries: &[ListedEntry], text: &str| {
+            parse_changes(text, Path::new(""), entries, &snapshot)
+                .unwrap_err()
+                .to_string()
+        };
+
+        let entries = listed_entries(&["a", "b", "c.rs"]);
+        let changes = parse_changes("1 b/\n2 a/\n", Path::new(""), &entries, &snapshot).unwrap();
+        assert_eq!(
+            changes
+                .operations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["Rename a → b", "Rename b → a", "Move c.rs to the trash"],
+            "Swapping entries and deleting one at once should be valid"
+        );
+
+        assert_eq!(
+            error(&entries, "2 b/\n3 a/c.rs\n"),
+            "`a/c.rs` can't be used, as `a` is moved to the trash",
+            "Deletions are applied last, so nothing can be moved into a deleted directory"
+        );
+        assert_eq!(
+            error(&entries, "1 a/\n2 b/\nc.rs\n"),
+            "`c.rs` can't be used, as `c.rs` is moved to the trash",
+        );
+        assert_eq!(
+            error(&entries, "1 z/\n2 b/\n3 c.rs\na/new.rs\n"),
+            "`a/new.rs` is in `a`, which is renamed",
+        );
+        assert_eq!(
+            error(&entries, "1 a/\n2 b/\n3 b/two.rs\n"),
+            "`b/two.rs` already exists",
+        );
+
+        let entries = listed_entries(&["a", "a/one.rs"]);
+        assert_eq!(
+            error(&entries, "1 z/\n2 a/two.rs\n"),
+            "`a/one.rs` can't be changed along with `a`, which contains it",
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use collections::HashMap;
+use std::collections::BTreeMap;
+
+const CAPTURE: &str = "${capture}";
+
+/// VS Code-style file nesting patterns, which group related files in a directory
+/// under a single parent file.
+#[derive(Debug, Default)]
+pub(crate) struct FileNestingPatterns {
+    patterns: Vec<(String, Vec<String>)>,
+}
+
+impl FileNestingPatterns {
+    pub(crate) fn new(patterns: &BTreeMap<String, String>) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|(parent, children)| {
+                    let children = children
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|child| !child.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    (parent.trim().to_string(), children)
+                })
+                .collect(),
+        }
+    }
+
+    /// Groups the names of sibling files into nests, returning the index of each parent
+    /// together with the ascending indices of the files nested under it.
+    ///
+    /// Nesting is a single level deep: a file that some other file's patterns match is never
+    /// a parent itself, and it stays unnested when all of the files matching it are nested too.
+    pub(crate) fn nest(&self, file_names: &[&str]) -> Vec<(usize, Vec<usize>)> {
+        if self.patterns.is_empty() || file_names.len() < 2 {
+            return Vec::new();
+        }
+
+        let ix_by_name = file_names
+            .iter()
+            .enumerate()
+            .map(|(ix, name)| (*name, ix))
+            .collect::<HashMap<_, _>>();
+        let candidates = file_names
+            .iter()
+            .enumerate()
+            .map(|(parent_ix, parent_name)| {
+                let mut children = Vec::new();
+                for (parent_pattern, child_patterns) in &self.patterns {
+                    let Some(capture) = capture(parent_pattern, parent_name) else {
+                        continue;
+                    };
+                    for child_pattern in child_patterns {
+                        let child_pattern = child_pattern.replace(CAPTURE, capture);
+                        if child_pattern.contains('*') {
+                            children.extend(file_names.iter().enumerate().filter_map(
+                                |(ix, name)| {
+                                    (ix != parent_ix && matches_pattern(&child_pattern, name))
+                                        .then_some(ix)
+                                },
+                            ));
+                        } else if let Some(&ix) = ix_by_name.get(child_pattern.as_str())
+                            && ix != parent_ix
+                        {
+                            children.push(ix);
+                        }
+                    }
+                }
+                children.sort_unstable();
+                children.dedup();
+                children
+            })
+            .collect::<Vec<_>>();
+
+        let mut is_child = vec![false; file_names.len()];
+        for &child_ix in candidates.iter().flatten() {
+            is_child[child_ix] = true;
+        }
+
+        let mut is_nested = vec![false; file_names.len()];
+        let mut nests = Vec::new();
+        for (parent_ix, children) in candidates.into_iter().enumerate() {
+            if is_child[parent_ix] {
+                continue;
+            }
+            let children = children
+                .into_iter()
+                .filter(|&child_ix| !std::mem::replace(&mut is_nested[child_ix], true))
+                .collect::<Vec<_>>();
+            if !children.is_empty() {
+                nests.push((parent_ix, children));
+            }
+        }
+        nests
+    }
+
+    /// Returns the name a nested file should be renamed to when the parent of its nest is renamed
+    /// from `old_parent` to `new_parent`, or `None` if the nested file's name isn't derived from
+    /// the parent's.
+    pub(crate) fn renamed_child(
+        &self,
+        old_parent: &str,
+        new_parent: &str,
+        child: &str,
+    ) -> Option<String> {
+        self.patterns
+            .iter()
+            .find_map(|(parent_pattern, child_patterns)| {
+                let old_capture = capture(parent_pattern, old_parent)?;
+                let new_capture = capture(parent_pattern, new_parent)?;
+                if old_capture.is_empty() || old_capture == new_capture {
+                    return None;
+                }
+                child_patterns
+                    .iter()
+                    .filter(|child_pattern| child_pattern.contains(CAPTURE))
+                    .any(|child_pattern| {
+                        matches_pattern(&child_pattern.replace(CAPTURE, old_capture), child)
+                    })
+                    .then(|| child.replacen(old_capture, new_capture, 1))
+            })
+    }
+}
+
+/// Matches `name` against a parent pattern, returning the text matched by its `*`,
+/// or an empty string for patterns without one.
+fn capture<'a>(pattern: &str, name: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            if name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+            {
+                Some(&name[prefix.len()..name.len() - suffix.len()])
+            } else {
+                None
+            }
+        }
+        None => (pattern == name).then_some(""),
+    }
+}
+
+/// Matches `name` against a pattern in which each `*` matches any (possibly empty) text.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(ix) => rest = &rest[ix + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
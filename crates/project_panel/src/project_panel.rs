@@ -1,13 +1,17 @@
+mod bulk_rename;
+mod file_nesting;
 mod project_panel_settings;
 mod utils;
 
 use anyhow::{Context as _, Result};
+use bulk_rename::BulkRename;
 use client::{ErrorCode, ErrorExt};
 use collections::{BTreeSet, HashMap, hash_map};
 use command_palette_hooks::CommandPaletteFilter;
 use db::kvp::KEY_VALUE_STORE;
 use editor::{
     Editor, EditorEvent, EditorSettings, ShowScrollbar,
+    actions::{MoveToEnd, SelectAll},
     items::{
         entry_diagnostic_aware_icon_decoration_and_color,
         entry_diagnostic_aware_icon_name_and_color, entry_git_aware_label_color,
@@ -15,17 +19,19 @@ use editor::{
     scroll::ScrollbarAutoHide,
 };
 use file_icons::FileIcons;
+use file_nesting::FileNestingPatterns;
+use fuzzy::{CharBag, PathMatchCandidate};
 use git::status::GitSummary;
 use git_ui::file_diff_view::FileDiffView;
 use gpui::{
     Action, AnyElement, App, ArcCow, AsyncWindowContext, Bounds, ClipboardItem, Context,
     CursorStyle, DismissEvent, Div, DragMoveEvent, Entity, EventEmitter, ExternalPaths,
-    FocusHandle, Focusable, Hsla, InteractiveElement, KeyContext, ListHorizontalSizingBehavior,
-    ListSizingBehavior, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent,
-    ParentElement, Pixels, Point, PromptLevel, Render, ScrollStrategy, Stateful, Styled,
-    Subscription, Task, UniformListScrollHandle, WeakEntity, Window, actions, anchored, deferred,
-    div, hsla, linear_color_stop, linear_gradient, point, px, size, transparent_white,
-    uniform_list,
+    FocusHandle, Focusable, Hsla, InteractiveElement, KeyContext, KeyDownEvent,
+    ListHorizontalSizingBehavior, ListSizingBehavior, Modifiers, ModifiersChangedEvent,
+    MouseButton, MouseDownEvent, ParentElement, Pixels, Point, PromptLevel, Render, ScrollStrategy,
+    Stateful, Styled, Subscription, Task, UniformListScrollHandle, WeakEntity, Window, actions,
+    anchored, deferred, div, hsla, linear_color_stop, linear_gradient, point, px, size,
+    transparent_white, uniform_list,
 };
 use indexmap::IndexMap;
 use language::DiagnosticSeverity;
@@ -38,7 +44,8 @@ use project::{
     relativize_path,
 };
 use project_panel_settings::{
-    ProjectPanelDockPosition, ProjectPanelSettings, ShowDiagnostics, ShowIndentGuides,
+    ProjectPanelDockPosition, ProjectPanelSettings, ProjectPanelSortBy, ProjectPanelSortMode,
+    ShowDiagnostics, ShowIndentGuides,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -57,9 +64,10 @@ use std::{
 };
 use theme::ThemeSettings;
 use ui::{
-    Color, ContextMenu, DecoratedIcon, Divider, Icon, IconDecoration, IconDecorationKind,
-    IndentGuideColors, IndentGuideLayout, KeyBinding, Label, LabelSize, ListItem, ListItemSpacing,
-    ScrollableHandle, Scrollbar, ScrollbarState, StickyCandidate, Tooltip, prelude::*, v_flex,
+    Color, ContextMenu, DecoratedIcon, Divider, HighlightedLabel, Icon, IconDecoration,
+    IconDecorationKind, IndentGuideColors, IndentGuideLayout, KeyBinding, Label, LabelSize,
+    ListItem, ListItemSpacing, ScrollableHandle, Scrollbar, ScrollbarState, StickyCandidate,
+    Tooltip, prelude::*, v_flex,
 };
 use util::{ResultExt, TakeUntilExt, TryFutureExt, maybe, paths::compare_paths};
 use workspace::{
@@ -73,6 +81,8 @@ use zed_actions::workspace::OpenWithSystem;
 
 const PROJECT_PANEL_KEY: &str = "ProjectPanel";
 const NEW_ENTRY_ID: ProjectEntryId = ProjectEntryId::MAX;
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(100);
+const MAX_FILTER_MATCHES: usize = 1000;
 
 struct VisibleEntriesForWorktree {
     worktree_id: WorktreeId,
@@ -98,12 +108,29 @@ pub struct ProjectPanel {
     drag_target_entry: Option<DragTargetEntry>,
     expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
     unfolded_dir_ids: HashSet<ProjectEntryId>,
+    /// Maps from the parent file of each nest (see `file_nesting` settings) to the files nested under it.
+    file_nests: HashMap<ProjectEntryId, FileNest>,
+    /// Maps from nested files to the parent file of their nest.
+    nested_file_parents: HashMap<ProjectEntryId, ProjectEntryId>,
+    /// Nests whose expansion differs from the `file_nesting.expand` setting.
+    toggled_nest_ids: HashSet<ProjectEntryId>,
     // Currently selected leaf entry (see auto-folding for a definition of that) in a file tree
     selection: Option<SelectedEntry>,
     marked_entries: Vec<SelectedEntry>,
     context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
     edit_state: Option<EditState>,
     filename_editor: Entity<Editor>,
+    filter_editor: Entity<Editor>,
+    /// Whether the filter editor is shown, narrowing the tree down to entries matching its query.
+    show_filter: bool,
+    /// Positions of the filter query's characters in the names it matched, once matched in the
+    /// background. Entries are filtered by these while set.
+    filter_name_matches: Option<Arc<HashMap<String, Vec<usize>>>>,
+    filter_task: Task<()>,
+    /// Positions of the filter query's characters in the names of the entries it matches.
+    filter_matches: HashMap<ProjectEntryId, Vec<usize>>,
+    /// Directories shown expanded while filtering, because they contain matching entries.
+    filter_ancestor_ids: HashSet<ProjectEntryId>,
     clipboard: Option<ClipboardEntry>,
     _dragged_entry_destination: Option<Arc<Path>>,
     workspace: WeakEntity<Workspace>,
@@ -126,6 +153,13 @@ pub struct ProjectPanel {
     last_reported_update: Instant,
 }
 
+#[derive(Debug, Default)]
+struct FileNest {
+    children: Vec<(ProjectEntryId, Arc<Path>)>,
+    /// The combined git status of the nested files.
+    git_summary: GitSummary,
+}
+
 struct DragTargetEntry {
     /// The entry currently under the mouse cursor during a drag operation
     entry_id: ProjectEntryId,
@@ -181,6 +215,9 @@ struct EntryDetails {
     kind: EntryKind,
     is_ignored: bool,
     is_expanded: bool,
+    is_nest_parent: bool,
+    /// Positions of the characters matching the filter query in `filename`.
+    filter_match_positions: Vec<usize>,
     is_selected: bool,
     is_marked: bool,
     is_editing: bool,
@@ -289,6 +326,12 @@ actions!(
         SelectPrevDirectory,
         /// Opens a diff view to compare two marked files.
         CompareMarkedFiles,
+        /// Filters the project tree by file name.
+        Filter,
+        /// Clears the file name filter.
+        ClearFilter,
+        /// Renames the marked entries, or the entries of the selected directory, by editing a list of them in a buffer.
+        RenameInBuffer,
     ]
 );
 
@@ -494,6 +537,7 @@ impl ProjectPanel {
                 | project::Event::WorktreeAdded(_)
                 | project::Event::WorktreeOrderChanged => {
                     this.update_visible_entries(None, cx);
+                    this.update_filter(cx);
                     cx.notify();
                 }
                 project::Event::ExpandedAllForEntry(worktree_id, entry_id) => {
@@ -572,14 +616,34 @@ impl ProjectPanel {
             )
             .detach();
 
+            let filter_editor = cx.new(|cx| {
+                let mut editor = Editor::single_line(window, cx);
+                editor.set_placeholder_text("Filter...", cx);
+                editor
+            });
+            cx.subscribe(
+                &filter_editor,
+                |project_panel, _, editor_event, cx| match editor_event {
+                    EditorEvent::BufferEdited => project_panel.update_filter(cx),
+                    EditorEvent::Blurred => {
+                        if project_panel.filter_query(cx).is_none() {
+                            project_panel.show_filter = false;
+                            cx.notify();
+                        }
+                    }
+                    _ => {}
+                },
+            )
+            .detach();
+
             cx.observe_global::<FileIcons>(|_, cx| {
                 cx.notify();
             })
             .detach();
 
-            let mut project_panel_settings = *ProjectPanelSettings::get_global(cx);
+            let mut project_panel_settings = ProjectPanelSettings::get_global(cx).clone();
             cx.observe_global::<SettingsStore>(move |this, cx| {
-                let new_settings = *ProjectPanelSettings::get_global(cx);
+                let new_settings = ProjectPanelSettings::get_global(cx).clone();
                 if project_panel_settings != new_settings {
                     if project_panel_settings.hide_gitignore != new_settings.hide_gitignore {
                         this.update_visible_entries(None, cx);
@@ -587,6 +651,12 @@ impl ProjectPanel {
                     if project_panel_settings.hide_root != new_settings.hide_root {
                         this.update_visible_entries(None, cx);
                     }
+                    if project_panel_settings.file_nesting != new_settings.file_nesting
+                        || project_panel_settings.sort_mode != new_settings.sort_mode
+                        || project_panel_settings.sort_by != new_settings.sort_by
+                    {
+                        this.update_visible_entries(None, cx);
+                    }
                     if project_panel_settings.sticky_scroll && !new_settings.sticky_scroll {
                         this.sticky_items_count = 0;
                     }
@@ -610,11 +680,20 @@ impl ProjectPanel {
                 last_worktree_root_id: Default::default(),
                 expanded_dir_ids: Default::default(),
                 unfolded_dir_ids: Default::default(),
+                file_nests: Default::default(),
+                nested_file_parents: Default::default(),
+                toggled_nest_ids: Default::default(),
                 selection: None,
                 marked_entries: Default::default(),
                 edit_state: None,
                 context_menu: None,
                 filename_editor,
+                filter_editor,
+                show_filter: false,
+                filter_name_matches: None,
+                filter_task: Task::ready(()),
+                filter_matches: Default::default(),
+                filter_ancestor_ids: Default::default(),
                 clipboard: None,
                 _dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
@@ -957,6 +1036,7 @@ impl ProjectPanel {
                             .when(!should_hide_rename, |menu| {
                                 menu.action("Rename", Box::new(Rename))
                             })
+                            .action("Rename in Buffer", Box::new(RenameInBuffer))
                             .when(!is_root & !is_remote, |menu| {
                                 menu.action("Trash", Box::new(Trash { skip_prompt: false }))
                             })
@@ -1035,6 +1115,18 @@ impl ProjectPanel {
                 cx.notify();
                 return;
             }
+            if self.file_nests.contains_key(&entry.id) {
+                let worktree_id = worktree.id();
+                let entry_id = entry.id;
+                if self.is_nest_expanded(entry_id, cx) {
+                    self.select_next(&SelectNext, window, cx);
+                } else {
+                    self.set_nest_expanded(entry_id, true, cx);
+                    self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+                    cx.notify();
+                }
+                return;
+            }
             if entry.is_dir() {
                 let worktree_id = worktree.id();
                 let entry_id = entry.id;
@@ -1083,6 +1175,19 @@ impl ProjectPanel {
             return;
         }
         let worktree_id = worktree.id();
+        let nest_parent_id = if self.file_nests.contains_key(&entry.id) {
+            Some(entry.id)
+        } else {
+            self.nested_file_parents.get(&entry.id).copied()
+        };
+        if let Some(parent_id) = nest_parent_id
+            && self.is_nest_expanded(parent_id, cx)
+        {
+            self.set_nest_expanded(parent_id, false, cx);
+            self.update_visible_entries(Some((worktree_id, parent_id)), cx);
+            cx.notify();
+            return;
+        }
         let expanded_dir_ids =
             if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
                 expanded_dir_ids
@@ -1133,6 +1238,16 @@ impl ProjectPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.file_nests.contains_key(&entry_id)
+            && let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx)
+        {
+            let is_expanded = self.is_nest_expanded(entry_id, cx);
+            self.set_nest_expanded(entry_id, !is_expanded, cx);
+            self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+            window.focus(&self.focus_handle);
+            cx.notify();
+            return;
+        }
         if let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx)
             && let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id)
         {
@@ -1295,6 +1410,11 @@ impl ProjectPanel {
     }
 
     fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.filter_editor.focus_handle(cx).is_focused(window) {
+            window.focus(&self.focus_handle);
+            cx.notify();
+            return;
+        }
         if let Some(task) = self.confirm_edit(window, cx) {
             task.detach_and_notify_err(window, cx);
         }
@@ -1445,8 +1565,20 @@ impl ProjectPanel {
                 return None;
             }
             edited_entry_id = entry.id;
+            let nested_file_renames = self.nested_file_renames(&entry, &filename, cx);
             edit_task = self.project.update(cx, |project, cx| {
-                project.rename_entry(entry.id, new_path.as_path(), cx)
+                let rename = project.rename_entry(entry.id, new_path.as_path(), cx);
+                let nested_renames = nested_file_renames
+                    .into_iter()
+                    .map(|(entry_id, new_path)| project.rename_entry(entry_id, new_path, cx))
+                    .collect::<Vec<_>>();
+                cx.spawn(async move |_, _| {
+                    let new_entry = rename.await?;
+                    for nested_rename in nested_renames {
+                        nested_rename.await.log_err();
+                    }
+                    anyhow::Ok(new_entry)
+                })
             });
         };
 
@@ -1519,12 +1651,46 @@ impl ProjectPanel {
         }))
     }
 
+    /// Returns the new paths of the files nested under `entry` when it's renamed to `new_filename`.
+    fn nested_file_renames(
+        &self,
+        entry: &Entry,
+        new_filename: &str,
+        cx: &App,
+    ) -> Vec<(ProjectEntryId, PathBuf)> {
+        let file_nesting = &ProjectPanelSettings::get_global(cx).file_nesting;
+        if !file_nesting.include_nested_files || Path::new(new_filename).components().count() > 1 {
+            return Vec::new();
+        }
+        let (Some(nest), Some(old_filename), Some(directory)) = (
+            self.file_nests.get(&entry.id),
+            entry.path.file_name().and_then(OsStr::to_str),
+            entry.path.parent(),
+        ) else {
+            return Vec::new();
+        };
+        let patterns = FileNestingPatterns::new(&file_nesting.patterns);
+        nest.children
+            .iter()
+            .filter_map(|(entry_id, path)| {
+                let child_filename = path.file_name()?.to_str()?;
+                let new_child_filename =
+                    patterns.renamed_child(old_filename, new_filename, child_filename)?;
+                Some((*entry_id, directory.join(new_child_filename)))
+            })
+            .collect()
+    }
+
     fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
         if cx.stop_active_drag(window) {
             self.drag_target_entry.take();
             self.hover_expand_task.take();
             return;
         }
+        if self.edit_state.is_none() && self.show_filter {
+            self.clear_filter(&ClearFilter, window, cx);
+            return;
+        }
 
         let previous_edit_state = self.edit_state.take();
         self.update_visible_entries(None, cx);
@@ -1541,6 +1707,95 @@ impl ProjectPanel {
         cx.notify();
     }
 
+    fn filter(&mut self, _: &Filter, window: &mut Window, cx: &mut Context<Self>) {
+        self.show_filter = true;
+        self.filter_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        window.focus(&self.filter_editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    fn clear_filter(&mut self, _: &ClearFilter, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.show_filter {
+            return;
+        }
+        self.show_filter = false;
+        self.filter_editor
+            .update(cx, |editor, cx| editor.clear(window, cx));
+        self.filter_name_matches = None;
+        self.filter_task = Task::ready(());
+        self.update_visible_entries(None, cx);
+        // Reveal the entry selected while filtering in the unfiltered tree.
+        if let Some(selection) = self.selection {
+            self.expand_entry(selection.worktree_id, selection.entry_id, cx);
+            self.update_visible_entries(None, cx);
+        }
+        self.autoscroll(cx);
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    /// Starts filtering the tree when a character is typed while the panel is focused and the
+    /// keystroke isn't bound to an action.
+    fn filter_on_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let modifiers = &event.keystroke.modifiers;
+        if self.edit_state.is_some()
+            || !self.focus_handle.is_focused(window)
+            || modifiers.control
+            || modifiers.alt
+            || modifiers.platform
+            || modifiers.function
+        {
+            return;
+        }
+        let Some(key_char) = event
+            .keystroke
+            .key_char
+            .as_ref()
+            .filter(|key_char| !key_char.trim().is_empty())
+        else {
+            return;
+        };
+        cx.stop_propagation();
+        self.show_filter = true;
+        let query = format!("{}{key_char}", self.filter_editor.read(cx).text(cx));
+        self.filter_editor.update(cx, |editor, cx| {
+            editor.set_text(query, window, cx);
+            editor.move_to_end(&MoveToEnd, window, cx);
+        });
+        window.focus(&self.filter_editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    /// Selects the first entry matching the filter, unless the selected entry matches it already.
+    fn select_first_filter_match(&mut self, cx: &mut Context<Self>) {
+        let selection_matches = self
+            .selection
+            .is_some_and(|selection| self.filter_matches.contains_key(&selection.entry_id));
+        if !selection_matches {
+            let first_match = self.visible_entries.iter().find_map(|worktree| {
+                let entry = worktree
+                    .entries
+                    .iter()
+                    .find(|entry| self.filter_matches.contains_key(&entry.id))?;
+                Some(SelectedEntry {
+                    worktree_id: worktree.worktree_id,
+                    entry_id: entry.id,
+                })
+            });
+            if first_match.is_some() {
+                self.selection = first_match;
+            }
+        }
+        self.autoscroll(cx);
+    }
+
     fn open_entry(
         &mut self,
         entry_id: ProjectEntryId,
@@ -2642,6 +2897,60 @@ impl ProjectPanel {
         }
     }
 
+    fn rename_in_buffer(
+        &mut self,
+        _: &RenameInBuffer,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+        let project = self.project.clone();
+        let Some(worktree) = project.read(cx).worktree_for_id(selection.worktree_id, cx) else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        let worktree_id = worktree.id();
+
+        let marked_paths = if self.marked_entries.len() > 1 {
+            let mut paths = self
+                .disjoint_entries(cx)
+                .into_iter()
+                .filter(|entry| entry.worktree_id == worktree_id)
+                .filter_map(|entry| Some(worktree.entry_for_id(entry.entry_id)?.path.clone()))
+                .collect::<Vec<_>>();
+            paths.sort_by(|a, b| compare_paths((a.as_ref(), true), (b.as_ref(), true)));
+            paths
+        } else {
+            Vec::new()
+        };
+        let directory = if marked_paths.is_empty() {
+            let Some(entry) = worktree.entry_for_id(self.resolve_entry(selection.entry_id)) else {
+                return;
+            };
+            if entry.is_dir() {
+                Some(entry.path.clone())
+            } else {
+                entry.path.parent().map(Arc::from)
+            }
+        } else {
+            None
+        };
+
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let bulk_rename = cx.new(|cx| match directory {
+                    Some(directory) => {
+                        BulkRename::for_directory(project, worktree_id, directory, window, cx)
+                    }
+                    None => BulkRename::for_paths(project, worktree_id, marked_paths, window, cx),
+                });
+                workspace.add_item_to_active_pane(Box::new(bulk_rename), None, true, window, cx);
+            })
+            .ok();
+    }
+
     fn open_system(&mut self, _: &OpenWithSystem, _: &mut Window, cx: &mut Context<Self>) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             let abs_path = worktree.abs_path().join(&entry.path);
@@ -2849,7 +3158,36 @@ impl ProjectPanel {
             }
         }
 
-        sanitized_entries
+        self.with_nested_files(sanitized_entries, cx)
+    }
+
+    /// Adds the files nested under the given entries, if operations on the parent file of a nest
+    /// should include its nested files.
+    fn with_nested_files(
+        &self,
+        mut entries: BTreeSet<SelectedEntry>,
+        cx: &App,
+    ) -> BTreeSet<SelectedEntry> {
+        if !ProjectPanelSettings::get_global(cx)
+            .file_nesting
+            .include_nested_files
+        {
+            return entries;
+        }
+        let nested_entries = entries
+            .iter()
+            .filter_map(|entry| Some((entry.worktree_id, self.file_nests.get(&entry.entry_id)?)))
+            .flat_map(|(worktree_id, nest)| {
+                nest.children
+                    .iter()
+                    .map(move |(entry_id, _)| SelectedEntry {
+                        worktree_id,
+                        entry_id: *entry_id,
+                    })
+            })
+            .collect::<Vec<_>>();
+        entries.extend(nested_entries);
+        entries
     }
 
     fn effective_entries(&self) -> BTreeSet<SelectedEntry> {
@@ -2943,6 +3281,9 @@ impl ProjectPanel {
                 expanded_dir_ids.insert(idx, entry.id);
             }
         }
+        if let Some(parent_id) = self.nested_file_parents.get(&entry.id).copied() {
+            self.set_nest_expanded(parent_id, true, cx);
+        }
 
         Some(())
     }
@@ -2978,8 +3319,10 @@ impl ProjectPanel {
         cx: &mut Context<Self>,
     ) {
         let now = Instant::now();
+        let filter_name_matches = self.filter_name_matches.clone();
         let settings = ProjectPanelSettings::get_global(cx);
-        let auto_collapse_dirs = settings.auto_fold_dirs;
+        // While filtering, every directory is searched and shown unfolded.
+        let auto_collapse_dirs = settings.auto_fold_dirs && filter_name_matches.is_none();
         let hide_gitignore = settings.hide_gitignore;
         let project = self.project.read(cx);
         let repo_snapshots = project.git_store().read(cx).repo_snapshots(cx);
@@ -2991,6 +3334,14 @@ impl ProjectPanel {
 
         let old_ancestors = std::mem::take(&mut self.ancestors);
         self.visible_entries.clear();
+        self.file_nests.clear();
+        self.nested_file_parents.clear();
+        self.filter_matches.clear();
+        self.filter_ancestor_ids.clear();
+        let file_nesting_patterns = (settings.file_nesting.enabled
+            && filter_name_matches.is_none())
+        .then(|| FileNestingPatterns::new(&settings.file_nesting.patterns));
+        let (sort_mode, sort_by) = (settings.sort_mode, settings.sort_by);
         let mut max_width_item = None;
 
         let visible_worktrees: Vec<_> = project.visible_worktrees(cx).collect();
@@ -3165,7 +3516,8 @@ impl ProjectPanel {
                     }
                 }
 
-                if expanded_dir_ids.binary_search(&entry.id).is_err()
+                if filter_name_matches.is_none()
+                    && expanded_dir_ids.binary_search(&entry.id).is_err()
                     && entry_iter.advance_to_sibling()
                 {
                     continue;
@@ -3173,7 +3525,13 @@ impl ProjectPanel {
                 entry_iter.advance();
             }
 
-            project::sort_worktree_entries(&mut visible_worktree_entries);
+            if let Some(filter_name_matches) = &filter_name_matches {
+                self.filter_entries(&mut visible_worktree_entries, filter_name_matches);
+            }
+            sort_visible_entries(&mut visible_worktree_entries, sort_mode, sort_by);
+            if let Some(file_nesting_patterns) = &file_nesting_patterns {
+                self.nest_files(&mut visible_worktree_entries, file_nesting_patterns, cx);
+            }
 
             self.visible_entries.push(VisibleEntriesForWorktree {
                 worktree_id,
@@ -3219,6 +3577,208 @@ impl ProjectPanel {
         }
     }
 
+    /// Returns the query entries are filtered by, if the filter is shown and isn't empty.
+    fn filter_query(&self, cx: &App) -> Option<String> {
+        if !self.show_filter {
+            return None;
+        }
+        let query = self.filter_editor.read(cx).text(cx);
+        let query = query.trim();
+        (!query.is_empty()).then(|| query.to_string())
+    }
+
+    /// Fuzzy matches the names of the worktrees' entries against the filter query on the
+    /// background executor, once the query stops changing, and filters the entries by the
+    /// matches when they come back. Only the best [`MAX_FILTER_MATCHES`] names are kept.
+    fn update_filter(&mut self, cx: &mut Context<Self>) {
+        let Some(query) = self.filter_query(cx) else {
+            self.filter_task = Task::ready(());
+            if self.filter_name_matches.take().is_some() {
+                self.update_visible_entries(None, cx);
+                cx.notify();
+            }
+            return;
+        };
+        let include_ignored = !ProjectPanelSettings::get_global(cx).hide_gitignore;
+        let snapshots = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).snapshot())
+            .collect::<Vec<_>>();
+        self.filter_task = cx.spawn(async move |project_panel, cx| {
+            cx.background_executor().timer(FILTER_DEBOUNCE).await;
+            let filter_name_matches = cx
+                .background_spawn(async move {
+                    let names = snapshots
+                        .iter()
+                        .flat_map(|snapshot| snapshot.entries(include_ignored, 0))
+                        .filter_map(|entry| entry.path.file_name()?.to_str())
+                        .collect::<HashSet<_>>();
+                    let candidates = names
+                        .into_iter()
+                        .map(|name| PathMatchCandidate {
+                            is_dir: false,
+                            path: Path::new(name),
+                            char_bag: CharBag::from(name),
+                        })
+                        .collect();
+                    fuzzy::match_fixed_path_set(candidates, 0, &query, false, MAX_FILTER_MATCHES)
+                        .into_iter()
+                        .filter_map(|path_match| {
+                            let name = path_match.path.to_str()?.to_string();
+                            Some((name, path_match.positions))
+                        })
+                        .collect::<HashMap<_, _>>()
+                })
+                .await;
+            project_panel
+                .update(cx, |project_panel, cx| {
+                    project_panel.filter_name_matches = Some(Arc::new(filter_name_matches));
+                    project_panel.update_visible_entries(None, cx);
+                    project_panel.select_first_filter_match(cx);
+                    cx.notify();
+                })
+                .ok();
+        });
+    }
+
+    /// Narrows the entries down to the ones whose names the filter query matched, along with
+    /// their ancestors, recording the matched positions for highlighting.
+    fn filter_entries(
+        &mut self,
+        entries: &mut Vec<GitEntry>,
+        filter_name_matches: &HashMap<String, Vec<usize>>,
+    ) {
+        let mut kept_paths = HashSet::new();
+        let mut ancestor_paths = HashSet::new();
+        for entry in entries.iter() {
+            if entry.id == NEW_ENTRY_ID {
+                continue;
+            }
+            let Some(positions) = entry
+                .path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .and_then(|name| filter_name_matches.get(name))
+            else {
+                continue;
+            };
+            self.filter_matches.insert(entry.id, positions.clone());
+            kept_paths.insert(entry.path.clone());
+            ancestor_paths.extend(entry.path.ancestors().skip(1).map(Arc::<Path>::from));
+        }
+        // Keep the worktree root, and the directory an entry is being created in.
+        ancestor_paths.insert(Arc::from(Path::new("")));
+        for entry in entries.iter() {
+            if entry.id == NEW_ENTRY_ID {
+                kept_paths.insert(entry.path.clone());
+                ancestor_paths.extend(entry.path.ancestors().skip(1).map(Arc::<Path>::from));
+            }
+        }
+
+        entries.retain(|entry| {
+            if ancestor_paths.contains(&entry.path) {
+                if entry.is_dir() {
+                    self.filter_ancestor_ids.insert(entry.id);
+                }
+                true
+            } else {
+                kept_paths.contains(&entry.path)
+            }
+        });
+    }
+
+    /// Groups the files of each directory into nests according to the `file_nesting` settings,
+    /// moving nested files right after their parent and hiding them while their nest is collapsed.
+    fn nest_files(
+        &mut self,
+        entries: &mut Vec<GitEntry>,
+        patterns: &FileNestingPatterns,
+        cx: &App,
+    ) {
+        let mut file_ixs_by_directory = HashMap::<&Path, Vec<usize>>::default();
+        for (ix, entry) in entries.iter().enumerate() {
+            if entry.is_file()
+                && entry.id != NEW_ENTRY_ID
+                && let Some(directory) = entry.path.parent()
+            {
+                file_ixs_by_directory.entry(directory).or_default().push(ix);
+            }
+        }
+        let mut nested_ixs = HashMap::default();
+        for file_ixs in file_ixs_by_directory.into_values() {
+            let file_names = file_ixs
+                .iter()
+                .map(|&ix| {
+                    entries[ix]
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            let file_names = file_names.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+            for (parent_ix, child_ixs) in patterns.nest(&file_names) {
+                nested_ixs.insert(
+                    file_ixs[parent_ix],
+                    child_ixs
+                        .into_iter()
+                        .map(|child_ix| file_ixs[child_ix])
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+        if nested_ixs.is_empty() {
+            return;
+        }
+
+        let mut slots = entries.drain(..).map(Some).collect::<Vec<_>>();
+        for (parent_ix, child_ixs) in &nested_ixs {
+            let Some(parent_id) = slots[*parent_ix].as_ref().map(|parent| parent.id) else {
+                continue;
+            };
+            let mut nest = FileNest::default();
+            for child_ix in child_ixs {
+                if let Some(child) = &slots[*child_ix] {
+                    nest.children.push((child.id, child.path.clone()));
+                    nest.git_summary += child.git_summary;
+                    self.nested_file_parents.insert(child.id, parent_id);
+                }
+            }
+            self.file_nests.insert(parent_id, nest);
+        }
+        let all_child_ixs = nested_ixs.values().flatten().collect::<HashSet<_>>();
+        for ix in 0..slots.len() {
+            if all_child_ixs.contains(&ix) {
+                continue;
+            }
+            let Some(entry) = slots[ix].take() else {
+                continue;
+            };
+            let child_ixs = nested_ixs
+                .get(&ix)
+                .filter(|_| self.is_nest_expanded(entry.id, cx));
+            entries.push(entry);
+            for child_ix in child_ixs.into_iter().flatten() {
+                entries.extend(slots[*child_ix].take());
+            }
+        }
+    }
+
+    fn is_nest_expanded(&self, parent_id: ProjectEntryId, cx: &App) -> bool {
+        ProjectPanelSettings::get_global(cx).file_nesting.expand
+            != self.toggled_nest_ids.contains(&parent_id)
+    }
+
+    fn set_nest_expanded(&mut self, parent_id: ProjectEntryId, expanded: bool, cx: &App) {
+        if expanded == ProjectPanelSettings::get_global(cx).file_nesting.expand {
+            self.toggled_nest_ids.remove(&parent_id);
+        } else {
+            self.toggled_nest_ids.insert(parent_id);
+        }
+    }
+
     fn expand_entry(
         &mut self,
         worktree_id: WorktreeId,
@@ -3250,6 +3810,9 @@ impl ProjectPanel {
                 }
             }
         });
+        if let Some(parent_id) = self.nested_file_parents.get(&entry_id).copied() {
+            self.set_nest_expanded(parent_id, true, cx);
+        }
     }
 
     fn drop_external_files(
@@ -3361,6 +3924,7 @@ impl ProjectPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        let selections = self.with_nested_files(selections.items().copied().collect(), cx);
         if Self::is_copy_modifier_set(&window.modifiers()) {
             let _ = maybe!({
                 let project = self.project.read(cx);
@@ -3373,7 +3937,7 @@ impl ProjectPanel {
 
                 let mut copy_tasks = Vec::new();
                 let mut disambiguation_range = None;
-                for selection in selections.items() {
+                for selection in &selections {
                     let (new_path, new_disambiguation_range) = self.create_paste_path(
                         selection,
                         (target_worktree.clone(), &target_entry),
@@ -3417,7 +3981,7 @@ impl ProjectPanel {
                 Some(())
             });
         } else {
-            for selection in selections.items() {
+            for selection in selections {
                 self.move_entry(selection.entry_id, target_entry_id, is_file, cx);
             }
         }
@@ -3534,9 +4098,12 @@ impl ProjectPanel {
                     .index
                     .get_or_init(|| visible.entries.iter().map(|e| (e.path.clone())).collect());
                 for entry in visible.entries[entry_range].iter() {
-                    let status = git_status_setting
+                    let mut status = git_status_setting
                         .then_some(entry.git_summary)
                         .unwrap_or_default();
+                    if git_status_setting && let Some(nest) = self.file_nests.get(&entry.id) {
+                        status += nest.git_summary;
+                    }
 
                     let mut details = self.details_for_entry(
                         entry,
@@ -3971,6 +4538,11 @@ impl ProjectPanel {
             .is_some_and(|selection| selection.entry_id == entry_id);
 
         let file_name = details.filename.clone();
+        let filter_match_positions = if details.is_processing {
+            Vec::new()
+        } else {
+            details.filter_match_positions.clone()
+        };
 
         let mut icon = details.icon.clone();
         if settings.file_icons && show_editor && details.kind.is_file() {
@@ -3994,6 +4566,7 @@ impl ProjectPanel {
 
         let depth = details.depth;
         let worktree_id = details.worktree_id;
+        let nest_toggle = details.is_nest_parent.then_some(details.is_expanded);
         let dragged_selection = DraggedSelection {
             active_selection: selection,
             marked_selections: Arc::from(self.marked_entries.clone()),
@@ -4329,6 +4902,12 @@ impl ProjectPanel {
                 ListItem::new(id)
                     .indent_level(depth)
                     .indent_step_size(px(settings.indent_size))
+                    .when_some(nest_toggle, |this, is_expanded| {
+                        this.toggle(is_expanded)
+                            .on_toggle(cx.listener(move |this, _, window, cx| {
+                                this.toggle_expanded(entry_id, window, cx);
+                            }))
+                    })
                     .spacing(match settings.entry_spacing {
                         project_panel_settings::EntrySpacing::Comfortable => ListItemSpacing::Dense,
                         project_panel_settings::EntrySpacing::Standard => {
@@ -4554,6 +5133,12 @@ impl ProjectPanel {
                                     }
 
                                     this
+                                } else if !filter_match_positions.is_empty() {
+                                    this.child(
+                                        HighlightedLabel::new(file_name, filter_match_positions)
+                                            .single_line()
+                                            .color(filename_text_color),
+                                    )
                                 } else {
                                     this.child(
                                         Label::new(file_name)
@@ -4631,7 +5216,14 @@ impl ProjectPanel {
             .get(&worktree_id)
             .map(Vec::as_slice)
             .unwrap_or(&[]);
-        let is_expanded = expanded_entry_ids.binary_search(&entry.id).is_ok();
+        let file_nest = self.file_nests.get(&entry.id);
+        let is_expanded = if file_nest.is_some() {
+            self.is_nest_expanded(entry.id, cx)
+        } else if self.filter_name_matches.is_some() {
+            self.filter_ancestor_ids.contains(&entry.id)
+        } else {
+            expanded_entry_ids.binary_search(&entry.id).is_ok()
+        };
 
         let icon = match entry.kind {
             EntryKind::File => {
@@ -4650,8 +5242,11 @@ impl ProjectPanel {
             }
         };
 
-        let (depth, difference) =
+        let (mut depth, difference) =
             ProjectPanel::calculate_depth_and_difference(entry, entries_paths);
+        if self.nested_file_parents.contains_key(&entry.id) {
+            depth += 1;
+        }
 
         let filename = match difference {
             diff if diff > 1 => entry
@@ -4676,9 +5271,12 @@ impl ProjectPanel {
         let is_marked = self.marked_entries.contains(&selection);
         let is_selected = self.selection == Some(selection);
 
-        let diagnostic_severity = self
-            .diagnostics
-            .get(&(worktree_id, entry.path.to_path_buf()))
+        let diagnostic_severity = file_nest
+            .into_iter()
+            .flat_map(|nest| nest.children.iter().map(|(_, path)| path))
+            .chain([&entry.path])
+            .filter_map(|path| self.diagnostics.get(&(worktree_id, path.to_path_buf())))
+            .min()
             .cloned();
 
         let filename_text_color =
@@ -4697,6 +5295,13 @@ impl ProjectPanel {
             kind: entry.kind,
             is_ignored: entry.is_ignored,
             is_expanded,
+            is_nest_parent: file_nest.is_some(),
+            filter_match_positions: self
+                .filter_matches
+                .get(&entry.id)
+                .filter(|_| difference <= 1)
+                .cloned()
+                .unwrap_or_default(),
             is_selected,
             is_marked,
             is_editing: false,
@@ -4712,6 +5317,45 @@ impl ProjectPanel {
         }
     }
 
+    fn render_filter(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let match_count =
+            self.filter_name_matches
+                .as_ref()
+                .map(|_| match self.filter_matches.len() {
+                    0 => "No matches".to_string(),
+                    1 => "1 match".to_string(),
+                    count => format!("{count} matches"),
+                });
+
+        h_flex()
+            .flex_none()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_1p5()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Icon::new(IconName::MagnifyingGlass)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(div().flex_1().child(self.filter_editor.clone()))
+            .children(match_count.map(|match_count| {
+                Label::new(match_count)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            }))
+            .child(
+                IconButton::new("project-panel-clear-filter", IconName::Close)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Clear Filter"))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.clear_filter(&ClearFilter, window, cx);
+                    })),
+            )
+    }
+
     fn render_vertical_scrollbar(&self, cx: &mut Context<Self>) -> Option<Stateful<Div>> {
         if !Self::should_show_scrollbar(cx)
             || !(self.show_scrollbar || self.vertical_scrollbar_state.is_dragging())
@@ -4814,7 +5458,9 @@ impl ProjectPanel {
         dispatch_context.add("ProjectPanel");
         dispatch_context.add("menu");
 
-        let identifier = if self.filename_editor.focus_handle(cx).is_focused(window) {
+        let identifier = if self.filename_editor.focus_handle(cx).is_focused(window)
+            || self.filter_editor.focus_handle(cx).is_focused(window)
+        {
             "editing"
         } else {
             "not_editing"
@@ -5116,6 +5762,102 @@ fn item_width_estimate(depth: usize, item_text_chars: usize, is_symlink: bool) -
     item_width
 }
 
+/// Sorts the entries of each directory according to the `sort_mode` and `sort_by` settings,
+/// keeping every entry right after its parent directory and before the parent's next sibling.
+fn sort_visible_entries(
+    entries: &mut Vec<GitEntry>,
+    sort_mode: ProjectPanelSortMode,
+    sort_by: ProjectPanelSortBy,
+) {
+    if sort_mode == ProjectPanelSortMode::DirectoriesFirst && sort_by == ProjectPanelSortBy::Natural
+    {
+        project::sort_worktree_entries(entries);
+        return;
+    }
+
+    let paths = entries
+        .iter()
+        .map(|entry| entry.path.clone())
+        .collect::<HashSet<_>>();
+    // Auto-folded directories aren't visible, so group entries by their closest visible ancestor.
+    let mut children_by_parent = HashMap::<Option<Arc<Path>>, Vec<GitEntry>>::default();
+    for entry in entries.drain(..) {
+        let parent = entry
+            .path
+            .ancestors()
+            .skip(1)
+            .find(|ancestor| paths.contains(*ancestor))
+            .map(Arc::from);
+        children_by_parent.entry(parent).or_default().push(entry);
+    }
+    for children in children_by_parent.values_mut() {
+        children.sort_by(|entry_a, entry_b| {
+            compare_sibling_entries(entry_a, entry_b, sort_mode, sort_by)
+        });
+    }
+
+    let mut stack = children_by_parent.remove(&None).unwrap_or_default();
+    stack.reverse();
+    while let Some(entry) = stack.pop() {
+        if let Some(children) = children_by_parent.remove(&Some(entry.path.clone())) {
+            stack.extend(children.into_iter().rev());
+        }
+        entries.push(entry);
+    }
+}
+
+fn compare_sibling_entries(
+    entry_a: &Entry,
+    entry_b: &Entry,
+    sort_mode: ProjectPanelSortMode,
+    sort_by: ProjectPanelSortBy,
+) -> cmp::Ordering {
+    let name_a = entry_a
+        .path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let name_b = entry_b
+        .path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let compare_names = || compare_paths((Path::new(&*name_a), true), (Path::new(&*name_b), true));
+    // The entry being created is shown first, as it is when sorting by name.
+    let new_entry_order = (entry_b.id == NEW_ENTRY_ID).cmp(&(entry_a.id == NEW_ENTRY_ID));
+    let kind_order = match sort_mode {
+        ProjectPanelSortMode::DirectoriesFirst => entry_a.is_file().cmp(&entry_b.is_file()),
+        ProjectPanelSortMode::Mixed => cmp::Ordering::Equal,
+        ProjectPanelSortMode::FilesFirst => entry_b.is_file().cmp(&entry_a.is_file()),
+    };
+    new_entry_order
+        .then(kind_order)
+        .then_with(|| match sort_by {
+            ProjectPanelSortBy::Natural => compare_names(),
+            ProjectPanelSortBy::Alphabetical => name_a
+                .to_lowercase()
+                .cmp(&name_b.to_lowercase())
+                .then_with(|| name_a.cmp(&name_b)),
+            ProjectPanelSortBy::Modified => {
+                let mtime_a = entry_a.mtime.map(|mtime| mtime.timestamp_for_user());
+                let mtime_b = entry_b.mtime.map(|mtime| mtime.timestamp_for_user());
+                mtime_b.cmp(&mtime_a).then_with(compare_names)
+            }
+            ProjectPanelSortBy::FileType => {
+                let extension = |entry: &Entry, name: &str| {
+                    entry
+                        .is_file()
+                        .then(|| Path::new(name).extension())
+                        .flatten()
+                        .map(|extension| extension.to_string_lossy().to_lowercase())
+                };
+                extension(entry_a, &name_a)
+                    .cmp(&extension(entry_b, &name_b))
+                    .then_with(compare_names)
+            }
+        })
+}
+
 impl ProjectPanel {
     /// Get the display name for a worktree
     pub fn worktree_display_name(&self, worktree_id: WorktreeId, cx: &mut Context<Self>) -> String {
@@ -5241,7 +5983,7 @@ impl Render for ProjectPanel {
                     }
                 }));
             }
-            h_flex()
+            v_flex()
                 .id("project-panel")
                 .group("project-panel")
                 .when(panel_settings.drag_and_drop, |this| {
@@ -5298,10 +6040,14 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::fold_directory))
                 .on_action(cx.listener(Self::remove_from_project))
                 .on_action(cx.listener(Self::compare_marked_files))
+                .on_action(cx.listener(Self::filter))
+                .on_action(cx.listener(Self::clear_filter))
+                .on_key_down(cx.listener(Self::filter_on_key_down))
                 .when(!project.is_read_only(cx), |el| {
                     el.on_action(cx.listener(Self::new_file))
                         .on_action(cx.listener(Self::new_directory))
                         .on_action(cx.listener(Self::rename))
+                        .on_action(cx.listener(Self::rename_in_buffer))
                         .on_action(cx.listener(Self::delete))
                         .on_action(cx.listener(Self::trash))
                         .on_action(cx.listener(Self::cut))
@@ -5350,6 +6096,7 @@ impl Render for ProjectPanel {
                     }),
                 )
                 .track_focus(&self.focus_handle(cx))
+                .when(self.show_filter, |this| this.child(self.render_filter(cx)))
                 .child(
                     uniform_list("entries", item_count, {
                         cx.processor(|this, range: Range<usize>, window, cx| {
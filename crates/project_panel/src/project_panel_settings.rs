@@ -3,6 +3,7 @@ use gpui::Pixels;
 use schemars::JsonSchema;
 use serde_derive::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources, SettingsUi};
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -28,7 +29,39 @@ pub enum EntrySpacing {
     Standard,
 }
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq, SettingsUi)]
+/// How directories and files are ordered relative to each other in the project panel.
+///
+/// Default: directories_first
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectPanelSortMode {
+    /// Show directories before files.
+    #[default]
+    DirectoriesFirst,
+    /// Show directories and files together.
+    Mixed,
+    /// Show files before directories.
+    FilesFirst,
+}
+
+/// What the entries of each directory are sorted by in the project panel.
+///
+/// Default: natural
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectPanelSortBy {
+    /// Sort by name, comparing runs of digits by their numeric value (`file2` before `file10`).
+    #[default]
+    Natural,
+    /// Sort by name, comparing characters one by one (`file10` before `file2`).
+    Alphabetical,
+    /// Sort by modification time, most recently modified first.
+    Modified,
+    /// Sort by file extension, then by name.
+    FileType,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, SettingsUi)]
 pub struct ProjectPanelSettings {
     pub button: bool,
     pub hide_gitignore: bool,
@@ -48,6 +81,9 @@ pub struct ProjectPanelSettings {
     pub show_diagnostics: ShowDiagnostics,
     pub hide_root: bool,
     pub drag_and_drop: bool,
+    pub file_nesting: FileNestingSettings,
+    pub sort_mode: ProjectPanelSortMode,
+    pub sort_by: ProjectPanelSortBy,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -77,6 +113,39 @@ pub struct ScrollbarSettingsContent {
     pub show: Option<Option<ShowScrollbar>>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct FileNestingSettings {
+    pub enabled: bool,
+    pub expand: bool,
+    pub include_nested_files: bool,
+    pub patterns: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct FileNestingSettingsContent {
+    /// Whether to nest related files under a parent file in the project panel.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Whether nested files are shown when a nest is first displayed.
+    ///
+    /// Default: false
+    pub expand: Option<bool>,
+    /// Whether renaming, deleting, cutting, copying or dragging a parent file
+    /// also applies to the files nested under it.
+    ///
+    /// Default: true
+    pub include_nested_files: Option<bool>,
+    /// Patterns that nest files under a parent file in the same directory.
+    ///
+    /// Keys match the parent's file name and may contain a single `*`, whose match
+    /// is available to the values as `${capture}`. Values are comma-separated
+    /// patterns for the names of the nested files, in which `*` matches any text.
+    ///
+    /// Example: `{ "*.ts": "${capture}.test.ts, ${capture}.d.ts" }`
+    pub patterns: Option<BTreeMap<String, String>>,
+}
+
 /// Whether to indicate diagnostic errors and/or warnings in project panel items.
 ///
 /// Default: all
@@ -165,6 +234,16 @@ pub struct ProjectPanelSettingsContent {
     ///
     /// Default: true
     pub drag_and_drop: Option<bool>,
+    /// Settings related to nesting related files under a parent file in the project panel.
+    pub file_nesting: Option<FileNestingSettingsContent>,
+    /// How directories and files are ordered relative to each other in the project panel.
+    ///
+    /// Default: directories_first
+    pub sort_mode: Option<ProjectPanelSortMode>,
+    /// What the entries of each directory are sorted by in the project panel.
+    ///
+    /// Default: natural
+    pub sort_by: Option<ProjectPanelSortBy>,
 }
 
 impl Settings for ProjectPanelSettings {
@@ -184,6 +263,35 @@ impl Settings for ProjectPanelSettings {
         vscode.bool_setting("explorer.autoReveal", &mut current.auto_reveal_entries);
         vscode.bool_setting("explorer.compactFolders", &mut current.auto_fold_dirs);
 
+        let mut file_nesting = current.file_nesting.clone().unwrap_or_default();
+        vscode.bool_setting("explorer.fileNesting.enabled", &mut file_nesting.enabled);
+        vscode.bool_setting("explorer.fileNesting.expand", &mut file_nesting.expand);
+        if let Some(patterns) = vscode
+            .read_value("explorer.fileNesting.patterns")
+            .and_then(|patterns| patterns.as_object())
+        {
+            file_nesting.patterns = Some(
+                patterns
+                    .iter()
+                    .filter_map(|(parent, children)| {
+                        Some((parent.clone(), children.as_str()?.to_string()))
+                    })
+                    .collect(),
+            );
+        }
+        if file_nesting != FileNestingSettingsContent::default() {
+            current.file_nesting = Some(file_nesting);
+        }
+
+        match vscode.read_string("explorer.sortOrder") {
+            Some("default") => current.sort_mode = Some(ProjectPanelSortMode::DirectoriesFirst),
+            Some("mixed") => current.sort_mode = Some(ProjectPanelSortMode::Mixed),
+            Some("filesFirst") => current.sort_mode = Some(ProjectPanelSortMode::FilesFirst),
+            Some("type") => current.sort_by = Some(ProjectPanelSortBy::FileType),
+            Some("modified") => current.sort_by = Some(ProjectPanelSortBy::Modified),
+            _ => {}
+        }
+
         if Some(false) == vscode.read_bool("git.decorations.enabled") {
             current.git_status = Some(false);
         }
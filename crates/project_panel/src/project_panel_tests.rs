@@ -1,4 +1,5 @@
 use super::*;
+use crate::project_panel_settings::FileNestingSettings;
 use collections::HashSet;
 use gpui::{Empty, Entity, TestAppContext, VisualTestContext, WindowHandle};
 use pretty_assertions::assert_eq;
@@ -319,7 +320,7 @@ async fn test_auto_collapse_dir_paths(cx: &mut gpui::TestAppContext) {
     let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
     let cx = &mut VisualTestContext::from_window(*workspace, cx);
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: true,
@@ -401,7 +402,7 @@ async fn test_auto_collapse_dir_paths(cx: &mut gpui::TestAppContext) {
             cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     auto_fold_dirs: true,
@@ -2607,7 +2608,7 @@ async fn test_select_first_last(cx: &mut gpui::TestAppContext) {
     );
 
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_root: true,
@@ -2915,7 +2916,7 @@ async fn test_rename_with_hide_root(cx: &mut gpui::TestAppContext) {
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     hide_root: true,
@@ -2953,7 +2954,7 @@ async fn test_rename_with_hide_root(cx: &mut gpui::TestAppContext) {
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     hide_root: true,
@@ -4129,7 +4130,7 @@ async fn test_deletion_gitignored(cx: &mut gpui::TestAppContext) {
 
     // Test 1: Auto selection with one gitignored file next to the deleted file
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_gitignore: true,
@@ -4226,7 +4227,7 @@ async fn test_nested_deletion_gitignore(cx: &mut gpui::TestAppContext) {
     let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_gitignore: true,
@@ -4895,7 +4896,7 @@ async fn test_expand_all_for_entry(cx: &mut gpui::TestAppContext) {
 
     // Test 1: When auto-fold is enabled
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: true,
@@ -4957,7 +4958,7 @@ async fn test_expand_all_for_entry(cx: &mut gpui::TestAppContext) {
 
     // Test 2: When auto-fold is disabled
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: false,
@@ -5121,7 +5122,7 @@ async fn test_collapse_all_for_entry(cx: &mut gpui::TestAppContext) {
     // Test 2: With auto-fold enabled
     {
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     auto_fold_dirs: true,
@@ -5176,7 +5177,7 @@ async fn test_collapse_all_for_entry(cx: &mut gpui::TestAppContext) {
     // Test 3: With auto-fold disabled
     {
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     auto_fold_dirs: false,
@@ -5316,7 +5317,7 @@ async fn test_create_entries_without_selection_hide_root(cx: &mut gpui::TestAppC
     let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_root: true,
@@ -5682,7 +5683,7 @@ async fn test_hide_root(cx: &mut gpui::TestAppContext) {
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     hide_root: false,
@@ -5716,7 +5717,7 @@ async fn test_hide_root(cx: &mut gpui::TestAppContext) {
 
         // Set hide_root to true
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     hide_root: true,
@@ -5758,7 +5759,7 @@ async fn test_hide_root(cx: &mut gpui::TestAppContext) {
 
         // Set hide_root to true
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     hide_root: true,
@@ -5793,7 +5794,7 @@ async fn test_hide_root(cx: &mut gpui::TestAppContext) {
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     hide_root: false,
@@ -5821,6 +5822,630 @@ async fn test_hide_root(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_file_nesting(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "config": {
+                ".env": "",
+                ".env.local": "",
+                ".env.production": "",
+            },
+            "crate": {
+                "Cargo.lock": "",
+                "Cargo.toml": "",
+            },
+            "src": {
+                "bar.ts": "",
+                "foo.d.ts": "",
+                "foo.test.ts": "",
+                "foo.ts": "",
+            },
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    enable_file_nesting(true, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    toggle_expand_dir(&panel, "root/config", cx);
+    toggle_expand_dir(&panel, "root/crate", cx);
+    toggle_expand_dir(&panel, "root/src", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v config",
+            "        > .env",
+            "    v crate",
+            "        > Cargo.toml",
+            "    v src  <== selected",
+            "          bar.ts",
+            "        > foo.ts",
+        ],
+        "Nested files should be hidden under their collapsed parents"
+    );
+
+    toggle_expand_dir(&panel, "root/crate/Cargo.toml", cx);
+    toggle_expand_dir(&panel, "root/src/foo.ts", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v config",
+            "        > .env",
+            "    v crate",
+            "        v Cargo.toml",
+            "              Cargo.lock",
+            "    v src",
+            "          bar.ts",
+            "        v foo.ts  <== selected",
+            "              foo.d.ts",
+            "              foo.test.ts",
+        ],
+        "Expanded nests should show their nested files right after the parent"
+    );
+
+    select_path(&panel, "root/src/foo.test.ts", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        panel.collapse_selected_entry(&CollapseSelectedEntry, window, cx)
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v config",
+            "        > .env",
+            "    v crate",
+            "        v Cargo.toml",
+            "              Cargo.lock",
+            "    v src",
+            "          bar.ts",
+            "        > foo.ts  <== selected",
+        ],
+        "Collapsing a nested file should collapse its nest and select the parent"
+    );
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel.expand_selected_entry(&ExpandSelectedEntry, window, cx)
+    });
+    panel.update_in(cx, |panel, window, cx| {
+        panel.expand_selected_entry(&ExpandSelectedEntry, window, cx)
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 6..50, cx),
+        &[
+            "    v src",
+            "          bar.ts",
+            "        v foo.ts",
+            "              foo.d.ts  <== selected",
+            "              foo.test.ts",
+        ],
+        "Expanding an expanded nest should move the selection to its first nested file"
+    );
+
+    panel.update(cx, |panel, cx| {
+        let worktree_id = panel
+            .project
+            .read(cx)
+            .worktrees(cx)
+            .next()
+            .unwrap()
+            .read(cx)
+            .id();
+        panel.diagnostics.insert(
+            (worktree_id, Path::new("src").join("foo.test.ts")),
+            DiagnosticSeverity::ERROR,
+        );
+    });
+    toggle_expand_dir(&panel, "root/src/foo.ts", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        let mut parent_severity = None;
+        panel.for_each_visible_entry(0..50, window, cx, |_, details, _, _| {
+            if details.filename == "foo.ts" {
+                parent_severity = Some(details.diagnostic_severity);
+            }
+        });
+        assert_eq!(
+            parent_severity,
+            Some(Some(DiagnosticSeverity::ERROR)),
+            "Diagnostics of nested files should be shown on the collapsed parent"
+        );
+    });
+
+    enable_file_nesting(false, cx);
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v config",
+            "          .env",
+            "          .env.local",
+            "          .env.production",
+            "    v crate",
+            "          Cargo.lock",
+            "          Cargo.toml",
+            "    v src",
+            "          bar.ts",
+            "          foo.d.ts",
+            "          foo.test.ts",
+            "          foo.ts  <== selected",
+        ],
+        "Disabling file nesting should show all files in their regular order"
+    );
+}
+
+#[gpui::test]
+async fn test_file_nesting_operations(cx: &mut gpui::TestAppContext) {
+    init_test_with_editor(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "crate": {
+                "Cargo.lock": "",
+                "Cargo.toml": "",
+            },
+            "src": {
+                "bar.ts": "",
+                "foo.d.ts": "",
+                "foo.test.ts": "",
+                "foo.ts": "",
+            },
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    enable_file_nesting(true, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    toggle_expand_dir(&panel, "root/src", cx);
+    select_path(&panel, "root/src/foo.ts", cx);
+    panel.update_in(cx, |panel, window, cx| panel.rename(&Rename, window, cx));
+    panel
+        .update_in(cx, |panel, window, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("baz.ts", window, cx));
+            panel.confirm_edit(window, cx).unwrap()
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    toggle_expand_dir(&panel, "root/src/baz.ts", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    > crate",
+            "    v src",
+            "          bar.ts",
+            "        v baz.ts  <== selected",
+            "              baz.d.ts",
+            "              baz.test.ts",
+        ],
+        "Renaming a nest parent should rename the files nested under it"
+    );
+
+    toggle_expand_dir(&panel, "root/src/baz.ts", cx);
+    submit_deletion(&panel, cx);
+    for path in [
+        "root/src/baz.ts",
+        "root/src/baz.d.ts",
+        "root/src/baz.test.ts",
+    ] {
+        assert!(
+            find_project_entry(&panel, path, cx).is_none(),
+            "Deleting a nest parent should delete {path} too"
+        );
+    }
+    assert!(find_project_entry(&panel, "root/src/bar.ts", cx).is_some());
+
+    cx.update(|_, cx| {
+        let settings = ProjectPanelSettings::get_global(cx).clone();
+        ProjectPanelSettings::override_global(
+            ProjectPanelSettings {
+                file_nesting: FileNestingSettings {
+                    include_nested_files: false,
+                    ..settings.file_nesting.clone()
+                },
+                ..settings
+            },
+            cx,
+        );
+    });
+    toggle_expand_dir(&panel, "root/crate", cx);
+    select_path(&panel, "root/crate/Cargo.toml", cx);
+    submit_deletion(&panel, cx);
+    assert!(find_project_entry(&panel, "root/crate/Cargo.toml", cx).is_none());
+    assert!(
+        find_project_entry(&panel, "root/crate/Cargo.lock", cx).is_some(),
+        "Nested files should be left alone when include_nested_files is disabled"
+    );
+}
+
+#[gpui::test]
+async fn test_filter(cx: &mut gpui::TestAppContext) {
+    init_test_with_editor(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "crates": {
+                "editor": {
+                    "src": {
+                        "editor.rs": "",
+                        "element.rs": "",
+                    },
+                },
+                "project": {
+                    "src": {
+                        "project.rs": "",
+                    },
+                },
+            },
+            "docs": {
+                "editing.md": "",
+            },
+            "README.md": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel.filter(&Filter, window, cx);
+        panel
+            .filter_editor
+            .update(cx, |editor, cx| editor.set_text("edit", window, cx));
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &["v root", "    > crates", "    > docs", "      README.md"],
+        "Entries should be filtered only once the query stops changing"
+    );
+    cx.executor().advance_clock(FILTER_DEBOUNCE);
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v crates",
+            "        v editor  <== selected",
+            "            v src",
+            "                  editor.rs",
+            "    v docs",
+            "          editing.md",
+        ],
+        "Filtering should show the matching entries of collapsed directories, along with their ancestors"
+    );
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel
+            .filter_editor
+            .update(cx, |editor, cx| editor.set_text("projrs", window, cx));
+    });
+    cx.executor().advance_clock(FILTER_DEBOUNCE);
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v crates",
+            "        v project",
+            "            v src",
+            "                  project.rs  <== selected",
+        ],
+        "Filtering should match file names fuzzily"
+    );
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel
+            .filter_editor
+            .update(cx, |editor, cx| editor.set_text("nothing", window, cx));
+    });
+    cx.executor().advance_clock(FILTER_DEBOUNCE);
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &["v root"],
+        "The worktree root should stay visible when nothing matches"
+    );
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel
+            .filter_editor
+            .update(cx, |editor, cx| editor.set_text("element", window, cx));
+    });
+    cx.executor().advance_clock(FILTER_DEBOUNCE);
+    cx.run_until_parked();
+    panel.update_in(cx, |panel, window, cx| {
+        panel.clear_filter(&ClearFilter, window, cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v crates",
+            "        v editor",
+            "            v src",
+            "                  editor.rs",
+            "                  element.rs  <== selected",
+            "        > project",
+            "    > docs",
+            "      README.md",
+        ],
+        "Clearing the filter should reveal the selected entry in the unfiltered tree"
+    );
+}
+
+#[gpui::test]
+async fn test_sort_order(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "dir10": {},
+            "dir2": {},
+            "a.rs": "",
+            "b.md": "",
+            "file10.txt": "",
+            "file2.txt": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    > dir2",
+            "    > dir10",
+            "      a.rs",
+            "      b.md",
+            "      file2.txt",
+            "      file10.txt",
+        ],
+        "Directories should be shown first, with numbers in names compared by their value"
+    );
+
+    set_sort_order(ProjectPanelSortMode::Mixed, ProjectPanelSortBy::Natural, cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "      a.rs",
+            "      b.md",
+            "    > dir2",
+            "    > dir10",
+            "      file2.txt",
+            "      file10.txt",
+        ],
+    );
+
+    set_sort_order(
+        ProjectPanelSortMode::FilesFirst,
+        ProjectPanelSortBy::Alphabetical,
+        cx,
+    );
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "      a.rs",
+            "      b.md",
+            "      file10.txt",
+            "      file2.txt",
+            "    > dir10",
+            "    > dir2",
+        ],
+    );
+
+    set_sort_order(
+        ProjectPanelSortMode::DirectoriesFirst,
+        ProjectPanelSortBy::FileType,
+        cx,
+    );
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    > dir2",
+            "    > dir10",
+            "      b.md",
+            "      a.rs",
+            "      file2.txt",
+            "      file10.txt",
+        ],
+    );
+}
+
+#[gpui::test]
+async fn test_sort_by_modified(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "src": {
+                "a.rs": "",
+                "b.rs": "",
+            },
+        }),
+    )
+    .await;
+    fs.insert_file("/root/src/c.rs", Vec::new()).await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    set_sort_order(
+        ProjectPanelSortMode::DirectoriesFirst,
+        ProjectPanelSortBy::Modified,
+        cx,
+    );
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    toggle_expand_dir(&panel, "root/src", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..50, cx),
+        &[
+            "v root",
+            "    v src  <== selected",
+            "          c.rs",
+            "          b.rs",
+            "          a.rs",
+        ],
+        "Most recently modified entries should be shown first"
+    );
+}
+
+#[gpui::test]
+async fn test_rename_in_buffer(cx: &mut gpui::TestAppContext) {
+    init_test_with_editor(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "src": {
+                "a.rs": "a",
+                "b.rs": "b",
+                "c.rs": "c",
+            },
+            "d.rs": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    toggle_expand_dir(&panel, "root/src", cx);
+    select_path(&panel, "root/src/a.rs", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        panel.rename_in_buffer(&RenameInBuffer, window, cx)
+    });
+    cx.run_until_parked();
+
+    let (item, editor) = workspace
+        .update(cx, |workspace, _, cx| {
+            let item = workspace.active_item(cx).unwrap();
+            let editor = item.act_as::<Editor>(cx).unwrap();
+            (item, editor)
+        })
+        .unwrap();
+    assert_eq!(
+        editor.update(cx, |editor, cx| editor.text(cx)),
+        "1 a.rs\n2 b.rs\n3 c.rs\n",
+        "Should list the children of the selected file's directory"
+    );
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.set_text("2 main.rs\n3 util/c.rs\nlib.rs\nmod/\n", window, cx)
+    });
+    assert!(cx.update(|_, cx| item.is_dirty(cx)));
+
+    let save_task = workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.save_active_item(workspace::SaveIntent::Save, window, cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    assert!(
+        cx.has_pending_prompt(),
+        "Should ask for confirmation before applying the changes"
+    );
+    cx.simulate_prompt_answer("Apply");
+    save_task.await.unwrap();
+    cx.run_until_parked();
+
+    assert!(find_project_entry(&panel, "root/src/a.rs", cx).is_none());
+    assert!(find_project_entry(&panel, "root/src/b.rs", cx).is_none());
+    assert!(find_project_entry(&panel, "root/src/c.rs", cx).is_none());
+    assert!(find_project_entry(&panel, "root/src/main.rs", cx).is_some());
+    assert!(find_project_entry(&panel, "root/src/util/c.rs", cx).is_some());
+    assert!(find_project_entry(&panel, "root/src/lib.rs", cx).is_some());
+    assert!(find_project_entry(&panel, "root/src/mod", cx).is_some());
+    assert!(find_project_entry(&panel, "root/d.rs", cx).is_some());
+    assert!(!cx.update(|_, cx| item.is_dirty(cx)));
+    assert_eq!(
+        editor.update(cx, |editor, cx| editor.text(cx)),
+        "1 lib.rs\n2 main.rs\n3 mod/\n4 util/\n",
+        "Should list the directory again after applying the changes"
+    );
+
+    // Swapping names goes through temporary names.
+    editor.update_in(cx, |editor, window, cx| {
+        editor.set_text("1 main.rs\n2 lib.rs\n3 mod/\n4 util/\n", window, cx)
+    });
+    let save_task = workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.save_active_item(workspace::SaveIntent::Save, window, cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    cx.simulate_prompt_answer("Apply");
+    save_task.await.unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        fs.load(path!("/root/src/lib.rs").as_ref()).await.unwrap(),
+        "b"
+    );
+    assert_eq!(
+        fs.load(path!("/root/src/main.rs").as_ref()).await.unwrap(),
+        ""
+    );
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.set_text(
+            "1 lib.rs\n2 main.rs\n3 mod/\n4 util/\n../d.rs\n",
+            window,
+            cx,
+        )
+    });
+    let save_task = workspace
+        .update(cx, |workspace, window, cx| {
+            workspace.save_active_item(workspace::SaveIntent::Save, window, cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    assert!(
+        !cx.has_pending_prompt(),
+        "Should not ask for confirmation when the listing is invalid"
+    );
+    assert!(save_task.await.is_err());
+}
+
 #[gpui::test]
 async fn test_compare_selected_files(cx: &mut gpui::TestAppContext) {
     init_test_with_editor(cx);
@@ -6086,7 +6711,7 @@ fn visible_entries_as_strings(
             }
 
             let indent = "    ".repeat(details.depth);
-            let icon = if details.kind.is_dir() {
+            let icon = if details.kind.is_dir() || details.is_nest_parent {
                 if details.is_expanded { "v " } else { "> " }
             } else {
                 "  "
@@ -6194,6 +6819,41 @@ fn ensure_single_file_is_opened(
         .unwrap();
 }
 
+fn enable_file_nesting(enabled: bool, cx: &mut VisualTestContext) {
+    cx.update(|_, cx| {
+        let settings = ProjectPanelSettings::get_global(cx).clone();
+        ProjectPanelSettings::override_global(
+            ProjectPanelSettings {
+                file_nesting: FileNestingSettings {
+                    enabled,
+                    ..settings.file_nesting.clone()
+                },
+                ..settings
+            },
+            cx,
+        );
+    });
+}
+
+fn set_sort_order(
+    sort_mode: ProjectPanelSortMode,
+    sort_by: ProjectPanelSortBy,
+    cx: &mut VisualTestContext,
+) {
+    cx.update(|_, cx| {
+        let settings = ProjectPanelSettings::get_global(cx).clone();
+        ProjectPanelSettings::override_global(
+            ProjectPanelSettings {
+                sort_mode,
+                sort_by,
+                ..settings
+            },
+            cx,
+        );
+    });
+    cx.run_until_parked();
+}
+
 fn submit_deletion(panel: &Entity<ProjectPanel>, cx: &mut VisualTestContext) {
     assert!(
         !cx.has_pending_prompt(),
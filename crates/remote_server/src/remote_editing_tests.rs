@@ -13,8 +13,10 @@ use fs::{FakeFs, Fs};
 use gpui::{AppContext as _, Entity, SemanticVersion, TestAppContext};
 use http_client::{BlockedHttpClient, FakeHttpClient};
 use language::{
-    Buffer, FakeLspAdapter, LanguageConfig, LanguageMatcher, LanguageRegistry, LineEnding,
+    Buffer, FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, LanguageRegistry,
+    LineEnding,
     language_settings::{AllLanguageSettings, language_settings},
+    tree_sitter_rust,
 };
 use lsp::{CompletionContext, CompletionResponse, CompletionTriggerKind, LanguageServerName};
 use node_runtime::NodeRuntime;
@@ -246,6 +248,95 @@ async fn test_remote_project_search(cx: &mut TestAppContext, server_cx: &mut Tes
     do_search(&project, cx.clone()).await;
 }
 
+#[gpui::test]
+async fn test_remote_structural_search(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        path!("/code"),
+        json!({
+            "project1": {
+                "README.md": "Call parse(x).unwrap() to parse x.",
+                "src": {
+                    "lib.rs": "fn one() -> usize {\n    parse(x).unwrap()\n}\n",
+                    "main.rs": "fn main() {\n    value.unwrap();\n}\n",
+                    "other.rs": "fn other() {}\n",
+                }
+            },
+        }),
+    )
+    .await;
+
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    project.update(cx, |project, _| {
+        project.languages().add(Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".into()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )));
+    });
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    for path in ["src/lib.rs", "src/main.rs"] {
+        project
+            .update(cx, |project, cx| {
+                project.open_buffer((worktree_id, Path::new(path)), cx)
+            })
+            .await
+            .unwrap();
+    }
+    cx.run_until_parked();
+
+    // The server only returns the files containing the longest literal word of the pattern,
+    // and the client matches their syntax trees.
+    let receiver = project.update(cx, |project, cx| {
+        project.search(
+            SearchQuery::structural(
+                "$F($$$ARGS).unwrap()",
+                false,
+                Default::default(),
+                Default::default(),
+                false,
+                None,
+            )
+            .unwrap(),
+            cx,
+        )
+    });
+    let mut results = Vec::new();
+    while let Ok(result) = receiver.recv().await {
+        let SearchResult::Buffer { buffer, ranges } = result else {
+            panic!("incorrect result");
+        };
+        buffer.read_with(cx, |buffer, cx| {
+            let path = buffer.file().unwrap().full_path(cx);
+            for range in ranges {
+                results.push((
+                    path.clone(),
+                    buffer.text_for_range(range).collect::<String>(),
+                ));
+            }
+        });
+    }
+    assert_eq!(
+        results,
+        vec![(
+            PathBuf::from(path!("project1/src/lib.rs")),
+            "parse(x).unwrap()".to_string()
+        )]
+    );
+}
+
 #[gpui::test]
 async fn test_remote_settings(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
     let fs = FakeFs::new(server_cx.executor());
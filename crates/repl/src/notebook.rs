@@ -1,4 +1,6 @@
 mod cell;
+mod document;
 mod notebook_ui;
 pub use cell::*;
+pub use document::*;
 pub use notebook_ui::*;
@@ -2,21 +2,24 @@
 use std::sync::Arc;
 
 use editor::{Editor, EditorMode, MultiBuffer};
-use futures::future::Shared;
+use futures::{FutureExt as _, future::Shared};
 use gpui::{
-    App, Entity, Hsla, RetainAllImageCache, Task, TextStyleRefinement, image_cache, prelude::*,
+    App, ClickEvent, Entity, EntityId, EventEmitter, Hsla, RetainAllImageCache, Subscription, Task,
+    TextStyleRefinement, image_cache, prelude::*,
 };
-use language::{Buffer, Language, LanguageRegistry};
+use language::{Buffer, BufferEvent, Language, LanguageRegistry};
 use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
-use nbformat::v4::{CellId, CellMetadata, CellType};
+use nbformat::v4::{CellId, CellType};
+use serde_json::{Map, Value};
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::{IconButtonShape, prelude::*};
 use util::ResultExt;
+use uuid::Uuid;
 
 use crate::{
-    notebook::{CODE_BLOCK_INSET, GUTTER_WIDTH},
-    outputs::{Output, plain::TerminalOutput, user_error::ErrorView},
+    notebook::{CODE_BLOCK_INSET, CellSnapshot, GUTTER_WIDTH},
+    outputs::{ExecutionView, Output, plain::TerminalOutput, user_error::ErrorView},
 };
 
 #[derive(Copy, Clone, PartialEq, PartialOrd)]
@@ -77,6 +80,14 @@ impl Clickable for CellControl {
     }
 }
 
+/// Events a cell emits to the notebook containing it.
+pub enum CellEvent {
+    /// The cell's run button was clicked.
+    Run,
+    /// The cell's source or outputs changed.
+    Edited,
+}
+
 /// A notebook cell
 #[derive(Clone)]
 pub enum Cell {
@@ -85,13 +96,10 @@ pub enum Cell {
     Raw(Entity<RawCell>),
 }
 
-fn convert_outputs(
-    outputs: &Vec<nbformat::v4::Output>,
-    window: &mut Window,
-    cx: &mut App,
-) -> Vec<Output> {
+fn convert_outputs(outputs: &[Value], window: &mut Window, cx: &mut App) -> Vec<Output> {
     outputs
         .iter()
+        .filter_map(|output| serde_json::from_value(output.clone()).log_err())
         .map(|output| match output {
             nbformat::v4::Output::Stream { text, .. } => Output::Stream {
                 content: cx.new(|cx| TerminalOutput::from(&text.0, window, cx)),
@@ -112,133 +120,223 @@ fn convert_outputs(
         .collect()
 }
 
+/// Parses a cell's ID. Cells from notebooks older than nbformat 4.5 don't have one, so they are
+/// given a new ID that is only used within Zed.
+pub fn cell_id(id: Option<&str>) -> CellId {
+    id.and_then(|id| serde_json::from_value(Value::from(id)).ok())
+        .unwrap_or_else(new_cell_id)
+}
+
+pub fn new_cell_id() -> CellId {
+    serde_json::from_value(Value::from(Uuid::new_v4().to_string()))
+        .expect("UUIDs are valid cell IDs")
+}
+
+/// Creates the editor for a cell's source, emitting [`CellEvent::Edited`] when it changes.
+fn cell_editor<T: EventEmitter<CellEvent>>(
+    source: &str,
+    language: Option<Shared<Task<Option<Arc<Language>>>>>,
+    window: &mut Window,
+    cx: &mut Context<T>,
+) -> (Entity<Editor>, Task<()>, Subscription) {
+    let buffer = cx.new(|cx| Buffer::local(source, cx));
+    let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+
+    let editor = cx.new(|cx| {
+        let mut editor = Editor::new(
+            EditorMode::AutoHeight {
+                min_lines: 1,
+                max_lines: Some(1024),
+            },
+            multi_buffer,
+            None,
+            window,
+            cx,
+        );
+
+        let theme = ThemeSettings::get_global(cx);
+
+        let refinement = TextStyleRefinement {
+            font_family: Some(theme.buffer_font.family.clone()),
+            font_size: Some(theme.buffer_font_size(cx).into()),
+            color: Some(cx.theme().colors().editor_foreground),
+            background_color: Some(gpui::transparent_black()),
+            ..Default::default()
+        };
+
+        editor.set_show_gutter(false, cx);
+        editor.set_text_style_refinement(refinement);
+        editor
+    });
+
+    let buffer_subscription = cx.subscribe(&buffer, |_, _, event, cx| {
+        if matches!(event, BufferEvent::Edited | BufferEvent::DirtyChanged) {
+            cx.emit(CellEvent::Edited);
+        }
+    });
+
+    let language_task = cx.spawn(async move |_, cx| {
+        if let Some(language) = language {
+            let language = language.await;
+            buffer
+                .update(cx, |buffer, cx| buffer.set_language(language, cx))
+                .ok();
+        }
+    });
+
+    (editor, language_task, buffer_subscription)
+}
+
+fn render_cell_editor(editor: &Entity<Editor>, cx: &App) -> Div {
+    div().py_1p5().w_full().child(
+        div()
+            .flex()
+            .size_full()
+            .flex_1()
+            .py_3()
+            .px_5()
+            .rounded_lg()
+            .border_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_background)
+            .child(div().w_full().child(editor.clone())),
+    )
+}
+
 impl Cell {
     pub fn load(
-        cell: &nbformat::v4::Cell,
+        id: CellId,
+        snapshot: CellSnapshot,
         languages: &Arc<LanguageRegistry>,
         notebook_language: Shared<Task<Option<Arc<Language>>>>,
         window: &mut Window,
         cx: &mut App,
     ) -> Self {
-        match cell {
-            nbformat::v4::Cell::Markdown {
-                id,
-                metadata,
-                source,
-                ..
-            } => {
-                let source = source.join("");
-
-                let entity = cx.new(|cx| {
-                    let markdown_parsing_task = {
-                        let languages = languages.clone();
-                        let source = source.clone();
-
-                        cx.spawn_in(window, async move |this, cx| {
-                            let parsed_markdown = cx
-                                .background_spawn(async move {
-                                    parse_markdown(&source, None, Some(languages)).await
-                                })
-                                .await;
-
-                            this.update(cx, |cell: &mut MarkdownCell, _| {
-                                cell.parsed_markdown = Some(parsed_markdown);
-                            })
-                            .log_err();
-                        })
-                    };
-
-                    MarkdownCell {
-                        markdown_parsing_task,
-                        image_cache: RetainAllImageCache::new(cx),
-                        languages: languages.clone(),
-                        id: id.clone(),
-                        metadata: metadata.clone(),
-                        source: source.clone(),
-                        parsed_markdown: None,
-                        selected: false,
-                        cell_position: None,
-                    }
-                });
-
-                Cell::Markdown(entity)
-            }
-            nbformat::v4::Cell::Code {
-                id,
-                metadata,
-                execution_count,
-                source,
-                outputs,
-            } => Cell::Code(cx.new(|cx| {
-                let text = source.join("");
-
-                let buffer = cx.new(|cx| Buffer::local(text.clone(), cx));
-                let multi_buffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
-
-                let editor_view = cx.new(|cx| {
-                    let mut editor = Editor::new(
-                        EditorMode::AutoHeight {
-                            min_lines: 1,
-                            max_lines: Some(1024),
-                        },
-                        multi_buffer,
-                        None,
-                        window,
-                        cx,
-                    );
-
-                    let theme = ThemeSettings::get_global(cx);
-
-                    let refinement = TextStyleRefinement {
-                        font_family: Some(theme.buffer_font.family.clone()),
-                        font_size: Some(theme.buffer_font_size(cx).into()),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        background_color: Some(gpui::transparent_black()),
-                        ..Default::default()
-                    };
-
-                    editor.set_text(text, window, cx);
-                    editor.set_show_gutter(false, cx);
-                    editor.set_text_style_refinement(refinement);
-
-                    // editor.set_read_only(true);
-                    editor
-                });
-
-                let buffer = buffer.clone();
-                let language_task = cx.spawn_in(window, async move |this, cx| {
-                    let language = notebook_language.await;
-
-                    buffer.update(cx, |buffer, cx| {
-                        buffer.set_language(language.clone(), cx);
-                    });
-                });
+        let CellSnapshot {
+            raw,
+            cell_type,
+            source,
+            execution_count,
+            outputs,
+            ..
+        } = snapshot;
+
+        match cell_type {
+            CellType::Markdown => Cell::Markdown(
+                cx.new(|cx| MarkdownCell::new(id, raw, &source, languages.clone(), window, cx)),
+            ),
+            CellType::Code => Cell::Code(cx.new(|cx| {
+                let (editor, language_task, buffer_subscription) =
+                    cell_editor(&source, Some(notebook_language), window, cx);
 
                 CodeCell {
-                    id: id.clone(),
-                    metadata: metadata.clone(),
-                    execution_count: *execution_count,
-                    source: source.join(""),
-                    editor: editor_view,
-                    outputs: convert_outputs(outputs, window, cx),
+                    id,
+                    raw,
+                    execution_count,
+                    editor,
+                    outputs: convert_outputs(&outputs, window, cx),
+                    notebook_outputs: outputs,
+                    outputs_edited: false,
+                    execution: None,
                     selected: false,
                     language_task,
                     cell_position: None,
+                    _buffer_subscription: buffer_subscription,
+                    _execution_subscription: None,
                 }
             })),
-            nbformat::v4::Cell::Raw {
-                id,
-                metadata,
-                source,
-            } => Cell::Raw(cx.new(|_| RawCell {
-                id: id.clone(),
-                metadata: metadata.clone(),
-                source: source.join(""),
-                selected: false,
-                cell_position: None,
+            CellType::Raw => Cell::Raw(cx.new(|cx| {
+                let (editor, language_task, buffer_subscription) =
+                    cell_editor(&source, None, window, cx);
+
+                RawCell {
+                    id,
+                    raw,
+                    editor,
+                    selected: false,
+                    cell_position: None,
+                    language_task,
+                    _buffer_subscription: buffer_subscription,
+                }
             })),
         }
     }
+
+    pub fn entity_id(&self) -> EntityId {
+        match self {
+            Cell::Code(cell) => cell.entity_id(),
+            Cell::Markdown(cell) => cell.entity_id(),
+            Cell::Raw(cell) => cell.entity_id(),
+        }
+    }
+
+    pub fn id(&self, cx: &App) -> CellId {
+        match self {
+            Cell::Code(cell) => cell.read(cx).id().clone(),
+            Cell::Markdown(cell) => cell.read(cx).id().clone(),
+            Cell::Raw(cell) => cell.read(cx).id().clone(),
+        }
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        match self {
+            Cell::Code(_) => CellType::Code,
+            Cell::Markdown(_) => CellType::Markdown,
+            Cell::Raw(_) => CellType::Raw,
+        }
+    }
+
+    pub fn editor(&self, cx: &App) -> Entity<Editor> {
+        match self {
+            Cell::Code(cell) => cell.read(cx).editor().clone(),
+            Cell::Markdown(cell) => cell.read(cx).editor().clone(),
+            Cell::Raw(cell) => cell.read(cx).editor().clone(),
+        }
+    }
+
+    pub fn source(&self, cx: &App) -> String {
+        self.editor(cx).read(cx).text(cx)
+    }
+
+    /// The cell's current contents, as they would be saved.
+    pub fn snapshot(&self, cx: &App) -> CellSnapshot {
+        let mut snapshot = CellSnapshot::new(
+            self.cell_type(),
+            Some(self.id(cx).to_string()),
+            self.source(cx),
+        );
+        match self {
+            Cell::Code(cell) => {
+                let cell = cell.read(cx);
+                snapshot.raw = cell.raw.clone();
+                snapshot.execution_count = cell.execution_count;
+                snapshot.outputs = cell.notebook_outputs.clone();
+            }
+            Cell::Markdown(cell) => snapshot.raw = cell.read(cx).raw.clone(),
+            Cell::Raw(cell) => snapshot.raw = cell.read(cx).raw.clone(),
+        }
+        snapshot
+    }
+
+    pub fn is_dirty(&self, cx: &App) -> bool {
+        let outputs_edited = match self {
+            Cell::Code(cell) => cell.read(cx).outputs_edited,
+            Cell::Markdown(_) | Cell::Raw(_) => false,
+        };
+        outputs_edited || self.editor(cx).read(cx).buffer().read(cx).is_dirty(cx)
+    }
+
+    pub fn did_save(&self, cx: &mut App) {
+        if let Some(buffer) = self.editor(cx).read(cx).buffer().read(cx).as_singleton() {
+            buffer.update(cx, |buffer, cx| buffer.did_save(buffer.version(), None, cx));
+        }
+        if let Cell::Code(cell) = self {
+            cell.update(cx, |cell, cx| {
+                cell.outputs_edited = false;
+                cx.notify();
+            });
+        }
+    }
 }
 
 pub trait RenderableCell: Render {
@@ -246,8 +344,10 @@ pub trait RenderableCell: Render {
 
     fn id(&self) -> &CellId;
     fn cell_type(&self) -> CellType;
-    fn metadata(&self) -> &CellMetadata;
-    fn source(&self) -> &String;
+    fn editor(&self) -> &Entity<Editor>;
+    fn source(&self, cx: &App) -> String {
+        self.editor().read(cx).text(cx)
+    }
     fn selected(&self) -> bool;
     fn set_selected(&mut self, selected: bool) -> &mut Self;
     fn selected_bg_color(&self, window: &mut Window, cx: &mut Context<Self>) -> Hsla {
@@ -332,16 +432,98 @@ pub trait RunnableCell: RenderableCell {
     fn run(&mut self, window: &mut Window, cx: &mut Context<Self>) -> ();
 }
 
+impl EventEmitter<CellEvent> for MarkdownCell {}
+impl EventEmitter<CellEvent> for CodeCell {}
+impl EventEmitter<CellEvent> for RawCell {}
+
 pub struct MarkdownCell {
     id: CellId,
-    metadata: CellMetadata,
+    raw: Map<String, Value>,
     image_cache: Entity<RetainAllImageCache>,
-    source: String,
+    editor: Entity<Editor>,
+    editing: bool,
     parsed_markdown: Option<markdown_preview::markdown_elements::ParsedMarkdown>,
     markdown_parsing_task: Task<()>,
     selected: bool,
     cell_position: Option<CellPosition>,
     languages: Arc<LanguageRegistry>,
+    language_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl MarkdownCell {
+    fn new(
+        id: CellId,
+        raw: Map<String, Value>,
+        source: &str,
+        languages: Arc<LanguageRegistry>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let markdown_language = cx
+            .spawn({
+                let languages = languages.clone();
+                async move |_, _| languages.language_for_name("Markdown").await.ok()
+            })
+            .shared();
+        let (editor, language_task, buffer_subscription) =
+            cell_editor(source, Some(markdown_language), window, cx);
+
+        // Leaving the editor shows the rendered markdown again.
+        let blur_subscription = cx.on_blur(&editor.focus_handle(cx), window, |this, _, cx| {
+            this.stop_editing(cx);
+        });
+
+        let mut this = Self {
+            id,
+            raw,
+            image_cache: RetainAllImageCache::new(cx),
+            editor,
+            editing: source.is_empty(),
+            parsed_markdown: None,
+            markdown_parsing_task: Task::ready(()),
+            selected: false,
+            cell_position: None,
+            languages,
+            language_task,
+            _subscriptions: vec![buffer_subscription, blur_subscription],
+        };
+        this.parse_markdown(cx);
+        this
+    }
+
+    pub fn start_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editing = true;
+        window.focus(&self.editor.focus_handle(cx));
+        cx.notify();
+    }
+
+    pub fn stop_editing(&mut self, cx: &mut Context<Self>) {
+        if self.editing {
+            self.editing = false;
+            self.parse_markdown(cx);
+            cx.notify();
+        }
+    }
+
+    fn parse_markdown(&mut self, cx: &mut Context<Self>) {
+        let source = self.source(cx);
+        let languages = self.languages.clone();
+
+        self.markdown_parsing_task = cx.spawn(async move |this, cx| {
+            let parsed_markdown = cx
+                .background_spawn(
+                    async move { parse_markdown(&source, None, Some(languages)).await },
+                )
+                .await;
+
+            this.update(cx, |cell, cx| {
+                cell.parsed_markdown = Some(parsed_markdown);
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
 }
 
 impl RenderableCell for MarkdownCell {
@@ -355,12 +537,8 @@ impl RenderableCell for MarkdownCell {
         CellType::Markdown
     }
 
-    fn metadata(&self) -> &CellMetadata {
-        &self.metadata
-    }
-
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn selected(&self) -> bool {
@@ -388,13 +566,43 @@ impl RenderableCell for MarkdownCell {
 
 impl Render for MarkdownCell {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let Some(parsed) = self.parsed_markdown.as_ref() else {
-            return div();
+        let content = if self.editing {
+            render_cell_editor(&self.editor, cx).into_any_element()
+        } else {
+            let mut markdown_render_context =
+                markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
+            let children = self
+                .parsed_markdown
+                .as_ref()
+                .map(|parsed| parsed.children.as_slice())
+                .unwrap_or_default();
+
+            v_flex()
+                .image_cache(self.image_cache.clone())
+                .id("markdown-preview")
+                .size_full()
+                .flex_1()
+                .p_3()
+                .font_ui(cx)
+                .text_size(TextSize::Default.rems(cx))
+                .on_click(cx.listener(|this, event: &ClickEvent, window, cx| {
+                    if event.click_count() > 1 {
+                        this.start_editing(window, cx);
+                    }
+                }))
+                .when(children.is_empty(), |this| {
+                    this.child(Label::new("Double-click to edit").color(Color::Muted))
+                })
+                .children(children.iter().map(|child| {
+                    div().relative().child(
+                        div()
+                            .relative()
+                            .child(render_markdown_block(child, &mut markdown_render_context)),
+                    )
+                }))
+                .into_any_element()
         };
 
-        let mut markdown_render_context =
-            markdown_preview::markdown_renderer::RenderContext::new(None, window, cx);
-
         v_flex()
             .size_full()
             // TODO: Move base cell render into trait impl so we don't have to repeat this
@@ -408,20 +616,7 @@ impl Render for MarkdownCell {
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .bg(self.selected_bg_color(window, cx))
                     .child(self.gutter(window, cx))
-                    .child(
-                        v_flex()
-                            .image_cache(self.image_cache.clone())
-                            .size_full()
-                            .flex_1()
-                            .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .children(parsed.children.iter().map(|child| {
-                                div().relative().child(div().relative().child(
-                                    render_markdown_block(child, &mut markdown_render_context),
-                                ))
-                            })),
-                    ),
+                    .child(content),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, window, cx))
@@ -430,26 +625,59 @@ impl Render for MarkdownCell {
 
 pub struct CodeCell {
     id: CellId,
-    metadata: CellMetadata,
+    raw: Map<String, Value>,
     execution_count: Option<i32>,
-    source: String,
     editor: Entity<editor::Editor>,
     outputs: Vec<Output>,
+    /// The outputs as they are stored in the notebook file.
+    notebook_outputs: Vec<Value>,
+    outputs_edited: bool,
+    /// The most recent run of this cell, which replaces the outputs loaded from the file.
+    execution: Option<Entity<ExecutionView>>,
     selected: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
+    _buffer_subscription: Subscription,
+    _execution_subscription: Option<Subscription>,
 }
 
 impl CodeCell {
-    pub fn is_dirty(&self, cx: &App) -> bool {
-        self.editor.read(cx).buffer().read(cx).is_dirty(cx)
-    }
     pub fn has_outputs(&self) -> bool {
-        !self.outputs.is_empty()
+        !self.outputs.is_empty() || self.execution.is_some()
+    }
+
+    pub fn clear_outputs(&mut self, cx: &mut Context<Self>) {
+        if !self.has_outputs() {
+            return;
+        }
+        self.outputs.clear();
+        self.notebook_outputs.clear();
+        self.execution = None;
+        self._execution_subscription = None;
+        self.outputs_edited = true;
+        cx.emit(CellEvent::Edited);
+        cx.notify();
     }
 
-    pub fn clear_outputs(&mut self) {
+    /// Shows the outputs of a new run of this cell, recording them in the notebook as they
+    /// arrive.
+    pub fn start_execution(&mut self, execution: Entity<ExecutionView>, cx: &mut Context<Self>) {
         self.outputs.clear();
+        self.notebook_outputs.clear();
+        self.outputs_edited = true;
+        self._execution_subscription = Some(cx.observe(&execution, |this, execution, cx| {
+            let execution = execution.read(cx);
+            this.notebook_outputs = execution.notebook_outputs.clone();
+            if let Some(execution_count) = execution.execution_count {
+                this.execution_count = Some(execution_count);
+            }
+            this.outputs_edited = true;
+            cx.emit(CellEvent::Edited);
+            cx.notify();
+        }));
+        self.execution = Some(execution);
+        cx.emit(CellEvent::Edited);
+        cx.notify();
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -513,12 +741,8 @@ impl RenderableCell for CodeCell {
         CellType::Code
     }
 
-    fn metadata(&self) -> &CellMetadata {
-        &self.metadata
-    }
-
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn control(&self, window: &mut Window, cx: &mut Context<Self>) -> Option<CellControl> {
@@ -526,8 +750,8 @@ impl RenderableCell for CodeCell {
             CellControl::new("rerun-cell", CellControlType::RerunCell)
         } else {
             CellControl::new("run-cell", CellControlType::RunCell)
-                .on_click(cx.listener(move |this, _, window, cx| this.run(window, cx)))
-        };
+        }
+        .on_click(cx.listener(move |this, _, window, cx| this.run(window, cx)));
 
         Some(cell_control)
     }
@@ -552,8 +776,9 @@ impl RenderableCell for CodeCell {
 }
 
 impl RunnableCell for CodeCell {
-    fn run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Running code cell: {}", self.id);
+    fn run(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        // Kernels belong to the notebook, so it runs the cell on our behalf.
+        cx.emit(CellEvent::Run);
     }
 
     fn execution_count(&self) -> Option<i32> {
@@ -583,21 +808,7 @@ impl Render for CodeCell {
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .bg(self.selected_bg_color(window, cx))
                     .child(self.gutter(window, cx))
-                    .child(
-                        div().py_1p5().w_full().child(
-                            div()
-                                .flex()
-                                .size_full()
-                                .flex_1()
-                                .py_3()
-                                .px_5()
-                                .rounded_lg()
-                                .border_1()
-                                .border_color(cx.theme().colors().border)
-                                .bg(cx.theme().colors().editor_background)
-                                .child(div().w_full().child(self.editor.clone())),
-                        ),
-                    ),
+                    .child(render_cell_editor(&self.editor, cx)),
             )
             // Output portion
             .child(
@@ -621,8 +832,8 @@ impl Render for CodeCell {
                                 .border_1()
                                 // .border_color(cx.theme().colors().border)
                                 // .bg(cx.theme().colors().editor_background)
-                                .child(div().w_full().children(self.outputs.iter().map(
-                                    |output| {
+                                .child(div().w_full().children(self.execution.clone()).children(
+                                    self.outputs.iter().map(|output| {
                                         let content = match output {
                                             Output::Plain { content, .. } => {
                                                 Some(content.clone().into_any_element())
@@ -658,8 +869,8 @@ impl Render for CodeCell {
                                             // .border_color(cx.theme().colors().border)
                                             // .shadow_xs()
                                             .children(content)
-                                    },
-                                ))),
+                                    }),
+                                )),
                         ),
                     ),
             )
@@ -670,10 +881,12 @@ impl Render for CodeCell {
 
 pub struct RawCell {
     id: CellId,
-    metadata: CellMetadata,
-    source: String,
+    raw: Map<String, Value>,
+    editor: Entity<Editor>,
     selected: bool,
     cell_position: Option<CellPosition>,
+    language_task: Task<()>,
+    _buffer_subscription: Subscription,
 }
 
 impl RenderableCell for RawCell {
@@ -687,12 +900,8 @@ impl RenderableCell for RawCell {
         CellType::Raw
     }
 
-    fn metadata(&self) -> &CellMetadata {
-        &self.metadata
-    }
-
-    fn source(&self) -> &String {
-        &self.source
+    fn editor(&self) -> &Entity<Editor> {
+        &self.editor
     }
 
     fn selected(&self) -> bool {
@@ -729,16 +938,7 @@ impl Render for RawCell {
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .bg(self.selected_bg_color(window, cx))
                     .child(self.gutter(window, cx))
-                    .child(
-                        div()
-                            .flex()
-                            .size_full()
-                            .flex_1()
-                            .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            .child(self.source.clone()),
-                    ),
+                    .child(render_cell_editor(&self.editor, cx)),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, window, cx))
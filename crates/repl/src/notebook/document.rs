@@ -0,0 +1,546 @@
+//! Reading and writing `.ipynb` files.
+//!
+//! Notebooks are kept as the JSON they were read from, and saving only rewrites the parts of a
+//! cell that were edited in Zed. Metadata and fields that nbformat doesn't know about survive a
+//! save untouched, and a notebook without changes is written back byte-for-byte.
+
+use anyhow::{Context as _, Result};
+use nbformat::v4::CellType;
+use runtimelib::JupyterMessageContent;
+use serde::Serialize as _;
+use serde_json::{Map, Value, json, ser::PrettyFormatter};
+
+/// How the notebook's JSON was laid out on disk, so that saving produces the same formatting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct JsonStyle {
+    indent: usize,
+    trailing_newline: bool,
+}
+
+impl JsonStyle {
+    /// Jupyter writes notebooks with a single space of indentation and a trailing newline.
+    const JUPYTER: Self = Self {
+        indent: 1,
+        trailing_newline: true,
+    };
+
+    fn detect(text: &str) -> Self {
+        let indent = text
+            .lines()
+            .nth(1)
+            .map(|line| line.len() - line.trim_start_matches(' ').len())
+            .filter(|indent| *indent > 0)
+            .unwrap_or(Self::JUPYTER.indent);
+
+        Self {
+            indent,
+            trailing_newline: text.ends_with('\n'),
+        }
+    }
+}
+
+pub struct NotebookDocument {
+    root: Map<String, Value>,
+    style: JsonStyle,
+    text: String,
+}
+
+impl NotebookDocument {
+    pub fn parse(text: &str) -> Result<Self> {
+        let root = match serde_json::from_str(text).context("parsing notebook JSON")? {
+            Value::Object(root) => root,
+            _ => anyhow::bail!("notebook is not a JSON object"),
+        };
+        let cells = root
+            .get("cells")
+            .and_then(Value::as_array)
+            .context("notebook has no cells")?;
+        if let Some(ix) = cells.iter().position(|cell| !cell.is_object()) {
+            anyhow::bail!("cell {ix} of the notebook is not a JSON object");
+        }
+
+        Ok(Self {
+            root,
+            style: JsonStyle::detect(text),
+            text: text.to_string(),
+        })
+    }
+
+    /// The text the notebook was read from.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Cell IDs were introduced in nbformat 4.5. Older notebooks must not contain them.
+    pub fn supports_cell_ids(&self) -> bool {
+        let major = self.root.get("nbformat").and_then(Value::as_u64);
+        let minor = self.root.get("nbformat_minor").and_then(Value::as_u64);
+        match (major, minor) {
+            (Some(major), _) if major > 4 => true,
+            (Some(4), Some(minor)) => minor >= 5,
+            _ => false,
+        }
+    }
+
+    pub fn cells(&self) -> Vec<CellSnapshot> {
+        self.root
+            .get("cells")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_object)
+            .map(|cell| CellSnapshot::from_json(cell.clone()))
+            .collect()
+    }
+
+    /// Produces the file contents for the given cells. Anything other than the cells is written
+    /// back as it was read.
+    pub fn serialize(&self, cells: &[CellSnapshot]) -> Result<String> {
+        let write_ids = self.supports_cell_ids();
+        let mut root = self.root.clone();
+        root.insert(
+            "cells".into(),
+            Value::Array(
+                cells
+                    .iter()
+                    .map(|cell| Value::Object(cell.to_json(write_ids)))
+                    .collect(),
+            ),
+        );
+
+        if root == self.root {
+            return Ok(self.text.clone());
+        }
+
+        self.format(&root)
+    }
+
+    fn format(&self, root: &Map<String, Value>) -> Result<String> {
+        let indent = vec![b' '; self.style.indent];
+        let mut bytes = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(
+            &mut bytes,
+            PrettyFormatter::with_indent(&indent),
+        );
+        root.serialize(&mut serializer)?;
+        if self.style.trailing_newline {
+            bytes.push(b'\n');
+        }
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+/// The state of a single cell, along with the JSON object it was read from.
+#[derive(Clone, Debug)]
+pub struct CellSnapshot {
+    /// The cell as it appeared in the file, including any fields Zed doesn't edit.
+    pub raw: Map<String, Value>,
+    pub id: Option<String>,
+    pub cell_type: CellType,
+    pub source: String,
+    pub execution_count: Option<i32>,
+    /// Outputs in their nbformat JSON representation. Always empty for non-code cells.
+    pub outputs: Vec<Value>,
+}
+
+impl CellSnapshot {
+    pub fn new(cell_type: CellType, id: Option<String>, source: String) -> Self {
+        Self {
+            raw: Map::new(),
+            id,
+            cell_type,
+            source,
+            execution_count: None,
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Cells of a type nbformat doesn't define, e.g. ones added by an extension, are shown as raw
+    /// cells and saved with their original type.
+    fn from_json(raw: Map<String, Value>) -> Self {
+        let cell_type = match raw.get("cell_type").and_then(Value::as_str) {
+            Some("code") => CellType::Code,
+            Some("markdown") => CellType::Markdown,
+            _ => CellType::Raw,
+        };
+
+        Self {
+            id: raw.get("id").and_then(Value::as_str).map(str::to_string),
+            cell_type,
+            source: raw.get("source").map(multiline_text).unwrap_or_default(),
+            execution_count: raw
+                .get("execution_count")
+                .and_then(Value::as_i64)
+                .map(|count| count as i32),
+            outputs: raw
+                .get("outputs")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+            raw,
+        }
+    }
+
+    /// Whether the cell was read with a type other than the ones nbformat defines.
+    fn has_unknown_type(&self) -> bool {
+        !self.raw.is_empty()
+            && !matches!(
+                self.raw.get("cell_type").and_then(Value::as_str),
+                Some("code" | "markdown" | "raw")
+            )
+    }
+
+    fn to_json(&self, write_id: bool) -> Map<String, Value> {
+        let cell_type = cell_type_name(&self.cell_type);
+        let is_code = matches!(self.cell_type, CellType::Code);
+
+        let keeps_type = if self.has_unknown_type() {
+            matches!(self.cell_type, CellType::Raw)
+        } else {
+            self.raw.get("cell_type").and_then(Value::as_str) == Some(cell_type)
+        };
+        let mut cell = if keeps_type {
+            self.raw.clone()
+        } else {
+            // New cells, and cells whose type changed, are written with the keys in the order
+            // Jupyter uses. Only the metadata carries over from a cell of a different type.
+            let mut cell = Map::new();
+            cell.insert("cell_type".into(), cell_type.into());
+            if is_code {
+                cell.insert("execution_count".into(), Value::Null);
+            }
+            if write_id {
+                cell.insert("id".into(), Value::Null);
+            }
+            cell.insert(
+                "metadata".into(),
+                self.raw
+                    .get("metadata")
+                    .cloned()
+                    .unwrap_or_else(|| json!({})),
+            );
+            if is_code {
+                cell.insert("outputs".into(), json!([]));
+            }
+            cell.insert("source".into(), json!([]));
+            cell
+        };
+
+        if write_id && let Some(id) = &self.id {
+            cell.insert("id".into(), id.clone().into());
+        }
+
+        // The same text can be stored either as a string or as a list of lines, so only replace
+        // the source when its contents changed.
+        if cell.get("source").map(multiline_text).unwrap_or_default() != self.source {
+            cell.insert("source".into(), split_lines(&self.source));
+        }
+
+        if is_code {
+            cell.insert(
+                "execution_count".into(),
+                self.execution_count.map_or(Value::Null, Value::from),
+            );
+            cell.insert("outputs".into(), Value::Array(self.outputs.clone()));
+        }
+
+        cell
+    }
+}
+
+fn cell_type_name(cell_type: &CellType) -> &'static str {
+    match cell_type {
+        CellType::Code => "code",
+        CellType::Markdown => "markdown",
+        CellType::Raw => "raw",
+    }
+}
+
+/// Reads a string that nbformat allows to be stored either whole or as a list of lines.
+fn multiline_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Splits text into lines the way Jupyter stores it, keeping each line's newline.
+fn split_lines(text: &str) -> Value {
+    Value::Array(
+        text.split_inclusive('\n')
+            .map(|line| Value::String(line.to_string()))
+            .collect(),
+    )
+}
+
+/// Converts a kernel message into the nbformat output it produces, if any.
+pub fn output_from_message(message: &JupyterMessageContent) -> Option<Value> {
+    let output = match message {
+        JupyterMessageContent::StreamContent(stream) => {
+            let stream = serde_json::to_value(stream).ok()?;
+            json!({
+                "name": stream["name"],
+                "output_type": "stream",
+                "text": split_lines(stream["text"].as_str()?),
+            })
+        }
+        JupyterMessageContent::DisplayData(display_data) => {
+            let display_data = serde_json::to_value(display_data).ok()?;
+            json!({
+                "data": mime_bundle(&display_data["data"]),
+                "metadata": display_data["metadata"],
+                "output_type": "display_data",
+            })
+        }
+        JupyterMessageContent::ExecuteResult(result) => {
+            let result = serde_json::to_value(result).ok()?;
+            json!({
+                "data": mime_bundle(&result["data"]),
+                "execution_count": result["execution_count"],
+                "metadata": result["metadata"],
+                "output_type": "execute_result",
+            })
+        }
+        JupyterMessageContent::ErrorOutput(error) => {
+            let error = serde_json::to_value(error).ok()?;
+            json!({
+                "ename": error["ename"],
+                "evalue": error["evalue"],
+                "output_type": "error",
+                "traceback": error["traceback"],
+            })
+        }
+        _ => return None,
+    };
+    Some(output)
+}
+
+/// The execution count the kernel assigned, reported when execution starts and when it finishes.
+pub fn execution_count_from_message(message: &JupyterMessageContent) -> Option<i32> {
+    let message = match message {
+        JupyterMessageContent::ExecuteInput(input) => serde_json::to_value(input).ok()?,
+        JupyterMessageContent::ExecuteReply(reply) => serde_json::to_value(reply).ok()?,
+        _ => return None,
+    };
+    message["execution_count"]
+        .as_i64()
+        .map(|count| count as i32)
+}
+
+/// Appends an output, merging consecutive writes to the same stream like Jupyter does.
+pub fn push_output(outputs: &mut Vec<Value>, output: Value) {
+    if output["output_type"] == "stream"
+        && let Some(last) = outputs.last_mut()
+        && last["output_type"] == "stream"
+        && last["name"] == output["name"]
+    {
+        let text = multiline_text(&last["text"]) + &multiline_text(&output["text"]);
+        last["text"] = split_lines(&text);
+        return;
+    }
+    outputs.push(output);
+}
+
+/// Text-based mime types are stored as lists of lines.
+fn mime_bundle(data: &Value) -> Value {
+    let Some(data) = data.as_object() else {
+        return json!({});
+    };
+    Value::Object(
+        data.iter()
+            .map(|(mime_type, value)| {
+                let value = match value {
+                    Value::String(text)
+                        if mime_type.starts_with("text/") || mime_type == "image/svg+xml" =>
+                    {
+                        split_lines(text)
+                    }
+                    value => value.clone(),
+                };
+                (mime_type.clone(), value)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: &[(&str, &str)] = &[
+        (
+            "analysis.ipynb",
+            include_str!("../../test_data/notebooks/analysis.ipynb"),
+        ),
+        (
+            "attachments.ipynb",
+            include_str!("../../test_data/notebooks/attachments.ipynb"),
+        ),
+        (
+            "colab.ipynb",
+            include_str!("../../test_data/notebooks/colab.ipynb"),
+        ),
+        (
+            "julia.ipynb",
+            include_str!("../../test_data/notebooks/julia.ipynb"),
+        ),
+        (
+            "jupyterlab.ipynb",
+            include_str!("../../test_data/notebooks/jupyterlab.ipynb"),
+        ),
+        (
+            "legacy.ipynb",
+            include_str!("../../test_data/notebooks/legacy.ipynb"),
+        ),
+        (
+            "legacy_edited.ipynb",
+            include_str!("../../test_data/notebooks/legacy_edited.ipynb"),
+        ),
+        (
+            "papermill.ipynb",
+            include_str!("../../test_data/notebooks/papermill.ipynb"),
+        ),
+    ];
+
+    #[test]
+    fn test_unmodified_notebooks_round_trip() {
+        for (name, text) in CORPUS {
+            let document = NotebookDocument::parse(text).unwrap();
+            let cells = document.cells();
+            assert!(!cells.is_empty(), "{name} has no cells");
+            assert_eq!(document.serialize(&cells).unwrap(), *text, "{name}");
+
+            // Also check the formatter, which is used once anything changes, reproduces the
+            // original layout.
+            let mut root = document.root.clone();
+            root.insert(
+                "cells".into(),
+                cells
+                    .iter()
+                    .map(|cell| Value::Object(cell.to_json(document.supports_cell_ids())))
+                    .collect(),
+            );
+            assert_eq!(document.format(&root).unwrap(), *text, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_editing_preserves_unknown_fields() {
+        let text = include_str!("../../test_data/notebooks/legacy.ipynb");
+        let document = NotebookDocument::parse(text).unwrap();
+        assert!(!document.supports_cell_ids());
+
+        let mut cells = document.cells();
+        cells[1].source = "print(*fib(3))".into();
+        cells[1].execution_count = Some(8);
+        cells[1].outputs.clear();
+        push_output(
+            &mut cells[1].outputs,
+            json!({"name": "stdout", "output_type": "stream", "text": ["1 1 "]}),
+        );
+        push_output(
+            &mut cells[1].outputs,
+            json!({"name": "stdout", "output_type": "stream", "text": ["2\n"]}),
+        );
+        cells.insert(
+            0,
+            CellSnapshot::new(CellType::Raw, Some("ignored".into()), "---\n".into()),
+        );
+
+        let saved = document.serialize(&cells).unwrap();
+        assert_eq!(
+            saved,
+            include_str!("../../test_data/notebooks/legacy_edited.ipynb")
+        );
+
+        let reloaded = NotebookDocument::parse(&saved).unwrap().cells();
+        assert_eq!(reloaded.len(), 4);
+        assert!(matches!(reloaded[0].cell_type, CellType::Raw));
+        assert_eq!(reloaded[0].source, "---\n");
+        assert_eq!(reloaded[2].source, "print(*fib(3))");
+        assert_eq!(reloaded[3].source, cells[3].source);
+    }
+
+    #[test]
+    fn test_changing_cell_type_keeps_id_and_metadata() {
+        let text = include_str!("../../test_data/notebooks/analysis.ipynb");
+        let document = NotebookDocument::parse(text).unwrap();
+        assert!(document.supports_cell_ids());
+
+        let mut cells = document.cells();
+        cells[5].cell_type = CellType::Code;
+        cells.swap(0, 1);
+
+        let saved = document.serialize(&cells).unwrap();
+        let reloaded = NotebookDocument::parse(&saved).unwrap().cells();
+        assert_eq!(reloaded[0].id.as_deref(), Some("a1b2c3d4"));
+        assert_eq!(reloaded[1].id.as_deref(), Some("0f6c1d2e"));
+        assert!(matches!(reloaded[5].cell_type, CellType::Code));
+        assert_eq!(reloaded[5].id.as_deref(), Some("e5f6a7b8"));
+        assert_eq!(
+            reloaded[5].raw["metadata"],
+            json!({"raw_mimetype": "text/restructuredtext"})
+        );
+        assert_eq!(reloaded[5].raw["outputs"], json!([]));
+        assert_eq!(
+            reloaded[5].raw.keys().collect::<Vec<_>>(),
+            [
+                "cell_type",
+                "execution_count",
+                "id",
+                "metadata",
+                "outputs",
+                "source"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_cell_types_are_kept() {
+        let text = include_str!("../../test_data/notebooks/papermill.ipynb");
+        let document = NotebookDocument::parse(text).unwrap();
+        let original = document.root["cells"].as_array().unwrap().clone();
+
+        let mut cells = document.cells();
+        assert_eq!(cells.len(), 5);
+        assert!(matches!(cells[2].cell_type, CellType::Raw));
+        assert_eq!(cells[2].source, "Results");
+        assert!(matches!(cells[4].cell_type, CellType::Raw));
+
+        // Editing another cell rewrites the file, but leaves the unknown cells as they were.
+        cells[3].source = "rows = fetch(region, limit)".into();
+        let saved = document.serialize(&cells).unwrap();
+        let saved = NotebookDocument::parse(&saved).unwrap();
+        let saved_cells = saved.root["cells"].as_array().unwrap();
+        assert_eq!(saved_cells.len(), 5);
+        assert_eq!(saved_cells[2], original[2]);
+        assert_eq!(saved_cells[4], original[4]);
+
+        // Edits to an unknown cell's source keep its type and other fields.
+        cells[4].source = "SELECT count(*) FROM sessions".into();
+        let saved = document.serialize(&cells).unwrap();
+        let saved = NotebookDocument::parse(&saved).unwrap();
+        let sql_cell = &saved.root["cells"][4];
+        assert_eq!(sql_cell["cell_type"], "sql");
+        assert_eq!(sql_cell["metadata"], json!({"connection": "warehouse"}));
+        assert_eq!(sql_cell["source"], json!(["SELECT count(*) FROM sessions"]));
+
+        // Turning it into a cell of a known type replaces the type.
+        cells[4].cell_type = CellType::Code;
+        let saved = document.serialize(&cells).unwrap();
+        let saved = NotebookDocument::parse(&saved).unwrap();
+        assert_eq!(saved.root["cells"][4]["cell_type"], "code");
+        assert_eq!(saved.root["cells"][4]["outputs"], json!([]));
+    }
+
+    #[test]
+    fn test_cells_must_be_objects() {
+        let text = r#"{"cells": [{"cell_type": "raw", "metadata": {}, "source": []}, 42], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"#;
+        let error = NotebookDocument::parse(text).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "cell 1 of the notebook is not a JSON object"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(unused, dead_code)]
+use std::env::temp_dir;
 use std::future::Future;
 use std::{path::PathBuf, sync::Arc};
 
@@ -9,20 +10,27 @@ use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
 use futures::FutureExt;
 use futures::future::Shared;
 use gpui::{
-    AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ListScrollEvent, ListState,
-    Point, Task, actions, list, prelude::*,
+    AnyElement, App, Entity, EntityId, EventEmitter, FocusHandle, Focusable, KeyContext,
+    ListScrollEvent, ListState, Point, Subscription, Task, WeakEntity, actions, list, prelude::*,
 };
 use language::{Language, LanguageRegistry};
 use project::{Project, ProjectEntryId, ProjectPath};
 use ui::{Tooltip, prelude::*};
 use workspace::item::{ItemEvent, SaveOptions, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
-use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation};
+use workspace::{Item, ItemHandle, Pane, ProjectItem, ToolbarItemLocation, Workspace};
 use workspace::{ToolbarItemEvent, ToolbarItemView};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::{
+    Cell, CellEvent, CellPosition, CellSnapshot, NotebookDocument, RenderableCell, cell_id,
+    new_cell_id,
+};
+use crate::outputs::{ExecutionStatus, ExecutionView};
+use crate::repl_store::ReplStore;
+use crate::session::{Session, SessionEvent};
 
 use nbformat::v4::CellId;
+use nbformat::v4::CellType;
 use nbformat::v4::Metadata as NotebookMetadata;
 
 actions!(
@@ -42,6 +50,20 @@ actions!(
         AddMarkdownBlock,
         /// Adds a new code cell.
         AddCodeBlock,
+        /// Runs the selected cell and selects the next one.
+        RunCell,
+        /// Deletes the selected cell.
+        DeleteCell,
+        /// Splits the selected cell at the cursor.
+        SplitCell,
+        /// Merges the selected cell with the cell below it.
+        MergeCellBelow,
+        /// Changes the selected cell into a code cell.
+        ChangeCellToCode,
+        /// Changes the selected cell into a markdown cell.
+        ChangeCellToMarkdown,
+        /// Changes the selected cell into a raw cell.
+        ChangeCellToRaw,
     ]
 );
 
@@ -77,6 +99,7 @@ pub struct NotebookEditor {
 
     focus_handle: FocusHandle,
     notebook_item: Entity<NotebookItem>,
+    notebook_language: Shared<Task<Option<Arc<Language>>>>,
 
     remote_id: Option<ViewId>,
     cell_list: ListState,
@@ -84,6 +107,27 @@ pub struct NotebookEditor {
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+    cell_subscriptions: HashMap<EntityId, Subscription>,
+
+    undo_stack: Vec<CellLayout>,
+    redo_stack: Vec<CellLayout>,
+    /// The cells as they were when the notebook was last loaded or saved.
+    saved_cells: Vec<EntityId>,
+}
+
+/// The cells of a notebook at some point in its history. Cells are replaced rather than modified
+/// when adding, deleting, moving, splitting, merging or changing the type of cells, so restoring a
+/// layout undoes those edits, while text edits are undone within each cell's editor.
+#[derive(Clone)]
+struct CellLayout {
+    cell_order: Vec<CellId>,
+    cell_map: HashMap<CellId, Cell>,
+    selected_cell_index: usize,
+}
+
+pub enum NotebookEditorEvent {
+    Edited,
+    TitleChanged,
 }
 
 impl NotebookEditor {
@@ -96,51 +140,138 @@ impl NotebookEditor {
         let focus_handle = cx.focus_handle();
 
         let languages = project.read(cx).languages().clone();
-        let language_name = notebook_item.read(cx).language_name();
 
         let notebook_language = notebook_item.read(cx).notebook_language();
         let notebook_language = cx
             .spawn_in(window, async move |_, _| notebook_language.await)
             .shared();
 
-        let mut cell_order = vec![]; // Vec<CellId>
-        let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
-
-        for (index, cell) in notebook_item
-            .read(cx)
-            .notebook
-            .clone()
-            .cells
-            .iter()
-            .enumerate()
-        {
-            let cell_id = cell.id();
-            cell_order.push(cell_id.clone());
-            cell_map.insert(
-                cell_id.clone(),
-                Cell::load(cell, &languages, notebook_language.clone(), window, cx),
-            );
-        }
-
-        let notebook_handle = cx.entity().downgrade();
-        let cell_count = cell_order.len();
-
-        let this = cx.entity();
-        let cell_list = ListState::new(cell_count, gpui::ListAlignment::Top, px(1000.));
+        let cell_list = ListState::new(0, gpui::ListAlignment::Top, px(1000.));
 
-        Self {
+        let mut this = Self {
             project,
-            languages: languages.clone(),
+            languages,
             focus_handle,
             notebook_item,
+            notebook_language,
             remote_id: None,
             cell_list,
             selected_cell_index: 0,
-            cell_order: cell_order.clone(),
-            cell_map: cell_map.clone(),
+            cell_order: Vec::new(),
+            cell_map: HashMap::default(),
+            cell_subscriptions: HashMap::default(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            saved_cells: Vec::new(),
+        };
+        this.load_cells(window, cx);
+        this
+    }
+
+    /// Replaces the cells with the ones in the notebook file, discarding any history.
+    fn load_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_order.clear();
+        self.cell_map.clear();
+        self.cell_subscriptions.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+
+        for snapshot in self.notebook_item.read(cx).document.cells() {
+            let mut id = cell_id(snapshot.id.as_deref());
+            if self.cell_map.contains_key(&id) {
+                id = new_cell_id();
+            }
+            let cell = self.new_cell(id.clone(), snapshot, window, cx);
+            self.cell_order.push(id.clone());
+            self.cell_map.insert(id, cell);
+        }
+
+        self.saved_cells = self.cell_entity_ids();
+        self.selected_cell_index = self
+            .selected_cell_index
+            .min(self.cell_count().saturating_sub(1));
+        self.cell_list.reset(self.cell_count());
+        cx.notify();
+    }
+
+    fn new_cell(
+        &mut self,
+        id: CellId,
+        snapshot: CellSnapshot,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Cell {
+        let cell = Cell::load(
+            id,
+            snapshot,
+            &self.languages,
+            self.notebook_language.clone(),
+            window,
+            cx,
+        );
+
+        let entity_id = cell.entity_id();
+        let subscription = match &cell {
+            Cell::Code(cell) => cx.subscribe_in(cell, window, move |this, _, event, window, cx| {
+                this.handle_cell_event(entity_id, event, window, cx)
+            }),
+            Cell::Markdown(cell) => {
+                cx.subscribe_in(cell, window, move |this, _, event, window, cx| {
+                    this.handle_cell_event(entity_id, event, window, cx)
+                })
+            }
+            Cell::Raw(cell) => cx.subscribe_in(cell, window, move |this, _, event, window, cx| {
+                this.handle_cell_event(entity_id, event, window, cx)
+            }),
+        };
+        self.cell_subscriptions.insert(entity_id, subscription);
+
+        cell
+    }
+
+    fn handle_cell_event(
+        &mut self,
+        cell_entity_id: EntityId,
+        event: &CellEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            CellEvent::Run => {
+                let index = self.cell_order.iter().position(|id| {
+                    self.cell_map
+                        .get(id)
+                        .is_some_and(|cell| cell.entity_id() == cell_entity_id)
+                });
+                if let Some(index) = index {
+                    self.set_selected_index(index, false, window, cx);
+                    self.run_cell(index, window, cx);
+                }
+            }
+            CellEvent::Edited => cx.emit(NotebookEditorEvent::Edited),
         }
     }
 
+    fn cells(&self) -> impl Iterator<Item = &Cell> {
+        self.cell_order
+            .iter()
+            .filter_map(|id| self.cell_map.get(id))
+    }
+
+    fn cell_at(&self, index: usize) -> Option<&Cell> {
+        self.cell_order
+            .get(index)
+            .and_then(|id| self.cell_map.get(id))
+    }
+
+    fn selected_cell(&self) -> Option<&Cell> {
+        self.cell_at(self.selected_cell_index)
+    }
+
+    fn cell_entity_ids(&self) -> Vec<EntityId> {
+        self.cells().map(Cell::entity_id).collect()
+    }
+
     fn has_outputs(&self, window: &mut Window, cx: &mut Context<Self>) -> bool {
         self.cell_map.values().any(|cell| {
             if let Cell::Code(code_cell) = cell {
@@ -154,39 +285,353 @@ impl NotebookEditor {
     fn clear_outputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         for cell in self.cell_map.values() {
             if let Cell::Code(code_cell) = cell {
-                code_cell.update(cx, |cell, _cx| {
-                    cell.clear_outputs();
+                code_cell.update(cx, |cell, cx| {
+                    cell.clear_outputs(cx);
                 });
             }
         }
     }
 
     fn run_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Cells would all run here, if that was implemented!");
+        for index in 0..self.cell_count() {
+            self.run_cell(index, window, cx);
+        }
+    }
+
+    fn run_selected_cell(&mut self, _: &RunCell, window: &mut Window, cx: &mut Context<Self>) {
+        self.run_cell(self.selected_cell_index, window, cx);
+        if self.selected_cell_index + 1 < self.cell_count() {
+            self.set_selected_index(self.selected_cell_index + 1, true, window, cx);
+            cx.notify();
+        }
+    }
+
+    fn run_cell(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(Cell::Code(cell)) = self.cell_at(index).cloned() else {
+            return;
+        };
+        let code = cell.read(cx).source(cx);
+        if code.trim().is_empty() {
+            return;
+        }
+
+        let (session, status) = match self.session(window, cx) {
+            Ok(session) => {
+                let status = session.read(cx).execution_status();
+                (Some(session), status)
+            }
+            Err(error) => (None, ExecutionStatus::KernelErrored(error.to_string())),
+        };
+
+        let workspace = window
+            .root::<Workspace>()
+            .flatten()
+            .map(|workspace| workspace.downgrade())
+            .unwrap_or_else(WeakEntity::new_invalid);
+        let execution_view = cx.new(|cx| ExecutionView::new(status, workspace, cx));
+        cell.update(cx, |cell, cx| {
+            cell.start_execution(execution_view.clone(), cx);
+        });
+
+        if let Some(session) = session {
+            session.update(cx, |session, cx| {
+                session.execute_in_view(code, execution_view, cx);
+            });
+        }
+    }
+
+    /// The kernel session for this notebook, starting one if needed.
+    fn session(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Result<Entity<Session>> {
+        let store = ReplStore::global(cx);
+        anyhow::ensure!(store.read(cx).is_enabled(), "The REPL is disabled");
+
+        let notebook_id = cx.entity_id();
+        if let Some(session) = store.read(cx).get_session(notebook_id) {
+            return Ok(session.clone());
+        }
+
+        let notebook_item = self.notebook_item.read(cx);
+        let kernel_name = notebook_item.kernel_name();
+        let worktree_id = notebook_item.project_path.worktree_id;
+        let working_directory = notebook_item
+            .path
+            .parent()
+            .map(|path| path.to_path_buf())
+            .unwrap_or_else(temp_dir);
+        let language = self.notebook_language.clone().now_or_never().flatten();
+
+        let kernel_specification = store
+            .read(cx)
+            .kernelspec_for_notebook(worktree_id, kernel_name.as_deref(), language, cx)
+            .context("No kernel found for this notebook")?;
+        let fs = store.read(cx).fs().clone();
+
+        let session = cx.new(|cx| {
+            Session::new_for_notebook(
+                notebook_id,
+                working_directory,
+                fs,
+                kernel_specification,
+                window,
+                cx,
+            )
+        });
+
+        cx.subscribe(&session, {
+            let store = store.clone();
+            move |_, _, event, cx| match event {
+                SessionEvent::Shutdown(_) => {
+                    store.update(cx, |store, _cx| {
+                        store.remove_session(notebook_id);
+                    });
+                }
+            }
+        })
+        .detach();
+
+        store.update(cx, |store, _cx| {
+            store.insert_session(notebook_id, session.clone());
+        });
+
+        Ok(session)
     }
 
     fn open_notebook(&mut self, _: &OpenNotebook, _window: &mut Window, _cx: &mut Context<Self>) {
         println!("Open notebook triggered");
     }
 
+    fn layout(&self) -> CellLayout {
+        CellLayout {
+            cell_order: self.cell_order.clone(),
+            cell_map: self.cell_map.clone(),
+            selected_cell_index: self.selected_cell_index,
+        }
+    }
+
+    /// Changes which cells the notebook contains, recording the previous cells for undo.
+    fn edit_cells(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        edit: impl FnOnce(&mut Self),
+    ) {
+        self.undo_stack.push(self.layout());
+        self.redo_stack.clear();
+        edit(self);
+        self.cells_changed(window, cx);
+    }
+
+    fn restore_layout(&mut self, layout: CellLayout, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_order = layout.cell_order;
+        self.cell_map = layout.cell_map;
+        self.selected_cell_index = layout.selected_cell_index;
+        self.cells_changed(window, cx);
+    }
+
+    fn cells_changed(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_list
+            .splice(0..self.cell_list.item_count(), self.cell_count());
+        self.selected_cell_index = self
+            .selected_cell_index
+            .min(self.cell_count().saturating_sub(1));
+        self.jump_to_cell(self.selected_cell_index, window, cx);
+        cx.emit(NotebookEditorEvent::Edited);
+        cx.notify();
+    }
+
+    fn undo(&mut self, _: &editor::actions::Undo, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(layout) = self.undo_stack.pop() {
+            self.redo_stack.push(self.layout());
+            self.restore_layout(layout, window, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &editor::actions::Redo, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(layout) = self.redo_stack.pop() {
+            self.undo_stack.push(self.layout());
+            self.restore_layout(layout, window, cx);
+        }
+    }
+
     fn move_cell_up(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_cell_index;
+        if index > 0 && index < self.cell_count() {
+            self.edit_cells(window, cx, |this| {
+                this.cell_order.swap(index, index - 1);
+                this.selected_cell_index = index - 1;
+            });
+        }
     }
 
     fn move_cell_down(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_cell_index;
+        if index + 1 < self.cell_count() {
+            self.edit_cells(window, cx, |this| {
+                this.cell_order.swap(index, index + 1);
+                this.selected_cell_index = index + 1;
+            });
+        }
     }
 
     fn add_markdown_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add markdown block triggered");
+        self.insert_cell(CellType::Markdown, window, cx);
     }
 
     fn add_code_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Add code block triggered");
+        self.insert_cell(CellType::Code, window, cx);
+    }
+
+    /// Inserts an empty cell below the selected one and starts editing it.
+    fn insert_cell(&mut self, cell_type: CellType, window: &mut Window, cx: &mut Context<Self>) {
+        let id = new_cell_id();
+        let snapshot = CellSnapshot::new(cell_type, Some(id.to_string()), String::new());
+        let cell = self.new_cell(id.clone(), snapshot, window, cx);
+        let index = if self.cell_order.is_empty() {
+            0
+        } else {
+            self.selected_cell_index + 1
+        };
+
+        let editor = cell.editor(cx);
+        self.edit_cells(window, cx, |this| {
+            this.cell_order.insert(index, id.clone());
+            this.cell_map.insert(id, cell);
+            this.selected_cell_index = index;
+        });
+        window.focus(&editor.focus_handle(cx));
+    }
+
+    fn delete_cell(&mut self, _: &DeleteCell, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index;
+        if index < self.cell_count() {
+            self.edit_cells(window, cx, |this| {
+                let id = this.cell_order.remove(index);
+                this.cell_map.remove(&id);
+            });
+            window.focus(&self.focus_handle);
+        }
+    }
+
+    fn split_cell(&mut self, _: &SplitCell, window: &mut Window, cx: &mut Context<Self>) {
+        let index = self.selected_cell_index;
+        let Some(cell) = self.cell_at(index).cloned() else {
+            return;
+        };
+
+        let editor = cell.editor(cx);
+        let offset = editor.update(cx, |editor, cx| {
+            editor.selections.newest::<usize>(cx).head()
+        });
+        let mut first = cell.snapshot(cx);
+        let (before, after) = first.source.split_at(offset.min(first.source.len()));
+        let before = before.strip_suffix('\n').unwrap_or(before).to_string();
+        let after = after.strip_prefix('\n').unwrap_or(after).to_string();
+
+        let second_id = new_cell_id();
+        let second = CellSnapshot::new(first.cell_type.clone(), Some(second_id.to_string()), after);
+        first.source = before;
+
+        let first_id = cell.id(cx);
+        let first = self.new_cell(first_id.clone(), first, window, cx);
+        let second = self.new_cell(second_id.clone(), second, window, cx);
+        let second_editor = second.editor(cx);
+        self.edit_cells(window, cx, |this| {
+            this.cell_map.insert(first_id, first);
+            this.cell_order.insert(index + 1, second_id.clone());
+            this.cell_map.insert(second_id, second);
+            this.selected_cell_index = index + 1;
+        });
+        window.focus(&second_editor.focus_handle(cx));
+    }
+
+    fn merge_cell_below(
+        &mut self,
+        _: &MergeCellBelow,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let index = self.selected_cell_index;
+        let (Some(cell), Some(below)) = (self.cell_at(index).cloned(), self.cell_at(index + 1))
+        else {
+            return;
+        };
+
+        let mut merged = cell.snapshot(cx);
+        let below_source = below.source(cx);
+        if !merged.source.is_empty() && !below_source.is_empty() {
+            merged.source.push('\n');
+        }
+        merged.source.push_str(&below_source);
+        merged.outputs.clear();
+        merged.execution_count = None;
+
+        let id = cell.id(cx);
+        let merged = self.new_cell(id.clone(), merged, window, cx);
+        self.edit_cells(window, cx, |this| {
+            let below_id = this.cell_order.remove(index + 1);
+            this.cell_map.remove(&below_id);
+            this.cell_map.insert(id, merged);
+        });
+        window.focus(&self.focus_handle);
+    }
+
+    fn change_cell_type(
+        &mut self,
+        cell_type: CellType,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(cell) = self.selected_cell().cloned() else {
+            return;
+        };
+        if std::mem::discriminant(&cell.cell_type()) == std::mem::discriminant(&cell_type) {
+            return;
+        }
+
+        let mut snapshot = cell.snapshot(cx);
+        snapshot.cell_type = cell_type;
+        snapshot.outputs.clear();
+        snapshot.execution_count = None;
+
+        let id = cell.id(cx);
+        let cell = self.new_cell(id.clone(), snapshot, window, cx);
+        self.edit_cells(window, cx, |this| {
+            this.cell_map.insert(id, cell);
+        });
+        window.focus(&self.focus_handle);
+    }
+
+    /// Starts editing the selected cell's source.
+    fn edit_selected_cell(
+        &mut self,
+        _: &menu::Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match self.selected_cell() {
+            Some(Cell::Markdown(cell)) => {
+                cell.update(cx, |cell, cx| cell.start_editing(window, cx));
+            }
+            Some(cell) => {
+                let editor = cell.editor(cx);
+                window.focus(&editor.focus_handle(cx));
+            }
+            None => {}
+        }
+    }
+
+    /// Leaves the cell editor once there is nothing left for it to cancel.
+    fn stop_editing_cell(
+        &mut self,
+        _: &editor::actions::Cancel,
+        window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle);
     }
 
     fn cell_count(&self) -> usize {
-        self.cell_map.len()
+        self.cell_order.len()
     }
 
     fn selected_index(&self) -> usize {
@@ -438,6 +883,20 @@ impl NotebookEditor {
             )
     }
 
+    fn dispatch_context(&self, window: &Window) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("notebook");
+
+        let identifier = if self.focus_handle.is_focused(window) {
+            "not_editing"
+        } else {
+            "editing"
+        };
+
+        dispatch_context.add(identifier);
+        dispatch_context
+    }
+
     fn cell_position(&self, index: usize) -> CellPosition {
         match index {
             0 => CellPosition::First,
@@ -457,7 +916,7 @@ impl NotebookEditor {
 
         let is_selected = index == self.selected_cell_index;
 
-        match cell {
+        let cell = match cell {
             Cell::Code(cell) => {
                 cell.update(cx, |cell, _cx| {
                     cell.set_selected(is_selected)
@@ -479,14 +938,23 @@ impl NotebookEditor {
                 });
                 cell.clone().into_any_element()
             }
-        }
+        };
+
+        div()
+            .capture_any_mouse_down(cx.listener(move |this, _, window, cx| {
+                if this.selected_cell_index != index {
+                    this.set_selected_index(index, false, window, cx);
+                    cx.notify();
+                }
+            }))
+            .child(cell)
     }
 }
 
 impl Render for NotebookEditor {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         div()
-            .key_context("notebook")
+            .key_context(self.dispatch_context(window))
             .track_focus(&self.focus_handle)
             .on_action(cx.listener(|this, &OpenNotebook, window, cx| {
                 this.open_notebook(&OpenNotebook, window, cx)
@@ -509,6 +977,23 @@ impl Render for NotebookEditor {
             .on_action(cx.listener(Self::select_previous))
             .on_action(cx.listener(Self::select_first))
             .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::run_selected_cell))
+            .on_action(cx.listener(Self::delete_cell))
+            .on_action(cx.listener(Self::split_cell))
+            .on_action(cx.listener(Self::merge_cell_below))
+            .on_action(cx.listener(|this, &ChangeCellToCode, window, cx| {
+                this.change_cell_type(CellType::Code, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeCellToMarkdown, window, cx| {
+                this.change_cell_type(CellType::Markdown, window, cx)
+            }))
+            .on_action(cx.listener(|this, &ChangeCellToRaw, window, cx| {
+                this.change_cell_type(CellType::Raw, window, cx)
+            }))
+            .on_action(cx.listener(Self::edit_selected_cell))
+            .on_action(cx.listener(Self::stop_editing_cell))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
             .flex()
             .items_start()
             .size_full()
@@ -553,8 +1038,9 @@ pub struct NotebookItem {
     languages: Arc<LanguageRegistry>,
     // Raw notebook data
     notebook: nbformat::v4::Notebook,
-    // Store our version of the notebook in memory (cell_order, cell_map)
-    id: ProjectEntryId,
+    // The notebook file as JSON, used to write back edits without losing unknown fields
+    document: NotebookDocument,
+    id: Option<ProjectEntryId>,
 }
 
 impl project::ProjectItem for NotebookItem {
@@ -576,22 +1062,7 @@ impl project::ProjectItem for NotebookItem {
 
                 // todo: watch for changes to the file
                 let file_content = fs.load(abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-
-                        nbformat::upgrade_legacy_notebook(legacy_notebook)?
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let (notebook, document) = parse_notebook(&file_content)?;
 
                 let id = project
                     .update(cx, |project, cx| project.entry_for_path(&path, cx))?
@@ -603,7 +1074,8 @@ impl project::ProjectItem for NotebookItem {
                     project_path: path,
                     languages,
                     notebook,
-                    id,
+                    document,
+                    id: Some(id),
                 })
             }))
         } else {
@@ -612,7 +1084,7 @@ impl project::ProjectItem for NotebookItem {
     }
 
     fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
-        Some(self.id)
+        self.id
     }
 
     fn project_path(&self, _: &App) -> Option<ProjectPath> {
@@ -624,7 +1096,32 @@ impl project::ProjectItem for NotebookItem {
     }
 }
 
+fn parse_notebook(text: &str) -> Result<(nbformat::v4::Notebook, NotebookDocument)> {
+    let notebook = match nbformat::parse_notebook(text) {
+        Ok(nbformat::Notebook::V4(notebook)) => notebook,
+        // 4.1 - 4.4 are converted to 4.5. Cell IDs generated by the upgrade are only used
+        // internally; they are not written back to notebooks that predate 4.5.
+        Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+            nbformat::upgrade_legacy_notebook(legacy_notebook)?
+        }
+        // Bad notebooks and notebooks v4.0 and below are not supported
+        Err(e) => {
+            anyhow::bail!("Failed to parse notebook: {:?}", e);
+        }
+    };
+    let document = NotebookDocument::parse(text)?;
+    Ok((notebook, document))
+}
+
 impl NotebookItem {
+    pub fn kernel_name(&self) -> Option<String> {
+        self.notebook
+            .metadata
+            .kernelspec
+            .as_ref()
+            .map(|spec| spec.name.clone())
+    }
+
     pub fn language_name(&self) -> Option<String> {
         self.notebook
             .metadata
@@ -653,7 +1150,40 @@ impl NotebookItem {
     }
 }
 
-impl EventEmitter<()> for NotebookEditor {}
+impl EventEmitter<NotebookEditorEvent> for NotebookEditor {}
+
+impl NotebookEditor {
+    fn save_to(&mut self, abs_path: PathBuf, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let snapshots = self
+            .cells()
+            .map(|cell| cell.snapshot(cx))
+            .collect::<Vec<_>>();
+        let text = match self.notebook_item.read(cx).document.serialize(&snapshots) {
+            Ok(text) => text,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let fs = self.project.read(cx).fs().clone();
+
+        cx.spawn(async move |this, cx| {
+            fs.atomic_write(abs_path, text.clone()).await?;
+            let (notebook, document) = parse_notebook(&text)?;
+
+            this.update(cx, |this, cx| {
+                this.notebook_item.update(cx, |item, _| {
+                    item.notebook = notebook;
+                    item.document = document;
+                });
+                let cells = this.cells().cloned().collect::<Vec<_>>();
+                for cell in cells {
+                    cell.did_save(cx);
+                }
+                this.saved_cells = this.cell_entity_ids();
+                cx.emit(NotebookEditorEvent::TitleChanged);
+                cx.notify();
+            })
+        })
+    }
+}
 
 // pub struct NotebookControls {
 //     pane_focused: bool,
@@ -701,7 +1231,20 @@ impl EventEmitter<()> for NotebookEditor {}
 // }
 
 impl Item for NotebookEditor {
-    type Event = ();
+    type Event = NotebookEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            NotebookEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+            NotebookEditorEvent::TitleChanged => {
+                f(ItemEvent::UpdateTab);
+                f(ItemEvent::UpdateBreadcrumbs);
+            }
+        }
+    }
 
     fn clone_on_split(
         &self,
@@ -772,49 +1315,77 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &App) -> bool {
-        false
+        true
     }
-    // TODO
+
     fn save(
         &mut self,
         _options: SaveOptions,
         _project: Entity<Project>,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        self.save_to(abs_path, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Entity<Project>,
-        _path: ProjectPath,
+        project: Entity<Project>,
+        path: ProjectPath,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
+        let Some(abs_path) = project.read(cx).absolute_path(&path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "finding the absolute path of {path:?}"
+            )));
+        };
+        let save = self.save_to(abs_path.clone(), cx);
+
+        cx.spawn(async move |this, cx| {
+            save.await?;
+            let id = project
+                .update(cx, |project, cx| project.entry_for_path(&path, cx))?
+                .map(|entry| entry.id);
+            this.update(cx, |this, cx| {
+                this.notebook_item.update(cx, |item, _| {
+                    item.path = abs_path;
+                    item.project_path = path;
+                    item.id = id;
+                });
+                cx.emit(NotebookEditorEvent::TitleChanged);
+            })
+        })
     }
-    // TODO
+
     fn reload(
         &mut self,
         _project: Entity<Project>,
-        _window: &mut Window,
-        _cx: &mut Context<Self>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        let fs = self.project.read(cx).fs().clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let text = fs.load(&abs_path).await?;
+            let (notebook, document) = parse_notebook(&text)?;
+
+            this.update_in(cx, |this, window, cx| {
+                this.notebook_item.update(cx, |item, _| {
+                    item.notebook = notebook;
+                    item.document = document;
+                });
+                this.load_cells(window, cx);
+                cx.emit(NotebookEditorEvent::TitleChanged);
+            })
+        })
     }
 
     fn is_dirty(&self, cx: &App) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
-        })
+        self.cell_entity_ids() != self.saved_cells || self.cells().any(|cell| cell.is_dirty(cx))
     }
 }
 
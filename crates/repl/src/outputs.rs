@@ -42,6 +42,7 @@ use gpui::{
 };
 use language::Buffer;
 use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
+use serde_json::Value;
 use ui::{Context, IntoElement, Styled, Tooltip, Window, div, prelude::*, v_flex};
 
 mod image;
@@ -60,6 +61,8 @@ pub(crate) mod user_error;
 use user_error::ErrorView;
 use workspace::Workspace;
 
+use crate::notebook::{execution_count_from_message, output_from_message, push_output};
+
 /// When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
 fn rank_mime_type(mimetype: &MimeType) -> usize {
     match mimetype {
@@ -320,6 +323,10 @@ pub struct ExecutionView {
     workspace: WeakEntity<Workspace>,
     pub outputs: Vec<Output>,
     pub status: ExecutionStatus,
+    /// The outputs in their nbformat representation, for storing in notebooks.
+    pub notebook_outputs: Vec<Value>,
+    pub execution_count: Option<i32>,
+    clear_notebook_outputs_on_next: bool,
 }
 
 impl ExecutionView {
@@ -332,6 +339,9 @@ impl ExecutionView {
             workspace,
             outputs: Default::default(),
             status,
+            notebook_outputs: Vec::new(),
+            execution_count: None,
+            clear_notebook_outputs_on_next: false,
         }
     }
 
@@ -342,6 +352,8 @@ impl ExecutionView {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.record_notebook_output(message);
+
         let output: Output = match message {
             JupyterMessageContent::ExecuteResult(result) => Output::new(
                 &result.data,
@@ -420,6 +432,25 @@ impl ExecutionView {
         cx.notify();
     }
 
+    fn record_notebook_output(&mut self, message: &JupyterMessageContent) {
+        if let Some(execution_count) = execution_count_from_message(message) {
+            self.execution_count = Some(execution_count);
+        }
+
+        if let JupyterMessageContent::ClearOutput(options) = message {
+            if options.wait {
+                self.clear_notebook_outputs_on_next = true;
+            } else {
+                self.notebook_outputs.clear();
+            }
+        } else if let Some(output) = output_from_message(message) {
+            if std::mem::take(&mut self.clear_notebook_outputs_on_next) {
+                self.notebook_outputs.clear();
+            }
+            push_output(&mut self.notebook_outputs, output);
+        }
+    }
+
     pub fn update_display_data(
         &mut self,
         data: &MimeBundle,
@@ -221,6 +221,26 @@ impl ReplStore {
         }
     }
 
+    /// The kernel for a notebook, preferring the kernel named in the notebook's metadata.
+    pub fn kernelspec_for_notebook(
+        &self,
+        worktree_id: WorktreeId,
+        kernel_name: Option<&str>,
+        language: Option<Arc<Language>>,
+        cx: &App,
+    ) -> Option<KernelSpecification> {
+        let found_by_name = kernel_name.and_then(|kernel_name| {
+            self.kernel_specifications_for_worktree(worktree_id)
+                .find(|spec| match spec {
+                    KernelSpecification::Jupyter(spec) => spec.name == kernel_name,
+                    _ => false,
+                })
+                .cloned()
+        });
+
+        found_by_name.or_else(|| self.active_kernelspec(worktree_id, language, cx))
+    }
+
     fn kernelspec_legacy_by_lang_only(
         &self,
         language_at_cursor: Arc<Language>,
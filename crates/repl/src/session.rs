@@ -19,7 +19,8 @@ use editor::{
 };
 use futures::FutureExt as _;
 use gpui::{
-    Context, Entity, EventEmitter, Render, Subscription, Task, WeakEntity, Window, div, prelude::*,
+    Context, Entity, EntityId, EventEmitter, Render, Subscription, Task, WeakEntity, Window, div,
+    prelude::*,
 };
 use language::Point;
 use project::Fs;
@@ -27,7 +28,7 @@ use runtimelib::{
     ExecuteRequest, ExecutionState, InterruptRequest, JupyterMessage, JupyterMessageContent,
     ShutdownRequest,
 };
-use std::{env::temp_dir, ops::Range, sync::Arc, time::Duration};
+use std::{env::temp_dir, ops::Range, path::PathBuf, sync::Arc, time::Duration};
 use theme::ActiveTheme;
 use ui::{IconButtonShape, Tooltip, prelude::*};
 use util::ResultExt as _;
@@ -35,8 +36,13 @@ use util::ResultExt as _;
 pub struct Session {
     fs: Arc<dyn Fs>,
     editor: WeakEntity<Editor>,
+    /// The entity the kernel was started for, either an editor or a notebook.
+    owner_id: EntityId,
+    working_directory: Option<PathBuf>,
     pub kernel: Kernel,
     blocks: HashMap<String, EditorBlock>,
+    /// Executions whose outputs are shown outside of the editor, such as notebook cells.
+    executions: HashMap<String, Entity<ExecutionView>>,
     pub kernel_specification: KernelSpecification,
     _buffer_subscription: Subscription,
 }
@@ -217,9 +223,12 @@ impl Session {
 
         let mut session = Self {
             fs,
+            owner_id: editor.entity_id(),
             editor,
+            working_directory: None,
             kernel: Kernel::StartingKernel(Task::ready(()).shared()),
             blocks: HashMap::default(),
+            executions: HashMap::default(),
             kernel_specification,
             _buffer_subscription: subscription,
         };
@@ -228,13 +237,43 @@ impl Session {
         session
     }
 
+    /// Starts a session for a notebook, which shows outputs in its cells rather than in an
+    /// editor. See [`Session::execute_in_view`].
+    pub fn new_for_notebook(
+        notebook_id: EntityId,
+        working_directory: PathBuf,
+        fs: Arc<dyn Fs>,
+        kernel_specification: KernelSpecification,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut session = Self {
+            fs,
+            editor: WeakEntity::new_invalid(),
+            owner_id: notebook_id,
+            working_directory: Some(working_directory),
+            kernel: Kernel::StartingKernel(Task::ready(()).shared()),
+            blocks: HashMap::default(),
+            executions: HashMap::default(),
+            kernel_specification,
+            _buffer_subscription: Subscription::new(|| {}),
+        };
+
+        session.start_kernel(window, cx);
+        session
+    }
+
     fn start_kernel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let kernel_language = self.kernel_specification.language();
-        let entity_id = self.editor.entity_id();
+        let entity_id = self.owner_id;
         let working_directory = self
-            .editor
-            .upgrade()
-            .and_then(|editor| editor.read(cx).working_directory(cx))
+            .working_directory
+            .clone()
+            .or_else(|| {
+                self.editor
+                    .upgrade()
+                    .and_then(|editor| editor.read(cx).working_directory(cx))
+            })
             .unwrap_or_else(temp_dir);
 
         telemetry::event!(
@@ -309,6 +348,15 @@ impl Session {
                 cx.notify();
             });
         });
+
+        for execution_view in self.executions.values() {
+            execution_view.update(cx, |execution_view, cx| {
+                if !matches!(execution_view.status, ExecutionStatus::Finished) {
+                    execution_view.status = ExecutionStatus::KernelErrored(error_message.clone());
+                    cx.notify();
+                }
+            });
+        }
     }
 
     fn on_buffer_event(
@@ -406,14 +454,7 @@ impl Session {
             })
             .ok();
 
-        let status = match &self.kernel {
-            Kernel::Restarting => ExecutionStatus::Restarting,
-            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
-            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
-            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
-            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
-            Kernel::Shutdown => ExecutionStatus::Shutdown,
-        };
+        let status = self.execution_status();
 
         let parent_message_id = message.header.msg_id.clone();
         let session_view = cx.entity().downgrade();
@@ -453,6 +494,53 @@ impl Session {
         self.blocks
             .insert(message.header.msg_id.clone(), editor_block);
 
+        self.send_when_ready(message, cx);
+
+        if move_down {
+            editor.update(cx, move |editor, cx| {
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::top_relative(8)),
+                    window,
+                    cx,
+                    |selections| {
+                        selections.select_ranges([new_cursor_pos..new_cursor_pos]);
+                    },
+                );
+            });
+        }
+    }
+
+    /// Runs code, showing its outputs in the given view instead of in a block in the editor.
+    /// The view should be created with the session's current [`Session::execution_status`].
+    pub fn execute_in_view(
+        &mut self,
+        code: String,
+        execution_view: Entity<ExecutionView>,
+        cx: &mut Context<Self>,
+    ) {
+        let execute_request = ExecuteRequest {
+            code,
+            ..ExecuteRequest::default()
+        };
+
+        let message: JupyterMessage = execute_request.into();
+        self.executions
+            .insert(message.header.msg_id.clone(), execution_view);
+        self.send_when_ready(message, cx);
+    }
+
+    pub fn execution_status(&self) -> ExecutionStatus {
+        match &self.kernel {
+            Kernel::Restarting => ExecutionStatus::Restarting,
+            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
+            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
+            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
+            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
+            Kernel::Shutdown => ExecutionStatus::Shutdown,
+        }
+    }
+
+    fn send_when_ready(&mut self, message: JupyterMessage, cx: &mut Context<Self>) {
         match &self.kernel {
             Kernel::RunningKernel(_) => {
                 self.send(message, cx).ok();
@@ -472,19 +560,6 @@ impl Session {
             }
             _ => {}
         }
-
-        if move_down {
-            editor.update(cx, move |editor, cx| {
-                editor.change_selections(
-                    SelectionEffects::scroll(Autoscroll::top_relative(8)),
-                    window,
-                    cx,
-                    |selections| {
-                        selections.select_ranges([new_cursor_pos..new_cursor_pos]);
-                    },
-                );
-            });
-        }
     }
 
     pub fn route(&mut self, message: &JupyterMessage, window: &mut Window, cx: &mut Context<Self>) {
@@ -517,11 +592,20 @@ impl Session {
                     return;
                 };
 
-                self.blocks.iter_mut().for_each(|(_, block)| {
-                    block.execution_view.update(cx, |execution_view, cx| {
-                        execution_view.update_display_data(&update.data, &display_id, window, cx);
+                self.blocks
+                    .values()
+                    .map(|block| &block.execution_view)
+                    .chain(self.executions.values())
+                    .for_each(|execution_view| {
+                        execution_view.update(cx, |execution_view, cx| {
+                            execution_view.update_display_data(
+                                &update.data,
+                                &display_id,
+                                window,
+                                cx,
+                            );
+                        });
                     });
-                });
                 return;
             }
             _ => {}
@@ -530,6 +614,17 @@ impl Session {
         if let Some(block) = self.blocks.get_mut(parent_message_id) {
             block.handle_message(message, window, cx);
         }
+
+        if let Some(execution_view) = self.executions.get(parent_message_id) {
+            execution_view.update(cx, |execution_view, cx| {
+                execution_view.push_message(&message.content, window, cx);
+            });
+            if let JupyterMessageContent::Status(status) = &message.content
+                && let ExecutionState::Idle = status.execution_state
+            {
+                self.executions.remove(parent_message_id);
+            }
+        }
     }
 
     pub fn interrupt(&mut self, cx: &mut Context<Self>) {
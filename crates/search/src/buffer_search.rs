@@ -3,7 +3,8 @@ mod registrar;
 use crate::{
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOption,
     SearchOptions, SearchSource, SelectAllMatches, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleStructural,
+    ToggleWholeWord,
     search_bar::{ActionButtonState, input_base_styles, render_action_button, render_text_input},
 };
 use any_vec::AnyVec;
@@ -149,6 +150,7 @@ impl Render for BufferSearchBar {
             case,
             word,
             regex,
+            structural,
             replacement,
             selection,
             find_in_results,
@@ -232,6 +234,13 @@ impl Render for BufferSearchBar {
                                 SearchSource::Buffer,
                                 focus_handle.clone(),
                             ))
+                        })
+                        .when(structural, |div| {
+                            div.child(SearchOption::Structural.as_button(
+                                self.search_options,
+                                SearchSource::Buffer,
+                                focus_handle.clone(),
+                            ))
                         }),
                 )
             });
@@ -443,6 +452,9 @@ impl Render for BufferSearchBar {
             .when(regex, |this| {
                 this.on_action(cx.listener(Self::toggle_regex))
             })
+            .when(structural, |this| {
+                this.on_action(cx.listener(Self::toggle_structural))
+            })
             .when(selection, |this| {
                 this.on_action(cx.listener(Self::toggle_selection))
             })
@@ -527,6 +539,13 @@ impl BufferSearchBar {
                 this.toggle_regex(action, window, cx);
             }
         }));
+        registrar.register_handler(ForDeployed(
+            |this, action: &ToggleStructural, window, cx| {
+                if this.supported_options(cx).structural {
+                    this.toggle_structural(action, window, cx);
+                }
+            },
+        ));
         registrar.register_handler(ForDeployed(|this, action: &ToggleSelection, window, cx| {
             if this.supported_options(cx).selection {
                 this.toggle_selection(action, window, cx);
@@ -889,7 +908,7 @@ impl BufferSearchBar {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        self.search_options.toggle(search_option);
+        self.search_options.toggle_exclusive(search_option);
         self.default_options = self.search_options;
         drop(self.update_matches(false, window, cx));
         self.adjust_query_regex_language(cx);
@@ -1122,6 +1141,15 @@ impl BufferSearchBar {
         self.toggle_search_option(SearchOptions::REGEX, window, cx)
     }
 
+    fn toggle_structural(
+        &mut self,
+        _: &ToggleStructural,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.toggle_search_option(SearchOptions::STRUCTURAL, window, cx)
+    }
+
     fn clear_active_searchable_item_matches(&mut self, window: &mut Window, cx: &mut App) {
         if let Some(active_searchable_item) = self.active_searchable_item.as_ref() {
             self.active_match_index = None;
@@ -1175,7 +1203,26 @@ impl BufferSearchBar {
                 {
                     search
                 } else {
-                    if self.search_options.contains(SearchOptions::REGEX) {
+                    if self.search_options.contains(SearchOptions::STRUCTURAL)
+                        && self.supported_options(cx).structural
+                    {
+                        match SearchQuery::structural(
+                            query,
+                            false,
+                            Default::default(),
+                            Default::default(),
+                            false,
+                            None,
+                        ) {
+                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Err(e) => {
+                                self.query_error = Some(e.to_string());
+                                self.clear_active_searchable_item_matches(window, cx);
+                                cx.notify();
+                                return done_rx;
+                            }
+                        }
+                    } else if self.search_options.contains(SearchOptions::REGEX) {
                         match SearchQuery::regex(
                             query,
                             self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1,12 +1,13 @@
 use crate::{
     BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext,
     SearchOption, SearchOptions, SearchSource, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleFuzzy, ToggleIncludeIgnored, ToggleRegex, ToggleReplace,
+    ToggleSemantic, ToggleSmartCase, ToggleStructural, ToggleWholeWord,
     buffer_search::Deploy,
     search_bar::{ActionButtonState, input_base_styles, render_action_button, render_text_input},
 };
 use anyhow::Context as _;
-use collections::HashMap;
+use collections::{HashMap, IndexMap};
 use editor::{
     Anchor, Editor, EditorEvent, EditorSettings, MAX_TAB_TITLE_LEN, MultiBuffer, SelectionEffects,
     actions::{Backtab, SelectAll, Tab},
@@ -15,18 +16,21 @@ use editor::{
 };
 use futures::{StreamExt, stream::FuturesOrdered};
 use gpui::{
-    Action, AnyElement, AnyView, App, Axis, Context, Entity, EntityId, EventEmitter, FocusHandle,
-    Focusable, Global, Hsla, InteractiveElement, IntoElement, KeyContext, ParentElement, Point,
-    Render, SharedString, Styled, Subscription, Task, UpdateGlobal, WeakEntity, Window, actions,
-    div,
+    Action, AnyElement, AnyView, App, Axis, BorrowAppContext as _, Context, Corner, Entity,
+    EntityId, EventEmitter, FocusHandle, Focusable, Global, Hsla, InteractiveElement, IntoElement,
+    KeyContext, ParentElement, Point, Render, SharedString, Styled, Subscription, Task,
+    UpdateGlobal, WeakEntity, Window, actions, div,
 };
-use language::{Buffer, Language};
+use language::{Bias, Buffer, BufferId, Language, OffsetRangeExt as _};
 use menu::Confirm;
 use project::{
     Project, ProjectPath,
     search::{SearchInputKind, SearchQuery},
-    search_history::SearchHistoryCursor,
+    search_history::{SavedSearch, SearchHistoryCursor},
 };
+use schemars::JsonSchema;
+use semantic_index::SemanticDb;
+use serde::Deserialize;
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -37,17 +41,21 @@ use std::{
     sync::Arc,
 };
 use ui::{
-    Icon, IconButton, IconButtonShape, IconName, KeyBinding, Label, LabelCommon, LabelSize,
-    Toggleable, Tooltip, h_flex, prelude::*, utils::SearchInputWidth, v_flex,
+    ContextMenu, Icon, IconButton, IconButtonShape, IconName, KeyBinding, Label, LabelCommon,
+    LabelSize, PopoverMenu, Toggleable, Tooltip, h_flex, prelude::*, utils::SearchInputWidth,
+    v_flex,
 };
 use util::{ResultExt as _, paths::PathMatcher};
 use workspace::{
     DeploySearch, ItemNavHistory, NewSearch, ToolbarItemEvent, ToolbarItemLocation,
-    ToolbarItemView, Workspace, WorkspaceId,
+    ToolbarItemView, WORKSPACE_DB, Workspace, WorkspaceId,
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, SaveOptions},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
 };
 
+/// The number of chunks of the semantic index shown by a semantic search.
+const SEMANTIC_SEARCH_LIMIT: usize = 50;
+
 actions!(
     project_search,
     [
@@ -58,10 +66,38 @@ actions!(
         /// Moves to the next input field.
         NextField,
         /// Toggles the search filters panel.
-        ToggleFilters
+        ToggleFilters,
+        /// Toggles running the next search only within the current search results.
+        ToggleSearchWithinResults
     ]
 );
 
+/// Saves the current project search under a name, so that it can be run again later.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = project_search)]
+#[serde(deny_unknown_fields)]
+pub struct SaveSearch {
+    /// The name to save the search under. Defaults to the query text.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Runs a previously saved project search.
+#[derive(PartialEq, Clone, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = project_search)]
+#[serde(deny_unknown_fields)]
+pub struct OpenSavedSearch {
+    pub name: String,
+}
+
+/// Deletes a previously saved project search.
+#[derive(PartialEq, Clone, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = project_search)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteSavedSearch {
+    pub name: String,
+}
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakEntity<Project>, ProjectSearchSettings>);
 
@@ -97,6 +133,51 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, window, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, window, cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleStructural, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::STRUCTURAL, window, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSmartCase, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::SMART_CASE, window, cx);
+            },
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleFuzzy, window, cx| {
+            search_bar.toggle_search_option(SearchOptions::FUZZY, window, cx);
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSearchWithinResults, _window, cx| {
+                search_bar.toggle_search_within_results(cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &SaveSearch, _window, cx| {
+                search_bar.save_search(action, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &OpenSavedSearch, window, cx| {
+                search_bar.open_saved_search(action, window, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, action: &DeleteSavedSearch, _window, cx| {
+                search_bar.delete_saved_search(action, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSemantic, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::SEMANTIC, window, cx);
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
@@ -219,6 +300,7 @@ pub struct ProjectSearchView {
     filters_enabled: bool,
     replace_enabled: bool,
     included_opened_only: bool,
+    search_within_results: bool,
     regex_language: Option<Arc<Language>>,
     _subscriptions: Vec<Subscription>,
 }
@@ -287,8 +369,8 @@ impl ProjectSearch {
         }
     }
 
-    fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
-        let search = self.project.update(cx, |project, cx| {
+    fn add_to_search_history(&mut self, query: &SearchQuery, cx: &mut Context<Self>) {
+        self.project.update(cx, |project, _| {
             project
                 .search_history_mut(SearchInputKind::Query)
                 .add(&mut self.search_history_cursor, query.as_str().to_string());
@@ -304,8 +386,14 @@ impl ProjectSearch {
                     .search_history_mut(SearchInputKind::Exclude)
                     .add(&mut self.search_excluded_history_cursor, excluded);
             }
-            project.search(query.clone(), cx)
         });
+    }
+
+    fn search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
+        self.add_to_search_history(&query, cx);
+        let search = self
+            .project
+            .update(cx, |project, cx| project.search(query.clone(), cx));
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
         self.active_query = Some(query);
@@ -380,6 +468,206 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+    /// Searches the project's semantic index for the code closest in meaning to the query.
+    fn semantic_search(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
+        self.add_to_search_history(&query, cx);
+        let project_index = cx.has_global::<SemanticDb>().then(|| {
+            let project = self.project.clone();
+            cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+                semantic_db.project_index(project, cx)
+            })
+        });
+        let search = project_index.flatten().map(|project_index| {
+            project_index.read(cx).search(
+                vec![query.as_str().to_string()],
+                SEMANTIC_SEARCH_LIMIT,
+                cx,
+            )
+        });
+        self.last_search_query_text = Some(query.as_str().to_string());
+        self.search_id += 1;
+        self.active_query = Some(query);
+        self.match_ranges.clear();
+        let project = self.project.clone();
+        self.pending_search = Some(cx.spawn(async move |project_search, cx| {
+            project_search
+                .update(cx, |project_search, cx| {
+                    project_search.match_ranges.clear();
+                    project_search
+                        .excerpts
+                        .update(cx, |excerpts, cx| excerpts.clear(cx));
+                    project_search.no_results = Some(true);
+                    project_search.limit_reached = false;
+                })
+                .ok()?;
+
+            let results = match search {
+                Some(search) => search.await.log_err().unwrap_or_default(),
+                None => Vec::new(),
+            };
+            // Results are sorted by score, so keep files in the order of their best match.
+            let mut ranges_by_path = IndexMap::<ProjectPath, Vec<Range<usize>>>::default();
+            for result in results {
+                let Ok(worktree_id) = result.worktree.read_with(cx, |worktree, _| worktree.id())
+                else {
+                    continue;
+                };
+                ranges_by_path
+                    .entry(ProjectPath {
+                        worktree_id,
+                        path: result.path,
+                    })
+                    .or_default()
+                    .push(result.range);
+            }
+
+            for (project_path, mut ranges) in ranges_by_path {
+                let Some(buffer) = project
+                    .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                    .ok()?
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+                ranges.sort_by_key(|range| range.start);
+                // The index may lag behind the file's contents, so clip the ranges to the buffer.
+                let ranges = buffer
+                    .read_with(cx, |buffer, _| {
+                        ranges
+                            .into_iter()
+                            .map(|range| {
+                                let start = buffer.clip_offset(range.start, Bias::Left);
+                                let end = buffer.clip_offset(range.end, Bias::Right);
+                                buffer.anchor_after(start)..buffer.anchor_before(end)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .ok()?;
+                let new_ranges = project_search
+                    .update(cx, |project_search, cx| {
+                        project_search.excerpts.update(cx, |excerpts, cx| {
+                            excerpts.set_anchored_excerpts_for_path(
+                                buffer,
+                                ranges,
+                                multibuffer_context_lines(cx),
+                                cx,
+                            )
+                        })
+                    })
+                    .ok()?
+                    .await;
+                project_search
+                    .update(cx, |project_search, cx| {
+                        project_search.match_ranges.extend(new_ranges);
+                        cx.notify();
+                    })
+                    .ok()?;
+            }
+
+            project_search
+                .update(cx, |project_search, cx| {
+                    if !project_search.match_ranges.is_empty() {
+                        project_search.no_results = Some(false);
+                    }
+                    project_search.pending_search.take();
+                    cx.notify();
+                })
+                .ok()?;
+
+            None
+        }));
+        cx.notify();
+    }
+
+    /// Runs the query over the excerpts of the current results only, replacing them with the new matches.
+    fn search_within_results(&mut self, query: SearchQuery, cx: &mut Context<Self>) {
+        self.add_to_search_history(&query, cx);
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let excerpts = snapshot
+            .excerpts()
+            .map(|(_, buffer, range)| (buffer.clone(), range.context.to_offset(buffer)))
+            .collect::<Vec<_>>();
+        self.last_search_query_text = Some(query.as_str().to_string());
+        self.search_id += 1;
+        self.active_query = Some(query.clone());
+        self.match_ranges.clear();
+        self.pending_search = Some(cx.spawn(async move |project_search, cx| {
+            let mut ranges_by_buffer =
+                IndexMap::<BufferId, Vec<Range<language::Anchor>>>::default();
+            for (buffer, range) in excerpts {
+                let ranges = query.search(&buffer, Some(range.clone())).await;
+                if ranges.is_empty() {
+                    continue;
+                }
+                ranges_by_buffer
+                    .entry(buffer.remote_id())
+                    .or_default()
+                    .extend(ranges.into_iter().map(|match_range| {
+                        buffer.anchor_after(range.start + match_range.start)
+                            ..buffer.anchor_before(range.start + match_range.end)
+                    }));
+            }
+
+            let buffers_with_ranges = project_search
+                .update(cx, |project_search, cx| {
+                    let excerpts = project_search.excerpts.read(cx);
+                    let buffers_with_ranges = ranges_by_buffer
+                        .into_iter()
+                        .filter_map(|(buffer_id, ranges)| {
+                            Some((excerpts.buffer(buffer_id)?, ranges))
+                        })
+                        .collect::<Vec<_>>();
+                    project_search.match_ranges.clear();
+                    project_search
+                        .excerpts
+                        .update(cx, |excerpts, cx| excerpts.clear(cx));
+                    project_search.no_results = Some(true);
+                    project_search.limit_reached = false;
+                    buffers_with_ranges
+                })
+                .ok()?;
+
+            let mut new_ranges = project_search
+                .update(cx, |project_search, cx| {
+                    project_search.excerpts.update(cx, |excerpts, cx| {
+                        buffers_with_ranges
+                            .into_iter()
+                            .map(|(buffer, ranges)| {
+                                excerpts.set_anchored_excerpts_for_path(
+                                    buffer,
+                                    ranges,
+                                    multibuffer_context_lines(cx),
+                                    cx,
+                                )
+                            })
+                            .collect::<FuturesOrdered<_>>()
+                    })
+                })
+                .ok()?;
+            while let Some(new_ranges) = new_ranges.next().await {
+                project_search
+                    .update(cx, |project_search, cx| {
+                        project_search.match_ranges.extend(new_ranges);
+                        cx.notify();
+                    })
+                    .ok()?;
+            }
+
+            project_search
+                .update(cx, |project_search, cx| {
+                    if !project_search.match_ranges.is_empty() {
+                        project_search.no_results = Some(false);
+                    }
+                    project_search.pending_search.take();
+                    cx.notify();
+                })
+                .ok()?;
+
+            None
+        }));
+        cx.notify();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -668,7 +956,7 @@ impl ProjectSearchView {
     }
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut Context<Self>) {
-        self.search_options.toggle(option);
+        self.search_options.toggle_exclusive(option);
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.entity.read(cx).project.downgrade(),
@@ -682,6 +970,96 @@ impl ProjectSearchView {
         self.included_opened_only = !self.included_opened_only;
     }
 
+    fn toggle_search_within_results(&mut self, cx: &mut Context<Self>) {
+        self.search_within_results = !self.search_within_results;
+        cx.notify();
+    }
+
+    fn saved_searches(&self, cx: &App) -> Vec<SavedSearch> {
+        let Some(workspace_id) = self.workspace_id(cx) else {
+            return Vec::new();
+        };
+        WORKSPACE_DB
+            .saved_searches(workspace_id)
+            .log_err()
+            .unwrap_or_default()
+    }
+
+    fn workspace_id(&self, cx: &App) -> Option<WorkspaceId> {
+        self.workspace
+            .read_with(cx, |workspace, _| workspace.database_id())
+            .ok()
+            .flatten()
+    }
+
+    fn save_search(&mut self, name: Option<String>, cx: &mut Context<Self>) {
+        let query = self.search_query_text(cx);
+        if query.is_empty() {
+            return;
+        }
+        let Some(workspace_id) = self.workspace_id(cx) else {
+            return;
+        };
+        let (files_to_include, files_to_exclude) = if self.filters_enabled {
+            (
+                self.included_files_editor.read(cx).text(cx),
+                self.excluded_files_editor.read(cx).text(cx),
+            )
+        } else {
+            Default::default()
+        };
+        let search = SavedSearch {
+            name: name.unwrap_or_else(|| query.clone()),
+            query,
+            files_to_include,
+            files_to_exclude,
+            search_options: self.search_options.bits(),
+        };
+        cx.background_spawn(async move { WORKSPACE_DB.save_search(workspace_id, search).await })
+            .detach_and_log_err(cx);
+    }
+
+    fn open_saved_search(
+        &mut self,
+        name: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<anyhow::Result<()>>> {
+        let search = self
+            .saved_searches(cx)
+            .into_iter()
+            .find(|search| search.name == name)?;
+        self.set_search_editor(SearchInputKind::Query, &search.query, window, cx);
+        self.set_search_editor(
+            SearchInputKind::Include,
+            &search.files_to_include,
+            window,
+            cx,
+        );
+        self.set_search_editor(
+            SearchInputKind::Exclude,
+            &search.files_to_exclude,
+            window,
+            cx,
+        );
+        self.filters_enabled =
+            !search.files_to_include.is_empty() || !search.files_to_exclude.is_empty();
+        self.search_options = SearchOptions::from_bits_truncate(search.search_options);
+        self.search_within_results = false;
+        self.adjust_query_regex_language(cx);
+        Some(self.prompt_to_save_if_dirty_then_search(window, cx))
+    }
+
+    fn delete_saved_search(&mut self, name: String, cx: &mut Context<Self>) {
+        let Some(workspace_id) = self.workspace_id(cx) else {
+            return;
+        };
+        cx.background_spawn(
+            async move { WORKSPACE_DB.delete_saved_search(workspace_id, name).await },
+        )
+        .detach_and_log_err(cx);
+    }
+
     fn replace_next(&mut self, _: &ReplaceNext, window: &mut Window, cx: &mut Context<Self>) {
         if self.entity.read(cx).match_ranges.is_empty() {
             return;
@@ -888,6 +1266,7 @@ impl ProjectSearchView {
             filters_enabled,
             replace_enabled: false,
             included_opened_only: false,
+            search_within_results: false,
             regex_language: None,
             _subscriptions: subscriptions,
         };
@@ -1130,7 +1509,19 @@ impl ProjectSearchView {
 
     fn search(&mut self, cx: &mut Context<Self>) {
         if let Some(query) = self.build_search_query(cx) {
-            self.entity.update(cx, |model, cx| model.search(query, cx));
+            let semantic = self.search_options.contains(SearchOptions::SEMANTIC)
+                && SemanticDb::enabled_for_project(&self.entity.read(cx).project, cx);
+            let within_results =
+                self.search_within_results && !self.entity.read(cx).match_ranges.is_empty();
+            self.entity.update(cx, |model, cx| {
+                if semantic {
+                    model.semantic_search(query, cx)
+                } else if within_results {
+                    model.search_within_results(query, cx)
+                } else {
+                    model.search(query, cx)
+                }
+            });
         }
     }
 
@@ -1209,11 +1600,33 @@ impl ProjectSearchView {
             .count()
             > 1;
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
-            match SearchQuery::regex(
+        let case_sensitive = self.search_options.contains(SearchOptions::CASE_SENSITIVE)
+            || (self.search_options.contains(SearchOptions::SMART_CASE)
+                && contains_uppercase(&text));
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                match_full_paths,
+                open_buffers,
+            )
+        } else if self.search_options.contains(SearchOptions::FUZZY) {
+            SearchQuery::fuzzy(
+                text,
+                case_sensitive,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                match_full_paths,
+                open_buffers,
+            )
+        } else if self.search_options.contains(SearchOptions::REGEX) {
+            SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
-                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                case_sensitive,
                 self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
                 self.search_options
                     .contains(SearchOptions::ONE_MATCH_PER_LINE),
@@ -1221,55 +1634,37 @@ impl ProjectSearchView {
                 excluded_files,
                 match_full_paths,
                 open_buffers,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error.is_some() {
-                        cx.notify();
-                    }
-
-                    Some(query)
-                }
-                Err(e) => {
-                    let should_mark_error = self
-                        .panels_with_errors
-                        .insert(InputPanel::Query, e.to_string());
-                    if should_mark_error.is_none() {
-                        cx.notify();
-                    }
-
-                    None
-                }
-            }
+            )
         } else {
-            match SearchQuery::text(
+            SearchQuery::text(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
-                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                case_sensitive,
                 self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
                 included_files,
                 excluded_files,
                 match_full_paths,
                 open_buffers,
-            ) {
-                Ok(query) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
-                    if should_unmark_error.is_some() {
-                        cx.notify();
-                    }
-
-                    Some(query)
+            )
+        };
+        let query = match query {
+            Ok(query) => {
+                let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                if should_unmark_error.is_some() {
+                    cx.notify();
                 }
-                Err(e) => {
-                    let should_mark_error = self
-                        .panels_with_errors
-                        .insert(InputPanel::Query, e.to_string());
-                    if should_mark_error.is_none() {
-                        cx.notify();
-                    }
 
-                    None
+                Some(query)
+            }
+            Err(e) => {
+                let should_mark_error = self
+                    .panels_with_errors
+                    .insert(InputPanel::Query, e.to_string());
+                if should_mark_error.is_none() {
+                    cx.notify();
                 }
+
+                None
             }
         };
         if !self.panels_with_errors.is_empty() {
@@ -1486,6 +1881,21 @@ impl ProjectSearchView {
                         window.dispatch_action(ToggleRegex.boxed_clone(), cx)
                     }),
             )
+            .child(
+                Button::new("structural", "Match with structural patterns")
+                    .icon(IconName::ListTree)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .key_binding(KeyBinding::for_action_in(
+                        &ToggleStructural,
+                        &focus_handle,
+                        window,
+                        cx,
+                    ))
+                    .on_click(|_event, window, cx| {
+                        window.dispatch_action(ToggleStructural.boxed_clone(), cx)
+                    }),
+            )
             .child(
                 Button::new("match-case", "Match case")
                     .icon(IconName::CaseSensitive)
@@ -1766,6 +2176,123 @@ impl ProjectSearchBar {
         true
     }
 
+    fn toggle_search_within_results(&mut self, cx: &mut Context<Self>) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_search_within_results(cx);
+            });
+            cx.notify();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_search_within_results_enabled(&self, cx: &App) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.read(cx).search_within_results
+        } else {
+            false
+        }
+    }
+
+    fn save_search(&mut self, action: &SaveSearch, cx: &mut Context<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.save_search(action.name.clone(), cx);
+            });
+        }
+    }
+
+    fn open_saved_search(
+        &mut self,
+        action: &OpenSavedSearch,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                if let Some(task) = search_view.open_saved_search(&action.name, window, cx) {
+                    task.detach_and_log_err(cx);
+                }
+            });
+            cx.notify();
+        }
+    }
+
+    fn delete_saved_search(&mut self, action: &DeleteSavedSearch, cx: &mut Context<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.delete_saved_search(action.name.clone(), cx);
+            });
+        }
+    }
+
+    fn render_saved_searches_menu(&self) -> impl IntoElement {
+        let search_view = self
+            .active_project_search
+            .as_ref()
+            .map(|search_view| search_view.downgrade());
+        PopoverMenu::new("project-search-saved-searches")
+            .trigger_with_tooltip(
+                IconButton::new("project-search-saved-searches-button", IconName::Star)
+                    .shape(IconButtonShape::Square),
+                Tooltip::text("Saved Searches"),
+            )
+            .anchor(Corner::TopRight)
+            .menu(move |window, cx| {
+                let search_view = search_view.clone()?.upgrade()?;
+                let saved_searches = search_view.read(cx).saved_searches(cx);
+                let search_view = search_view.downgrade();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    if !saved_searches.is_empty() {
+                        menu = menu.header("Saved Searches");
+                    }
+                    for saved_search in saved_searches {
+                        let name = saved_search.name;
+                        menu = menu.entry_with_end_slot_on_hover(
+                            name.clone(),
+                            None,
+                            {
+                                let search_view = search_view.clone();
+                                let name = name.clone();
+                                move |window, cx| {
+                                    search_view
+                                        .update(cx, |search_view, cx| {
+                                            if let Some(task) =
+                                                search_view.open_saved_search(&name, window, cx)
+                                            {
+                                                task.detach_and_log_err(cx);
+                                            }
+                                        })
+                                        .ok();
+                                }
+                            },
+                            IconName::Trash,
+                            "Delete Saved Search".into(),
+                            {
+                                let search_view = search_view.clone();
+                                move |_, cx| {
+                                    search_view
+                                        .update(cx, |search_view, cx| {
+                                            search_view.delete_saved_search(name.clone(), cx);
+                                        })
+                                        .ok();
+                                }
+                            },
+                        );
+                    }
+                    let search_view = search_view.clone();
+                    menu.separator()
+                        .entry("Save Current Search", None, move |_, cx| {
+                            search_view
+                                .update(cx, |search_view, cx| search_view.save_search(None, cx))
+                                .ok();
+                        })
+                }))
+            })
+    }
+
     fn is_opened_only_enabled(&self, cx: &App) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.read(cx).included_opened_only
@@ -1923,6 +2450,7 @@ impl Render for ProjectSearchBar {
         let theme_colors = cx.theme().colors();
         let project_search = search.entity.read(cx);
         let limit_reached = project_search.limit_reached;
+        let semantic_index_enabled = SemanticDb::enabled_for_project(&project_search.project, cx);
 
         let color_override = match (
             &project_search.pending_search,
@@ -1969,6 +2497,11 @@ impl Render for ProjectSearchBar {
                         SearchSource::Project(cx),
                         focus_handle.clone(),
                     ))
+                    .child(SearchOption::SmartCase.as_button(
+                        search.search_options,
+                        SearchSource::Project(cx),
+                        focus_handle.clone(),
+                    ))
                     .child(SearchOption::WholeWord.as_button(
                         search.search_options,
                         SearchSource::Project(cx),
@@ -1978,7 +2511,24 @@ impl Render for ProjectSearchBar {
                         search.search_options,
                         SearchSource::Project(cx),
                         focus_handle.clone(),
-                    )),
+                    ))
+                    .child(SearchOption::Structural.as_button(
+                        search.search_options,
+                        SearchSource::Project(cx),
+                        focus_handle.clone(),
+                    ))
+                    .child(SearchOption::Fuzzy.as_button(
+                        search.search_options,
+                        SearchSource::Project(cx),
+                        focus_handle.clone(),
+                    ))
+                    .when(semantic_index_enabled, |this| {
+                        this.child(SearchOption::Semantic.as_button(
+                            search.search_options,
+                            SearchSource::Project(cx),
+                            focus_handle.clone(),
+                        ))
+                    }),
             );
 
         let query_focus = search.query_editor.focus_handle(cx);
@@ -2071,6 +2621,16 @@ impl Render for ProjectSearchBar {
                 &ToggleReplace,
                 focus_handle.clone(),
             ))
+            .child(render_action_button(
+                "project-search-within-results",
+                IconName::ListFilter,
+                self.is_search_within_results_enabled(cx)
+                    .then_some(ActionButtonState::Toggled),
+                "Search Within Results",
+                &ToggleSearchWithinResults,
+                focus_handle.clone(),
+            ))
+            .child(self.render_saved_searches_menu())
             .child(matches_column);
 
         let search_line = h_flex()
@@ -4127,6 +4687,139 @@ pub mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_search_within_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+        let results_text = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, _, cx| {
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx))
+                })
+                .unwrap()
+        };
+        let match_count =
+            |cx: &mut TestAppContext| search.read_with(cx, |search, _| search.match_ranges.len());
+
+        perform_search(search_view, "TWO", cx);
+        assert_eq!(match_count(cx), 3);
+
+        search_view
+            .update(cx, |search_view, _, cx| {
+                search_view.toggle_search_within_results(cx)
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+            "Only the files of the previous results should be searched"
+        );
+        assert_eq!(match_count(cx), 6);
+
+        perform_search(search_view, "THREE", cx);
+        assert_eq!(
+            results_text(cx),
+            "\n\nconst THREE: usize = one::ONE + two::TWO;",
+            "Each search should narrow down the results of the previous one"
+        );
+        assert_eq!(match_count(cx), 1);
+
+        search_view
+            .update(cx, |search_view, _, cx| {
+                search_view.toggle_search_within_results(cx)
+            })
+            .unwrap();
+        perform_search(search_view, "ONE", cx);
+        assert_eq!(
+            match_count(cx),
+            9,
+            "The whole project should be searched again once toggled off"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_smart_case(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let window = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+        let workspace = window.root(cx).unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+        let case_sensitive_and_match_count = |cx: &mut TestAppContext| {
+            search.read_with(cx, |search, _| {
+                (
+                    search.active_query.as_ref().unwrap().case_sensitive(),
+                    search.match_ranges.len(),
+                )
+            })
+        };
+
+        search_view
+            .update(cx, |search_view, _, cx| {
+                search_view.toggle_search_option(SearchOptions::SMART_CASE, cx)
+            })
+            .unwrap();
+        perform_search(search_view, "one", cx);
+        assert_eq!(
+            case_sensitive_and_match_count(cx),
+            (false, 5),
+            "A lowercase query should ignore case"
+        );
+        perform_search(search_view, "ONE", cx);
+        assert_eq!(
+            case_sensitive_and_match_count(cx),
+            (true, 3),
+            "A query with an uppercase letter should match case"
+        );
+        perform_search(search_view, "One", cx);
+        assert_eq!(case_sensitive_and_match_count(cx), (true, 0));
+
+        search_view
+            .update(cx, |search_view, _, cx| {
+                search_view.toggle_search_option(SearchOptions::SMART_CASE, cx)
+            })
+            .unwrap();
+        perform_search(search_view, "One", cx);
+        assert_eq!(
+            case_sensitive_and_match_count(cx),
+            (false, 5),
+            "Without smart case, uppercase letters should not make the query case sensitive"
+        );
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
@@ -37,6 +37,14 @@ actions!(
         ToggleIncludeIgnored,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles smart case, which makes the search case-sensitive only when the query contains uppercase characters.
+        ToggleSmartCase,
+        /// Toggles fuzzy search, which matches identifiers containing the query's characters in order.
+        ToggleFuzzy,
+        /// Toggles structural search, which matches syntax trees against patterns like `$X.unwrap()`.
+        ToggleStructural,
+        /// Toggles semantic search, which finds code by meaning using the project's semantic index.
+        ToggleSemantic,
         /// Toggles the replace interface.
         ToggleReplace,
         /// Toggles searching within selection only.
@@ -62,7 +70,7 @@ actions!(
 
 bitflags! {
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
-    pub struct SearchOptions: u8 {
+    pub struct SearchOptions: u16 {
         const NONE = 0;
         const WHOLE_WORD = 1 << SearchOption::WholeWord as u8;
         const CASE_SENSITIVE = 1 << SearchOption::CaseSensitive as u8;
@@ -71,6 +79,10 @@ bitflags! {
         const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 1 << SearchOption::Backwards as u8;
+        const SEMANTIC = 1 << SearchOption::Semantic as u8;
+        const STRUCTURAL = 1 << SearchOption::Structural as u8;
+        const SMART_CASE = 1 << SearchOption::SmartCase as u8;
+        const FUZZY = 1 << SearchOption::Fuzzy as u8;
     }
 }
 
@@ -83,6 +95,10 @@ pub enum SearchOption {
     Regex,
     OneMatchPerLine,
     Backwards,
+    Semantic,
+    Structural,
+    SmartCase,
+    Fuzzy,
 }
 
 pub(crate) enum SearchSource<'a, 'b> {
@@ -92,7 +108,7 @@ pub(crate) enum SearchSource<'a, 'b> {
 
 impl SearchOption {
     pub fn as_options(&self) -> SearchOptions {
-        SearchOptions::from_bits(1 << *self as u8).unwrap()
+        SearchOptions::from_bits(1 << *self as u16).unwrap()
     }
 
     pub fn label(&self) -> &'static str {
@@ -103,6 +119,10 @@ impl SearchOption {
             SearchOption::Regex => "Use Regular Expressions",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
+            SearchOption::Semantic => "Search by Meaning",
+            SearchOption::Structural => "Use Structural Patterns",
+            SearchOption::SmartCase => "Use Smart Case",
+            SearchOption::Fuzzy => "Match Identifiers Fuzzily",
         }
     }
 
@@ -112,6 +132,10 @@ impl SearchOption {
             SearchOption::CaseSensitive => ui::IconName::CaseSensitive,
             SearchOption::IncludeIgnored => ui::IconName::Sliders,
             SearchOption::Regex => ui::IconName::Regex,
+            SearchOption::Semantic => ui::IconName::Sparkle,
+            SearchOption::Structural => ui::IconName::ListTree,
+            SearchOption::SmartCase => ui::IconName::FontSize,
+            SearchOption::Fuzzy => ui::IconName::Crosshair,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +146,10 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::Semantic => &ToggleSemantic,
+            SearchOption::Structural => &ToggleStructural,
+            SearchOption::SmartCase => &ToggleSmartCase,
+            SearchOption::Fuzzy => &ToggleFuzzy,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -174,7 +202,9 @@ impl SearchOptions {
         options.set(SearchOptions::WHOLE_WORD, query.whole_word());
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
-        options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::REGEX, query.is_regex() && !query.is_fuzzy());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
+        options.set(SearchOptions::FUZZY, query.is_fuzzy());
         options
     }
 
@@ -186,6 +216,20 @@ impl SearchOptions {
         options.set(SearchOptions::REGEX, settings.regex);
         options
     }
+
+    /// Options that change how the query text is interpreted, at most one of which can be enabled.
+    pub const QUERY_SYNTAX: SearchOptions = SearchOptions::REGEX
+        .union(SearchOptions::STRUCTURAL)
+        .union(SearchOptions::FUZZY);
+
+    /// Toggles `option`. Regular expressions, structural patterns and fuzzy queries are
+    /// alternative query syntaxes, so enabling one of them disables the others.
+    pub fn toggle_exclusive(&mut self, option: SearchOptions) {
+        self.toggle(option);
+        if self.contains(option) && Self::QUERY_SYNTAX.contains(option) {
+            self.remove(Self::QUERY_SYNTAX.difference(option));
+        }
+    }
 }
 
 pub(crate) fn show_no_more_matches(window: &mut Window, cx: &mut App) {
@@ -50,7 +50,7 @@ fn main() {
 
         let embedding_provider = Arc::new(OpenAiEmbeddingProvider::new(
             http,
-            OpenAiEmbeddingModel::TextEmbedding3Small,
+            OpenAiEmbeddingModel::TextEmbedding3Small.id().to_string(),
             open_ai::OPEN_AI_API_URL.to_string(),
             api_key,
         ));
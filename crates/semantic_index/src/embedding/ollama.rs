@@ -6,14 +6,10 @@ use std::sync::Arc;
 
 use crate::{Embedding, EmbeddingProvider, TextToEmbed};
 
-pub enum OllamaEmbeddingModel {
-    NomicEmbedText,
-    MxbaiEmbedLarge,
-}
-
 pub struct OllamaEmbeddingProvider {
     client: Arc<dyn HttpClient>,
-    model: OllamaEmbeddingModel,
+    api_url: String,
+    model: String,
 }
 
 #[derive(Serialize)]
@@ -28,32 +24,29 @@ struct OllamaEmbeddingResponse {
 }
 
 impl OllamaEmbeddingProvider {
-    pub fn new(client: Arc<dyn HttpClient>, model: OllamaEmbeddingModel) -> Self {
-        Self { client, model }
+    pub fn new(client: Arc<dyn HttpClient>, api_url: String, model: String) -> Self {
+        Self {
+            client,
+            api_url,
+            model,
+        }
     }
 }
 
 impl EmbeddingProvider for OllamaEmbeddingProvider {
     fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
-        //
-        let model = match self.model {
-            OllamaEmbeddingModel::NomicEmbedText => "nomic-embed-text",
-            OllamaEmbeddingModel::MxbaiEmbedLarge => "mxbai-embed-large",
-        };
-
         futures::future::try_join_all(texts.iter().map(|to_embed| {
             let request = OllamaEmbeddingRequest {
-                model: model.to_string(),
+                model: self.model.clone(),
                 prompt: to_embed.text.to_string(),
             };
 
             let request = serde_json::to_string(&request).unwrap();
 
-            async {
-                let response = self
-                    .client
-                    .post_json("http://localhost:11434/api/embeddings", request.into())
-                    .await?;
+            let uri = format!("{}/api/embeddings", self.api_url.trim_end_matches('/'));
+
+            async move {
+                let response = self.client.post_json(&uri, request.into()).await?;
 
                 let mut body = String::new();
                 response.into_body().read_to_string(&mut body).await?;
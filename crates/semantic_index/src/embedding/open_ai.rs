@@ -5,9 +5,11 @@ use http_client::HttpClient;
 pub use open_ai::OpenAiEmbeddingModel;
 use std::sync::Arc;
 
+/// Computes embeddings with OpenAI, or with any server implementing
+/// OpenAI's `/embeddings` endpoint.
 pub struct OpenAiEmbeddingProvider {
     client: Arc<dyn HttpClient>,
-    model: OpenAiEmbeddingModel,
+    model: String,
     api_url: String,
     api_key: String,
 }
@@ -15,7 +17,7 @@ pub struct OpenAiEmbeddingProvider {
 impl OpenAiEmbeddingProvider {
     pub fn new(
         client: Arc<dyn HttpClient>,
-        model: OpenAiEmbeddingModel,
+        model: String,
         api_url: String,
         api_key: String,
     ) -> Self {
@@ -34,7 +36,7 @@ impl EmbeddingProvider for OpenAiEmbeddingProvider {
             self.client.as_ref(),
             &self.api_url,
             &self.api_key,
-            self.model,
+            &self.model,
             texts.iter().map(|to_embed| to_embed.text),
         );
         async move {
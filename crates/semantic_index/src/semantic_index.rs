@@ -4,16 +4,23 @@ mod embedding_index;
 mod indexing;
 mod project_index;
 mod project_index_debug_view;
+mod semantic_index_settings;
 mod summary_backlog;
 mod summary_index;
 mod worktree_index;
 
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 use collections::HashMap;
+use credentials_provider::CredentialsProvider;
 use fs::Fs;
-use gpui::{App, AppContext as _, AsyncApp, BorrowAppContext, Context, Entity, Global, WeakEntity};
+use gpui::{
+    App, AppContext as _, AsyncApp, BorrowAppContext, Context, Entity, Global, Task, WeakEntity,
+};
+use http_client::HttpClient;
 use language::LineEnding;
+use language_models::{AllLanguageModelSettings, provider::open_ai_compatible};
 use project::{Project, Worktree};
+use settings::{Settings as _, SettingsLocation, SettingsStore};
 use std::{
     cmp::Ordering,
     path::{Path, PathBuf},
@@ -25,8 +32,155 @@ use workspace::Workspace;
 pub use embedding::*;
 pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
+pub use semantic_index_settings::{EmbeddingProviderSettings, SemanticIndexSettings};
 pub use summary_index::FileSummary;
 
+pub fn init(http_client: Arc<dyn HttpClient>, cx: &mut App) {
+    SemanticIndexSettings::register(cx);
+
+    cx.set_global(SemanticDbLoader {
+        http_client,
+        provider_settings: SemanticIndexSettings::get_global(cx)
+            .embedding_provider
+            .clone(),
+        _load: None,
+    });
+
+    cx.observe_global::<SettingsStore>(|cx| {
+        let provider_settings = SemanticIndexSettings::get_global(cx)
+            .embedding_provider
+            .clone();
+        let loader = cx.global_mut::<SemanticDbLoader>();
+        if loader.provider_settings != provider_settings {
+            // Embeddings computed by different models can't be compared with
+            // each other, so start over with the database of the new model.
+            loader.provider_settings = provider_settings;
+            loader._load = None;
+            if cx.has_global::<SemanticDb>() {
+                cx.remove_global::<SemanticDb>();
+            }
+        }
+        SemanticDb::refresh_all_projects(cx);
+    })
+    .detach();
+
+    cx.observe_new(
+        |workspace: &mut Workspace, _window, cx: &mut Context<Workspace>| {
+            let project = workspace.project().clone();
+            cx.subscribe(&project, |_, project, event, cx| {
+                if let project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) = event
+                {
+                    SemanticDb::refresh_project(project, cx);
+                }
+            })
+            .detach();
+            SemanticDb::refresh_project(project, cx);
+        },
+    )
+    .detach();
+}
+
+/// Opens the [`SemanticDb`] the first time a project enables semantic indexing.
+struct SemanticDbLoader {
+    http_client: Arc<dyn HttpClient>,
+    provider_settings: EmbeddingProviderSettings,
+    _load: Option<Task<()>>,
+}
+
+impl Global for SemanticDbLoader {}
+
+impl SemanticDbLoader {
+    fn load(cx: &mut App) {
+        let Some(loader) = cx.try_global::<SemanticDbLoader>() else {
+            return;
+        };
+        if loader._load.is_some() {
+            return;
+        }
+
+        let provider_settings = loader.provider_settings.clone();
+        let embedding_provider =
+            embedding_provider(&provider_settings, loader.http_client.clone(), cx);
+        let db_path = paths::embeddings_dir().join(format!(
+            "semantic-index-db.{}.mdb",
+            provider_settings.db_name()
+        ));
+        let load = cx.spawn(async move |cx| {
+            let result = async {
+                let embedding_provider = embedding_provider.await?;
+                SemanticDb::new(db_path, embedding_provider, cx).await
+            }
+            .await;
+            cx.update(|cx| match result {
+                Ok(semantic_db) => {
+                    if cx.global::<SemanticDbLoader>().provider_settings == provider_settings {
+                        cx.set_global(semantic_db);
+                        SemanticDb::refresh_all_projects(cx);
+                    }
+                }
+                Err(error) => log::error!("failed to open the semantic index: {error:#}"),
+            })
+            .ok();
+        });
+        cx.global_mut::<SemanticDbLoader>()._load = Some(load);
+    }
+}
+
+fn embedding_provider(
+    settings: &EmbeddingProviderSettings,
+    http_client: Arc<dyn HttpClient>,
+    cx: &App,
+) -> Task<Result<Arc<dyn EmbeddingProvider>>> {
+    let language_model_settings = AllLanguageModelSettings::get_global(cx);
+    match settings {
+        EmbeddingProviderSettings::Ollama { model } => {
+            Task::ready(Ok(Arc::new(OllamaEmbeddingProvider::new(
+                http_client,
+                language_model_settings.ollama.api_url.clone(),
+                model.clone(),
+            ))))
+        }
+        EmbeddingProviderSettings::LmStudio { model } => {
+            Task::ready(Ok(Arc::new(LmStudioEmbeddingProvider::new(
+                http_client,
+                language_model_settings.lmstudio.api_url.clone(),
+                model.clone(),
+            ))))
+        }
+        EmbeddingProviderSettings::OpenAiCompatible { name, model } => {
+            let Some(provider_settings) = language_model_settings.openai_compatible.get(name)
+            else {
+                return Task::ready(Err(anyhow!(
+                    "no provider named {name:?} in `language_models.openai_compatible`"
+                )));
+            };
+            let api_url = provider_settings.api_url.clone();
+            let env_var_name = open_ai_compatible::api_key_env_var_name(name);
+            let model = model.clone();
+            let credentials_provider = <dyn CredentialsProvider>::global(cx);
+            cx.spawn(async move |cx| {
+                let api_key = if let Ok(api_key) = std::env::var(&env_var_name) {
+                    api_key
+                } else {
+                    let (_, api_key) = credentials_provider
+                        .read_credentials(&api_url, cx)
+                        .await?
+                        .with_context(|| {
+                        format!("no API key set for {api_url}, nor in ${env_var_name}")
+                    })?;
+                    String::from_utf8(api_key).context("invalid API key")?
+                };
+                Ok(Arc::new(OpenAiEmbeddingProvider::new(
+                    http_client,
+                    model,
+                    api_url,
+                    api_key,
+                )) as Arc<dyn EmbeddingProvider>)
+            })
+        }
+    }
+}
+
 pub struct SemanticDb {
     embedding_provider: Arc<dyn EmbeddingProvider>,
     db_connection: Option<heed::Env>,
@@ -54,24 +208,6 @@ impl SemanticDb {
             .await
             .context("opening database connection")?;
 
-        cx.update(|cx| {
-            cx.observe_new(
-                |workspace: &mut Workspace, _window, cx: &mut Context<Workspace>| {
-                    let project = workspace.project().clone();
-
-                    if cx.has_global::<SemanticDb>() {
-                        cx.update_global::<SemanticDb, _>(|this, cx| {
-                            this.create_project_index(project, cx);
-                        })
-                    } else {
-                        log::info!("No SemanticDb, skipping project index")
-                    }
-                },
-            )
-            .detach();
-        })
-        .ok();
-
         Ok(SemanticDb {
             db_connection: Some(db_connection),
             embedding_provider,
@@ -203,6 +339,55 @@ impl SemanticDb {
         Ok(loaded_results)
     }
 
+    /// Whether semantic indexing is enabled in any of the project's visible worktrees.
+    pub fn enabled_for_project(project: &Entity<Project>, cx: &App) -> bool {
+        // Without either global, `init` wasn't called and the settings aren't registered.
+        if !cx.has_global::<SemanticDbLoader>() && !cx.has_global::<SemanticDb>() {
+            return false;
+        }
+        let project = project.read(cx);
+        project.is_local()
+            && project.visible_worktrees(cx).any(|worktree| {
+                SemanticIndexSettings::get(
+                    Some(SettingsLocation {
+                        worktree_id: worktree.read(cx).id(),
+                        path: Path::new(""),
+                    }),
+                    cx,
+                )
+                .enabled
+            })
+    }
+
+    /// Creates or drops the project's index, depending on whether the
+    /// settings enable semantic indexing for it.
+    pub fn refresh_project(project: Entity<Project>, cx: &mut App) {
+        let enabled = Self::enabled_for_project(&project, cx);
+        if !cx.has_global::<SemanticDb>() {
+            if enabled {
+                SemanticDbLoader::load(cx);
+            }
+            return;
+        }
+
+        cx.update_global::<SemanticDb, _>(|this, cx| {
+            let has_index = this.project_indices.contains_key(&project.downgrade());
+            if enabled && !has_index {
+                this.create_project_index(project, cx);
+            } else if !enabled && has_index {
+                this.project_indices.remove(&project.downgrade());
+            }
+        });
+    }
+
+    fn refresh_all_projects(cx: &mut App) {
+        for window in workspace::local_workspace_windows(cx) {
+            if let Ok(project) = window.read_with(cx, |workspace, _| workspace.project().clone()) {
+                Self::refresh_project(project, cx);
+            }
+        }
+    }
+
     pub fn project_index(
         &mut self,
         project: Entity<Project>,
@@ -287,6 +472,7 @@ mod tests {
             language::init(cx);
             cx.update_flags(false, vec![]);
             Project::init_settings(cx);
+            SemanticIndexSettings::register(cx);
             SettingsStore::update(cx, |store, cx| {
                 store.update_user_settings::<AllLanguageSettings>(cx, |_| {});
             });
@@ -327,6 +513,56 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    async fn test_project_index_follows_settings(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let semantic_db = SemanticDb::new(
+            temp_dir.path().into(),
+            Arc::new(FakeEmbeddingProvider),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        cx.update(|cx| cx.set_global(semantic_db));
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({ "main.rs": "fn main() {}" }))
+            .await;
+        let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+        let has_index = |cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                cx.update_global::<SemanticDb, _>(|semantic_db, cx| {
+                    semantic_db.project_index(project.clone(), cx).is_some()
+                })
+            })
+        };
+
+        cx.update(|cx| SemanticDb::refresh_project(project.clone(), cx));
+        assert!(!has_index(cx), "semantic indexing is disabled by default");
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<SemanticIndexSettings>(cx, |settings| {
+                    settings.enabled = true;
+                });
+            });
+            SemanticDb::refresh_project(project.clone(), cx);
+        });
+        assert!(has_index(cx));
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<SemanticIndexSettings>(cx, |settings| {
+                    settings.enabled = false;
+                });
+            });
+            SemanticDb::refresh_project(project.clone(), cx);
+        });
+        assert!(!has_index(cx));
+    }
+
     #[gpui::test]
     async fn test_search(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
@@ -0,0 +1,82 @@
+use gpui::App;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsUi};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, SettingsUi)]
+#[serde(default)]
+pub struct SemanticIndexSettings {
+    /// Whether to build a semantic index of the project, enabling the
+    /// `semantic_search` agent tool and the semantic project search mode.
+    ///
+    /// This can be enabled per project in `.zed/settings.json`.
+    ///
+    /// Default: false
+    pub enabled: bool,
+    /// The model used to compute the embeddings of the project's files.
+    ///
+    /// Default: {"provider": "ollama", "model": "nomic-embed-text"}
+    #[settings_ui(skip)]
+    pub embedding_provider: EmbeddingProviderSettings,
+}
+
+/// Which embedding model to index the project with. The API URLs of the
+/// providers are the ones configured under `language_models`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum EmbeddingProviderSettings {
+    /// Use an embedding model served by Ollama.
+    Ollama { model: String },
+    /// Use an embedding model served by LM Studio.
+    #[serde(rename = "lmstudio")]
+    LmStudio { model: String },
+    /// Use an embedding model served by one of the providers configured
+    /// in `language_models.openai_compatible`.
+    OpenAiCompatible {
+        /// The name of the provider in `language_models.openai_compatible`.
+        name: Arc<str>,
+        model: String,
+    },
+}
+
+impl Default for EmbeddingProviderSettings {
+    fn default() -> Self {
+        Self::Ollama {
+            model: "nomic-embed-text".into(),
+        }
+    }
+}
+
+impl EmbeddingProviderSettings {
+    /// A name identifying the embedding model, safe to use in a file name.
+    pub fn db_name(&self) -> String {
+        let (provider, model) = match self {
+            Self::Ollama { model } => ("ollama", model.as_str()),
+            Self::LmStudio { model } => ("lmstudio", model.as_str()),
+            Self::OpenAiCompatible { name, model } => (name.as_ref(), model.as_str()),
+        };
+        format!("{provider}-{model}")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+
+    fn import_from_vscode(_vscode: &settings::VsCodeSettings, _current: &mut Self::FileContent) {}
+}
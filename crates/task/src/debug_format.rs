@@ -93,6 +93,9 @@ pub struct LaunchRequest {
     pub args: Vec<String>,
     #[serde(default)]
     pub env: FxHashMap<String, String>,
+    /// A core dump of `program` to inspect instead of starting a new process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub core_file: Option<PathBuf>,
 }
 
 impl LaunchRequest {
@@ -133,6 +136,10 @@ impl DebugRequest {
                             .iter()
                             .map(|(k, v)| (k.clone(), v.clone()))
                             .collect(),
+                        core_file: launch_request
+                            .core_file
+                            .as_ref()
+                            .map(|core_file| core_file.to_string_lossy().into_owned()),
                     },
                 )),
             },
@@ -156,11 +163,13 @@ impl DebugRequest {
                 cwd,
                 args,
                 env,
+                core_file,
             }) => Ok(DebugRequest::Launch(LaunchRequest {
                 program,
                 cwd: cwd.map(From::from),
                 args,
                 env: env.into_iter().collect(),
+                core_file: core_file.map(From::from),
             })),
 
             proto::debug_request::Request::DebugAttachRequest(proto::DebugAttachRequest {
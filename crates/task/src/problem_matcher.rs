@@ -0,0 +1,494 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A problem matcher of a task template: either the name of a built-in matcher, such as `$rustc`, or a custom one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcherTemplate {
+    /// One of the built-in matchers: `$rustc`, `$tsc`, `$gcc` or `$eslint-stylish`.
+    Builtin(String),
+    Custom(ProblemMatcher),
+}
+
+impl ProblemMatcherTemplate {
+    /// Returns the matcher this template refers to, looking up built-in matchers by name.
+    pub fn resolve(&self) -> Result<ProblemMatcher> {
+        match self {
+            Self::Builtin(name) => builtin_problem_matcher(name)
+                .with_context(|| format!("Unknown problem matcher `{name}`")),
+            Self::Custom(matcher) => Ok(matcher.clone()),
+        }
+    }
+}
+
+/// Turns the output of a task into problems, reported as diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcher {
+    /// Patterns matched against consecutive lines of the task output.
+    /// A problem is reported once all of them matched; each pattern may fill in different parts of it.
+    pub pattern: Vec<ProblemPattern>,
+    /// Directory to resolve relative file paths against, defaults to the task's working directory.
+    #[serde(default)]
+    pub relative_to: Option<String>,
+    /// Severity of the problems whose pattern has no `severity` group.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+}
+
+/// A regular expression matching a line of task output, and the indices of its capture groups holding parts of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    pub regexp: String,
+    #[serde(default)]
+    pub file: Option<usize>,
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    #[serde(default)]
+    pub severity: Option<usize>,
+    #[serde(default)]
+    pub code: Option<usize>,
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern of a multi-line matcher may match several lines in a row,
+    /// each reporting a problem that shares the parts matched by the previous patterns.
+    #[serde(default, rename = "loop")]
+    pub is_loop: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl ProblemSeverity {
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "error" | "fatal" | "fatal error" | "e" => Some(Self::Error),
+            "warning" | "warn" | "w" => Some(Self::Warning),
+            "info" | "information" | "note" | "i" => Some(Self::Info),
+            "hint" | "help" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskProblem {
+    pub path: PathBuf,
+    /// 1-based line of the problem.
+    pub line: u32,
+    /// 1-based column of the problem, if reported.
+    pub column: Option<u32>,
+    pub end_line: Option<u32>,
+    pub end_column: Option<u32>,
+    pub severity: ProblemSeverity,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Default)]
+struct PartialProblem {
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl PartialProblem {
+    fn fill(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let group = |index: Option<usize>| {
+            index
+                .and_then(|index| captures.get(index))
+                .map(|group| group.as_str())
+                .filter(|text| !text.is_empty())
+        };
+        let number = |index: Option<usize>| group(index).and_then(|text| text.parse().ok());
+
+        if let Some(file) = group(pattern.file) {
+            self.file = Some(file.to_owned());
+        }
+        if let Some(line) = number(pattern.line) {
+            self.line = Some(line);
+        }
+        if let Some(column) = number(pattern.column) {
+            self.column = Some(column);
+        }
+        if let Some(end_line) = number(pattern.end_line) {
+            self.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(pattern.end_column) {
+            self.end_column = Some(end_column);
+        }
+        if let Some(severity) = group(pattern.severity).and_then(ProblemSeverity::parse) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = group(pattern.code) {
+            self.code = Some(code.to_owned());
+        }
+        if let Some(message) = group(pattern.message) {
+            self.message = Some(message.trim().to_owned());
+        }
+    }
+
+    fn to_problem(
+        &self,
+        base_dir: Option<&Path>,
+        default_severity: ProblemSeverity,
+    ) -> Option<TaskProblem> {
+        let file = Path::new(self.file.as_deref()?.trim());
+        let path = match base_dir {
+            Some(base_dir) if file.is_relative() => base_dir.join(file),
+            _ => file.to_path_buf(),
+        };
+        Some(TaskProblem {
+            path,
+            line: self.line?,
+            column: self.column,
+            end_line: self.end_line,
+            end_column: self.end_column,
+            severity: self.severity.unwrap_or(default_severity),
+            code: self.code.clone(),
+            message: self.message.clone()?,
+        })
+    }
+}
+
+impl ProblemMatcher {
+    /// Finds all problems in the given lines of task output.
+    /// Relative paths are resolved against `relative_to`, or `cwd` if the matcher has none.
+    pub fn match_lines<'a>(
+        &self,
+        lines: impl IntoIterator<Item = &'a str>,
+        cwd: Option<&Path>,
+    ) -> Result<Vec<TaskProblem>> {
+        let mut line_matcher = self.line_matcher(cwd)?;
+        Ok(lines
+            .into_iter()
+            .filter_map(|line| line_matcher.match_line(line))
+            .collect())
+    }
+
+    /// Returns a matcher fed the lines of task output one at a time, as they are printed.
+    /// Relative paths are resolved against `relative_to`, or `cwd` if the matcher has none.
+    pub fn line_matcher(&self, cwd: Option<&Path>) -> Result<ProblemLineMatcher> {
+        let regexes = self
+            .pattern
+            .iter()
+            .map(|pattern| {
+                Regex::new(&pattern.regexp)
+                    .with_context(|| format!("Invalid problem matcher regex `{}`", pattern.regexp))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let base_dir = self
+            .relative_to
+            .as_deref()
+            .map(Path::new)
+            .or(cwd)
+            .map(Path::to_path_buf);
+        Ok(ProblemLineMatcher {
+            matcher: self.clone(),
+            regexes,
+            base_dir,
+            next_pattern: 0,
+            partial: PartialProblem::default(),
+        })
+    }
+}
+
+/// Matches task output against a [`ProblemMatcher`] line by line, keeping track of the
+/// multi-line problems matched partially so far.
+pub struct ProblemLineMatcher {
+    matcher: ProblemMatcher,
+    regexes: Vec<Regex>,
+    base_dir: Option<PathBuf>,
+    /// The pattern the next line has to match to continue the current multi-line problem, or 0.
+    next_pattern: usize,
+    partial: PartialProblem,
+}
+
+impl ProblemLineMatcher {
+    /// Matches the next line of task output, returning the problem it completes, if any.
+    pub fn match_line(&mut self, line: &str) -> Option<TaskProblem> {
+        let patterns = &self.matcher.pattern;
+        let last_pattern = patterns.len().checked_sub(1)?;
+        let base_dir = self.base_dir.as_deref();
+        let severity = self.matcher.severity;
+
+        if self.next_pattern > 0 {
+            if let Some(captures) = self.regexes[self.next_pattern].captures(line) {
+                let mut matched = self.partial.clone();
+                matched.fill(&patterns[self.next_pattern], &captures);
+                if self.next_pattern == last_pattern {
+                    if !patterns[last_pattern].is_loop {
+                        self.next_pattern = 0;
+                    }
+                    return matched.to_problem(base_dir, severity);
+                }
+                self.partial = matched;
+                self.next_pattern += 1;
+                return None;
+            }
+            // The multi-line match got interrupted, this line may start a new one.
+            self.next_pattern = 0;
+        }
+
+        let captures = self.regexes[0].captures(line)?;
+        self.partial = PartialProblem::default();
+        self.partial.fill(&patterns[0], &captures);
+        if last_pattern == 0 {
+            self.partial.to_problem(base_dir, severity)
+        } else {
+            self.next_pattern = 1;
+            None
+        }
+    }
+}
+
+/// Returns the built-in problem matcher with the given name, e.g. `$rustc`.
+pub fn builtin_problem_matcher(name: &str) -> Option<ProblemMatcher> {
+    let pattern = match name {
+        "$rustc" => vec![
+            ProblemPattern {
+                regexp: r"^(warning|error)(?:\[(\S+)\])?: (.*)$".into(),
+                severity: Some(1),
+                code: Some(2),
+                message: Some(3),
+                ..ProblemPattern::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s*-->\s+(.*?):(\d+):(\d+)$".into(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                ..ProblemPattern::default()
+            },
+        ],
+        "$tsc" => vec![ProblemPattern {
+            regexp: r"^([^\s].*?)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                .into(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            code: Some(5),
+            message: Some(6),
+            ..ProblemPattern::default()
+        }],
+        "$gcc" => vec![ProblemPattern {
+            regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$".into(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            ..ProblemPattern::default()
+        }],
+        "$eslint-stylish" => vec![
+            ProblemPattern {
+                regexp: r"^([^\s].*)$".into(),
+                file: Some(1),
+                ..ProblemPattern::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$".into(),
+                line: Some(1),
+                column: Some(2),
+                severity: Some(3),
+                message: Some(4),
+                code: Some(5),
+                is_loop: true,
+                ..ProblemPattern::default()
+            },
+        ],
+        _ => return None,
+    };
+    Some(ProblemMatcher {
+        pattern,
+        relative_to: None,
+        severity: ProblemSeverity::Error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(matcher: &str, output: &str) -> Vec<TaskProblem> {
+        builtin_problem_matcher(matcher)
+            .unwrap()
+            .match_lines(output.lines(), Some(Path::new("/project")))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rustc_problem_matcher() {
+        let output = r#"
+   Compiling crash v0.1.0 (/project)
+warning: unused variable: `x`
+ --> src/main.rs:2:9
+  |
+2 |     let x = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+error[E0425]: cannot find value `y` in this scope
+ --> src/main.rs:3:5
+error: could not compile `crash` (bin "crash") due to 1 previous error
+"#;
+        assert_eq!(
+            problems("$rustc", output),
+            vec![
+                TaskProblem {
+                    path: PathBuf::from("/project/src/main.rs"),
+                    line: 2,
+                    column: Some(9),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `x`".into(),
+                },
+                TaskProblem {
+                    path: PathBuf::from("/project/src/main.rs"),
+                    line: 3,
+                    column: Some(5),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".into()),
+                    message: "cannot find value `y` in this scope".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_line_problem_matchers() {
+        assert_eq!(
+            problems(
+                "$tsc",
+                "src/index.ts(4,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+                 src/util.ts:10:1 - warning TS6133: 'x' is declared but its value is never read."
+            )
+            .into_iter()
+            .map(|problem| (problem.path, problem.line, problem.severity, problem.code))
+            .collect::<Vec<_>>(),
+            vec![
+                (
+                    PathBuf::from("/project/src/index.ts"),
+                    4,
+                    ProblemSeverity::Error,
+                    Some("TS2322".into())
+                ),
+                (
+                    PathBuf::from("/project/src/util.ts"),
+                    10,
+                    ProblemSeverity::Warning,
+                    Some("TS6133".into())
+                ),
+            ]
+        );
+
+        assert_eq!(
+            problems(
+                "$gcc",
+                "/abs/crash.c:5:12: warning: division by zero [-Wdiv-by-zero]\n\
+                 crash.c: In function 'main':\n\
+                 crash.c:7:3: error: expected ';' before '}' token"
+            )
+            .into_iter()
+            .map(|problem| (problem.path, problem.line, problem.column, problem.message))
+            .collect::<Vec<_>>(),
+            vec![
+                (
+                    PathBuf::from("/abs/crash.c"),
+                    5,
+                    Some(12),
+                    "division by zero [-Wdiv-by-zero]".into()
+                ),
+                (
+                    PathBuf::from("/project/crash.c"),
+                    7,
+                    Some(3),
+                    "expected ';' before '}' token".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_looping_problem_matcher() {
+        let output = "/project/src/a.js\n  \
+              1:10  error    'foo' is defined but never used  no-unused-vars\n  \
+              3:1   warning  Unexpected console statement      no-console\n\
+            \n\
+            /project/src/b.js\n  \
+              7:5  error  Missing semicolon  semi\n\
+            \n\
+            ✖ 3 problems (2 errors, 1 warning)";
+        assert_eq!(
+            problems("$eslint-stylish", output)
+                .into_iter()
+                .map(|problem| (
+                    problem.path,
+                    problem.line,
+                    problem.severity,
+                    problem.message,
+                    problem.code
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    PathBuf::from("/project/src/a.js"),
+                    1,
+                    ProblemSeverity::Error,
+                    "'foo' is defined but never used".into(),
+                    Some("no-unused-vars".into())
+                ),
+                (
+                    PathBuf::from("/project/src/a.js"),
+                    3,
+                    ProblemSeverity::Warning,
+                    "Unexpected console statement".into(),
+                    Some("no-console".into())
+                ),
+                (
+                    PathBuf::from("/project/src/b.js"),
+                    7,
+                    ProblemSeverity::Error,
+                    "Missing semicolon".into(),
+                    Some("semi".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_builtin_problem_matcher() {
+        assert!(
+            ProblemMatcherTemplate::Builtin("$unknown".into())
+                .resolve()
+                .is_err()
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use serde::de::{self, Deserializer, Visitor};
+use schemars::JsonSchema;
+use serde::{
+    Deserialize,
+    de::{self, Deserializer, Visitor},
+};
 use std::fmt;
 
 /// Deserializes a non-empty string array.
@@ -35,3 +39,23 @@ where
 
     deserializer.deserialize_seq(NonEmptyStringVecVisitor)
 }
+
+/// Either a single value or an array of them, see [`one_or_many`].
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+/// Deserializes either a single value or an array of them.
+pub fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
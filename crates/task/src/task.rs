@@ -2,10 +2,12 @@
 
 mod adapter_schema;
 mod debug_format;
+mod problem_matcher;
 mod serde_helpers;
 mod shell_builder;
 pub mod static_source;
 mod task_template;
+mod terminal_layout;
 mod vscode_debug_format;
 mod vscode_format;
 
@@ -22,10 +24,17 @@ pub use debug_format::{
     AttachRequest, BuildTaskDefinition, DebugRequest, DebugScenario, DebugTaskFile, LaunchRequest,
     Request, TcpArgumentsTemplate, ZedDebugConfig,
 };
+pub use problem_matcher::{
+    ProblemLineMatcher, ProblemMatcher, ProblemMatcherTemplate, ProblemPattern, ProblemSeverity,
+    TaskProblem, builtin_problem_matcher,
+};
 pub use shell_builder::{ShellBuilder, ShellKind};
 pub use task_template::{
-    DebugArgsRequest, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates,
-    substitute_variables_in_map, substitute_variables_in_str,
+    BackgroundTaskPatterns, DebugArgsRequest, DependsOrder, HideStrategy, RevealStrategy,
+    TaskTemplate, TaskTemplates, substitute_variables_in_map, substitute_variables_in_str,
+};
+pub use terminal_layout::{
+    TerminalLayout, TerminalLayoutDirection, TerminalLayoutPane, TerminalLayoutTemplate,
 };
 pub use vscode_debug_format::VsCodeDebugTaskFile;
 pub use vscode_format::VsCodeTaskFile;
@@ -74,6 +83,10 @@ pub struct SpawnInTerminal {
     pub show_command: bool,
     /// Whether to show the rerun button in the terminal tab.
     pub show_rerun: bool,
+    /// Problem matchers to run over the task's output once it finishes.
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Set for background tasks: patterns on their output, telling when they are ready.
+    pub background: Option<BackgroundTaskPatterns>,
 }
 
 impl SpawnInTerminal {
@@ -117,6 +130,8 @@ pub struct ResolvedTask {
     pub id: TaskId,
     /// A template the task got resolved from.
     original_task: TaskTemplate,
+    /// The context the task was resolved with, used to resolve the tasks it depends on.
+    context: TaskContext,
     /// Full, unshortened label of the task after all resolutions are made.
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
@@ -137,6 +152,21 @@ impl ResolvedTask {
         &self.substituted_variables
     }
 
+    /// The context the task was resolved with.
+    pub fn context(&self) -> &TaskContext {
+        &self.context
+    }
+
+    /// Whether this task has to wait for other tasks before it runs.
+    pub fn has_dependencies(&self) -> bool {
+        !self.original_task.depends_on.is_empty()
+    }
+
+    /// Whether this task only runs its dependencies and has no command of its own.
+    pub fn is_compound(&self) -> bool {
+        self.resolved.command.is_none()
+    }
+
     /// A human-readable label to display in the UI.
     pub fn display_label(&self) -> &str {
         self.resolved.label.as_str()
@@ -9,8 +9,9 @@ use util::serde::default_true;
 use util::{ResultExt, truncate_and_remove_front};
 
 use crate::{
-    AttachRequest, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
-    VariableName, ZED_VARIABLE_NAME_PREFIX, serde_helpers::non_empty_string_vec,
+    AttachRequest, ProblemMatcherTemplate, ResolvedTask, RevealTarget, Shell, SpawnInTerminal,
+    TaskContext, TaskId, TerminalLayoutTemplate, VariableName, ZED_VARIABLE_NAME_PREFIX,
+    serde_helpers::{OneOrMany, non_empty_string_vec, one_or_many},
 };
 
 /// A template definition of a Zed task to run.
@@ -72,6 +73,31 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks to run before this one. This task only starts if all of them succeed.
+    /// A task with dependencies and no `command` is a compound task that only runs its dependencies.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How the tasks in `depends_on` are run:
+    /// * `parallel` — start all of them at once (default)
+    /// * `sequential` — start each one after the previous one succeeded, in the listed order
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Problem matchers that turn the task's output into diagnostics, cleared on the next run of the task.
+    /// Either names of built-in matchers (`$rustc`, `$tsc`, `$gcc`, `$eslint-stylish`) or custom ones:
+    /// `{ "pattern": [{ "regexp": "^(.*):(\\d+): (.*)$", "file": 1, "line": 2, "message": 3 }] }`
+    /// A single matcher may be given instead of a list, e.g. `"$rustc"`.
+    #[serde(default, deserialize_with = "one_or_many")]
+    #[schemars(with = "OneOrMany<ProblemMatcherTemplate>")]
+    pub problem_matcher: Vec<ProblemMatcherTemplate>,
+    /// Whether the task keeps running instead of terminating, e.g. a dev server or a watcher.
+    /// Background tasks are considered done once they are ready rather than once they exit,
+    /// so the tasks and debug scenarios depending on them can start.
+    #[serde(default)]
+    pub is_background: bool,
+    /// Patterns on the output of a background task, telling when it is ready.
+    /// Without an `ends` pattern, the task is ready as soon as it starts.
+    #[serde(default)]
+    pub background_patterns: Option<BackgroundTaskPatterns>,
 }
 
 #[derive(Deserialize, Eq, PartialEq, Clone, Debug)]
@@ -96,6 +122,29 @@ pub enum RevealStrategy {
     Never,
 }
 
+/// How the dependencies of a task are run.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Start all dependencies at once.
+    #[default]
+    Parallel,
+    /// Start each dependency after the previous one succeeded, in the listed order.
+    Sequential,
+}
+
+/// Regular expressions matched against the output lines of a background task.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BackgroundTaskPatterns {
+    /// Marks the task as busy again, e.g. when a watcher starts recompiling.
+    #[serde(default)]
+    pub begins: Option<String>,
+    /// Marks the task as ready, e.g. when a dev server starts listening.
+    #[serde(default)]
+    pub ends: Option<String>,
+}
+
 /// What to do with the terminal pane and tab, after the command has finished.
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -113,13 +162,22 @@ pub enum HideStrategy {
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TaskTemplates(pub Vec<TaskTemplate>);
 
+/// An entry of a tasks file: either a task or a terminal layout.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum TasksFileEntry {
+    Task(TaskTemplate),
+    TerminalLayout(TerminalLayoutTemplate),
+}
+
 impl TaskTemplates {
     /// Generates JSON schema of Tasks JSON template format.
     pub fn generate_json_schema() -> serde_json_lenient::Value {
         let schema = schemars::generate::SchemaSettings::draft2019_09()
             .with_transform(DefaultDenyUnknownFields)
             .into_generator()
-            .root_schema_for::<Self>();
+            .root_schema_for::<Vec<TasksFileEntry>>();
 
         serde_json_lenient::to_value(schema).unwrap()
     }
@@ -133,7 +191,9 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
@@ -213,6 +273,26 @@ impl TaskTemplate {
             &mut substituted_variables,
         )?;
 
+        let problem_matchers = self
+            .problem_matcher
+            .iter()
+            .filter_map(|matcher| {
+                let mut matcher = matcher
+                    .resolve()
+                    .with_context(|| format!("resolving problem matcher of task {full_label:?}"))
+                    .log_err()?;
+                if let Some(relative_to) = &matcher.relative_to {
+                    matcher.relative_to = Some(substitute_all_template_variables_in_str(
+                        relative_to,
+                        &task_variables,
+                        &variable_names,
+                        &mut substituted_variables,
+                    )?);
+                }
+                Some(matcher)
+            })
+            .collect();
+
         let task_hash = to_hex_hash(self)
             .context("hashing task template")
             .log_err()?;
@@ -245,6 +325,7 @@ impl TaskTemplate {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
+            context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: SpawnInTerminal {
                 id,
@@ -259,7 +340,7 @@ impl TaskTemplate {
                         command_label
                     },
                 ),
-                command: Some(command),
+                command: (!command.trim().is_empty()).then_some(command),
                 args: args_with_substitutions,
                 env,
                 use_new_terminal: self.use_new_terminal,
@@ -271,6 +352,10 @@ impl TaskTemplate {
                 show_summary: self.show_summary,
                 show_command: self.show_command,
                 show_rerun: true,
+                problem_matchers,
+                background: self
+                    .is_background
+                    .then(|| self.background_patterns.clone().unwrap_or_default()),
             },
         })
     }
@@ -468,6 +553,107 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolving_compound_templates() {
+        let compound_task = TaskTemplate {
+            label: "build all".to_string(),
+            depends_on: vec!["build server".to_string(), "build client".to_string()],
+            ..TaskTemplate::default()
+        };
+
+        let resolved_task = compound_task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("compound tasks should resolve without a command");
+        assert!(resolved_task.is_compound());
+        assert!(resolved_task.has_dependencies());
+        assert_eq!(resolved_task.resolved.command, None);
+    }
+
+    #[test]
+    fn test_resolving_background_templates() {
+        let task = TaskTemplate {
+            label: "watch".to_string(),
+            command: "cargo watch".to_string(),
+            ..TaskTemplate::default()
+        };
+        let resolve = |task: &TaskTemplate| {
+            task.resolve_task(TEST_ID_BASE, &TaskContext::default())
+                .unwrap()
+                .resolved
+                .background
+        };
+        assert_eq!(resolve(&task), None);
+
+        let background_task = TaskTemplate {
+            is_background: true,
+            ..task.clone()
+        };
+        assert_eq!(
+            resolve(&background_task),
+            Some(BackgroundTaskPatterns::default()),
+            "Background tasks without patterns are ready right away"
+        );
+
+        let patterns = BackgroundTaskPatterns {
+            begins: Some("^\\[Running".to_string()),
+            ends: Some("^\\[Finished".to_string()),
+        };
+        assert_eq!(
+            resolve(&TaskTemplate {
+                background_patterns: Some(patterns.clone()),
+                ..background_task
+            }),
+            Some(patterns)
+        );
+        assert_eq!(
+            resolve(&TaskTemplate {
+                background_patterns: Some(BackgroundTaskPatterns::default()),
+                ..task
+            }),
+            None,
+            "Patterns are ignored for tasks that are not in the background"
+        );
+    }
+
+    #[test]
+    fn test_deserializing_problem_matchers() {
+        let problem_matcher = |json: &str| {
+            serde_json::from_str::<TaskTemplate>(json)
+                .unwrap()
+                .problem_matcher
+        };
+        let rustc = ProblemMatcherTemplate::Builtin("$rustc".to_string());
+
+        assert_eq!(
+            problem_matcher(r#"{ "label": "check", "command": "cargo check" }"#),
+            Vec::new()
+        );
+        assert_eq!(
+            problem_matcher(
+                r#"{ "label": "check", "command": "cargo check", "problem_matcher": "$rustc" }"#
+            ),
+            vec![rustc.clone()]
+        );
+        assert_eq!(
+            problem_matcher(
+                r#"{ "label": "check", "command": "cargo check", "problem_matcher": ["$rustc", "$tsc"] }"#
+            ),
+            vec![rustc, ProblemMatcherTemplate::Builtin("$tsc".to_string())]
+        );
+
+        let custom = problem_matcher(
+            r#"{
+                "label": "lint",
+                "command": "lint",
+                "problem_matcher": { "pattern": [{ "regexp": "^(.*):(\\d+): (.*)$", "file": 1, "line": 2, "message": 3 }] }
+            }"#,
+        );
+        assert!(
+            matches!(custom.as_slice(), [ProblemMatcherTemplate::Custom(matcher)] if matcher.pattern.len() == 1),
+            "{custom:?}"
+        );
+    }
+
     #[test]
     fn test_template_cwd_resolution() {
         let task_without_cwd = TaskTemplate {
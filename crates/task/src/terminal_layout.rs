@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use collections::HashMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A named terminal layout defined in a tasks file, next to the tasks.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TerminalLayoutTemplate {
+    /// The name the layout is opened by.
+    pub label: String,
+    /// The terminals to open.
+    pub terminal_layout: TerminalLayout,
+}
+
+/// A terminal, or a split of terminals, opened as part of a named layout.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(untagged)]
+pub enum TerminalLayout {
+    Split {
+        /// How the panes are laid out: "horizontal" puts them side by side, "vertical" stacks them.
+        direction: TerminalLayoutDirection,
+        /// The terminals and nested splits, in order.
+        panes: Vec<TerminalLayout>,
+    },
+    Pane(TerminalLayoutPane),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalLayoutDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// A terminal opened as part of a named layout.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TerminalLayoutPane {
+    /// A command typed into the terminal's shell once it starts.
+    /// The shell keeps running after the command exits.
+    pub command: Option<String>,
+    /// The working directory of the terminal, relative to the first project directory.
+    /// Absolute paths are used as they are.
+    pub cwd: Option<PathBuf>,
+    /// Environment variables set in the terminal, on top of `terminal.env`.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// A name shared by the terminals that `terminal_panel::ToggleBroadcastInput`
+    /// can mirror input to together.
+    pub group: Option<String>,
+}
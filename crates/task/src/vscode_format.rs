@@ -1,9 +1,13 @@
-use anyhow::bail;
+use anyhow::{Context as _, bail};
 use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{EnvVariableReplacer, TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    BackgroundTaskPatterns, DependsOrder, EnvVariableReplacer, ProblemMatcher,
+    ProblemMatcherTemplate, ProblemPattern, ProblemSeverity, TaskTemplate, TaskTemplates,
+    VariableName, builtin_problem_matcher,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +28,39 @@ struct VsCodeTaskDefinition {
     options: Option<TaskOptions>,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default, rename = "loop")]
+    is_loop: bool,
+}
+
+impl From<VsCodeProblemPattern> for ProblemPattern {
+    fn from(pattern: VsCodeProblemPattern) -> Self {
+        Self {
+            regexp: pattern.regexp,
+            file: pattern.file,
+            line: pattern.line,
+            column: pattern.column,
+            end_line: pattern.end_line,
+            end_column: pattern.end_column,
+            severity: pattern.severity,
+            code: pattern.code,
+            message: pattern.message,
+            is_loop: pattern.is_loop,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, PartialEq, Debug)]
 #[serde(tag = "type")]
 #[serde(rename_all = "camelCase")]
@@ -46,25 +83,35 @@ impl VsCodeTaskDefinition {
         self,
         replacer: &EnvVariableReplacer,
     ) -> anyhow::Result<Option<TaskTemplate>> {
-        if self.other_attributes.contains_key("dependsOn") {
-            log::warn!(
-                "Skipping deserializing of a task `{}` with the unsupported `dependsOn` key",
-                self.label
-            );
-            return Ok(None);
-        }
+        let depends_on = self.depends_on()?;
+        let problem_matcher = self.problem_matchers(replacer);
+        let is_background = self
+            .other_attributes
+            .get("isBackground")
+            .and_then(|is_background| is_background.as_bool())
+            .unwrap_or(false);
+        let background_patterns = if is_background {
+            self.background_patterns()
+        } else {
+            None
+        };
+        let depends_order = match self
+            .other_attributes
+            .get("dependsOrder")
+            .and_then(|order| order.as_str())
+        {
+            Some("sequence") => DependsOrder::Sequential,
+            _ => DependsOrder::Parallel,
+        };
         // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
         // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
-        };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        // Tasks with `dependsOn` and no command of their own become compound tasks.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -73,6 +120,11 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
+            problem_matcher,
+            is_background,
+            background_patterns,
             ..TaskTemplate::default()
         };
         if let Some(options) = self.options {
@@ -81,6 +133,177 @@ impl VsCodeTaskDefinition {
         }
         Ok(Some(template))
     }
+
+    /// Labels of the tasks from `dependsOn`, which may be either a single label or a list of them.
+    fn depends_on(&self) -> anyhow::Result<Vec<String>> {
+        let Some(depends_on) = self.other_attributes.get("dependsOn") else {
+            return Ok(Vec::new());
+        };
+        let labels = match depends_on {
+            serde_json_lenient::Value::String(label) => vec![label.clone()],
+            serde_json_lenient::Value::Array(labels) => labels
+                .iter()
+                .map(|label| match label {
+                    serde_json_lenient::Value::String(label) => Ok(label.clone()),
+                    // Tasks contributed by VS Code extensions are referenced by their `task` name.
+                    serde_json_lenient::Value::Object(task) => task
+                        .get("task")
+                        .and_then(|task| task.as_str())
+                        .map(ToOwned::to_owned)
+                        .with_context(|| format!("Unsupported `dependsOn` entry {label}")),
+                    _ => bail!("Unsupported `dependsOn` entry {label}"),
+                })
+                .collect::<anyhow::Result<_>>()?,
+            _ => bail!("Unsupported `dependsOn` value {depends_on}"),
+        };
+        Ok(labels)
+    }
+
+    /// Matchers from `problemMatcher`, which may be either a single matcher or a list of them.
+    /// Matchers that cannot be translated are skipped.
+    fn problem_matchers(&self, replacer: &EnvVariableReplacer) -> Vec<ProblemMatcherTemplate> {
+        self.problem_matcher_values()
+            .into_iter()
+            .filter_map(|matcher| {
+                problem_matcher(matcher, replacer)
+                    .with_context(|| format!("task {:?}", self.label))
+                    .log_err()
+            })
+            .collect()
+    }
+
+    /// Background task patterns, which VS Code defines as part of the problem matchers.
+    fn background_patterns(&self) -> Option<BackgroundTaskPatterns> {
+        self.problem_matcher_values()
+            .into_iter()
+            .find_map(|matcher| match matcher {
+                serde_json_lenient::Value::String(name) => builtin_background_patterns(name),
+                serde_json_lenient::Value::Object(matcher) => match matcher.get("background") {
+                    Some(background) => {
+                        let pattern = |key: &str| {
+                            background.get(key).and_then(|value| match value {
+                                serde_json_lenient::Value::String(regexp) => Some(regexp.clone()),
+                                value => value
+                                    .get("regexp")
+                                    .and_then(|regexp| regexp.as_str())
+                                    .map(ToOwned::to_owned),
+                            })
+                        };
+                        Some(BackgroundTaskPatterns {
+                            begins: pattern("beginsPattern"),
+                            ends: pattern("endsPattern"),
+                        })
+                    }
+                    None => matcher
+                        .get("base")
+                        .and_then(|base| base.as_str())
+                        .and_then(builtin_background_patterns),
+                },
+                _ => None,
+            })
+    }
+
+    fn problem_matcher_values(&self) -> Vec<&serde_json_lenient::Value> {
+        match self.other_attributes.get("problemMatcher") {
+            Some(serde_json_lenient::Value::Array(matchers)) => matchers.iter().collect(),
+            Some(matcher) => vec![matcher],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Background patterns of VS Code's built-in problem matchers for watch tasks.
+fn builtin_background_patterns(name: &str) -> Option<BackgroundTaskPatterns> {
+    match name {
+        "$tsc-watch" => Some(BackgroundTaskPatterns {
+            begins: Some(
+                r"(Starting compilation in watch mode|File change detected\. Starting incremental compilation)\.\.\."
+                    .to_owned(),
+            ),
+            ends: Some(
+                r"(Compilation complete\.|Found \d+ errors?\.) Watching for file changes\.".to_owned(),
+            ),
+        }),
+        _ => None,
+    }
+}
+
+fn problem_matcher(
+    matcher: &serde_json_lenient::Value,
+    replacer: &EnvVariableReplacer,
+) -> anyhow::Result<ProblemMatcherTemplate> {
+    let matcher = match matcher {
+        serde_json_lenient::Value::String(name) => {
+            return Ok(ProblemMatcherTemplate::Builtin(
+                builtin_problem_matcher_name(name)?.to_owned(),
+            ));
+        }
+        serde_json_lenient::Value::Object(matcher) => matcher,
+        _ => bail!("Unsupported `problemMatcher` entry {matcher}"),
+    };
+
+    let mut problem_matcher = match matcher.get("base").and_then(|base| base.as_str()) {
+        Some(base) => builtin_problem_matcher(builtin_problem_matcher_name(base)?)
+            .context("resolving base problem matcher")?,
+        None => ProblemMatcher {
+            pattern: Vec::new(),
+            relative_to: None,
+            severity: ProblemSeverity::default(),
+        },
+    };
+    if let Some(pattern) = matcher.get("pattern") {
+        let patterns = match pattern {
+            serde_json_lenient::Value::Array(patterns) => patterns.iter().collect(),
+            serde_json_lenient::Value::Object(_) => vec![pattern],
+            _ => bail!("Unsupported problem pattern {pattern}"),
+        };
+        problem_matcher.pattern = patterns
+            .into_iter()
+            .map(|pattern| {
+                serde_json_lenient::from_value::<VsCodeProblemPattern>(pattern.clone())
+                    .map(ProblemPattern::from)
+                    .with_context(|| format!("Unsupported problem pattern {pattern}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+    }
+    // Either `"absolute"`, `"relative"` (to the workspace folder) or `["relative", "path/to/dir"]`.
+    match matcher.get("fileLocation") {
+        Some(serde_json_lenient::Value::String(location)) if location == "relative" => {
+            problem_matcher.relative_to = Some(format!("${{{}}}", VariableName::WorktreeRoot));
+        }
+        Some(serde_json_lenient::Value::Array(location)) => {
+            if let Some(dir) = location.get(1).and_then(|dir| dir.as_str()) {
+                problem_matcher.relative_to = Some(replacer.replace(dir));
+            }
+        }
+        _ => {}
+    }
+    if let Some(severity) = matcher
+        .get("severity")
+        .and_then(|severity| severity.as_str())
+        .and_then(ProblemSeverity::parse)
+    {
+        problem_matcher.severity = severity;
+    }
+    anyhow::ensure!(
+        !problem_matcher.pattern.is_empty(),
+        "Problem matcher has no pattern"
+    );
+    Ok(ProblemMatcherTemplate::Custom(problem_matcher))
+}
+
+/// Maps names of VS Code's built-in problem matchers to the Zed ones.
+fn builtin_problem_matcher_name(name: &str) -> anyhow::Result<&str> {
+    let name = match name {
+        // Watch mode reports problems the same way, in addition to marking the start and end of each compilation.
+        "$tsc-watch" => "$tsc",
+        name => name,
+    };
+    anyhow::ensure!(
+        builtin_problem_matcher(name).is_some(),
+        "Unsupported problem matcher `{name}`"
+    );
+    Ok(name)
 }
 
 /// [`VsCodeTaskFile`] is a superset of Code's task definition format.
@@ -124,11 +347,12 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        DependsOrder, ProblemMatcher, ProblemMatcherTemplate, TaskTemplate, TaskTemplates,
+        VsCodeTaskFile, builtin_problem_matcher,
         vscode_format::{Command, VsCodeTaskDefinition},
     };
 
-    use super::EnvVariableReplacer;
+    use super::{EnvVariableReplacer, builtin_background_patterns};
 
     fn compare_without_other_attributes(lhs: VsCodeTaskDefinition, rhs: VsCodeTaskDefinition) {
         assert_eq!(
@@ -227,6 +451,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -238,18 +463,23 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$tsc".to_string())],
+                is_background: true,
+                background_patterns: builtin_background_patterns("$tsc-watch"),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -325,33 +555,97 @@ mod tests {
             .iter()
             .zip(expected)
             .for_each(|(lhs, rhs)| compare_without_other_attributes(lhs.clone(), rhs));
+        let tsc_problem_matcher = ProblemMatcherTemplate::Custom(ProblemMatcher {
+            relative_to: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+            ..builtin_problem_matcher("$tsc").unwrap()
+        });
         let expected = vec![
             TaskTemplate {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matcher: vec![tsc_problem_matcher.clone()],
+                is_background: true,
+                background_patterns: builtin_background_patterns("$tsc-watch"),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matcher: vec![tsc_problem_matcher.clone()],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matcher: vec![tsc_problem_matcher.clone()],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$rustc".to_string())],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                problem_matcher: vec![ProblemMatcherTemplate::Builtin("$rustc".to_string())],
+                ..Default::default()
+            },
+        ];
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(tasks.0, expected);
+    }
+
+    #[test]
+    fn can_deserialize_sequential_dependencies() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "Lint",
+                        "type": "shell",
+                        "command": "cargo clippy",
+                        "dependsOn": "Format"
+                    },
+                    {
+                        "label": "Check",
+                        "dependsOn": ["Lint", { "type": "npm", "task": "npm: test" }],
+                        "dependsOrder": "sequence"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expected = vec![
+            TaskTemplate {
+                label: "Lint".to_string(),
+                command: "cargo clippy".to_string(),
+                depends_on: vec!["Format".to_string()],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Check".to_string(),
+                depends_on: vec!["Lint".to_string(), "npm: test".to_string()],
+                depends_order: DependsOrder::Sequential,
                 ..Default::default()
             },
         ];
@@ -0,0 +1,184 @@
+use gpui::{Corner, Entity, Subscription, WeakEntity};
+use project::Project;
+use task::TaskId;
+use terminal::Terminal;
+use ui::{ButtonLike, ContextMenu, PopoverMenu, Tooltip, prelude::*};
+use workspace::{StatusItemView, Workspace, item::ItemHandle};
+use zed_actions::{RestartBackgroundTask, StopBackgroundTask};
+
+/// A status bar item listing the running background tasks, with actions to restart or stop them.
+pub struct BackgroundTasksIndicator {
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    _project_subscription: Subscription,
+}
+
+impl BackgroundTasksIndicator {
+    pub fn new(workspace: &Workspace, cx: &mut Context<Self>) -> Self {
+        let project = workspace.project().clone();
+        let project_subscription = cx.subscribe(&project, |_, _, event, cx| {
+            if let project::Event::BackgroundTasksChanged = event {
+                cx.notify();
+            }
+        });
+        Self {
+            project,
+            workspace: workspace.weak_handle(),
+            _project_subscription: project_subscription,
+        }
+    }
+}
+
+impl Render for BackgroundTasksIndicator {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let tasks = self
+            .project
+            .read(cx)
+            .background_tasks(cx)
+            .into_iter()
+            .filter_map(|terminal| {
+                let task = terminal.read(cx).task()?;
+                let ready = task
+                    .background
+                    .as_ref()
+                    .is_some_and(|background| background.is_ready());
+                Some((task.id.clone(), task.label.clone(), ready))
+            })
+            .collect::<Vec<_>>();
+        if tasks.is_empty() {
+            return div().into_any_element();
+        }
+
+        let all_ready = tasks.iter().all(|(_, _, ready)| *ready);
+        let label = match tasks.as_slice() {
+            [(_, label, _)] => label.clone(),
+            tasks => format!("{} background tasks", tasks.len()),
+        };
+        let workspace = self.workspace.clone();
+        PopoverMenu::new("background-tasks-menu")
+            .menu(move |window, cx| {
+                let workspace = workspace.clone();
+                let tasks = tasks.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    for (ix, (task_id, label, ready)) in tasks.into_iter().enumerate() {
+                        if ix > 0 {
+                            menu = menu.separator();
+                        }
+                        let status = if ready { "ready" } else { "starting" };
+                        let restart = RestartBackgroundTask {
+                            task_id: Some(task_id.0.clone()),
+                        };
+                        let stop = StopBackgroundTask {
+                            task_id: Some(task_id.0),
+                        };
+                        menu = menu
+                            .header(format!("{label} ({status})"))
+                            .entry("Restart", None, {
+                                let workspace = workspace.clone();
+                                move |window, cx| {
+                                    workspace
+                                        .update(cx, |workspace, cx| {
+                                            restart_background_task(workspace, &restart, window, cx)
+                                        })
+                                        .ok();
+                                }
+                            })
+                            .entry("Stop", None, {
+                                let workspace = workspace.clone();
+                                move |_, cx| {
+                                    workspace
+                                        .update(cx, |workspace, cx| {
+                                            stop_background_task(workspace, &stop, cx)
+                                        })
+                                        .ok();
+                                }
+                            });
+                    }
+                    menu
+                }))
+            })
+            .anchor(Corner::BottomLeft)
+            .trigger_with_tooltip(
+                ButtonLike::new("background-tasks-indicator").child(
+                    h_flex()
+                        .gap_1()
+                        .child(Icon::new(IconName::PlayFilled).size(IconSize::Small).color(
+                            if all_ready {
+                                Color::Success
+                            } else {
+                                Color::Muted
+                            },
+                        ))
+                        .child(Label::new(label).size(LabelSize::Small)),
+                ),
+                Tooltip::text("Background Tasks"),
+            )
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for BackgroundTasksIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        _: Option<&dyn ItemHandle>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+    }
+}
+
+pub(crate) fn stop_background_task(
+    workspace: &mut Workspace,
+    action: &StopBackgroundTask,
+    cx: &mut Context<Workspace>,
+) {
+    if let Some(terminal) = background_task(workspace, action.task_id.as_deref(), cx) {
+        terminal.update(cx, |terminal, _| terminal.kill_active_task());
+    }
+}
+
+pub(crate) fn restart_background_task(
+    workspace: &mut Workspace,
+    action: &RestartBackgroundTask,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(terminal) = background_task(workspace, action.task_id.as_deref(), cx) else {
+        return;
+    };
+    let Some(task_id) = terminal.read(cx).task().map(|task| task.id.clone()) else {
+        return;
+    };
+    let Some((task_source_kind, task)) = workspace
+        .project()
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
+        .and_then(|inventory| inventory.read(cx).last_scheduled_task(Some(&task_id)))
+    else {
+        log::warn!("Cannot restart background task {task_id:?}, it was not scheduled");
+        return;
+    };
+
+    terminal.update(cx, |terminal, _| terminal.kill_active_task());
+    workspace.schedule_resolved_task(task_source_kind, task, false, window, cx);
+}
+
+/// The running background task with the given id, or the last started one.
+fn background_task(
+    workspace: &Workspace,
+    task_id: Option<&str>,
+    cx: &App,
+) -> Option<Entity<Terminal>> {
+    let background_tasks = workspace.project().read(cx).background_tasks(cx);
+    match task_id {
+        Some(task_id) => background_tasks.into_iter().find(|terminal| {
+            terminal
+                .read(cx)
+                .task()
+                .is_some_and(|task| task.id == TaskId(task_id.to_owned()))
+        }),
+        None => background_tasks.into_iter().last(),
+    }
+}
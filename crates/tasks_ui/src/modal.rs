@@ -10,7 +10,7 @@ use gpui::{
 };
 use itertools::Itertools;
 use picker::{Picker, PickerDelegate, highlighted_match_with_paths::HighlightedMatch};
-use project::{TaskSourceKind, task_store::TaskStore};
+use project::{TaskGraphStatus, TaskSourceKind, task_store::TaskStore};
 use task::{DebugScenario, ResolvedTask, RevealTarget, TaskContext, TaskTemplate};
 use ui::{
     ActiveTheme, Clickable, FluentBuilder as _, IconButtonShape, IconWithIndicator, Indicator,
@@ -461,6 +461,25 @@ impl PickerDelegate for TasksModalDelegate {
             tooltip_label_text.push_str(&resolved_task.resolved.command_label);
         }
 
+        let task_graph_status = if resolved_task.has_dependencies() {
+            self.task_store
+                .read(cx)
+                .task_inventory()
+                .and_then(|inventory| inventory.read(cx).task_graph_status(&resolved_task.id))
+        } else {
+            None
+        };
+        if !template.depends_on.is_empty() {
+            if !tooltip_label_text.trim().is_empty() {
+                tooltip_label_text.push('\n');
+            }
+            tooltip_label_text.push_str("Depends on: ");
+            tooltip_label_text.push_str(&template.depends_on.join(", "));
+        }
+        if let Some(TaskGraphStatus::Failed { label }) = &task_graph_status {
+            tooltip_label_text.push_str(&format!("\nLast run failed: {label}"));
+        }
+
         if !template.tags.is_empty() {
             tooltip_label_text.push('\n');
             tooltip_label_text.push_str(
@@ -509,6 +528,22 @@ impl PickerDelegate for TasksModalDelegate {
             IconWithIndicator::new(icon, indicator)
                 .indicator_border_color(Some(cx.theme().colors().border_transparent))
         });
+        let task_graph_status = task_graph_status.map(|status| match status {
+            TaskGraphStatus::Running { finished, total } => {
+                Label::new(format!("{finished}/{total}"))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .into_any_element()
+            }
+            TaskGraphStatus::Succeeded => Icon::new(IconName::Check)
+                .color(Color::Success)
+                .size(IconSize::Small)
+                .into_any_element(),
+            TaskGraphStatus::Failed { .. } => Icon::new(IconName::XCircle)
+                .color(Color::Error)
+                .size(IconSize::Small)
+                .into_any_element(),
+        });
         let history_run_icon = if Some(ix) <= self.divider_index {
             Some(
                 Icon::new(IconName::HistoryRerun)
@@ -542,6 +577,7 @@ impl PickerDelegate for TasksModalDelegate {
                             MAX_TAGS_LINE_LEN,
                         )))
                         .flex_none()
+                        .children(task_graph_status)
                         .child(history_run_icon.unwrap())
                         .into_any_element(),
                 )
@@ -6,9 +6,12 @@ use gpui::{App, AppContext as _, Context, Entity, Task, Window};
 use project::{Location, TaskContexts, TaskSourceKind, Worktree};
 use task::{RevealTarget, TaskContext, TaskId, TaskTemplate, TaskVariables, VariableName};
 use workspace::Workspace;
+use zed_actions::StopBackgroundTask;
 
+mod background_tasks;
 mod modal;
 
+pub use background_tasks::BackgroundTasksIndicator;
 pub use modal::{Rerun, ShowAttachModal, Spawn, TaskOverrides, TasksModal};
 
 pub fn init(cx: &mut App) {
@@ -89,7 +92,11 @@ pub fn init(cx: &mut App) {
                             cx,
                         );
                     };
-                });
+                })
+                .register_action(|workspace, action: &StopBackgroundTask, _, cx| {
+                    background_tasks::stop_background_task(workspace, action, cx)
+                })
+                .register_action(background_tasks::restart_background_task);
         },
     )
     .detach();
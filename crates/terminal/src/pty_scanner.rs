@@ -0,0 +1,361 @@
+use std::{
+    io::{self, Read},
+    ptr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Instant,
+};
+
+use alacritty_terminal::{
+    Term,
+    event::{Event as AlacTermEvent, EventListener, OnResize, WindowSize},
+    grid::{Dimensions, Row},
+    index::Line,
+    sync::FairMutex,
+    term::{TermMode, cell::Cell},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+    vte::ansi::Processor,
+};
+use futures::channel::mpsc::UnboundedSender;
+use polling::{Event as PollingEvent, PollMode, Poller};
+
+use crate::{
+    ZedListener,
+    shell_integration::{OscScanner, ShellMarker},
+    terminal_images::{ImageEvent, ImageScanner},
+};
+
+const READ_BUFFER_LEN: usize = 64 * 1024;
+
+/// A PTY that parses its output into the terminal itself, rather than leaving it to Alacritty's
+/// event loop, so that it can tell where the cursor was at each shell marker.
+///
+/// The sequences Alacritty ignores are picked out of the output before it gets parsed: shell
+/// markers are reported along with their lines, and images are replaced with placeholders.
+pub(crate) struct ScanningPty<T> {
+    pty: T,
+    term: Arc<FairMutex<Term<ZedListener>>>,
+    listener: ZedListener,
+    parser: OutputParser,
+    markers_tx: UnboundedSender<(ShellMarker, usize)>,
+    image_scanner: ImageScanner,
+    images_tx: UnboundedSender<ImageEvent>,
+    read_buffer: Box<[u8]>,
+    scanned: Vec<u8>,
+}
+
+impl<T> ScanningPty<T> {
+    pub(crate) fn new(
+        pty: T,
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        listener: ZedListener,
+        window_size: WindowSize,
+        parser: OutputParser,
+        markers_tx: UnboundedSender<(ShellMarker, usize)>,
+        images_tx: UnboundedSender<ImageEvent>,
+    ) -> Self {
+        Self {
+            pty,
+            term,
+            listener,
+            parser,
+            markers_tx,
+            image_scanner: ImageScanner::new(window_size.cell_width, window_size.cell_height),
+            images_tx,
+            read_buffer: vec![0; READ_BUFFER_LEN].into_boxed_slice(),
+            scanned: Vec::new(),
+        }
+    }
+}
+
+impl<T: EventedReadWrite> Read for ScanningPty<T> {
+    /// Parses whatever the PTY has to read, and leaves nothing to Alacritty's event loop, which
+    /// then goes back to waiting for more output.
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.pty.reader().read(&mut self.read_buffer)?;
+        if read == 0 {
+            return Ok(0);
+        }
+
+        let mut image_events = Vec::new();
+        self.image_scanner
+            .scan(&self.read_buffer[..read], &mut self.scanned, |event| {
+                image_events.push(event)
+            });
+        let synchronizing = {
+            // Alacritty holds the lease of the terminal while reading, so locking it fairly
+            // would wait for ourselves.
+            let mut term = self.term.lock_unfair();
+            let markers_tx = &self.markers_tx;
+            self.parser
+                .advance(&mut term, &self.scanned, |marker, line| {
+                    markers_tx.unbounded_send((marker, line)).ok();
+                })
+        };
+        self.scanned.clear();
+        // Images are only reported once their placeholders are in the grid, so that the terminal
+        // doesn't drop them for missing their anchor cell.
+        for event in image_events {
+            self.images_tx.unbounded_send(event).ok();
+        }
+
+        if !synchronizing {
+            self.listener.send_event(AlacTermEvent::Wakeup);
+        }
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+}
+
+/// Parses terminal output, noting the line of each shell marker when the parser reaches it.
+///
+/// Lines are counted from the first line of the terminal, including the lines dropped from the
+/// scrollback since, so that they keep pointing at the same output once the scrollback is full.
+pub(crate) struct OutputParser {
+    parser: Processor,
+    osc_scanner: OscScanner,
+    /// Lines scrolled into the scrollback so far, shared with the terminal, which reads it while
+    /// holding the lock of the grid.
+    scrolled_lines: Arc<AtomicUsize>,
+    max_history: usize,
+}
+
+impl OutputParser {
+    /// `scrolled_lines` starts at the size of the scrollback, which is at most `max_history`.
+    pub(crate) fn new(scrolled_lines: Arc<AtomicUsize>, max_history: usize) -> Self {
+        Self {
+            parser: Processor::new(),
+            osc_scanner: OscScanner::default(),
+            scrolled_lines,
+            max_history,
+        }
+    }
+
+    /// Parses `output` into the terminal. Returns whether the terminal is in the middle of a
+    /// synchronized update, and should not be drawn yet.
+    pub(crate) fn advance<T: EventListener>(
+        &mut self,
+        term: &mut Term<T>,
+        output: &[u8],
+        mut on_marker: impl FnMut(ShellMarker, usize),
+    ) -> bool {
+        // A synchronized update left open is applied with the next output, as there is no timer
+        // to end it.
+        if self
+            .parser
+            .sync_timeout()
+            .sync_timeout()
+            .is_some_and(|timeout| timeout <= Instant::now())
+        {
+            self.parser.stop_sync(term);
+        }
+
+        let mut markers = Vec::new();
+        self.osc_scanner
+            .scan(output, |end, marker| markers.push((end, marker)));
+        let mut start = 0;
+        for (end, marker) in markers {
+            self.parse(term, &output[start..end]);
+            start = end;
+            let cursor_line = term.grid().cursor.point.line.0.max(0) as usize;
+            on_marker(
+                marker,
+                self.scrolled_lines.load(Ordering::Relaxed) + cursor_line,
+            );
+        }
+        self.parse(term, &output[start..]);
+
+        self.parser.sync_timeout().sync_timeout().is_some()
+    }
+
+    /// Parses output, counting the lines it scrolls into the scrollback.
+    fn parse<T: EventListener>(&mut self, term: &mut Term<T>, mut output: &[u8]) {
+        // Until the scrollback is full, it grows by the lines scrolled into it. Rows may move in
+        // memory while it grows, so it is checked after each line feed.
+        while !output.is_empty() && term.history_size() < self.max_history {
+            let end = output
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(output.len(), |ix| ix + 1);
+            let history_size = term.history_size();
+            self.parser.advance(term, &output[..end]);
+            self.add_scrolled_lines(term.history_size().saturating_sub(history_size));
+            output = &output[end..];
+        }
+        if output.is_empty() {
+            return;
+        }
+
+        // Once it is full, each line scrolled into it drops its first line instead. Rows rotate
+        // in place then, which tells how far the row at the top of the screen moved up, unless
+        // it got dropped as well.
+        let alternate_screen = term.mode().contains(TermMode::ALT_SCREEN);
+        let top_row: *const Row<Cell> = &term.grid()[Line(0)];
+        self.parser.advance(term, output);
+        if alternate_screen || term.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+        let grid = term.grid();
+        let scrolled = (0..=grid.history_size())
+            .find(|lines| ptr::eq(&grid[Line(-(*lines as i32))], top_row))
+            .unwrap_or_else(|| output.iter().filter(|byte| **byte == b'\n').count());
+        self.add_scrolled_lines(scrolled);
+    }
+
+    fn add_scrolled_lines(&self, lines: usize) {
+        if lines > 0 {
+            self.scrolled_lines.fetch_add(lines, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<T: EventedReadWrite> EventedReadWrite for ScanningPty<T> {
+    type Reader = Self;
+    type Writer = T::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poller: &Arc<Poller>,
+        event: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.pty.register(poller, event, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poller: &Arc<Poller>,
+        event: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poller, event, mode)
+    }
+
+    fn deregister(&mut self, poller: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poller)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<T: EventedPty> EventedPty for ScanningPty<T> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<T: OnResize> OnResize for ScanningPty<T> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.image_scanner
+            .set_cell_size(window_size.cell_width, window_size.cell_height);
+        self.pty.on_resize(window_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::{
+        event::VoidListener,
+        term::{Config, test::TermSize},
+    };
+
+    use crate::row_to_string;
+
+    struct TestTerminal {
+        term: Term<VoidListener>,
+        parser: OutputParser,
+        scrolled_lines: Arc<AtomicUsize>,
+    }
+
+    impl TestTerminal {
+        /// A terminal of 4 lines, with a scrollback of 5 lines.
+        fn new() -> Self {
+            let config = Config {
+                scrolling_history: 5,
+                ..Config::default()
+            };
+            let scrolled_lines = Arc::new(AtomicUsize::new(0));
+            Self {
+                term: Term::new(config, &TermSize::new(20, 4), VoidListener),
+                parser: OutputParser::new(scrolled_lines.clone(), 5),
+                scrolled_lines,
+            }
+        }
+
+        fn write(&mut self, output: &str) -> Vec<(ShellMarker, usize)> {
+            let mut markers = Vec::new();
+            self.parser
+                .advance(&mut self.term, output.as_bytes(), |marker, line| {
+                    markers.push((marker, line))
+                });
+            markers
+        }
+
+        fn scrolled_lines(&self) -> usize {
+            self.scrolled_lines.load(Ordering::Relaxed)
+        }
+
+        /// The text at a line counted like the lines of markers.
+        fn line(&self, line: usize) -> Option<String> {
+            let line = Line(line as i32 - self.scrolled_lines() as i32);
+            (line >= self.term.topmost_line()).then(|| {
+                row_to_string(&self.term.grid()[line])
+                    .trim_end()
+                    .to_string()
+            })
+        }
+    }
+
+    #[test]
+    fn test_markers_of_a_single_read() {
+        let mut terminal = TestTerminal::new();
+        let markers = terminal.write(
+            "\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\r\nc\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ ",
+        );
+        assert_eq!(
+            markers,
+            vec![
+                (ShellMarker::PromptStart, 0),
+                (ShellMarker::CommandStart, 0),
+                (ShellMarker::CommandExecuted, 1),
+                (ShellMarker::CommandFinished { exit_code: Some(0) }, 4),
+                (ShellMarker::PromptStart, 4),
+            ]
+        );
+        assert_eq!(terminal.scrolled_lines(), 1);
+        assert_eq!(terminal.line(0).as_deref(), Some("$ ls"));
+        assert_eq!(terminal.line(1).as_deref(), Some("a"));
+        assert_eq!(terminal.line(4).as_deref(), Some("$"));
+    }
+
+    #[test]
+    fn test_marker_lines_past_a_full_scrollback() {
+        let mut terminal = TestTerminal::new();
+        let mut output = String::new();
+        for line in 0..13 {
+            output.push_str(&format!("{line}\r\n"));
+        }
+        terminal.write(&output);
+        assert_eq!(terminal.term.history_size(), 5);
+        assert_eq!(terminal.scrolled_lines(), 10);
+        assert_eq!(terminal.line(4), None, "The line was dropped");
+        assert_eq!(terminal.line(5).as_deref(), Some("5"));
+
+        // Lines keep counting once the scrollback is full, however they get scrolled.
+        let markers = terminal.write("\x1b]133;C\x07out\r\nput\r\n");
+        assert_eq!(markers, vec![(ShellMarker::CommandExecuted, 13)]);
+        terminal.write("more\r\n");
+        terminal.write("\x1b[2Sa line wrapping at the bottom\r\n");
+        assert_eq!(terminal.scrolled_lines(), 17);
+        assert_eq!(terminal.line(13).as_deref(), Some("out"));
+        assert_eq!(terminal.line(14).as_deref(), Some("put"));
+    }
+}
@@ -0,0 +1,351 @@
+use alacritty_terminal::{
+    grid::{Dimensions, Grid, GridCell},
+    index::{Column, Line},
+    term::cell::{Cell, Flags},
+    vte::ansi::{Attr, Color as AnsiColor, Handler},
+};
+use serde::{Deserialize, Serialize};
+
+/// How much of a terminal's scrollback is saved, in bytes of serialized lines, counted from its end.
+pub const MAX_SAVED_SCROLLBACK_BYTES: usize = 512 * 1024;
+
+const RESTORED_SESSION_DELIMITER: &str = "── Restored session ──";
+
+/// The cell flags kept when saving a scrollback.
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::ITALIC)
+    .union(Flags::DIM)
+    .union(Flags::INVERSE)
+    .union(Flags::STRIKEOUT)
+    .union(Flags::UNDERLINE)
+    .union(Flags::DOUBLE_UNDERLINE)
+    .union(Flags::UNDERCURL);
+
+/// Lines of a terminal's grid with their colors, saved to show them again after a restart.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedScrollback {
+    pub lines: Vec<SavedLine>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedLine {
+    pub text: String,
+    /// Styles of consecutive chars of the text.
+    pub runs: Vec<SavedRun>,
+    /// Whether the line was wrapped at the terminal's width, and continues on the next line.
+    pub wrapped: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedRun {
+    /// Number of chars styled by the run.
+    pub len: usize,
+    pub fg: AnsiColor,
+    pub bg: AnsiColor,
+    pub flags: Flags,
+}
+
+impl SavedScrollback {
+    /// Saves the last lines of the grid, up to `max_bytes` once serialized. Styles count too, as
+    /// output with many colors takes more room for its runs than for its text.
+    pub fn from_grid(grid: &Grid<Cell>, max_bytes: usize) -> Self {
+        let mut lines = Vec::new();
+        let mut saved_bytes = serde_json::to_vec(&Self::default()).map_or(0, |json| json.len());
+        for line in (grid.topmost_line().0..=grid.bottommost_line().0).rev() {
+            let line = save_line(grid, Line(line));
+            // The empty lines below the output aren't saved.
+            if lines.is_empty() && line.text.is_empty() && !line.wrapped {
+                continue;
+            }
+            saved_bytes += line.serialized_len();
+            if saved_bytes > max_bytes {
+                break;
+            }
+            lines.push(line);
+        }
+        lines.reverse();
+        Self { lines }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Writes the saved lines into the terminal, dimmed, followed by a delimiter.
+    pub fn restore(&self, term: &mut impl Handler) {
+        for line in &self.lines {
+            let mut chars = line.text.chars();
+            for run in &line.runs {
+                set_style(term, run);
+                for c in chars.by_ref().take(run.len) {
+                    term.input(c);
+                }
+            }
+            term.terminal_attribute(Attr::Reset);
+            if !line.wrapped {
+                term.carriage_return();
+                term.linefeed();
+            }
+        }
+
+        term.terminal_attribute(Attr::Dim);
+        for c in RESTORED_SESSION_DELIMITER.chars() {
+            term.input(c);
+        }
+        term.terminal_attribute(Attr::Reset);
+        term.carriage_return();
+        term.linefeed();
+    }
+}
+
+impl SavedLine {
+    /// The size of the line in the saved scrollback, separator included.
+    fn serialized_len(&self) -> usize {
+        serde_json::to_vec(self).map_or(0, |json| json.len()) + 1
+    }
+}
+
+fn save_line(grid: &Grid<Cell>, line: Line) -> SavedLine {
+    let row = &grid[line];
+    let columns = grid.columns();
+    let wrapped = row[Column(columns - 1)].flags.contains(Flags::WRAPLINE);
+    let end = if wrapped {
+        columns
+    } else {
+        (0..columns)
+            .rposition(|column| !row[Column(column)].is_empty())
+            .map_or(0, |last| last + 1)
+    };
+
+    let mut text = String::new();
+    let mut runs = Vec::<SavedRun>::new();
+    for column in 0..end {
+        let cell = &row[Column(column)];
+        // The second half of a wide char, or the padding of one moved to the next line.
+        if cell
+            .flags
+            .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+        {
+            continue;
+        }
+
+        let mut len = 1;
+        text.push(cell.c);
+        if let Some(zerowidth) = cell.zerowidth() {
+            text.extend(zerowidth);
+            len += zerowidth.len();
+        }
+
+        let flags = cell.flags & STYLE_FLAGS;
+        match runs.last_mut() {
+            Some(run) if run.fg == cell.fg && run.bg == cell.bg && run.flags == flags => {
+                run.len += len
+            }
+            _ => runs.push(SavedRun {
+                len,
+                fg: cell.fg,
+                bg: cell.bg,
+                flags,
+            }),
+        }
+    }
+
+    SavedLine {
+        text,
+        runs,
+        wrapped,
+    }
+}
+
+fn set_style(term: &mut impl Handler, run: &SavedRun) {
+    term.terminal_attribute(Attr::Reset);
+    term.terminal_attribute(Attr::Foreground(run.fg));
+    term.terminal_attribute(Attr::Background(run.bg));
+    for (flag, attr) in [
+        (Flags::BOLD, Attr::Bold),
+        (Flags::ITALIC, Attr::Italic),
+        (Flags::INVERSE, Attr::Reverse),
+        (Flags::STRIKEOUT, Attr::Strike),
+        (Flags::UNDERLINE, Attr::Underline),
+        (Flags::DOUBLE_UNDERLINE, Attr::DoubleUnderline),
+        (Flags::UNDERCURL, Attr::Undercurl),
+    ] {
+        if run.flags.contains(flag) {
+            term.terminal_attribute(attr);
+        }
+    }
+    // Restored lines are dimmed, to tell them apart from the new session's output.
+    term.terminal_attribute(Attr::Dim);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::{
+        Term,
+        event::VoidListener,
+        term::{Config, test::TermSize},
+        vte::ansi::{Color, NamedColor},
+    };
+
+    fn term_with_output(columns: usize, output: &str) -> Term<VoidListener> {
+        let mut term = Term::new(Config::default(), &TermSize::new(columns, 5), VoidListener);
+        for c in output.chars() {
+            match c {
+                '\n' => {
+                    term.carriage_return();
+                    term.linefeed();
+                }
+                c => term.input(c),
+            }
+        }
+        term
+    }
+
+    fn texts(scrollback: &SavedScrollback) -> Vec<(&str, bool)> {
+        scrollback
+            .lines
+            .iter()
+            .map(|line| (line.text.as_str(), line.wrapped))
+            .collect()
+    }
+
+    #[test]
+    fn test_saving_wrapped_lines() {
+        let term = term_with_output(10, "short\n0123456789abcdef\nend");
+        let scrollback = SavedScrollback::from_grid(term.grid(), MAX_SAVED_SCROLLBACK_BYTES);
+        assert_eq!(
+            texts(&scrollback),
+            vec![
+                ("short", false),
+                ("0123456789", true),
+                ("abcdef", false),
+                ("end", false),
+            ]
+        );
+
+        // Restoring into a wider terminal joins the wrapped lines back.
+        let mut restored = term_with_output(20, "");
+        scrollback.restore(&mut restored);
+        let restored = SavedScrollback::from_grid(restored.grid(), MAX_SAVED_SCROLLBACK_BYTES);
+        assert_eq!(
+            texts(&restored),
+            vec![
+                ("short", false),
+                ("0123456789abcdef", false),
+                ("end", false),
+                (RESTORED_SESSION_DELIMITER, false),
+            ]
+        );
+        assert!(
+            restored.lines[0]
+                .runs
+                .iter()
+                .all(|run| run.flags.contains(Flags::DIM))
+        );
+    }
+
+    #[test]
+    fn test_saving_wide_chars() {
+        // The wide char does not fit at the end of the first line, so it is moved to the next one.
+        let term = term_with_output(5, "a文字b\n1234文");
+        let scrollback = SavedScrollback::from_grid(term.grid(), MAX_SAVED_SCROLLBACK_BYTES);
+        assert_eq!(
+            texts(&scrollback),
+            vec![("a文字", true), ("b", false), ("1234", true), ("文", false)]
+        );
+        assert_eq!(
+            scrollback.lines[0].runs,
+            vec![SavedRun {
+                len: 3,
+                fg: Color::Named(NamedColor::Foreground),
+                bg: Color::Named(NamedColor::Background),
+                flags: Flags::empty(),
+            }]
+        );
+
+        let mut restored = term_with_output(10, "");
+        scrollback.restore(&mut restored);
+        let restored = SavedScrollback::from_grid(restored.grid(), MAX_SAVED_SCROLLBACK_BYTES);
+        assert_eq!(
+            texts(&restored),
+            vec![
+                ("a文字b", false),
+                ("1234文", false),
+                (RESTORED_SESSION_DELIMITER, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_saving_colors_and_size_limit() {
+        let mut term = term_with_output(10, "");
+        term.terminal_attribute(Attr::Foreground(Color::Named(NamedColor::Red)));
+        term.terminal_attribute(Attr::Bold);
+        for c in "err".chars() {
+            term.input(c);
+        }
+        term.terminal_attribute(Attr::Reset);
+        for c in " ok".chars() {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+        for c in "last".chars() {
+            term.input(c);
+        }
+
+        let scrollback = SavedScrollback::from_grid(term.grid(), MAX_SAVED_SCROLLBACK_BYTES);
+        assert_eq!(
+            scrollback.lines[0].runs,
+            vec![
+                SavedRun {
+                    len: 3,
+                    fg: Color::Named(NamedColor::Red),
+                    bg: Color::Named(NamedColor::Background),
+                    flags: Flags::BOLD,
+                },
+                SavedRun {
+                    len: 3,
+                    fg: Color::Named(NamedColor::Foreground),
+                    bg: Color::Named(NamedColor::Background),
+                    flags: Flags::empty(),
+                },
+            ]
+        );
+
+        let scrollback = SavedScrollback::from_grid(term.grid(), 5);
+        assert_eq!(texts(&scrollback), Vec::new());
+        let scrollback = SavedScrollback::from_grid(term.grid(), 200);
+        assert_eq!(texts(&scrollback), vec![("last", false)]);
+    }
+
+    #[test]
+    fn test_size_limit_counts_styles() {
+        // Every cell has its own color, so each line holds far more runs than text.
+        let mut term = Term::new(Config::default(), &TermSize::new(40, 100), VoidListener);
+        for line in 0..100 {
+            for column in 0..40 {
+                term.terminal_attribute(Attr::Foreground(Color::Indexed(
+                    ((line + column) % 256) as u8,
+                )));
+                term.input('x');
+            }
+            term.carriage_return();
+            term.linefeed();
+        }
+
+        let max_bytes = 16 * 1024;
+        let scrollback = SavedScrollback::from_grid(term.grid(), max_bytes);
+        let serialized = serde_json::to_string(&scrollback).unwrap();
+        assert!(serialized.len() <= max_bytes, "{}", serialized.len());
+        assert!(!scrollback.is_empty());
+        assert!(scrollback.lines.len() < 100);
+        assert!(
+            scrollback
+                .lines
+                .iter()
+                .all(|line| line.text.len() == 40 && line.runs.len() == 40)
+        );
+    }
+}
@@ -0,0 +1,572 @@
+//! Shell integration: scripts that make bash, zsh and fish report their prompts, commands and
+//! working directory with OSC 133, OSC 633 and OSC 7 escape sequences, and the command blocks
+//! built from those reports.
+//!
+//! Alacritty ignores these sequences, so they are picked out of the PTY output while it gets
+//! parsed, see [`OutputParser`](crate::pty_scanner::OutputParser).
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use util::ResultExt as _;
+
+const BASH_SCRIPT: &str = include_str!("shell_integration/zed.bash");
+const ZSH_SCRIPT: &str = include_str!("shell_integration/zed.zsh");
+const FISH_SCRIPT: &str = include_str!("shell_integration/zed.fish");
+
+/// How many finished commands are remembered per terminal.
+const MAX_COMMAND_BLOCKS: usize = 1_000;
+/// Longest OSC payload that is buffered while looking for shell markers.
+const MAX_OSC_LEN: usize = 4_096;
+
+/// A report from the shell integration script running in the terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ShellMarker {
+    /// `OSC 133 ; A`: the shell started drawing its prompt.
+    PromptStart,
+    /// `OSC 133 ; B`: the prompt is drawn, and the command is about to be typed.
+    CommandStart,
+    /// `OSC 133 ; C`: the command was submitted and runs now.
+    CommandExecuted,
+    /// `OSC 133 ; D [; exit code]`: the command finished.
+    CommandFinished { exit_code: Option<i32> },
+    /// `OSC 633 ; E ; command line`: the command line about to be run.
+    CommandLine(String),
+    /// `OSC 7 ; file://host/path` or `OSC 633 ; P ; Cwd=path`: the shell changed its directory.
+    WorkingDirectory(PathBuf),
+}
+
+/// A command run at a shell prompt, as reported by the shell integration.
+///
+/// Lines are counted from the first line of the terminal, including the lines dropped from the
+/// scrollback since.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// The line the command was typed at.
+    pub prompt_line: usize,
+    /// The first line of the command's output, once the command runs.
+    pub output_line: Option<usize>,
+    /// The line the terminal cursor was at when the command finished.
+    pub end_line: Option<usize>,
+    pub command: Option<String>,
+    pub started_at: Option<Instant>,
+    pub finished_at: Option<Instant>,
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The prompt is shown, no command was submitted yet.
+    Prompt,
+    Running,
+    /// The command finished, the exit code is unknown if the shell did not report it.
+    Finished {
+        exit_code: Option<i32>,
+    },
+}
+
+impl CommandBlock {
+    fn new(prompt_line: usize) -> Self {
+        Self {
+            prompt_line,
+            output_line: None,
+            end_line: None,
+            command: None,
+            started_at: None,
+            finished_at: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn status(&self) -> CommandStatus {
+        match (self.started_at, self.finished_at) {
+            (None, _) => CommandStatus::Prompt,
+            (Some(_), None) => CommandStatus::Running,
+            (Some(_), Some(_)) => CommandStatus::Finished {
+                exit_code: self.exit_code,
+            },
+        }
+    }
+
+    /// How long the command ran, or has been running for.
+    pub fn duration(&self) -> Option<Duration> {
+        let started_at = self.started_at?;
+        Some(
+            self.finished_at
+                .unwrap_or_else(Instant::now)
+                .saturating_duration_since(started_at),
+        )
+    }
+}
+
+/// Commands run in a terminal, in the order they were run.
+#[derive(Debug, Default)]
+pub(crate) struct CommandBlocks {
+    blocks: VecDeque<CommandBlock>,
+    working_directory: Option<PathBuf>,
+}
+
+impl CommandBlocks {
+    /// Applies a marker reported while the terminal cursor was at `line`.
+    pub(crate) fn apply(&mut self, marker: ShellMarker, line: usize, now: Instant) {
+        match marker {
+            ShellMarker::PromptStart => {
+                if let Some(last) = self.blocks.back_mut() {
+                    match last.status() {
+                        // Nothing was run at the previous prompt, e.g. it got interrupted.
+                        CommandStatus::Prompt => {
+                            self.blocks.pop_back();
+                        }
+                        // The shell did not report the end of the command.
+                        CommandStatus::Running => {
+                            last.finished_at = Some(now);
+                            last.end_line = Some(line);
+                        }
+                        CommandStatus::Finished { .. } => {}
+                    }
+                }
+                self.blocks.push_back(CommandBlock::new(line));
+                if self.blocks.len() > MAX_COMMAND_BLOCKS {
+                    self.blocks.pop_front();
+                }
+            }
+            ShellMarker::CommandStart => match self.blocks.back_mut() {
+                Some(last) if last.status() == CommandStatus::Prompt => last.prompt_line = line,
+                _ => self.blocks.push_back(CommandBlock::new(line)),
+            },
+            ShellMarker::CommandLine(command) => {
+                if let Some(last) = self.blocks.back_mut()
+                    && last.finished_at.is_none()
+                    && !command.trim().is_empty()
+                {
+                    last.command = Some(command);
+                }
+            }
+            ShellMarker::CommandExecuted => {
+                // Shells that do not mark their prompts still report the commands.
+                if !self
+                    .blocks
+                    .back()
+                    .is_some_and(|last| last.status() == CommandStatus::Prompt)
+                {
+                    self.blocks
+                        .push_back(CommandBlock::new(line.saturating_sub(1)));
+                }
+                if let Some(last) = self.blocks.back_mut() {
+                    last.started_at = Some(now);
+                    last.output_line = Some(line);
+                }
+            }
+            ShellMarker::CommandFinished { exit_code } => {
+                if let Some(last) = self.blocks.back_mut()
+                    && last.status() == CommandStatus::Running
+                {
+                    last.finished_at = Some(now);
+                    last.end_line = Some(line);
+                    last.exit_code = exit_code;
+                }
+            }
+            ShellMarker::WorkingDirectory(path) => self.working_directory = Some(path),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    pub(crate) fn blocks(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> {
+        self.blocks.iter()
+    }
+
+    pub(crate) fn last_finished(&self) -> Option<&CommandBlock> {
+        self.blocks
+            .iter()
+            .rev()
+            .find(|block| matches!(block.status(), CommandStatus::Finished { .. }))
+    }
+
+    /// The first line after the command's output: the next prompt, or the line it finished at.
+    pub(crate) fn output_end(&self, block: &CommandBlock) -> Option<usize> {
+        let next_prompt = self
+            .blocks
+            .iter()
+            .find(|other| other.prompt_line > block.prompt_line)
+            .map(|next| next.prompt_line);
+        next_prompt.or(block.end_line)
+    }
+
+    pub(crate) fn working_directory(&self) -> Option<&Path> {
+        self.working_directory.as_deref()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Finds shell markers in a stream of terminal output.
+#[derive(Debug, Default)]
+pub(crate) struct OscScanner {
+    state: ScanState,
+    payload: Vec<u8>,
+}
+
+impl OscScanner {
+    /// Reports each marker with the offset in `bytes` right after the sequence.
+    pub(crate) fn scan(&mut self, bytes: &[u8], mut on_marker: impl FnMut(usize, ShellMarker)) {
+        let mut start = 0;
+        if self.state == ScanState::Ground {
+            match bytes.iter().position(|byte| *byte == 0x1b) {
+                Some(escape) => start = escape,
+                None => return,
+            }
+        }
+
+        for (ix, &byte) in bytes.iter().enumerate().skip(start) {
+            let mut report = |marker| on_marker(ix + 1, marker);
+            self.state = match self.state {
+                ScanState::Ground if byte == 0x1b => ScanState::Escape,
+                ScanState::Ground => ScanState::Ground,
+                ScanState::Escape => self.after_escape(byte),
+                ScanState::Osc => match byte {
+                    // BEL
+                    0x07 => {
+                        on_marker_from(&self.payload, &mut report);
+                        ScanState::Ground
+                    }
+                    0x1b => ScanState::OscEscape,
+                    // CAN and SUB abort the sequence
+                    0x18 | 0x1a => ScanState::Ground,
+                    _ if self.payload.len() < MAX_OSC_LEN => {
+                        self.payload.push(byte);
+                        ScanState::Osc
+                    }
+                    _ => ScanState::Ground,
+                },
+                // ST
+                ScanState::OscEscape if byte == b'\\' => {
+                    on_marker_from(&self.payload, &mut report);
+                    ScanState::Ground
+                }
+                ScanState::OscEscape => self.after_escape(byte),
+            };
+        }
+    }
+
+    fn after_escape(&mut self, byte: u8) -> ScanState {
+        match byte {
+            b']' => {
+                self.payload.clear();
+                ScanState::Osc
+            }
+            0x1b => ScanState::Escape,
+            _ => ScanState::Ground,
+        }
+    }
+}
+
+fn on_marker_from(payload: &[u8], on_marker: &mut impl FnMut(ShellMarker)) {
+    if let Some(marker) = std::str::from_utf8(payload).ok().and_then(parse_marker) {
+        on_marker(marker);
+    }
+}
+
+fn parse_marker(payload: &str) -> Option<ShellMarker> {
+    let (code, rest) = payload.split_once(';').unwrap_or((payload, ""));
+    match code {
+        "133" | "633" => {
+            let mut params = rest.split(';');
+            match (code, params.next()?) {
+                (_, "A") => Some(ShellMarker::PromptStart),
+                (_, "B") => Some(ShellMarker::CommandStart),
+                (_, "C") => Some(ShellMarker::CommandExecuted),
+                (_, "D") => Some(ShellMarker::CommandFinished {
+                    exit_code: params.next().and_then(|code| code.parse().ok()),
+                }),
+                ("633", "E") => Some(ShellMarker::CommandLine(unescape_633(params.next()?))),
+                ("633", "P") => {
+                    let cwd = params.next()?.strip_prefix("Cwd=")?;
+                    Some(ShellMarker::WorkingDirectory(PathBuf::from(unescape_633(
+                        cwd,
+                    ))))
+                }
+                _ => None,
+            }
+        }
+        "7" => {
+            let url = rest.strip_prefix("file://")?;
+            let path = &url[url.find('/')?..];
+            let path = urlencoding::decode(path).ok()?;
+            Some(ShellMarker::WorkingDirectory(PathBuf::from(path.as_ref())))
+        }
+        _ => None,
+    }
+}
+
+/// Undoes the escaping of OSC 633 values: `\\` for a backslash, and `\xAB` for any byte.
+fn unescape_633(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match rest {
+            [b'\\', tail @ ..] => {
+                bytes.push(b'\\');
+                rest = tail;
+            }
+            [b'x', high, low, tail @ ..] => {
+                match std::str::from_utf8(&[*high, *low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(escaped) => {
+                        bytes.push(escaped);
+                        rest = tail;
+                    }
+                    None => bytes.push(b'\\'),
+                }
+            }
+            _ => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl ShellKind {
+    fn new(program: &str) -> Option<Self> {
+        match Path::new(program).file_stem()?.to_str()? {
+            "bash" => Some(Self::Bash),
+            "zsh" => Some(Self::Zsh),
+            "fish" => Some(Self::Fish),
+            _ => None,
+        }
+    }
+}
+
+/// Sets up the shell to load Zed's integration script when it starts.
+///
+/// `program` is the shell about to be spawned, or `None` for the system shell.
+/// Returns the program and arguments to spawn instead, if they need to change.
+pub(crate) fn inject(
+    program: Option<&str>,
+    args: Option<&[String]>,
+    env: &mut HashMap<String, String>,
+) -> Option<(String, Vec<String>)> {
+    if cfg!(windows) {
+        return None;
+    }
+    let is_system_shell = program.is_none();
+    let program = match program {
+        Some(program) => program.to_owned(),
+        None => std::env::var("SHELL").ok()?,
+    };
+    let kind = ShellKind::new(&program)?;
+    let scripts_dir = scripts_dir()?;
+    let inherited = |env: &HashMap<String, String>, name: &str| {
+        env.get(name).cloned().or_else(|| std::env::var(name).ok())
+    };
+
+    match kind {
+        ShellKind::Bash => {
+            // Bash has no way to add a startup file, so the script replaces `.bashrc` and
+            // sources the user's startup files itself.
+            if args.is_some_and(|args| !args.is_empty()) {
+                return None;
+            }
+            // Alacritty starts the system shell as a login shell on macOS.
+            if is_system_shell && cfg!(target_os = "macos") {
+                env.insert("ZED_SHELL_LOGIN".to_string(), "1".to_string());
+            }
+            let init_file = scripts_dir.join("zed.bash");
+            Some((
+                program,
+                vec![
+                    "--init-file".to_string(),
+                    init_file.to_string_lossy().into_owned(),
+                ],
+            ))
+        }
+        ShellKind::Zsh => {
+            if let Some(zdotdir) = inherited(env, "ZDOTDIR") {
+                env.insert("ZED_USER_ZDOTDIR".to_string(), zdotdir);
+            }
+            env.insert(
+                "ZDOTDIR".to_string(),
+                scripts_dir.join("zsh").to_string_lossy().into_owned(),
+            );
+            None
+        }
+        ShellKind::Fish => {
+            let data_dirs = inherited(env, "XDG_DATA_DIRS");
+            env.insert(
+                "ZED_USER_XDG_DATA_DIRS".to_string(),
+                data_dirs.clone().unwrap_or_default(),
+            );
+            env.insert(
+                "XDG_DATA_DIRS".to_string(),
+                format!(
+                    "{}:{}",
+                    scripts_dir.to_string_lossy(),
+                    data_dirs
+                        .as_deref()
+                        .unwrap_or("/usr/local/share:/usr/share")
+                ),
+            );
+            None
+        }
+    }
+}
+
+/// The directory with the integration scripts, written once per Zed process.
+fn scripts_dir() -> Option<&'static Path> {
+    static SCRIPTS_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    SCRIPTS_DIR
+        .get_or_init(|| {
+            let dir = paths::temp_dir().join("shell_integration");
+            write_scripts(&dir)
+                .context("writing shell integration scripts")
+                .log_err()?;
+            Some(dir)
+        })
+        .as_deref()
+}
+
+fn write_scripts(dir: &Path) -> Result<()> {
+    let zsh_dir = dir.join("zsh");
+    let fish_dir = dir.join("fish").join("vendor_conf.d");
+    std::fs::create_dir_all(&zsh_dir)?;
+    std::fs::create_dir_all(&fish_dir)?;
+
+    std::fs::write(dir.join("zed.bash"), BASH_SCRIPT)?;
+    std::fs::write(fish_dir.join("zed-shell-integration.fish"), FISH_SCRIPT)?;
+    std::fs::write(zsh_dir.join("zed.zsh"), ZSH_SCRIPT)?;
+    // Zsh reads its startup files from `ZDOTDIR`, so each one loads the user's file of the same
+    // name, and `.zshrc` additionally sets up the integration and gives `ZDOTDIR` back.
+    for name in [".zshenv", ".zprofile", ".zshrc"] {
+        let mut contents = format!(
+            "ZDOTDIR=\"${{ZED_USER_ZDOTDIR:-$HOME}}\"\n\
+             [[ -r \"$ZDOTDIR/{name}\" ]] && source \"$ZDOTDIR/{name}\"\n\
+             ZED_USER_ZDOTDIR=\"$ZDOTDIR\"\n"
+        );
+        if name == ".zshrc" {
+            contents.push_str(&format!(
+                "source {}\n\
+                 if [[ \"$ZED_USER_ZDOTDIR\" == \"$HOME\" ]]; then unset ZDOTDIR; else export ZDOTDIR=\"$ZED_USER_ZDOTDIR\"; fi\n\
+                 unset ZED_USER_ZDOTDIR\n",
+                shell_quote(&zsh_dir.join("zed.zsh"))
+            ));
+        } else {
+            contents.push_str(&format!("ZDOTDIR={}\n", shell_quote(&zsh_dir)));
+        }
+        std::fs::write(zsh_dir.join(name), contents)?;
+    }
+    Ok(())
+}
+
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(chunks: &[&[u8]]) -> Vec<ShellMarker> {
+        let mut scanner = OscScanner::default();
+        let mut markers = Vec::new();
+        for chunk in chunks {
+            scanner.scan(chunk, |_, marker| markers.push(marker));
+        }
+        markers
+    }
+
+    #[test]
+    fn test_scanning_shell_markers() {
+        assert_eq!(
+            scan(&[
+                b"\x1b]133;A\x07$ \x1b]133;B\x1b\\",
+                b"\x1b]633;E;echo a\\x3bb \\\\ c\x07\x1b]133;C\x07a;b \\ c\r\n",
+                b"\x1b]133;D;1\x07\x1b]7;file://host/tmp/some%20dir\x07\x1b]2;title\x07",
+            ]),
+            vec![
+                ShellMarker::PromptStart,
+                ShellMarker::CommandStart,
+                ShellMarker::CommandLine("echo a;b \\ c".to_string()),
+                ShellMarker::CommandExecuted,
+                ShellMarker::CommandFinished { exit_code: Some(1) },
+                ShellMarker::WorkingDirectory(PathBuf::from("/tmp/some dir")),
+            ]
+        );
+
+        // Sequences split between reads, and sequences interrupted by another escape
+        assert_eq!(
+            scan(&[b"\x1b]13", b"3;D\x1b", b"\\\x1b]133\x1b]133;A\x07"]),
+            vec![
+                ShellMarker::CommandFinished { exit_code: None },
+                ShellMarker::PromptStart,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_blocks() {
+        let now = Instant::now();
+        let mut blocks = CommandBlocks::default();
+        let markers = [
+            (ShellMarker::PromptStart, 0),
+            (ShellMarker::CommandStart, 0),
+            (ShellMarker::CommandLine("ls".to_string()), 0),
+            (ShellMarker::CommandExecuted, 1),
+            (ShellMarker::CommandFinished { exit_code: Some(0) }, 4),
+            // An interrupted prompt is dropped
+            (ShellMarker::PromptStart, 4),
+            (ShellMarker::CommandStart, 4),
+            (ShellMarker::PromptStart, 5),
+            (ShellMarker::CommandStart, 5),
+            (ShellMarker::CommandLine("false".to_string()), 5),
+            (ShellMarker::CommandExecuted, 6),
+            (ShellMarker::CommandFinished { exit_code: Some(1) }, 6),
+            (ShellMarker::PromptStart, 6),
+        ];
+        for (marker, line) in markers {
+            blocks.apply(marker, line, now);
+        }
+
+        let all = blocks.blocks().collect::<Vec<_>>();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].command.as_deref(), Some("ls"));
+        assert_eq!(all[0].output_line, Some(1));
+        assert_eq!(blocks.output_end(all[0]), Some(5));
+        assert_eq!(
+            all[1].status(),
+            CommandStatus::Finished { exit_code: Some(1) }
+        );
+        assert_eq!(all[2].status(), CommandStatus::Prompt);
+        assert_eq!(
+            blocks.last_finished().map(|block| block.prompt_line),
+            Some(5)
+        );
+    }
+}
@@ -0,0 +1,56 @@
+# Zed shell integration for bash, loaded with `bash --init-file`.
+# Reports prompts, commands and their exit codes with OSC 133 and OSC 633, and the working
+# directory with OSC 7.
+
+if [[ -n "$ZED_SHELL_LOGIN" ]]; then
+    unset ZED_SHELL_LOGIN
+    [[ -r /etc/profile ]] && . /etc/profile
+    if [[ -r ~/.bash_profile ]]; then
+        . ~/.bash_profile
+    elif [[ -r ~/.bash_login ]]; then
+        . ~/.bash_login
+    elif [[ -r ~/.profile ]]; then
+        . ~/.profile
+    fi
+else
+    [[ -r ~/.bashrc ]] && . ~/.bashrc
+fi
+
+if [[ -z "$__zed_shell_integration" && $- == *i* ]]; then
+    __zed_shell_integration=1
+
+    __zed_escape() {
+        local value="$1"
+        value="${value//\\/\\\\}"
+        value="${value//;/\\x3b}"
+        value="${value//$'\n'/\\x0a}"
+        printf '%s' "$value"
+    }
+
+    # Runs first, while `$?` is still the exit code of the command.
+    __zed_prompt_start() {
+        local status=$?
+        printf '\e]133;D;%s\a' "$status"
+        printf '\e]7;file://%s%s\a' "$HOSTNAME" "$PWD"
+        return $status
+    }
+
+    # Runs last, after prompt frameworks had a chance to rewrite `PS1`.
+    __zed_prompt_end() {
+        local status=$?
+        if [[ "$PS1" != *'133;A'* ]]; then
+            PS1='\[\e]133;A\a\]'"$PS1"'\[\e]133;B\a\]'
+        fi
+        return $status
+    }
+
+    __zed_command_line() {
+        local command
+        command="$(HISTTIMEFORMAT= builtin history 1)"
+        command="${command#*[0-9]*  }"
+        printf '\e]633;E;%s\a' "$(__zed_escape "$command")"
+    }
+
+    PROMPT_COMMAND="__zed_prompt_start${PROMPT_COMMAND:+; $PROMPT_COMMAND}; __zed_prompt_end"
+    PS0='$(__zed_command_line)\e]133;C\a'"$PS0"
+fi
@@ -0,0 +1,29 @@
+# Zed shell integration for fish, loaded from `vendor_conf.d` through `XDG_DATA_DIRS`.
+# Reports prompts, commands and their exit codes with OSC 133 and OSC 633, and the working
+# directory with OSC 7.
+
+if test -n "$ZED_USER_XDG_DATA_DIRS"
+    set -gx XDG_DATA_DIRS $ZED_USER_XDG_DATA_DIRS
+else
+    set -e XDG_DATA_DIRS
+end
+set -e ZED_USER_XDG_DATA_DIRS
+
+status is-interactive; or exit
+set -q __zed_shell_integration; and exit
+set -g __zed_shell_integration 1
+
+function __zed_prompt_start --on-event fish_prompt
+    printf '\e]7;file://%s%s\a' (hostname) $PWD
+    printf '\e]133;A\a'
+end
+
+function __zed_preexec --on-event fish_preexec
+    set -l command (string replace -a -- '\\' '\\\\' $argv[1] | string replace -a -- ';' '\\x3b' | string join -- '\\x0a')
+    printf '\e]633;E;%s\a' $command
+    printf '\e]133;C\a'
+end
+
+function __zed_postexec --on-event fish_postexec
+    printf '\e]133;D;%s\a' $status
+end
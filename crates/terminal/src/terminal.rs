@@ -3,7 +3,11 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+mod pty_scanner;
+mod scrollback;
+mod shell_integration;
 mod terminal_hyperlinks;
+mod terminal_images;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -25,11 +29,14 @@ use alacritty_terminal::{
         ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
     },
 };
-use anyhow::{Result, bail};
+use anyhow::{Context as _, Result, bail};
 
 use futures::{
     FutureExt,
-    channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded},
+    channel::{
+        mpsc::{UnboundedReceiver, UnboundedSender, unbounded},
+        oneshot,
+    },
 };
 
 use mappings::mouse::{
@@ -40,15 +47,19 @@ use mappings::mouse::{
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
 use pty_info::PtyProcessInfo;
+use pty_scanner::{OutputParser, ScanningPty};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{CommandBlocks, ShellMarker};
 use smol::channel::{Receiver, Sender};
-use task::{HideStrategy, Shell, TaskId};
+use task::{BackgroundTaskPatterns, HideStrategy, Shell, TaskId};
 use terminal_hyperlinks::RegexSearches;
+use terminal_images::{ImageEvent, TerminalImages};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use urlencoding;
-use util::{paths::home_dir, truncate_and_trailoff};
+use util::{ResultExt as _, paths::home_dir, truncate_and_trailoff};
 
 use std::{
     borrow::Cow,
@@ -57,11 +68,18 @@ use std::{
     ops::{Deref, RangeInclusive},
     path::PathBuf,
     process::ExitStatus,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
     time::Instant,
 };
 use thiserror::Error;
 
+pub use scrollback::{MAX_SAVED_SCROLLBACK_BYTES, SavedScrollback};
+pub use shell_integration::{CommandBlock, CommandStatus};
+pub use terminal_images::{ImagePlacement, TerminalImage, is_image_placeholder};
+
 use gpui::{
     App, AppContext as _, Bounds, ClipboardItem, Context, EventEmitter, Hsla, Keystroke, Modifiers,
     MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Point, Rgba,
@@ -103,6 +121,14 @@ actions!(
         ToggleViMode,
         /// Selects all text in the terminal.
         SelectAll,
+        /// Scrolls to the previous shell prompt.
+        ScrollToPreviousPrompt,
+        /// Scrolls to the next shell prompt.
+        ScrollToNextPrompt,
+        /// Copies the output of the last finished shell command to the clipboard.
+        CopyLastCommandOutput,
+        /// Runs the last shell command again.
+        RerunLastCommand,
     ]
 );
 
@@ -130,6 +156,10 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The task running in the terminal has finished, and its whole output is available.
+    TaskFinished,
+    /// The background task running in the terminal became ready, or busy again.
+    TaskReadinessChanged,
 }
 
 #[derive(Clone, Debug)]
@@ -353,6 +383,7 @@ impl TerminalBuilder {
         is_ssh_terminal: bool,
         window_id: u64,
         completion_tx: Option<Sender<Option<ExitStatus>>>,
+        restored_scrollback: Option<SavedScrollback>,
         cx: &App,
         activation_script: Vec<String>,
     ) -> Result<TerminalBuilder> {
@@ -380,7 +411,7 @@ impl TerminalBuilder {
             title_override: Option<SharedString>,
         }
 
-        let shell_params = match shell.clone() {
+        let mut shell_params = match shell.clone() {
             Shell::System => {
                 #[cfg(target_os = "windows")]
                 {
@@ -408,6 +439,26 @@ impl TerminalBuilder {
         };
         let terminal_title_override = shell_params.as_ref().and_then(|e| e.title_override.clone());
 
+        // Keep the injected variables out of the template, so that cloned terminals set them up anew.
+        let mut spawn_env = env.clone();
+        if task.is_none()
+            && !is_ssh_terminal
+            && TerminalSettings::try_get(cx).is_some_and(|settings| settings.shell_integration)
+            && let Some((program, args)) = shell_integration::inject(
+                shell_params.as_ref().map(|params| params.program.as_str()),
+                shell_params
+                    .as_ref()
+                    .and_then(|params| params.args.as_deref()),
+                &mut spawn_env,
+            )
+        {
+            shell_params = Some(ShellParams {
+                program,
+                args: Some(args),
+                title_override: terminal_title_override.clone(),
+            });
+        }
+
         #[cfg(windows)]
         let shell_program = shell_params.as_ref().map(|params| {
             use util::ResultExt;
@@ -428,7 +479,7 @@ impl TerminalBuilder {
                     .clone()
                     .or_else(|| Some(home_dir().to_path_buf())),
                 drain_on_exit: true,
-                env: env.clone().into_iter().collect(),
+                env: spawn_env.into_iter().collect(),
             }
         };
 
@@ -464,6 +515,12 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Written before the shell starts, so that its first prompt is printed below.
+        if let Some(scrollback) = restored_scrollback {
+            scrollback.restore(&mut term);
+        }
+
+        let scrolled_lines = Arc::new(AtomicUsize::new(term.history_size()));
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -479,6 +536,17 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let (shell_markers_tx, shell_markers_rx) = unbounded();
+        let (image_events_tx, image_events_rx) = unbounded();
+        let pty = ScanningPty::new(
+            pty,
+            term.clone(),
+            ZedListener(events_tx.clone()),
+            TerminalBounds::default().into(),
+            OutputParser::new(scrolled_lines.clone(), scrolling_history),
+            shell_markers_tx,
+            image_events_tx,
+        );
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -518,6 +586,11 @@ impl TerminalBuilder {
             is_ssh_terminal,
             last_mouse_move_time: Instant::now(),
             last_hyperlink_search_position: None,
+            shell_markers_rx,
+            command_blocks: CommandBlocks::default(),
+            scrolled_lines,
+            image_events_rx,
+            images: TerminalImages::default(),
             #[cfg(windows)]
             shell_program,
             activation_script: activation_script.clone(),
@@ -652,6 +725,11 @@ pub struct TerminalContent {
     pub cells: Vec<IndexedCell>,
     pub mode: TermMode,
     pub display_offset: usize,
+    pub history_size: usize,
+    /// Lines scrolled into the scrollback so far, including the ones dropped from it. Command
+    /// blocks count their lines from the first line of the terminal, so the line at the top of
+    /// the viewport is `scrolled_lines - display_offset`.
+    pub scrolled_lines: usize,
     pub selection_text: Option<String>,
     pub selection: Option<SelectionRange>,
     pub cursor: RenderableCursor,
@@ -660,6 +738,8 @@ pub struct TerminalContent {
     pub last_hovered_word: Option<HoveredWord>,
     pub scrolled_to_top: bool,
     pub scrolled_to_bottom: bool,
+    /// Images reaching into the viewport, anchored at points of the grid.
+    pub images: Vec<ImagePlacement>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -675,6 +755,8 @@ impl Default for TerminalContent {
             cells: Default::default(),
             mode: Default::default(),
             display_offset: Default::default(),
+            history_size: Default::default(),
+            scrolled_lines: Default::default(),
             selection_text: Default::default(),
             selection: Default::default(),
             cursor: RenderableCursor {
@@ -686,6 +768,7 @@ impl Default for TerminalContent {
             last_hovered_word: None,
             scrolled_to_top: false,
             scrolled_to_bottom: false,
+            images: Vec::new(),
         }
     }
 }
@@ -719,6 +802,13 @@ pub struct Terminal {
     is_ssh_terminal: bool,
     last_mouse_move_time: Instant,
     last_hyperlink_search_position: Option<Point<Pixels>>,
+    shell_markers_rx: UnboundedReceiver<(ShellMarker, usize)>,
+    command_blocks: CommandBlocks,
+    /// Lines scrolled into the scrollback since the terminal started, updated by the PTY reader
+    /// while it holds the lock of the grid.
+    scrolled_lines: Arc<AtomicUsize>,
+    image_events_rx: UnboundedReceiver<ImageEvent>,
+    images: TerminalImages,
     #[cfg(windows)]
     shell_program: Option<String>,
     template: CopyTemplate,
@@ -745,6 +835,125 @@ pub struct TaskState {
     pub show_summary: bool,
     pub show_command: bool,
     pub show_rerun: bool,
+    /// Set for background tasks, which are done once they are ready rather than once they exit.
+    pub background: Option<BackgroundTaskState>,
+}
+
+/// Readiness of a background task, e.g. a dev server or a watcher, tracked from its output.
+pub struct BackgroundTaskState {
+    begins: Option<Regex>,
+    ends: Option<Regex>,
+    ready: bool,
+    ready_waiters: Vec<oneshot::Sender<Option<ExitStatus>>>,
+    output: TaskOutputReader,
+}
+
+impl BackgroundTaskState {
+    pub fn new(patterns: &BackgroundTaskPatterns) -> Self {
+        let regex = |pattern: &Option<String>| {
+            let pattern = pattern.as_deref()?;
+            Regex::new(pattern)
+                .with_context(|| format!("Invalid background task pattern `{pattern}`"))
+                .log_err()
+        };
+        let ends = regex(&patterns.ends);
+        Self {
+            begins: regex(&patterns.begins),
+            ready: ends.is_none(),
+            ends,
+            ready_waiters: Vec::new(),
+            output: TaskOutputReader::default(),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Matches the output printed since the last update against the patterns, the latest line
+    /// matching either of them tells the current state. Returns whether the state changed.
+    fn update<T>(&mut self, term: &Term<T>, scrolled_lines: usize) -> bool {
+        let Some(ends) = &self.ends else {
+            return false;
+        };
+        if self.ready && self.begins.is_none() {
+            return false;
+        }
+
+        // The line at the cursor may still be printed to, so it gets matched again next time.
+        let mut ready = None;
+        for line in self.output.read(term, scrolled_lines, true) {
+            if ends.is_match(&line) {
+                ready = Some(true);
+            } else if self
+                .begins
+                .as_ref()
+                .is_some_and(|begins| begins.is_match(&line))
+            {
+                ready = Some(false);
+            }
+        }
+
+        match ready {
+            Some(ready) if ready != self.ready => {
+                self.ready = ready;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Reads the output of a task as it gets printed, a line at a time once it's complete.
+#[derive(Default)]
+pub struct TaskOutputReader {
+    /// The first output line not read yet, counted from the top of the scrollback.
+    next_line: usize,
+}
+
+impl TaskOutputReader {
+    /// Returns the non-empty lines printed since the last read, with the rows of wrapped lines
+    /// joined and trailing whitespace trimmed. The line at the cursor may still be printed to:
+    /// it's only returned with `include_cursor_line`, and then read again next time.
+    fn read<T>(
+        &mut self,
+        term: &Term<T>,
+        scrolled_lines: usize,
+        include_cursor_line: bool,
+    ) -> Vec<String> {
+        let grid = term.grid();
+        let scrolled_lines = scrolled_lines as i32;
+        let last_column = Column(grid.columns() - 1);
+        let wraps = |line: Line| grid[line][last_column].flags.contains(Flags::WRAPLINE);
+        let cursor_line = grid.cursor.point.line;
+        let mut line = Line(self.next_line as i32 - scrolled_lines).max(grid.topmost_line());
+
+        let mut cursor_line_start = cursor_line;
+        while cursor_line_start > grid.topmost_line() && wraps(Line(cursor_line_start.0 - 1)) {
+            cursor_line_start = Line(cursor_line_start.0 - 1);
+        }
+        self.next_line = (cursor_line_start.0 + scrolled_lines).max(0) as usize;
+        let last_line = if include_cursor_line {
+            cursor_line
+        } else {
+            Line(cursor_line_start.0 - 1)
+        };
+
+        let mut lines = Vec::new();
+        while line <= last_line {
+            let mut text = row_to_string(&grid[line]);
+            while line < last_line && wraps(line) {
+                line = Line(line.0 + 1);
+                text.push_str(&row_to_string(&grid[line]));
+            }
+            let text = text.trim_end();
+            if !text.is_empty() {
+                lines.push(text.to_owned());
+            }
+            line = Line(line.0 + 1);
+        }
+        lines
+    }
 }
 
 /// A status of the current terminal tab's task.
@@ -829,6 +1038,9 @@ impl Terminal {
             }
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
+                self.process_shell_markers();
+                self.process_image_events();
+                self.update_background_task_readiness(cx);
 
                 if self.pty_info.has_changed() {
                     cx.emit(Event::TitleChanged);
@@ -905,6 +1117,7 @@ impl Terminal {
                 if (new_cursor.line.0 as usize) < term.screen_lines() - 1 {
                     term.grid_mut().reset_region((new_cursor.line + 1)..);
                 }
+                self.command_blocks.clear();
 
                 cx.emit(Event::Wakeup);
             }
@@ -1222,6 +1435,50 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
     }
 
+    /// Scrolls the closest prompt above the viewport to its top.
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let top_line = self.viewport_top_line();
+        if let Some(block) = self
+            .command_blocks
+            .blocks()
+            .rev()
+            .find(|block| block.prompt_line < top_line)
+        {
+            self.scroll_line_to_top(block.prompt_line);
+        }
+    }
+
+    /// Scrolls the closest prompt below the top of the viewport to its top.
+    pub fn scroll_to_next_prompt(&mut self) {
+        let top_line = self.viewport_top_line();
+        match self
+            .command_blocks
+            .blocks()
+            .find(|block| block.prompt_line > top_line)
+        {
+            Some(block) => self.scroll_line_to_top(block.prompt_line),
+            None => self.scroll_to_bottom(),
+        }
+    }
+
+    /// The line at the top of the viewport, counted like the lines of command blocks.
+    fn viewport_top_line(&self) -> usize {
+        self.last_content
+            .scrolled_lines
+            .saturating_sub(self.last_content.display_offset)
+    }
+
+    fn scroll_line_to_top(&mut self, line: usize) {
+        let display_offset = self
+            .last_content
+            .scrolled_lines
+            .saturating_sub(line)
+            .min(self.last_content.history_size);
+        let delta = display_offset as i32 - self.last_content.display_offset as i32;
+        self.events
+            .push_back(InternalEvent::Scroll(AlacScroll::Delta(delta)));
+    }
+
     pub fn scrolled_to_top(&self) -> bool {
         self.last_content.scrolled_to_top
     }
@@ -1397,11 +1654,26 @@ impl Terminal {
         while let Some(e) = self.events.pop_front() {
             self.process_terminal_event(&e, &mut terminal, window, cx)
         }
+        // The images of the main screen are kept while the alternate screen is shown.
+        if !terminal.mode().contains(TermMode::ALT_SCREEN) {
+            self.images
+                .retain_anchored(terminal.grid(), self.scrolled_lines.load(Ordering::Relaxed));
+        }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content = Self::make_content(
+            &terminal,
+            &self.images,
+            self.scrolled_lines.load(Ordering::Relaxed),
+            &self.last_content,
+        );
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        images: &TerminalImages,
+        scrolled_lines: usize,
+        last_content: &TerminalContent,
+    ) -> TerminalContent {
         let content = term.renderable_content();
 
         // Pre-allocate with estimated size to reduce reallocations
@@ -1423,6 +1695,8 @@ impl Terminal {
             cells,
             mode: content.mode,
             display_offset: content.display_offset,
+            history_size: term.history_size(),
+            scrolled_lines,
             selection_text,
             selection: content.selection,
             cursor: content.cursor,
@@ -1431,6 +1705,7 @@ impl Terminal {
             last_hovered_word: last_content.last_hovered_word.clone(),
             scrolled_to_top: content.display_offset == term.history_size(),
             scrolled_to_bottom: content.display_offset == 0,
+            images: images.placements(term.grid(), content.display_offset),
         }
     }
 
@@ -1817,7 +2092,10 @@ impl Terminal {
             // the working directory on the client and persist that.
             None
         } else {
-            self.client_side_working_directory()
+            self.command_blocks
+                .working_directory()
+                .map(|directory| directory.to_path_buf())
+                .or_else(|| self.client_side_working_directory())
         }
     }
 
@@ -1908,17 +2186,58 @@ impl Terminal {
         Task::ready(None)
     }
 
-    fn register_task_finished(&mut self, error_code: Option<i32>, cx: &mut Context<Terminal>) {
-        let e: Option<ExitStatus> = error_code.map(|code| {
-            #[cfg(unix)]
-            {
-                std::os::unix::process::ExitStatusExt::from_raw(code)
+    /// Waits for the task to be ready if it runs in the background, or to complete otherwise.
+    /// A background task that exits before getting ready resolves with its exit status.
+    pub fn wait_for_task_ready(&mut self, cx: &App) -> Task<Option<ExitStatus>> {
+        if let Some(task) = &mut self.task
+            && task.status == TaskStatus::Running
+            && let Some(background) = &mut task.background
+        {
+            if background.ready {
+                return Task::ready(Some(exit_status(0)));
             }
-            #[cfg(windows)]
-            {
-                std::os::windows::process::ExitStatusExt::from_raw(code as u32)
+            let (tx, rx) = oneshot::channel();
+            background.ready_waiters.push(tx);
+            return cx.background_spawn(async move { rx.await.ok().flatten() });
+        }
+        self.wait_for_completed_task(cx)
+    }
+
+    fn update_background_task_readiness(&mut self, cx: &mut Context<Self>) {
+        let Some(task) = &mut self.task else {
+            return;
+        };
+        if task.status != TaskStatus::Running {
+            return;
+        }
+        let Some(background) = &mut task.background else {
+            return;
+        };
+        let scrolled_lines = self.scrolled_lines.load(Ordering::Relaxed);
+        if !background.update(&self.term.lock(), scrolled_lines) {
+            return;
+        }
+        if background.ready {
+            for waiter in background.ready_waiters.drain(..) {
+                waiter.send(Some(exit_status(0))).ok();
             }
-        });
+        }
+        cx.emit(Event::TaskReadinessChanged);
+    }
+
+    /// Returns the lines the task printed since `reader` last read its output. The line at the
+    /// cursor is only read once the task has finished, as it may still be printed to until then.
+    pub fn read_task_output(&self, reader: &mut TaskOutputReader) -> Vec<String> {
+        let finished = self
+            .task
+            .as_ref()
+            .is_some_and(|task| task.status != TaskStatus::Running);
+        let scrolled_lines = self.scrolled_lines.load(Ordering::Relaxed);
+        reader.read(&self.term.lock(), scrolled_lines, finished)
+    }
+
+    fn register_task_finished(&mut self, error_code: Option<i32>, cx: &mut Context<Terminal>) {
+        let e: Option<ExitStatus> = error_code.map(exit_status);
 
         if let Some(tx) = &self.completion_tx {
             tx.try_send(e).ok();
@@ -1943,6 +2262,11 @@ impl Terminal {
                 task.status.register_terminal_exit();
             }
         };
+        if let Some(background) = &mut task.background {
+            for waiter in background.ready_waiters.drain(..) {
+                waiter.send(e).ok();
+            }
+        }
 
         let (finished_successfully, task_line, command_line) = task_summary(task, error_code);
         let mut lines_to_show = Vec::new();
@@ -1960,6 +2284,7 @@ impl Terminal {
             // After the task summary is output once, no more text is appended to the terminal.
             unsafe { append_text_to_term(&mut self.term.lock(), &lines_to_show) };
         }
+        cx.emit(Event::TaskFinished);
 
         match task.hide {
             HideStrategy::Never => {}
@@ -1974,6 +2299,89 @@ impl Terminal {
         }
     }
 
+    /// Commands run at the shell prompt, if the shell reports them through the shell integration.
+    pub fn command_blocks(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> {
+        self.command_blocks.blocks()
+    }
+
+    /// The exit status of the last command the shell reported as finished.
+    pub fn last_command_exit_status(&self) -> Option<ExitStatus> {
+        self.command_blocks
+            .last_finished()?
+            .exit_code
+            .map(exit_status)
+    }
+
+    /// The output of the last command the shell reported as finished.
+    pub fn last_command_output(&self) -> Option<String> {
+        let block = self.command_blocks.last_finished()?;
+        let output_start = block.output_line?;
+        let output_end = self.command_blocks.output_end(block)?;
+        if output_end <= output_start {
+            return Some(String::new());
+        }
+
+        let term = self.term.lock_unfair();
+        let scrolled_lines = self.scrolled_lines.load(Ordering::Relaxed) as i32;
+        let start = Line(output_start as i32 - scrolled_lines).max(term.topmost_line());
+        let end = Line(output_end as i32 - 1 - scrolled_lines).min(term.bottommost_line());
+        if end < start {
+            return None;
+        }
+        let output = term.bounds_to_string(
+            AlacPoint::new(start, Column(0)),
+            AlacPoint::new(end, term.last_column()),
+        );
+        Some(output.trim_end().to_string())
+    }
+
+    /// Runs the last command entered at the shell prompt again, unless a command is running.
+    pub fn rerun_last_command(&mut self) {
+        if self
+            .command_blocks
+            .blocks()
+            .next_back()
+            .is_some_and(|block| block.status() == CommandStatus::Running)
+        {
+            return;
+        }
+        if let Some(command) = self
+            .command_blocks
+            .blocks()
+            .rev()
+            .find_map(|block| block.command.clone())
+        {
+            self.input(format!("{command}\r").into_bytes());
+        }
+    }
+
+    fn process_shell_markers(&mut self) {
+        let now = Instant::now();
+        while let Ok(Some((marker, line))) = self.shell_markers_rx.try_next() {
+            self.command_blocks.apply(marker, line, now);
+        }
+    }
+
+    /// The end of the terminal's scrollback, to restore it after a restart.
+    pub fn save_scrollback(&self) -> SavedScrollback {
+        let term = self.term.lock();
+        SavedScrollback::from_grid(term.grid(), MAX_SAVED_SCROLLBACK_BYTES)
+    }
+
+    fn process_image_events(&mut self) {
+        while let Ok(Some(event)) = self.image_events_rx.try_next() {
+            match event {
+                ImageEvent::Placed {
+                    id,
+                    kitty_id,
+                    image,
+                } => self.images.place(id, kitty_id, image),
+                ImageEvent::Deleted { kitty_id } => self.images.delete(kitty_id),
+                ImageEvent::Reply(reply) => self.write_to_pty(reply),
+            }
+        }
+    }
+
     pub fn vi_mode_enabled(&self) -> bool {
         self.vi_mode_enabled
     }
@@ -1995,6 +2403,7 @@ impl Terminal {
             self.is_ssh_terminal,
             self.template.window_id,
             None,
+            None,
             cx,
             self.activation_script.clone(),
         )
@@ -2070,6 +2479,17 @@ unsafe fn append_text_to_term(term: &mut Term<ZedListener>, text_lines: &[&str])
     }
 }
 
+fn exit_status(code: i32) -> ExitStatus {
+    #[cfg(unix)]
+    {
+        std::os::unix::process::ExitStatusExt::from_raw(code)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::process::ExitStatusExt::from_raw(code as u32)
+    }
+}
+
 impl Drop for Terminal {
     fn drop(&mut self) {
         self.pty_tx.0.send(Msg::Shutdown).ok();
@@ -2198,8 +2618,9 @@ mod tests {
         rgb_for_index,
     };
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, test::TermSize},
     };
     use collections::HashMap;
     use gpui::{Pixels, Point, TestAppContext, bounds, point, size};
@@ -2227,6 +2648,7 @@ mod tests {
                 false,
                 0,
                 Some(completion_tx),
+                None,
                 cx,
                 vec![],
             )
@@ -2243,6 +2665,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_background_task_initial_readiness() {
+        let state = |begins: Option<&str>, ends: Option<&str>| {
+            BackgroundTaskState::new(&task::BackgroundTaskPatterns {
+                begins: begins.map(ToOwned::to_owned),
+                ends: ends.map(ToOwned::to_owned),
+            })
+        };
+        assert!(state(None, None).is_ready());
+        assert!(state(Some("Compiling"), None).is_ready());
+        assert!(!state(None, Some("Listening on")).is_ready());
+        assert!(
+            state(Some("Compiling"), Some("(")).is_ready(),
+            "An invalid end pattern should not keep the task from getting ready"
+        );
+    }
+
+    #[test]
+    fn test_background_task_readiness_transitions() {
+        let mut state = BackgroundTaskState::new(&task::BackgroundTaskPatterns {
+            begins: Some("^Compiling".to_owned()),
+            ends: Some("^Listening on".to_owned()),
+        });
+        // A scrollback of 3 lines, which is full after a few requests.
+        let config = Config {
+            scrolling_history: 3,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &TermSize::new(20, 4), VoidListener);
+        let scrolled_lines = Arc::new(AtomicUsize::new(0));
+        let mut parser = OutputParser::new(scrolled_lines.clone(), 3);
+        // Prints the output, and tells whether the readiness changed, and whether the task is ready.
+        let mut print = |output: &str| {
+            parser.advance(&mut term, output.as_bytes(), |_, _| {});
+            let changed = state.update(&term, scrolled_lines.load(Ordering::Relaxed));
+            (changed, state.is_ready())
+        };
+
+        assert_eq!(print("Compiling app\r\n"), (false, false));
+
+        // The end pattern is matched once the whole line is printed, even if it wraps.
+        assert_eq!(print("Listening"), (false, false));
+        assert_eq!(print(" on http://localhost:8080\r\n"), (true, true));
+
+        // Output matching no pattern keeps the state, even once the match scrolls away.
+        for request in 0..10 {
+            assert_eq!(print(&format!("GET /{request}\r\n")), (false, true));
+        }
+
+        // A rebuild makes the task busy until it listens again, lines printed between two
+        // updates count in the order they were printed.
+        assert_eq!(print("Compiling app\r\nwarning: unused\r\n"), (true, false));
+        assert_eq!(
+            print("Compiling app\r\nListening on :8080\r\n"),
+            (true, true)
+        );
+        assert_eq!(
+            print("Listening on :8080\r\nCompiling app\r\n"),
+            (true, false)
+        );
+
+        // The line at the cursor is matched again when it gets redrawn.
+        assert_eq!(print("Building 1/2"), (false, false));
+        assert_eq!(print("\rListening on :8080"), (true, true));
+    }
+
+    #[test]
+    fn test_task_output_reader() {
+        let mut reader = TaskOutputReader::default();
+        // A scrollback of 3 lines, much shorter than the output.
+        let config = Config {
+            scrolling_history: 3,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &TermSize::new(20, 4), VoidListener);
+        let scrolled_lines = Arc::new(AtomicUsize::new(0));
+        let mut parser = OutputParser::new(scrolled_lines.clone(), 3);
+        let mut print = |output: &str, include_cursor_line: bool| {
+            parser.advance(&mut term, output.as_bytes(), |_, _| {});
+            reader.read(
+                &term,
+                scrolled_lines.load(Ordering::Relaxed),
+                include_cursor_line,
+            )
+        };
+
+        // Lines are read once complete, wrapped ones joined and empty ones skipped.
+        assert_eq!(
+            print("error: first\r\n\r\n  --> src/", false),
+            ["error: first"]
+        );
+        assert_eq!(print("main.rs:1:1\r\n", false), ["  --> src/main.rs:1:1"]);
+
+        // Every line is read once, even after the first ones scrolled out of the scrollback.
+        let mut lines = Vec::new();
+        for ix in 0..10 {
+            lines.extend(print(&format!("line {ix}\r\n"), false));
+        }
+        let expected = (0..10).map(|ix| format!("line {ix}")).collect::<Vec<_>>();
+        assert_eq!(lines, expected);
+
+        // The line at the cursor is only read when asked for, and then read again next time.
+        assert_eq!(print("warning: last", false), Vec::<String>::new());
+        assert_eq!(print("", true), ["warning: last"]);
+        assert_eq!(print("", true), ["warning: last"]);
+    }
+
     #[test]
     fn test_rgb_for_index() {
         // Test every possible value in the color cube.
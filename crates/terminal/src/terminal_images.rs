@@ -0,0 +1,1237 @@
+//! Inline images, printed with the kitty graphics protocol or as sixels.
+//!
+//! Alacritty drops both kinds of escape sequences, so they are taken out of the PTY output before
+//! it gets parsed, see [`ImageScanner`]. In their place, the cell where the image starts gets a
+//! placeholder char identifying the image, and the cursor is moved past the image. Images are
+//! drawn over the cells from that anchor cell, so they scroll with the grid, and disappear once
+//! their anchor cell is overwritten.
+
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
+
+use alacritty_terminal::{
+    grid::{Dimensions, Grid},
+    index::{Column, Line, Point as AlacPoint},
+    term::cell::Cell,
+};
+use anyhow::{Context as _, Result, anyhow, bail};
+use base64::{
+    Engine as _, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use collections::{HashMap, HashSet};
+use gpui::RenderImage;
+use image::{Frame, ImageFormat, RgbaImage};
+use util::ResultExt as _;
+
+const ESC: u8 = 0x1b;
+const CAN: u8 = 0x18;
+const SUB: u8 = 0x1a;
+
+/// First char of the private use plane whose chars mark the anchor cells of images.
+const PLACEHOLDER_START: u32 = 0xF0000;
+const PLACEHOLDER_COUNT: u32 = 0xFFFE;
+
+/// Longest image sequence that is buffered, larger images are dropped.
+const MAX_SEQUENCE_LEN: usize = 64 * 1024 * 1024;
+/// Longest CSI sequence that is buffered while looking for device attribute requests.
+const MAX_CSI_LEN: usize = 32;
+/// Largest width or height of an image, in pixels.
+const MAX_IMAGE_SIZE: u32 = 10_000;
+/// Most columns or lines an image can cover.
+const MAX_IMAGE_CELLS: u32 = 1_000;
+/// How many images transmitted with the kitty protocol are kept to be displayed later.
+const MAX_TRANSMITTED_IMAGES: usize = 64;
+/// How much decoded image data a terminal keeps, older images are dropped past it.
+const MAX_IMAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// The reply to primary device attributes requests, advertising sixel support.
+const DEVICE_ATTRIBUTES: &[u8] = b"\x1b[?62;4;22c";
+
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Whether the char marks the anchor cell of an image, and should not be drawn as text.
+pub fn is_image_placeholder(c: char) -> bool {
+    placeholder_id(c).is_some()
+}
+
+fn placeholder_char(id: u32) -> char {
+    char::from_u32(PLACEHOLDER_START + id % PLACEHOLDER_COUNT)
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+fn placeholder_id(c: char) -> Option<u32> {
+    (c as u32)
+        .checked_sub(PLACEHOLDER_START)
+        .filter(|id| *id < PLACEHOLDER_COUNT)
+}
+
+/// A decoded image, and the cells it covers from its anchor cell.
+#[derive(Clone)]
+pub struct TerminalImage {
+    pub data: Arc<RenderImage>,
+    pub columns: usize,
+    pub lines: usize,
+}
+
+/// An image shown in the terminal, anchored at a grid point.
+#[derive(Clone)]
+pub struct ImagePlacement {
+    pub point: AlacPoint,
+    pub image: TerminalImage,
+}
+
+pub(crate) enum ImageEvent {
+    /// An image was placed at the cell now holding the placeholder for `id`.
+    Placed {
+        id: u32,
+        kitty_id: Option<u32>,
+        image: TerminalImage,
+    },
+    /// The images placed with the given kitty image id, or all images, were deleted.
+    Deleted { kitty_id: Option<u32> },
+    /// A response to write back to the PTY.
+    Reply(Vec<u8>),
+}
+
+/// Images placed in a terminal, by the id of their placeholder.
+#[derive(Default)]
+pub(crate) struct TerminalImages {
+    images: HashMap<u32, (Option<u32>, TerminalImage)>,
+    order: VecDeque<u32>,
+    bytes: usize,
+    /// The line of each image's anchor cell when it was last found, counted like the terminal's
+    /// scrolled lines, so that it keeps pointing at the same line as the output scrolls.
+    anchors: HashMap<u32, usize>,
+}
+
+impl TerminalImages {
+    pub(crate) fn place(&mut self, id: u32, kitty_id: Option<u32>, image: TerminalImage) {
+        self.remove(id);
+        self.bytes += image_bytes(&image);
+        self.images.insert(id, (kitty_id, image));
+        self.order.push_back(id);
+        while self.bytes > MAX_IMAGE_BYTES
+            && let Some(oldest) = self.order.front().copied()
+        {
+            self.remove(oldest);
+        }
+    }
+
+    pub(crate) fn delete(&mut self, kitty_id: Option<u32>) {
+        let deleted = self
+            .images
+            .iter()
+            .filter(|(_, (image_kitty_id, _))| kitty_id.is_none() || *image_kitty_id == kitty_id)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in deleted {
+            self.remove(id);
+        }
+    }
+
+    fn remove(&mut self, id: u32) {
+        if let Some((_, image)) = self.images.remove(&id) {
+            self.bytes -= image_bytes(&image);
+            self.order.retain(|order_id| *order_id != id);
+            self.anchors.remove(&id);
+        }
+    }
+
+    /// Drops the images whose placeholder is no longer in the grid, as their anchor cell was
+    /// overwritten, cleared, or dropped from the scrollback. The grid is only searched for the
+    /// images that aren't on the line they were last found on, e.g. after the lines got reflowed.
+    pub(crate) fn retain_anchored(&mut self, grid: &Grid<Cell>, scrolled_lines: usize) {
+        let topmost_line = grid.topmost_line().0;
+        let bottommost_line = grid.bottommost_line().0;
+        let row_holds = |line: i32, id: u32| {
+            let row = &grid[Line(line)];
+            (0..grid.columns()).any(|column| placeholder_id(row[Column(column)].c) == Some(id))
+        };
+        let mut missing = self
+            .images
+            .keys()
+            .copied()
+            .filter(|id| {
+                !self.anchors.get(id).is_some_and(|anchor| {
+                    let line = *anchor as i64 - scrolled_lines as i64;
+                    (topmost_line as i64..=bottommost_line as i64).contains(&line)
+                        && row_holds(line as i32, *id)
+                })
+            })
+            .collect::<HashSet<_>>();
+
+        // Images are usually placed at the bottom of the output.
+        for line in (topmost_line..=bottommost_line).rev() {
+            if missing.is_empty() {
+                return;
+            }
+            let row = &grid[Line(line)];
+            for column in 0..grid.columns() {
+                if let Some(id) = placeholder_id(row[Column(column)].c)
+                    && missing.remove(&id)
+                {
+                    let anchor = (line as i64 + scrolled_lines as i64).max(0) as usize;
+                    self.anchors.insert(id, anchor);
+                }
+            }
+        }
+        for id in missing {
+            self.remove(id);
+        }
+    }
+
+    /// The images reaching into the viewport, found by the placeholders in their anchor cells.
+    pub(crate) fn placements(
+        &self,
+        grid: &Grid<Cell>,
+        display_offset: usize,
+    ) -> Vec<ImagePlacement> {
+        let Some(max_lines) = self.images.values().map(|(_, image)| image.lines).max() else {
+            return Vec::new();
+        };
+        let viewport_top = -(display_offset as i32);
+        let top = (viewport_top - max_lines as i32 + 1).max(grid.topmost_line().0);
+        let bottom = (viewport_top + grid.screen_lines() as i32 - 1).min(grid.bottommost_line().0);
+
+        let mut placements = Vec::new();
+        for line in top..=bottom {
+            let row = &grid[Line(line)];
+            for column in 0..grid.columns() {
+                if let Some(id) = placeholder_id(row[Column(column)].c)
+                    && let Some((_, image)) = self.images.get(&id)
+                    && line + image.lines as i32 > viewport_top
+                {
+                    placements.push(ImagePlacement {
+                        point: AlacPoint::new(Line(line), Column(column)),
+                        image: image.clone(),
+                    });
+                }
+            }
+        }
+        placements
+    }
+}
+
+fn image_bytes(image: &TerminalImage) -> usize {
+    image.data.as_bytes(0).map_or(0, |bytes| bytes.len())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    Escape,
+    Csi,
+    Apc,
+    ApcEscape,
+    Dcs,
+    DcsEscape,
+}
+
+/// Where the cursor goes after an image is placed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CursorMovement {
+    /// Stays at the image's top left cell.
+    None,
+    /// Moves to the right of the image, on its last line, as kitty does.
+    AfterImage,
+    /// Moves to the start of the line below the image, as sixel terminals do.
+    BelowImage,
+}
+
+#[derive(Clone)]
+struct DecodedImage {
+    data: Arc<RenderImage>,
+    width: u32,
+    height: u32,
+}
+
+impl DecodedImage {
+    fn new(mut image: RgbaImage) -> Self {
+        let (width, height) = image.dimensions();
+        // Convert from RGBA to BGRA.
+        for pixel in image.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        Self {
+            data: Arc::new(RenderImage::new(vec![Frame::new(image)])),
+            width,
+            height,
+        }
+    }
+}
+
+/// Picks kitty graphics and sixel sequences out of the PTY output, replacing them with image
+/// placeholders, and answers device attributes requests to advertise sixel support.
+pub(crate) struct ImageScanner {
+    state: ScanState,
+    sequence: Vec<u8>,
+    /// Set when the sequence being buffered grew too long, and is dropped.
+    overflowed: bool,
+    cell_width: u32,
+    cell_height: u32,
+    next_placeholder: u32,
+    next_kitty_id: u32,
+    kitty_chunks: Option<(KittyCommand, Vec<u8>)>,
+    transmitted: HashMap<u32, DecodedImage>,
+    transmitted_order: VecDeque<u32>,
+}
+
+impl ImageScanner {
+    pub(crate) fn new(cell_width: u16, cell_height: u16) -> Self {
+        Self {
+            state: ScanState::Ground,
+            sequence: Vec::new(),
+            overflowed: false,
+            cell_width: cell_width.max(1) as u32,
+            cell_height: cell_height.max(1) as u32,
+            next_placeholder: 0,
+            next_kitty_id: u32::MAX,
+            kitty_chunks: None,
+            transmitted: HashMap::default(),
+            transmitted_order: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn set_cell_size(&mut self, cell_width: u16, cell_height: u16) {
+        self.cell_width = cell_width.max(1) as u32;
+        self.cell_height = cell_height.max(1) as u32;
+    }
+
+    /// Scans a chunk of PTY output, writing what Alacritty should parse to `output`.
+    pub(crate) fn scan(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        mut on_event: impl FnMut(ImageEvent),
+    ) {
+        let mut ix = 0;
+        while ix < input.len() {
+            match self.state {
+                ScanState::Ground => {
+                    let end = input[ix..]
+                        .iter()
+                        .position(|byte| *byte == ESC)
+                        .map_or(input.len(), |position| ix + position);
+                    output.extend_from_slice(&input[ix..end]);
+                    if end < input.len() {
+                        self.state = ScanState::Escape;
+                    }
+                    ix = end + 1;
+                }
+                ScanState::Escape => {
+                    let byte = input[ix];
+                    ix += 1;
+                    self.sequence.clear();
+                    self.overflowed = false;
+                    match byte {
+                        b'_' => self.state = ScanState::Apc,
+                        b'P' => self.state = ScanState::Dcs,
+                        b'[' => self.state = ScanState::Csi,
+                        ESC => output.push(ESC),
+                        _ => {
+                            output.extend_from_slice(&[ESC, byte]);
+                            self.state = ScanState::Ground;
+                        }
+                    }
+                }
+                ScanState::Csi => {
+                    let byte = input[ix];
+                    ix += 1;
+                    if byte == ESC {
+                        self.flush(b'[', output);
+                        self.state = ScanState::Escape;
+                        continue;
+                    }
+                    self.sequence.push(byte);
+                    if (0x40..=0x7e).contains(&byte) {
+                        if self.sequence == b"c" || self.sequence == b"0c" {
+                            on_event(ImageEvent::Reply(DEVICE_ATTRIBUTES.to_vec()));
+                        } else {
+                            self.flush(b'[', output);
+                        }
+                        self.state = ScanState::Ground;
+                    } else if !(0x20..0x40).contains(&byte) || self.sequence.len() >= MAX_CSI_LEN {
+                        self.flush(b'[', output);
+                        self.state = ScanState::Ground;
+                    }
+                }
+                ScanState::Apc | ScanState::Dcs => {
+                    let end = input[ix..]
+                        .iter()
+                        .position(|byte| matches!(*byte, ESC | CAN | SUB))
+                        .map_or(input.len(), |position| ix + position);
+                    self.buffer(&input[ix..end]);
+                    ix = end;
+                    if let Some(&byte) = input.get(end) {
+                        ix += 1;
+                        if byte == ESC {
+                            self.state = if self.state == ScanState::Apc {
+                                ScanState::ApcEscape
+                            } else {
+                                ScanState::DcsEscape
+                            };
+                        } else {
+                            // The sequence was cancelled.
+                            if self.state == ScanState::Dcs {
+                                self.flush_dcs(output);
+                            }
+                            output.push(byte);
+                            self.state = ScanState::Ground;
+                        }
+                    }
+                }
+                ScanState::ApcEscape | ScanState::DcsEscape => {
+                    let terminated = input[ix] == b'\\';
+                    if terminated {
+                        ix += 1;
+                    }
+                    let sequence = std::mem::take(&mut self.sequence);
+                    match self.state {
+                        ScanState::ApcEscape if terminated && !self.overflowed => {
+                            if let Some(command) = sequence.strip_prefix(b"G") {
+                                self.kitty(command, output, &mut on_event);
+                            }
+                        }
+                        ScanState::DcsEscape if terminated && !self.overflowed => {
+                            if let Some(data) = sixel_data(&sequence) {
+                                self.sixel(data, output, &mut on_event);
+                            } else {
+                                self.sequence = sequence;
+                                self.flush(b'P', output);
+                                output.extend_from_slice(b"\x1b\\");
+                            }
+                        }
+                        ScanState::DcsEscape if !terminated => {
+                            // Another escape sequence cut this one off.
+                            self.sequence = sequence;
+                            self.flush_dcs(output);
+                        }
+                        _ => {}
+                    }
+                    self.sequence.clear();
+                    self.state = if terminated {
+                        ScanState::Ground
+                    } else {
+                        ScanState::Escape
+                    };
+                }
+            }
+        }
+    }
+
+    fn buffer(&mut self, bytes: &[u8]) {
+        if self.overflowed {
+            return;
+        }
+        if self.sequence.len() + bytes.len() > MAX_SEQUENCE_LEN {
+            self.overflowed = true;
+            self.sequence = Vec::new();
+        } else {
+            self.sequence.extend_from_slice(bytes);
+        }
+    }
+
+    /// Passes a buffered sequence, that is not an image, on to Alacritty.
+    fn flush(&mut self, introducer: u8, output: &mut Vec<u8>) {
+        output.extend_from_slice(&[ESC, introducer]);
+        output.append(&mut self.sequence);
+    }
+
+    fn flush_dcs(&mut self, output: &mut Vec<u8>) {
+        if !self.overflowed && sixel_data(&self.sequence).is_none() {
+            self.flush(b'P', output);
+        }
+        self.sequence.clear();
+    }
+
+    fn sixel(&mut self, data: &[u8], output: &mut Vec<u8>, on_event: &mut impl FnMut(ImageEvent)) {
+        let Some(image) = decode_sixel(data).context("decoding sixel image").log_err() else {
+            return;
+        };
+        self.place(
+            &DecodedImage::new(image),
+            None,
+            None,
+            None,
+            CursorMovement::BelowImage,
+            output,
+            on_event,
+        );
+    }
+
+    fn kitty(
+        &mut self,
+        command: &[u8],
+        output: &mut Vec<u8>,
+        on_event: &mut impl FnMut(ImageEvent),
+    ) {
+        let (control, payload) = match command.iter().position(|byte| *byte == b';') {
+            Some(ix) => (&command[..ix], &command[ix + 1..]),
+            None => (command, &[][..]),
+        };
+        let command = KittyCommand::parse(control);
+        // Chunks after the first one only say whether more follow.
+        let (command, data) = match self.kitty_chunks.take() {
+            Some((mut first, mut data)) => {
+                first.more = command.more;
+                data.extend_from_slice(payload);
+                (first, data)
+            }
+            None => (command, payload.to_vec()),
+        };
+        if command.more {
+            if data.len() <= MAX_SEQUENCE_LEN {
+                self.kitty_chunks = Some((command, data));
+            }
+            return;
+        }
+
+        let reply = match command.action {
+            b't' | b'T' | b'q' => match decode_kitty(&command, &data) {
+                Ok(_) if command.action == b'q' => Ok(command.id),
+                Ok(image) => {
+                    let image = DecodedImage::new(image);
+                    let id = command
+                        .id
+                        .or_else(|| command.number.map(|_| self.allocate_kitty_id()));
+                    if let Some(id) = id {
+                        self.transmit(id, image.clone());
+                    }
+                    if command.action == b'T' {
+                        self.place(
+                            &image,
+                            id,
+                            command.columns,
+                            command.rows,
+                            command.cursor_movement(),
+                            output,
+                            on_event,
+                        );
+                    }
+                    Ok(id)
+                }
+                Err(error) => Err(error),
+            },
+            b'p' => match command.id.and_then(|id| self.transmitted.get(&id)).cloned() {
+                Some(image) => {
+                    self.place(
+                        &image,
+                        command.id,
+                        command.columns,
+                        command.rows,
+                        command.cursor_movement(),
+                        output,
+                        on_event,
+                    );
+                    Ok(command.id)
+                }
+                None => Err(anyhow!("ENOENT:image not found")),
+            },
+            b'd' => {
+                self.delete(&command, on_event);
+                return;
+            }
+            _ => Err(anyhow!("EINVAL:unsupported action")),
+        };
+        if let Some(reply) = command.reply(reply) {
+            on_event(ImageEvent::Reply(reply));
+        }
+    }
+
+    fn delete(&mut self, command: &KittyCommand, on_event: &mut impl FnMut(ImageEvent)) {
+        // Uppercase variants free the image data too.
+        let free = command.delete.is_ascii_uppercase();
+        match command.delete.to_ascii_lowercase() {
+            b'a' => {
+                if free {
+                    self.transmitted.clear();
+                    self.transmitted_order.clear();
+                }
+                on_event(ImageEvent::Deleted { kitty_id: None });
+            }
+            b'i' => {
+                let Some(id) = command.id else {
+                    return;
+                };
+                if free {
+                    self.transmitted.remove(&id);
+                    self.transmitted_order
+                        .retain(|transmitted| *transmitted != id);
+                }
+                on_event(ImageEvent::Deleted { kitty_id: Some(id) });
+            }
+            _ => {}
+        }
+    }
+
+    fn allocate_kitty_id(&mut self) -> u32 {
+        let id = self.next_kitty_id;
+        self.next_kitty_id = self.next_kitty_id.checked_sub(1).unwrap_or(u32::MAX);
+        id
+    }
+
+    fn transmit(&mut self, id: u32, image: DecodedImage) {
+        if self.transmitted.insert(id, image).is_none() {
+            self.transmitted_order.push_back(id);
+        }
+        while self.transmitted_order.len() > MAX_TRANSMITTED_IMAGES
+            && let Some(oldest) = self.transmitted_order.pop_front()
+        {
+            self.transmitted.remove(&oldest);
+        }
+    }
+
+    /// Writes the image's placeholder at the cursor, and moves the cursor past the image.
+    fn place(
+        &mut self,
+        image: &DecodedImage,
+        kitty_id: Option<u32>,
+        columns: Option<u32>,
+        lines: Option<u32>,
+        cursor_movement: CursorMovement,
+        output: &mut Vec<u8>,
+        on_event: &mut impl FnMut(ImageEvent),
+    ) {
+        let columns = columns
+            .filter(|columns| *columns > 0)
+            .unwrap_or_else(|| image.width.div_ceil(self.cell_width))
+            .clamp(1, MAX_IMAGE_CELLS);
+        let lines = lines
+            .filter(|lines| *lines > 0)
+            .unwrap_or_else(|| image.height.div_ceil(self.cell_height))
+            .clamp(1, MAX_IMAGE_CELLS);
+        let id = self.next_placeholder;
+        self.next_placeholder = (id + 1) % PLACEHOLDER_COUNT;
+
+        // Saving and restoring the cursor keeps the current colors and position.
+        output.extend_from_slice(b"\x1b7");
+        output.extend_from_slice(placeholder_char(id).encode_utf8(&mut [0; 4]).as_bytes());
+        output.extend_from_slice(b"\x1b8");
+        match cursor_movement {
+            CursorMovement::None => {}
+            CursorMovement::AfterImage => {
+                output.extend(std::iter::repeat_n(b'\n', lines as usize - 1));
+                output.extend_from_slice(format!("\x1b[{columns}C").as_bytes());
+            }
+            CursorMovement::BelowImage => {
+                output.extend(std::iter::repeat_n(b'\n', lines as usize - 1));
+                output.extend_from_slice(b"\r\n");
+            }
+        }
+
+        on_event(ImageEvent::Placed {
+            id,
+            kitty_id,
+            image: TerminalImage {
+                data: image.data.clone(),
+                columns: columns as usize,
+                lines: lines as usize,
+            },
+        });
+    }
+}
+
+/// The keys of a kitty graphics command used by Zed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct KittyCommand {
+    action: u8,
+    quiet: u8,
+    format: u32,
+    medium: u8,
+    compressed: bool,
+    more: bool,
+    id: Option<u32>,
+    number: Option<u32>,
+    placement_id: Option<u32>,
+    width: u32,
+    height: u32,
+    columns: Option<u32>,
+    rows: Option<u32>,
+    keep_cursor: bool,
+    delete: u8,
+}
+
+impl Default for KittyCommand {
+    fn default() -> Self {
+        Self {
+            action: b't',
+            quiet: 0,
+            format: 32,
+            medium: b'd',
+            compressed: false,
+            more: false,
+            id: None,
+            number: None,
+            placement_id: None,
+            width: 0,
+            height: 0,
+            columns: None,
+            rows: None,
+            keep_cursor: false,
+            delete: b'a',
+        }
+    }
+}
+
+impl KittyCommand {
+    fn parse(control: &[u8]) -> Self {
+        let mut command = Self::default();
+        for pair in control.split(|byte| *byte == b',') {
+            let [key, b'=', value @ ..] = pair else {
+                continue;
+            };
+            let number = std::str::from_utf8(value)
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok());
+            let letter = value.first().copied();
+            match key {
+                b'a' => command.action = letter.unwrap_or(command.action),
+                b'q' => command.quiet = number.unwrap_or(0) as u8,
+                b'f' => command.format = number.unwrap_or(command.format),
+                b't' => command.medium = letter.unwrap_or(command.medium),
+                b'o' => command.compressed = letter.is_some(),
+                b'm' => command.more = number == Some(1),
+                b'i' => command.id = number.filter(|id| *id > 0),
+                b'I' => command.number = number.filter(|number| *number > 0),
+                b'p' => command.placement_id = number,
+                b's' => command.width = number.unwrap_or(0),
+                b'v' => command.height = number.unwrap_or(0),
+                b'c' => command.columns = number,
+                b'r' => command.rows = number,
+                b'C' => command.keep_cursor = number == Some(1),
+                b'd' => command.delete = letter.unwrap_or(command.delete),
+                _ => {}
+            }
+        }
+        command
+    }
+
+    fn cursor_movement(&self) -> CursorMovement {
+        if self.keep_cursor {
+            CursorMovement::None
+        } else {
+            CursorMovement::AfterImage
+        }
+    }
+
+    /// The response to the command, which is only sent for commands with an image id or number,
+    /// and not when they asked to be quiet.
+    fn reply(&self, result: Result<Option<u32>>) -> Option<Vec<u8>> {
+        let (id, message) = match result {
+            Ok(id) if self.quiet == 0 => (id.or(self.id), "OK".to_string()),
+            Err(error) if self.quiet < 2 => {
+                let message = error.to_string().replace([';', '\x1b'], " ");
+                (self.id, message)
+            }
+            _ => return None,
+        };
+        let mut keys = Vec::new();
+        if let Some(id) = id {
+            keys.push(format!("i={id}"));
+        }
+        if let Some(number) = self.number {
+            keys.push(format!("I={number}"));
+        }
+        if keys.is_empty() {
+            return None;
+        }
+        if let Some(placement_id) = self.placement_id {
+            keys.push(format!("p={placement_id}"));
+        }
+        Some(format!("\x1b_G{};{message}\x1b\\", keys.join(",")).into_bytes())
+    }
+}
+
+fn decode_kitty(command: &KittyCommand, data: &[u8]) -> Result<RgbaImage> {
+    if command.compressed {
+        bail!("EINVAL:compressed data is not supported");
+    }
+    let data = data
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    let data = BASE64
+        .decode(data)
+        .map_err(|error| anyhow!("EINVAL:invalid base64 data: {error}"))?;
+    let bytes = match command.medium {
+        b'd' => data,
+        b'f' | b't' => {
+            let path = PathBuf::from(
+                String::from_utf8(data).map_err(|_| anyhow!("EINVAL:invalid file path"))?,
+            );
+            let bytes = std::fs::read(&path)
+                .map_err(|error| anyhow!("EBADF:could not read {path:?}: {error}"))?;
+            // Temporary files are deleted once read, as long as they are named as such.
+            if command.medium == b't'
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().contains("tty-graphics-protocol"))
+            {
+                std::fs::remove_file(&path).ok();
+            }
+            bytes
+        }
+        _ => bail!("EINVAL:unsupported transmission medium"),
+    };
+
+    let image = match command.format {
+        100 => image::load_from_memory_with_format(&bytes, ImageFormat::Png)
+            .map_err(|error| anyhow!("EBADPNG:{error}"))?
+            .into_rgba8(),
+        format @ (24 | 32) => {
+            let (width, height) = (command.width, command.height);
+            if width == 0 || height == 0 || width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE {
+                bail!("EINVAL:invalid image size {width}x{height}");
+            }
+            let channels = (format / 8) as usize;
+            let expected_len = width as usize * height as usize * channels;
+            if bytes.len() < expected_len {
+                bail!("ENODATA:expected {expected_len} bytes, got {}", bytes.len());
+            }
+            let rgba = if channels == 4 {
+                bytes[..expected_len].to_vec()
+            } else {
+                bytes[..expected_len]
+                    .chunks_exact(3)
+                    .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+                    .collect()
+            };
+            RgbaImage::from_raw(width, height, rgba).context("EINVAL:invalid image data")?
+        }
+        format => bail!("EINVAL:unsupported format {format}"),
+    };
+    if image.width() > MAX_IMAGE_SIZE || image.height() > MAX_IMAGE_SIZE {
+        bail!("EINVAL:image is too large");
+    }
+    Ok(image)
+}
+
+/// The data of a sixel image, if the DCS sequence is one. Its parameters are ignored.
+fn sixel_data(sequence: &[u8]) -> Option<&[u8]> {
+    let end = sequence
+        .iter()
+        .position(|byte| !matches!(byte, b'0'..=b'9' | b';'))?;
+    (sequence[end] == b'q').then(|| &sequence[end + 1..])
+}
+
+/// Default colors of the VT340, in percents of red, green and blue.
+const SIXEL_PALETTE: [[u32; 3]; 16] = [
+    [0, 0, 0],
+    [20, 20, 80],
+    [80, 13, 13],
+    [20, 80, 20],
+    [80, 20, 80],
+    [20, 80, 80],
+    [80, 80, 20],
+    [53, 53, 53],
+    [26, 26, 26],
+    [33, 33, 60],
+    [60, 26, 26],
+    [33, 60, 33],
+    [60, 33, 60],
+    [33, 60, 60],
+    [60, 60, 33],
+    [80, 80, 80],
+];
+
+/// Decodes sixel data. Pixels that no sixel sets are left transparent.
+fn decode_sixel(data: &[u8]) -> Result<RgbaImage> {
+    let mut palette = [[0, 0, 0, u8::MAX]; 256];
+    for (color, [r, g, b]) in palette.iter_mut().zip(SIXEL_PALETTE) {
+        *color = [percent(r), percent(g), percent(b), u8::MAX];
+    }
+    let mut color = palette[0];
+    let mut rows: Vec<Vec<[u8; 4]>> = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let (mut width, mut height) = (0, 0);
+
+    let mut ix = 0;
+    while ix < data.len() {
+        let byte = data[ix];
+        ix += 1;
+        match byte {
+            b'"' => {
+                let (params, next) = sixel_params(data, ix);
+                ix = next;
+                if let [_, _, raster_width, raster_height, ..] = params[..] {
+                    width = raster_width.min(MAX_IMAGE_SIZE) as usize;
+                    height = raster_height.min(MAX_IMAGE_SIZE) as usize;
+                }
+            }
+            b'#' => {
+                let (params, next) = sixel_params(data, ix);
+                ix = next;
+                let register = params.first().copied().unwrap_or(0) as usize % palette.len();
+                match params[..] {
+                    [_, 1, hue, lightness, saturation] => {
+                        palette[register] = hls_to_rgba(hue, lightness, saturation)
+                    }
+                    [_, 2, r, g, b] => {
+                        palette[register] = [percent(r), percent(g), percent(b), u8::MAX]
+                    }
+                    _ => {}
+                }
+                color = palette[register];
+            }
+            b'!' => {
+                let (params, next) = sixel_params(data, ix);
+                ix = next;
+                let count = params.first().copied().unwrap_or(1).max(1) as usize;
+                if let Some(&sixel @ 0x3f..=0x7e) = data.get(ix) {
+                    ix += 1;
+                    paint_sixel(&mut rows, x, y, count, sixel - 0x3f, color)?;
+                    x += count;
+                }
+            }
+            b'$' => x = 0,
+            b'-' => {
+                x = 0;
+                y += 6;
+            }
+            0x3f..=0x7e => {
+                paint_sixel(&mut rows, x, y, 1, byte - 0x3f, color)?;
+                x += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0).max(width);
+    let height = rows.len().max(height);
+    if width == 0 || height == 0 {
+        bail!("empty sixel image");
+    }
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for line in 0..height {
+        let row = rows.get(line).map_or(&[][..], Vec::as_slice);
+        for column in 0..width {
+            pixels.extend_from_slice(&row.get(column).copied().unwrap_or([0; 4]));
+        }
+    }
+    RgbaImage::from_raw(width as u32, height as u32, pixels).context("invalid sixel image size")
+}
+
+fn paint_sixel(
+    rows: &mut Vec<Vec<[u8; 4]>>,
+    x: usize,
+    y: usize,
+    count: usize,
+    sixel: u8,
+    color: [u8; 4],
+) -> Result<()> {
+    if x + count > MAX_IMAGE_SIZE as usize || y + 6 > MAX_IMAGE_SIZE as usize {
+        bail!("sixel image is too large");
+    }
+    for bit in 0..6 {
+        if sixel & (1 << bit) == 0 {
+            continue;
+        }
+        if rows.len() <= y + bit {
+            rows.resize(y + bit + 1, Vec::new());
+        }
+        let row = &mut rows[y + bit];
+        if row.len() < x + count {
+            row.resize(x + count, [0; 4]);
+        }
+        row[x..x + count].fill(color);
+    }
+    Ok(())
+}
+
+/// Numeric parameters starting at `ix`, and the index after them.
+fn sixel_params(data: &[u8], mut ix: usize) -> (Vec<u32>, usize) {
+    let mut params = vec![0u32];
+    while let Some(&byte) = data.get(ix) {
+        match byte {
+            b'0'..=b'9' => {
+                let param = params.last_mut().unwrap();
+                *param = param
+                    .saturating_mul(10)
+                    .saturating_add((byte - b'0') as u32);
+            }
+            b';' => params.push(0),
+            _ => break,
+        }
+        ix += 1;
+    }
+    (params, ix)
+}
+
+fn percent(value: u32) -> u8 {
+    (value.min(100) * 255 / 100) as u8
+}
+
+/// Converts a sixel HLS color, where a hue of 0 is blue, to RGBA.
+fn hls_to_rgba(hue: u32, lightness: u32, saturation: u32) -> [u8; 4] {
+    let hue = ((hue + 240) % 360) as f32;
+    let lightness = lightness.min(100) as f32 / 100.;
+    let saturation = saturation.min(100) as f32 / 100.;
+    let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+    let x = chroma * (1. - ((hue / 60.) % 2. - 1.).abs());
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let m = lightness - chroma / 2.;
+    let channel = |value: f32| ((value + m) * 255.).round().clamp(0., 255.) as u8;
+    [channel(r), channel(g), channel(b), u8::MAX]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::{
+        Term,
+        event::VoidListener,
+        term::{Config, test::TermSize},
+        vte::ansi::Processor,
+    };
+    use gpui::{DevicePixels, size};
+
+    struct TestTerminal {
+        scanner: ImageScanner,
+        term: Term<VoidListener>,
+        parser: Processor,
+        images: TerminalImages,
+        replies: Vec<String>,
+    }
+
+    impl TestTerminal {
+        fn new() -> Self {
+            Self {
+                // Cells of 2x4 pixels.
+                scanner: ImageScanner::new(2, 4),
+                term: Term::new(Config::default(), &TermSize::new(10, 5), VoidListener),
+                parser: Processor::new(),
+                images: TerminalImages::default(),
+                replies: Vec::new(),
+            }
+        }
+
+        fn write(&mut self, chunks: &[&[u8]]) {
+            for chunk in chunks {
+                let mut output = Vec::new();
+                let mut events = Vec::new();
+                self.scanner
+                    .scan(chunk, &mut output, |event| events.push(event));
+                self.parser.advance(&mut self.term, &output);
+                for event in events {
+                    match event {
+                        ImageEvent::Placed {
+                            id,
+                            kitty_id,
+                            image,
+                        } => self.images.place(id, kitty_id, image),
+                        ImageEvent::Deleted { kitty_id } => self.images.delete(kitty_id),
+                        ImageEvent::Reply(reply) => {
+                            self.replies.push(String::from_utf8(reply).unwrap())
+                        }
+                    }
+                }
+                // The scrollback never fills up in tests, so its size counts the scrolled lines.
+                self.images
+                    .retain_anchored(self.term.grid(), self.term.history_size());
+            }
+        }
+
+        /// Anchors and sizes, in cells and pixels, of the images in the viewport.
+        fn placements(&self) -> Vec<((i32, usize), (usize, usize), (i32, i32))> {
+            self.images
+                .placements(self.term.grid(), self.term.grid().display_offset())
+                .into_iter()
+                .map(|placement| {
+                    let size = placement.image.data.size(0);
+                    (
+                        (placement.point.line.0, placement.point.column.0),
+                        (placement.image.columns, placement.image.lines),
+                        (size.width.0, size.height.0),
+                    )
+                })
+                .collect()
+        }
+
+        fn cursor(&self) -> (i32, usize) {
+            let point = self.term.grid().cursor.point;
+            (point.line.0, point.column.0)
+        }
+
+        fn line(&self, line: i32) -> String {
+            let row = &self.term.grid()[Line(line)];
+            (0..self.term.columns())
+                .map(|column| row[Column(column)].c)
+                .filter(|c| !is_image_placeholder(*c))
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        }
+    }
+
+    fn rgba_payload(width: usize, height: usize) -> String {
+        BASE64.encode(vec![0xff; width * height * 4])
+    }
+
+    #[test]
+    fn test_kitty_image_placement() {
+        let mut terminal = TestTerminal::new();
+        let payload = rgba_payload(3, 6);
+        let (first, second) = payload.split_at(5);
+        terminal.write(&[
+            b"ab\x1b_Ga=T,f=32,s=3,v=6;",
+            first.as_bytes(),
+            second.as_bytes(),
+            b"\x1b\\cd",
+        ]);
+
+        // 3x6 pixels take 2x2 cells, and the cursor moves right of the image, on its last line.
+        assert_eq!(terminal.placements(), vec![((0, 2), (2, 2), (3, 6))]);
+        assert_eq!(terminal.line(0), "ab");
+        assert_eq!(terminal.line(1), "    cd");
+        assert_eq!(terminal.cursor(), (1, 6));
+        assert!(terminal.replies.is_empty());
+
+        // Scrolling the anchor out of the screen keeps the image in the scrollback.
+        terminal.write(&[b"\r\n\n\n\n\n"]);
+        assert_eq!(terminal.placements(), Vec::new());
+        terminal
+            .term
+            .scroll_display(alacritty_terminal::grid::Scroll::Delta(1));
+        assert_eq!(terminal.placements(), vec![((-2, 2), (2, 2), (3, 6))]);
+        terminal
+            .term
+            .scroll_display(alacritty_terminal::grid::Scroll::Bottom);
+
+        // Overwriting the anchor cell removes the image.
+        terminal.write(&[
+            b"\x1b_Ga=T,f=32,s=3,v=6,c=4,r=1,C=1;",
+            payload.as_bytes(),
+            b"\x1b\\",
+        ]);
+        assert_eq!(terminal.placements(), vec![((4, 0), (4, 1), (3, 6))]);
+        assert_eq!(terminal.cursor(), (4, 0));
+        terminal.write(&[b"x"]);
+        assert_eq!(terminal.placements(), Vec::new());
+        assert_eq!(terminal.line(4), "x");
+    }
+
+    #[test]
+    fn test_overwritten_images_are_freed() {
+        let mut terminal = TestTerminal::new();
+        let payload = rgba_payload(3, 6);
+        let image = format!("\x1b_Ga=T,f=32,s=3,v=6;{payload}\x1b\\");
+        terminal.write(&[image.as_bytes(), b"\r\n", image.as_bytes()]);
+        assert_eq!(terminal.images.images.len(), 2);
+        assert_eq!(terminal.images.bytes, 2 * 3 * 6 * 4);
+
+        // Overwriting the anchor cell of the first image frees it.
+        terminal.write(&[b"\x1b[1;1Hx"]);
+        assert_eq!(terminal.images.images.len(), 1);
+        assert_eq!(terminal.images.bytes, 3 * 6 * 4);
+
+        // Scrolling the other one into the scrollback keeps it.
+        terminal.write(&[b"\x1b[5;1H\n\n\n\n"]);
+        assert_eq!(terminal.images.images.len(), 1);
+
+        // Clearing the screen and the scrollback frees the other one.
+        terminal.write(&[b"\x1b[2J\x1b[3J"]);
+        assert!(terminal.images.images.is_empty());
+        assert!(terminal.images.anchors.is_empty());
+        assert_eq!(terminal.images.bytes, 0);
+    }
+
+    #[test]
+    fn test_kitty_transmission_and_replies() {
+        let mut terminal = TestTerminal::new();
+        let payload = rgba_payload(2, 4);
+        let (first, second) = payload.split_at(8);
+        terminal.write(&[
+            format!("\x1b_Ga=t,f=32,s=2,v=4,i=7,m=1;{first}\x1b\\").as_bytes(),
+            format!("\x1b_Gm=0;{second}\x1b\\").as_bytes(),
+        ]);
+        assert_eq!(terminal.placements(), Vec::new());
+        assert_eq!(terminal.replies, vec!["\x1b_Gi=7;OK\x1b\\"]);
+
+        terminal.write(&[b"\x1b_Ga=p,i=7,q=1\x1b\\\x1b_Ga=p,i=8\x1b\\"]);
+        assert_eq!(terminal.placements(), vec![((0, 0), (1, 1), (2, 4))]);
+        assert_eq!(
+            terminal.replies[1..],
+            ["\x1b_Gi=8;ENOENT:image not found\x1b\\"]
+        );
+
+        // Support queries are answered without showing anything.
+        terminal.write(&[b"\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\", b"\x1b[c"]);
+        assert_eq!(
+            terminal.replies[2..],
+            ["\x1b_Gi=31;OK\x1b\\", "\x1b[?62;4;22c"]
+        );
+        assert_eq!(terminal.placements().len(), 1);
+
+        terminal.write(&[b"\x1b_Ga=d,d=i,i=7\x1b\\"]);
+        assert_eq!(terminal.placements(), Vec::new());
+    }
+
+    #[test]
+    fn test_sixel_images() {
+        let mut terminal = TestTerminal::new();
+        // A red 3x6 block next to a green 1x6 one, and a blue pixel on the line below.
+        terminal.write(&[
+            b"a\x1bP0;1;0q\"1;1;4;8#1;2;100;0;0#1!3~#2;2;0",
+            b";100;0~-#3;2;0;0;100@\x1b\\b",
+        ]);
+
+        assert_eq!(terminal.placements(), vec![((0, 1), (2, 2), (4, 8))]);
+        assert_eq!(terminal.line(0), "a");
+        assert_eq!(terminal.line(2), "b");
+        assert_eq!(terminal.cursor(), (2, 1));
+
+        let placement = terminal
+            .images
+            .placements(terminal.term.grid(), 0)
+            .pop()
+            .unwrap();
+        assert_eq!(
+            placement.image.data.size(0),
+            size(DevicePixels(4), DevicePixels(8))
+        );
+        let pixels = placement.image.data.as_bytes(0).unwrap();
+        let pixel = |x: usize, y: usize| {
+            let [b, g, r, a] = pixels[(y * 4 + x) * 4..][..4] else {
+                unreachable!()
+            };
+            [r, g, b, a]
+        };
+        assert_eq!(pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(2, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(3, 0), [0, 255, 0, 255]);
+        assert_eq!(pixel(0, 6), [0, 0, 255, 255]);
+        assert_eq!(pixel(1, 6), [0, 0, 0, 0]);
+        assert_eq!(pixel(0, 7), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_other_sequences_pass_through() {
+        let mut scanner = ImageScanner::new(1, 1);
+        let mut output = Vec::new();
+        let mut events = 0;
+        let input: &[&[u8]] = &[
+            b"\x1b[31mred\x1b[",
+            b"0m \x1b]0;title\x07\x1bP=1s\x1b",
+            b"\\\x1b_Xignored\x1b\\\x1b[?1049h\x1b7",
+        ];
+        for chunk in input {
+            scanner.scan(chunk, &mut output, |_| events += 1);
+        }
+        assert_eq!(events, 0);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\x1b[31mred\x1b[0m \x1b]0;title\x07\x1bP=1s\x1b\\\x1b[?1049h\x1b7"
+        );
+    }
+}
@@ -9,6 +9,7 @@ use serde_derive::{Deserialize, Serialize};
 use settings::{SettingsSources, SettingsUi};
 use std::path::PathBuf;
 use task::Shell;
+pub use task::{TerminalLayout, TerminalLayoutDirection, TerminalLayoutPane};
 use theme::FontFamilyName;
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -46,6 +47,9 @@ pub struct TerminalSettings {
     pub default_width: Pixels,
     pub default_height: Pixels,
     pub detect_venv: VenvSettings,
+    pub shell_integration: bool,
+    pub restore_scrollback: bool,
+    pub layouts: HashMap<String, TerminalLayout>,
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
     pub scrollbar: ScrollbarSettings,
@@ -223,6 +227,23 @@ pub struct TerminalSettingsContent {
     ///
     /// Default: on
     pub detect_venv: Option<VenvSettings>,
+    /// Whether to load Zed's integration script into bash, zsh and fish, so that the
+    /// shell reports its prompts, commands and their exit codes to the terminal.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: true
+    pub shell_integration: Option<bool>,
+    /// Whether to save the end of each terminal's scrollback when Zed quits,
+    /// and show it dimmed above the new shell prompt when the terminal is restored.
+    ///
+    /// Default: true
+    pub restore_scrollback: Option<bool>,
+    /// Named arrangements of terminals, opened in the terminal panel with
+    /// `terminal_panel::OpenLayout`. Each layout is either a single terminal,
+    /// or a split of terminals and nested splits.
+    ///
+    /// Default: {}
+    pub layouts: Option<HashMap<String, TerminalLayout>>,
     /// The maximum number of lines to keep in the scrollback history.
     /// Maximum allowed value is 100_000, all values above that will be treated as 100_000.
     /// 0 disables the scrolling.
@@ -280,6 +301,10 @@ impl settings::Settings for TerminalSettings {
             current.font_family = Some(FontFamilyName(font_family.into()));
         }
         vscode.bool_setting(&name("copyOnSelection"), &mut current.copy_on_select);
+        vscode.bool_setting(
+            &name("shellIntegration.enabled"),
+            &mut current.shell_integration,
+        );
         vscode.bool_setting("macOptionIsMeta", &mut current.option_as_meta);
         vscode.usize_setting("scrollback", &mut current.max_scroll_history_lines);
         match vscode.read_bool(&name("cursorBlinking")) {
@@ -417,6 +417,12 @@ impl Domain for TerminalDb {
             ALTER TABLE terminals ADD COLUMN working_directory_path TEXT;
             UPDATE terminals SET working_directory_path = CAST(working_directory AS TEXT);
         ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN layout_pane TEXT;
+        ),
     ];
 }
 
@@ -464,6 +470,40 @@ impl TerminalDb {
         .await
     }
 
+    query! {
+        pub async fn save_scrollback(item_id: ItemId, workspace_id: WorkspaceId, scrollback: String) -> Result<()> {
+            INSERT INTO terminals(item_id, workspace_id, scrollback)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                scrollback = ?3
+        }
+    }
+
+    query! {
+        pub fn get_scrollback(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
+            SELECT scrollback
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_layout_pane(item_id: ItemId, workspace_id: WorkspaceId, layout_pane: String) -> Result<()> {
+            INSERT INTO terminals(item_id, workspace_id, layout_pane)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                layout_pane = ?3
+        }
+    }
+
+    query! {
+        pub fn get_layout_pane(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
+            SELECT layout_pane
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
     query! {
         pub fn get_working_directory(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
             SELECT working_directory
@@ -1,19 +1,19 @@
 use editor::{CursorLayout, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, DispatchPhase,
-    Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle, FontWeight,
-    GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity,
-    IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels,
-    Point, ShapedLine, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle,
-    UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, div, fill, point, px, relative,
-    size,
+    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, Corners,
+    DispatchPhase, Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle,
+    FontWeight, GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement,
+    Interactivity, IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton,
+    MouseMoveEvent, Pixels, Point, ShapedLine, StatefulInteractiveElement, StrikethroughStyle,
+    Styled, TextRun, TextStyle, UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window,
+    div, fill, point, px, relative, size,
 };
 use itertools::Itertools;
 use language::CursorShape;
 use settings::Settings;
 use std::time::Instant;
 use terminal::{
-    IndexedCell, Terminal, TerminalBounds, TerminalContent,
+    CommandStatus, ImagePlacement, IndexedCell, Terminal, TerminalBounds, TerminalContent,
     alacritty_terminal::{
         grid::Dimensions,
         index::Point as AlacPoint,
@@ -23,6 +23,7 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    is_image_placeholder,
     terminal_settings::TerminalSettings,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
@@ -49,6 +50,9 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// Visible lines of shell prompts, with the color of their command's status.
+    command_markers: Vec<(usize, Hsla)>,
+    images: Vec<ImagePlacement>,
     block_below_cursor_element: Option<AnyElement>,
     base_text_style: TextStyle,
     content_mode: ContentMode,
@@ -374,6 +378,11 @@ impl TerminalElement {
                     continue;
                 }
 
+                // Images are painted over their anchor cells
+                if is_image_placeholder(cell.c) {
+                    continue;
+                }
+
                 // Skip spaces that follow cells with extras (emoji variation sequences)
                 if cell.c == ' ' && previous_cell_had_extras {
                     previous_cell_had_extras = false;
@@ -1015,8 +1024,10 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    images,
                     ..
                 } = &self.terminal.read(cx).last_content;
+                let images = images.clone();
                 let mode = *mode;
                 let display_offset = *display_offset;
 
@@ -1121,6 +1132,36 @@ impl Element for TerminalElement {
                     )
                 };
 
+                let command_markers = {
+                    let terminal = self.terminal.read(cx);
+                    let content = &terminal.last_content;
+                    let top_line = content
+                        .scrolled_lines
+                        .saturating_sub(content.display_offset);
+                    let screen_lines = dimensions.num_lines();
+                    terminal
+                        .command_blocks()
+                        .filter_map(|block| {
+                            let line = block.prompt_line.checked_sub(top_line)?;
+                            if line >= screen_lines {
+                                return None;
+                            }
+                            let color = match block.status() {
+                                CommandStatus::Prompt => return None,
+                                CommandStatus::Running => theme.colors().text_accent,
+                                CommandStatus::Finished { exit_code: Some(0) } => {
+                                    theme.status().success
+                                }
+                                CommandStatus::Finished { exit_code: None } => {
+                                    theme.colors().text_muted
+                                }
+                                CommandStatus::Finished { .. } => theme.status().error,
+                            };
+                            Some((line, color))
+                        })
+                        .collect()
+                };
+
                 let block_below_cursor_element = if let Some(block) = &self.block_below_cursor {
                     let terminal = self.terminal.read(cx);
                     if terminal.last_content.display_offset == 0 {
@@ -1165,6 +1206,8 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    command_markers,
+                    images,
                     block_below_cursor_element,
                     base_text_style: text_style,
                     content_mode,
@@ -1251,6 +1294,52 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, window);
                     }
 
+                    let line_height = layout.dimensions.line_height;
+                    for (line, color) in &layout.command_markers {
+                        let marker_bounds = Bounds::new(
+                            point(
+                                bounds.origin.x + layout.gutter / 4.,
+                                origin.y + *line as f32 * line_height + line_height / 4.,
+                            ),
+                            size(layout.gutter / 2., line_height / 2.),
+                        );
+                        window.paint_quad(
+                            fill(marker_bounds, *color).corner_radii(layout.gutter / 4.),
+                        );
+                    }
+
+                    for placement in &layout.images {
+                        let line = placement.point.line.0 + layout.display_offset as i32;
+                        let cell_origin = point(
+                            origin.x + placement.point.column.0 as f32 * layout.dimensions.cell_width,
+                            origin.y + line as f32 * line_height,
+                        );
+                        // Fit the image in its cells, keeping its aspect ratio.
+                        let image_size = placement.image.data.size(0);
+                        let scale = f32::min(
+                            placement.image.columns as f32 * f32::from(layout.dimensions.cell_width)
+                                / image_size.width.0.max(1) as f32,
+                            placement.image.lines as f32 * f32::from(line_height)
+                                / image_size.height.0.max(1) as f32,
+                        );
+                        let image_bounds = Bounds::new(
+                            cell_origin,
+                            size(
+                                px(image_size.width.0 as f32 * scale),
+                                px(image_size.height.0 as f32 * scale),
+                            ),
+                        );
+                        window
+                            .paint_image(
+                                image_bounds,
+                                Corners::default(),
+                                placement.image.data.clone(),
+                                0,
+                                false,
+                            )
+                            .log_err();
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
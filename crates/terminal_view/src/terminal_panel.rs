@@ -1,7 +1,14 @@
-use std::{cmp, ops::ControlFlow, path::PathBuf, process::ExitStatus, sync::Arc, time::Duration};
+use std::{
+    cmp,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    sync::Arc,
+    time::Duration,
+};
 
 use crate::{
-    TerminalView, default_working_directory,
+    TerminalView, default_working_directory, first_project_directory,
     persistence::{
         SerializedItems, SerializedTerminalPanel, deserialize_terminal_panel, serialize_pane_group,
     },
@@ -9,20 +16,28 @@ use crate::{
 use breadcrumbs::Breadcrumbs;
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use futures::{channel::oneshot, future::join_all};
+use futures::{
+    channel::oneshot,
+    future::{join_all, try_join_all},
+};
 use gpui::{
-    Action, AnyView, App, AsyncApp, AsyncWindowContext, Context, Corner, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, Focusable, IntoElement, ParentElement, Pixels, Render, Styled,
-    Task, WeakEntity, Window, actions,
+    Action, AnyView, App, AsyncApp, AsyncWindowContext, Axis, Context, Corner, Entity, EntityId,
+    EventEmitter, ExternalPaths, FocusHandle, Focusable, IntoElement, ParentElement, Pixels,
+    Render, Styled, Task, WeakEntity, Window, actions,
 };
 use itertools::Itertools;
-use project::{Fs, Project, ProjectEntryId};
+use project::{Fs, Project, ProjectEntryId, terminals::TerminalShellOptions};
+use schemars::JsonSchema;
 use search::{BufferSearchBar, buffer_search::DivRegistrar};
-use settings::Settings;
+use serde::Deserialize;
+use settings::{Settings, SettingsLocation};
 use task::{RevealStrategy, RevealTarget, ShellBuilder, SpawnInTerminal, TaskId};
 use terminal::{
     Terminal,
-    terminal_settings::{TerminalDockPosition, TerminalSettings},
+    terminal_settings::{
+        TerminalDockPosition, TerminalLayout, TerminalLayoutDirection, TerminalLayoutPane,
+        TerminalSettings,
+    },
 };
 use ui::{
     ButtonCommon, Clickable, ContextMenu, FluentBuilder, PopoverMenu, Toggleable, Tooltip,
@@ -31,10 +46,10 @@ use ui::{
 use util::{ResultExt, TryFutureExt};
 use workspace::{
     ActivateNextPane, ActivatePane, ActivatePaneDown, ActivatePaneLeft, ActivatePaneRight,
-    ActivatePaneUp, ActivatePreviousPane, DraggedSelection, DraggedTab, ItemId, MoveItemToPane,
-    MoveItemToPaneInDirection, NewTerminal, Pane, PaneGroup, SplitDirection, SplitDown, SplitLeft,
-    SplitRight, SplitUp, SwapPaneDown, SwapPaneLeft, SwapPaneRight, SwapPaneUp, ToggleZoom,
-    Workspace,
+    ActivatePaneUp, ActivatePreviousPane, DraggedSelection, DraggedTab, ItemId, Member,
+    MoveItemToPane, MoveItemToPaneInDirection, NewTerminal, Pane, PaneAxis, PaneGroup,
+    SplitDirection, SplitDown, SplitLeft, SplitRight, SplitUp, SwapPaneDown, SwapPaneLeft,
+    SwapPaneRight, SwapPaneUp, ToggleZoom, Workspace,
     dock::{DockPosition, Panel, PanelEvent, PanelHandle},
     item::SerializableItem,
     move_active_item, move_item, pane,
@@ -54,11 +69,40 @@ actions!(
     ]
 );
 
+/// Opens a named layout of terminals, from the `terminal.layouts` settings, in the terminal panel.
+#[derive(Clone, Debug, Deserialize, JsonSchema, PartialEq, Action)]
+#[action(namespace = terminal_panel)]
+#[serde(deny_unknown_fields)]
+pub struct OpenLayout {
+    /// The name of the layout.
+    pub name: String,
+}
+
+/// Toggles mirroring the input typed into a terminal of the panel to the other terminals.
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, PartialEq, Action)]
+#[action(namespace = terminal_panel)]
+#[serde(deny_unknown_fields)]
+pub struct ToggleBroadcastInput {
+    /// Only mirror input between the terminals of this layout group, instead of all terminals.
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+/// The terminals of the panel that mirror the input typed into any of them to each other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum BroadcastInput {
+    All,
+    /// The terminals opened from layout panes of this group.
+    Group(String),
+}
+
 pub fn init(cx: &mut App) {
     cx.observe_new(
         |workspace: &mut Workspace, _window, _: &mut Context<Workspace>| {
             workspace.register_action(TerminalPanel::new_terminal);
             workspace.register_action(TerminalPanel::open_terminal);
+            workspace.register_action(TerminalPanel::open_layout);
+            workspace.register_action(TerminalPanel::toggle_broadcast_input);
             workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
                 if is_enabled_in_workspace(workspace, cx) {
                     workspace.toggle_panel_focus::<TerminalPanel>(window, cx);
@@ -82,6 +126,7 @@ pub struct TerminalPanel {
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
     active: bool,
+    broadcast_input: Option<BroadcastInput>,
 }
 
 impl TerminalPanel {
@@ -102,6 +147,7 @@ impl TerminalPanel {
             assistant_enabled: false,
             assistant_tab_bar_button: None,
             active: false,
+            broadcast_input: None,
         };
         terminal_panel.apply_tab_bar_buttons(&terminal_panel.active_pane, cx);
         terminal_panel
@@ -130,18 +176,54 @@ impl TerminalPanel {
 
     fn apply_tab_bar_buttons(&self, terminal_pane: &Entity<Pane>, cx: &mut Context<Self>) {
         let assistant_tab_bar_button = self.assistant_tab_bar_button.clone();
+        let terminal_panel = cx.entity().downgrade();
         terminal_pane.update(cx, |pane, cx| {
             pane.set_render_tab_bar_buttons(cx, move |pane, window, cx| {
                 let split_context = pane
                     .active_item()
                     .and_then(|item| item.downcast::<TerminalView>())
                     .map(|terminal_view| terminal_view.read(cx).focus_handle.clone());
+                let (layout_names, broadcast_groups, broadcast_input) = terminal_panel
+                    .read_with(cx, |terminal_panel, cx| {
+                        (
+                            terminal_panel.layout_names(cx),
+                            terminal_panel.broadcast_groups(cx),
+                            terminal_panel.broadcast_input.clone(),
+                        )
+                    })
+                    .unwrap_or_default();
+                let broadcast_indicator = broadcast_input.as_ref().map(|broadcast_input| {
+                    let tooltip = match broadcast_input {
+                        BroadcastInput::All => "Broadcasting Input to All Terminals".to_string(),
+                        BroadcastInput::Group(group) => {
+                            format!("Broadcasting Input to Group \"{group}\"")
+                        }
+                    };
+                    let action = ToggleBroadcastInput {
+                        group: match broadcast_input {
+                            BroadcastInput::All => None,
+                            BroadcastInput::Group(group) => Some(group.clone()),
+                        },
+                    };
+                    IconButton::new("terminal-broadcast-input", IconName::Keyboard)
+                        .icon_size(IconSize::Small)
+                        .icon_color(Color::Warning)
+                        .toggle_state(true)
+                        .tooltip(Tooltip::text(tooltip))
+                        .on_click(move |_, window, cx| {
+                            window.dispatch_action(action.boxed_clone(), cx)
+                        })
+                });
                 if !pane.has_focus(window, cx) && !pane.context_menu_focused(window, cx) {
-                    return (None, None);
+                    return (
+                        None,
+                        broadcast_indicator.map(|indicator| indicator.into_any_element()),
+                    );
                 }
                 let focus_handle = pane.focus_handle(cx);
                 let right_children = h_flex()
                     .gap(DynamicSpacing::Base02.rems(cx))
+                    .children(broadcast_indicator)
                     .child(
                         PopoverMenu::new("terminal-tab-bar-popover-menu")
                             .trigger_with_tooltip(
@@ -165,6 +247,47 @@ impl TerminalPanel {
                                             "Spawn task",
                                             zed_actions::Spawn::modal().boxed_clone(),
                                         )
+                                        .when(!layout_names.is_empty(), |menu| {
+                                            layout_names.iter().fold(
+                                                menu.separator(),
+                                                |menu, name| {
+                                                    menu.action(
+                                                        format!("Open Layout \"{name}\""),
+                                                        OpenLayout { name: name.clone() }
+                                                            .boxed_clone(),
+                                                    )
+                                                },
+                                            )
+                                        })
+                                        .separator()
+                                        .action(
+                                            if broadcast_input == Some(BroadcastInput::All) {
+                                                "Stop Broadcasting Input"
+                                            } else {
+                                                "Broadcast Input to All Terminals"
+                                            },
+                                            ToggleBroadcastInput::default().boxed_clone(),
+                                        )
+                                        .map(|menu| {
+                                            broadcast_groups.iter().fold(menu, |menu, group| {
+                                                let label = if broadcast_input
+                                                    == Some(BroadcastInput::Group(group.clone()))
+                                                {
+                                                    format!(
+                                                        "Stop Broadcasting Input to Group \"{group}\""
+                                                    )
+                                                } else {
+                                                    format!("Broadcast Input to Group \"{group}\"")
+                                                };
+                                                menu.action(
+                                                    label,
+                                                    ToggleBroadcastInput {
+                                                        group: Some(group.clone()),
+                                                    }
+                                                    .boxed_clone(),
+                                                )
+                                            })
+                                        })
                                 });
 
                                 Some(menu)
@@ -968,6 +1091,291 @@ impl TerminalPanel {
         })
     }
 
+    fn open_layout(
+        workspace: &mut Workspace,
+        action: &OpenLayout,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(terminal_panel) = workspace.panel::<Self>(cx) else {
+            return;
+        };
+        let Some(layout) = terminal_layouts(workspace, cx).remove(&action.name) else {
+            workspace.show_error(
+                &anyhow!(
+                    "No terminal layout named {:?} in the settings or tasks",
+                    action.name
+                ),
+                cx,
+            );
+            return;
+        };
+
+        let task = terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.add_layout(layout, window, cx)
+        });
+        cx.spawn_in(window, async move |workspace, cx| {
+            if let Err(error) = task.await {
+                workspace
+                    .update(cx, |workspace, cx| workspace.show_error(&error, cx))
+                    .ok();
+            }
+        })
+        .detach();
+    }
+
+    /// Opens the terminals of a layout, next to the terminals already in the panel.
+    pub fn add_layout(
+        &mut self,
+        layout: TerminalLayout,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |terminal_panel, cx| {
+            let (project, database_id, terminals) = workspace.update(cx, |workspace, cx| {
+                anyhow::ensure!(
+                    is_enabled_in_workspace(workspace, cx),
+                    "terminal not yet supported for remote projects"
+                );
+                let project = workspace.project().clone();
+                let project_directory = first_project_directory(workspace, cx);
+                let terminals = layout_panes(&layout)
+                    .into_iter()
+                    .map(|layout_pane| {
+                        let cwd = match &layout_pane.cwd {
+                            Some(cwd) if cwd.is_absolute() => Some(cwd.clone()),
+                            Some(cwd) => project_directory
+                                .as_ref()
+                                .map(|directory| directory.join(cwd)),
+                            None => default_working_directory(workspace, cx),
+                        };
+                        let options = TerminalShellOptions {
+                            env: layout_pane.env.clone(),
+                            restored_scrollback: None,
+                        };
+                        project.update(cx, |project, cx| {
+                            project.create_terminal_shell_with_options(cwd, options, cx)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                anyhow::Ok((project, workspace.database_id(), terminals))
+            })??;
+            let terminals = try_join_all(terminals).await?;
+
+            terminal_panel.update_in(cx, |terminal_panel, window, cx| {
+                let terminal_views = layout_panes(&layout)
+                    .into_iter()
+                    .zip(terminals)
+                    .map(|(layout_pane, terminal)| {
+                        cx.new(|cx| {
+                            let mut terminal_view = TerminalView::new(
+                                terminal,
+                                terminal_panel.workspace.clone(),
+                                database_id,
+                                project.downgrade(),
+                                window,
+                                cx,
+                            );
+                            terminal_view.set_layout_pane(layout_pane.clone());
+                            terminal_view.run_layout_command(cx);
+                            terminal_view
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let zoomed = terminal_panel.active_pane.read(cx).is_zoomed();
+                let layout_member = terminal_panel
+                    .build_layout_member(
+                        &layout,
+                        &mut terminal_views.into_iter(),
+                        &project,
+                        zoomed,
+                        window,
+                        cx,
+                    )
+                    .context("terminal layout has no panes")?;
+                let layout_group = PaneGroup::with_root(layout_member);
+                let first_pane = layout_group.first_pane();
+
+                let has_terminals = terminal_panel
+                    .center
+                    .panes()
+                    .into_iter()
+                    .any(|pane| pane.read(cx).items_len() > 0);
+                terminal_panel.center = if has_terminals {
+                    // Side by side along the longer side of the dock.
+                    let axis = match terminal_panel.position(window, cx) {
+                        DockPosition::Bottom => Axis::Horizontal,
+                        DockPosition::Left | DockPosition::Right => Axis::Vertical,
+                    };
+                    PaneGroup::with_root(Member::Axis(PaneAxis::new(
+                        axis,
+                        vec![terminal_panel.center.root.clone(), layout_group.root],
+                    )))
+                } else {
+                    layout_group
+                };
+                terminal_panel.active_pane = first_pane.clone();
+                terminal_panel.serialize(cx);
+                cx.notify();
+
+                terminal_panel
+                    .workspace
+                    .update(cx, |workspace, cx| {
+                        workspace.focus_panel::<Self>(window, cx);
+                    })
+                    .ok();
+                window.focus(&first_pane.focus_handle(cx));
+                anyhow::Ok(())
+            })?
+        })
+    }
+
+    fn build_layout_member(
+        &self,
+        layout: &TerminalLayout,
+        terminal_views: &mut impl Iterator<Item = Entity<TerminalView>>,
+        project: &Entity<Project>,
+        zoomed: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Member> {
+        match layout {
+            TerminalLayout::Split { direction, panes } => {
+                let mut members = panes
+                    .iter()
+                    .filter_map(|layout| {
+                        self.build_layout_member(
+                            layout,
+                            terminal_views,
+                            project,
+                            zoomed,
+                            window,
+                            cx,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if members.len() <= 1 {
+                    return members.pop();
+                }
+                let axis = match direction {
+                    TerminalLayoutDirection::Horizontal => Axis::Horizontal,
+                    TerminalLayoutDirection::Vertical => Axis::Vertical,
+                };
+                Some(Member::Axis(PaneAxis::new(axis, members)))
+            }
+            TerminalLayout::Pane(_) => {
+                let terminal_view = terminal_views.next()?;
+                let pane =
+                    new_terminal_pane(self.workspace.clone(), project.clone(), zoomed, window, cx);
+                self.apply_tab_bar_buttons(&pane, cx);
+                pane.update(cx, |pane, cx| {
+                    pane.add_item(Box::new(terminal_view), true, false, None, window, cx);
+                });
+                Some(Member::Pane(pane))
+            }
+        }
+    }
+
+    fn layout_names(&self, cx: &App) -> Vec<String> {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return Vec::new();
+        };
+        terminal_layouts(workspace.read(cx), cx)
+            .into_keys()
+            .sorted()
+            .collect()
+    }
+
+    fn toggle_broadcast_input(
+        workspace: &mut Workspace,
+        action: &ToggleBroadcastInput,
+        _: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(terminal_panel) = workspace.panel::<Self>(cx) else {
+            return;
+        };
+        let broadcast_input = match &action.group {
+            Some(group) => BroadcastInput::Group(group.clone()),
+            None => BroadcastInput::All,
+        };
+        terminal_panel.update(cx, |terminal_panel, cx| {
+            if terminal_panel.broadcast_input.as_ref() == Some(&broadcast_input) {
+                terminal_panel.broadcast_input = None;
+            } else {
+                terminal_panel.broadcast_input = Some(broadcast_input);
+            }
+            for pane in terminal_panel.center.panes() {
+                pane.update(cx, |_, cx| cx.notify());
+            }
+            for terminal_view in terminal_panel.terminal_views(cx) {
+                terminal_view.update(cx, |_, cx| cx.notify());
+            }
+            cx.notify();
+        });
+    }
+
+    fn terminal_views(&self, cx: &App) -> Vec<Entity<TerminalView>> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| pane.read(cx).items())
+            .filter_map(|item| item.downcast::<TerminalView>())
+            .collect()
+    }
+
+    /// The layout groups of the panel's terminals, that input can be broadcast to.
+    fn broadcast_groups(&self, cx: &App) -> Vec<String> {
+        self.terminal_views(cx)
+            .iter()
+            .filter_map(|terminal_view| Some(terminal_view.read(cx).broadcast_group()?.to_string()))
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    /// Whether the terminals of the given layout group mirror their input to each other.
+    fn broadcasts_to(&self, group: Option<&str>) -> bool {
+        match &self.broadcast_input {
+            None => false,
+            Some(BroadcastInput::All) => true,
+            Some(BroadcastInput::Group(broadcast_group)) => group == Some(broadcast_group.as_str()),
+        }
+    }
+
+    /// Whether the given terminal of the panel, of the given layout group, mirrors its input to other terminals.
+    pub(crate) fn receives_broadcast_input(
+        &self,
+        terminal_view: EntityId,
+        group: Option<&str>,
+        cx: &App,
+    ) -> bool {
+        self.broadcasts_to(group)
+            && self
+                .terminal_views(cx)
+                .iter()
+                .any(|view| view.entity_id() == terminal_view)
+    }
+
+    /// The terminals that the input typed into the given one is mirrored to.
+    pub(crate) fn broadcast_targets(
+        &self,
+        terminal_view: EntityId,
+        cx: &App,
+    ) -> Vec<Entity<Terminal>> {
+        self.terminal_views(cx)
+            .into_iter()
+            .filter(|view| view.entity_id() != terminal_view)
+            .filter_map(|view| {
+                let view = view.read(cx);
+                let terminal = view.terminal();
+                (terminal.read(cx).task().is_none() && self.broadcasts_to(view.broadcast_group()))
+                    .then(|| terminal.clone())
+            })
+            .collect()
+    }
+
     fn has_no_terminals(&self, cx: &App) -> bool {
         self.active_pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0
     }
@@ -1018,6 +1426,38 @@ fn is_enabled_in_workspace(workspace: &Workspace, cx: &App) -> bool {
     workspace.project().read(cx).supports_terminal(cx)
 }
 
+/// The terminal layouts from the settings and the tasks files, including the ones of the first
+/// project directory. Layouts from the tasks files take precedence.
+fn terminal_layouts(workspace: &Workspace, cx: &App) -> HashMap<String, TerminalLayout> {
+    let worktree_id = workspace
+        .visible_worktrees(cx)
+        .next()
+        .map(|worktree| worktree.read(cx).id());
+    let settings_location = worktree_id.map(|worktree_id| SettingsLocation {
+        worktree_id,
+        path: Path::new(""),
+    });
+    let mut layouts = TerminalSettings::get(settings_location, cx).layouts.clone();
+    if let Some(inventory) = workspace
+        .project()
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
+    {
+        layouts.extend(inventory.read(cx).terminal_layouts(worktree_id));
+    }
+    layouts
+}
+
+/// The terminals of a layout, in the order they appear in it.
+fn layout_panes(layout: &TerminalLayout) -> Vec<&TerminalLayoutPane> {
+    match layout {
+        TerminalLayout::Split { panes, .. } => panes.iter().flat_map(layout_panes).collect(),
+        TerminalLayout::Pane(layout_pane) => vec![layout_pane],
+    }
+}
+
 pub fn new_terminal_pane(
     workspace: WeakEntity<Workspace>,
     project: Entity<Project>,
@@ -1558,7 +1998,7 @@ impl workspace::TerminalProvider for TerminalProvider {
             match terminal {
                 Ok(terminal) => {
                     let exit_status = terminal
-                        .read_with(cx, |terminal, cx| terminal.wait_for_completed_task(cx))
+                        .update(cx, |terminal, cx| terminal.wait_for_task_ready(cx))
                         .ok()?
                         .await?;
                     Some(Ok(exit_status))
@@ -1592,3 +2032,298 @@ impl Render for InlineAssistTabBarButton {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::RealFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use task::Shell;
+    use util::test::TempTree;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_add_layout_with_nested_splits(cx: &mut TestAppContext) {
+        if cfg!(windows) {
+            return;
+        }
+        let (tree, _workspace, terminal_panel, cx) = init_test(cx).await;
+
+        let layout = TerminalLayout::Split {
+            direction: TerminalLayoutDirection::Horizontal,
+            panes: vec![
+                TerminalLayout::Pane(TerminalLayoutPane {
+                    env: HashMap::from_iter([("LAYOUT_VAR".to_string(), "web".to_string())]),
+                    ..layout_pane("web", None, Some("echo \"$LAYOUT_VAR\" > out"))
+                }),
+                TerminalLayout::Split {
+                    direction: TerminalLayoutDirection::Vertical,
+                    panes: vec![
+                        TerminalLayout::Pane(layout_pane("worker", None, Some("pwd > out"))),
+                        TerminalLayout::Pane(layout_pane("db", None, None)),
+                    ],
+                },
+            ],
+        };
+        add_layout(layout, &terminal_panel, cx).await;
+
+        terminal_panel.read_with(cx, |terminal_panel, cx| {
+            let Member::Axis(root) = &terminal_panel.center.root else {
+                panic!("The layout should be opened as a split");
+            };
+            assert_eq!(root.axis, Axis::Horizontal);
+            let [Member::Pane(web), Member::Axis(nested)] = root.members.as_slice() else {
+                panic!("The layout should have a pane next to a nested split");
+            };
+            assert_eq!(nested.axis, Axis::Vertical);
+            let [Member::Pane(worker), Member::Pane(db)] = nested.members.as_slice() else {
+                panic!("The nested split should have two panes");
+            };
+            for pane in [web, worker, db] {
+                assert_eq!(pane.read(cx).items_len(), 1);
+            }
+            assert_eq!(&terminal_panel.active_pane, web);
+        });
+
+        // Each terminal runs its command in its own directory, with its own environment.
+        let web_output = tree.path().join("web").join("out");
+        let worker_output = tree.path().join("worker").join("out");
+        wait_until(cx, || {
+            file_contents(&web_output).ends_with('\n')
+                && file_contents(&worker_output).ends_with('\n')
+        });
+        assert_eq!(file_contents(&web_output), "web\n");
+        assert_eq!(
+            Path::new(file_contents(&worker_output).trim())
+                .canonicalize()
+                .unwrap(),
+            tree.path().join("worker").canonicalize().unwrap()
+        );
+        assert!(!tree.path().join("db").join("out").exists());
+    }
+
+    #[gpui::test]
+    async fn test_broadcast_input_to_all_terminals_or_a_group(cx: &mut TestAppContext) {
+        if cfg!(windows) {
+            return;
+        }
+        let (tree, workspace, terminal_panel, cx) = init_test(cx).await;
+
+        let layout = TerminalLayout::Split {
+            direction: TerminalLayoutDirection::Horizontal,
+            panes: vec![
+                TerminalLayout::Pane(layout_pane("web", Some("servers"), None)),
+                TerminalLayout::Pane(layout_pane("worker", Some("servers"), None)),
+                TerminalLayout::Pane(layout_pane("db", None, None)),
+            ],
+        };
+        add_layout(layout, &terminal_panel, cx).await;
+        let terminal_views = terminal_panel.read_with(cx, |terminal_panel, cx| {
+            assert_eq!(terminal_panel.broadcast_groups(cx), vec!["servers"]);
+            terminal_panel.terminal_views(cx)
+        });
+        assert_eq!(terminal_views.len(), 3);
+        let type_into_first_terminal = |text: &str, cx: &mut VisualTestContext| {
+            terminal_views[0].update(cx, |terminal_view, cx| terminal_view.commit_text(text, cx));
+        };
+        let touched = |directory: &str, file: &str| tree.path().join(directory).join(file).exists();
+
+        toggle_broadcast_input(Some("servers"), &workspace, cx);
+        type_into_first_terminal("touch group\r", cx);
+        wait_until(cx, || touched("web", "group") && touched("worker", "group"));
+
+        toggle_broadcast_input(None, &workspace, cx);
+        type_into_first_terminal("touch all\r", cx);
+        wait_until(cx, || {
+            touched("web", "all") && touched("worker", "all") && touched("db", "all")
+        });
+
+        // Toggling the same broadcast again turns it off.
+        toggle_broadcast_input(None, &workspace, cx);
+        type_into_first_terminal("touch alone\r", cx);
+        wait_until(cx, || touched("web", "alone"));
+
+        // Shells run their input in order, so once the other terminals ran one more command,
+        // the broadcasts they did not run never reached them.
+        for terminal_view in &terminal_views[1..] {
+            terminal_view.update(cx, |terminal_view, cx| {
+                terminal_view.commit_text("touch sync\r", cx)
+            });
+        }
+        wait_until(cx, || touched("worker", "sync") && touched("db", "sync"));
+        assert!(
+            !touched("db", "group"),
+            "Only the group should receive its broadcast"
+        );
+        assert!(!touched("worker", "alone"));
+        assert!(!touched("db", "alone"));
+    }
+
+    #[gpui::test]
+    async fn test_layout_pane_restored_without_running_its_command(cx: &mut TestAppContext) {
+        if cfg!(windows) {
+            return;
+        }
+        let (tree, workspace, terminal_panel, cx) = init_test(cx).await;
+
+        let layout_pane = TerminalLayoutPane {
+            env: HashMap::from_iter([("LAYOUT_VAR".to_string(), "web".to_string())]),
+            ..layout_pane("web", Some("servers"), Some("touch ran"))
+        };
+        add_layout(
+            TerminalLayout::Pane(layout_pane.clone()),
+            &terminal_panel,
+            cx,
+        )
+        .await;
+        let ran = tree.path().join("web").join("ran");
+        wait_until(cx, || ran.exists());
+        std::fs::remove_file(&ran).unwrap();
+
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let item_id: ItemId = 1234;
+        let terminal_view = terminal_panel.read_with(cx, |terminal_panel, cx| {
+            terminal_panel.terminal_views(cx)[0].clone()
+        });
+        let serialization = workspace.update_in(cx, |workspace, window, cx| {
+            terminal_view.update(cx, |terminal_view, cx| {
+                terminal_view.workspace_id = Some(workspace_id);
+                terminal_view.serialize(workspace, item_id, false, window, cx)
+            })
+        });
+        serialization
+            .expect("The layout terminal should be serialized")
+            .await
+            .unwrap();
+
+        let project = workspace.read_with(cx, |workspace, _| workspace.project().clone());
+        let restored = cx
+            .update(|window, cx| {
+                TerminalView::deserialize(
+                    project,
+                    workspace.downgrade(),
+                    workspace_id,
+                    item_id,
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        restored.read_with(cx, |terminal_view, _| {
+            assert_eq!(terminal_view.layout_pane.as_ref(), Some(&layout_pane));
+            assert_eq!(terminal_view.broadcast_group(), Some("servers"));
+        });
+
+        // The shell is restored in the pane's directory, with its environment.
+        restored.update(cx, |terminal_view, cx| {
+            terminal_view.commit_text("echo \"$LAYOUT_VAR\" > restored\r", cx)
+        });
+        let restored_output = tree.path().join("web").join("restored");
+        wait_until(cx, || file_contents(&restored_output).ends_with('\n'));
+        assert_eq!(file_contents(&restored_output), "web\n");
+        assert!(
+            !ran.exists(),
+            "The command of the layout pane should not run again on restore"
+        );
+    }
+
+    async fn init_test(
+        cx: &mut TestAppContext,
+    ) -> (
+        TempTree,
+        Entity<Workspace>,
+        Entity<TerminalPanel>,
+        &mut VisualTestContext,
+    ) {
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init_settings(cx);
+            workspace::init_settings(cx);
+            client::init_settings(cx);
+            Project::init_settings(cx);
+            terminal::init(cx);
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<TerminalSettings>(cx, |settings| {
+                    settings.shell = Some(Shell::Program("sh".to_string()));
+                });
+            });
+        });
+
+        let tree = TempTree::new(json!({
+            "web": {},
+            "worker": {},
+            "db": {},
+        }));
+        let fs = Arc::new(RealFs::new(None, cx.executor()));
+        let project = Project::test(fs, [tree.path()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+        let terminal_panel = workspace.update_in(cx, |workspace, window, cx| {
+            let terminal_panel = cx.new(|cx| TerminalPanel::new(workspace, window, cx));
+            workspace.add_panel(terminal_panel.clone(), window, cx);
+            terminal_panel
+        });
+        (tree, workspace, terminal_panel, cx)
+    }
+
+    fn layout_pane(cwd: &str, group: Option<&str>, command: Option<&str>) -> TerminalLayoutPane {
+        TerminalLayoutPane {
+            command: command.map(ToOwned::to_owned),
+            cwd: Some(PathBuf::from(cwd)),
+            env: HashMap::default(),
+            group: group.map(ToOwned::to_owned),
+        }
+    }
+
+    async fn add_layout(
+        layout: TerminalLayout,
+        terminal_panel: &Entity<TerminalPanel>,
+        cx: &mut VisualTestContext,
+    ) {
+        terminal_panel
+            .update_in(cx, |terminal_panel, window, cx| {
+                terminal_panel.add_layout(layout, window, cx)
+            })
+            .await
+            .unwrap();
+    }
+
+    fn toggle_broadcast_input(
+        group: Option<&str>,
+        workspace: &Entity<Workspace>,
+        cx: &mut VisualTestContext,
+    ) {
+        workspace.update_in(cx, |workspace, window, cx| {
+            TerminalPanel::toggle_broadcast_input(
+                workspace,
+                &ToggleBroadcastInput {
+                    group: group.map(ToOwned::to_owned),
+                },
+                window,
+                cx,
+            )
+        });
+    }
+
+    fn wait_until(cx: &mut VisualTestContext, condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(20));
+            cx.run_until_parked();
+        }
+    }
+
+    fn file_contents(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap_or_default()
+    }
+}
@@ -9,24 +9,27 @@ pub mod terminal_tab_tooltip;
 use assistant_slash_command::SlashCommandRegistry;
 use editor::{EditorSettings, actions::SelectAll, scroll::ScrollbarAutoHide};
 use gpui::{
-    Action, AnyElement, App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
-    KeyContext, KeyDownEvent, Keystroke, MouseButton, MouseDownEvent, Pixels, Render,
+    Action, AnyElement, App, ClipboardItem, DismissEvent, Entity, EventEmitter, FocusHandle,
+    Focusable, KeyContext, KeyDownEvent, Keystroke, MouseButton, MouseDownEvent, Pixels, Render,
     ScrollWheelEvent, Stateful, Styled, Subscription, Task, WeakEntity, actions, anchored,
     deferred, div,
 };
 use persistence::TERMINAL_DB;
-use project::{Project, search::SearchQuery};
+use project::{Project, search::SearchQuery, terminals::TerminalShellOptions};
 use schemars::JsonSchema;
 use task::TaskId;
 use terminal::{
-    Clear, Copy, Event, HoveredWord, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp,
-    ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskState,
-    TaskStatus, Terminal, TerminalBounds, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, HoveredWord, MaybeNavigationTarget, Paste,
+    RerunLastCommand, SavedScrollback, ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp,
+    ScrollToBottom, ScrollToNextPrompt, ScrollToPreviousPrompt, ScrollToTop, ShowCharacterPalette,
+    TaskState, TaskStatus, Terminal, TerminalBounds, ToggleViMode,
     alacritty_terminal::{
         index::Point,
         term::{TermMode, point_to_viewport, search::RegexSearch},
     },
-    terminal_settings::{self, CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
+    terminal_settings::{
+        self, CursorShape, TerminalBlink, TerminalLayoutPane, TerminalSettings, WorkingDirectory,
+    },
 };
 use terminal_element::TerminalElement;
 use terminal_panel::TerminalPanel;
@@ -140,6 +143,8 @@ pub struct TerminalView {
     hide_scrollbar_task: Option<Task<()>>,
     marked_text: Option<String>,
     marked_range_utf16: Option<Range<usize>>,
+    /// The pane of a named terminal layout this terminal was opened from.
+    layout_pane: Option<TerminalLayoutPane>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -265,15 +270,39 @@ impl TerminalView {
             cwd_serialized: false,
             marked_text: None,
             marked_range_utf16: None,
+            layout_pane: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
+                cx.on_app_quit(Self::save_scrollback),
             ],
             _terminal_subscriptions: terminal_subscriptions,
         }
     }
 
+    /// Saves the terminal's scrollback on quit, so that it is shown again when the workspace is restored.
+    fn save_scrollback(&mut self, cx: &mut Context<Self>) -> Task<()> {
+        let terminal = self.terminal.read(cx);
+        let Some(workspace_id) = self.workspace_id.filter(|_| {
+            matches!(self.mode, TerminalMode::Standalone)
+                && terminal.task().is_none()
+                && TerminalSettings::get_global(cx).restore_scrollback
+        }) else {
+            return Task::ready(());
+        };
+        let item_id = cx.entity_id().as_u64();
+        let scrollback = terminal.save_scrollback();
+        cx.background_spawn(async move {
+            if let Some(scrollback) = serde_json::to_string(&scrollback).log_err() {
+                TERMINAL_DB
+                    .save_scrollback(item_id, workspace_id, scrollback)
+                    .await
+                    .log_err();
+            }
+        })
+    }
+
     /// Enable 'embedded' mode where the terminal displays the full content with an optional limit of lines.
     pub fn set_embedded_mode(
         &mut self,
@@ -351,7 +380,75 @@ impl TerminalView {
             self.terminal.update(cx, |term, _| {
                 term.input(text.to_string().into_bytes());
             });
+            self.broadcast(cx, |term, _| term.input(text.to_string().into_bytes()));
+        }
+    }
+
+    /// Marks the terminal as opened from a pane of a named layout.
+    pub(crate) fn set_layout_pane(&mut self, layout_pane: TerminalLayoutPane) {
+        self.layout_pane = Some(layout_pane);
+        self.cwd_serialized = false;
+    }
+
+    /// Types the command of the terminal's layout pane into its shell.
+    pub(crate) fn run_layout_command(&self, cx: &mut Context<Self>) {
+        if let Some(command) = self
+            .layout_pane
+            .as_ref()
+            .and_then(|layout_pane| layout_pane.command.as_ref())
+        {
+            self.terminal.update(cx, |term, _| {
+                term.input(format!("{command}\r").into_bytes());
+            });
+        }
+    }
+
+    /// The name of the group of terminals, in the terminal panel, that input can be broadcast to together.
+    pub(crate) fn broadcast_group(&self) -> Option<&str> {
+        self.layout_pane.as_ref()?.group.as_deref()
+    }
+
+    /// Whether the input typed into this terminal is mirrored to other terminals of the terminal panel,
+    /// and the other way around.
+    fn receives_broadcast_input(&self, cx: &Context<Self>) -> bool {
+        self.terminal.read(cx).task().is_none()
+            && self.terminal_panel(cx).is_some_and(|terminal_panel| {
+                terminal_panel.read(cx).receives_broadcast_input(
+                    cx.entity_id(),
+                    self.broadcast_group(),
+                    cx,
+                )
+            })
+    }
+
+    /// Mirrors input to the other terminals of the terminal panel, while input is broadcast to this one.
+    fn broadcast(
+        &self,
+        cx: &mut Context<Self>,
+        mut input: impl FnMut(&mut Terminal, &mut Context<Terminal>),
+    ) {
+        if !self.receives_broadcast_input(cx) {
+            return;
+        }
+        let Some(terminal_panel) = self.terminal_panel(cx) else {
+            return;
+        };
+        let targets = terminal_panel
+            .read(cx)
+            .broadcast_targets(cx.entity_id(), cx);
+        for target in targets {
+            target.update(cx, |term, cx| input(term, cx));
+        }
+    }
+
+    fn terminal_panel(&self, cx: &App) -> Option<Entity<TerminalPanel>> {
+        if !matches!(self.mode, TerminalMode::Standalone) {
+            return None;
         }
+        self.workspace
+            .upgrade()?
+            .read(cx)
+            .panel::<TerminalPanel>(cx)
     }
 
     pub(crate) fn terminal_bounds(&self, cx: &App) -> TerminalBounds {
@@ -602,6 +699,44 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(
+        &mut self,
+        _: &ScrollToNextPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(
+        &mut self,
+        _: &CopyLastCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(output) = self.terminal.read(cx).last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
+    fn rerun_last_command(&mut self, _: &RerunLastCommand, _: &mut Window, cx: &mut Context<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.rerun_last_command());
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, _: &mut Window, cx: &mut Context<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -711,6 +846,7 @@ impl TerminalView {
         if let Some(clipboard_string) = cx.read_from_clipboard().and_then(|item| item.text()) {
             self.terminal
                 .update(cx, |terminal, _cx| terminal.paste(&clipboard_string));
+            self.broadcast(cx, |terminal, _| terminal.paste(&clipboard_string));
         }
     }
 
@@ -719,6 +855,7 @@ impl TerminalView {
         self.terminal.update(cx, |term, _| {
             term.input(text.0.to_string().into_bytes());
         });
+        self.broadcast(cx, |term, _| term.input(text.0.to_string().into_bytes()));
     }
 
     fn send_keystroke(&mut self, text: &SendKeystroke, _: &mut Window, cx: &mut Context<Self>) {
@@ -732,6 +869,9 @@ impl TerminalView {
                 }
                 processed
             });
+            self.broadcast(cx, |term, cx| {
+                term.try_keystroke(&keystroke, TerminalSettings::get_global(cx).option_as_meta);
+            });
         }
     }
 
@@ -1096,6 +1236,8 @@ fn subscribe_for_terminal_events(
                     window.invalidate_character_coordinates();
                     cx.emit(SearchEvent::ActiveMatchChanged)
                 }
+                Event::TaskFinished => {}
+                Event::TaskReadinessChanged => cx.emit(ItemEvent::UpdateTab),
             }
         },
     );
@@ -1119,15 +1261,21 @@ impl TerminalView {
         self.clear_bell(cx);
         self.pause_cursor_blinking(window, cx);
 
-        self.terminal.update(cx, |term, cx| {
-            let handled = term.try_keystroke(
+        let handled = self.terminal.update(cx, |term, cx| {
+            term.try_keystroke(
                 &event.keystroke,
                 TerminalSettings::get_global(cx).option_as_meta,
-            );
-            if handled {
-                cx.stop_propagation();
-            }
+            )
         });
+        if handled {
+            cx.stop_propagation();
+            self.broadcast(cx, |term, cx| {
+                term.try_keystroke(
+                    &event.keystroke,
+                    TerminalSettings::get_global(cx).option_as_meta,
+                );
+            });
+        }
     }
 
     fn focus_in(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -1184,6 +1332,10 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
+            .on_action(cx.listener(TerminalView::rerun_last_command))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1232,6 +1384,15 @@ impl Render for TerminalView {
                         div.child(scrollbar)
                     }),
             )
+            .when(self.receives_broadcast_input(cx), |this| {
+                this.child(
+                    div()
+                        .absolute()
+                        .inset_0()
+                        .border_2()
+                        .border_color(cx.theme().status().warning_border),
+                )
+            })
             .children(self.context_menu.as_ref().map(|(menu, position, _)| {
                 deferred(
                     anchored()
@@ -1265,7 +1426,16 @@ impl Item for TerminalView {
             Some(terminal_task) => match &terminal_task.status {
                 TaskStatus::Running => (
                     IconName::PlayFilled,
-                    Color::Disabled,
+                    // Background tasks that are ready keep running, but are done for their dependents.
+                    if terminal_task
+                        .background
+                        .as_ref()
+                        .is_some_and(|background| background.is_ready())
+                    {
+                        Color::Success
+                    } else {
+                        Color::Disabled
+                    },
                     TerminalView::rerun_button(terminal_task),
                 ),
                 TaskStatus::Unknown => (
@@ -1445,10 +1615,20 @@ impl SerializableItem for TerminalView {
 
         if let Some((cwd, workspace_id)) = terminal.working_directory().zip(self.workspace_id) {
             self.cwd_serialized = true;
+            let layout_pane = self
+                .layout_pane
+                .as_ref()
+                .and_then(|layout_pane| serde_json::to_string(layout_pane).log_err());
             Some(cx.background_spawn(async move {
                 TERMINAL_DB
                     .save_working_directory(item_id, workspace_id, cwd)
-                    .await
+                    .await?;
+                if let Some(layout_pane) = layout_pane {
+                    TERMINAL_DB
+                        .save_layout_pane(item_id, workspace_id, layout_pane)
+                        .await?;
+                }
+                anyhow::Ok(())
             }))
         } else {
             None
@@ -1468,6 +1648,28 @@ impl SerializableItem for TerminalView {
         cx: &mut App,
     ) -> Task<anyhow::Result<Entity<Self>>> {
         window.spawn(cx, async move |cx| {
+            let scrollback = cx
+                .update(|_window, cx| {
+                    if !TerminalSettings::get_global(cx).restore_scrollback {
+                        return None;
+                    }
+                    let scrollback = TERMINAL_DB
+                        .get_scrollback(item_id, workspace_id)
+                        .log_err()
+                        .flatten()?;
+                    serde_json::from_str::<SavedScrollback>(&scrollback)
+                        .log_err()
+                        .filter(|scrollback| !scrollback.is_empty())
+                })
+                .ok()
+                .flatten();
+            let layout_pane = TERMINAL_DB
+                .get_layout_pane(item_id, workspace_id)
+                .log_err()
+                .flatten()
+                .and_then(|layout_pane| {
+                    serde_json::from_str::<TerminalLayoutPane>(&layout_pane).log_err()
+                });
             let cwd = cx
                 .update(|_window, cx| {
                     let from_db = TERMINAL_DB
@@ -1488,19 +1690,34 @@ impl SerializableItem for TerminalView {
                 .ok()
                 .flatten();
 
+            let options = TerminalShellOptions {
+                env: layout_pane
+                    .as_ref()
+                    .map(|layout_pane| layout_pane.env.clone())
+                    .unwrap_or_default(),
+                restored_scrollback: scrollback,
+            };
             let terminal = project
-                .update(cx, |project, cx| project.create_terminal_shell(cwd, cx))?
+                .update(cx, |project, cx| {
+                    project.create_terminal_shell_with_options(cwd, options, cx)
+                })?
                 .await?;
             cx.update(|window, cx| {
                 cx.new(|cx| {
-                    TerminalView::new(
+                    let mut terminal_view = TerminalView::new(
                         terminal,
                         workspace,
                         Some(workspace_id),
                         project.downgrade(),
                         window,
                         cx,
-                    )
+                    );
+                    // Layout terminals are restored without their commands, which may not be
+                    // safe to run again.
+                    if let Some(layout_pane) = layout_pane {
+                        terminal_view.set_layout_pane(layout_pane);
+                    }
+                    terminal_view
                 })
             })
         })
@@ -1515,6 +1732,7 @@ impl SearchableItem for TerminalView {
             case: false,
             word: false,
             regex: true,
+            structural: false,
             replacement: false,
             selection: false,
             find_in_results: false,
@@ -1668,7 +1886,7 @@ pub(crate) fn default_working_directory(workspace: &Workspace, cx: &App) -> Opti
     }
 }
 ///Gets the first project's home directory, or the home directory
-fn first_project_directory(workspace: &Workspace, cx: &App) -> Option<PathBuf> {
+pub(crate) fn first_project_directory(workspace: &Workspace, cx: &App) -> Option<PathBuf> {
     let worktree = workspace.worktrees(cx).next()?.read(cx);
     if !worktree.root_entry()?.is_dir() {
         return None;
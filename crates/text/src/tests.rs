@@ -657,6 +657,179 @@ fn test_finalize_last_transaction() {
     assert_eq!(buffer.text(), "ab2cde6");
 }
 
+#[test]
+fn test_undo_tree() {
+    let now = Instant::now();
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "one");
+
+    let transaction_1 = buffer.start_transaction_at(now).unwrap();
+    buffer.edit([(3..3, " two")]);
+    buffer.end_transaction_at(now);
+    let transaction_2 = buffer.start_transaction_at(now).unwrap();
+    buffer.edit([(7..7, " three")]);
+    buffer.end_transaction_at(now);
+    assert_eq!(buffer.text(), "one two three");
+
+    // Editing after an undo starts a new branch, keeping the undone transaction.
+    buffer.undo();
+    let transaction_3 = buffer.start_transaction_at(now).unwrap();
+    buffer.edit([(7..7, " four")]);
+    buffer.end_transaction_at(now);
+    assert_eq!(buffer.text(), "one two four");
+    buffer.redo();
+    assert_eq!(buffer.text(), "one two four");
+
+    let tree = buffer.undo_tree();
+    assert_eq!(tree.current, Some(transaction_3));
+    assert_eq!(
+        tree.states
+            .iter()
+            .map(|state| (state.transaction_id, state.parent, state.text.to_string()))
+            .collect::<Vec<_>>(),
+        [
+            (transaction_1, None, "one two".to_string()),
+            (
+                transaction_2,
+                Some(transaction_1),
+                "one two three".to_string()
+            ),
+            (
+                transaction_3,
+                Some(transaction_1),
+                "one two four".to_string()
+            ),
+        ]
+    );
+
+    buffer.undo_tree_move_to(Some(transaction_2));
+    assert_eq!(buffer.text(), "one two three");
+    assert_eq!(buffer.undo_tree().current, Some(transaction_2));
+
+    // Undo and redo follow the branch that was moved to.
+    buffer.undo();
+    assert_eq!(buffer.text(), "one two");
+    buffer.redo();
+    assert_eq!(buffer.text(), "one two three");
+
+    buffer.undo_tree_move_to(None);
+    assert_eq!(buffer.text(), "one");
+    buffer.redo();
+    buffer.redo();
+    assert_eq!(buffer.text(), "one two four");
+
+    // Moving to a state that isn't in the tree does nothing.
+    assert!(
+        buffer
+            .undo_tree_move_to(Some(buffer.lamport_clock))
+            .is_empty()
+    );
+    assert_eq!(buffer.text(), "one two four");
+}
+
+#[test]
+fn test_undo_tree_chronological_navigation() {
+    let mut now = Instant::now();
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "");
+
+    let mut transactions = Vec::new();
+    for text in ["a", "b", "c"] {
+        now += Duration::from_secs(60);
+        transactions.push(buffer.start_transaction_at(now).unwrap());
+        buffer.edit([(buffer.len()..buffer.len(), text)]);
+        buffer.end_transaction_at(now);
+    }
+    buffer.undo();
+    buffer.undo();
+    now += Duration::from_secs(60);
+    transactions.push(buffer.start_transaction_at(now).unwrap());
+    buffer.edit([(1..1, "d")]);
+    buffer.end_transaction_at(now);
+    assert_eq!(buffer.text(), "ad");
+
+    // Moving by states goes through the branches in the order their states were created.
+    let tree = buffer.undo_tree();
+    assert_eq!(tree.sequence_number(tree.current), 4);
+    assert_eq!(tree.earlier(1), Some(transactions[2]));
+    assert_eq!(tree.earlier(2), Some(transactions[1]));
+    assert_eq!(tree.earlier(10), None);
+    assert_eq!(tree.later(1), Some(transactions[3]));
+
+    buffer.undo_tree_move_to(tree.earlier(1));
+    assert_eq!(buffer.text(), "abc");
+    let tree = buffer.undo_tree();
+    assert_eq!(tree.later(1), Some(transactions[3]));
+    assert_eq!(tree.later(2), Some(transactions[3]));
+
+    // Moving by time goes to the last state edited in the given period.
+    assert_eq!(
+        tree.earlier_by(Duration::from_secs(90)),
+        Some(transactions[0])
+    );
+    assert_eq!(tree.earlier_by(Duration::from_secs(600)), None);
+    assert_eq!(
+        tree.later_by(Duration::from_secs(30)),
+        Some(transactions[2])
+    );
+    assert_eq!(
+        tree.later_by(Duration::from_secs(60)),
+        Some(transactions[3])
+    );
+}
+
+#[test]
+fn test_restore_undo_tree() {
+    let now = Instant::now();
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "one two four");
+    let states = [
+        SavedUndoState {
+            id: 1,
+            parent: None,
+            edited_at: now,
+            edits: vec![(3..3, " two".into())],
+        },
+        SavedUndoState {
+            id: 2,
+            parent: Some(1),
+            edited_at: now,
+            edits: vec![(7..7, " three".into())],
+        },
+        SavedUndoState {
+            id: 3,
+            parent: Some(1),
+            edited_at: now,
+            edits: vec![(7..7, " four".into())],
+        },
+    ];
+
+    // The saved tree must end up with the buffer's text.
+    assert!(buffer.restore_undo_tree("one", &states, Some(2)).is_none());
+    assert_eq!(buffer.text(), "one two four");
+    assert!(buffer.undo_tree().states.is_empty());
+
+    assert!(buffer.restore_undo_tree("one", &states, Some(3)).is_some());
+    assert_eq!(buffer.text(), "one two four");
+    let tree = buffer.undo_tree();
+    assert_eq!(tree.root_text.to_string(), "one");
+    assert_eq!(
+        tree.states
+            .iter()
+            .map(|state| state.text.to_string())
+            .collect::<Vec<_>>(),
+        ["one two", "one two three", "one two four"]
+    );
+
+    buffer.undo();
+    assert_eq!(buffer.text(), "one two");
+    buffer.undo();
+    assert_eq!(buffer.text(), "one");
+    assert!(buffer.undo().is_none());
+    buffer.undo_tree_move_to(Some(tree.states[1].transaction_id));
+    assert_eq!(buffer.text(), "one two three");
+
+    // Only a buffer without an undo history can be restored.
+    assert!(buffer.restore_undo_tree("one", &states, Some(2)).is_none());
+}
+
 #[test]
 fn test_edited_ranges_for_transaction() {
     let now = Instant::now();
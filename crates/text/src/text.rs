@@ -29,6 +29,7 @@ use std::{
     fmt::Display,
     future::Future,
     iter::Iterator,
+    mem,
     num::NonZeroU64,
     ops::{self, Deref, Range, Sub},
     str,
@@ -121,6 +122,8 @@ pub struct HistoryEntry {
     first_edit_at: Instant,
     last_edit_at: Instant,
     suppress_grouping: bool,
+    /// The buffer's text as of the end of the transaction.
+    text: Rope,
 }
 
 #[derive(Clone, Debug)]
@@ -147,10 +150,123 @@ struct History {
     operations: TreeMap<clock::Lamport, Operation>,
     undo_stack: Vec<HistoryEntry>,
     redo_stack: Vec<HistoryEntry>,
+    /// Redo stacks that were abandoned by editing after an undo. Together with the undo and
+    /// redo stacks they form the undo tree, where the undo stack is the path to the current state.
+    branches: Vec<UndoBranch>,
+    /// The text the undo tree starts from when it differs from `base_text`.
+    undo_root_text: Option<Rope>,
     transaction_depth: usize,
     group_interval: Duration,
 }
 
+#[derive(Clone, Debug)]
+struct UndoBranch {
+    /// The transaction the branch was forked from, or `None` if it starts at the root.
+    parent: Option<TransactionId>,
+    /// The branch's entries in redo stack order, so the last entry is the parent's child.
+    entries: Vec<HistoryEntry>,
+}
+
+/// A snapshot of a buffer's undo history. Editing after an undo starts a new branch instead of
+/// discarding the undone transactions, so the states of the history form a tree.
+#[derive(Clone, Debug)]
+pub struct UndoTree {
+    /// The text before any of the tree's transactions.
+    pub root_text: Rope,
+    /// The tree's states, in the order their transactions were started.
+    pub states: Vec<UndoState>,
+    /// The state the buffer is in, or `None` if it's at the root.
+    pub current: Option<TransactionId>,
+}
+
+/// A state of an [`UndoTree`], reached by applying a transaction to its parent state.
+#[derive(Clone, Debug)]
+pub struct UndoState {
+    pub transaction_id: TransactionId,
+    /// The state this one was edited from, or `None` for the root.
+    pub parent: Option<TransactionId>,
+    pub first_edit_at: Instant,
+    pub last_edit_at: Instant,
+    /// The buffer's text in this state.
+    pub text: Rope,
+}
+
+impl UndoTree {
+    pub fn state(&self, transaction_id: TransactionId) -> Option<&UndoState> {
+        let ix = self
+            .states
+            .binary_search_by_key(&transaction_id, |state| state.transaction_id)
+            .ok()?;
+        Some(&self.states[ix])
+    }
+
+    /// Returns the states edited from the given one, or from the root if it's `None`.
+    pub fn children(&self, parent: Option<TransactionId>) -> impl Iterator<Item = &UndoState> {
+        self.states
+            .iter()
+            .filter(move |state| state.parent == parent)
+    }
+
+    /// Numbers the states in the order they were created, starting with zero for the root.
+    pub fn sequence_number(&self, state: Option<TransactionId>) -> usize {
+        state.map_or(0, |transaction_id| {
+            self.states
+                .binary_search_by_key(&transaction_id, |state| state.transaction_id)
+                .map_or(0, |ix| ix + 1)
+        })
+    }
+
+    fn state_with_sequence_number(&self, sequence_number: usize) -> Option<TransactionId> {
+        let ix = sequence_number.checked_sub(1)?;
+        Some(self.states.get(ix)?.transaction_id)
+    }
+
+    /// Returns the state created `count` states before the current one, regardless of branches.
+    pub fn earlier(&self, count: usize) -> Option<TransactionId> {
+        self.state_with_sequence_number(self.sequence_number(self.current).saturating_sub(count))
+    }
+
+    /// Returns the state created `count` states after the current one, regardless of branches.
+    pub fn later(&self, count: usize) -> Option<TransactionId> {
+        let sequence_number = self.sequence_number(self.current).saturating_add(count);
+        self.state_with_sequence_number(sequence_number.min(self.states.len()))
+    }
+
+    /// Returns the last state that was edited at least `duration` before the current one.
+    pub fn earlier_by(&self, duration: Duration) -> Option<TransactionId> {
+        let current = self.state(self.current?)?;
+        let time = current.last_edit_at.checked_sub(duration)?;
+        self.states[..self.sequence_number(self.current)]
+            .iter()
+            .rfind(|state| state.last_edit_at <= time)
+            .map(|state| state.transaction_id)
+    }
+
+    /// Returns the last state that was edited at most `duration` after the current one.
+    pub fn later_by(&self, duration: Duration) -> Option<TransactionId> {
+        let time = match self.current.and_then(|current| self.state(current)) {
+            Some(current) => current.last_edit_at,
+            None => self.states.first()?.first_edit_at,
+        } + duration;
+        self.states[self.sequence_number(self.current)..]
+            .iter()
+            .take_while(|state| state.last_edit_at <= time)
+            .last()
+            .map_or(self.current, |state| Some(state.transaction_id))
+    }
+}
+
+/// A state of an undo tree that outlived its buffer, see [`Buffer::restore_undo_tree`].
+#[derive(Clone, Debug)]
+pub struct SavedUndoState {
+    pub id: u64,
+    /// The state this one was edited from, or `None` for the root.
+    pub parent: Option<u64>,
+    pub edited_at: Instant,
+    /// The edits turning the parent's text into this state's text, in offsets of the parent.
+    pub edits: Vec<(Range<usize>, Arc<str>)>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct InsertionSlice {
     edit_id: clock::Lamport,
@@ -191,6 +307,8 @@ impl History {
             operations: Default::default(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            branches: Vec::new(),
+            undo_root_text: None,
             transaction_depth: 0,
             // Don't group transactions in tests unless we opt in, because it's a footgun.
             #[cfg(any(test, feature = "test-support"))]
@@ -222,6 +340,7 @@ impl History {
                 first_edit_at: now,
                 last_edit_at: now,
                 suppress_grouping: false,
+                text: Rope::new(),
             });
             Some(id)
         } else {
@@ -229,7 +348,7 @@ impl History {
         }
     }
 
-    fn end_transaction(&mut self, now: Instant) -> Option<&HistoryEntry> {
+    fn end_transaction(&mut self, now: Instant, text: Rope) -> Option<&HistoryEntry> {
         assert_ne!(self.transaction_depth, 0);
         self.transaction_depth -= 1;
         if self.transaction_depth == 0 {
@@ -244,9 +363,12 @@ impl History {
                 self.undo_stack.pop();
                 None
             } else {
-                self.redo_stack.clear();
+                if !self.redo_stack.is_empty() {
+                    self.fork_redo_stack();
+                }
                 let entry = self.undo_stack.last_mut().unwrap();
                 entry.last_edit_at = now;
+                entry.text = text;
                 Some(entry)
             }
         } else {
@@ -254,6 +376,22 @@ impl History {
         }
     }
 
+    /// Moves the redo stack into a branch of the undo tree, because the transaction on top of the
+    /// undo stack was started after undoing the redo stack's transactions.
+    fn fork_redo_stack(&mut self) {
+        let mut entries = self.undo_stack.iter_mut().rev().skip(1);
+        let parent = entries.next().map(|entry| {
+            // Grouping the new transaction with the state the branch was forked from would
+            // change that state for the branch too.
+            entry.suppress_grouping = true;
+            entry.transaction.id
+        });
+        self.branches.push(UndoBranch {
+            parent,
+            entries: mem::take(&mut self.redo_stack),
+        });
+    }
+
     fn group(&mut self) -> Option<TransactionId> {
         let mut count = 0;
         let mut entries = self.undo_stack.iter();
@@ -298,6 +436,7 @@ impl History {
 
             if let Some(entry) = entries_to_merge.last_mut() {
                 last_entry.last_edit_at = entry.last_edit_at;
+                last_entry.text = entry.text.clone();
             }
         }
 
@@ -312,13 +451,14 @@ impl History {
         })
     }
 
-    fn push_transaction(&mut self, transaction: Transaction, now: Instant) {
+    fn push_transaction(&mut self, transaction: Transaction, now: Instant, text: Rope) {
         assert_eq!(self.transaction_depth, 0);
         self.undo_stack.push(HistoryEntry {
             transaction,
             first_edit_at: now,
             last_edit_at: now,
             suppress_grouping: false,
+            text,
         });
     }
 
@@ -338,6 +478,7 @@ impl History {
         start: clock::Global,
         now: Instant,
         clock: &mut clock::Lamport,
+        text: Rope,
     ) -> TransactionId {
         assert_eq!(self.transaction_depth, 0);
         let id = clock.tick();
@@ -351,6 +492,7 @@ impl History {
             first_edit_at: now,
             last_edit_at: now,
             suppress_grouping: false,
+            text,
         });
         id
     }
@@ -428,28 +570,45 @@ impl History {
                 self.redo_stack
                     .iter()
                     .rfind(|entry| entry.transaction.id == transaction_id)
+            })
+            .or_else(|| {
+                self.branches
+                    .iter()
+                    .flat_map(|branch| &branch.entries)
+                    .find(|entry| entry.transaction.id == transaction_id)
             })?;
         Some(&entry.transaction)
     }
 
-    fn transaction_mut(&mut self, transaction_id: TransactionId) -> Option<&mut Transaction> {
-        let entry = self
-            .undo_stack
+    fn entry_mut(&mut self, transaction_id: TransactionId) -> Option<&mut HistoryEntry> {
+        self.undo_stack
             .iter_mut()
             .rfind(|entry| entry.transaction.id == transaction_id)
             .or_else(|| {
                 self.redo_stack
                     .iter_mut()
                     .rfind(|entry| entry.transaction.id == transaction_id)
-            })?;
-        Some(&mut entry.transaction)
+            })
+    }
+
+    fn transaction_mut(&mut self, transaction_id: TransactionId) -> Option<&mut Transaction> {
+        Some(&mut self.entry_mut(transaction_id)?.transaction)
     }
 
-    fn merge_transactions(&mut self, transaction: TransactionId, destination: TransactionId) {
+    fn merge_transactions(
+        &mut self,
+        transaction: TransactionId,
+        destination: TransactionId,
+        text: Rope,
+    ) {
         if let Some(transaction) = self.forget(transaction)
-            && let Some(destination) = self.transaction_mut(destination)
+            && let Some(destination) = self.entry_mut(destination)
         {
-            destination.edit_ids.extend(transaction.edit_ids);
+            destination
+                .transaction
+                .edit_ids
+                .extend(transaction.edit_ids);
+            destination.text = text;
         }
     }
 
@@ -477,6 +636,155 @@ impl History {
         }
         &self.undo_stack[undo_stack_start_len..]
     }
+
+    /// Returns every entry of the undo tree along with the transaction it was edited from.
+    fn tree_entries(&self) -> Vec<(Option<TransactionId>, &HistoryEntry)> {
+        let mut entries = Vec::new();
+        let mut parent = None;
+        for entry in &self.undo_stack {
+            entries.push((parent, entry));
+            parent = Some(entry.transaction.id);
+        }
+        for entry in self.redo_stack.iter().rev() {
+            entries.push((parent, entry));
+            parent = Some(entry.transaction.id);
+        }
+        for branch in &self.branches {
+            let mut parent = branch.parent;
+            for entry in branch.entries.iter().rev() {
+                entries.push((parent, entry));
+                parent = Some(entry.transaction.id);
+            }
+        }
+        entries
+    }
+
+    fn undo_tree(&self, base_text: &Rope) -> UndoTree {
+        let mut states = self
+            .tree_entries()
+            .into_iter()
+            .map(|(parent, entry)| UndoState {
+                transaction_id: entry.transaction.id,
+                parent,
+                first_edit_at: entry.first_edit_at,
+                last_edit_at: entry.last_edit_at,
+                text: entry.text.clone(),
+            })
+            .collect::<Vec<_>>();
+        states.sort_by_key(|state| state.transaction_id);
+        UndoTree {
+            root_text: self
+                .undo_root_text
+                .clone()
+                .unwrap_or_else(|| base_text.clone()),
+            states,
+            current: self.undo_stack.last().map(|entry| entry.transaction.id),
+        }
+    }
+
+    /// Rearranges the undo tree so that the undo stack leads to the given state, returning the
+    /// transactions that have to be undone or redone to get there, in order.
+    fn move_to(&mut self, target: Option<TransactionId>) -> Option<Vec<Transaction>> {
+        assert_eq!(self.transaction_depth, 0);
+
+        let parents = self
+            .tree_entries()
+            .into_iter()
+            .map(|(parent, entry)| (entry.transaction.id, parent))
+            .collect::<HashMap<_, _>>();
+        let mut path = Vec::new();
+        let mut state = target;
+        while let Some(transaction_id) = state {
+            path.push(transaction_id);
+            state = *parents.get(&transaction_id)?;
+        }
+        path.reverse();
+
+        let common_len = self
+            .undo_stack
+            .iter()
+            .zip(&path)
+            .take_while(|(entry, transaction_id)| entry.transaction.id == **transaction_id)
+            .count();
+        let mut transactions = self.undo_stack[common_len..]
+            .iter()
+            .rev()
+            .map(|entry| entry.transaction.clone())
+            .collect::<Vec<_>>();
+
+        let mut children = HashMap::<Option<TransactionId>, Vec<TransactionId>>::default();
+        for (transaction_id, parent) in &parents {
+            children.entry(*parent).or_default().push(*transaction_id);
+        }
+        for siblings in children.values_mut() {
+            siblings.sort();
+        }
+        let mut entries = self
+            .undo_stack
+            .drain(..)
+            .chain(self.redo_stack.drain(..))
+            .chain(self.branches.drain(..).flat_map(|branch| branch.entries))
+            .map(|entry| (entry.transaction.id, entry))
+            .collect::<HashMap<_, _>>();
+
+        self.undo_stack = path
+            .iter()
+            .filter_map(|transaction_id| entries.remove(transaction_id))
+            .collect();
+        transactions.extend(
+            self.undo_stack[common_len..]
+                .iter()
+                .map(|entry| entry.transaction.clone()),
+        );
+
+        // Redoing from the new state follows its most recent child, and the remaining states
+        // are split into branches that do the same.
+        self.redo_stack = newest_child(target, &children, &entries)
+            .map(|child| take_undo_branch(child, &children, &mut entries))
+            .unwrap_or_default();
+        let mut remaining = entries.keys().copied().collect::<Vec<_>>();
+        remaining.sort();
+        for transaction_id in remaining {
+            if entries.contains_key(&transaction_id) {
+                self.branches.push(UndoBranch {
+                    parent: parents[&transaction_id],
+                    entries: take_undo_branch(transaction_id, &children, &mut entries),
+                });
+            }
+        }
+
+        Some(transactions)
+    }
+}
+
+fn newest_child(
+    parent: Option<TransactionId>,
+    children: &HashMap<Option<TransactionId>, Vec<TransactionId>>,
+    entries: &HashMap<TransactionId, HistoryEntry>,
+) -> Option<TransactionId> {
+    children
+        .get(&parent)?
+        .iter()
+        .rev()
+        .find(|transaction_id| entries.contains_key(transaction_id))
+        .copied()
+}
+
+/// Removes the chain of entries that starts at the given one and follows the most recent child
+/// of each entry, returning it in redo stack order.
+fn take_undo_branch(
+    transaction_id: TransactionId,
+    children: &HashMap<Option<TransactionId>, Vec<TransactionId>>,
+    entries: &mut HashMap<TransactionId, HistoryEntry>,
+) -> Vec<HistoryEntry> {
+    let mut branch = Vec::new();
+    let mut next = Some(transaction_id);
+    while let Some(entry) = next.and_then(|transaction_id| entries.remove(&transaction_id)) {
+        next = newest_child(Some(entry.transaction.id), children, entries);
+        branch.push(entry);
+    }
+    branch.reverse();
+    branch
 }
 
 struct Edits<'a, D: TextDimension, F: FnMut(&FragmentSummary) -> bool> {
@@ -1402,7 +1710,10 @@ impl Buffer {
     }
 
     pub fn end_transaction_at(&mut self, now: Instant) -> Option<(TransactionId, clock::Global)> {
-        if let Some(entry) = self.history.end_transaction(now) {
+        if let Some(entry) = self
+            .history
+            .end_transaction(now, self.snapshot.visible_text.clone())
+        {
             let since = entry.transaction.start.clone();
             let id = self.history.group().unwrap();
             Some((id, since))
@@ -1470,7 +1781,11 @@ impl Buffer {
     }
 
     pub fn merge_transactions(&mut self, transaction: TransactionId, destination: TransactionId) {
-        self.history.merge_transactions(transaction, destination);
+        self.history.merge_transactions(
+            transaction,
+            destination,
+            self.snapshot.visible_text.clone(),
+        );
     }
 
     pub fn redo(&mut self) -> Option<(TransactionId, Operation)> {
@@ -1498,6 +1813,102 @@ impl Buffer {
             .collect()
     }
 
+    pub fn undo_tree(&self) -> UndoTree {
+        self.history.undo_tree(&self.history.base_text)
+    }
+
+    /// Undoes and redoes transactions until the buffer is in the given state of its undo tree,
+    /// where `None` is the tree's root.
+    pub fn undo_tree_move_to(&mut self, target: Option<TransactionId>) -> Vec<Operation> {
+        let Some(transactions) = self.history.move_to(target) else {
+            return Vec::new();
+        };
+        transactions
+            .into_iter()
+            .map(|transaction| self.undo_or_redo(transaction))
+            .collect()
+    }
+
+    /// Rebuilds an undo tree that was saved from a previous buffer with the same text, by
+    /// replaying the edits of each of its states. Returns `None` without changing the buffer if it
+    /// already has an undo history, or if the saved tree doesn't end up with the buffer's text.
+    pub fn restore_undo_tree(
+        &mut self,
+        root_text: &str,
+        states: &[SavedUndoState],
+        current: Option<u64>,
+    ) -> Option<Vec<Operation>> {
+        if self.history.transaction_depth > 0
+            || !self.history.undo_stack.is_empty()
+            || !self.history.redo_stack.is_empty()
+            || !self.history.branches.is_empty()
+        {
+            return None;
+        }
+
+        let mut texts = HashMap::default();
+        for state in states {
+            let mut text = match state.parent {
+                Some(parent) => texts.get(&parent).cloned()?,
+                None => root_text.to_string(),
+            };
+            let parent_len = text.len();
+            let mut delta = 0_isize;
+            let mut previous_end = 0;
+            for (range, new_text) in &state.edits {
+                if range.start < previous_end || range.start > range.end || range.end > parent_len {
+                    return None;
+                }
+                previous_end = range.end;
+                let start = (range.start as isize + delta) as usize;
+                let end = (range.end as isize + delta) as usize;
+                if !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                    return None;
+                }
+                text.replace_range(start..end, new_text);
+                delta += new_text.len() as isize - range.len() as isize;
+            }
+            texts.insert(state.id, text);
+        }
+        let current_text = match current {
+            Some(current) => texts.get(&current)?.as_str(),
+            None => root_text,
+        };
+        if states.is_empty() || self.text() != current_text {
+            return None;
+        }
+
+        let mut operations = Vec::new();
+        let transaction_id = self.start_transaction()?;
+        operations.push(self.edit([(0..self.len(), root_text)]));
+        self.end_transaction();
+        self.forget_transaction(transaction_id);
+
+        let mut transaction_ids = HashMap::default();
+        for state in states {
+            let parent = state
+                .parent
+                .and_then(|parent| transaction_ids.get(&parent).copied().flatten());
+            operations.extend(self.undo_tree_move_to(parent));
+            self.start_transaction_at(state.edited_at);
+            if !state.edits.is_empty() {
+                operations.push(self.edit(state.edits.iter().cloned()));
+            }
+            let transaction_id = self
+                .end_transaction_at(state.edited_at)
+                .map(|(transaction_id, _)| transaction_id)
+                .or(parent);
+            // Keep the replayed states apart even though they're edited at about the same time.
+            self.finalize_last_transaction();
+            transaction_ids.insert(state.id, transaction_id);
+        }
+        let current = current.and_then(|current| transaction_ids.get(&current).copied().flatten());
+        operations.extend(self.undo_tree_move_to(current));
+        self.history.undo_root_text = Some(Rope::from(root_text));
+
+        Some(operations)
+    }
+
     fn undo_or_redo(&mut self, transaction: Transaction) -> Operation {
         let mut counts = HashMap::default();
         for edit_id in transaction.edit_ids {
@@ -1523,7 +1934,8 @@ impl Buffer {
     }
 
     pub fn push_transaction(&mut self, transaction: Transaction, now: Instant) {
-        self.history.push_transaction(transaction, now);
+        self.history
+            .push_transaction(transaction, now, self.snapshot.visible_text.clone());
     }
 
     /// Differs from `push_transaction` in that it does not clear the redo stack.
@@ -1540,8 +1952,12 @@ impl Buffer {
     /// `end_transaction` methods and merging the resulting transactions into
     /// the transaction created by this method
     pub fn push_empty_transaction(&mut self, now: Instant) -> TransactionId {
-        self.history
-            .push_empty_transaction(self.version.clone(), now, &mut self.lamport_clock)
+        self.history.push_empty_transaction(
+            self.version.clone(),
+            now,
+            &mut self.lamport_clock,
+            self.snapshot.visible_text.clone(),
+        )
     }
 
     pub fn edited_ranges_for_transaction_id<D>(
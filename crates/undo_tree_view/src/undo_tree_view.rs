@@ -0,0 +1,294 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use editor::Editor;
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ParentElement,
+    Render, Styled, WeakEntity, Window, actions,
+};
+use language::{TransactionId, UndoState, UndoTree, line_diff};
+use picker::{Picker, PickerDelegate};
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(
+    undo_tree_view,
+    [
+        /// Toggles the undo tree of the active editor, which lists every state its buffer has
+        /// been in, including the ones on abandoned branches.
+        Toggle
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(UndoTreeView::register).detach();
+}
+
+pub struct UndoTreeView {
+    picker: Entity<Picker<UndoTreeViewDelegate>>,
+}
+
+impl UndoTreeView {
+    fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(move |workspace, _: &Toggle, window, cx| {
+            Self::toggle(workspace, window, cx);
+        });
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Option<()> {
+        let editor = workspace.active_item(cx)?.act_as::<Editor>(cx)?;
+        let tree = editor.read(cx).buffer().read(cx).undo_tree(cx)?;
+
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            UndoTreeView::new(editor, tree, window, cx)
+        });
+        Some(())
+    }
+
+    fn new(
+        editor: Entity<Editor>,
+        tree: UndoTree,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = UndoTreeViewDelegate {
+            undo_tree_view: cx.entity().downgrade(),
+            editor,
+            tree: Arc::new(tree),
+            entries: Vec::new(),
+            selected_index: 0,
+        };
+
+        let picker = cx.new(|cx| Picker::nonsearchable_uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+impl Render for UndoTreeView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("UndoTreeView")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl Focusable for UndoTreeView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for UndoTreeView {}
+impl ModalView for UndoTreeView {}
+
+/// A row of the undo tree, describing one of its states.
+struct UndoTreeEntry {
+    /// The state's transaction, or `None` for the root.
+    transaction_id: Option<TransactionId>,
+    sequence_number: usize,
+    /// How many branches away from the oldest line of edits the state is.
+    depth: usize,
+    last_edit_at: Option<Instant>,
+    lines_added: u32,
+    lines_removed: u32,
+}
+
+pub struct UndoTreeViewDelegate {
+    undo_tree_view: WeakEntity<UndoTreeView>,
+    editor: Entity<Editor>,
+    tree: Arc<UndoTree>,
+    entries: Vec<UndoTreeEntry>,
+    selected_index: usize,
+}
+
+/// Lists the states of the tree depth-first, so that each branch follows the state it was
+/// edited from, along with the diff of every state against its parent.
+fn undo_tree_entries(tree: &UndoTree) -> Vec<UndoTreeEntry> {
+    let mut entries = vec![UndoTreeEntry {
+        transaction_id: None,
+        sequence_number: 0,
+        depth: 0,
+        last_edit_at: None,
+        lines_added: 0,
+        lines_removed: 0,
+    }];
+    let mut stack = Vec::new();
+    push_children(tree, None, 0, &mut stack);
+    while let Some((state, depth)) = stack.pop() {
+        let parent_text = match state.parent {
+            Some(parent) => tree
+                .state(parent)
+                .map_or_else(String::new, |parent| parent.text.to_string()),
+            None => tree.root_text.to_string(),
+        };
+        let (mut lines_added, mut lines_removed) = (0, 0);
+        for (old_rows, new_rows) in line_diff(&parent_text, &state.text.to_string()) {
+            lines_removed += old_rows.end - old_rows.start;
+            lines_added += new_rows.end - new_rows.start;
+        }
+        entries.push(UndoTreeEntry {
+            transaction_id: Some(state.transaction_id),
+            sequence_number: tree.sequence_number(Some(state.transaction_id)),
+            depth,
+            last_edit_at: Some(state.last_edit_at),
+            lines_added,
+            lines_removed,
+        });
+        push_children(tree, Some(state.transaction_id), depth, &mut stack);
+    }
+    entries
+}
+
+/// Pushes the children of `parent` so that the oldest one is popped first. It continues the
+/// parent's line of edits, while the later branches are indented one level further.
+fn push_children<'a>(
+    tree: &'a UndoTree,
+    parent: Option<TransactionId>,
+    depth: usize,
+    stack: &mut Vec<(&'a UndoState, usize)>,
+) {
+    let children = tree.children(parent).collect::<Vec<_>>();
+    for (ix, child) in children.into_iter().enumerate().rev() {
+        stack.push((child, if ix == 0 { depth } else { depth + 1 }));
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", seconds / 60),
+        3600..86400 => format!("{} h ago", seconds / 3600),
+        _ => format!("{} d ago", seconds / 86400),
+    }
+}
+
+impl PickerDelegate for UndoTreeViewDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Undo tree".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            let target = entry.transaction_id;
+            self.editor.update(cx, |editor, cx| {
+                editor.undo_tree_move_to(target, window, cx);
+            });
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.undo_tree_view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        _query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let tree = self.tree.clone();
+        let entries = cx
+            .background_executor()
+            .spawn(async move { undo_tree_entries(&tree) });
+        cx.spawn_in(window, async move |this, cx| {
+            let entries = entries.await;
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.selected_index = entries
+                    .iter()
+                    .position(|entry| entry.transaction_id == delegate.tree.current)
+                    .unwrap_or(0);
+                delegate.entries = entries;
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = self.entries.get(ix)?;
+        let is_current = entry.transaction_id == self.tree.current;
+        let age = entry
+            .last_edit_at
+            .map(|last_edit_at| format_age(Instant::now().saturating_duration_since(last_edit_at)))
+            .unwrap_or_else(|| "original".to_string());
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .indent_level(entry.depth)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Label::new(entry.sequence_number.to_string()).color(if is_current {
+                                Color::Accent
+                            } else {
+                                Color::Default
+                            }),
+                        )
+                        .child(Label::new(age).color(Color::Muted))
+                        .when(is_current, |this| {
+                            this.child(Label::new("(current)").color(Color::Muted))
+                        }),
+                )
+                .end_slot(
+                    h_flex()
+                        .gap_1()
+                        .when(entry.lines_added > 0, |this| {
+                            this.child(
+                                Label::new(format!("+{}", entry.lines_added)).color(Color::Created),
+                            )
+                        })
+                        .when(entry.lines_removed > 0, |this| {
+                            this.child(
+                                Label::new(format!("-{}", entry.lines_removed))
+                                    .color(Color::Deleted),
+                            )
+                        }),
+                ),
+        )
+    }
+}
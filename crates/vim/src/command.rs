@@ -34,12 +34,14 @@ use zed_actions::{OpenDocs, RevealTarget};
 
 use crate::{
     ToggleMarksView, ToggleRegistersView, Vim,
+    mapping::{DefineMapping, MAP_COMMANDS, is_map_command},
     motion::{EndOfDocument, Motion, MotionKind, StartOfDocument},
     normal::{
         JoinLines,
         search::{FindCommand, ReplaceCommand, Replacement},
     },
     object::Object,
+    rewrap::Rewrap,
     state::{Mark, Mode},
     visual::VisualDeleteLine,
 };
@@ -128,7 +130,7 @@ impl VimOption {
         .map(|(_, option)| option)
     }
 
-    fn from(option: &str) -> Option<Self> {
+    pub(crate) fn from(option: &str) -> Option<Self> {
         match option {
             "wrap" => Some(Self::Wrap(true)),
             "nowrap" => Some(Self::Wrap(false)),
@@ -147,6 +149,23 @@ impl VimOption {
         }
     }
 
+    pub(crate) fn apply(&self, editor: &mut Editor, cx: &mut Context<Editor>) {
+        match self {
+            VimOption::Wrap(true) => {
+                editor.set_soft_wrap_mode(language::language_settings::SoftWrap::EditorWidth, cx);
+            }
+            VimOption::Wrap(false) => {
+                editor.set_soft_wrap_mode(language::language_settings::SoftWrap::None, cx);
+            }
+            VimOption::Number(enabled) => {
+                editor.set_show_line_numbers(*enabled, cx);
+            }
+            VimOption::RelativeNumber(enabled) => {
+                editor.set_relative_line_number(Some(*enabled), cx);
+            }
+        }
+    }
+
     fn to_string(&self) -> &'static str {
         match self {
             VimOption::Wrap(true) => "wrap",
@@ -243,21 +262,7 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     // Vim::action(editor, cx, |vim, action: &StartOfLine, window, cx| {
     Vim::action(editor, cx, |vim, action: &VimSet, _, cx| {
         for option in action.options.iter() {
-            vim.update_editor(cx, |_, editor, cx| match option {
-                VimOption::Wrap(true) => {
-                    editor
-                        .set_soft_wrap_mode(language::language_settings::SoftWrap::EditorWidth, cx);
-                }
-                VimOption::Wrap(false) => {
-                    editor.set_soft_wrap_mode(language::language_settings::SoftWrap::None, cx);
-                }
-                VimOption::Number(enabled) => {
-                    editor.set_show_line_numbers(*enabled, cx);
-                }
-                VimOption::RelativeNumber(enabled) => {
-                    editor.set_relative_line_number(Some(*enabled), cx);
-                }
-            });
+            vim.update_editor(cx, |_, editor, cx| option.apply(editor, cx));
         }
     });
     Vim::action(editor, cx, |vim, _: &VisualCommand, window, cx| {
@@ -1255,6 +1260,17 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::new(("reg", "isters"), ToggleRegistersView).bang(ToggleRegistersView),
         VimCommand::new(("di", "splay"), ToggleRegistersView).bang(ToggleRegistersView),
         VimCommand::new(("marks", ""), ToggleMarksView).bang(ToggleMarksView),
+        VimCommand::new(("ea", "rlier"), editor::actions::UndoEarlier::default()).args(
+            |_, args| {
+                let (count, seconds) = parse_undo_tree_steps(&args)?;
+                Some(editor::actions::UndoEarlier { count, seconds }.boxed_clone())
+            },
+        ),
+        VimCommand::new(("lat", "er"), editor::actions::UndoLater::default()).args(|_, args| {
+            let (count, seconds) = parse_undo_tree_steps(&args)?;
+            Some(editor::actions::UndoLater { count, seconds }.boxed_clone())
+        }),
+        VimCommand::str(("undol", "ist"), "undo_tree_view::Toggle"),
         VimCommand::new(("delm", "arks"), ArgumentRequired)
             .bang(DeleteMarks::AllLocal)
             .args(|_, args| Some(DeleteMarks::Marks(args).boxed_clone())),
@@ -1283,9 +1299,98 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         VimCommand::new(("ex", ""), editor::actions::ReloadFile).bang(editor::actions::ReloadFile),
         VimCommand::new(("cpp", "link"), editor::actions::CopyPermalinkToLine).range(act_on_range),
         VimCommand::str(("opt", "ions"), "zed::OpenDefaultSettings"),
-        VimCommand::str(("map", ""), "vim::OpenDefaultKeymap"),
         VimCommand::new(("h", "elp"), OpenDocs),
+        // Helix's typable commands, where they differ from vim's.
+        VimCommand::new(
+            ("write-q", "uit"),
+            workspace::CloseActiveItem {
+                save_intent: Some(SaveIntent::Save),
+                close_pinned: false,
+            },
+        )
+        .bang(workspace::CloseActiveItem {
+            save_intent: Some(SaveIntent::Overwrite),
+            close_pinned: true,
+        }),
+        VimCommand::new(
+            ("write-a", "ll"),
+            workspace::SaveAll {
+                save_intent: Some(SaveIntent::SaveAll),
+            },
+        )
+        .bang(workspace::SaveAll {
+            save_intent: Some(SaveIntent::Overwrite),
+        }),
+        VimCommand::new(
+            ("quit-a", "ll"),
+            workspace::CloseAllItemsAndPanes {
+                save_intent: Some(SaveIntent::Close),
+            },
+        )
+        .bang(workspace::CloseAllItemsAndPanes {
+            save_intent: Some(SaveIntent::Skip),
+        }),
+        VimCommand::new(
+            ("write-quit-a", "ll"),
+            workspace::CloseAllItemsAndPanes {
+                save_intent: Some(SaveIntent::SaveAll),
+            },
+        )
+        .bang(workspace::CloseAllItemsAndPanes {
+            save_intent: Some(SaveIntent::Overwrite),
+        }),
+        VimCommand::new(
+            ("buffer-c", "lose"),
+            workspace::CloseActiveItem {
+                save_intent: Some(SaveIntent::Close),
+                close_pinned: false,
+            },
+        )
+        .bang(workspace::CloseActiveItem {
+            save_intent: Some(SaveIntent::Skip),
+            close_pinned: true,
+        }),
+        VimCommand::new(
+            ("bc", "lose"),
+            workspace::CloseActiveItem {
+                save_intent: Some(SaveIntent::Close),
+                close_pinned: false,
+            },
+        )
+        .bang(workspace::CloseActiveItem {
+            save_intent: Some(SaveIntent::Skip),
+            close_pinned: true,
+        }),
+        VimCommand::new(("buffer-n", "ext"), workspace::ActivateNextItem).count(),
+        VimCommand::new(("buffer-p", "revious"), workspace::ActivatePreviousItem).count(),
+        VimCommand::new(("o", "pen"), ArgumentRequired)
+            .args(|_, args| Some(VimEdit { filename: args }.boxed_clone())),
+        VimCommand::new(("rel", "oad"), editor::actions::ReloadFile),
+        VimCommand::new(("rl", ""), editor::actions::ReloadFile),
+        VimCommand::new(("hs", "plit"), workspace::SplitHorizontal).args(|_, args| {
+            Some(
+                VimSplit {
+                    vertical: false,
+                    filename: args,
+                }
+                .boxed_clone(),
+            )
+        }),
+        VimCommand::new(("format", ""), editor::actions::Format),
+        VimCommand::new(("fmt", ""), editor::actions::Format),
+        VimCommand::new(("reflow", ""), Rewrap).range(act_on_range),
+        VimCommand::new(("lsp-r", "estart"), editor::actions::RestartLanguageServer),
+        VimCommand::str(("lang", ""), "language_selector::Toggle"),
+        VimCommand::str(("set-l", "anguage"), "language_selector::Toggle"),
+        VimCommand::str(("config-o", "pen"), "zed::OpenSettings"),
+        VimCommand::str(("log-o", "pen"), "zed::OpenLog"),
     ]
+    .into_iter()
+    .chain(MAP_COMMANDS.iter().map(|&(prefix, suffix, modes, remap)| {
+        VimCommand::str((prefix, suffix), "vim::OpenDefaultKeymap")
+            .args(move |_, args| Some(DefineMapping::new(modes, remap, args).boxed_clone()))
+    }))
+    .collect()
 }
 
 struct VimCommands(Vec<VimCommand>);
@@ -1301,6 +1406,25 @@ fn commands(cx: &App) -> &Vec<VimCommand> {
         .0
 }
 
+/// Parses the argument of `:earlier` and `:later`, which is either a number of states or a
+/// number of seconds, minutes, hours or days when followed by `s`, `m`, `h` or `d`.
+fn parse_undo_tree_steps(args: &str) -> Option<(Option<usize>, Option<u64>)> {
+    let (number, unit) = args.split_at(
+        args.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(args.len()),
+    );
+    let number = number.parse::<u64>().ok()?;
+    let seconds = match unit {
+        "" => return Some((Some(number as usize), None)),
+        "s" => number,
+        "m" => number.saturating_mul(60),
+        "h" => number.saturating_mul(60 * 60),
+        "d" => number.saturating_mul(24 * 60 * 60),
+        _ => return None,
+    };
+    Some((None, Some(seconds)))
+}
+
 fn act_on_range(action: Box<dyn Action>, range: &CommandRange) -> Option<Box<dyn Action>> {
     Some(
         WithRange {
@@ -1388,6 +1512,9 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
         } else {
             None
         }
+    } else if is_map_command(query) {
+        // Leave mappings to the commands below, as their keys may look like other commands.
+        None
     } else if query.starts_with('g') || query.starts_with('v') {
         let mut global = "global".chars().peekable();
         let mut query = query.chars().peekable();
@@ -1865,6 +1992,8 @@ impl ShellExec {
                     show_summary: false,
                     show_command: false,
                     show_rerun: false,
+                    problem_matchers: Vec::new(),
+                    background: None,
                 };
 
                 let task_status = workspace.spawn_in_terminal(spawn_in_terminal, window, cx);
@@ -2578,4 +2707,72 @@ mod test {
             assert_active_item(workspace, path!("/root/dir/file_3.rs"), "", cx);
         });
     }
+
+    #[gpui::test]
+    async fn test_earlier_and_later(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone", Mode::Normal);
+        cx.simulate_keystrokes("A space t w o escape");
+        cx.simulate_keystrokes("A space t h r e e escape");
+        cx.simulate_keystrokes("u");
+        cx.simulate_keystrokes("A space f o u r escape");
+        assert_eq!(cx.buffer_text(), "one two four");
+
+        // The undone change is still reachable by going back in time.
+        cx.simulate_keystrokes("g -");
+        assert_eq!(cx.buffer_text(), "one two three");
+        cx.simulate_keystrokes("g -");
+        assert_eq!(cx.buffer_text(), "one two");
+        cx.simulate_keystrokes("2 g +");
+        assert_eq!(cx.buffer_text(), "one two four");
+
+        cx.simulate_keystrokes(": e a r l i e r space 2 enter");
+        assert_eq!(cx.buffer_text(), "one two");
+        cx.simulate_keystrokes(": l a t e r enter");
+        assert_eq!(cx.buffer_text(), "one two three");
+
+        cx.simulate_keystrokes(": e a r l i e r space 1 0 m enter");
+        assert_eq!(cx.buffer_text(), "");
+        cx.simulate_keystrokes(": l a t e r space 1 h enter");
+        assert_eq!(cx.buffer_text(), "one two four");
+    }
+
+    #[gpui::test]
+    async fn test_helix_typable_commands(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(path!("/root/dir/file_2.rs"), "file_2".as_bytes().to_vec())
+            .await;
+
+        cx.simulate_keystrokes(": o space dir/file_2.rs enter");
+        cx.workspace(|workspace, _, cx| {
+            assert_eq!(workspace.items(cx).count(), 2);
+            assert_active_item(workspace, path!("/root/dir/file_2.rs"), "file_2", cx);
+        });
+
+        let active_item_index = |cx: &mut VimTestContext| {
+            cx.workspace(|workspace, _, cx| workspace.active_pane().read(cx).active_item_index())
+        };
+        cx.simulate_keystrokes(": b u f f e r - p r e v i o u s enter");
+        assert_eq!(active_item_index(&mut cx), 0);
+        cx.simulate_keystrokes(": b u f f e r - n e x t enter");
+        assert_eq!(active_item_index(&mut cx), 1);
+
+        cx.simulate_keystrokes(": b c enter");
+        cx.workspace(|workspace, _, cx| assert_eq!(workspace.items(cx).count(), 1));
+
+        cx.simulate_keystrokes(": h s p l i t enter");
+        cx.workspace(|workspace, _, _| assert_eq!(workspace.panes().len(), 2));
+
+        let path = Path::new(path!("/root/dir/file.rs"));
+        cx.simulate_keystrokes("i @ escape");
+        cx.simulate_keystrokes(": w r i t e - a l l enter");
+        assert_eq!(fs.load(path).await.unwrap().replace("\r\n", "\n"), "@\n");
+
+        cx.simulate_keystrokes(": q u i t - a l l enter");
+        cx.workspace(|workspace, _, cx| assert_eq!(workspace.items(cx).count(), 0));
+    }
 }
@@ -11,9 +11,14 @@ use crate::motion;
 use crate::{
     Vim,
     motion::{Motion, right},
-    state::Mode,
+    object::Object,
+    state::{Mode, Operator},
 };
 
+mod select;
+
+pub use select::HelixRegexSelection;
+
 actions!(
     vim,
     [
@@ -29,6 +34,12 @@ actions!(
         HelixGotoLastModification,
         /// Select entire line or multiple lines, extending downwards.
         HelixSelectLine,
+        /// Keeps only the primary selection.
+        HelixKeepPrimarySelection,
+        /// Removes the primary selection.
+        HelixRemovePrimarySelection,
+        /// Trims whitespace from both ends of the selections.
+        HelixTrimSelections,
     ]
 );
 
@@ -39,6 +50,10 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     Vim::action(editor, cx, Vim::helix_append);
     Vim::action(editor, cx, Vim::helix_yank);
     Vim::action(editor, cx, Vim::helix_goto_last_modification);
+    Vim::action(editor, cx, Vim::helix_keep_primary_selection);
+    Vim::action(editor, cx, Vim::helix_remove_primary_selection);
+    Vim::action(editor, cx, Vim::helix_trim_selections);
+    select::register(editor, cx);
 }
 
 impl Vim {
@@ -439,6 +454,159 @@ impl Vim {
         self.switch_mode(Mode::HelixNormal, true, window, cx);
     }
 
+    /// Extends empty selections over the character under the cursor, which Helix considers
+    /// selected.
+    pub(crate) fn helix_expand_cursors(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    if selection.is_empty() {
+                        selection.end = movement::saturating_right(map, selection.end)
+                    }
+                })
+            })
+        });
+    }
+
+    pub(crate) fn helix_object(
+        &mut self,
+        object: Object,
+        times: Option<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut waiting_operator = None;
+        match self.maybe_pop_operator() {
+            Some(Operator::Object { around }) => {
+                self.helix_select_object(object, around, times, window, cx)
+            }
+            Some(Operator::ChangeSurrounds { target: None }) => {
+                if self.check_and_move_to_valid_bracket_pair(object, window, cx) {
+                    waiting_operator = Some(Operator::ChangeSurrounds {
+                        target: Some(object),
+                    });
+                }
+            }
+            _ => {}
+        }
+        self.clear_operator(window, cx);
+        if let Some(operator) = waiting_operator {
+            self.push_operator(operator, window, cx);
+        }
+    }
+
+    fn helix_select_object(
+        &mut self,
+        object: Object,
+        around: bool,
+        times: Option<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.move_with(|map, selection| {
+                    // Objects are found from the character under the cursor, which is the last
+                    // character of a forward selection.
+                    let mut cursor_selection = selection.clone();
+                    if !selection.reversed && !selection.is_empty() && object != Object::Tag {
+                        cursor_selection
+                            .set_head(movement::left(map, selection.head()), selection.goal);
+                    }
+                    let Some(mut range) = object.range(map, cursor_selection, around, times) else {
+                        return;
+                    };
+                    // Selecting the same object again selects the one around it.
+                    if range == selection.range()
+                        && object.always_expands_both_ways()
+                        && let Some(outer_range) =
+                            object.range(map, selection.clone(), around, times)
+                    {
+                        range = outer_range;
+                    }
+                    if !range.is_empty() {
+                        selection.start = range.start;
+                        selection.end = range.end;
+                        selection.reversed = false;
+                        selection.goal = SelectionGoal::None;
+                    }
+                });
+            });
+        });
+    }
+
+    fn helix_keep_primary_selection(
+        &mut self,
+        _: &HelixKeepPrimarySelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Default::default(), window, cx, |s| {
+                let primary = s.newest_anchor().clone();
+                s.select_anchors(vec![primary]);
+            });
+        });
+    }
+
+    fn helix_remove_primary_selection(
+        &mut self,
+        _: &HelixRemovePrimarySelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Default::default(), window, cx, |s| {
+                let primary_id = s.newest_anchor().id;
+                let selections = s
+                    .disjoint_anchors()
+                    .iter()
+                    .filter(|selection| selection.id != primary_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !selections.is_empty() {
+                    s.select_anchors(selections);
+                }
+            });
+        });
+    }
+
+    fn helix_trim_selections(
+        &mut self,
+        _: &HelixTrimSelections,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let selections = editor.selections.all::<usize>(cx);
+            let mut ranges = Vec::new();
+            for selection in &selections {
+                let text = snapshot
+                    .text_for_range(selection.range())
+                    .collect::<String>();
+                let trimmed_start = text.trim_start();
+                // Like Helix, selections of only whitespace are dropped.
+                if trimmed_start.is_empty() && !text.is_empty() {
+                    continue;
+                }
+                let start = selection.start + text.len() - trimmed_start.len();
+                let end = start + trimmed_start.trim_end().len();
+                ranges.push(if selection.reversed {
+                    end..start
+                } else {
+                    start..end
+                });
+            }
+            if ranges.is_empty() {
+                return;
+            }
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.select_ranges(ranges);
+            });
+        });
+    }
+
     pub fn helix_goto_last_modification(
         &mut self,
         _: &HelixGotoLastModification,
@@ -1034,4 +1202,188 @@ mod test {
             Mode::HelixNormal,
         );
     }
+
+    #[gpui::test]
+    async fn test_select_text_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("hello wˇorld foo", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i w");
+        cx.assert_state("hello «worldˇ» foo", Mode::HelixNormal);
+
+        cx.set_state("hello wˇorld foo", Mode::HelixNormal);
+        cx.simulate_keystrokes("m a w");
+        cx.assert_state("hello «world ˇ»foo", Mode::HelixNormal);
+
+        cx.set_state("call(aˇrg)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i (");
+        cx.assert_state("call(«argˇ»)", Mode::HelixNormal);
+
+        cx.set_state("call(aˇrg)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("call«(arg)ˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_surround(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        // Surrounds never add padding, whichever side of the pair is typed.
+        cx.set_state("hello «worldˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s (");
+        cx.assert_state("hello «(world)ˇ»", Mode::HelixNormal);
+
+        // A cursor surrounds the character under it.
+        cx.set_state("hello ˇworld", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s ]");
+        cx.assert_state("hello «[w]ˇ»orld", Mode::HelixNormal);
+
+        cx.set_state("hello (woˇrld)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m d (");
+        cx.assert_state("hello ˇworld", Mode::HelixNormal);
+
+        cx.set_state("hello (woˇrld)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m r ( [");
+        cx.assert_state("hello ˇ[world]", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_primary_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("«aˇ» «bˇ» «cˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes(",");
+        cx.assert_state("a b «cˇ»", Mode::HelixNormal);
+
+        cx.set_state("«aˇ» «bˇ» «cˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-,");
+        cx.assert_state("«aˇ» «bˇ» c", Mode::HelixNormal);
+
+        // The last selection is never removed.
+        cx.simulate_keystrokes("alt-, alt-,");
+        cx.assert_state("«aˇ» b c", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_trim_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("«  foo  ˇ»bar«  baz\nˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("_");
+        cx.assert_state("  «fooˇ»  bar  «bazˇ»\n", Mode::HelixNormal);
+
+        // Selections of only whitespace are dropped.
+        cx.set_state("«fooˇ» «   ˇ»bar", Mode::HelixNormal);
+        cx.simulate_keystrokes("_");
+        cx.assert_state("«fooˇ»    bar", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_copy_selection_on_next_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("«abˇ»cd\nefgh", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-c");
+        cx.assert_state("«abˇ»cd\n«efˇ»gh", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_collapse_and_flip_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        // Selections collapse onto the character under their cursor.
+        cx.set_state("«aˇ»b «cdˇ» «ˇef»", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("ˇab cˇd ˇef", Mode::HelixNormal);
+
+        cx.set_state("«helloˇ» world", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-;");
+        cx.assert_state("«ˇhello» world", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-;");
+        cx.assert_state("«helloˇ» world", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("alt-; ;");
+        cx.assert_state("ˇhello world", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_syntax_node_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state(
+            indoc! {"
+            fn main() {
+                let x = «fooˇ»(1, 2);
+            }"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("alt-o");
+        cx.assert_state(
+            indoc! {"
+            fn main() {
+                let x = «foo(1, 2)ˇ»;
+            }"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("alt-o");
+        cx.assert_state(
+            indoc! {"
+            fn main() {
+                «let x = foo(1, 2);ˇ»
+            }"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("alt-i");
+        cx.assert_state(
+            indoc! {"
+            fn main() {
+                let x = «foo(1, 2)ˇ»;
+            }"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("alt-i");
+        cx.assert_state(
+            indoc! {"
+            fn main() {
+                let x = «fooˇ»(1, 2);
+            }"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_space_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("«oneˇ» two one", Mode::HelixNormal);
+        cx.simulate_keystrokes("space h");
+        cx.assert_state("«oneˇ» two «oneˇ»", Mode::HelixNormal);
+
+        cx.set_state("«oneˇ» two", Mode::HelixNormal);
+        cx.simulate_keystrokes("space y");
+        assert_eq!(
+            cx.read_from_clipboard()
+                .and_then(|item| item.text())
+                .as_deref(),
+            Some("one")
+        );
+        cx.set_state("one «twoˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("space p");
+        assert_eq!(cx.buffer_text(), "one one");
+
+        cx.set_state("«let x = 1;ˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("space c");
+        assert_eq!(cx.buffer_text(), "// let x = 1;");
+        cx.simulate_keystrokes("space c");
+        assert_eq!(cx.buffer_text(), "let x = 1;");
+    }
 }
@@ -0,0 +1,273 @@
+use std::ops::Range;
+
+use anyhow::Context as _;
+use editor::{Editor, MultiBufferSnapshot, ToOffset};
+use gpui::{Context, Window, actions};
+use regex::{Regex, RegexBuilder};
+use search::{BufferSearchBar, SearchOptions, buffer_search};
+use workspace::notifications::NotifyResultExt;
+
+use crate::{Vim, state::SearchState};
+
+actions!(
+    vim,
+    [
+        /// Prompts for a regex and selects its matches within the selections.
+        HelixSelectRegex,
+        /// Prompts for a regex and splits the selections on its matches.
+        HelixSplitSelection,
+        /// Prompts for a regex and keeps only the selections that match it.
+        HelixKeepSelections,
+        /// Prompts for a regex and removes the selections that match it.
+        HelixRemoveSelections,
+    ]
+);
+
+/// What to do with the selections once the regex prompt of a Helix selection command is submitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HelixRegexSelection {
+    Select,
+    Split,
+    Keep,
+    Remove,
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, window, cx| {
+        vim.helix_regex_prompt(HelixRegexSelection::Select, window, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelection, window, cx| {
+        vim.helix_regex_prompt(HelixRegexSelection::Split, window, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixKeepSelections, window, cx| {
+        vim.helix_regex_prompt(HelixRegexSelection::Keep, window, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixRemoveSelections, window, cx| {
+        vim.helix_regex_prompt(HelixRegexSelection::Remove, window, cx)
+    });
+}
+
+impl Vim {
+    /// Opens the buffer search bar to read the regex of a Helix selection command, which is
+    /// applied by [`Vim::helix_regex_submit`].
+    fn helix_regex_prompt(
+        &mut self,
+        kind: HelixRegexSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        let prior_selections = self.editor_selections(window, cx);
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    if !search_bar.show(window, cx) {
+                        return;
+                    }
+
+                    search_bar.select_query(window, cx);
+                    cx.focus_self(window);
+
+                    search_bar.set_replacement(None, cx);
+                    search_bar.set_search_options(SearchOptions::REGEX, cx);
+                    self.search = SearchState {
+                        prior_selections,
+                        prior_mode: self.mode,
+                        helix_regex: Some(kind),
+                        ..Default::default()
+                    };
+                });
+            }
+        })
+    }
+
+    pub(crate) fn helix_regex_submit(
+        &mut self,
+        kind: HelixRegexSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(pane) = self.pane(window, cx) else {
+            return;
+        };
+        let query = pane.update(cx, |pane, cx| {
+            let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+            search_bar.update(cx, |search_bar, cx| {
+                let query = search_bar.query(cx);
+                search_bar.dismiss(&buffer_search::Dismiss, window, cx);
+                Some(query)
+            })
+        });
+        let prior_selections = std::mem::take(&mut self.search.prior_selections);
+        let prior_mode = self.search.prior_mode;
+        if prior_mode != self.mode {
+            self.switch_mode(prior_mode, true, window, cx);
+        }
+        let Some(query) = query.filter(|query| !query.is_empty()) else {
+            self.update_editor(cx, |_, editor, cx| {
+                editor.change_selections(Default::default(), window, cx, |s| {
+                    s.select_anchor_ranges(prior_selections);
+                });
+            });
+            return;
+        };
+
+        // Like Helix, the regex is case-insensitive unless it contains an uppercase letter.
+        let regex = RegexBuilder::new(&query)
+            .case_insensitive(!query.chars().any(char::is_uppercase))
+            .multi_line(true)
+            .build()
+            .with_context(|| format!("Invalid regex: {query}"));
+        let regex = match regex {
+            Ok(regex) => regex,
+            Err(error) => {
+                if let Some(workspace) = self.workspace(window) {
+                    workspace.update(cx, |workspace, cx| {
+                        Err::<(), _>(error).notify_err(workspace, cx);
+                    });
+                }
+                return;
+            }
+        };
+
+        self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let prior_ranges = prior_selections
+                .iter()
+                .map(|range| range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot))
+                .collect::<Vec<_>>();
+            let mut ranges = helix_regex_ranges(kind, &regex, &prior_ranges, &snapshot);
+            // Helix keeps the selections as they were when nothing matches.
+            if ranges.is_empty() {
+                ranges = prior_ranges;
+            }
+            editor.change_selections(Default::default(), window, cx, |s| {
+                s.select_ranges(ranges);
+            });
+        });
+    }
+}
+
+/// Returns the selections that result from applying `regex` to the given selections, whose
+/// ranges may be reversed. Empty selections cover the character under the cursor, like Helix's.
+fn helix_regex_ranges(
+    kind: HelixRegexSelection,
+    regex: &Regex,
+    selections: &[Range<usize>],
+    snapshot: &MultiBufferSnapshot,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    for selection in selections {
+        let start = selection.start.min(selection.end);
+        let mut end = selection.start.max(selection.end);
+        if start == end {
+            end += snapshot.chars_at(start).next().map_or(0, char::len_utf8);
+        }
+        let text = snapshot.text_for_range(start..end).collect::<String>();
+        match kind {
+            HelixRegexSelection::Select => ranges.extend(
+                regex
+                    .find_iter(&text)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| start + found.start()..start + found.end()),
+            ),
+            HelixRegexSelection::Split => {
+                let mut segment_start = start;
+                for found in regex.find_iter(&text) {
+                    ranges.push(segment_start..start + found.start());
+                    segment_start = start + found.end();
+                }
+                if segment_start < end {
+                    ranges.push(segment_start..end);
+                }
+            }
+            HelixRegexSelection::Keep | HelixRegexSelection::Remove => {
+                if regex.is_match(&text) == (kind == HelixRegexSelection::Keep) {
+                    ranges.push(selection.clone());
+                }
+            }
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_select_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("«one two one threeˇ» one", Mode::HelixNormal);
+        cx.simulate_keystrokes("s o n e enter");
+        cx.assert_state("«oneˇ» two «oneˇ» three one", Mode::HelixNormal);
+
+        // Uppercase letters make the regex case-sensitive.
+        cx.set_state("«One one ONEˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("s o n e enter");
+        cx.assert_state("«Oneˇ» «oneˇ» «ONEˇ»", Mode::HelixNormal);
+        cx.set_state("«One one ONEˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("s shift-o n e enter");
+        cx.assert_state("«Oneˇ» one ONE", Mode::HelixNormal);
+
+        // Without a match the selections are left alone.
+        cx.set_state("«one twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("s t h r e e enter");
+        cx.assert_state("«one twoˇ» three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_split_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("«a, b, cˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s , space enter");
+        cx.assert_state("«aˇ», «bˇ», «cˇ»", Mode::HelixNormal);
+
+        cx.set_state(
+            indoc! {"
+            «one
+            two
+            threeˇ»
+            "},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("shift-s \\ n enter");
+        cx.assert_state(
+            indoc! {"
+            «oneˇ»
+            «twoˇ»
+            «threeˇ»
+            "},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_keep_and_remove_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+
+        cx.set_state("«fooˇ» «barˇ» «bazˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-k b a enter");
+        cx.assert_state("foo «barˇ» «bazˇ»", Mode::HelixNormal);
+
+        cx.set_state("«fooˇ» «barˇ» «bazˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-shift-k b a enter");
+        cx.assert_state("«fooˇ» bar baz", Mode::HelixNormal);
+
+        // Removing every selection leaves them as they were.
+        cx.set_state("«fooˇ» «barˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-shift-k . enter");
+        cx.assert_state("«fooˇ» «barˇ»", Mode::HelixNormal);
+    }
+}
@@ -0,0 +1,590 @@
+use anyhow::{Context as _, Result, anyhow, bail};
+use editor::Editor;
+use gpui::{
+    Action, App, Context, Global, KeyBinding, KeyBindingContextPredicate, Keystroke, NoAction,
+};
+use settings::KeybindSource;
+use util::ResultExt;
+use workspace::notifications::NotifyResultExt;
+
+use crate::{Vim, command::VimOption};
+
+/// The leader used by `<leader>` until a vimrc sets `mapleader`.
+const DEFAULT_LEADER: &str = "\\";
+
+/// Sends the keystrokes of a user-defined mapping.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub struct MappedKeystrokes {
+    keystrokes: String,
+    /// Whether other user-defined mappings apply to the keystrokes, as with `:map` rather than
+    /// `:noremap`.
+    remap: bool,
+}
+
+/// Defines a mapping from the arguments of a `:map` command.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct DefineMapping {
+    modes: MapModes,
+    remap: bool,
+    args: String,
+}
+
+/// The vim modes a mapping applies in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MapModes {
+    /// Normal, visual and operator-pending modes, as with `:map`.
+    NormalVisualOperator,
+    Normal,
+    Visual,
+    OperatorPending,
+    Insert,
+}
+
+impl MapModes {
+    fn context(self) -> &'static str {
+        match self {
+            MapModes::NormalVisualOperator => {
+                "(vim_mode == normal || vim_mode == visual || vim_mode == operator) && !menu"
+            }
+            MapModes::Normal => "vim_mode == normal && !menu",
+            MapModes::Visual => "vim_mode == visual && !menu",
+            MapModes::OperatorPending => "vim_mode == operator",
+            MapModes::Insert => "vim_mode == insert",
+        }
+    }
+}
+
+/// The `:map` family of commands, as the required prefix and optional suffix of their name,
+/// the modes they map in and whether their mappings are recursive.
+pub(crate) const MAP_COMMANDS: &[(&str, &str, MapModes, bool)] = &[
+    ("map", "", MapModes::NormalVisualOperator, true),
+    ("no", "remap", MapModes::NormalVisualOperator, false),
+    ("nm", "ap", MapModes::Normal, true),
+    ("nn", "oremap", MapModes::Normal, false),
+    ("vm", "ap", MapModes::Visual, true),
+    ("vn", "oremap", MapModes::Visual, false),
+    ("xm", "ap", MapModes::Visual, true),
+    ("xn", "oremap", MapModes::Visual, false),
+    ("om", "ap", MapModes::OperatorPending, true),
+    ("ono", "remap", MapModes::OperatorPending, false),
+    ("im", "ap", MapModes::Insert, true),
+    ("ino", "remap", MapModes::Insert, false),
+];
+
+impl DefineMapping {
+    pub(crate) fn new(modes: MapModes, remap: bool, args: String) -> Self {
+        Self { modes, remap, args }
+    }
+}
+
+/// A mapping from keystrokes to other keystrokes, in Zed's keystroke syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct VimMapping {
+    modes: MapModes,
+    lhs: String,
+    /// The keystrokes to send, or `None` for a `<Nop>` mapping that disables `lhs`.
+    rhs: Option<String>,
+    remap: bool,
+}
+
+impl VimMapping {
+    /// Parses the arguments of a `:map` command, such as `<silent> <leader>f :Files<CR>`.
+    pub(crate) fn parse(modes: MapModes, remap: bool, args: &str, leader: &str) -> Result<Self> {
+        let mut args = args.trim_start();
+        loop {
+            let Some(rest) = args.strip_prefix('<') else {
+                break;
+            };
+            let Some((argument, rest)) = rest.split_once('>') else {
+                break;
+            };
+            match argument.to_ascii_lowercase().as_str() {
+                "silent" | "nowait" | "unique" | "special" | "script" => {}
+                "buffer" => bail!("buffer-local mappings are not supported"),
+                "expr" => bail!("expression mappings are not supported"),
+                _ => break,
+            }
+            args = rest.trim_start();
+        }
+
+        let (lhs, rhs) = args
+            .split_once(char::is_whitespace)
+            .map(|(lhs, rhs)| (lhs, rhs.trim()))
+            .unwrap_or((args, ""));
+        if lhs.is_empty() || rhs.is_empty() {
+            bail!("listing mappings is not supported");
+        }
+
+        Ok(Self {
+            modes,
+            lhs: keystrokes(lhs, leader)?,
+            rhs: if rhs.eq_ignore_ascii_case("<nop>") {
+                None
+            } else {
+                Some(keystrokes(rhs, leader)?)
+            },
+            remap,
+        })
+    }
+
+    fn key_binding(&self, cx: &App) -> Result<KeyBinding> {
+        let action = match &self.rhs {
+            Some(keystrokes) => MappedKeystrokes {
+                keystrokes: keystrokes.clone(),
+                remap: self.remap,
+            }
+            .boxed_clone(),
+            None => NoAction.boxed_clone(),
+        };
+        let predicate = KeyBindingContextPredicate::parse(self.modes.context())?;
+        let key_binding = KeyBinding::load(
+            &self.lhs,
+            action,
+            Some(predicate.into()),
+            false,
+            None,
+            cx.keyboard_mapper().as_ref(),
+        )?;
+        Ok(key_binding.with_meta(KeybindSource::User.meta()))
+    }
+}
+
+/// Converts vim key notation, such as `<C-w>v` or `<leader>gd`, into space-separated Zed
+/// keystrokes.
+fn keystrokes(notation: &str, leader: &str) -> Result<String> {
+    let mut keystrokes = Vec::new();
+    let mut rest = notation;
+    while let Some(c) = rest.chars().next() {
+        if c == '<'
+            && let Some(end) = rest.find('>')
+        {
+            let name = &rest[1..end];
+            if name.eq_ignore_ascii_case("leader") || name.eq_ignore_ascii_case("localleader") {
+                keystrokes.extend(leader.chars().map(char_keystroke));
+                rest = &rest[end + 1..];
+                continue;
+            }
+            if let Some(keystroke) = special_keystroke(name)? {
+                keystrokes.push(keystroke);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        keystrokes.push(char_keystroke(c));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    for keystroke in &keystrokes {
+        Keystroke::parse(keystroke)?;
+    }
+    Ok(keystrokes.join(" "))
+}
+
+/// Converts the name inside `<...>` into a Zed keystroke, or returns `None` when it isn't key
+/// notation, in which case the brackets are taken literally.
+fn special_keystroke(name: &str) -> Result<Option<String>> {
+    let (modifiers, key) = match name.rsplit_once('-') {
+        // `<C-->` maps the `-` key.
+        Some((modifiers, "")) => (modifiers.strip_suffix('-').unwrap_or(modifiers), "-"),
+        Some((modifiers, key)) => (modifiers, key),
+        None => ("", name),
+    };
+
+    let mut prefix = String::new();
+    for modifier in modifiers.split('-').filter(|modifier| !modifier.is_empty()) {
+        prefix.push_str(match modifier.to_ascii_lowercase().as_str() {
+            "c" => "ctrl-",
+            "s" => "shift-",
+            "a" | "m" => "alt-",
+            "d" => "cmd-",
+            _ => return Ok(None),
+        });
+    }
+
+    let key = match key.to_ascii_lowercase().as_str() {
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "esc" => "escape".to_string(),
+        "space" => "space".to_string(),
+        "tab" => "tab".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "insert" => "insert".to_string(),
+        "up" | "down" | "left" | "right" | "home" | "end" | "pageup" | "pagedown" => {
+            key.to_ascii_lowercase()
+        }
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        "nop" => bail!("<Nop> can only be used as the whole right-hand side of a mapping"),
+        function_key
+            if function_key.len() > 1
+                && function_key.starts_with('f')
+                && function_key[1..].parse::<u8>().is_ok() =>
+        {
+            function_key.to_string()
+        }
+        _ if !prefix.is_empty() && key.chars().count() == 1 => {
+            let c = key.chars().next().unwrap_or_default();
+            // Vim doesn't distinguish the case of letters with the control modifier.
+            if prefix.contains("ctrl-") && !prefix.contains("shift-") {
+                c.to_ascii_lowercase().to_string()
+            } else if c.is_ascii_uppercase() && !prefix.contains("shift-") {
+                prefix.push_str("shift-");
+                c.to_ascii_lowercase().to_string()
+            } else {
+                c.to_string()
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(prefix + &key))
+}
+
+fn char_keystroke(c: char) -> String {
+    match c {
+        ' ' => "space".to_string(),
+        '\t' => "tab".to_string(),
+        c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+        c => c.to_string(),
+    }
+}
+
+/// User configuration from the vimrc file, and the mappings of `:map` commands run since.
+#[derive(Default)]
+pub struct VimUserConfig {
+    leader: String,
+    options: Vec<VimOption>,
+    vimrc_mappings: Vec<VimMapping>,
+    command_mappings: Vec<VimMapping>,
+}
+
+impl Global for VimUserConfig {}
+
+impl VimUserConfig {
+    fn leader(cx: &App) -> &str {
+        cx.try_global::<Self>()
+            .map(|config| config.leader.as_str())
+            .filter(|leader| !leader.is_empty())
+            .unwrap_or(DEFAULT_LEADER)
+    }
+
+    /// The options set by the vimrc file, applied to each editor that vim mode activates in.
+    pub(crate) fn options(cx: &App) -> Vec<VimOption> {
+        cx.try_global::<Self>()
+            .map(|config| config.options.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Returns key bindings for the user-defined mappings, to be loaded after the default vim
+/// key bindings.
+pub fn user_key_bindings(cx: &App) -> Vec<KeyBinding> {
+    let Some(config) = cx.try_global::<VimUserConfig>() else {
+        return Vec::new();
+    };
+    config
+        .vimrc_mappings
+        .iter()
+        .chain(&config.command_mappings)
+        .filter_map(|mapping| mapping.key_binding(cx).log_err())
+        .collect()
+}
+
+/// Sources the contents of the vimrc file, replacing the mappings and options of any previously
+/// sourced contents. Supports mappings, `:set` and `let mapleader`; other lines are logged as
+/// unsupported, and their count is returned.
+pub fn source_vimrc(content: &str, cx: &mut App) -> usize {
+    let mut leader = DEFAULT_LEADER.to_string();
+    let mut mappings = Vec::new();
+    let mut options = Vec::new();
+    let mut unsupported_lines = 0;
+    for (row, line) in content.lines().enumerate() {
+        let line = line.trim().trim_start_matches(':').trim_start();
+        if line.is_empty() || line.starts_with('"') {
+            continue;
+        }
+        let result = maybe_source_line(line, &leader).and_then(|sourced| match sourced {
+            SourcedLine::Leader(new_leader) => {
+                leader = new_leader;
+                Ok(())
+            }
+            SourcedLine::Mapping(mapping) => {
+                mappings.push(mapping);
+                Ok(())
+            }
+            SourcedLine::Options(new_options) => {
+                options.extend(new_options);
+                Ok(())
+            }
+        });
+        if let Err(error) = result {
+            log::warn!("vimrc line {}: {error}: {line}", row + 1);
+            unsupported_lines += 1;
+        }
+    }
+
+    cx.update_default_global::<VimUserConfig, _>(|config, _| {
+        config.leader = leader;
+        config.options = options;
+        config.vimrc_mappings = mappings;
+    });
+    unsupported_lines
+}
+
+enum SourcedLine {
+    Leader(String),
+    Mapping(VimMapping),
+    Options(Vec<VimOption>),
+}
+
+fn maybe_source_line(line: &str, leader: &str) -> Result<SourcedLine> {
+    let (command, args) = line
+        .split_once(char::is_whitespace)
+        .map(|(command, args)| (command, args.trim()))
+        .unwrap_or((line, ""));
+
+    if command == "let" {
+        let (name, value) = args.split_once('=').context("unsupported command")?;
+        let name = name.trim();
+        if name != "mapleader" && name != "g:mapleader" {
+            bail!("only `mapleader` can be set with `let`");
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            })
+            .context("expected a quoted string")?;
+        let leader = match value {
+            "\\<Space>" | "\\<space>" => " ".to_string(),
+            "\\\\" => "\\".to_string(),
+            value if !value.is_empty() => value.to_string(),
+            _ => bail!("the leader can't be empty"),
+        };
+        return Ok(SourcedLine::Leader(leader));
+    }
+
+    if command == "se" || command == "set" {
+        let options = args
+            .split_whitespace()
+            .map(|option| {
+                VimOption::from(option).ok_or_else(|| anyhow!("unsupported option `{option}`"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(SourcedLine::Options(options));
+    }
+
+    if let Some((modes, remap)) = map_command(command) {
+        return VimMapping::parse(modes, remap, args, leader).map(SourcedLine::Mapping);
+    }
+
+    Err(anyhow!("unsupported command"))
+}
+
+/// Returns the modes and recursiveness of the `:map` family command with the given name.
+fn map_command(command: &str) -> Option<(MapModes, bool)> {
+    MAP_COMMANDS
+        .iter()
+        .find(|(prefix, suffix, _, _)| {
+            command
+                .strip_prefix(prefix)
+                .is_some_and(|rest| suffix.starts_with(rest))
+        })
+        .map(|&(_, _, modes, remap)| (modes, remap))
+}
+
+/// Returns whether `query` runs one of the `:map` family of commands.
+pub(crate) fn is_map_command(query: &str) -> bool {
+    query
+        .split_whitespace()
+        .next()
+        .is_some_and(|command| map_command(command).is_some())
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, action: &MappedKeystrokes, window, cx| {
+        let Some(workspace) = vim.workspace(window) else {
+            return;
+        };
+        // User-defined mappings fall through to the bindings they replaced for the keystrokes of
+        // a `:noremap` mapping.
+        if workspace.read(cx).is_dispatching_without_remap() {
+            cx.propagate();
+            return;
+        }
+        let keystrokes = action
+            .keystrokes
+            .split(' ')
+            .filter_map(|keystroke| Keystroke::parse(keystroke).log_err())
+            .collect();
+        let _ = workspace.update(cx, |workspace, cx| {
+            if action.remap {
+                workspace.send_keystrokes_impl(keystrokes, window, cx)
+            } else {
+                workspace.send_keystrokes_without_remap(keystrokes, window, cx)
+            }
+        });
+    });
+
+    Vim::action(editor, cx, |vim, action: &DefineMapping, window, cx| {
+        let result = VimMapping::parse(
+            action.modes,
+            action.remap,
+            &action.args,
+            VimUserConfig::leader(cx),
+        )
+        .and_then(|mapping| {
+            let key_binding = mapping.key_binding(cx)?;
+            cx.bind_keys([key_binding]);
+            cx.update_default_global::<VimUserConfig, _>(|config, _| {
+                config.command_mappings.push(mapping)
+            });
+            Ok(())
+        });
+        if result.is_err() {
+            let Some(workspace) = vim.workspace(window) else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                result.notify_err(workspace, cx);
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{state::Mode, test::VimTestContext};
+    use gpui::TestAppContext;
+
+    fn parse(args: &str) -> Result<(String, Option<String>)> {
+        VimMapping::parse(MapModes::Normal, false, args, ",")
+            .map(|mapping| (mapping.lhs, mapping.rhs))
+    }
+
+    #[test]
+    fn test_parse_mapping() {
+        assert_eq!(
+            parse("<silent> <leader>f :Files<CR>").unwrap(),
+            (
+                ", f".to_string(),
+                Some(": shift-f i l e s enter".to_string())
+            )
+        );
+        assert_eq!(
+            parse("<C-w>V <C-W><C-v>").unwrap(),
+            (
+                "ctrl-w shift-v".to_string(),
+                Some("ctrl-w ctrl-v".to_string())
+            )
+        );
+        assert_eq!(
+            parse("<Space>y \"+y").unwrap(),
+            ("space y".to_string(), Some("\" + y".to_string()))
+        );
+        assert_eq!(
+            parse("<lt>a <A-j><S-Tab><F5>").unwrap(),
+            ("< a".to_string(), Some("alt-j shift-tab f5".to_string()))
+        );
+        assert_eq!(parse("Q <Nop>").unwrap(), ("shift-q".to_string(), None));
+        assert!(parse("<buffer> x y").is_err());
+        assert!(parse("<expr> x y").is_err());
+        assert!(parse("x").is_err());
+    }
+
+    #[gpui::test]
+    async fn test_map_commands(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        for command in [
+            "nnoremap x 4l",
+            "nmap Q x",
+            "nnoremap W x",
+            "nnoremap s 2l",
+            "nmap E sQ",
+        ] {
+            cx.simulate_keystrokes(":");
+            cx.simulate_input(command);
+            cx.simulate_keystrokes("enter");
+        }
+
+        cx.set_state("ˇ123456789", Mode::Normal);
+        cx.simulate_keystrokes("x");
+        cx.assert_state("1234ˇ56789", Mode::Normal);
+
+        // `:nmap` mappings expand other mappings.
+        cx.set_state("ˇ123456789", Mode::Normal);
+        cx.simulate_keystrokes("shift-q");
+        cx.assert_state("1234ˇ56789", Mode::Normal);
+
+        // `:nnoremap` mappings use the default bindings.
+        cx.set_state("ˇ123456789", Mode::Normal);
+        cx.simulate_keystrokes("shift-w");
+        cx.assert_state("ˇ23456789", Mode::Normal);
+
+        // Only the keystrokes of a `:nnoremap` mapping skip other mappings.
+        cx.set_state("ˇ123456789", Mode::Normal);
+        cx.simulate_keystrokes("shift-e");
+        cx.assert_state("123456ˇ789", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_source_vimrc(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        let unsupported_lines = cx.update(|_, cx| {
+            let unsupported_lines = source_vimrc(
+                indoc::indoc! {r#"
+                    " Leader mappings
+                    let mapleader = ","
+                    nnoremap <leader>w 2l
+                    inoremap jk <Esc>
+                    set relativenumber
+                    syntax on
+                "#},
+                cx,
+            );
+            cx.bind_keys(user_key_bindings(cx));
+            unsupported_lines
+        });
+        assert_eq!(unsupported_lines, 1);
+
+        cx.set_state("ˇ123456789", Mode::Normal);
+        cx.simulate_keystrokes(", w");
+        cx.assert_state("12ˇ3456789", Mode::Normal);
+
+        cx.simulate_keystrokes("i j k");
+        cx.assert_state("1ˇ23456789", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_vimrc_options_apply_to_open_editors(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let is_relative = |cx: &mut VimTestContext| {
+            cx.update_editor(|editor, _, cx| editor.should_use_relative_line_numbers(cx))
+        };
+        assert!(!is_relative(&mut cx));
+
+        cx.update(|_, cx| source_vimrc("set relativenumber", cx));
+        assert!(is_relative(&mut cx));
+
+        // Defining a mapping leaves options changed with `:set` alone.
+        cx.simulate_keystrokes(":");
+        cx.simulate_input("set norelativenumber");
+        cx.simulate_keystrokes("enter");
+        cx.simulate_keystrokes(":");
+        cx.simulate_input("nnoremap Q 2l");
+        cx.simulate_keystrokes("enter");
+        assert!(!is_relative(&mut cx));
+
+        cx.update(|_, cx| source_vimrc("set relativenumber number", cx));
+        assert!(is_relative(&mut cx));
+    }
+}
@@ -94,6 +94,10 @@ actions!(
         Redo,
         /// Undoes all changes to the most recently changed line.
         UndoLastLine,
+        /// Moves to the previous state of the undo tree, in the order the states were created.
+        UndoEarlier,
+        /// Moves to the next state of the undo tree, in the order the states were created.
+        UndoLater,
     ]
 );
 
@@ -213,6 +217,34 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
             }
         });
     });
+    Vim::action(editor, cx, |vim, _: &UndoEarlier, window, cx| {
+        let count = Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        vim.update_editor(cx, |_, editor, cx| {
+            editor.undo_earlier(
+                &editor::actions::UndoEarlier {
+                    count,
+                    seconds: None,
+                },
+                window,
+                cx,
+            );
+        });
+    });
+    Vim::action(editor, cx, |vim, _: &UndoLater, window, cx| {
+        let count = Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        vim.update_editor(cx, |_, editor, cx| {
+            editor.undo_later(
+                &editor::actions::UndoLater {
+                    count,
+                    seconds: None,
+                },
+                window,
+                cx,
+            );
+        });
+    });
     Vim::action(editor, cx, |vim, _: &UndoLastLine, window, cx| {
         Vim::take_forced_motion(cx);
         vim.update_editor(cx, |vim, editor, cx| {
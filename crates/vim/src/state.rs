@@ -1,4 +1,6 @@
 use crate::command::command_interceptor;
+use crate::helix::HelixRegexSelection;
+use crate::mapping::MappedKeystrokes;
 use crate::motion::MotionKind;
 use crate::normal::repeat::Replayer;
 use crate::surrounds::SurroundsType;
@@ -883,6 +885,10 @@ impl VimGlobals {
     }
 
     pub fn observe_action(&mut self, action: Box<dyn Action>) {
+        // The actions that a mapping's keystrokes dispatch are recorded instead.
+        if action.as_any().is::<MappedKeystrokes>() {
+            return;
+        }
         if self.dot_recording {
             self.recording_actions
                 .push(ReplayableAction::Action(action.boxed_clone()));
@@ -979,6 +985,8 @@ pub struct SearchState {
     pub prior_selections: Vec<Range<Anchor>>,
     pub prior_operator: Option<Operator>,
     pub prior_mode: Mode,
+    /// Set while the search bar reads the regex of a Helix selection command.
+    pub helix_regex: Option<HelixRegexSelection>,
 }
 
 impl Operator {
@@ -1047,7 +1055,9 @@ impl Operator {
 
     pub fn is_waiting(&self, mode: Mode) -> bool {
         match self {
-            Operator::AddSurrounds { target } => target.is_some() || mode.is_visual(),
+            Operator::AddSurrounds { target } => {
+                target.is_some() || mode.is_visual() || mode == Mode::HelixNormal
+            }
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
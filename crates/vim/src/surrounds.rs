@@ -88,7 +88,12 @@ impl Vim {
                         let (start_cursor_str, end_cursor_str) = if mode == Mode::VisualLine {
                             (format!("{}\n", pair.start), format!("\n{}", pair.end))
                         } else {
-                            let maybe_space = if surround { " " } else { "" };
+                            // Helix never pads surrounds with spaces.
+                            let maybe_space = if surround && mode != Mode::HelixNormal {
+                                " "
+                            } else {
+                                ""
+                            };
                             (
                                 format!("{}{}", pair.start, maybe_space),
                                 format!("{}{}", maybe_space, pair.end),
@@ -98,7 +103,13 @@ impl Vim {
 
                         edits.push((start..start, start_cursor_str));
                         edits.push((end..end, end_cursor_str));
-                        anchors.push(start_anchor..start_anchor);
+                        if mode == Mode::HelixNormal {
+                            // Helix keeps the surrounded text selected, along with the surrounds.
+                            let end_anchor = display_map.buffer_snapshot.anchor_after(end);
+                            anchors.push(start_anchor..end_anchor);
+                        } else {
+                            anchors.push(start_anchor..start_anchor);
+                        }
                     } else {
                         let start_anchor = display_map
                             .buffer_snapshot
@@ -118,7 +129,9 @@ impl Vim {
                 });
             });
         });
-        self.switch_mode(Mode::Normal, false, window, cx);
+        if mode != Mode::HelixNormal {
+            self.switch_mode(Mode::Normal, false, window, cx);
+        }
     }
 
     pub fn delete_surrounds(
@@ -226,6 +239,7 @@ impl Vim {
     ) {
         if let Some(will_replace_pair) = object_to_bracket_pair(target) {
             self.stop_recording(cx);
+            let mode = self.mode;
             self.update_editor(cx, |_, editor, cx| {
                 editor.transact(window, cx, |editor, window, cx| {
                     editor.set_clip_at_line_ends(false, cx);
@@ -240,7 +254,8 @@ impl Vim {
                             newline: false,
                         },
                     };
-                    let surround = pair.end != surround_alias((*text).as_ref());
+                    let surround =
+                        pair.end != surround_alias((*text).as_ref()) && mode != Mode::HelixNormal;
                     let (display_map, selections) = editor.selections.all_adjusted_display(cx);
                     let mut edits = Vec::new();
                     let mut anchors = Vec::new();
@@ -9,6 +9,7 @@ mod digraph;
 mod helix;
 mod indent;
 mod insert;
+mod mapping;
 mod mode_indicator;
 mod motion;
 mod normal;
@@ -32,6 +33,7 @@ use gpui::{
 };
 use insert::{NormalBefore, TemporaryNormal};
 use language::{CharKind, CursorShape, Point, Selection, SelectionGoal, TransactionId};
+pub use mapping::{VimUserConfig, source_vimrc, user_key_bindings};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
 use normal::search::SearchSubmit;
@@ -488,6 +490,28 @@ impl Vim {
             vim.update(cx, |vim, _| {
                 vim.mode = Mode::Insert;
             });
+        } else {
+            let mut options = VimUserConfig::options(cx);
+            for option in &options {
+                option.apply(editor, cx);
+            }
+            // Re-apply the options when the vimrc is sourced after this editor was opened, but
+            // not when only the mappings change, so that `:set` commands aren't overridden.
+            vim.update(cx, |vim, cx| {
+                vim._subscriptions
+                    .push(cx.observe_global::<VimUserConfig>(move |vim, cx| {
+                        let new_options = VimUserConfig::options(cx);
+                        if new_options == options {
+                            return;
+                        }
+                        options = new_options;
+                        vim.update_editor(cx, |_, editor, cx| {
+                            for option in &options {
+                                option.apply(editor, cx);
+                            }
+                        });
+                    }));
+            });
         }
 
         editor.register_addon(VimAddon {
@@ -772,6 +796,7 @@ impl Vim {
             visual::register(editor, cx);
             change_list::register(editor, cx);
             digraph::register(editor, cx);
+            mapping::register(editor, cx);
 
             cx.defer_in(window, |vim, window, cx| {
                 vim.focused(false, window, cx);
@@ -1660,10 +1685,15 @@ impl Vim {
                     self.add_surrounds(text, SurroundsType::Selection, window, cx);
                     self.clear_operator(window, cx);
                 }
+                Mode::HelixNormal => {
+                    self.helix_expand_cursors(window, cx);
+                    self.add_surrounds(text, SurroundsType::Selection, window, cx);
+                    self.clear_operator(window, cx);
+                }
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::ChangeSurrounds { target }) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     if let Some(target) = target {
                         self.change_surrounds(text, target, window, cx);
                         self.clear_operator(window, cx);
@@ -1672,7 +1702,7 @@ impl Vim {
                 _ => self.clear_operator(window, cx),
             },
             Some(Operator::DeleteSurrounds) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     self.delete_surrounds(text, window, cx);
                     self.clear_operator(window, cx);
                 }
@@ -19,7 +19,7 @@ use gpui::{Axis, Bounds, Task, WindowBounds, WindowId, point, size};
 use project::debugger::breakpoint_store::{BreakpointState, SourceBreakpoint};
 
 use language::{LanguageName, Toolchain};
-use project::WorktreeId;
+use project::{WorktreeId, search_history::SavedSearch};
 use remote::{RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions};
 use sqlez::{
     bindable::{Bind, Column, StaticColumnCount},
@@ -711,6 +711,19 @@ impl Domain for WorkspaceDb {
 
             CREATE UNIQUE INDEX ix_workspaces_location ON workspaces(remote_connection_id, paths);
         ),
+        sql!(
+            CREATE TABLE saved_searches (
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                query TEXT NOT NULL,
+                files_to_include TEXT NOT NULL,
+                files_to_exclude TEXT NOT NULL,
+                search_options INTEGER NOT NULL,
+                PRIMARY KEY (workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        ),
     ];
 
     // Allow recovering from bad migration that was initially shipped to nightly
@@ -1558,6 +1571,64 @@ impl WorkspaceDb {
         }
     }
 
+    query! {
+        fn saved_searches_query(workspace_id: WorkspaceId) -> Result<Vec<(String, String, String, String, u16)>> {
+            SELECT name, query, files_to_include, files_to_exclude, search_options
+            FROM saved_searches
+            WHERE workspace_id = ?
+            ORDER BY name
+        }
+    }
+
+    /// Returns the searches saved in the given workspace, ordered by name.
+    pub fn saved_searches(&self, workspace_id: WorkspaceId) -> Result<Vec<SavedSearch>> {
+        Ok(self
+            .saved_searches_query(workspace_id)?
+            .into_iter()
+            .map(
+                |(name, query, files_to_include, files_to_exclude, search_options)| SavedSearch {
+                    name,
+                    query,
+                    files_to_include,
+                    files_to_exclude,
+                    search_options,
+                },
+            )
+            .collect())
+    }
+
+    query! {
+        async fn save_search_query(workspace_id: WorkspaceId, name: String, query: String, files_to_include: String, files_to_exclude: String, search_options: u16) -> Result<()> {
+            INSERT INTO saved_searches(workspace_id, name, query, files_to_include, files_to_exclude, search_options)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT DO UPDATE SET
+                query = ?3,
+                files_to_include = ?4,
+                files_to_exclude = ?5,
+                search_options = ?6
+        }
+    }
+
+    /// Saves `search` in the given workspace, replacing any saved search with the same name.
+    pub async fn save_search(&self, workspace_id: WorkspaceId, search: SavedSearch) -> Result<()> {
+        self.save_search_query(
+            workspace_id,
+            search.name,
+            search.query,
+            search.files_to_include,
+            search.files_to_exclude,
+            search.search_options,
+        )
+        .await
+    }
+
+    query! {
+        pub async fn delete_saved_search(workspace_id: WorkspaceId, name: String) -> Result<()> {
+            DELETE FROM saved_searches
+            WHERE workspace_id = ?1 AND name = ?2
+        }
+    }
+
     pub async fn toolchain(
         &self,
         workspace_id: WorkspaceId,
@@ -1963,6 +2034,52 @@ mod tests {
         assert!(empty_breakpoints.is_none());
     }
 
+    #[gpui::test]
+    async fn test_saved_searches() {
+        zlog::init_test();
+
+        let db = WorkspaceDb::open_test_db("test_saved_searches").await;
+        let id = db.next_id().await.unwrap();
+
+        let todos = SavedSearch {
+            name: "todos".into(),
+            query: "TODO|FIXME".into(),
+            files_to_include: "*.rs".into(),
+            files_to_exclude: String::new(),
+            search_options: 8,
+        };
+        let unwraps = SavedSearch {
+            name: "unwraps".into(),
+            query: "$X.unwrap()".into(),
+            files_to_include: String::new(),
+            files_to_exclude: "tests/".into(),
+            search_options: 128,
+        };
+        db.save_search(id, unwraps.clone()).await.unwrap();
+        db.save_search(id, todos.clone()).await.unwrap();
+        assert_eq!(
+            db.saved_searches(id).unwrap(),
+            vec![todos.clone(), unwraps.clone()]
+        );
+
+        // Saving under an existing name replaces the previous search.
+        let todos = SavedSearch {
+            query: "TODO".into(),
+            ..todos
+        };
+        db.save_search(id, todos.clone()).await.unwrap();
+        assert_eq!(
+            db.saved_searches(id).unwrap(),
+            vec![todos.clone(), unwraps.clone()]
+        );
+
+        db.delete_saved_search(id, "unwraps".into()).await.unwrap();
+        assert_eq!(db.saved_searches(id).unwrap(), vec![todos]);
+
+        let other_id = db.next_id().await.unwrap();
+        assert_eq!(db.saved_searches(other_id).unwrap(), Vec::new());
+    }
+
     #[gpui::test]
     async fn test_next_id_stability() {
         zlog::init_test();
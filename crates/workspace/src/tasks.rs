@@ -1,6 +1,6 @@
 use std::process::ExitStatus;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use gpui::{AppContext, Context, Entity, Task};
 use language::Buffer;
 use project::{TaskSourceKind, WorktreeId};
@@ -55,6 +55,31 @@ impl Workspace {
         cx: &mut Context<Workspace>,
     ) {
         let spawn_in_terminal = resolved_task.resolved.clone();
+        let task_graph = if resolved_task.has_dependencies() {
+            let task_graph = self
+                .project
+                .read(cx)
+                .task_store()
+                .read(cx)
+                .task_inventory()
+                .context("No task inventory to resolve task dependencies with")
+                .and_then(|inventory| {
+                    inventory
+                        .read(cx)
+                        .resolve_task_graph(task_source_kind.clone(), resolved_task.clone())
+                });
+            match task_graph {
+                Ok(task_graph) => Some(task_graph),
+                Err(e) => {
+                    log::error!("Failed to resolve task dependencies: {e:#}");
+                    self.show_error(&e, cx);
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
         if !omit_history {
             if let Some(debugger_provider) = self.debugger_provider.as_ref() {
                 debugger_provider.task_scheduled(cx);
@@ -71,7 +96,34 @@ impl Workspace {
             });
         }
 
-        if let Some(terminal_provider) = self.terminal_provider.as_ref() {
+        if let Some(task_graph) = task_graph {
+            let workspace = cx.entity().downgrade();
+            let window_handle = window.window_handle();
+            let graph_run = self.project.update(cx, |project, cx| {
+                project.run_task_graph(
+                    task_graph,
+                    move |spawn_in_terminal, cx| {
+                        window_handle
+                            .update(cx, |_, window, cx| {
+                                workspace.update(cx, |workspace, cx| {
+                                    workspace.spawn_in_terminal(spawn_in_terminal, window, cx)
+                                })
+                            })
+                            .ok()
+                            .and_then(|spawned| spawned.ok())
+                            .unwrap_or_else(|| Task::ready(None))
+                    },
+                    cx,
+                )
+            });
+            self.scheduled_tasks.push(cx.background_spawn(async move {
+                if graph_run.await {
+                    log::debug!("Task with dependencies succeeded");
+                } else {
+                    log::debug!("Task with dependencies failed");
+                }
+            }));
+        } else if let Some(terminal_provider) = self.terminal_provider.as_ref() {
             let task_status = terminal_provider.spawn(spawn_in_terminal, window, cx);
             let task = cx.background_spawn(async move {
                 match task_status.await {
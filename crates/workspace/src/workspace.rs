@@ -1090,7 +1090,10 @@ type PromptForOpenPath = Box<
 #[derive(Default)]
 struct DispatchingKeystrokes {
     dispatched: HashSet<Vec<Keystroke>>,
-    queue: VecDeque<Keystroke>,
+    /// The keystrokes to dispatch, along with whether user-defined mappings apply to them.
+    queue: VecDeque<(Keystroke, bool)>,
+    /// Whether the keystroke being dispatched is exempt from user-defined mappings.
+    dispatching_without_remap: bool,
     task: Option<Shared<Task<()>>>,
 }
 
@@ -2541,6 +2544,34 @@ impl Workspace {
         keystrokes: Vec<Keystroke>,
         window: &mut Window,
         cx: &mut Context<Self>,
+    ) -> Shared<Task<()>> {
+        self.queue_keystrokes(keystrokes, true, window, cx)
+    }
+
+    /// Sends keystrokes that user-defined mappings don't apply to, as with vim's `:noremap`.
+    /// Other keystrokes sent in the meantime are still remapped.
+    pub fn send_keystrokes_without_remap(
+        &mut self,
+        keystrokes: Vec<Keystroke>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Shared<Task<()>> {
+        self.queue_keystrokes(keystrokes, false, window, cx)
+    }
+
+    /// Whether the keystroke being dispatched was sent with [`Self::send_keystrokes_without_remap`].
+    pub fn is_dispatching_without_remap(&self) -> bool {
+        self.dispatching_keystrokes
+            .borrow()
+            .dispatching_without_remap
+    }
+
+    fn queue_keystrokes(
+        &mut self,
+        keystrokes: Vec<Keystroke>,
+        remap: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
     ) -> Shared<Task<()>> {
         let mut state = self.dispatching_keystrokes.borrow_mut();
         if !state.dispatched.insert(keystrokes.clone()) {
@@ -2548,7 +2579,9 @@ impl Workspace {
             return state.task.clone().unwrap();
         }
 
-        state.queue.extend(keystrokes);
+        state
+            .queue
+            .extend(keystrokes.into_iter().map(|keystroke| (keystroke, remap)));
 
         let keystrokes = self.dispatching_keystrokes.clone();
         if state.task.is_none() {
@@ -2558,15 +2591,17 @@ impl Workspace {
                         // limit to 100 keystrokes to avoid infinite recursion.
                         for _ in 0..100 {
                             let mut state = keystrokes.borrow_mut();
-                            let Some(keystroke) = state.queue.pop_front() else {
+                            let Some((keystroke, remap)) = state.queue.pop_front() else {
                                 state.dispatched.clear();
                                 state.task.take();
                                 return;
                             };
+                            state.dispatching_without_remap = !remap;
                             drop(state);
                             cx.update(|window, cx| {
                                 let focused = window.focused(cx);
                                 window.dispatch_keystroke(keystroke.clone(), cx);
+                                keystrokes.borrow_mut().dispatching_without_remap = false;
                                 if window.focused(cx) != focused {
                                     // dispatch_keystroke may cause the focus to change.
                                     // draw's side effect is to schedule the FocusChanged events in the current flush effect cycle
@@ -55,7 +55,7 @@ use zed::{
     OpenListener, OpenRequest, RawOpenRequest, app_menus, build_window_options,
     derive_paths_with_position, edit_prediction_registry, handle_cli_connection,
     handle_keymap_file_changes, handle_settings_changed, handle_settings_file_changes,
-    initialize_workspace, open_paths_with_positions,
+    handle_vimrc_file_changes, initialize_workspace, open_paths_with_positions,
 };
 
 use crate::zed::OpenRequestKind;
@@ -341,6 +341,11 @@ pub fn main() {
         fs.clone(),
         paths::keymap_file().clone(),
     );
+    let vimrc_file_rx = watch_config_file(
+        &app.background_executor(),
+        fs.clone(),
+        paths::vimrc_file().clone(),
+    );
 
     let (shell_env_loaded_tx, shell_env_loaded_rx) = oneshot::channel();
     if !stdout_is_a_pty() {
@@ -398,6 +403,7 @@ pub fn main() {
             handle_settings_changed,
         );
         handle_keymap_file_changes(user_keymap_file_rx, cx);
+        handle_vimrc_file_changes(vimrc_file_rx, cx);
         client::init_settings(cx);
         let user_agent = format!(
             "Zed/{} ({}; {})",
@@ -583,6 +589,7 @@ pub fn main() {
             cx,
         );
         assistant_tools::init(app_state.client.http_client(), cx);
+        semantic_index::init(app_state.client.http_client(), cx);
         repl::init(app_state.fs.clone(), cx);
         extension_host::init(
             extension_host_proxy,
@@ -609,6 +616,7 @@ pub fn main() {
         file_finder::init(cx);
         tab_switcher::init(cx);
         outline::init(cx);
+        undo_tree_view::init(cx);
         project_symbols::init(cx);
         project_panel::init(cx);
         outline_panel::init(cx);
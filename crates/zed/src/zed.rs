@@ -384,6 +384,7 @@ pub fn initialize_workspace(
         let search_button = cx.new(|_| search::search_status_button::SearchButton::new());
         let diagnostic_summary =
             cx.new(|cx| diagnostics::items::DiagnosticIndicator::new(workspace, cx));
+        let background_tasks = cx.new(|cx| tasks_ui::BackgroundTasksIndicator::new(workspace, cx));
         let activity_indicator = activity_indicator::ActivityIndicator::new(
             workspace,
             workspace.project().read(cx).languages().clone(),
@@ -412,6 +413,7 @@ pub fn initialize_workspace(
             status_bar.add_left_item(search_button, window, cx);
             status_bar.add_left_item(lsp_button, window, cx);
             status_bar.add_left_item(diagnostic_summary, window, cx);
+            status_bar.add_left_item(background_tasks, window, cx);
             status_bar.add_left_item(activity_indicator, window, cx);
             status_bar.add_right_item(edit_prediction_button, window, cx);
             status_bar.add_right_item(active_buffer_language, window, cx);
@@ -1295,6 +1297,7 @@ pub fn handle_keymap_file_changes(
 
     let (base_keymap_tx, mut base_keymap_rx) = mpsc::unbounded();
     let (keyboard_layout_tx, mut keyboard_layout_rx) = mpsc::unbounded();
+    let (vim_user_config_tx, mut vim_user_config_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
     let mut old_helix_enabled = vim_mode_setting::HelixModeSetting::get_global(cx).0;
@@ -1317,6 +1320,11 @@ pub fn handle_keymap_file_changes(
     })
     .detach();
 
+    cx.observe_global::<vim::VimUserConfig>(move |_| {
+        vim_user_config_tx.unbounded_send(()).ok();
+    })
+    .detach();
+
     let mut current_layout_id = cx.keyboard_layout().id().to_string();
     cx.on_keyboard_layout_change(move |cx| {
         let next_layout_id = cx.keyboard_layout().id();
@@ -1339,6 +1347,7 @@ pub fn handle_keymap_file_changes(
             select_biased! {
                 _ = base_keymap_rx.next() => {},
                 _ = keyboard_layout_rx.next() => {},
+                _ = vim_user_config_rx.next() => {},
                 content = user_keymap_file_rx.next() => {
                     if let Some(content) = content {
                         if let Ok(Some(migrated_content)) = migrate_keymap(&content) {
@@ -1386,6 +1395,40 @@ pub fn handle_keymap_file_changes(
     .detach();
 }
 
+pub fn handle_vimrc_file_changes(mut vimrc_file_rx: mpsc::UnboundedReceiver<String>, cx: &mut App) {
+    struct VimrcUnsupportedLinesNotification;
+    let notification_id = NotificationId::unique::<VimrcUnsupportedLinesNotification>();
+
+    cx.spawn(async move |cx| {
+        while let Some(content) = vimrc_file_rx.next().await {
+            cx.update(|cx| {
+                let unsupported_lines = vim::source_vimrc(&content, cx);
+                if unsupported_lines == 0 {
+                    dismiss_app_notification(&notification_id, cx);
+                    return;
+                }
+                let message: SharedString = format!(
+                    "{unsupported_lines} line{} of the vimrc file could not be applied. See the log for details.",
+                    if unsupported_lines == 1 { "" } else { "s" }
+                )
+                .into();
+                show_app_notification(notification_id.clone(), cx, move |cx| {
+                    cx.new(|cx| {
+                        MessageNotification::new(message.clone(), cx)
+                            .primary_message("Open Log")
+                            .primary_on_click(|window, cx| {
+                                window.dispatch_action(OpenLog.boxed_clone(), cx);
+                                cx.emit(DismissEvent);
+                            })
+                    })
+                });
+            })
+            .ok();
+        }
+    })
+    .detach();
+}
+
 fn show_keymap_file_json_error(
     notification_id: NotificationId,
     error: &anyhow::Error,
@@ -1477,6 +1520,7 @@ fn show_markdown_app_notification<F>(
 fn reload_keymaps(cx: &mut App, mut user_key_bindings: Vec<KeyBinding>) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
+    cx.bind_keys(vim::user_key_bindings(cx));
 
     for key_binding in &mut user_key_bindings {
         key_binding.set_meta(KeybindSource::User.meta());
@@ -442,6 +442,26 @@ pub struct Rerun {
     pub task_id: Option<String>,
 }
 
+/// Stops a running background task.
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema, Action)]
+#[action(namespace = task)]
+#[serde(deny_unknown_fields)]
+pub struct StopBackgroundTask {
+    /// If present, stop the background task with this ID, otherwise stop the last started one.
+    #[serde(skip)]
+    pub task_id: Option<String>,
+}
+
+/// Restarts a running background task.
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema, Action)]
+#[action(namespace = task)]
+#[serde(deny_unknown_fields)]
+pub struct RestartBackgroundTask {
+    /// If present, restart the background task with this ID, otherwise restart the last started one.
+    #[serde(skip)]
+    pub task_id: Option<String>,
+}
+
 pub mod outline {
     use std::sync::OnceLock;
 